@@ -0,0 +1,353 @@
+// core/blockchain-monitor/src/arc.rs
+// ARC broadcast: submit with a status callback, persist miner responses and apply callbacks in order
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{generate_webhook_secret, validate_txid};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::time::Duration;
+
+use crate::{update_transaction_confirmations, AppState, ServiceError};
+
+// ============================================================================
+// STATUS ORDERING
+// ============================================================================
+
+/// Position of an ARC `txStatus` in the broadcast lifecycle. Callbacks can arrive out of
+/// order, so a status only replaces one that ranks lower. Rejections are final but can
+/// still be overtaken by MINED (ARC reports the block that settled the conflict).
+pub fn status_rank(status: &str) -> u8 {
+    match status {
+        "QUEUED" => 1,
+        "RECEIVED" => 2,
+        "STORED" => 3,
+        "ANNOUNCED_TO_NETWORK" => 4,
+        "REQUESTED_BY_NETWORK" => 5,
+        "SENT_TO_NETWORK" => 6,
+        "ACCEPTED_BY_NETWORK" => 7,
+        "SEEN_IN_ORPHAN_MEMPOOL" => 8,
+        "SEEN_ON_NETWORK" => 9,
+        "DOUBLE_SPEND_ATTEMPTED" | "REJECTED" => 10,
+        "MINED" => 11,
+        _ => 0,
+    }
+}
+
+pub fn supersedes(current: &str, incoming: &str) -> bool {
+    status_rank(incoming) > status_rank(current)
+}
+
+pub fn is_rejection(status: &str) -> bool {
+    matches!(status, "REJECTED" | "DOUBLE_SPEND_ATTEMPTED")
+}
+
+/// Compare bearer tokens without leaking the matching prefix length through timing
+fn tokens_match(a: &str, b: &str) -> bool {
+    a.len() == b.len() && a.bytes().zip(b.bytes()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// ARC response body (`POST /v1/tx`) and callback payload share these fields
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ArcStatus {
+    pub txid: String,
+    pub tx_status: String,
+    pub block_hash: Option<String>,
+    pub block_height: Option<i32>,
+    pub extra_info: Option<String>,
+    pub competing_txs: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ArcBroadcast {
+    pub txid: String,
+    pub arc_url: String,
+    pub tx_status: String,
+    pub block_hash: Option<String>,
+    pub block_height: Option<i32>,
+    pub extra_info: Option<String>,
+    pub competing_txs: Option<Value>,
+    pub response_payload: Value,
+    pub response_signature: Option<String>,
+    pub response_public_key: Option<String>,
+    pub callback_count: i32,
+    pub submitted_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Signed miner responses wrap the payload in `{ payload, signature, publicKey }`
+/// (the mAPI envelope); plain ARC returns the payload itself.
+fn unwrap_envelope(body: Value) -> Result<(Value, Option<String>, Option<String>), ServiceError> {
+    let field = |body: &Value, name: &str| body.get(name).and_then(Value::as_str).map(str::to_string);
+    match body.get("payload") {
+        Some(payload) => {
+            let signature = field(&body, "signature");
+            let public_key = field(&body, "publicKey");
+            let payload = match payload {
+                Value::String(s) => serde_json::from_str(s)
+                    .map_err(|e| ServiceError::ApiError(format!("Invalid ARC payload: {}", e)))?,
+                other => other.clone(),
+            };
+            Ok((payload, signature, public_key))
+        }
+        None => Ok((body, None, None)),
+    }
+}
+
+// ============================================================================
+// SUBMISSION
+// ============================================================================
+
+/// Submit through ARC and record the response. Returns None when ARC is not configured
+/// or unreachable, so the caller can fall back to the chain providers.
+pub async fn submit(state: &AppState, tx_hex: &str) -> Result<Option<ArcStatus>, ServiceError> {
+    let Some(arc_url) = state.config.arc_url.as_deref() else {
+        return Ok(None);
+    };
+    let token = generate_webhook_secret();
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default();
+
+    let mut request = client
+        .post(format!("{}/v1/tx", arc_url.trim_end_matches('/')))
+        .json(&json!({ "rawTx": tx_hex }));
+    if let Some(key) = state.config.arc_api_key.as_deref() {
+        request = request.bearer_auth(key);
+    }
+    if let Some(callback) = state.config.arc_callback_url.as_deref() {
+        request = request
+            .header("X-CallbackUrl", callback)
+            .header("X-CallbackToken", &token);
+    }
+
+    let response = match request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("ARC at {} unreachable: {}", arc_url, e);
+            return Ok(None);
+        }
+    };
+    if response.status().is_server_error() {
+        tracing::warn!("ARC at {} returned {}", arc_url, response.status());
+        return Ok(None);
+    }
+
+    // 4xx bodies carry the rejection reason in the same shape
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| ServiceError::ApiError(format!("Invalid ARC response: {}", e)))?;
+    let (payload, signature, public_key) = unwrap_envelope(body)?;
+    let status: ArcStatus = match serde_json::from_value(payload.clone()) {
+        Ok(status) => status,
+        Err(_) => {
+            let detail = payload.get("detail").or_else(|| payload.get("title")).and_then(Value::as_str);
+            return Err(ServiceError::ApiError(format!(
+                "ARC rejected transaction: {}",
+                detail.unwrap_or("no reason given")
+            )));
+        }
+    };
+
+    sqlx::query(
+        r#"
+        INSERT INTO arc_broadcasts
+            (txid, arc_url, tx_status, block_hash, block_height, extra_info, competing_txs,
+             response_payload, response_signature, response_public_key, callback_token)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+        ON CONFLICT (txid) DO UPDATE SET
+            arc_url = $2, tx_status = $3, block_hash = $4, block_height = $5, extra_info = $6,
+            competing_txs = $7, response_payload = $8, response_signature = $9,
+            response_public_key = $10, callback_token = $11, updated_at = NOW()
+        "#
+    )
+    .bind(&status.txid)
+    .bind(arc_url)
+    .bind(&status.tx_status)
+    .bind(&status.block_hash)
+    .bind(status.block_height)
+    .bind(&status.extra_info)
+    .bind(status.competing_txs.as_ref().map(|txs| json!(txs)))
+    .bind(&payload)
+    .bind(&signature)
+    .bind(&public_key)
+    .bind(&token)
+    .execute(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!("ARC status for {}: {}", status.txid, status.tx_status);
+    Ok(Some(status))
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+/// `POST /arc/callback`, called by ARC with `Authorization: Bearer <X-CallbackToken>`
+pub async fn arc_callback(
+    data: web::Data<AppState>,
+    http_req: HttpRequest,
+    body: web::Json<Value>,
+) -> Result<HttpResponse, ServiceError> {
+    let body = body.into_inner();
+    let status: ArcStatus = serde_json::from_value(body.clone())
+        .map_err(|e| ServiceError::ValidationError(format!("Invalid ARC callback: {}", e)))?;
+    validate_txid(&status.txid).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    let presented = http_req
+        .headers()
+        .get("Authorization")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    let current: Option<(String, String)> = sqlx::query_as(
+        "SELECT tx_status, callback_token FROM arc_broadcasts WHERE txid = $1"
+    )
+    .bind(&status.txid)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    // Same answer for unknown txids and bad tokens
+    let Some((current_status, token)) = current.filter(|(_, token)| tokens_match(token, presented)) else {
+        return Ok(HttpResponse::Unauthorized().json(json!({ "error": "Unknown transaction or invalid callback token" })));
+    };
+
+    let applied = supersedes(&current_status, &status.tx_status);
+    sqlx::query(
+        r#"
+        UPDATE arc_broadcasts SET
+            tx_status = CASE WHEN $2 THEN $3 ELSE tx_status END,
+            block_hash = CASE WHEN $2 THEN COALESCE($4, block_hash) ELSE block_hash END,
+            block_height = CASE WHEN $2 THEN COALESCE($5, block_height) ELSE block_height END,
+            extra_info = CASE WHEN $2 THEN $6 ELSE extra_info END,
+            competing_txs = CASE WHEN $2 THEN COALESCE($7, competing_txs) ELSE competing_txs END,
+            response_payload = CASE WHEN $2 THEN $8 ELSE response_payload END,
+            callback_count = callback_count + 1,
+            updated_at = NOW()
+        WHERE txid = $1 AND callback_token = $9
+        "#
+    )
+    .bind(&status.txid)
+    .bind(applied)
+    .bind(&status.tx_status)
+    .bind(&status.block_hash)
+    .bind(status.block_height)
+    .bind(&status.extra_info)
+    .bind(status.competing_txs.as_ref().map(|txs| json!(txs)))
+    .bind(&body)
+    .bind(&token)
+    .execute(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    if applied {
+        tracing::info!("ARC callback for {}: {} → {}", status.txid, current_status, status.tx_status);
+        if is_rejection(&status.tx_status) {
+            tracing::warn!("ARC reports {} as {}: {}", status.txid, status.tx_status,
+                status.extra_info.as_deref().unwrap_or_default());
+        }
+        if status.tx_status == "MINED" {
+            // Pick up the block immediately rather than on the next poll
+            if let Err(e) = update_transaction_confirmations(&data, &status.txid).await {
+                tracing::debug!("Confirmation refresh for {} after MINED callback failed: {}", status.txid, e);
+            }
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(json!({ "txid": status.txid, "applied": applied })))
+}
+
+pub async fn get_broadcast_status(
+    data: web::Data<AppState>,
+    txid: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_txid(&txid).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    let broadcast = sqlx::query_as::<_, ArcBroadcast>(
+        r#"
+        SELECT txid, arc_url, tx_status, block_hash, block_height, extra_info, competing_txs,
+               response_payload, response_signature, response_public_key, callback_count,
+               submitted_at, updated_at
+        FROM arc_broadcasts
+        WHERE txid = $1
+        "#
+    )
+    .bind(txid.as_str())
+    .fetch_optional(&data.db)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::NotFoundError(format!("No ARC broadcast for {}", txid)))?;
+
+    Ok(HttpResponse::Ok().json(broadcast))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_only_moves_forward() {
+        assert!(supersedes("RECEIVED", "SEEN_ON_NETWORK"));
+        assert!(!supersedes("SEEN_ON_NETWORK", "STORED"));
+        assert!(supersedes("SEEN_ON_NETWORK", "MINED"));
+        assert!(!supersedes("MINED", "SEEN_ON_NETWORK"));
+        assert!(supersedes("SEEN_ON_NETWORK", "DOUBLE_SPEND_ATTEMPTED"));
+        assert!(!supersedes("REJECTED", "ACCEPTED_BY_NETWORK"));
+        assert!(!supersedes("MINED", "SOMETHING_NEW"));
+    }
+
+    #[test]
+    fn test_parses_callback() {
+        let status: ArcStatus = serde_json::from_value(json!({
+            "timestamp": "2025-11-25T12:00:00Z",
+            "txid": "ab",
+            "txStatus": "MINED",
+            "blockHash": "00ff",
+            "blockHeight": 870000
+        }))
+        .unwrap();
+        assert_eq!(status.tx_status, "MINED");
+        assert_eq!(status.block_height, Some(870000));
+        assert!(status.competing_txs.is_none());
+    }
+
+    #[test]
+    fn test_unwraps_signed_envelope() {
+        let signed = json!({
+            "payload": "{\"txid\":\"ab\",\"txStatus\":\"SEEN_ON_NETWORK\"}",
+            "signature": "3045",
+            "publicKey": "02ab"
+        });
+        let (payload, signature, public_key) = unwrap_envelope(signed).unwrap();
+        assert_eq!(payload["txStatus"], "SEEN_ON_NETWORK");
+        assert_eq!(signature.as_deref(), Some("3045"));
+        assert_eq!(public_key.as_deref(), Some("02ab"));
+
+        let plain = json!({ "txid": "ab", "txStatus": "STORED" });
+        let (payload, signature, _) = unwrap_envelope(plain).unwrap();
+        assert_eq!(payload["txid"], "ab");
+        assert!(signature.is_none());
+    }
+
+    #[test]
+    fn test_tokens_match() {
+        assert!(tokens_match("abc", "abc"));
+        assert!(!tokens_match("abc", "abd"));
+        assert!(!tokens_match("abc", "ab"));
+    }
+}
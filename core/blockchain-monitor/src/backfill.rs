@@ -0,0 +1,197 @@
+// core/blockchain-monitor/src/backfill.rs
+// Historical backfill: import the confirmed history of newly watched addresses
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::validate_address;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::{update_transaction_confirmations, AppState, ServiceError};
+
+/// Worker lease on an address, renewed after every page
+const BACKFILL_LEASE_SECS: i64 = 900;
+/// Wait before retrying an address whose page failed
+const BACKFILL_RETRY_SECS: i64 = 60;
+/// Pause between transaction lookups so a long history stays inside provider rate limits
+const TX_PACING: Duration = Duration::from_millis(400);
+
+#[derive(Debug, sqlx::FromRow)]
+struct Claimed {
+    address: String,
+    backfill_provider: Option<String>,
+    backfill_cursor: Option<String>,
+    backfill_tx_count: i32,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct BackfillStatus {
+    pub address: String,
+    pub backfill_status: String,
+    pub backfill_tx_count: i32,
+    pub backfill_error: Option<String>,
+    pub backfill_completed_at: Option<DateTime<Utc>>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+// ============================================================================
+// WORKER
+// ============================================================================
+
+pub async fn start_backfill_worker(state: web::Data<AppState>) {
+    let interval = Duration::from_secs(state.config.polling_interval_secs);
+
+    tokio::spawn(async move {
+        loop {
+            // Drain every address that is due before sleeping
+            loop {
+                match claim_next(&state).await {
+                    Ok(Some(claimed)) => backfill_address(&state, claimed).await,
+                    Ok(None) => break,
+                    Err(e) => {
+                        tracing::error!("Failed to claim address for backfill: {}", e);
+                        break;
+                    }
+                }
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+/// Pending addresses, and running ones whose worker lost its lease, resume from their cursor
+async fn claim_next(state: &AppState) -> Result<Option<Claimed>, sqlx::Error> {
+    sqlx::query_as::<_, Claimed>(
+        r#"
+        UPDATE watched_addresses
+        SET backfill_status = 'running', backfill_lease_until = NOW() + make_interval(secs => $1)
+        WHERE id = (
+            SELECT id FROM watched_addresses
+            WHERE backfill_status <> 'complete'
+              AND address IS NOT NULL
+              AND status = 'active'
+              AND (backfill_lease_until IS NULL OR backfill_lease_until <= NOW())
+            ORDER BY created_at
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING address, backfill_provider, backfill_cursor, backfill_tx_count
+        "#
+    )
+    .bind(BACKFILL_LEASE_SECS as f64)
+    .fetch_optional(&state.db)
+    .await
+}
+
+async fn backfill_address(state: &AppState, claimed: Claimed) {
+    tracing::info!("Backfilling history of {}", claimed.address);
+
+    match import_history(state, &claimed).await {
+        Ok(imported) => {
+            tracing::info!("Backfill of {} complete: {} transactions", claimed.address, imported);
+            let _ = sqlx::query(
+                r#"
+                UPDATE watched_addresses
+                SET backfill_status = 'complete', backfill_cursor = NULL, backfill_lease_until = NULL,
+                    backfill_error = NULL, backfill_completed_at = NOW()
+                WHERE address = $1
+                "#
+            )
+            .bind(&claimed.address)
+            .execute(&state.db)
+            .await;
+        }
+        Err(e) => {
+            tracing::warn!("Backfill of {} interrupted: {}", claimed.address, e);
+            let _ = sqlx::query(
+                r#"
+                UPDATE watched_addresses
+                SET backfill_status = 'pending', backfill_error = $2,
+                    backfill_lease_until = NOW() + make_interval(secs => $3)
+                WHERE address = $1
+                "#
+            )
+            .bind(&claimed.address)
+            .bind(e.to_string())
+            .bind(BACKFILL_RETRY_SECS as f64)
+            .execute(&state.db)
+            .await;
+        }
+    }
+}
+
+/// Walk the history page by page, saving the cursor after each so a restart resumes
+/// where it stopped. Returns the total number of transactions imported.
+async fn import_history(state: &AppState, claimed: &Claimed) -> Result<i32, ServiceError> {
+    let mut provider = claimed.backfill_provider.clone();
+    let mut cursor = claimed.backfill_cursor.clone();
+    let mut imported = claimed.backfill_tx_count;
+
+    loop {
+        let (source, page) = state.chain
+            .get_address_history(provider.as_deref(), &claimed.address, cursor.as_deref())
+            .await?;
+
+        for txid in &page.txids {
+            // Already recorded by live monitoring or an earlier attempt
+            if state.get_transaction(txid).await?.is_some() {
+                continue;
+            }
+            update_transaction_confirmations(state, txid).await?;
+            imported += 1;
+            tokio::time::sleep(TX_PACING).await;
+        }
+
+        let Some(next) = page.next_page else {
+            return Ok(imported);
+        };
+        provider = Some(source.to_string());
+        cursor = Some(next);
+
+        sqlx::query(
+            r#"
+            UPDATE watched_addresses
+            SET backfill_provider = $2, backfill_cursor = $3, backfill_tx_count = $4,
+                backfill_lease_until = NOW() + make_interval(secs => $5)
+            WHERE address = $1
+            "#
+        )
+        .bind(&claimed.address)
+        .bind(&provider)
+        .bind(&cursor)
+        .bind(imported)
+        .bind(BACKFILL_LEASE_SECS as f64)
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+    }
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+pub async fn get_backfill_status(
+    data: web::Data<AppState>,
+    address: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_address(&address).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    let status = sqlx::query_as::<_, BackfillStatus>(
+        r#"
+        SELECT address, backfill_status, backfill_tx_count, backfill_error, backfill_completed_at
+        FROM watched_addresses
+        WHERE address = $1
+        "#
+    )
+    .bind(address.as_str())
+    .fetch_optional(&data.db)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::NotFoundError(format!("Address {} is not watched", address)))?;
+
+    Ok(HttpResponse::Ok().json(status))
+}
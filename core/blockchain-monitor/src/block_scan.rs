@@ -0,0 +1,99 @@
+// core/blockchain-monitor/src/block_scan.rs
+// Block-level scanning: match each new block's transactions against watched scripts instead of polling addresses
+
+use bsv_bank_common::transaction::{address_script, parse_block, ParsedTx};
+use sqlx::Row;
+use std::collections::HashSet;
+
+use crate::{update_transaction_confirmations, AppState, ServiceError};
+
+/// Blocks processed per monitoring pass while catching up
+const MAX_BLOCKS_PER_PASS: i32 = 10;
+
+// ============================================================================
+// SCANNING
+// ============================================================================
+
+/// Scan blocks mined since the last pass. The first pass starts at the tip; history
+/// before it is covered by the address backfill.
+pub async fn scan_new_blocks(state: &AppState) -> Result<usize, ServiceError> {
+    let tip = state.chain.get_chain_info().await?.blocks;
+    let last: Option<i32> = sqlx::query_scalar("SELECT MAX(height) FROM scanned_blocks")
+        .fetch_one(&state.db)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    let start = last.map_or(tip, |h| h + 1);
+    let end = tip.min(start + MAX_BLOCKS_PER_PASS - 1);
+    if start > end {
+        return Ok(0);
+    }
+
+    let mut scripts: HashSet<Vec<u8>> = state.watched_addresses
+        .read()
+        .await
+        .iter()
+        .filter_map(|address| address_script(address))
+        .collect();
+    scripts.extend(state.watched_scripts.read().await.values().cloned());
+
+    let mut matched = 0;
+    for height in start..=end {
+        matched += scan_block(state, height, &scripts).await?;
+    }
+    Ok(matched)
+}
+
+async fn scan_block(state: &AppState, height: i32, scripts: &HashSet<Vec<u8>>) -> Result<usize, ServiceError> {
+    let hash = state.chain.get_block_hash(height).await?;
+    let raw = hex::decode(state.chain.get_raw_block(&hash).await?.trim())
+        .map_err(|e| ServiceError::ApiError(format!("Invalid block hex: {}", e)))?;
+    let txs = parse_block(&raw).map_err(ServiceError::ApiError)?;
+
+    // Spends of outputs of transactions we already track (withdrawals, channel closes)
+    let prev_txids: Vec<String> = txs.iter()
+        .flat_map(|tx| tx.prevouts.iter().map(|(txid, _)| txid.clone()))
+        .collect::<HashSet<_>>()
+        .into_iter()
+        .collect();
+    let known: HashSet<String> = sqlx::query("SELECT txid FROM blockchain_transactions WHERE txid = ANY($1)")
+        .bind(&prev_txids)
+        .fetch_all(&state.db)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .map(|row| row.get("txid"))
+        .collect();
+
+    let matches: Vec<&ParsedTx> = txs.iter()
+        .filter(|tx| {
+            tx.outputs.iter().any(|output| scripts.contains(&output.script))
+                || tx.prevouts.iter().any(|(txid, _)| known.contains(txid))
+        })
+        .collect();
+
+    for tx in &matches {
+        if let Err(e) = update_transaction_confirmations(state, &tx.txid).await {
+            tracing::error!("Failed to record {} from block {}: {}", tx.txid, height, e);
+        }
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO scanned_blocks (height, block_hash, tx_count, matched_txs)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (height) DO UPDATE SET
+            block_hash = $2, tx_count = $3, matched_txs = $4, scanned_at = NOW()
+        "#
+    )
+    .bind(height)
+    .bind(&hash)
+    .bind(txs.len() as i32)
+    .bind(matches.len() as i32)
+    .execute(&state.db)
+    .await
+    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    tracing::info!("Scanned block {} ({}): {} of {} transactions matched", height, hash, matches.len(), txs.len());
+    Ok(matches.len())
+}
@@ -0,0 +1,124 @@
+// core/blockchain-monitor/src/budget.rs
+// Outbound request budget: a token bucket per chain data provider, exported as metrics
+
+use prometheus::{GaugeVec, IntCounterVec, Opts, Registry};
+use std::sync::Mutex;
+use std::time::Instant;
+
+/// Share of a provider's bucket background polling leaves for API requests
+pub const BACKGROUND_RESERVE: f64 = 0.25;
+
+struct Bucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+/// Holds up to `capacity` requests and refills continuously at `refill_per_sec`
+pub struct TokenBucket {
+    capacity: f64,
+    refill_per_sec: f64,
+    bucket: Mutex<Bucket>,
+}
+
+impl TokenBucket {
+    /// Starts full
+    pub fn new(capacity: u32, refill_per_sec: f64, now: Instant) -> Self {
+        let capacity = capacity.max(1) as f64;
+        Self {
+            capacity,
+            refill_per_sec: refill_per_sec.max(0.0),
+            bucket: Mutex::new(Bucket { tokens: capacity, refilled: now }),
+        }
+    }
+
+    fn refill(&self, bucket: &mut Bucket, now: Instant) {
+        let elapsed = now.saturating_duration_since(bucket.refilled).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        bucket.refilled = now;
+    }
+
+    /// Spend one request if the bucket holds one
+    pub fn try_take(&self, now: Instant) -> bool {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn remaining(&self, now: Instant) -> f64 {
+        let mut bucket = self.bucket.lock().unwrap();
+        self.refill(&mut bucket, now);
+        bucket.tokens
+    }
+
+    /// Whether background work may spend from the bucket without eating into the reserve
+    pub fn above_reserve(&self, now: Instant) -> bool {
+        self.remaining(now) - 1.0 >= self.capacity * BACKGROUND_RESERVE
+    }
+}
+
+pub struct BudgetMetrics {
+    pub remaining: GaugeVec,
+    pub exhausted: IntCounterVec,
+}
+
+impl BudgetMetrics {
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let remaining = GaugeVec::new(
+            Opts::new("blockchain_monitor_provider_budget_remaining", "Requests left in each provider's token bucket"),
+            &["provider"],
+        )?;
+        let exhausted = IntCounterVec::new(
+            Opts::new(
+                "blockchain_monitor_provider_budget_exhausted_total",
+                "Requests not sent to a provider because its budget was spent",
+            ),
+            &["provider"],
+        )?;
+        registry.register(Box::new(remaining.clone()))?;
+        registry.register(Box::new(exhausted.clone()))?;
+        Ok(Self { remaining, exhausted })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_bucket_drains_and_refills() {
+        let start = Instant::now();
+        let bucket = TokenBucket::new(3, 3.0, start);
+        assert!(bucket.try_take(start));
+        assert!(bucket.try_take(start));
+        assert!(bucket.try_take(start));
+        assert!(!bucket.try_take(start));
+
+        // A third of a second buys one request back
+        let later = start + Duration::from_millis(334);
+        assert!(bucket.try_take(later));
+        assert!(!bucket.try_take(later));
+
+        // Never refills past capacity
+        assert_eq!(bucket.remaining(start + Duration::from_secs(60)), 3.0);
+    }
+
+    #[test]
+    fn test_background_reserve() {
+        let start = Instant::now();
+        let bucket = TokenBucket::new(4, 0.0, start);
+        assert!(bucket.above_reserve(start));
+        bucket.try_take(start);
+        bucket.try_take(start);
+        assert!(bucket.above_reserve(start));
+        bucket.try_take(start);
+        // One left: spending it would leave nothing for API requests
+        assert!(!bucket.above_reserve(start));
+        assert!(bucket.try_take(start));
+    }
+}
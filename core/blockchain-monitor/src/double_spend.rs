@@ -0,0 +1,326 @@
+// core/blockchain-monitor/src/double_spend.rs
+// Double-spend detection: index the outpoints monitored transactions spend and alert on conflicts
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::validate_txid;
+use chrono::{DateTime, Utc};
+use prometheus::{IntCounterVec, Opts, Registry};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::events::MonitorEvent;
+use crate::{outbox, webhooks, AppState, ServiceError, WocTransaction};
+
+/// Unconfirmed transactions whose inputs are re-checked per monitoring pass
+const CHECK_BATCH: i64 = 100;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Outpoint {
+    pub txid: String,
+    pub vout: u32,
+}
+
+impl std::fmt::Display for Outpoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.txid, self.vout)
+    }
+}
+
+/// Where the conflicting spend was found
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictStatus {
+    Mempool,
+    Block,
+}
+
+impl ConflictStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictStatus::Mempool => "mempool",
+            ConflictStatus::Block => "block",
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DoubleSpendAlert {
+    pub id: Uuid,
+    pub prev_txid: String,
+    pub prev_vout: i32,
+    pub original_txid: String,
+    pub conflicting_txid: String,
+    pub conflict_status: String,
+    pub detected_at: DateTime<Utc>,
+}
+
+pub fn metric(registry: &Registry) -> Result<IntCounterVec, prometheus::Error> {
+    let counter = IntCounterVec::new(
+        Opts::new("blockchain_monitor_double_spends_total", "Double-spend conflicts detected"),
+        &["conflict"],
+    )?;
+    registry.register(Box::new(counter.clone()))?;
+    Ok(counter)
+}
+
+/// Outpoints spent by the transaction; coinbase inputs have none
+pub fn spent_outpoints(woc_tx: &WocTransaction) -> Vec<Outpoint> {
+    woc_tx
+        .inputs
+        .iter()
+        .flatten()
+        .filter_map(|input| match (&input.txid, input.vout) {
+            (Some(txid), Some(vout)) if !txid.is_empty() => Some(Outpoint { txid: txid.clone(), vout }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+// ============================================================================
+// DETECTION
+// ============================================================================
+
+/// Index the transaction's inputs and alert on any outpoint another recorded transaction spends
+pub async fn record_inputs(
+    state: &AppState,
+    txid: &str,
+    confirmations: i32,
+    woc_tx: &WocTransaction,
+    addresses: &[String],
+) -> Result<(), ServiceError> {
+    for outpoint in spent_outpoints(woc_tx) {
+        sqlx::query(
+            r#"
+            INSERT INTO tx_spent_outpoints (prev_txid, prev_vout, spending_txid)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+            "#
+        )
+        .bind(&outpoint.txid)
+        .bind(outpoint.vout as i32)
+        .bind(txid)
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+
+        let rivals = sqlx::query(
+            r#"
+            SELECT s.spending_txid, COALESCE(t.confirmations, 0) AS confirmations
+            FROM tx_spent_outpoints s
+            LEFT JOIN blockchain_transactions t ON t.txid = s.spending_txid
+            WHERE s.prev_txid = $1 AND s.prev_vout = $2 AND s.spending_txid <> $3
+            "#
+        )
+        .bind(&outpoint.txid)
+        .bind(outpoint.vout as i32)
+        .bind(txid)
+        .fetch_all(&state.db)
+        .await
+        .map_err(db_error)?;
+
+        for rival in rivals {
+            let rival_txid: String = rival.get("spending_txid");
+            let rival_confirmations: i32 = rival.get("confirmations");
+            // Alert on both sides: each was someone's expected payment
+            let status = if confirmations > 0 { ConflictStatus::Block } else { ConflictStatus::Mempool };
+            raise_alert(state, &outpoint, &rival_txid, txid, status, &[]).await?;
+            let status = if rival_confirmations > 0 { ConflictStatus::Block } else { ConflictStatus::Mempool };
+            raise_alert(state, &outpoint, txid, &rival_txid, status, addresses).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Ask the chain providers who spent each input of our unconfirmed transactions. This catches conflicts
+/// paying addresses we do not watch, which the local index never sees.
+pub async fn check_unconfirmed(state: &AppState) -> Result<(), ServiceError> {
+    let rows = sqlx::query(
+        r#"
+        SELECT s.prev_txid, s.prev_vout, s.spending_txid
+        FROM tx_spent_outpoints s
+        JOIN blockchain_transactions t ON t.txid = s.spending_txid
+        WHERE t.confirmations = 0
+        ORDER BY t.first_seen DESC
+        LIMIT $1
+        "#
+    )
+    .bind(CHECK_BATCH)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    for row in rows {
+        let outpoint = Outpoint {
+            txid: row.get("prev_txid"),
+            vout: row.get::<i32, _>("prev_vout") as u32,
+        };
+        let original: String = row.get("spending_txid");
+
+        let spent = match state.chain.get_spender(&outpoint.txid, outpoint.vout).await {
+            Ok(Some(spent)) => spent,
+            Ok(None) => continue,
+            Err(e) => {
+                tracing::debug!("Spent lookup for {} failed: {}", outpoint, e);
+                continue;
+            }
+        };
+        if spent.txid != original {
+            let status = if spent.status.as_deref() == Some("confirmed") {
+                ConflictStatus::Block
+            } else {
+                ConflictStatus::Mempool
+            };
+            raise_alert(state, &outpoint, &original, &spent.txid, status, &[]).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Record the conflict once, with its outbox event, then publish it as an event, webhook deliveries and a metric
+async fn raise_alert(
+    state: &AppState,
+    outpoint: &Outpoint,
+    original_txid: &str,
+    conflicting_txid: &str,
+    status: ConflictStatus,
+    addresses: &[String],
+) -> Result<(), ServiceError> {
+    let mut db_tx = state.db.begin().await.map_err(db_error)?;
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO double_spend_alerts (prev_txid, prev_vout, original_txid, conflicting_txid, conflict_status)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT DO NOTHING
+        "#
+    )
+    .bind(&outpoint.txid)
+    .bind(outpoint.vout as i32)
+    .bind(original_txid)
+    .bind(conflicting_txid)
+    .bind(status.as_str())
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .rows_affected() > 0;
+    if !inserted {
+        return Ok(());
+    }
+
+    // Address subscribers of the original transaction are told as well
+    let mut addresses = addresses.to_vec();
+    if addresses.is_empty() {
+        addresses = recorded_addresses(state, original_txid).await?;
+    }
+
+    let event = MonitorEvent::DoubleSpend {
+        txid: original_txid.to_string(),
+        conflicting_txid: conflicting_txid.to_string(),
+        outpoint: outpoint.to_string(),
+        conflict: status.as_str(),
+        addresses: addresses.clone(),
+        timestamp: Utc::now(),
+    };
+    outbox::record(state, &mut *db_tx, &event).await?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::warn!("Double spend of {}: {} conflicts with {} ({})",
+        outpoint, conflicting_txid, original_txid, status.as_str());
+    state.double_spends.with_label_values(&[status.as_str()]).inc();
+    state.events.publish(event);
+
+    webhooks::enqueue_event(state, webhooks::EVENT_TX_DOUBLE_SPEND, original_txid, &addresses, serde_json::json!({
+        "txid": original_txid,
+        "conflicting_txid": conflicting_txid,
+        "outpoint": outpoint.to_string(),
+        "conflict": status.as_str(),
+    }))
+    .await
+}
+
+async fn recorded_addresses(state: &AppState, txid: &str) -> Result<Vec<String>, ServiceError> {
+    let row = sqlx::query("SELECT from_address, to_address FROM blockchain_transactions WHERE txid = $1")
+        .bind(txid)
+        .fetch_optional(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    Ok(row
+        .map(|row| {
+            [row.get::<Option<String>, _>("from_address"), row.get::<Option<String>, _>("to_address")]
+                .into_iter()
+                .flatten()
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct AlertsQuery {
+    pub txid: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub async fn list_alerts(
+    data: web::Data<AppState>,
+    query: web::Query<AlertsQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    if let Some(txid) = &query.txid {
+        validate_txid(txid).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+
+    let alerts = sqlx::query_as::<_, DoubleSpendAlert>(
+        r#"
+        SELECT * FROM double_spend_alerts
+        WHERE $1::text IS NULL OR original_txid = $1 OR conflicting_txid = $1
+        ORDER BY detected_at DESC
+        LIMIT $2
+        "#
+    )
+    .bind(&query.txid)
+    .bind(query.limit.unwrap_or(50).clamp(1, 500))
+    .fetch_all(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "total_alerts": alerts.len(),
+        "alerts": alerts
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_spent_outpoints_skips_coinbase() {
+        let woc_tx: WocTransaction = serde_json::from_value(serde_json::json!({
+            "txid": "ab",
+            "vin": [
+                { "txid": "11", "vout": 1, "scriptSig": {} },
+                { "coinbase": "03abcd", "sequence": 4294967295u32 },
+                { "txid": "22", "vout": 0 }
+            ]
+        }))
+        .unwrap();
+
+        let outpoints = spent_outpoints(&woc_tx);
+        assert_eq!(outpoints.len(), 2);
+        assert_eq!(outpoints[0].to_string(), "11:1");
+        assert_eq!(outpoints[1], Outpoint { txid: "22".to_string(), vout: 0 });
+    }
+}
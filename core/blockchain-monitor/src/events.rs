@@ -0,0 +1,447 @@
+// core/blockchain-monitor/src/events.rs
+// Real-time event stream: the monitoring loop publishes, WebSocket clients filter by address/txid
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use actix_ws::Message;
+use chrono::{DateTime, Utc};
+use futures_util::StreamExt;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::time::Duration;
+use tokio::sync::broadcast;
+
+use crate::AppState;
+
+/// Events buffered per client before it is told it lagged
+const EVENT_BUFFER: usize = 1024;
+/// Most addresses plus txids a single connection may follow
+const MAX_SUBSCRIPTIONS: usize = 1000;
+/// Ping interval; a client silent for two intervals is dropped
+const HEARTBEAT: Duration = Duration::from_secs(30);
+
+// ============================================================================
+// EVENTS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MonitorEvent {
+    /// First time the monitor has seen the transaction
+    TxSeen {
+        txid: String,
+        addresses: Vec<String>,
+        confirmations: i32,
+        amount_satoshis: i64,
+        timestamp: DateTime<Utc>,
+    },
+    /// Confirmation count went up
+    ConfirmationChanged {
+        txid: String,
+        addresses: Vec<String>,
+        old_confirmations: i32,
+        new_confirmations: i32,
+        block_height: Option<i32>,
+        timestamp: DateTime<Utc>,
+    },
+    /// The transaction lost confirmations or moved to a different block
+    Reorg {
+        txid: String,
+        addresses: Vec<String>,
+        old_confirmations: i32,
+        new_confirmations: i32,
+        old_block_hash: Option<String>,
+        new_block_hash: Option<String>,
+        timestamp: DateTime<Utc>,
+    },
+    /// The transaction reached the confirmation target of its watched addresses' purpose; sent once
+    Finalized {
+        txid: String,
+        addresses: Vec<String>,
+        confirmations: i32,
+        target_confirmations: i32,
+        timestamp: DateTime<Utc>,
+    },
+    /// An input of the transaction was spent by `conflicting_txid`
+    DoubleSpend {
+        txid: String,
+        conflicting_txid: String,
+        outpoint: String,
+        /// "mempool" or "block"
+        conflict: &'static str,
+        addresses: Vec<String>,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl MonitorEvent {
+    /// Classify a transaction update; None when nothing observable changed
+    pub fn from_update(
+        txid: &str,
+        addresses: Vec<String>,
+        old: Option<(i32, Option<&str>)>,
+        new_confirmations: i32,
+        new_block_hash: Option<&str>,
+        block_height: Option<i32>,
+        amount_satoshis: i64,
+    ) -> Option<Self> {
+        let timestamp = Utc::now();
+        let Some((old_confirmations, old_block_hash)) = old else {
+            return Some(MonitorEvent::TxSeen {
+                txid: txid.to_string(),
+                addresses,
+                confirmations: new_confirmations,
+                amount_satoshis,
+                timestamp,
+            });
+        };
+
+        let moved_block = matches!((old_block_hash, new_block_hash), (Some(a), Some(b)) if a != b);
+        if new_confirmations < old_confirmations || moved_block {
+            Some(MonitorEvent::Reorg {
+                txid: txid.to_string(),
+                addresses,
+                old_confirmations,
+                new_confirmations,
+                old_block_hash: old_block_hash.map(str::to_string),
+                new_block_hash: new_block_hash.map(str::to_string),
+                timestamp,
+            })
+        } else if new_confirmations > old_confirmations {
+            Some(MonitorEvent::ConfirmationChanged {
+                txid: txid.to_string(),
+                addresses,
+                old_confirmations,
+                new_confirmations,
+                block_height,
+                timestamp,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// The serialized `type` tag
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            MonitorEvent::TxSeen { .. } => "tx_seen",
+            MonitorEvent::ConfirmationChanged { .. } => "confirmation_changed",
+            MonitorEvent::Reorg { .. } => "reorg",
+            MonitorEvent::Finalized { .. } => "finalized",
+            MonitorEvent::DoubleSpend { .. } => "double_spend",
+        }
+    }
+
+    pub fn txid(&self) -> &str {
+        match self {
+            MonitorEvent::TxSeen { txid, .. }
+            | MonitorEvent::ConfirmationChanged { txid, .. }
+            | MonitorEvent::Reorg { txid, .. }
+            | MonitorEvent::Finalized { txid, .. }
+            | MonitorEvent::DoubleSpend { txid, .. } => txid,
+        }
+    }
+
+    fn addresses(&self) -> &[String] {
+        match self {
+            MonitorEvent::TxSeen { addresses, .. }
+            | MonitorEvent::ConfirmationChanged { addresses, .. }
+            | MonitorEvent::Reorg { addresses, .. }
+            | MonitorEvent::Finalized { addresses, .. }
+            | MonitorEvent::DoubleSpend { addresses, .. } => addresses,
+        }
+    }
+}
+
+/// Fan-out of monitor events to every connected stream
+#[derive(Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<MonitorEvent>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self { sender: broadcast::channel(EVENT_BUFFER).0 }
+    }
+
+    pub fn publish(&self, event: MonitorEvent) {
+        // No connected clients is the common case; ignore the send error
+        let _ = self.sender.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<MonitorEvent> {
+        self.sender.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// ============================================================================
+// SUBSCRIPTIONS
+// ============================================================================
+
+/// Client → server control messages
+#[derive(Debug, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        #[serde(default)]
+        addresses: Vec<String>,
+        #[serde(default)]
+        txids: Vec<String>,
+    },
+    Unsubscribe {
+        #[serde(default)]
+        addresses: Vec<String>,
+        #[serde(default)]
+        txids: Vec<String>,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct Subscription {
+    addresses: HashSet<String>,
+    txids: HashSet<String>,
+}
+
+impl Subscription {
+    pub fn matches(&self, event: &MonitorEvent) -> bool {
+        self.txids.contains(event.txid()) || event.addresses().iter().any(|a| self.addresses.contains(a))
+    }
+
+    fn len(&self) -> usize {
+        self.addresses.len() + self.txids.len()
+    }
+
+    /// Apply a control message, rejecting malformed ids and oversized subscriptions
+    pub fn apply(&mut self, message: ClientMessage) -> Result<(), String> {
+        match message {
+            ClientMessage::Subscribe { addresses, txids } => {
+                for address in &addresses {
+                    bsv_bank_common::validate_address(address).map_err(|e| e.to_string())?;
+                }
+                for txid in &txids {
+                    bsv_bank_common::validate_txid(txid).map_err(|e| e.to_string())?;
+                }
+                let new = addresses.iter().filter(|a| !self.addresses.contains(*a)).count()
+                    + txids.iter().filter(|t| !self.txids.contains(*t)).count();
+                if self.len() + new > MAX_SUBSCRIPTIONS {
+                    return Err(format!("At most {} addresses and txids per connection", MAX_SUBSCRIPTIONS));
+                }
+                self.addresses.extend(addresses);
+                self.txids.extend(txids);
+            }
+            ClientMessage::Unsubscribe { addresses, txids } => {
+                for address in &addresses {
+                    self.addresses.remove(address);
+                }
+                for txid in &txids {
+                    self.txids.remove(txid);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// WEBSOCKET ENDPOINT
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct StreamQuery {
+    /// Comma-separated addresses to follow from the start
+    pub addresses: Option<String>,
+    /// Comma-separated txids to follow from the start
+    pub txids: Option<String>,
+}
+
+fn split_list(value: &Option<String>) -> Vec<String> {
+    value
+        .iter()
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Acknowledge a subscription change, flagging addresses the poller does not watch:
+/// their transactions only appear once someone registers them via /watch/address.
+async fn ack(state: &AppState, subscription: &Subscription) -> String {
+    let watched = state.watched_addresses.read().await;
+    let mut unwatched: Vec<&String> = subscription.addresses.iter().filter(|a| !watched.contains(*a)).collect();
+    unwatched.sort();
+    serde_json::json!({
+        "type": "subscribed",
+        "addresses": subscription.addresses.len(),
+        "txids": subscription.txids.len(),
+        "unwatched_addresses": unwatched,
+    })
+    .to_string()
+}
+
+fn error_message(message: &str) -> String {
+    serde_json::json!({ "type": "error", "message": message }).to_string()
+}
+
+pub async fn event_stream(
+    state: web::Data<AppState>,
+    req: HttpRequest,
+    body: web::Payload,
+    query: web::Query<StreamQuery>,
+) -> Result<HttpResponse, actix_web::Error> {
+    let mut subscription = Subscription::default();
+    subscription
+        .apply(ClientMessage::Subscribe {
+            addresses: split_list(&query.addresses),
+            txids: split_list(&query.txids),
+        })
+        .map_err(actix_web::error::ErrorBadRequest)?;
+
+    let (response, mut session, mut messages) = actix_ws::handle(&req, body)?;
+    // Subscribe before acknowledging so nothing published in between is missed
+    let mut events = state.events.subscribe();
+
+    actix_web::rt::spawn(async move {
+        let mut heartbeat = tokio::time::interval(HEARTBEAT);
+        let mut awaiting_pong = false;
+
+        if session.text(ack(&state, &subscription).await).await.is_err() {
+            return;
+        }
+
+        let reason = loop {
+            tokio::select! {
+                event = events.recv() => {
+                    let text = match event {
+                        Ok(event) if subscription.matches(&event) => {
+                            serde_json::to_string(&event).unwrap_or_default()
+                        }
+                        Ok(_) => continue,
+                        Err(broadcast::error::RecvError::Lagged(missed)) => {
+                            serde_json::json!({ "type": "lagged", "missed": missed }).to_string()
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break None,
+                    };
+                    if session.text(text).await.is_err() {
+                        return;
+                    }
+                }
+                message = messages.next() => {
+                    let reply = match message {
+                        Some(Ok(Message::Text(text))) => {
+                            match serde_json::from_str::<ClientMessage>(&text)
+                                .map_err(|e| format!("Invalid message: {}", e))
+                                .and_then(|m| subscription.apply(m))
+                            {
+                                Ok(()) => ack(&state, &subscription).await,
+                                Err(e) => error_message(&e),
+                            }
+                        }
+                        Some(Ok(Message::Ping(bytes))) => {
+                            if session.pong(&bytes).await.is_err() {
+                                return;
+                            }
+                            continue;
+                        }
+                        Some(Ok(Message::Pong(_))) => {
+                            awaiting_pong = false;
+                            continue;
+                        }
+                        Some(Ok(Message::Close(reason))) => break reason,
+                        Some(Ok(_)) => continue,
+                        Some(Err(_)) | None => break None,
+                    };
+                    if session.text(reply).await.is_err() {
+                        return;
+                    }
+                }
+                _ = heartbeat.tick() => {
+                    if awaiting_pong {
+                        tracing::debug!("Dropping unresponsive event stream client");
+                        break None;
+                    }
+                    awaiting_pong = true;
+                    if session.ping(b"").await.is_err() {
+                        return;
+                    }
+                }
+            }
+        };
+
+        let _ = session.close(reason).await;
+    });
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TXID: &str = "aa00000000000000000000000000000000000000000000000000000000000000";
+
+    fn update(old: Option<(i32, Option<&str>)>, confirmations: i32, hash: Option<&str>) -> Option<MonitorEvent> {
+        MonitorEvent::from_update(TXID, vec!["mAddr".to_string()], old, confirmations, hash, Some(100), 5_000)
+    }
+
+    #[test]
+    fn test_classifies_updates() {
+        assert!(matches!(update(None, 0, None), Some(MonitorEvent::TxSeen { .. })));
+        assert!(matches!(update(Some((0, None)), 1, Some("b1")), Some(MonitorEvent::ConfirmationChanged { .. })));
+        assert!(matches!(update(Some((3, Some("b1"))), 1, Some("b1")), Some(MonitorEvent::Reorg { .. })));
+        assert!(matches!(update(Some((3, Some("b1"))), 4, Some("b2")), Some(MonitorEvent::Reorg { .. })));
+        assert!(matches!(update(Some((3, Some("b1"))), 0, None), Some(MonitorEvent::Reorg { .. })));
+        assert!(update(Some((2, Some("b1"))), 2, Some("b1")).is_none());
+    }
+
+    #[test]
+    fn test_event_serialization_is_tagged() {
+        let json = serde_json::to_value(update(None, 0, None).unwrap()).unwrap();
+        assert_eq!(json["type"], "tx_seen");
+        assert_eq!(json["txid"], TXID);
+
+        // The outbox records the tag separately; it must not drift from serde's
+        for event in [update(None, 0, None), update(Some((0, None)), 1, Some("b1")), update(Some((3, Some("b1"))), 1, Some("b1"))] {
+            let event = event.unwrap();
+            assert_eq!(serde_json::to_value(&event).unwrap()["type"], event.event_type());
+        }
+    }
+
+    #[test]
+    fn test_subscription_matching() {
+        let mut subscription = Subscription::default();
+        let event = update(None, 0, None).unwrap();
+        assert!(!subscription.matches(&event));
+
+        subscription.addresses.insert("mAddr".to_string());
+        assert!(subscription.matches(&event));
+
+        subscription.apply(ClientMessage::Unsubscribe { addresses: vec!["mAddr".to_string()], txids: vec![] }).unwrap();
+        assert!(!subscription.matches(&event));
+
+        subscription.txids.insert(TXID.to_string());
+        assert!(subscription.matches(&event));
+    }
+
+    #[test]
+    fn test_parses_control_messages() {
+        let message: ClientMessage =
+            serde_json::from_str(r#"{"action":"subscribe","txids":["ab"]}"#).unwrap();
+        assert!(matches!(message, ClientMessage::Subscribe { ref txids, .. } if txids.len() == 1));
+        assert!(serde_json::from_str::<ClientMessage>(r#"{"action":"replay"}"#).is_err());
+        assert!(Subscription::default()
+            .apply(ClientMessage::Subscribe { addresses: vec![], txids: vec!["not-a-txid".to_string()] })
+            .is_err());
+    }
+
+    #[test]
+    fn test_split_list() {
+        assert_eq!(split_list(&Some("a, b,,c".to_string())), vec!["a", "b", "c"]);
+        assert!(split_list(&None).is_empty());
+    }
+}
@@ -0,0 +1,113 @@
+// core/blockchain-monitor/src/fees.rs
+// Transaction fees: resolve input prevouts to compute the fee actually paid and the fee rate
+
+use std::collections::HashMap;
+
+use crate::double_spend::spent_outpoints;
+use crate::{AppState, ServiceError, WocTransaction};
+
+/// Transactions with more inputs than this are stored without a fee rather than
+/// spending hundreds of provider calls on one record
+const MAX_FEE_INPUTS: usize = 100;
+
+pub fn to_satoshis(value: f64) -> i64 {
+    (value * 100_000_000.0).round() as i64
+}
+
+/// Value of output `vout`, matched on its index rather than its position in the list
+pub fn output_value(woc_tx: &WocTransaction, vout: u32) -> Option<i64> {
+    woc_tx.outputs.as_ref()?
+        .iter()
+        .enumerate()
+        .find(|(position, output)| output.n.unwrap_or(*position as u32) == vout)
+        .and_then(|(_, output)| output.value)
+        .map(to_satoshis)
+}
+
+pub fn total_output(woc_tx: &WocTransaction) -> i64 {
+    woc_tx.outputs.iter().flatten().filter_map(|o| o.value).map(to_satoshis).sum()
+}
+
+/// Serialized size, from the provider or the raw hex
+pub fn size_bytes(woc_tx: &WocTransaction) -> Option<i32> {
+    woc_tx.size
+        .or_else(|| woc_tx.hex.as_ref().map(|hex| (hex.len() / 2) as i64))
+        .and_then(|size| i32::try_from(size).ok())
+}
+
+/// Satoshis per byte
+pub fn fee_rate(fee_satoshis: Option<i64>, size_bytes: Option<i32>) -> Option<f64> {
+    match (fee_satoshis, size_bytes) {
+        (Some(fee), Some(size)) if size > 0 => Some(fee as f64 / size as f64),
+        _ => None,
+    }
+}
+
+/// Inputs minus outputs. None for coinbase transactions, oversized ones, or when a
+/// prevout cannot be resolved; a negative result means the provider data is inconsistent.
+pub async fn resolve_fee(state: &AppState, woc_tx: &WocTransaction) -> Result<Option<i64>, ServiceError> {
+    let outpoints = spent_outpoints(woc_tx);
+    if outpoints.is_empty() || outpoints.len() > MAX_FEE_INPUTS {
+        return Ok(None);
+    }
+
+    // Several inputs often spend outputs of the same parent
+    let mut parents: HashMap<String, WocTransaction> = HashMap::new();
+    let mut inputs = 0i64;
+    for outpoint in outpoints {
+        if !parents.contains_key(&outpoint.txid) {
+            let parent = state.chain.get_transaction(&outpoint.txid).await?;
+            parents.insert(outpoint.txid.clone(), parent);
+        }
+        match output_value(&parents[&outpoint.txid], outpoint.vout) {
+            Some(value) => inputs += value,
+            None => return Ok(None),
+        }
+    }
+
+    let fee = inputs - total_output(woc_tx);
+    if fee < 0 {
+        tracing::warn!("Negative fee computed for {}: inputs {} < outputs", woc_tx.txid, inputs);
+        return Ok(None);
+    }
+    Ok(Some(fee))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn woc_tx(value: serde_json::Value) -> WocTransaction {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_output_value_by_index() {
+        let tx = woc_tx(serde_json::json!({
+            "txid": "aa",
+            "vout": [{ "value": 0.0001, "n": 0 }, { "value": 0.12345678, "n": 1 }]
+        }));
+        assert_eq!(output_value(&tx, 1), Some(12_345_678));
+        assert_eq!(output_value(&tx, 2), None);
+        assert_eq!(total_output(&tx), 12_355_678);
+    }
+
+    #[test]
+    fn test_satoshi_rounding() {
+        // 0.29 BSV is not exactly representable; truncation would lose a satoshi
+        assert_eq!(to_satoshis(0.29), 29_000_000);
+        assert_eq!(to_satoshis(0.00000001), 1);
+    }
+
+    #[test]
+    fn test_size_and_rate() {
+        let tx = woc_tx(serde_json::json!({ "txid": "aa", "hex": "00".repeat(250) }));
+        assert_eq!(size_bytes(&tx), Some(250));
+        let sized = woc_tx(serde_json::json!({ "txid": "aa", "size": 191, "hex": "00" }));
+        assert_eq!(size_bytes(&sized), Some(191));
+
+        assert_eq!(fee_rate(Some(125), Some(250)), Some(0.5));
+        assert_eq!(fee_rate(None, Some(250)), None);
+        assert_eq!(fee_rate(Some(125), Some(0)), None);
+    }
+}
@@ -3,6 +3,25 @@
 // Monitors BSV testnet via WhatsOnChain API
 // Phase 6 Production Hardening
 
+mod arc;
+mod backfill;
+mod block_scan;
+mod budget;
+mod double_spend;
+mod events;
+mod fees;
+mod metrics;
+mod outbox;
+mod policies;
+mod providers;
+mod scripts;
+mod search;
+mod tracking;
+mod tx_cache;
+mod watchlist;
+mod webhooks;
+mod xpub;
+
 use actix_web::{web, App, HttpResponse, HttpServer, Result, middleware};
 use actix_cors::Cors;
 use serde::{Deserialize, Serialize};
@@ -12,13 +31,15 @@ use tokio::sync::RwLock;
 use std::collections::{HashMap, HashSet};
 use chrono::{DateTime, Utc};
 use bsv_bank_common::{
-    init_logging, ServiceMetrics,
+    init_logging, JwtManager, ServiceMetrics,
     validate_txid, validate_address,
+    publisher_from_env,
 };
 use dotenv::dotenv;
 use prometheus::Registry;
 use std::time::SystemTime;
 use thiserror::Error;
+use uuid::Uuid;
 
 // ============================================================================
 // ERROR TYPES (Phase 6)
@@ -34,6 +55,10 @@ enum ServiceError {
     ApiError(String),
     #[error("Transaction not found: {0}")]
     NotFoundError(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
 }
 
 impl actix_web::ResponseError for ServiceError {
@@ -63,6 +88,18 @@ impl actix_web::ResponseError for ServiceError {
                     "message": msg
                 }))
             }
+            ServiceError::Unauthorized(msg) => {
+                HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "unauthorized",
+                    "message": msg
+                }))
+            }
+            ServiceError::Forbidden(msg) => {
+                HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "forbidden",
+                    "message": msg
+                }))
+            }
         }
     }
 }
@@ -77,6 +114,29 @@ struct Config {
     woc_api_base: String,
     network: String,
     polling_interval_secs: u64,
+    webhook_poll_secs: u64,
+    webhook_max_attempts: i32,
+    /// Also poll watched addresses' unconfirmed history so deposits appear before they are mined
+    mempool_detection: bool,
+    /// SCAN_MODE=block: match new blocks against watched scripts instead of polling each address
+    block_scanning: bool,
+    /// Chain data providers in failover order
+    chain_providers: Vec<String>,
+    tx_cache_capacity: usize,
+    tx_cache_ttl_secs: u64,
+    /// ARC endpoint for `/broadcast`; unset broadcasts through the chain providers only
+    arc_url: Option<String>,
+    arc_api_key: Option<String>,
+    /// Public URL of this service's `/arc/callback`, passed to ARC with each submission
+    arc_callback_url: Option<String>,
+    /// Archive addresses with no activity for this many days; 0 disables archiving
+    archive_after_days: u64,
+    /// Poll interval for deep transactions still short of their target and for dormant addresses
+    slow_polling_interval_secs: u64,
+    /// Addresses without activity for this long are polled at the slow interval
+    dormant_after_hours: u64,
+    /// EVENT_BUS is set: record events in the outbox for publication to the message bus
+    event_outbox: bool,
 }
 
 impl Config {
@@ -92,6 +152,52 @@ impl Config {
                 .ok()
                 .and_then(|s| s.parse().ok())
                 .unwrap_or(10),
+            webhook_poll_secs: std::env::var("WEBHOOK_POLL_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+            webhook_max_attempts: std::env::var("WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(8),
+            mempool_detection: std::env::var("MEMPOOL_DETECTION")
+                .map(|v| v != "false" && v != "0")
+                .unwrap_or(true),
+            block_scanning: std::env::var("SCAN_MODE")
+                .map(|v| v == "block")
+                .unwrap_or(false),
+            chain_providers: std::env::var("CHAIN_PROVIDERS")
+                .unwrap_or_else(|_| "whatsonchain,bitails,gorillapool,node".to_string())
+                .split(',')
+                .map(|p| p.trim().to_lowercase())
+                .filter(|p| !p.is_empty())
+                .collect(),
+            tx_cache_capacity: std::env::var("TX_CACHE_CAPACITY")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(10_000),
+            tx_cache_ttl_secs: std::env::var("TX_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(60),
+            arc_url: std::env::var("ARC_URL").ok().filter(|v| !v.is_empty()),
+            arc_api_key: std::env::var("ARC_API_KEY").ok().filter(|v| !v.is_empty()),
+            arc_callback_url: std::env::var("ARC_CALLBACK_URL").ok().filter(|v| !v.is_empty()),
+            archive_after_days: std::env::var("ARCHIVE_AFTER_DAYS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(90),
+            slow_polling_interval_secs: std::env::var("SLOW_POLLING_INTERVAL")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(300),
+            dormant_after_hours: std::env::var("DORMANT_AFTER_HOURS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(24),
+            event_outbox: std::env::var("EVENT_BUS")
+                .map(|v| !v.is_empty() && v != "none")
+                .unwrap_or(false),
         }
     }
 }
@@ -114,6 +220,57 @@ struct Transaction {
     block_height: Option<i32>,
     block_time: Option<DateTime<Utc>>,
     raw_tx: Option<String>,
+    /// When the monitor first recorded the transaction, mempool or block
+    first_seen: Option<DateTime<Utc>>,
+    size_bytes: Option<i32>,
+    /// Satoshis per byte, derived from `fee_satoshis` and `size_bytes`
+    fee_rate: Option<f64>,
+    /// Confirmations required by the purpose of the watched addresses involved
+    target_confirmations: Option<i32>,
+    finalized_at: Option<DateTime<Utc>>,
+}
+
+/// `seen` for a transaction known only from the mempool, `confirmed` once mined
+fn confirmation_status(confirmations: i32) -> &'static str {
+    if confirmations > 0 { "confirmed" } else { "seen" }
+}
+
+/// Map a `blockchain_transactions` row selected with `TRANSACTION_COLUMNS`
+fn transaction_from_row(row: &sqlx::postgres::PgRow) -> Result<Transaction, ServiceError> {
+    let db_error = |e: sqlx::Error| ServiceError::DatabaseError(e.to_string());
+    let fee_satoshis: Option<i64> = row.try_get("fee_satoshis").map_err(db_error)?;
+    let size_bytes: Option<i32> = row.try_get("size_bytes").map_err(db_error)?;
+    Ok(Transaction {
+        txid: row.try_get("txid").map_err(db_error)?,
+        tx_type: row.try_get("tx_type").map_err(db_error)?,
+        from_address: row.try_get("from_address").map_err(db_error)?,
+        to_address: row.try_get("to_address").map_err(db_error)?,
+        amount_satoshis: row.try_get("amount_satoshis").map_err(db_error)?,
+        fee_satoshis,
+        confirmations: row.try_get("confirmations").map_err(db_error)?,
+        status: row.try_get("status").map_err(db_error)?,
+        block_hash: row.try_get("block_hash").map_err(db_error)?,
+        block_height: row.try_get("block_height").map_err(db_error)?,
+        block_time: row.try_get("block_time").map_err(db_error)?,
+        raw_tx: row.try_get("raw_tx").map_err(db_error)?,
+        first_seen: row.try_get("first_seen").map_err(db_error)?,
+        size_bytes,
+        fee_rate: fees::fee_rate(fee_satoshis, size_bytes),
+        target_confirmations: row.try_get("target_confirmations").map_err(db_error)?,
+        finalized_at: row.try_get("finalized_at").map_err(db_error)?,
+    })
+}
+
+const TRANSACTION_COLUMNS: &str = "txid, tx_type, from_address, to_address, amount_satoshis, \
+    fee_satoshis, confirmations, status, block_hash, block_height, block_time, raw_tx, first_seen, size_bytes, \
+    target_confirmations, finalized_at";
+
+/// A watched address or script due a poll
+#[derive(Debug, sqlx::FromRow)]
+struct DueWatch {
+    id: Uuid,
+    address: Option<String>,
+    script_hash: Option<String>,
 }
 
 #[allow(dead_code)]
@@ -151,7 +308,6 @@ struct ConfirmationUpdate {
 // WhatsOnChain API response types
 #[derive(Debug, Deserialize)]
 struct WocTransaction {
-    #[allow(dead_code)]
     txid: String,
     confirmations: Option<i32>,
     blockhash: Option<String>,
@@ -161,14 +317,13 @@ struct WocTransaction {
     inputs: Option<Vec<WocInput>>,
     #[serde(rename = "vout")]
     outputs: Option<Vec<WocOutput>>,
+    size: Option<i64>,
     hex: Option<String>,
 }
 
 #[derive(Debug, Deserialize)]
 struct WocInput {
-    #[allow(dead_code)]
     txid: Option<String>,
-    #[allow(dead_code)]
     vout: Option<u32>,
     #[serde(rename = "scriptSig")]
     script_sig: Option<WocScript>,
@@ -177,7 +332,6 @@ struct WocInput {
 #[derive(Debug, Deserialize)]
 struct WocOutput {
     value: Option<f64>,
-    #[allow(dead_code)]
     n: Option<u32>,
     #[serde(rename = "scriptPubKey")]
     script_pub_key: Option<WocScript>,
@@ -204,6 +358,38 @@ struct WocUtxo {
     height: Option<i32>,
 }
 
+#[derive(Debug, Deserialize)]
+struct WocHistoryEntry {
+    tx_hash: String,
+}
+
+/// WoC returns history either bare or wrapped in `{ "result": [...] }`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum WocHistory {
+    Wrapped { result: Vec<WocHistoryEntry> },
+    Plain(Vec<WocHistoryEntry>),
+}
+
+impl WocHistory {
+    fn into_txids(self) -> Vec<String> {
+        let entries = match self {
+            WocHistory::Wrapped { result } => result,
+            WocHistory::Plain(entries) => entries,
+        };
+        entries.into_iter().map(|e| e.tx_hash).collect()
+    }
+}
+
+/// WoC `/address/{address}/confirmed/history`, oldest first, paged by token
+#[derive(Debug, Deserialize)]
+struct WocHistoryPage {
+    #[serde(default)]
+    result: Vec<WocHistoryEntry>,
+    #[serde(rename = "nextPageToken")]
+    next_page_token: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 struct WocBalance {
     confirmed: i64,
@@ -217,34 +403,49 @@ struct WocBalance {
 struct AppState {
     db: PgPool,
     config: Config,
-    client: reqwest::Client,
+    chain: providers::ProviderPool,
     watched_addresses: Arc<RwLock<HashSet<String>>>,
-    tx_cache: Arc<RwLock<HashMap<String, Transaction>>>,
+    /// Locking scripts with no base58 address, keyed by script hash
+    watched_scripts: Arc<RwLock<HashMap<String, Vec<u8>>>>,
+    tx_cache: tx_cache::TxCache,
+    events: events::EventBus,
+    double_spends: prometheus::IntCounterVec,
+    metrics: metrics::MonitorMetrics,
     start_time: SystemTime,
 }
 
 impl AppState {
-    async fn new(config: Config) -> Result<Self, sqlx::Error> {
+    async fn new(
+        config: Config,
+        chain: providers::ProviderPool,
+        double_spends: prometheus::IntCounterVec,
+        metrics: metrics::MonitorMetrics,
+        tx_cache: tx_cache::TxCache,
+    ) -> Result<Self, sqlx::Error> {
         let db = PgPool::connect(&config.database_url).await?;
-        let client = reqwest::Client::new();
         
         let state = Self {
             db,
             config,
-            client,
+            chain,
             watched_addresses: Arc::new(RwLock::new(HashSet::new())),
-            tx_cache: Arc::new(RwLock::new(HashMap::new())),
+            watched_scripts: Arc::new(RwLock::new(HashMap::new())),
+            tx_cache,
+            events: events::EventBus::new(),
+            double_spends,
+            metrics,
             start_time: SystemTime::now(),
         };
         
-        // Load watched addresses from database
+        // Load watched addresses and scripts from database
         state.load_watched_addresses().await?;
+        state.load_watched_scripts().await?;
         
         Ok(state)
     }
     
     async fn load_watched_addresses(&self) -> Result<(), sqlx::Error> {
-        let rows = sqlx::query("SELECT address FROM watched_addresses")
+        let rows = sqlx::query("SELECT address FROM watched_addresses WHERE address IS NOT NULL AND status = 'active'")
             .fetch_all(&self.db)
             .await?;
         
@@ -258,144 +459,38 @@ impl AppState {
     }
 }
 
-// ============================================================================
-// WhatsOnChain API Client
-// ============================================================================
-
-impl AppState {
-    async fn woc_get_transaction(&self, txid: &str) -> Result<WocTransaction, ServiceError> {
-        let url = format!("{}/tx/{}", self.config.woc_api_base, txid);
-        
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ServiceError::ApiError(format!("Request failed: {}", e)))?;
-        
-        if !response.status().is_success() {
-            return Err(ServiceError::ApiError(format!("Status: {}", response.status())));
-        }
-        
-        response
-            .json::<WocTransaction>()
-            .await
-            .map_err(|e| ServiceError::ApiError(format!("Parse error: {}", e)))
-    }
-    
-    async fn woc_get_chain_info(&self) -> Result<WocChainInfo, ServiceError> {
-        let url = format!("{}/chain/info", self.config.woc_api_base);
-        
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))?;
-        
-        response
-            .json::<WocChainInfo>()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))
-    }
-    
-    async fn woc_get_address_utxos(&self, address: &str) -> Result<Vec<WocUtxo>, ServiceError> {
-        let url = format!("{}/address/{}/unspent", self.config.woc_api_base, address);
-        
-        let response = self.client
-            .get(&url)
-            .send()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))?;
-        
-        if !response.status().is_success() {
-            return Ok(vec![]); // Address has no UTXOs
-        }
-        
-        response
-            .json::<Vec<WocUtxo>>()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))
-    }
-    
-    async fn woc_get_address_balance(&self, address: &str) -> Result<WocBalance, ServiceError> {
-        let url = format!("{}/address/{}/balance", self.config.woc_api_base, address);
-        
-        tracing::debug!("Fetching balance from: {}", url);
-        
-        let response = self.client
-            .get(&url)
-            .timeout(std::time::Duration::from_secs(30))
-            .send()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))?;
-        
-        let status = response.status();
-        
-        if !status.is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            tracing::error!("WoC API error for {}: status={}, body={}", address, status, error_text);
-            return Err(ServiceError::ApiError(format!("Status: {} - {}", status, error_text)));
-        }
-        
-        let response_text = response
-            .text()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))?;
-        
-        if response_text.trim().is_empty() {
-            tracing::warn!("Empty response from WoC for address: {}", address);
-            return Ok(WocBalance {
-                confirmed: 0,
-                unconfirmed: 0,
-            });
-        }
-        
-        serde_json::from_str::<WocBalance>(&response_text)
-            .map_err(|e| ServiceError::ApiError(format!("Parse error: {}", e)))
-    }
-    
-    async fn woc_broadcast_transaction(&self, tx_hex: &str) -> Result<String, ServiceError> {
-        let url = format!("{}/tx/raw", self.config.woc_api_base);
-        
-        #[derive(Serialize)]
-        struct BroadcastRequest {
-            txhex: String,
-        }
-        
-        let response = self.client
-            .post(&url)
-            .json(&BroadcastRequest {
-                txhex: tx_hex.to_string(),
-            })
-            .send()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))?;
-        
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(ServiceError::ApiError(format!("Broadcast failed: {}", error_text)));
-        }
-        
-        response
-            .text()
-            .await
-            .map_err(|e| ServiceError::ApiError(e.to_string()))
-    }
-}
-
 // ============================================================================
 // Database Operations
 // ============================================================================
 
 impl AppState {
-    async fn save_transaction(&self, tx: &Transaction) -> Result<(), ServiceError> {
+    /// Upsert the transaction, recording `event` in the outbox within the same database transaction
+    async fn save_transaction(&self, tx: &Transaction, event: Option<&events::MonitorEvent>) -> Result<(), ServiceError> {
+        let mut db_tx = self.db.begin().await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        
         sqlx::query(
             r#"
             INSERT INTO blockchain_transactions 
                 (txid, tx_type, from_address, to_address, amount_satoshis, 
                  fee_satoshis, confirmations, status, block_hash, block_height, 
-                 block_time, raw_tx, first_seen)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())
+                 block_time, raw_tx, first_seen, size_bytes)
+            VALUES ($1, COALESCE($2, 'unknown'), $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW(), $13)
             ON CONFLICT (txid) DO UPDATE SET
+                from_address = COALESCE(blockchain_transactions.from_address, $3),
+                to_address = COALESCE(blockchain_transactions.to_address, $4),
+                -- A pending row is a /track/tx placeholder: take the details now it is seen
+                amount_satoshis = CASE WHEN blockchain_transactions.status = 'pending'
+                                       THEN $5
+                                       ELSE blockchain_transactions.amount_satoshis
+                                  END,
+                raw_tx = COALESCE(blockchain_transactions.raw_tx, $12),
+                first_seen = CASE WHEN blockchain_transactions.status = 'pending'
+                                  THEN NOW()
+                                  ELSE blockchain_transactions.first_seen
+                             END,
+                fee_satoshis = COALESCE(blockchain_transactions.fee_satoshis, $6),
+                size_bytes = COALESCE(blockchain_transactions.size_bytes, $13),
                 confirmations = $7,
                 status = $8,
                 block_hash = $9,
@@ -419,46 +514,32 @@ impl AppState {
         .bind(tx.block_height)
         .bind(tx.block_time)
         .bind(&tx.raw_tx)
-        .execute(&self.db)
+        .bind(tx.size_bytes)
+        .execute(&mut *db_tx)
         .await
         .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
         
+        if let Some(event) = event {
+            outbox::record(self, &mut *db_tx, event).await?;
+        }
+        
+        db_tx.commit().await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        
         Ok(())
     }
     
     async fn get_transaction(&self, txid: &str) -> Result<Option<Transaction>, ServiceError> {
-        let row = sqlx::query(
-            r#"
-            SELECT txid, tx_type, from_address, to_address, amount_satoshis,
-                   fee_satoshis, confirmations, status, block_hash, block_height,
-                   block_time, raw_tx
-            FROM blockchain_transactions
-            WHERE txid = $1
-            "#
-        )
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM blockchain_transactions WHERE txid = $1",
+            TRANSACTION_COLUMNS
+        ))
         .bind(txid)
         .fetch_optional(&self.db)
         .await
         .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
         
-        if let Some(row) = row {
-            Ok(Some(Transaction {
-                txid: row.try_get("txid").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                tx_type: row.try_get("tx_type").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                from_address: row.try_get("from_address").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                to_address: row.try_get("to_address").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                amount_satoshis: row.try_get("amount_satoshis").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                fee_satoshis: row.try_get("fee_satoshis").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                confirmations: row.try_get("confirmations").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                status: row.try_get("status").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                block_hash: row.try_get("block_hash").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                block_height: row.try_get("block_height").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                block_time: row.try_get("block_time").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-                raw_tx: row.try_get("raw_tx").map_err(|e| ServiceError::DatabaseError(e.to_string()))?,
-            }))
-        } else {
-            Ok(None)
-        }
+        row.map(|row| transaction_from_row(&row)).transpose()
     }
     
     async fn save_confirmation_event(&self, update: &ConfirmationUpdate) -> Result<(), ServiceError> {
@@ -485,7 +566,8 @@ impl AppState {
             r#"
             INSERT INTO watched_addresses (address, paymail, purpose, created_at)
             VALUES ($1, $2, $3, NOW())
-            ON CONFLICT (address) DO NOTHING
+            ON CONFLICT (address) DO UPDATE SET status = 'active', status_changed_at = NOW()
+            WHERE watched_addresses.status <> 'active'
             "#
         )
         .bind(address)
@@ -502,21 +584,109 @@ impl AppState {
         Ok(())
     }
     
+    /// Unsettled transactions due a poll: those under 6 confirmations every pass, deeper
+    /// ones still short of their target at the slow interval. Longest-waiting first.
     async fn get_pending_transactions(&self) -> Result<Vec<String>, ServiceError> {
         let rows = sqlx::query(
             r#"
             SELECT txid FROM blockchain_transactions
-            WHERE status = 'pending' OR confirmations < 6
-            ORDER BY first_seen DESC
+            WHERE (status = 'pending' OR confirmations < 6
+                   OR (finalized_at IS NULL AND confirmations < target_confirmations))
+              AND (last_checked_at IS NULL OR last_checked_at <= NOW() - make_interval(
+                       secs => CASE WHEN confirmations < 6 THEN $1 ELSE $2 END))
+            ORDER BY last_checked_at NULLS FIRST, first_seen DESC
             LIMIT 100
             "#
         )
+        .bind(self.config.polling_interval_secs as f64)
+        .bind(self.config.slow_polling_interval_secs as f64)
         .fetch_all(&self.db)
         .await
         .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
         
         Ok(rows.into_iter().map(|row| row.get("txid")).collect())
     }
+    
+    /// Refresh the watch-set and poll-queue gauges
+    async fn update_metrics(&self) -> Result<(), ServiceError> {
+        self.metrics.watched_addresses.set(self.watched_addresses.read().await.len() as i64);
+        self.metrics.watched_scripts.set(self.watched_scripts.read().await.len() as i64);
+        
+        let pending: i64 = sqlx::query_scalar(
+            r#"
+            SELECT COUNT(*) FROM blockchain_transactions
+            WHERE status = 'pending' OR confirmations < 6
+               OR (finalized_at IS NULL AND confirmations < target_confirmations)
+            "#
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        self.metrics.pending_transactions.set(pending);
+        
+        Ok(())
+    }
+    
+    /// Seconds since the transaction was broadcast through ARC, registered through
+    /// `/track/tx` or first seen, whichever came first
+    async fn broadcast_age_secs(&self, txid: &str) -> Result<Option<f64>, ServiceError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT EXTRACT(EPOCH FROM NOW() - LEAST(
+                       (SELECT submitted_at FROM arc_broadcasts WHERE txid = $1),
+                       (SELECT registered_at FROM tracked_transactions WHERE txid = $1),
+                       first_seen))::FLOAT8
+            FROM blockchain_transactions WHERE txid = $1
+            "#
+        )
+        .bind(txid)
+        .fetch_optional(&self.db)
+        .await
+        .map(Option::flatten)
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+    
+    async fn mark_transaction_checked(&self, txid: &str) -> Result<(), ServiceError> {
+        sqlx::query("UPDATE blockchain_transactions SET last_checked_at = NOW() WHERE txid = $1")
+            .bind(txid)
+            .execute(&self.db)
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        
+        Ok(())
+    }
+    
+    /// Active watched addresses and scripts due a poll: every pass while recently active,
+    /// at the slow interval once dormant. Longest-waiting first.
+    async fn get_due_watches(&self) -> Result<Vec<DueWatch>, ServiceError> {
+        sqlx::query_as::<_, DueWatch>(
+            r#"
+            SELECT id, address, script_hash FROM watched_addresses
+            WHERE status = 'active'
+              AND (last_checked IS NULL OR last_checked <= NOW() - make_interval(
+                       secs => CASE WHEN COALESCE(last_activity, created_at) >= NOW() - make_interval(hours => $3)
+                                    THEN $1 ELSE $2 END))
+            ORDER BY last_checked NULLS FIRST
+            LIMIT 500
+            "#
+        )
+        .bind(self.config.polling_interval_secs as f64)
+        .bind(self.config.slow_polling_interval_secs as f64)
+        .bind(self.config.dormant_after_hours as i32)
+        .fetch_all(&self.db)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))
+    }
+    
+    async fn mark_watch_checked(&self, id: Uuid) -> Result<(), ServiceError> {
+        sqlx::query("UPDATE watched_addresses SET last_checked = NOW() WHERE id = $1")
+            .bind(id)
+            .execute(&self.db)
+            .await
+            .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+        
+        Ok(())
+    }
 }
 
 // ============================================================================
@@ -528,24 +698,74 @@ async fn start_monitoring_task(state: web::Data<AppState>) {
     
     tokio::spawn(async move {
         loop {
-            // Update pending transactions
+            state.chain.update_budget_metrics();
+            if let Err(e) = state.update_metrics().await {
+                tracing::error!("Failed to update monitor metrics: {}", e);
+            }
+            
+            // Update pending transactions that are due; whatever the budget does not cover
+            // stays due and goes first next pass
             if let Ok(pending_txids) = state.get_pending_transactions().await {
                 for txid in pending_txids {
-                    if let Err(e) = update_transaction_confirmations(&state, &txid).await {
-                        tracing::error!("Error updating TX {}: {}", txid, e);
+                    if !state.chain.has_background_budget() {
+                        tracing::debug!("Provider budget low; deferring remaining transaction polls");
+                        break;
+                    }
+                    match update_transaction_confirmations(&state, &txid).await {
+                        Ok(()) => {
+                            if let Err(e) = state.mark_transaction_checked(&txid).await {
+                                tracing::error!("Error marking TX {} checked: {}", txid, e);
+                            }
+                        }
+                        Err(e) => tracing::error!("Error updating TX {}: {}", txid, e),
                     }
                 }
             }
             
-            // Check watched addresses
-            let addresses = {
-                let addr_set = state.watched_addresses.read().await;
-                addr_set.iter().cloned().collect::<Vec<_>>()
+            // Look for conflicting spends of unconfirmed transactions' inputs
+            if state.chain.has_background_budget() {
+                if let Err(e) = double_spend::check_unconfirmed(&state).await {
+                    tracing::error!("Double-spend check failed: {}", e);
+                }
+            }
+            
+            // Scan new blocks for watched scripts; poll addresses if block mode is off or failing
+            let scanned = state.config.block_scanning && match block_scan::scan_new_blocks(&state).await {
+                Ok(_) => true,
+                Err(e) => {
+                    tracing::error!("Block scan failed, polling addresses instead: {}", e);
+                    false
+                }
             };
             
-            for address in addresses {
-                if let Err(e) = check_address_for_new_transactions(&state, &address).await {
-                    tracing::error!("Error checking address {}: {}", address, e);
+            if !scanned {
+                let due = state.get_due_watches().await.unwrap_or_else(|e| {
+                    tracing::error!("Failed to load watched addresses due a poll: {}", e);
+                    Vec::new()
+                });
+                
+                for watch in due {
+                    if !state.chain.has_background_budget() {
+                        tracing::debug!("Provider budget low; deferring remaining address polls");
+                        break;
+                    }
+                    let result = match (&watch.address, &watch.script_hash) {
+                        (Some(address), _) => check_address_for_new_transactions(&state, address).await,
+                        (None, Some(script_hash)) => scripts::check_script_for_new_transactions(&state, script_hash).await,
+                        (None, None) => continue,
+                    };
+                    match result {
+                        Ok(()) => {
+                            if let Err(e) = state.mark_watch_checked(watch.id).await {
+                                tracing::error!("Error marking watch {} checked: {}", watch.id, e);
+                            }
+                        }
+                        Err(e) => tracing::error!(
+                            "Error checking {}: {}",
+                            watch.address.as_deref().or(watch.script_hash.as_deref()).unwrap_or_default(),
+                            e
+                        ),
+                    }
                 }
             }
             
@@ -555,34 +775,77 @@ async fn start_monitoring_task(state: web::Data<AppState>) {
 }
 
 async fn update_transaction_confirmations(state: &AppState, txid: &str) -> Result<(), ServiceError> {
-    // Get current state from database
-    let old_tx = state.get_transaction(txid).await?;
+    // Get current state from database; a pending row is registered but not seen yet
+    let registered = state.get_transaction(txid).await?;
+    let old_tx = registered.clone().filter(|t| t.status != "pending");
     let old_confs = old_tx.as_ref().map(|t| t.confirmations).unwrap_or(0);
     
     // Query WhatsOnChain
-    let woc_tx = state.woc_get_transaction(txid).await?;
+    let woc_tx = state.chain.get_transaction(txid).await?;
     let new_confs = woc_tx.confirmations.unwrap_or(0);
     
-    // Update if changed
-    if new_confs != old_confs {
+    let old_hash = old_tx.as_ref().and_then(|t| t.block_hash.as_deref());
+    let moved_block = matches!((old_hash, woc_tx.blockhash.as_deref()), (Some(a), Some(b)) if a != b);
+    
+    // Update if new, changed or reorganised into another block
+    if old_tx.is_none() || new_confs != old_confs || moved_block {
+        // Prevouts are resolved once; a failed lookup is retried on the next update
+        let fee_satoshis = match old_tx.as_ref().and_then(|t| t.fee_satoshis) {
+            Some(fee) => Some(fee),
+            None => fees::resolve_fee(state, &woc_tx).await.unwrap_or_else(|e| {
+                tracing::debug!("Could not resolve fee for {}: {}", txid, e);
+                None
+            }),
+        };
+        let size_bytes = fees::size_bytes(&woc_tx);
         let tx = Transaction {
             txid: txid.to_string(),
-            tx_type: old_tx.as_ref().and_then(|t| t.tx_type.clone()),
+            tx_type: registered.as_ref().and_then(|t| t.tx_type.clone()),
             from_address: extract_from_address(&woc_tx),
             to_address: extract_to_address(&woc_tx),
             amount_satoshis: calculate_output_amount(&woc_tx),
-            fee_satoshis: None,
+            fee_satoshis,
             confirmations: new_confs,
-            status: if new_confs > 0 { "confirmed" } else { "pending" }.to_string(),
+            status: confirmation_status(new_confs).to_string(),
             block_hash: woc_tx.blockhash.clone(),
             block_height: woc_tx.blockheight,
             block_time: woc_tx.blocktime.map(|t| {
                 DateTime::<Utc>::from_timestamp(t, 0).unwrap_or_else(Utc::now)
             }),
-            raw_tx: woc_tx.hex,
+            raw_tx: woc_tx.hex.clone(),
+            first_seen: old_tx.as_ref().and_then(|t| t.first_seen),
+            size_bytes,
+            fee_rate: fees::fee_rate(fee_satoshis, size_bytes),
+            target_confirmations: old_tx.as_ref().and_then(|t| t.target_confirmations),
+            finalized_at: old_tx.as_ref().and_then(|t| t.finalized_at),
         };
         
-        state.save_transaction(&tx).await?;
+        // Queue notifications first; a retry after a failed save will not duplicate them
+        let addresses = webhooks::transaction_addresses(&woc_tx);
+        webhooks::enqueue_matches(state, &tx, &addresses, old_tx.as_ref().map(|t| t.confirmations)).await?;
+        
+        let event = events::MonitorEvent::from_update(
+            txid,
+            addresses.clone(),
+            old_tx.as_ref().map(|t| (t.confirmations, t.block_hash.as_deref())),
+            new_confs,
+            tx.block_hash.as_deref(),
+            tx.block_height,
+            tx.amount_satoshis,
+        );
+        state.save_transaction(&tx, event.as_ref()).await?;
+        if matches!(event, Some(events::MonitorEvent::Reorg { .. })) {
+            state.metrics.reorgs.inc();
+        }
+        if metrics::reached_latency_target(old_tx.as_ref().map(|t| t.confirmations), new_confs) {
+            if let Some(secs) = state.broadcast_age_secs(txid).await? {
+                state.metrics.confirmation_latency.observe(secs);
+            }
+        }
+        double_spend::record_inputs(state, txid, new_confs, &woc_tx, &addresses).await?;
+        xpub::note_activity(state, &addresses).await?;
+        watchlist::note_activity(state, &addresses).await?;
+        policies::check_finalized(state, &tx, &addresses).await?;
         
         // Log confirmation event
         let update = ConfirmationUpdate {
@@ -595,24 +858,44 @@ async fn update_transaction_confirmations(state: &AppState, txid: &str) -> Resul
         state.save_confirmation_event(&update).await?;
         
         tracing::info!("TX {} confirmations: {} → {}", txid, old_confs, new_confs);
+        
+        state.tx_cache.invalidate(txid);
+        if let Some(event) = event {
+            state.events.publish(event);
+        }
     }
     
     Ok(())
 }
 
 async fn check_address_for_new_transactions(state: &AppState, address: &str) -> Result<(), ServiceError> {
-    // Get UTXOs for address
-    let utxos = state.woc_get_address_utxos(address).await?;
+    // Get UTXOs for address, plus mempool transactions so deposits show up before mining
+    let mut txids: Vec<String> = state.chain.get_address_utxos(address).await?
+        .into_iter()
+        .map(|utxo| utxo.tx_hash)
+        .collect();
+    if state.config.mempool_detection {
+        txids.extend(state.chain.get_address_unconfirmed(address).await?);
+    }
+    record_new_transactions(state, address, txids).await
+}
+
+/// Fetch and store any of `txids` not recorded yet; `target` names the watched address or script
+async fn record_new_transactions(state: &AppState, target: &str, txids: Vec<String>) -> Result<(), ServiceError> {
+    let mut checked = HashSet::new();
     
-    // Check each UTXO's transaction
-    for utxo in utxos {
+    // Check each transaction once
+    for txid in txids {
+        if !checked.insert(txid.clone()) {
+            continue;
+        }
         // Check if we've seen this transaction
-        if state.get_transaction(&utxo.tx_hash).await?.is_none() {
+        if state.get_transaction(&txid).await?.is_none() {
             // New transaction found!
-            tracing::info!("New TX detected for {}: {}", address, utxo.tx_hash);
+            tracing::info!("New TX detected for {}: {}", target, txid);
             
-            // Fetch and store transaction
-            if let Err(e) = update_transaction_confirmations(state, &utxo.tx_hash).await {
+            // Fetch and store transaction; unmined ones are recorded as `seen`
+            if let Err(e) = update_transaction_confirmations(state, &txid).await {
                 tracing::error!("Failed to fetch new TX: {}", e);
             }
         }
@@ -640,14 +923,7 @@ fn extract_to_address(woc_tx: &WocTransaction) -> Option<String> {
 }
 
 fn calculate_output_amount(woc_tx: &WocTransaction) -> i64 {
-    woc_tx.outputs.as_ref()
-        .map(|outputs| {
-            outputs.iter()
-                .filter_map(|o| o.value)
-                .map(|v| (v * 100_000_000.0) as i64)
-                .sum()
-        })
-        .unwrap_or(0)
+    fees::total_output(woc_tx)
 }
 
 // ============================================================================
@@ -666,7 +942,7 @@ async fn health_check(data: web::Data<AppState>) -> Result<HttpResponse, Service
         "network": data.config.network,
         "version": "0.1.0",
         "uptime_seconds": uptime,
-        "features": ["woc-integration", "tx-monitoring", "phase6-hardening"]
+        "features": ["woc-integration", "tx-monitoring", "mempool-detection", "double-spend-alerts", "webhooks", "event-stream", "phase6-hardening"]
     })))
 }
 
@@ -681,28 +957,32 @@ async fn readiness_check(data: web::Data<AppState>) -> Result<HttpResponse, Serv
         .await
         .is_ok();
     
-    // Check WoC API
-    let woc_ok = data.woc_get_chain_info().await.is_ok();
+    // Ready while at least one chain data provider answers
+    let chain_ok = data.chain.get_chain_info().await.is_ok();
+    let providers = data.chain.status();
     
-    if db_ok && woc_ok {
-        Ok(HttpResponse::Ok().json(serde_json::json!({
-            "status": "ready",
-            "checks": {
-                "database": "ok",
-                "whatsonchain_api": "ok"
-            }
-        })))
+    let body = serde_json::json!({
+        "status": if db_ok && chain_ok { "ready" } else { "not_ready" },
+        "checks": {
+            "database": if db_ok { "ok" } else { "error" },
+            "chain_data": if chain_ok { "ok" } else { "error" }
+        },
+        "providers": providers
+    });
+    
+    if db_ok && chain_ok {
+        Ok(HttpResponse::Ok().json(body))
     } else {
-        Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
-            "status": "not_ready",
-            "checks": {
-                "database": if db_ok { "ok" } else { "error" },
-                "whatsonchain_api": if woc_ok { "ok" } else { "error" }
-            }
-        })))
+        Ok(HttpResponse::ServiceUnavailable().json(body))
     }
 }
 
+async fn providers_status(data: web::Data<AppState>) -> Result<HttpResponse, ServiceError> {
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "providers": data.chain.status()
+    })))
+}
+
 #[derive(Deserialize)]
 struct GetTxQuery {
     include_raw: Option<bool>,
@@ -718,25 +998,17 @@ async fn get_transaction(
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
     
     // Check cache first
-    {
-        let cache = data.tx_cache.read().await;
-        if let Some(tx) = cache.get(txid.as_str()) {
-            let mut response = tx.clone();
-            if !query.include_raw.unwrap_or(false) {
-                response.raw_tx = None;
-            }
-            return Ok(HttpResponse::Ok().json(response));
+    if let Some(mut response) = data.tx_cache.get(&txid) {
+        if !query.include_raw.unwrap_or(false) {
+            response.raw_tx = None;
         }
+        return Ok(HttpResponse::Ok().json(response));
     }
     
     // Check database
     match data.get_transaction(&txid).await? {
         Some(tx) => {
-            // Cache it
-            {
-                let mut cache = data.tx_cache.write().await;
-                cache.insert(txid.to_string(), tx.clone());
-            }
+            data.tx_cache.insert(tx.clone());
             
             let mut response = tx;
             if !query.include_raw.unwrap_or(false) {
@@ -746,7 +1018,8 @@ async fn get_transaction(
         }
         None => {
             // Not in DB, query WhatsOnChain
-            let woc_tx = data.woc_get_transaction(&txid).await?;
+            let woc_tx = data.chain.get_transaction(&txid).await?;
+            let size_bytes = fees::size_bytes(&woc_tx);
             
             let tx = Transaction {
                 txid: txid.to_string(),
@@ -756,11 +1029,7 @@ async fn get_transaction(
                 amount_satoshis: calculate_output_amount(&woc_tx),
                 fee_satoshis: None,
                 confirmations: woc_tx.confirmations.unwrap_or(0),
-                status: if woc_tx.confirmations.unwrap_or(0) > 0 { 
-                    "confirmed" 
-                } else { 
-                    "pending" 
-                }.to_string(),
+                status: confirmation_status(woc_tx.confirmations.unwrap_or(0)).to_string(),
                 block_hash: woc_tx.blockhash.clone(),
                 block_height: woc_tx.blockheight,
                 block_time: woc_tx.blocktime.map(|t| {
@@ -771,10 +1040,15 @@ async fn get_transaction(
                 } else {
                     None
                 },
+                first_seen: None,
+                size_bytes,
+                fee_rate: None,
+                target_confirmations: None,
+                finalized_at: None,
             };
             
             // Save to database
-            let _ = data.save_transaction(&tx).await;
+            let _ = data.save_transaction(&tx, None).await;
             
             Ok(HttpResponse::Ok().json(tx))
         }
@@ -796,18 +1070,18 @@ async fn get_confirmations(
     validate_txid(&txid)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
     
-    let woc_tx = data.woc_get_transaction(&txid).await?;
+    let woc_tx = data.chain.get_transaction(&txid).await?;
     let confirmations = woc_tx.confirmations.unwrap_or(0);
     
     Ok(HttpResponse::Ok().json(ConfirmationsResponse {
         txid: txid.to_string(),
         confirmations,
-        status: if confirmations > 0 { "confirmed" } else { "pending" }.to_string(),
+        status: confirmation_status(confirmations).to_string(),
     }))
 }
 
 async fn get_chain_info(data: web::Data<AppState>) -> Result<HttpResponse, ServiceError> {
-    let info = data.woc_get_chain_info().await?;
+    let info = data.chain.get_chain_info().await?;
     
     Ok(HttpResponse::Ok().json(ChainInfo {
         height: info.blocks,
@@ -833,7 +1107,7 @@ async fn get_address_balance(
     validate_address(&address)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
     
-    let balance = data.woc_get_address_balance(&address).await?;
+    let balance = data.chain.get_address_balance(&address).await?;
     
     Ok(HttpResponse::Ok().json(AddressBalanceResponse {
         address: address.to_string(),
@@ -858,7 +1132,7 @@ async fn get_address_utxos(
     validate_address(&address)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
     
-    let woc_utxos = data.woc_get_address_utxos(&address).await?;
+    let woc_utxos = data.chain.get_address_utxos(&address).await?;
     
     let utxos: Vec<Utxo> = woc_utxos.into_iter().map(|u| Utxo {
         txid: u.tx_hash,
@@ -903,7 +1177,7 @@ async fn watch_address(
     Ok(HttpResponse::Ok().json(WatchAddressResponse {
         success: true,
         address: req.address.clone(),
-        message: "Address is now being monitored".to_string(),
+        message: "Address is now being monitored; its confirmed history is being backfilled".to_string(),
     }))
 }
 
@@ -916,6 +1190,9 @@ struct BroadcastRequest {
 struct BroadcastResponse {
     success: bool,
     txid: String,
+    /// ARC `txStatus` when the transaction went through ARC
+    #[serde(skip_serializing_if = "Option::is_none")]
+    arc_status: Option<String>,
 }
 
 async fn broadcast_transaction(
@@ -927,7 +1204,19 @@ async fn broadcast_transaction(
         return Err(ServiceError::ValidationError("Invalid transaction hex".to_string()));
     }
     
-    let txid = data.woc_broadcast_transaction(&req.tx_hex).await?;
+    // ARC first for acceptance tracking; the providers cover ARC being unavailable
+    let (txid, arc_status) = match arc::submit(&data, &req.tx_hex).await? {
+        Some(status) if arc::is_rejection(&status.tx_status) => {
+            return Err(ServiceError::ApiError(format!(
+                "Transaction {} {}: {}",
+                status.txid,
+                status.tx_status,
+                status.extra_info.unwrap_or_default()
+            )));
+        }
+        Some(status) => (status.txid, Some(status.tx_status)),
+        None => (data.chain.broadcast(&req.tx_hex).await?, None),
+    };
     
     // Start monitoring this transaction
     let _ = update_transaction_confirmations(&data, &txid).await;
@@ -935,6 +1224,7 @@ async fn broadcast_transaction(
     Ok(HttpResponse::Ok().json(BroadcastResponse {
         success: true,
         txid,
+        arc_status,
     }))
 }
 
@@ -965,34 +1255,75 @@ async fn main() -> std::io::Result<()> {
     let config = Config::from_env();
     println!("   Network: {}", config.network);
     println!("   API: {}", config.woc_api_base);
+    println!("   Chain providers: {}", config.chain_providers.join(", "));
     println!("   Polling interval: {}s", config.polling_interval_secs);
+    println!("   Mempool detection: {}", config.mempool_detection);
+    println!("   Scan mode: {}", if config.block_scanning { "block" } else { "poll" });
     
     // Phase 6: Initialize structured logging
     init_logging("blockchain-monitor");
     tracing::info!("Starting Blockchain Monitor on port 8084");
     tracing::info!("WhatsOnChain API: {}", config.woc_api_base);
+    if config.block_scanning && config.mempool_detection {
+        tracing::warn!("SCAN_MODE=block does not poll addresses; deposits are detected once mined, not from the mempool");
+    }
+    
+    // Phase 6: Prometheus metrics
+    let registry = Registry::new();
+    let _service_metrics = ServiceMetrics::new(&registry, "blockchain_monitor")
+        .expect("Failed to create service metrics");
+    let double_spends = double_spend::metric(&registry)
+        .expect("Failed to create double-spend metric");
+    let monitor_metrics = metrics::MonitorMetrics::new(&registry)
+        .expect("Failed to create monitor metrics");
+    let chain = providers::ProviderPool::from_config(&config, &registry)
+        .expect("Failed to create provider budget metrics");
+    let tx_cache = tx_cache::TxCache::new(
+        config.tx_cache_capacity,
+        std::time::Duration::from_secs(config.tx_cache_ttl_secs),
+        &registry,
+    )
+    .expect("Failed to create transaction cache metrics");
+    tracing::info!("Metrics initialized");
     
     // Initialize application state
     let state = web::Data::new(
-        AppState::new(config.clone())
+        AppState::new(config.clone(), chain, double_spends, monitor_metrics, tx_cache)
             .await
             .expect("Failed to initialize application state")
     );
     
     tracing::info!("Database connection established");
     
-    // Phase 6: Prometheus metrics
-    let registry = Registry::new();
-    let _service_metrics = ServiceMetrics::new(&registry, "blockchain_monitor")
-        .expect("Failed to create service metrics");
-    tracing::info!("Metrics initialized");
-    
     let registry_data = web::Data::new(registry);
     
+    let jwt_secret = std::env::var("JWT_SECRET")
+        .unwrap_or_else(|_| {
+            println!("⚠️  JWT_SECRET not set, using development default");
+            "development-secret-change-in-production".to_string()
+        });
+    let jwt_manager = web::Data::new(JwtManager::new(jwt_secret));
+    
     // Start background monitoring task
     start_monitoring_task(state.clone()).await;
     tracing::info!("Background monitoring task started");
     
+    webhooks::start_delivery_worker(state.clone()).await;
+    tracing::info!("Webhook delivery worker started");
+    
+    backfill::start_backfill_worker(state.clone()).await;
+    tracing::info!("Address history backfill worker started");
+    
+    watchlist::start_archive_worker(state.clone()).await;
+    
+    match publisher_from_env().await.expect("Failed to set up event bus") {
+        Some(publisher) => {
+            tracing::info!("Publishing monitor events via {}", publisher.name());
+            outbox::start_outbox_worker(state.clone(), publisher).await;
+        }
+        None => tracing::info!("EVENT_BUS not set; monitor events are not published to a message bus"),
+    }
+    
     println!("✅ Service ready on http://127.0.0.1:8084");
     println!("📋 Health: http://127.0.0.1:8084/health");
     println!("📊 Metrics: http://127.0.0.1:8084/metrics");
@@ -1003,7 +1334,7 @@ async fn main() -> std::io::Result<()> {
         let cors = Cors::default()
             .allowed_origin("http://localhost:3000")
             .allowed_origin("http://localhost:5173")
-            .allowed_methods(vec!["GET", "POST"])
+            .allowed_methods(vec!["GET", "POST", "PUT", "DELETE"])
             .allowed_headers(vec![
                 actix_web::http::header::CONTENT_TYPE,
             ])
@@ -1022,6 +1353,7 @@ async fn main() -> std::io::Result<()> {
             )
             .app_data(state.clone())
             .app_data(registry_data.clone())
+            .app_data(jwt_manager.clone())
             
             // Health endpoints (no auth)
             .route("/health", web::get().to(health_check))
@@ -1032,11 +1364,17 @@ async fn main() -> std::io::Result<()> {
             .route("/metrics", web::get().to(metrics_handler))
             
             // Transaction endpoints
+            .route("/txs", web::get().to(search::search_transactions))
             .route("/tx/{txid}", web::get().to(get_transaction))
             .route("/tx/{txid}/confirmations", web::get().to(get_confirmations))
             
             // Chain info
             .route("/chain/info", web::get().to(get_chain_info))
+            .route("/providers/status", web::get().to(providers_status))
+            
+            // Confirmation policies
+            .route("/policies", web::get().to(policies::list_policies))
+            .route("/policies/{purpose}", web::put().to(policies::set_policy))
             
             // Address endpoints
             .route("/address/{address}/balance", web::get().to(get_address_balance))
@@ -1044,11 +1382,70 @@ async fn main() -> std::io::Result<()> {
             
             // Monitoring endpoints
             .route("/watch/address", web::post().to(watch_address))
+            .route("/watch/address/{address}", web::delete().to(watchlist::unwatch_address))
+            .route("/watch/address/{address}/status", web::put().to(watchlist::set_status))
+            .route("/watch/addresses", web::get().to(watchlist::list_watched))
+            .route("/watch/address/{address}/backfill", web::get().to(backfill::get_backfill_status))
+            .route("/watch/script", web::post().to(scripts::watch_script))
+            .route("/watch/xpub", web::post().to(xpub::watch_xpub))
+            .route("/watch/xpub/{id}", web::get().to(xpub::get_watched_xpub))
+            
+            // Internal: txids registered by sibling services (service token)
+            .route("/track/tx", web::post().to(tracking::track_transaction))
+            
+            // Real-time event stream
+            .route("/ws/events", web::get().to(events::event_stream))
+            
+            // Double-spend alerts
+            .route("/alerts/double-spends", web::get().to(double_spend::list_alerts))
+            
+            // Webhook subscriptions
+            .route("/webhooks", web::post().to(webhooks::register_webhook))
+            .route("/webhooks/{id}", web::get().to(webhooks::get_webhook))
+            .route("/webhooks/{id}", web::delete().to(webhooks::delete_webhook))
+            .route("/webhooks/{id}/deliveries", web::get().to(webhooks::list_deliveries))
+            .route("/webhooks/deliveries/{id}/retry", web::post().to(webhooks::retry_delivery))
             
             // Broadcast endpoint
             .route("/broadcast", web::post().to(broadcast_transaction))
+            .route("/broadcast/{txid}/status", web::get().to(arc::get_broadcast_status))
+            .route("/arc/callback", web::post().to(arc::arc_callback))
     })
     .bind("127.0.0.1:8084")?
     .run()
     .await
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_confirmation_status() {
+        assert_eq!(confirmation_status(0), "seen");
+        assert_eq!(confirmation_status(1), "confirmed");
+    }
+
+    #[test]
+    fn test_parses_both_history_shapes() {
+        let plain: WocHistory = serde_json::from_str(r#"[{"tx_hash":"aa"},{"tx_hash":"bb","height":0}]"#).unwrap();
+        assert_eq!(plain.into_txids(), vec!["aa", "bb"]);
+
+        let wrapped: WocHistory =
+            serde_json::from_str(r#"{"address":"m1","script":"76a9","result":[{"tx_hash":"cc"}],"error":""}"#).unwrap();
+        assert_eq!(wrapped.into_txids(), vec!["cc"]);
+    }
+
+    #[test]
+    fn test_parses_confirmed_history_page() {
+        let page: WocHistoryPage = serde_json::from_str(
+            r#"{"address":"m1","script":"76a9","result":[{"tx_hash":"aa","height":5}],"error":"","nextPageToken":"t1"}"#,
+        )
+        .unwrap();
+        assert_eq!(page.result.len(), 1);
+        assert_eq!(page.next_page_token.as_deref(), Some("t1"));
+
+        let last: WocHistoryPage = serde_json::from_str(r#"{"result":[],"error":""}"#).unwrap();
+        assert!(last.next_page_token.is_none());
+    }
+}
@@ -0,0 +1,113 @@
+// core/blockchain-monitor/src/metrics.rs
+// Prometheus metrics for the monitor's internals: provider calls, watch set, poll queue, confirmations
+
+use prometheus::{Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec, IntGauge, Opts, Registry};
+use std::time::Duration;
+
+/// Seconds from broadcast to six confirmations; blocks average ten minutes
+const CONFIRMATION_LATENCY_BUCKETS: [f64; 10] =
+    [600.0, 1_800.0, 3_600.0, 5_400.0, 7_200.0, 10_800.0, 14_400.0, 21_600.0, 43_200.0, 86_400.0];
+
+/// Confirmations at which the confirmation latency is measured
+pub const LATENCY_CONFIRMATIONS: i32 = 6;
+
+/// Requests sent to each chain data provider
+pub struct ProviderMetrics {
+    /// outcome: `ok`, `not_found`, `rate_limited` (HTTP 429) or `error`
+    requests: IntCounterVec,
+    duration: HistogramVec,
+}
+
+impl ProviderMetrics {
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let requests = IntCounterVec::new(
+            Opts::new("blockchain_monitor_provider_requests_total", "Requests sent to chain data providers"),
+            &["provider", "outcome"],
+        )?;
+        let duration = HistogramVec::new(
+            HistogramOpts::new(
+                "blockchain_monitor_provider_request_duration_seconds",
+                "Chain data provider response time",
+            )
+            .buckets(vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0, 30.0]),
+            &["provider"],
+        )?;
+        registry.register(Box::new(requests.clone()))?;
+        registry.register(Box::new(duration.clone()))?;
+        Ok(Self { requests, duration })
+    }
+
+    pub fn observe(&self, provider: &str, outcome: &str, elapsed: Duration) {
+        self.requests.with_label_values(&[provider, outcome]).inc();
+        self.duration.with_label_values(&[provider]).observe(elapsed.as_secs_f64());
+    }
+}
+
+pub struct MonitorMetrics {
+    pub watched_addresses: IntGauge,
+    pub watched_scripts: IntGauge,
+    /// Transactions the monitoring loop still polls, due or not
+    pub pending_transactions: IntGauge,
+    pub confirmation_latency: Histogram,
+    pub reorgs: IntCounter,
+}
+
+impl MonitorMetrics {
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let watched_addresses =
+            IntGauge::new("blockchain_monitor_watched_addresses", "Active watched addresses")?;
+        let watched_scripts =
+            IntGauge::new("blockchain_monitor_watched_scripts", "Active watched scripts without an address")?;
+        let pending_transactions = IntGauge::new(
+            "blockchain_monitor_pending_transactions",
+            "Transactions awaiting confirmation polls",
+        )?;
+        let confirmation_latency = Histogram::with_opts(
+            HistogramOpts::new(
+                "blockchain_monitor_confirmation_latency_seconds",
+                "Time from broadcast, or first sighting, to six confirmations",
+            )
+            .buckets(CONFIRMATION_LATENCY_BUCKETS.to_vec()),
+        )?;
+        let reorgs = IntCounter::new(
+            "blockchain_monitor_reorgs_total",
+            "Tracked transactions that lost confirmations or moved to another block",
+        )?;
+
+        registry.register(Box::new(watched_addresses.clone()))?;
+        registry.register(Box::new(watched_scripts.clone()))?;
+        registry.register(Box::new(pending_transactions.clone()))?;
+        registry.register(Box::new(confirmation_latency.clone()))?;
+        registry.register(Box::new(reorgs.clone()))?;
+        Ok(Self { watched_addresses, watched_scripts, pending_transactions, confirmation_latency, reorgs })
+    }
+}
+
+/// Whether an update from `old` to `new` confirmations is the one that reached six
+pub fn reached_latency_target(old: Option<i32>, new: i32) -> bool {
+    matches!(old, Some(old) if old < LATENCY_CONFIRMATIONS) && new >= LATENCY_CONFIRMATIONS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reached_latency_target() {
+        assert!(reached_latency_target(Some(0), 6));
+        assert!(reached_latency_target(Some(5), 7));
+        assert!(!reached_latency_target(Some(6), 7));
+        assert!(!reached_latency_target(Some(3), 5));
+        // First seen already deep: no broadcast time to measure from
+        assert!(!reached_latency_target(None, 6));
+    }
+
+    #[test]
+    fn test_register() {
+        let registry = Registry::new();
+        assert!(ProviderMetrics::new(&registry).is_ok());
+        assert!(MonitorMetrics::new(&registry).is_ok());
+        // Names are unique per registry
+        assert!(MonitorMetrics::new(&registry).is_err());
+    }
+}
@@ -0,0 +1,178 @@
+// core/blockchain-monitor/src/outbox.rs
+// Transactional outbox: monitor events are stored with the state change they describe,
+// then published to the message bus for the other services
+
+use actix_web::web;
+use bsv_bank_common::{EventEnvelope, EventPublisher};
+use chrono::{DateTime, Utc};
+use sqlx::PgExecutor;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::events::MonitorEvent;
+use crate::webhooks::retry_delay;
+use crate::{AppState, ServiceError};
+
+/// `source` of every envelope this service publishes
+const SOURCE: &str = "blockchain-monitor";
+/// Events claimed per worker pass
+const PUBLISH_BATCH: i64 = 100;
+/// How long claimed events are hidden from other workers
+const PUBLISH_LEASE_SECS: i64 = 60;
+const PUBLISH_POLL: Duration = Duration::from_secs(1);
+/// Published events are kept this long for replay and auditing
+const RETENTION_DAYS: i32 = 7;
+
+#[derive(sqlx::FromRow)]
+struct PendingEvent {
+    id: Uuid,
+    seq: i64,
+    event_type: String,
+    event_key: String,
+    payload: serde_json::Value,
+    created_at: DateTime<Utc>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Store `event` for publication. Pass the transaction that makes the state change so the
+/// event exists exactly when the change does. A no-op when no event bus is configured.
+pub async fn record<'e, E: PgExecutor<'e>>(
+    state: &AppState,
+    executor: E,
+    event: &MonitorEvent,
+) -> Result<(), ServiceError> {
+    if !state.config.event_outbox {
+        return Ok(());
+    }
+
+    let payload = serde_json::to_value(event).map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    sqlx::query("INSERT INTO monitor_event_outbox (event_type, event_key, payload) VALUES ($1, $2, $3)")
+        .bind(event.event_type())
+        .bind(event.txid())
+        .bind(payload)
+        .execute(executor)
+        .await
+        .map_err(db_error)?;
+
+    Ok(())
+}
+
+// ============================================================================
+// PUBLISHER WORKER
+// ============================================================================
+
+/// Publishes stored events in order. At-least-once: an event published just before a
+/// crash is sent again, with the same envelope id.
+pub async fn start_outbox_worker(state: web::Data<AppState>, publisher: Box<dyn EventPublisher>) {
+    tokio::spawn(async move {
+        loop {
+            let published = match publish_due(&state, publisher.as_ref()).await {
+                Ok(n) => n,
+                Err(e) => {
+                    tracing::error!("Outbox publishing failed: {}", e);
+                    0
+                }
+            };
+            if let Err(e) = purge_published(&state).await {
+                tracing::error!("Failed to purge published outbox events: {}", e);
+            }
+            // Keep draining while there is a backlog
+            if published < PUBLISH_BATCH as usize {
+                tokio::time::sleep(PUBLISH_POLL).await;
+            }
+        }
+    });
+}
+
+async fn publish_due(state: &AppState, publisher: &dyn EventPublisher) -> Result<usize, ServiceError> {
+    let mut due = sqlx::query_as::<_, PendingEvent>(
+        r#"
+        UPDATE monitor_event_outbox
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        WHERE id IN (
+            SELECT id FROM monitor_event_outbox
+            WHERE published_at IS NULL AND next_attempt_at <= NOW()
+            ORDER BY seq
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, seq, event_type, event_key, payload, created_at
+        "#
+    )
+    .bind(PUBLISH_BATCH)
+    .bind(PUBLISH_LEASE_SECS as f64)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+    due.sort_by_key(|event| event.seq);
+
+    let mut published = Vec::with_capacity(due.len());
+    let mut failure = None;
+    for event in &due {
+        let envelope = EventEnvelope {
+            id: event.id,
+            source: SOURCE.to_string(),
+            event_type: event.event_type.clone(),
+            key: event.event_key.clone(),
+            occurred_at: event.created_at,
+            payload: event.payload.clone(),
+        };
+        match publisher.publish(&envelope).await {
+            Ok(()) => published.push(event.id),
+            Err(e) => {
+                failure = Some((event.id, e.to_string()));
+                break;
+            }
+        }
+    }
+
+    sqlx::query("UPDATE monitor_event_outbox SET published_at = NOW(), last_error = NULL WHERE id = ANY($1)")
+        .bind(&published)
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    if let Some((failed_id, error)) = failure {
+        // The rest of the batch waits with the failed event so the bus sees them in order
+        let attempts: i32 = sqlx::query_scalar(
+            "UPDATE monitor_event_outbox SET attempts = attempts + 1, last_error = $2 WHERE id = $1 RETURNING attempts"
+        )
+        .bind(failed_id)
+        .bind(&error)
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_error)?;
+
+        let held: Vec<Uuid> = due.iter().map(|event| event.id).filter(|id| !published.contains(id)).collect();
+        sqlx::query(
+            "UPDATE monitor_event_outbox SET next_attempt_at = NOW() + make_interval(secs => $2) WHERE id = ANY($1)"
+        )
+        .bind(&held)
+        .bind(retry_delay(attempts - 1).as_secs_f64())
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+
+        tracing::warn!("Publishing event {} via {} failed (attempt {}): {}", failed_id, publisher.name(), attempts, error);
+    }
+
+    Ok(published.len())
+}
+
+async fn purge_published(state: &AppState) -> Result<(), ServiceError> {
+    sqlx::query(
+        r#"
+        DELETE FROM monitor_event_outbox
+        WHERE published_at IS NOT NULL AND published_at < NOW() - make_interval(days => $1)
+        "#
+    )
+    .bind(RETENTION_DAYS)
+    .execute(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
@@ -0,0 +1,196 @@
+// core/blockchain-monitor/src/policies.rs
+// Confirmation policies: per-purpose confirmation targets and the one-time finalized event
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::events::MonitorEvent;
+use crate::tracking::service_claims;
+use crate::{outbox, webhooks, AppState, ServiceError, Transaction};
+
+/// Target for transactions whose watched addresses have no policy, or that were
+/// matched by script rather than by address or registration
+pub const DEFAULT_TARGET: i32 = 6;
+const MAX_TARGET: i32 = 100;
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ConfirmationPolicy {
+    pub purpose: String,
+    pub target_confirmations: i32,
+    pub updated_at: DateTime<Utc>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// A target of zero would finalize transactions before any block includes them
+fn validate_target(target: i32) -> Result<(), ServiceError> {
+    if !(1..=MAX_TARGET).contains(&target) {
+        return Err(ServiceError::ValidationError(format!(
+            "target_confirmations must be between 1 and {}",
+            MAX_TARGET
+        )));
+    }
+    Ok(())
+}
+
+/// Strictest target among the purposes of the watched addresses a transaction touches
+/// and the purpose it was registered with through `/track/tx`
+async fn target_for(state: &AppState, txid: &str, addresses: &[String]) -> Result<i32, ServiceError> {
+    let target: Option<i32> = sqlx::query_scalar(
+        r#"
+        SELECT MAX(COALESCE(p.target_confirmations, $3))
+        FROM (
+            SELECT purpose FROM watched_addresses WHERE address = ANY($1)
+            UNION
+            SELECT purpose FROM tracked_transactions WHERE txid = $2
+        ) purposes
+        LEFT JOIN confirmation_policies p ON p.purpose = purposes.purpose
+        "#
+    )
+    .bind(addresses)
+    .bind(txid)
+    .bind(DEFAULT_TARGET)
+    .fetch_one(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(target.unwrap_or(DEFAULT_TARGET))
+}
+
+/// Record the transaction's target and, the first time it is met, mark it finalized and
+/// notify. Finalization is permanent: a later reorg is reported through the reorg event.
+pub async fn check_finalized(state: &AppState, tx: &Transaction, addresses: &[String]) -> Result<(), ServiceError> {
+    let target = target_for(state, &tx.txid, addresses).await?;
+    let mut db_tx = state.db.begin().await.map_err(db_error)?;
+
+    // No row back means the transaction was already finalized
+    let finalized_at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        r#"
+        UPDATE blockchain_transactions
+        SET target_confirmations = $2,
+            finalized_at = CASE WHEN confirmations >= $2 THEN NOW() END
+        WHERE txid = $1 AND finalized_at IS NULL
+        RETURNING finalized_at
+        "#
+    )
+    .bind(&tx.txid)
+    .bind(target)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .flatten();
+
+    let Some(finalized_at) = finalized_at else {
+        return db_tx.commit().await.map_err(db_error);
+    };
+
+    let event = MonitorEvent::Finalized {
+        txid: tx.txid.clone(),
+        addresses: addresses.to_vec(),
+        confirmations: tx.confirmations,
+        target_confirmations: target,
+        timestamp: finalized_at,
+    };
+    outbox::record(state, &mut *db_tx, &event).await?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("TX {} finalized at {} of {} confirmations", tx.txid, tx.confirmations, target);
+    state.events.publish(event);
+
+    webhooks::enqueue_event(state, webhooks::EVENT_TX_FINALIZED, &tx.txid, addresses, serde_json::json!({
+        "txid": tx.txid,
+        "confirmations": tx.confirmations,
+        "target_confirmations": target,
+        "block_hash": tx.block_hash,
+        "block_height": tx.block_height,
+        "amount_satoshis": tx.amount_satoshis,
+    }))
+    .await
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+pub async fn list_policies(data: web::Data<AppState>) -> Result<HttpResponse, ServiceError> {
+    let policies = sqlx::query_as::<_, ConfirmationPolicy>(
+        "SELECT purpose, target_confirmations, updated_at FROM confirmation_policies ORDER BY purpose"
+    )
+    .fetch_all(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "default_target_confirmations": DEFAULT_TARGET,
+        "policies": policies
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetPolicyRequest {
+    pub target_confirmations: i32,
+}
+
+/// `PUT /policies/{purpose}`: service or admin tokens only; applies to transactions not finalized yet
+pub async fn set_policy(
+    data: web::Data<AppState>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    purpose: web::Path<String>,
+    req: web::Json<SetPolicyRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = service_claims(&http_req, &jwt)?;
+    if purpose.trim().is_empty() || purpose.len() > 50 {
+        return Err(ServiceError::ValidationError("purpose must be 1-50 characters".to_string()));
+    }
+    validate_target(req.target_confirmations)?;
+
+    let policy = sqlx::query_as::<_, ConfirmationPolicy>(
+        r#"
+        INSERT INTO confirmation_policies (purpose, target_confirmations)
+        VALUES ($1, $2)
+        ON CONFLICT (purpose) DO UPDATE SET target_confirmations = $2, updated_at = NOW()
+        RETURNING purpose, target_confirmations, updated_at
+        "#
+    )
+    .bind(purpose.trim())
+    .bind(req.target_confirmations)
+    .fetch_one(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!("{} set the {} confirmation target to {}", claims.sub, policy.purpose, policy.target_confirmations);
+    Ok(HttpResponse::Ok().json(policy))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_target_needs_a_confirmation() {
+        assert!(validate_target(0).is_err());
+        assert!(validate_target(1).is_ok());
+        assert!(validate_target(MAX_TARGET).is_ok());
+        assert!(validate_target(MAX_TARGET + 1).is_err());
+    }
+
+    #[test]
+    fn test_only_service_or_admin_tokens_set_policies() {
+        let jwt = JwtManager::new("test-secret".to_string());
+        let bearer = |token: String| TestRequest::default().insert_header(("Authorization", format!("Bearer {}", token))).to_http_request();
+
+        assert!(matches!(service_claims(&TestRequest::default().to_http_request(), &jwt), Err(ServiceError::Unauthorized(_))));
+        let user = jwt.create_token("alice@example.com", vec!["read".to_string()], 1).unwrap();
+        assert!(matches!(service_claims(&bearer(user), &jwt), Err(ServiceError::Forbidden(_))));
+
+        assert!(service_claims(&bearer(jwt.create_service_token("deposit-service").unwrap()), &jwt).is_ok());
+        let admin = jwt.create_token("ops@example.com", vec!["admin".to_string()], 1).unwrap();
+        assert!(service_claims(&bearer(admin), &jwt).is_ok());
+    }
+}
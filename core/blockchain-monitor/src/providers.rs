@@ -0,0 +1,993 @@
+// core/blockchain-monitor/src/providers.rs
+// Chain data providers (WhatsOnChain, Bitails, GorillaPool ARC, local node) behind health-based failover
+
+use async_trait::async_trait;
+use prometheus::Registry;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::budget::{BudgetMetrics, TokenBucket};
+use crate::metrics::ProviderMetrics;
+use crate::{
+    Config, ServiceError, WocBalance, WocChainInfo, WocHistory, WocHistoryPage, WocInput, WocOutput, WocScript,
+    WocTransaction, WocUtxo,
+};
+
+/// Consecutive failures before a provider is taken out of rotation
+const FAILURE_THRESHOLD: u32 = 3;
+/// First cooldown once a provider is marked down; doubles per further failure
+const BASE_COOLDOWN: Duration = Duration::from_secs(30);
+const MAX_COOLDOWN: Duration = Duration::from_secs(300);
+
+// ============================================================================
+// PROVIDER TRAIT
+// ============================================================================
+
+#[derive(Debug)]
+pub enum ProviderError {
+    /// The provider answered but does not know the object
+    NotFound,
+    /// The provider has no equivalent of this call
+    Unsupported,
+    /// Transport, status or parse failure; counts against the provider's health
+    Failed(String),
+    /// HTTP 429: over the provider's rate limit; also counts against its health
+    RateLimited(String),
+}
+
+/// Spender of an outpoint, as reported by WoC `/tx/{txid}/{vout}/spent`
+#[derive(Debug, Deserialize)]
+pub struct WocSpent {
+    pub txid: String,
+    #[serde(default)]
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct HistoryPage {
+    pub txids: Vec<String>,
+    pub next_page: Option<String>,
+}
+
+/// One call of `ChainDataProvider`, for asking whether a provider serves it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Capability {
+    Transaction,
+    ChainInfo,
+    AddressUtxos,
+    AddressUnconfirmed,
+    AddressBalance,
+    AddressHistory,
+    ScriptUtxos,
+    ScriptUnconfirmed,
+    BlockHash,
+    RawBlock,
+    Spender,
+    Broadcast,
+}
+
+/// A source of chain data. Responses use the WhatsOnChain shapes the monitor was built
+/// around; other providers translate into them. Calls a provider cannot serve default
+/// to `Unsupported` and are routed elsewhere.
+#[async_trait]
+pub trait ChainDataProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Whether the provider overrides the call; the pool skips the rest without spending budget
+    fn supports(&self, capability: Capability) -> bool;
+
+    async fn get_transaction(&self, _txid: &str) -> Result<WocTransaction, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    async fn get_chain_info(&self) -> Result<WocChainInfo, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    async fn get_address_utxos(&self, _address: &str) -> Result<Vec<WocUtxo>, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    async fn get_address_unconfirmed(&self, _address: &str) -> Result<Vec<String>, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    async fn get_address_balance(&self, _address: &str) -> Result<WocBalance, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    /// One page of the address's confirmed history, oldest first. Page tokens are
+    /// provider-specific, so a backfill must stay with the provider that issued them.
+    async fn get_address_history(&self, _address: &str, _page: Option<&str>) -> Result<HistoryPage, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    /// UTXOs locked by the script with this (byte-reversed SHA-256) hash
+    async fn get_script_utxos(&self, _script_hash: &str) -> Result<Vec<WocUtxo>, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    async fn get_script_unconfirmed(&self, _script_hash: &str) -> Result<Vec<String>, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    async fn get_block_hash(&self, _height: i32) -> Result<String, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    /// Serialized block as hex
+    async fn get_raw_block(&self, _hash: &str) -> Result<String, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    /// Who spent `txid:vout`; None while it is unspent
+    async fn get_spender(&self, _txid: &str, _vout: u32) -> Result<Option<WocSpent>, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+
+    /// Broadcast a raw transaction, returning its txid
+    async fn broadcast(&self, _tx_hex: &str) -> Result<String, ProviderError> {
+        Err(ProviderError::Unsupported)
+    }
+}
+
+fn http_client() -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .build()
+        .unwrap_or_default()
+}
+
+async fn send(request: reqwest::RequestBuilder) -> Result<reqwest::Response, ProviderError> {
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ProviderError::Failed(format!("Request failed: {}", e)))?;
+
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        reqwest::StatusCode::NOT_FOUND => Err(ProviderError::NotFound),
+        reqwest::StatusCode::TOO_MANY_REQUESTS => {
+            let body = response.text().await.unwrap_or_default();
+            Err(ProviderError::RateLimited(format!("Status: 429 Too Many Requests - {}", body.trim())))
+        }
+        status => {
+            let body = response.text().await.unwrap_or_default();
+            Err(ProviderError::Failed(format!("Status: {} - {}", status, body.trim())))
+        }
+    }
+}
+
+async fn parse<T: for<'de> Deserialize<'de>>(response: reqwest::Response) -> Result<T, ProviderError> {
+    response
+        .json::<T>()
+        .await
+        .map_err(|e| ProviderError::Failed(format!("Parse error: {}", e)))
+}
+
+// ============================================================================
+// WHATSONCHAIN
+// ============================================================================
+
+pub struct WhatsOnChain {
+    client: reqwest::Client,
+    base: String,
+}
+
+impl WhatsOnChain {
+    pub fn new(base: String) -> Self {
+        Self { client: http_client(), base }
+    }
+}
+
+#[async_trait]
+impl ChainDataProvider for WhatsOnChain {
+    fn name(&self) -> &'static str {
+        "whatsonchain"
+    }
+
+    fn supports(&self, capability: Capability) -> bool {
+        capability != Capability::RawBlock
+    }
+
+    async fn get_transaction(&self, txid: &str) -> Result<WocTransaction, ProviderError> {
+        parse(send(self.client.get(format!("{}/tx/{}", self.base, txid))).await?).await
+    }
+
+    async fn get_chain_info(&self) -> Result<WocChainInfo, ProviderError> {
+        parse(send(self.client.get(format!("{}/chain/info", self.base))).await?).await
+    }
+
+    async fn get_address_utxos(&self, address: &str) -> Result<Vec<WocUtxo>, ProviderError> {
+        match send(self.client.get(format!("{}/address/{}/unspent", self.base, address))).await {
+            Ok(response) => parse(response).await,
+            // Address has no UTXOs
+            Err(ProviderError::NotFound) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get_address_unconfirmed(&self, address: &str) -> Result<Vec<String>, ProviderError> {
+        match send(self.client.get(format!("{}/address/{}/unconfirmed/history", self.base, address))).await {
+            Ok(response) => parse::<WocHistory>(response).await.map(WocHistory::into_txids),
+            // Nothing in the mempool for this address
+            Err(ProviderError::NotFound) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get_address_balance(&self, address: &str) -> Result<WocBalance, ProviderError> {
+        let response = send(self.client.get(format!("{}/address/{}/balance", self.base, address))).await?;
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ProviderError::Failed(e.to_string()))?;
+
+        if text.trim().is_empty() {
+            tracing::warn!("Empty response from WoC for address: {}", address);
+            return Ok(WocBalance { confirmed: 0, unconfirmed: 0 });
+        }
+        serde_json::from_str(&text).map_err(|e| ProviderError::Failed(format!("Parse error: {}", e)))
+    }
+
+    async fn get_address_history(&self, address: &str, page: Option<&str>) -> Result<HistoryPage, ProviderError> {
+        let mut request = self.client
+            .get(format!("{}/address/{}/confirmed/history", self.base, address))
+            .query(&[("order", "asc")]);
+        if let Some(token) = page {
+            request = request.query(&[("token", token)]);
+        }
+        match send(request).await {
+            Ok(response) => {
+                let page: WocHistoryPage = parse(response).await?;
+                Ok(HistoryPage {
+                    txids: page.result.into_iter().map(|e| e.tx_hash).collect(),
+                    next_page: page.next_page_token.filter(|t| !t.is_empty()),
+                })
+            }
+            // Never used
+            Err(ProviderError::NotFound) => Ok(HistoryPage::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get_script_utxos(&self, script_hash: &str) -> Result<Vec<WocUtxo>, ProviderError> {
+        match send(self.client.get(format!("{}/script/{}/unspent", self.base, script_hash))).await {
+            Ok(response) => parse(response).await,
+            Err(ProviderError::NotFound) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get_script_unconfirmed(&self, script_hash: &str) -> Result<Vec<String>, ProviderError> {
+        match send(self.client.get(format!("{}/script/{}/unconfirmed/history", self.base, script_hash))).await {
+            Ok(response) => parse::<WocHistory>(response).await.map(WocHistory::into_txids),
+            Err(ProviderError::NotFound) => Ok(vec![]),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn get_block_hash(&self, height: i32) -> Result<String, ProviderError> {
+        let block: Value = parse(send(self.client.get(format!("{}/block/height/{}", self.base, height))).await?).await?;
+        block.get("hash")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| ProviderError::Failed("Block response has no hash".to_string()))
+    }
+
+    async fn get_spender(&self, txid: &str, vout: u32) -> Result<Option<WocSpent>, ProviderError> {
+        match send(self.client.get(format!("{}/tx/{}/{}/spent", self.base, txid, vout))).await {
+            Ok(response) => parse(response).await.map(Some),
+            // Unspent
+            Err(ProviderError::NotFound) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    async fn broadcast(&self, tx_hex: &str) -> Result<String, ProviderError> {
+        let response = send(self.client.post(format!("{}/tx/raw", self.base)).json(&json!({ "txhex": tx_hex }))).await?;
+        let text = response
+            .text()
+            .await
+            .map_err(|e| ProviderError::Failed(e.to_string()))?;
+        // WoC answers with the txid as a JSON string
+        Ok(text.trim().trim_matches('"').to_string())
+    }
+}
+
+// ============================================================================
+// BITAILS
+// ============================================================================
+
+pub struct Bitails {
+    client: reqwest::Client,
+    base: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitailsTx {
+    txid: String,
+    #[serde(default)]
+    confirmations: Option<i32>,
+    #[serde(default, rename = "blockhash")]
+    block_hash: Option<String>,
+    #[serde(default, rename = "blockheight")]
+    block_height: Option<i32>,
+    #[serde(default)]
+    time: Option<i64>,
+    #[serde(default)]
+    inputs: Vec<BitailsInput>,
+    #[serde(default)]
+    outputs: Vec<BitailsOutput>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitailsInput {
+    #[serde(default)]
+    source: Option<BitailsSource>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitailsSource {
+    txid: String,
+    index: u32,
+    #[serde(default)]
+    address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitailsOutput {
+    index: u32,
+    satoshis: i64,
+    #[serde(default)]
+    address: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitailsUnspent {
+    #[serde(default)]
+    unspent: Vec<BitailsUtxo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BitailsUtxo {
+    txid: String,
+    vout: u32,
+    satoshis: i64,
+    #[serde(default)]
+    blockheight: Option<i32>,
+}
+
+impl Bitails {
+    pub fn new(base: String) -> Self {
+        Self { client: http_client(), base }
+    }
+}
+
+impl From<BitailsTx> for WocTransaction {
+    fn from(tx: BitailsTx) -> Self {
+        let script = |address: Option<String>| Some(WocScript { addresses: Some(address.into_iter().collect()) });
+        WocTransaction {
+            txid: tx.txid,
+            confirmations: tx.confirmations,
+            blockhash: tx.block_hash,
+            blockheight: tx.block_height,
+            blocktime: tx.time,
+            inputs: Some(tx.inputs.into_iter().filter_map(|i| i.source).map(|s| WocInput {
+                txid: Some(s.txid),
+                vout: Some(s.index),
+                script_sig: script(s.address),
+            }).collect()),
+            outputs: Some(tx.outputs.into_iter().map(|o| WocOutput {
+                value: Some(o.satoshis as f64 / 100_000_000.0),
+                n: Some(o.index),
+                script_pub_key: script(o.address),
+            }).collect()),
+            size: None,
+            hex: None,
+        }
+    }
+}
+
+#[async_trait]
+impl ChainDataProvider for Bitails {
+    fn name(&self) -> &'static str {
+        "bitails"
+    }
+
+    fn supports(&self, capability: Capability) -> bool {
+        matches!(
+            capability,
+            Capability::Transaction | Capability::ChainInfo | Capability::AddressUtxos
+                | Capability::AddressBalance | Capability::Broadcast
+        )
+    }
+
+    async fn get_transaction(&self, txid: &str) -> Result<WocTransaction, ProviderError> {
+        let tx: BitailsTx = parse(send(self.client.get(format!("{}/tx/{}", self.base, txid))).await?).await?;
+        Ok(tx.into())
+    }
+
+    async fn get_chain_info(&self) -> Result<WocChainInfo, ProviderError> {
+        parse(send(self.client.get(format!("{}/network/info", self.base))).await?).await
+    }
+
+    async fn get_address_utxos(&self, address: &str) -> Result<Vec<WocUtxo>, ProviderError> {
+        let unspent: BitailsUnspent = match send(self.client.get(format!("{}/address/{}/unspent", self.base, address))).await {
+            Ok(response) => parse(response).await?,
+            Err(ProviderError::NotFound) => return Ok(vec![]),
+            Err(e) => return Err(e),
+        };
+        Ok(unspent
+            .unspent
+            .into_iter()
+            .map(|u| WocUtxo { tx_hash: u.txid, tx_pos: u.vout, value: u.satoshis, height: u.blockheight })
+            .collect())
+    }
+
+    async fn get_address_balance(&self, address: &str) -> Result<WocBalance, ProviderError> {
+        parse(send(self.client.get(format!("{}/address/{}/balance", self.base, address))).await?).await
+    }
+
+    async fn broadcast(&self, tx_hex: &str) -> Result<String, ProviderError> {
+        let body: Value = parse(send(self.client.post(format!("{}/tx/broadcast", self.base)).json(&json!({ "raw": tx_hex }))).await?).await?;
+        body.get("txid")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+            .ok_or_else(|| ProviderError::Failed("Broadcast response has no txid".to_string()))
+    }
+}
+
+// ============================================================================
+// GORILLAPOOL (ARC)
+// ============================================================================
+
+/// GorillaPool's ARC endpoint accepts transactions straight into its miner, but only
+/// reports status by txid, so it serves broadcasts only.
+pub struct GorillaPool {
+    client: reqwest::Client,
+    base: String,
+    api_key: Option<String>,
+}
+
+impl GorillaPool {
+    pub fn new(base: String, api_key: Option<String>) -> Self {
+        Self { client: http_client(), base, api_key }
+    }
+}
+
+#[async_trait]
+impl ChainDataProvider for GorillaPool {
+    fn name(&self) -> &'static str {
+        "gorillapool"
+    }
+
+    fn supports(&self, capability: Capability) -> bool {
+        capability == Capability::Broadcast
+    }
+
+    async fn broadcast(&self, tx_hex: &str) -> Result<String, ProviderError> {
+        let mut request = self.client.post(format!("{}/v1/tx", self.base)).json(&json!({ "rawTx": tx_hex }));
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let body: Value = parse(send(request).await?).await?;
+
+        match body.get("txStatus").and_then(Value::as_str) {
+            Some("REJECTED") | Some("DOUBLE_SPEND_ATTEMPTED") => Err(ProviderError::Failed(
+                body.get("extraInfo").and_then(Value::as_str).unwrap_or("Rejected by ARC").to_string(),
+            )),
+            _ => body
+                .get("txid")
+                .and_then(Value::as_str)
+                .map(str::to_string)
+                .ok_or_else(|| ProviderError::Failed("ARC response has no txid".to_string())),
+        }
+    }
+}
+
+// ============================================================================
+// LOCAL NODE
+// ============================================================================
+
+/// bsv-node JSON-RPC. Arbitrary transactions need `-txindex`; the node keeps no address index.
+pub struct Node {
+    client: reqwest::Client,
+    url: String,
+    user: String,
+    password: String,
+}
+
+impl Node {
+    pub fn new(url: String, user: String, password: String) -> Self {
+        Self { client: http_client(), url, user, password }
+    }
+
+    async fn call<T: for<'de> Deserialize<'de>>(&self, method: &str, params: Value) -> Result<T, ProviderError> {
+        let response = self.client
+            .post(&self.url)
+            .basic_auth(&self.user, Some(&self.password))
+            .json(&json!({ "jsonrpc": "1.0", "id": "blockchain-monitor", "method": method, "params": params }))
+            .send()
+            .await
+            .map_err(|e| ProviderError::Failed(format!("Request failed: {}", e)))?;
+        // bitcoind answers RPC errors with a 500 and a JSON body
+        let body: Value = response.json().await.map_err(|e| ProviderError::Failed(format!("Parse error: {}", e)))?;
+
+        if let Some(error) = body.get("error").filter(|e| !e.is_null()) {
+            // -5: no such transaction / block
+            if error.get("code").and_then(Value::as_i64) == Some(-5) {
+                return Err(ProviderError::NotFound);
+            }
+            return Err(ProviderError::Failed(error.get("message").and_then(Value::as_str).unwrap_or("RPC error").to_string()));
+        }
+        serde_json::from_value(body.get("result").cloned().unwrap_or(Value::Null))
+            .map_err(|e| ProviderError::Failed(format!("Parse error: {}", e)))
+    }
+}
+
+#[async_trait]
+impl ChainDataProvider for Node {
+    fn name(&self) -> &'static str {
+        "node"
+    }
+
+    fn supports(&self, capability: Capability) -> bool {
+        matches!(
+            capability,
+            Capability::Transaction | Capability::ChainInfo | Capability::BlockHash
+                | Capability::RawBlock | Capability::Broadcast
+        )
+    }
+
+    async fn get_transaction(&self, txid: &str) -> Result<WocTransaction, ProviderError> {
+        // Verbose getrawtransaction is the format WoC mirrors
+        self.call("getrawtransaction", json!([txid, true])).await
+    }
+
+    async fn get_chain_info(&self) -> Result<WocChainInfo, ProviderError> {
+        self.call("getblockchaininfo", json!([])).await
+    }
+
+    async fn get_block_hash(&self, height: i32) -> Result<String, ProviderError> {
+        self.call("getblockhash", json!([height])).await
+    }
+
+    async fn get_raw_block(&self, hash: &str) -> Result<String, ProviderError> {
+        // Verbosity 0: the serialized block
+        self.call("getblock", json!([hash, 0])).await
+    }
+
+    async fn broadcast(&self, tx_hex: &str) -> Result<String, ProviderError> {
+        self.call("sendrawtransaction", json!([tx_hex])).await
+    }
+}
+
+// ============================================================================
+// FAILOVER POOL
+// ============================================================================
+
+#[derive(Debug, Default)]
+pub struct Health {
+    consecutive_failures: u32,
+    down_until: Option<Instant>,
+    last_error: Option<String>,
+    successes: u64,
+    failures: u64,
+}
+
+impl Health {
+    pub fn is_up(&self, now: Instant) -> bool {
+        self.down_until.is_none_or(|until| now >= until)
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.down_until = None;
+        self.successes += 1;
+    }
+
+    pub fn record_failure(&mut self, now: Instant, error: String) {
+        self.consecutive_failures += 1;
+        self.failures += 1;
+        self.last_error = Some(error);
+        if self.consecutive_failures >= FAILURE_THRESHOLD {
+            self.down_until = Some(now + cooldown(self.consecutive_failures));
+        }
+    }
+}
+
+/// Time out of rotation after `failures` consecutive failures
+pub fn cooldown(failures: u32) -> Duration {
+    let doublings = failures.saturating_sub(FAILURE_THRESHOLD).min(8);
+    (BASE_COOLDOWN * 2u32.pow(doublings)).min(MAX_COOLDOWN)
+}
+
+struct Slot {
+    provider: Box<dyn ChainDataProvider>,
+    health: Mutex<Health>,
+    budget: TokenBucket,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ProviderStatus {
+    pub name: &'static str,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+    pub down_for_secs: Option<u64>,
+    pub last_error: Option<String>,
+    pub successes: u64,
+    pub failures: u64,
+    pub budget_remaining: f64,
+}
+
+/// Outcome of trying each provider in turn
+#[derive(Default)]
+struct Attempts {
+    not_found: bool,
+    errors: Vec<String>,
+}
+
+impl Attempts {
+    fn into_error(self, operation: &str) -> ServiceError {
+        if self.errors.is_empty() && self.not_found {
+            ServiceError::NotFoundError(operation.to_string())
+        } else if self.errors.is_empty() {
+            ServiceError::ApiError(format!("No chain data provider available for {}", operation))
+        } else {
+            ServiceError::ApiError(self.errors.join("; "))
+        }
+    }
+}
+
+/// Try each provider in configured order, healthy ones first, skipping any that cannot serve
+/// the call (or is not the one `only` names) and any whose budget is spent
+macro_rules! failover {
+    ($pool:expr, $operation:expr, $capability:expr, |$provider:ident| $call:expr) => {
+        failover!($pool, $operation, $capability, None, |$provider| $call)
+    };
+    ($pool:expr, $operation:expr, $capability:expr, $only:expr, |$provider:ident| $call:expr) => {{
+        let mut attempts = Attempts::default();
+        for slot in $pool.candidates() {
+            if !$pool.admit(slot, $capability, $only, &mut attempts) {
+                continue;
+            }
+            let $provider = slot.provider.as_ref();
+            let started = Instant::now();
+            let result = $call.await;
+            if let Some(value) = $pool.settle(slot, result, started.elapsed(), &mut attempts) {
+                return Ok(value);
+            }
+        }
+        Err(attempts.into_error(&$operation))
+    }};
+}
+
+pub struct ProviderPool {
+    slots: Vec<Slot>,
+    metrics: BudgetMetrics,
+    requests: ProviderMetrics,
+}
+
+impl ProviderPool {
+    /// Build the providers named in `CHAIN_PROVIDERS` (in priority order)
+    pub fn from_config(config: &Config, registry: &Registry) -> Result<Self, prometheus::Error> {
+        let mainnet = config.network == "mainnet";
+        let env = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let mut pool = ProviderPool {
+            slots: Vec::new(),
+            metrics: BudgetMetrics::new(registry)?,
+            requests: ProviderMetrics::new(registry)?,
+        };
+
+        for name in config.chain_providers.iter().map(String::as_str) {
+            let provider: Box<dyn ChainDataProvider> = match name {
+                "whatsonchain" => Box::new(WhatsOnChain::new(config.woc_api_base.clone())),
+                "bitails" => Box::new(Bitails::new(env("BITAILS_API_BASE").unwrap_or_else(|| {
+                    if mainnet { "https://api.bitails.io" } else { "https://test-api.bitails.io" }.to_string()
+                }))),
+                "gorillapool" => Box::new(GorillaPool::new(
+                    env("GORILLAPOOL_ARC_URL").unwrap_or_else(|| "https://arc.gorillapool.io".to_string()),
+                    env("GORILLAPOOL_API_KEY"),
+                )),
+                "node" => match env("BSV_NODE_RPC_URL") {
+                    Some(url) => Box::new(Node::new(
+                        url,
+                        env("BSV_NODE_RPC_USER").unwrap_or_default(),
+                        env("BSV_NODE_RPC_PASSWORD").unwrap_or_default(),
+                    )),
+                    None => {
+                        tracing::warn!("Chain provider 'node' listed but BSV_NODE_RPC_URL is not set");
+                        continue;
+                    }
+                },
+                other => {
+                    tracing::warn!("Unknown chain provider '{}' ignored", other);
+                    continue;
+                }
+            };
+
+            // <NAME>_RATE_LIMIT requests per second, in bursts of up to <NAME>_BURST;
+            // WoC's free tier allows 3 per second
+            let per_second: u32 = env(&format!("{}_RATE_LIMIT", name.to_uppercase()))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(if name == "whatsonchain" { 3 } else { 10 });
+            let burst = env(&format!("{}_BURST", name.to_uppercase()))
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(per_second);
+            pool.add(provider, burst, per_second);
+        }
+
+        if pool.slots.is_empty() {
+            tracing::warn!("No chain providers configured; falling back to WhatsOnChain");
+            pool.add(Box::new(WhatsOnChain::new(config.woc_api_base.clone())), 3, 3);
+        }
+        Ok(pool)
+    }
+
+    fn add(&mut self, provider: Box<dyn ChainDataProvider>, burst: u32, per_second: u32) {
+        let budget = TokenBucket::new(burst, per_second as f64, Instant::now());
+        self.slots.push(Slot { provider, health: Mutex::new(Health::default()), budget });
+    }
+
+    /// Healthy providers in priority order, then the ones cooling down as a last resort
+    fn candidates(&self) -> Vec<&Slot> {
+        let now = Instant::now();
+        let (up, down): (Vec<&Slot>, Vec<&Slot>) = self
+            .slots
+            .iter()
+            .partition(|slot| slot.health.lock().unwrap().is_up(now));
+        up.into_iter().chain(down).collect()
+    }
+
+    fn admit(&self, slot: &Slot, capability: Capability, only: Option<&str>, attempts: &mut Attempts) -> bool {
+        let now = Instant::now();
+        let name = slot.provider.name();
+        if !slot.provider.supports(capability) || only.is_some_and(|only| only != name) {
+            return false;
+        }
+        let admitted = slot.budget.try_take(now);
+        if !admitted {
+            self.metrics.exhausted.with_label_values(&[name]).inc();
+            attempts.errors.push(format!("{}: request budget exhausted", name));
+        }
+        self.metrics.remaining.with_label_values(&[name]).set(slot.budget.remaining(now));
+        admitted
+    }
+
+    /// Refresh the remaining-budget gauges, which otherwise only move when a request is sent
+    pub fn update_budget_metrics(&self) {
+        let now = Instant::now();
+        for slot in &self.slots {
+            self.metrics.remaining.with_label_values(&[slot.provider.name()]).set(slot.budget.remaining(now));
+        }
+    }
+
+    /// Whether a healthy provider has budget to spare for background polling; the
+    /// monitoring loop stops a pass early rather than starve API requests
+    pub fn has_background_budget(&self) -> bool {
+        let now = Instant::now();
+        self.slots.iter().any(|slot| slot.health.lock().unwrap().is_up(now) && slot.budget.above_reserve(now))
+    }
+
+    fn settle<T>(
+        &self,
+        slot: &Slot,
+        result: Result<T, ProviderError>,
+        elapsed: Duration,
+        attempts: &mut Attempts,
+    ) -> Option<T> {
+        let outcome = match &result {
+            Ok(_) => Some("ok"),
+            Err(ProviderError::NotFound) => Some("not_found"),
+            // Answered locally; no request was sent
+            Err(ProviderError::Unsupported) => None,
+            Err(ProviderError::RateLimited(_)) => Some("rate_limited"),
+            Err(ProviderError::Failed(_)) => Some("error"),
+        };
+        if let Some(outcome) = outcome {
+            self.requests.observe(slot.provider.name(), outcome, elapsed);
+        }
+
+        let mut health = slot.health.lock().unwrap();
+        match result {
+            Ok(value) => {
+                health.record_success();
+                Some(value)
+            }
+            Err(ProviderError::NotFound) => {
+                // A clean "not found" is a healthy answer; another provider may still know it
+                health.record_success();
+                attempts.not_found = true;
+                None
+            }
+            Err(ProviderError::Unsupported) => None,
+            Err(ProviderError::Failed(error)) | Err(ProviderError::RateLimited(error)) => {
+                tracing::warn!("Chain provider {} failed: {}", slot.provider.name(), error);
+                attempts.errors.push(format!("{}: {}", slot.provider.name(), error));
+                health.record_failure(Instant::now(), error);
+                None
+            }
+        }
+    }
+
+    pub fn status(&self) -> Vec<ProviderStatus> {
+        let now = Instant::now();
+        self.slots
+            .iter()
+            .map(|slot| {
+                let health = slot.health.lock().unwrap();
+                ProviderStatus {
+                    name: slot.provider.name(),
+                    healthy: health.is_up(now),
+                    consecutive_failures: health.consecutive_failures,
+                    down_for_secs: health.down_until.filter(|until| *until > now).map(|until| (until - now).as_secs()),
+                    last_error: health.last_error.clone(),
+                    successes: health.successes,
+                    failures: health.failures,
+                    budget_remaining: slot.budget.remaining(now),
+                }
+            })
+            .collect()
+    }
+
+    pub async fn get_transaction(&self, txid: &str) -> Result<WocTransaction, ServiceError> {
+        failover!(self, format!("Transaction {}", txid), Capability::Transaction, |p| p.get_transaction(txid))
+    }
+
+    pub async fn get_chain_info(&self) -> Result<WocChainInfo, ServiceError> {
+        failover!(self, "chain info", Capability::ChainInfo, |p| p.get_chain_info())
+    }
+
+    pub async fn get_address_utxos(&self, address: &str) -> Result<Vec<WocUtxo>, ServiceError> {
+        failover!(self, format!("UTXOs for {}", address), Capability::AddressUtxos, |p| p.get_address_utxos(address))
+    }
+
+    pub async fn get_address_unconfirmed(&self, address: &str) -> Result<Vec<String>, ServiceError> {
+        failover!(self, format!("Unconfirmed history for {}", address), Capability::AddressUnconfirmed, |p| p.get_address_unconfirmed(address))
+    }
+
+    pub async fn get_address_balance(&self, address: &str) -> Result<WocBalance, ServiceError> {
+        failover!(self, format!("Balance for {}", address), Capability::AddressBalance, |p| p.get_address_balance(address))
+    }
+
+    /// History pages are only requested from the first healthy provider that supports
+    /// them; its page tokens mean nothing to the others.
+    pub async fn get_address_history(
+        &self,
+        provider: Option<&str>,
+        address: &str,
+        page: Option<&str>,
+    ) -> Result<(&'static str, HistoryPage), ServiceError> {
+        failover!(self, format!("History for {}", address), Capability::AddressHistory, provider, |p| {
+            async move { p.get_address_history(address, page).await.map(|page| (p.name(), page)) }
+        })
+    }
+
+    pub async fn get_spender(&self, txid: &str, vout: u32) -> Result<Option<WocSpent>, ServiceError> {
+        failover!(self, format!("Spender of {}:{}", txid, vout), Capability::Spender, |p| p.get_spender(txid, vout))
+    }
+
+    pub async fn get_script_utxos(&self, script_hash: &str) -> Result<Vec<WocUtxo>, ServiceError> {
+        failover!(self, format!("UTXOs for script {}", script_hash), Capability::ScriptUtxos, |p| p.get_script_utxos(script_hash))
+    }
+
+    pub async fn get_script_unconfirmed(&self, script_hash: &str) -> Result<Vec<String>, ServiceError> {
+        failover!(self, format!("Unconfirmed history for script {}", script_hash), Capability::ScriptUnconfirmed, |p| p.get_script_unconfirmed(script_hash))
+    }
+
+    pub async fn get_block_hash(&self, height: i32) -> Result<String, ServiceError> {
+        failover!(self, format!("Block at height {}", height), Capability::BlockHash, |p| p.get_block_hash(height))
+    }
+
+    pub async fn get_raw_block(&self, hash: &str) -> Result<String, ServiceError> {
+        failover!(self, format!("Block {}", hash), Capability::RawBlock, |p| p.get_raw_block(hash))
+    }
+
+    pub async fn broadcast(&self, tx_hex: &str) -> Result<String, ServiceError> {
+        failover!(self, "broadcast", Capability::Broadcast, |p| p.broadcast(tx_hex))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_provider_goes_down_after_threshold_and_recovers() {
+        let now = Instant::now();
+        let mut health = Health::default();
+
+        health.record_failure(now, "timeout".to_string());
+        health.record_failure(now, "timeout".to_string());
+        assert!(health.is_up(now));
+
+        health.record_failure(now, "timeout".to_string());
+        assert!(!health.is_up(now));
+        assert!(health.is_up(now + BASE_COOLDOWN));
+
+        health.record_success();
+        assert!(health.is_up(now));
+        assert_eq!(health.consecutive_failures, 0);
+        assert_eq!((health.successes, health.failures), (1, 3));
+    }
+
+    #[test]
+    fn test_cooldown_doubles_to_cap() {
+        assert_eq!(cooldown(3), Duration::from_secs(30));
+        assert_eq!(cooldown(4), Duration::from_secs(60));
+        assert_eq!(cooldown(5), Duration::from_secs(120));
+        assert_eq!(cooldown(7), MAX_COOLDOWN);
+        assert_eq!(cooldown(50), MAX_COOLDOWN);
+    }
+
+    #[test]
+    fn test_bitails_transaction_maps_to_woc_shape() {
+        let tx: BitailsTx = serde_json::from_value(json!({
+            "txid": "ab",
+            "confirmations": 2,
+            "blockhash": "00ff",
+            "blockheight": 100,
+            "inputs": [{ "source": { "txid": "cd", "index": 1, "address": "mSender" } }, {}],
+            "outputs": [{ "index": 0, "satoshis": 150_000_000, "address": "mReceiver" }]
+        }))
+        .unwrap();
+
+        let woc: WocTransaction = tx.into();
+        assert_eq!(woc.confirmations, Some(2));
+        assert_eq!(woc.blockheight, Some(100));
+        let inputs = woc.inputs.unwrap();
+        assert_eq!(inputs.len(), 1);
+        assert_eq!(inputs[0].vout, Some(1));
+        let outputs = woc.outputs.unwrap();
+        assert_eq!(outputs[0].value, Some(1.5));
+        assert_eq!(
+            outputs[0].script_pub_key.as_ref().and_then(|s| s.addresses.clone()),
+            Some(vec!["mReceiver".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_unsupported_calls_leave_budget_alone() {
+        let registry = Registry::new();
+        let mut pool = ProviderPool {
+            slots: Vec::new(),
+            metrics: BudgetMetrics::new(&registry).unwrap(),
+            requests: ProviderMetrics::new(&registry).unwrap(),
+        };
+        pool.add(Box::new(GorillaPool::new("http://127.0.0.1:9".to_string(), None)), 1, 1);
+        pool.add(Box::new(Node::new("http://127.0.0.1:9".to_string(), String::new(), String::new())), 1, 1);
+
+        let now = Instant::now();
+        let mut attempts = Attempts::default();
+        assert!(!pool.admit(&pool.slots[0], Capability::ChainInfo, None, &mut attempts));
+        assert!(!pool.admit(&pool.slots[1], Capability::ChainInfo, Some("gorillapool"), &mut attempts));
+        assert!(attempts.errors.is_empty());
+        assert_eq!(pool.slots[0].budget.remaining(now), 1.0);
+        assert_eq!(pool.slots[1].budget.remaining(now), 1.0);
+
+        assert!(pool.admit(&pool.slots[1], Capability::ChainInfo, None, &mut attempts));
+        assert!(!pool.admit(&pool.slots[1], Capability::ChainInfo, None, &mut attempts));
+        assert_eq!(attempts.errors, vec!["node: request budget exhausted".to_string()]);
+    }
+
+    #[test]
+    fn test_parses_spent_response() {
+        let spent: WocSpent = serde_json::from_str(r#"{"txid":"cc","vin":0,"status":"confirmed"}"#).unwrap();
+        assert_eq!(spent.txid, "cc");
+        assert_eq!(spent.status.as_deref(), Some("confirmed"));
+    }
+
+    #[test]
+    fn test_attempts_error_mapping() {
+        let not_found = Attempts { not_found: true, errors: vec![] };
+        assert!(matches!(not_found.into_error("tx"), ServiceError::NotFoundError(_)));
+
+        let failed = Attempts { not_found: true, errors: vec!["whatsonchain: Status: 503".to_string()] };
+        assert!(matches!(failed.into_error("tx"), ServiceError::ApiError(m) if m.contains("503")));
+
+        assert!(matches!(Attempts::default().into_error("tx"), ServiceError::ApiError(_)));
+    }
+}
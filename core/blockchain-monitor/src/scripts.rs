@@ -0,0 +1,147 @@
+// core/blockchain-monitor/src/scripts.rs
+// Watched locking scripts: outputs that have no base58 address, tracked by script hash
+
+use actix_web::{web, HttpResponse, Result};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::Row;
+
+use crate::{record_new_transactions, AppState, ServiceError};
+
+/// Standard relay policy caps locking scripts well below this
+const MAX_SCRIPT_BYTES: usize = 10_000;
+
+/// Byte-reversed SHA-256 of the script, as WhatsOnChain and Electrum-style indexers key it
+pub fn script_hash(script: &[u8]) -> String {
+    let mut hash = Sha256::digest(script).to_vec();
+    hash.reverse();
+    hex::encode(hash)
+}
+
+pub fn parse_script(script_hex: &str) -> Result<Vec<u8>, String> {
+    let script = hex::decode(script_hex.trim()).map_err(|e| format!("locking_script is not hex: {}", e))?;
+    if script.is_empty() {
+        return Err("locking_script is empty".to_string());
+    }
+    if script.len() > MAX_SCRIPT_BYTES {
+        return Err(format!("locking_script exceeds {} bytes", MAX_SCRIPT_BYTES));
+    }
+    Ok(script)
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+impl AppState {
+    pub(crate) async fn load_watched_scripts(&self) -> Result<(), sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT script_hash, locking_script FROM watched_addresses WHERE script_hash IS NOT NULL AND status = 'active'"
+        )
+        .fetch_all(&self.db)
+        .await?;
+
+        let mut scripts = self.watched_scripts.write().await;
+        for row in rows {
+            let hash: String = row.try_get("script_hash")?;
+            match parse_script(&row.try_get::<String, _>("locking_script")?) {
+                Ok(script) => {
+                    scripts.insert(hash, script);
+                }
+                Err(e) => tracing::warn!("Skipping watched script {}: {}", hash, e),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Poll a watched script's UTXOs (and mempool history) for transactions not yet recorded
+pub async fn check_script_for_new_transactions(state: &AppState, script_hash: &str) -> Result<(), ServiceError> {
+    let mut txids: Vec<String> = state.chain.get_script_utxos(script_hash).await?
+        .into_iter()
+        .map(|utxo| utxo.tx_hash)
+        .collect();
+    if state.config.mempool_detection {
+        txids.extend(state.chain.get_script_unconfirmed(script_hash).await?);
+    }
+    record_new_transactions(state, &format!("script {}", script_hash), txids).await
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct WatchScriptRequest {
+    /// Hex locking script, e.g. a bare multisig funding output
+    pub locking_script: String,
+    pub paymail: String,
+    pub purpose: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WatchScriptResponse {
+    pub success: bool,
+    pub script_hash: String,
+    pub message: String,
+}
+
+pub async fn watch_script(
+    data: web::Data<AppState>,
+    req: web::Json<WatchScriptRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let script = parse_script(&req.locking_script).map_err(ServiceError::ValidationError)?;
+    if req.paymail.trim().is_empty() || req.purpose.trim().is_empty() {
+        return Err(ServiceError::ValidationError("paymail and purpose are required".to_string()));
+    }
+    let hash = script_hash(&script);
+
+    sqlx::query(
+        r#"
+        INSERT INTO watched_addresses (paymail, purpose, locking_script, script_hash, created_at)
+        VALUES ($1, $2, $3, $4, NOW())
+        ON CONFLICT (script_hash) WHERE script_hash IS NOT NULL
+        DO UPDATE SET status = 'active', status_changed_at = NOW()
+        WHERE watched_addresses.status <> 'active'
+        "#
+    )
+    .bind(req.paymail.trim())
+    .bind(req.purpose.trim())
+    .bind(hex::encode(&script))
+    .bind(&hash)
+    .execute(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    data.watched_scripts.write().await.insert(hash.clone(), script);
+
+    Ok(HttpResponse::Ok().json(WatchScriptResponse {
+        success: true,
+        script_hash: hash,
+        message: "Script is now being monitored".to_string(),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_hash_is_reversed_sha256() {
+        // OP_TRUE
+        let hash = script_hash(&[0x51]);
+        let mut forward = hex::decode(&hash).unwrap();
+        forward.reverse();
+        assert_eq!(forward, Sha256::digest([0x51]).to_vec());
+        assert_eq!(hash.len(), 64);
+    }
+
+    #[test]
+    fn test_parse_script() {
+        assert_eq!(parse_script(" 006a0474657374 ").unwrap(), vec![0x00, 0x6a, 0x04, 0x74, 0x65, 0x73, 0x74]);
+        assert!(parse_script("").is_err());
+        assert!(parse_script("zz").is_err());
+        assert!(parse_script(&"00".repeat(MAX_SCRIPT_BYTES + 1)).is_err());
+    }
+}
@@ -0,0 +1,146 @@
+// core/blockchain-monitor/src/search.rs
+// Search and filter stored transactions for support investigations
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::validate_address;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::Row;
+
+use crate::{transaction_from_row, AppState, ServiceError, TRANSACTION_COLUMNS};
+
+const DEFAULT_PER_PAGE: i64 = 50;
+const MAX_PER_PAGE: i64 = 200;
+const STATUSES: [&str; 4] = ["pending", "seen", "confirmed", "failed"];
+
+#[derive(Debug, Deserialize)]
+pub struct SearchQuery {
+    /// Matches either side of the transaction
+    pub address: Option<String>,
+    pub status: Option<String>,
+    /// RFC 3339 bounds on when the monitor first saw the transaction
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub min_amount: Option<i64>,
+    /// 1-based
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+impl SearchQuery {
+    fn validate(&self) -> Result<(), ServiceError> {
+        if let Some(address) = &self.address {
+            validate_address(address).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+        }
+        if let Some(status) = self.status.as_deref() {
+            if !STATUSES.contains(&status) {
+                return Err(ServiceError::ValidationError(format!(
+                    "status must be one of {}",
+                    STATUSES.join(", ")
+                )));
+            }
+        }
+        if let (Some(from), Some(to)) = (self.from, self.to) {
+            if from > to {
+                return Err(ServiceError::ValidationError("from must not be after to".to_string()));
+            }
+        }
+        if self.min_amount.is_some_and(|amount| amount < 0) {
+            return Err(ServiceError::ValidationError("min_amount cannot be negative".to_string()));
+        }
+        if self.page.is_some_and(|page| page < 1) {
+            return Err(ServiceError::ValidationError("page starts at 1".to_string()));
+        }
+        Ok(())
+    }
+
+    fn per_page(&self) -> i64 {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE)
+    }
+
+    fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1) - 1).saturating_mul(self.per_page())
+    }
+}
+
+/// `GET /txs`, newest first. Raw transactions are left out of listings; fetch one by txid for it.
+pub async fn search_transactions(
+    data: web::Data<AppState>,
+    query: web::Query<SearchQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    query.validate()?;
+
+    let rows = sqlx::query(&format!(
+        r#"
+        SELECT {}, COUNT(*) OVER () AS total
+        FROM blockchain_transactions
+        WHERE ($1::text IS NULL OR to_address = $1 OR from_address = $1)
+          AND ($2::text IS NULL OR status = $2)
+          AND ($3::timestamptz IS NULL OR first_seen >= $3)
+          AND ($4::timestamptz IS NULL OR first_seen <= $4)
+          AND ($5::bigint IS NULL OR amount_satoshis >= $5)
+        ORDER BY first_seen DESC, txid
+        LIMIT $6 OFFSET $7
+        "#,
+        TRANSACTION_COLUMNS
+    ))
+    .bind(&query.address)
+    .bind(&query.status)
+    .bind(query.from)
+    .bind(query.to)
+    .bind(query.min_amount)
+    .bind(query.per_page())
+    .bind(query.offset())
+    .fetch_all(&data.db)
+    .await
+    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    let total: i64 = rows.first().map(|row| row.get("total")).unwrap_or(0);
+    let transactions = rows
+        .iter()
+        .map(|row| {
+            transaction_from_row(row).map(|mut tx| {
+                tx.raw_tx = None;
+                tx
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "page": query.page.unwrap_or(1),
+        "per_page": query.per_page(),
+        "total": total,
+        "transactions": transactions
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(json: serde_json::Value) -> SearchQuery {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_paging() {
+        let q = query(serde_json::json!({ "page": 3, "per_page": 20 }));
+        assert_eq!(q.offset(), 40);
+        assert_eq!(query(serde_json::json!({ "per_page": 5000 })).per_page(), MAX_PER_PAGE);
+        assert_eq!(query(serde_json::json!({})).offset(), 0);
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(query(serde_json::json!({ "status": "confirmed", "min_amount": 0 })).validate().is_ok());
+        assert!(query(serde_json::json!({ "status": "lost" })).validate().is_err());
+        assert!(query(serde_json::json!({ "min_amount": -1 })).validate().is_err());
+        assert!(query(serde_json::json!({ "page": 0 })).validate().is_err());
+        assert!(query(serde_json::json!({
+            "from": "2025-11-25T00:00:00Z",
+            "to": "2025-11-24T00:00:00Z"
+        }))
+        .validate()
+        .is_err());
+    }
+}
@@ -0,0 +1,164 @@
+// core/blockchain-monitor/src/tracking.rs
+// Internal txid registration: sibling services hand over the transactions they create
+// so tracking starts immediately instead of when an address poll finds them
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{auth::extract_bearer_token, validate_txid, Claims, JwtManager, SERVICE_PERMISSION};
+use serde::{Deserialize, Serialize};
+
+use crate::{update_transaction_confirmations, AppState, ServiceError};
+
+/// Purposes also fill `blockchain_transactions.tx_type`, which is VARCHAR(20)
+const MAX_PURPOSE_LEN: usize = 20;
+const MAX_REFERENCE_LEN: usize = 255;
+
+#[derive(Debug, Deserialize)]
+pub struct TrackTransactionRequest {
+    pub txid: String,
+    /// e.g. `deposit`, `channel_funding` or `settlement`; selects the confirmation policy
+    pub purpose: String,
+    /// The caller's own id for the transaction, e.g. the deposit or channel id
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrackTransactionResponse {
+    pub txid: String,
+    pub purpose: String,
+    /// `pending` until a provider has seen the transaction
+    pub status: String,
+    pub confirmations: i32,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Claims of a valid service or admin token on the request
+pub(crate) fn service_claims(req: &HttpRequest, jwt: &JwtManager) -> Result<Claims, ServiceError> {
+    let header = req
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .ok_or_else(|| ServiceError::Unauthorized("Missing authorization header".to_string()))?;
+    let token = extract_bearer_token(header).map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+    let claims = jwt.verify_token(&token).map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+
+    if !claims.has_permission(SERVICE_PERMISSION) {
+        return Err(ServiceError::Forbidden("A service token is required".to_string()));
+    }
+    Ok(claims)
+}
+
+fn validate_request(req: &TrackTransactionRequest) -> Result<(), ServiceError> {
+    validate_txid(&req.txid).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    if req.purpose.trim().is_empty() || req.purpose.len() > MAX_PURPOSE_LEN {
+        return Err(ServiceError::ValidationError(format!(
+            "purpose must be 1-{} characters",
+            MAX_PURPOSE_LEN
+        )));
+    }
+    if req.reference.as_ref().is_some_and(|r| r.len() > MAX_REFERENCE_LEN) {
+        return Err(ServiceError::ValidationError(format!(
+            "reference must be at most {} characters",
+            MAX_REFERENCE_LEN
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+/// `POST /track/tx`: start tracking a txid the caller just created. Idempotent; the first
+/// registration's purpose wins.
+pub async fn track_transaction(
+    data: web::Data<AppState>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    req: web::Json<TrackTransactionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = service_claims(&http_req, &jwt)?;
+    validate_request(&req)?;
+    let purpose = req.purpose.trim();
+
+    let mut db_tx = data.db.begin().await.map_err(db_error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO tracked_transactions (txid, purpose, source, reference)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (txid) DO NOTHING
+        "#
+    )
+    .bind(&req.txid)
+    .bind(purpose)
+    .bind(&claims.sub)
+    .bind(&req.reference)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+
+    // A pending placeholder puts the txid in the monitoring loop's poll set until it is seen
+    sqlx::query(
+        r#"
+        INSERT INTO blockchain_transactions (txid, tx_type, amount_satoshis, confirmations, status, first_seen)
+        VALUES ($1, $2, 0, 0, 'pending', NOW())
+        ON CONFLICT (txid) DO UPDATE SET
+            tx_type = CASE WHEN blockchain_transactions.tx_type = 'unknown'
+                           THEN $2
+                           ELSE blockchain_transactions.tx_type
+                      END
+        "#
+    )
+    .bind(&req.txid)
+    .bind(purpose)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("{} registered {} for tracking as {}", claims.sub, req.txid, purpose);
+
+    // Usually not propagated yet; the monitoring loop keeps polling it
+    if let Err(e) = update_transaction_confirmations(&data, &req.txid).await {
+        tracing::debug!("Registered transaction {} not available yet: {}", req.txid, e);
+    }
+    data.tx_cache.invalidate(&req.txid);
+
+    let tx = data
+        .get_transaction(&req.txid)
+        .await?
+        .ok_or_else(|| ServiceError::NotFoundError(req.txid.clone()))?;
+
+    Ok(HttpResponse::Accepted().json(TrackTransactionResponse {
+        txid: tx.txid,
+        purpose: purpose.to_string(),
+        status: tx.status,
+        confirmations: tx.confirmations,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(purpose: &str) -> TrackTransactionRequest {
+        TrackTransactionRequest {
+            txid: "ab".repeat(32),
+            purpose: purpose.to_string(),
+            reference: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert!(validate_request(&request("settlement")).is_ok());
+        assert!(validate_request(&request(" ")).is_err());
+        assert!(validate_request(&request(&"p".repeat(MAX_PURPOSE_LEN + 1))).is_err());
+
+        let mut bad_txid = request("deposit");
+        bad_txid.txid = "mock-settlement-1".to_string();
+        assert!(validate_request(&bad_txid).is_err());
+    }
+}
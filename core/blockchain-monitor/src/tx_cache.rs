@@ -0,0 +1,152 @@
+// core/blockchain-monitor/src/tx_cache.rs
+// Bounded LRU cache of stored transactions with a TTL and hit/miss metrics
+
+use lru::LruCache;
+use prometheus::{IntCounter, IntCounterVec, IntGauge, Opts, Registry};
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::Transaction;
+
+struct Entry {
+    tx: Transaction,
+    inserted: Instant,
+}
+
+pub struct TxCache {
+    entries: Mutex<LruCache<String, Entry>>,
+    ttl: Duration,
+    lookups: IntCounterVec,
+    evictions: IntCounter,
+    size: IntGauge,
+}
+
+impl TxCache {
+    pub fn new(capacity: usize, ttl: Duration, registry: &Registry) -> Result<Self, prometheus::Error> {
+        let lookups = IntCounterVec::new(
+            Opts::new("blockchain_monitor_tx_cache_lookups_total", "Transaction cache lookups"),
+            &["result"],
+        )?;
+        let evictions = IntCounter::new(
+            "blockchain_monitor_tx_cache_evictions_total",
+            "Transactions dropped from the cache to stay within capacity",
+        )?;
+        let size = IntGauge::new("blockchain_monitor_tx_cache_entries", "Transactions currently cached")?;
+        registry.register(Box::new(lookups.clone()))?;
+        registry.register(Box::new(evictions.clone()))?;
+        registry.register(Box::new(size.clone()))?;
+
+        let capacity = NonZeroUsize::new(capacity).unwrap_or(NonZeroUsize::MIN);
+        Ok(Self { entries: Mutex::new(LruCache::new(capacity)), ttl, lookups, evictions, size })
+    }
+
+    pub fn get(&self, txid: &str) -> Option<Transaction> {
+        let mut entries = self.entries.lock().unwrap();
+        let cached = entries.get(txid).map(|entry| (entry.inserted.elapsed() < self.ttl, entry.tx.clone()));
+
+        let (result, tx) = match cached {
+            Some((true, tx)) => ("hit", Some(tx)),
+            Some((false, _)) => {
+                entries.pop(txid);
+                self.size.set(entries.len() as i64);
+                ("expired", None)
+            }
+            None => ("miss", None),
+        };
+        self.lookups.with_label_values(&[result]).inc();
+        tx
+    }
+
+    pub fn insert(&self, tx: Transaction) {
+        let mut entries = self.entries.lock().unwrap();
+        let txid = tx.txid.clone();
+        if let Some((evicted, _)) = entries.push(txid.clone(), Entry { tx, inserted: Instant::now() }) {
+            // `push` also hands back the old value when the key was already present
+            if evicted != txid {
+                self.evictions.inc();
+            }
+        }
+        self.size.set(entries.len() as i64);
+    }
+
+    /// Drop a transaction whose stored state (confirmations, block) changed
+    pub fn invalidate(&self, txid: &str) {
+        let mut entries = self.entries.lock().unwrap();
+        entries.pop(txid);
+        self.size.set(entries.len() as i64);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache(capacity: usize, ttl: Duration) -> TxCache {
+        TxCache::new(capacity, ttl, &Registry::new()).unwrap()
+    }
+
+    fn tx(txid: &str) -> Transaction {
+        serde_json::from_value(serde_json::json!({
+            "txid": txid,
+            "tx_type": null,
+            "from_address": null,
+            "to_address": null,
+            "amount_satoshis": 0,
+            "fee_satoshis": null,
+            "confirmations": 0,
+            "status": "seen",
+            "block_hash": null,
+            "block_height": null,
+            "block_time": null,
+            "raw_tx": null,
+            "first_seen": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_evicts_least_recently_used() {
+        let cache = cache(2, Duration::from_secs(60));
+        cache.insert(tx("a"));
+        cache.insert(tx("b"));
+        assert!(cache.get("a").is_some());
+        cache.insert(tx("c"));
+
+        assert!(cache.get("b").is_none());
+        assert!(cache.get("a").is_some());
+        assert!(cache.get("c").is_some());
+        assert_eq!(cache.evictions.get(), 1);
+        assert_eq!(cache.size.get(), 2);
+    }
+
+    #[test]
+    fn test_reinsert_is_not_an_eviction() {
+        let cache = cache(2, Duration::from_secs(60));
+        cache.insert(tx("a"));
+        cache.insert(tx("a"));
+        assert_eq!(cache.evictions.get(), 0);
+        assert_eq!(cache.size.get(), 1);
+    }
+
+    #[test]
+    fn test_expired_entries_are_dropped() {
+        let cache = cache(2, Duration::ZERO);
+        cache.insert(tx("a"));
+        assert!(cache.get("a").is_none());
+        assert_eq!(cache.lookups.with_label_values(&["expired"]).get(), 1);
+        assert_eq!(cache.size.get(), 0);
+    }
+
+    #[test]
+    fn test_invalidate_and_metrics() {
+        let cache = cache(4, Duration::from_secs(60));
+        cache.insert(tx("a"));
+        assert!(cache.get("a").is_some());
+        cache.invalidate("a");
+        assert!(cache.get("a").is_none());
+
+        assert_eq!(cache.lookups.with_label_values(&["hit"]).get(), 1);
+        assert_eq!(cache.lookups.with_label_values(&["miss"]).get(), 1);
+    }
+}
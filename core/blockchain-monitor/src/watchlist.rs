@@ -0,0 +1,284 @@
+// core/blockchain-monitor/src/watchlist.rs
+// Watched address lifecycle: unwatch, pause and archive, and listing by owner
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::validate_address;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::time::Duration;
+
+use crate::{AppState, ServiceError};
+
+const STATUSES: [&str; 3] = ["active", "paused", "archived"];
+const DEFAULT_PER_PAGE: i64 = 50;
+const MAX_PER_PAGE: i64 = 200;
+/// Inactivity is measured in days; checking hourly is plenty
+const ARCHIVE_CHECK_INTERVAL: Duration = Duration::from_secs(3600);
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WatchedEntry {
+    pub address: Option<String>,
+    pub script_hash: Option<String>,
+    pub paymail: String,
+    pub purpose: String,
+    pub status: String,
+    pub created_at: Option<DateTime<Utc>>,
+    pub last_activity: Option<DateTime<Utc>>,
+    pub status_changed_at: Option<DateTime<Utc>>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+fn validate_status(status: &str) -> Result<(), ServiceError> {
+    if STATUSES.contains(&status) {
+        Ok(())
+    } else {
+        Err(ServiceError::ValidationError(format!("status must be one of {}", STATUSES.join(", "))))
+    }
+}
+
+/// Record activity on watched addresses; archived ones the monitor still hears about
+/// (a spend of a tracked output, a payment alongside another watched address) become active again
+pub async fn note_activity(state: &AppState, addresses: &[String]) -> Result<(), ServiceError> {
+    if addresses.is_empty() {
+        return Ok(());
+    }
+
+    let revived: Vec<String> = sqlx::query(
+        r#"
+        UPDATE watched_addresses
+        SET status = 'active', status_changed_at = NOW()
+        WHERE address = ANY($1) AND status = 'archived'
+        RETURNING address
+        "#
+    )
+    .bind(addresses)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?
+    .into_iter()
+    .map(|row| row.get("address"))
+    .collect();
+
+    sqlx::query("UPDATE watched_addresses SET last_activity = NOW() WHERE address = ANY($1)")
+        .bind(addresses)
+        .execute(&state.db)
+        .await
+        .map_err(db_error)?;
+
+    if !revived.is_empty() {
+        tracing::info!("Reactivated {} archived address(es) on new activity", revived.len());
+        state.watched_addresses.write().await.extend(revived);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// ARCHIVING
+// ============================================================================
+
+pub async fn start_archive_worker(state: web::Data<AppState>) {
+    if state.config.archive_after_days == 0 {
+        tracing::info!("Automatic archiving of inactive addresses is disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        loop {
+            match archive_inactive(&state).await {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Archived {} inactive watched address(es)", n),
+                Err(e) => tracing::error!("Failed to archive inactive addresses: {}", e),
+            }
+            tokio::time::sleep(ARCHIVE_CHECK_INTERVAL).await;
+        }
+    });
+}
+
+/// Archive active addresses with no activity within the configured window. Script entries
+/// are left alone: their activity is not attributed back to them.
+async fn archive_inactive(state: &AppState) -> Result<usize, ServiceError> {
+    let archived: Vec<String> = sqlx::query(
+        r#"
+        UPDATE watched_addresses
+        SET status = 'archived', status_changed_at = NOW()
+        WHERE status = 'active'
+          AND address IS NOT NULL
+          AND COALESCE(last_activity, created_at) < NOW() - make_interval(days => $1)
+        RETURNING address
+        "#
+    )
+    .bind(state.config.archive_after_days as i32)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?
+    .into_iter()
+    .map(|row| row.get("address"))
+    .collect();
+
+    let mut watched = state.watched_addresses.write().await;
+    for address in &archived {
+        watched.remove(address);
+    }
+    Ok(archived.len())
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ListQuery {
+    pub paymail: Option<String>,
+    pub purpose: Option<String>,
+    pub status: Option<String>,
+    /// 1-based
+    pub page: Option<i64>,
+    pub per_page: Option<i64>,
+}
+
+impl ListQuery {
+    fn validate(&self) -> Result<(), ServiceError> {
+        if let Some(status) = self.status.as_deref() {
+            validate_status(status)?;
+        }
+        if self.page.is_some_and(|page| page < 1) {
+            return Err(ServiceError::ValidationError("page starts at 1".to_string()));
+        }
+        Ok(())
+    }
+
+    fn per_page(&self) -> i64 {
+        self.per_page.unwrap_or(DEFAULT_PER_PAGE).clamp(1, MAX_PER_PAGE)
+    }
+
+    fn offset(&self) -> i64 {
+        (self.page.unwrap_or(1) - 1).saturating_mul(self.per_page())
+    }
+}
+
+/// `GET /watch/addresses`, newest first; includes watched scripts
+pub async fn list_watched(
+    data: web::Data<AppState>,
+    query: web::Query<ListQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    query.validate()?;
+
+    let rows = sqlx::query(
+        r#"
+        SELECT address, script_hash, paymail, purpose, status, created_at, last_activity,
+               status_changed_at, COUNT(*) OVER () AS total
+        FROM watched_addresses
+        WHERE ($1::text IS NULL OR paymail = $1)
+          AND ($2::text IS NULL OR purpose = $2)
+          AND ($3::text IS NULL OR status = $3)
+        ORDER BY created_at DESC, id
+        LIMIT $4 OFFSET $5
+        "#
+    )
+    .bind(&query.paymail)
+    .bind(&query.purpose)
+    .bind(&query.status)
+    .bind(query.per_page())
+    .bind(query.offset())
+    .fetch_all(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    let total: i64 = rows.first().map(|row| row.get("total")).unwrap_or(0);
+    let entries = rows
+        .iter()
+        .map(<WatchedEntry as sqlx::FromRow<_>>::from_row)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "page": query.page.unwrap_or(1),
+        "per_page": query.per_page(),
+        "total": total,
+        "addresses": entries
+    })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetStatusRequest {
+    pub status: String,
+}
+
+/// `PUT /watch/address/{address}/status`
+pub async fn set_status(
+    data: web::Data<AppState>,
+    address: web::Path<String>,
+    req: web::Json<SetStatusRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_address(&address).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_status(&req.status)?;
+
+    let entry = sqlx::query_as::<_, WatchedEntry>(
+        r#"
+        UPDATE watched_addresses
+        SET status = $2,
+            status_changed_at = CASE WHEN status = $2 THEN status_changed_at ELSE NOW() END
+        WHERE address = $1
+        RETURNING address, script_hash, paymail, purpose, status, created_at, last_activity, status_changed_at
+        "#
+    )
+    .bind(address.as_str())
+    .bind(&req.status)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::NotFoundError(format!("Address {} is not watched", address)))?;
+
+    let mut watched = data.watched_addresses.write().await;
+    if entry.status == "active" {
+        watched.insert(address.to_string());
+    } else {
+        watched.remove(address.as_str());
+    }
+
+    Ok(HttpResponse::Ok().json(entry))
+}
+
+/// `DELETE /watch/address/{address}`: stop watching and forget the entry.
+/// Recorded transactions and webhook subscriptions are kept.
+pub async fn unwatch_address(
+    data: web::Data<AppState>,
+    address: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_address(&address).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    let result = sqlx::query("DELETE FROM watched_addresses WHERE address = $1")
+        .bind(address.as_str())
+        .execute(&data.db)
+        .await
+        .map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Err(ServiceError::NotFoundError(format!("Address {} is not watched", address)));
+    }
+
+    data.watched_addresses.write().await.remove(address.as_str());
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "address": address.as_str(), "watched": false })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(json: serde_json::Value) -> ListQuery {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_list_query() {
+        assert!(query(serde_json::json!({ "paymail": "alice@bsvbank.io", "status": "archived" })).validate().is_ok());
+        assert!(query(serde_json::json!({ "status": "deleted" })).validate().is_err());
+        assert!(query(serde_json::json!({ "page": 0 })).validate().is_err());
+        assert_eq!(query(serde_json::json!({ "page": 2, "per_page": 25 })).offset(), 25);
+        assert_eq!(query(serde_json::json!({ "per_page": 0 })).per_page(), 1);
+    }
+}
@@ -0,0 +1,556 @@
+// core/blockchain-monitor/src/webhooks.rs
+// Push notifications: address/txid subscriptions with a durable, HMAC-signed delivery queue
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::{generate_webhook_secret, sign_payload, validate_address, validate_txid, validate_url, webhook};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::{AppState, ServiceError, Transaction, WocTransaction};
+
+/// Fired when a matching transaction is first seen (`min_confirmations` = 0)
+pub const EVENT_TX_DETECTED: &str = "tx.detected";
+/// Fired when a matching transaction reaches `min_confirmations`
+pub const EVENT_TX_CONFIRMED: &str = "tx.confirmed";
+/// Fired when an input of a matching transaction is spent by another transaction
+pub const EVENT_TX_DOUBLE_SPEND: &str = "tx.double_spend";
+/// Fired once when a matching transaction reaches its purpose's confirmation target
+pub const EVENT_TX_FINALIZED: &str = "tx.finalized";
+
+/// Deliveries claimed per worker pass
+const DELIVERY_BATCH: i64 = 50;
+/// How long a claimed delivery is hidden from other workers
+const DELIVERY_LEASE_SECS: i64 = 60;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct MonitorWebhook {
+    pub id: Uuid,
+    pub owner: String,
+    pub url: String,
+    pub address: Option<String>,
+    pub txid: Option<String>,
+    pub min_confirmations: i32,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+impl MonitorWebhook {
+    fn event(&self) -> &'static str {
+        if self.min_confirmations == 0 { EVENT_TX_DETECTED } else { EVENT_TX_CONFIRMED }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterWebhookRequest {
+    pub url: String,
+    /// Service or paymail registering the callback
+    pub owner: String,
+    pub address: Option<String>,
+    pub txid: Option<String>,
+    #[serde(default)]
+    pub min_confirmations: i32,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WebhookDelivery {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub event_type: String,
+    pub txid: String,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub delivered_at: Option<DateTime<Utc>>,
+}
+
+#[derive(sqlx::FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    event_type: String,
+    payload: String,
+    attempts: i32,
+    url: String,
+    secret: String,
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookEnvelope<'a> {
+    id: Uuid,
+    event: &'a str,
+    timestamp: DateTime<Utc>,
+    data: serde_json::Value,
+}
+
+// ============================================================================
+// MATCHING
+// ============================================================================
+
+/// True when this update is the one that takes the transaction to `min_confirmations`.
+/// `old` is None for a transaction the monitor had not stored before.
+pub fn crosses_threshold(min_confirmations: i32, old: Option<i32>, new: i32) -> bool {
+    match old {
+        None => new >= min_confirmations,
+        Some(old) => old < min_confirmations && new >= min_confirmations,
+    }
+}
+
+/// Every address paid by or spending into the transaction
+pub fn transaction_addresses(woc_tx: &WocTransaction) -> Vec<String> {
+    let inputs = woc_tx.inputs.iter().flatten().filter_map(|i| i.script_sig.as_ref());
+    let outputs = woc_tx.outputs.iter().flatten().filter_map(|o| o.script_pub_key.as_ref());
+
+    let mut addresses: Vec<String> = inputs
+        .chain(outputs)
+        .filter_map(|script| script.addresses.as_ref())
+        .flatten()
+        .cloned()
+        .collect();
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
+/// Delay before retry number `attempts` + 1: 30s doubling up to an hour
+pub fn retry_delay(attempts: i32) -> Duration {
+    Duration::from_secs((30u64 << attempts.clamp(0, 7) as u32).min(3600))
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Queue deliveries for every subscription this confirmation change satisfies.
+/// Safe to call repeatedly for the same update: each subscription fires once per transaction and event.
+pub async fn enqueue_matches(
+    state: &AppState,
+    tx: &Transaction,
+    addresses: &[String],
+    old_confirmations: Option<i32>,
+) -> Result<(), ServiceError> {
+    let hooks = sqlx::query_as::<_, MonitorWebhook>(
+        r#"
+        SELECT * FROM monitor_webhooks
+        WHERE active
+          AND (txid = $1 OR address = ANY($2))
+          AND min_confirmations <= $3
+        "#
+    )
+    .bind(&tx.txid)
+    .bind(addresses)
+    .bind(tx.confirmations)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    for hook in hooks {
+        if !crosses_threshold(hook.min_confirmations, old_confirmations, tx.confirmations) {
+            continue;
+        }
+        // An address subscription must match the subscribed address, not just any txid filter
+        let matched_address = hook.address.as_ref().filter(|a| addresses.contains(a));
+        if hook.txid.as_deref() != Some(tx.txid.as_str()) && matched_address.is_none() {
+            continue;
+        }
+
+        queue_delivery(state, hook.id, hook.event(), &tx.txid, serde_json::json!({
+            "webhook_id": hook.id,
+            "txid": tx.txid,
+            "address": matched_address,
+            "confirmations": tx.confirmations,
+            "min_confirmations": hook.min_confirmations,
+            "status": tx.status,
+            "block_hash": tx.block_hash,
+            "block_height": tx.block_height,
+            "amount_satoshis": tx.amount_satoshis,
+        }))
+        .await?;
+    }
+
+    Ok(())
+}
+
+/// Queue `event` for every subscription on `txid` or one of its addresses,
+/// whatever its confirmation threshold
+pub async fn enqueue_event(
+    state: &AppState,
+    event: &str,
+    txid: &str,
+    addresses: &[String],
+    data: serde_json::Value,
+) -> Result<(), ServiceError> {
+    let hooks = sqlx::query_as::<_, MonitorWebhook>(
+        "SELECT * FROM monitor_webhooks WHERE active AND (txid = $1 OR address = ANY($2))"
+    )
+    .bind(txid)
+    .bind(addresses)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    for hook in hooks {
+        let mut data = data.clone();
+        data["webhook_id"] = serde_json::json!(hook.id);
+        queue_delivery(state, hook.id, event, txid, data).await?;
+    }
+
+    Ok(())
+}
+
+async fn queue_delivery(
+    state: &AppState,
+    webhook_id: Uuid,
+    event: &str,
+    txid: &str,
+    data: serde_json::Value,
+) -> Result<(), ServiceError> {
+    let envelope = WebhookEnvelope {
+        id: Uuid::new_v4(),
+        event,
+        timestamp: Utc::now(),
+        data,
+    };
+    let body = serde_json::to_string(&envelope).unwrap_or_default();
+
+    sqlx::query(
+        r#"
+        INSERT INTO monitor_webhook_deliveries (id, webhook_id, event_type, txid, payload)
+        VALUES ($1, $2, $3, $4, $5::jsonb)
+        ON CONFLICT (webhook_id, txid, event_type) DO NOTHING
+        "#
+    )
+    .bind(envelope.id)
+    .bind(webhook_id)
+    .bind(event)
+    .bind(txid)
+    .bind(&body)
+    .execute(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(())
+}
+
+// ============================================================================
+// DELIVERY WORKER
+// ============================================================================
+
+/// Sends due deliveries until they succeed or run out of attempts. Deliveries live
+/// in the database, so a restart resumes where the previous process stopped.
+pub async fn start_delivery_worker(state: web::Data<AppState>) {
+    let interval = Duration::from_secs(state.config.webhook_poll_secs);
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()
+        .unwrap_or_default();
+
+    tokio::spawn(async move {
+        loop {
+            match claim_due(&state).await {
+                Ok(due) => {
+                    for delivery in due {
+                        deliver(&state, &client, delivery).await;
+                    }
+                }
+                Err(e) => tracing::error!("Failed to load webhook deliveries: {}", e),
+            }
+            tokio::time::sleep(interval).await;
+        }
+    });
+}
+
+async fn claim_due(state: &AppState) -> Result<Vec<DueDelivery>, sqlx::Error> {
+    sqlx::query_as::<_, DueDelivery>(
+        r#"
+        UPDATE monitor_webhook_deliveries d
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        FROM monitor_webhooks w
+        WHERE d.webhook_id = w.id
+          AND d.id IN (
+              SELECT id FROM monitor_webhook_deliveries
+              WHERE status = 'pending' AND next_attempt_at <= NOW()
+              ORDER BY next_attempt_at
+              LIMIT $1
+              FOR UPDATE SKIP LOCKED
+          )
+        RETURNING d.id, d.event_type, d.payload::text AS payload, d.attempts, w.url, w.secret
+        "#
+    )
+    .bind(DELIVERY_BATCH)
+    .bind(DELIVERY_LEASE_SECS as f64)
+    .fetch_all(&state.db)
+    .await
+}
+
+async fn deliver(state: &AppState, client: &reqwest::Client, delivery: DueDelivery) {
+    let timestamp = Utc::now().timestamp();
+    let signature = sign_payload(&delivery.secret, timestamp, &delivery.payload);
+
+    let result = client
+        .post(&delivery.url)
+        .header("Content-Type", "application/json")
+        .header(webhook::EVENT_HEADER, &delivery.event_type)
+        .header(webhook::TIMESTAMP_HEADER, timestamp.to_string())
+        .header(webhook::SIGNATURE_HEADER, signature)
+        .body(delivery.payload.clone())
+        .send()
+        .await;
+
+    let attempts = delivery.attempts + 1;
+    let (status, error) = match result {
+        Ok(response) if response.status().is_success() => {
+            let _ = sqlx::query(
+                r#"
+                UPDATE monitor_webhook_deliveries
+                SET status = 'delivered', attempts = $1, response_status = $2,
+                    delivered_at = NOW(), last_error = NULL
+                WHERE id = $3
+                "#
+            )
+            .bind(attempts)
+            .bind(response.status().as_u16() as i32)
+            .bind(delivery.id)
+            .execute(&state.db)
+            .await;
+            return;
+        }
+        Ok(response) => (Some(response.status().as_u16() as i32), format!("Status: {}", response.status())),
+        Err(e) => (None, e.to_string()),
+    };
+
+    let exhausted = attempts >= state.config.webhook_max_attempts;
+    if exhausted {
+        tracing::warn!("Webhook delivery {} to {} failed after {} attempts: {}",
+            delivery.id, delivery.url, attempts, error);
+    }
+
+    let _ = sqlx::query(
+        r#"
+        UPDATE monitor_webhook_deliveries
+        SET status = CASE WHEN $1 THEN 'failed' ELSE 'pending' END,
+            attempts = $2, response_status = $3, last_error = $4,
+            next_attempt_at = NOW() + make_interval(secs => $5)
+        WHERE id = $6
+        "#
+    )
+    .bind(exhausted)
+    .bind(attempts)
+    .bind(status)
+    .bind(error)
+    .bind(retry_delay(attempts).as_secs_f64())
+    .bind(delivery.id)
+    .execute(&state.db)
+    .await;
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+pub async fn register_webhook(
+    data: web::Data<AppState>,
+    req: web::Json<RegisterWebhookRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_url(&req.url).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    if req.owner.trim().is_empty() {
+        return Err(ServiceError::ValidationError("owner is required".to_string()));
+    }
+    if req.address.is_none() && req.txid.is_none() {
+        return Err(ServiceError::ValidationError("Provide an address or txid to watch".to_string()));
+    }
+    if let Some(address) = &req.address {
+        validate_address(address).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+    if let Some(txid) = &req.txid {
+        validate_txid(txid).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+    if req.min_confirmations < 0 {
+        return Err(ServiceError::ValidationError("min_confirmations cannot be negative".to_string()));
+    }
+
+    let secret = generate_webhook_secret();
+    let webhook = sqlx::query_as::<_, MonitorWebhook>(
+        r#"
+        INSERT INTO monitor_webhooks (owner, url, secret, address, txid, min_confirmations)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#
+    )
+    .bind(req.owner.trim())
+    .bind(&req.url)
+    .bind(&secret)
+    .bind(&req.address)
+    .bind(&req.txid)
+    .bind(req.min_confirmations)
+    .fetch_one(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    // The poller only sees addresses it watches and transactions it has stored
+    if let Some(address) = &req.address {
+        data.add_watched_address(address, req.owner.trim(), "webhook").await?;
+    }
+    if let Some(txid) = &req.txid {
+        if let Err(e) = crate::update_transaction_confirmations(&data, txid).await {
+            tracing::warn!("Webhook {} registered for {} before it was visible: {}", webhook.id, txid, e);
+        }
+    }
+
+    tracing::info!("Webhook {} registered by {}: {}", webhook.id, webhook.owner, webhook.url);
+    Ok(HttpResponse::Created().json(serde_json::json!({
+        "webhook": webhook,
+        "secret": secret,
+        "message": "Store this secret; it is used to verify the X-BSVBank-Signature header and will not be shown again."
+    })))
+}
+
+pub async fn get_webhook(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    let webhook = sqlx::query_as::<_, MonitorWebhook>("SELECT * FROM monitor_webhooks WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::NotFoundError(format!("Webhook {}", id)))?;
+
+    Ok(HttpResponse::Ok().json(webhook))
+}
+
+pub async fn delete_webhook(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    let result = sqlx::query("UPDATE monitor_webhooks SET active = false WHERE id = $1")
+        .bind(id)
+        .execute(&data.db)
+        .await
+        .map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Err(ServiceError::NotFoundError(format!("Webhook {}", id)));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "webhook_id": id, "active": false })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeliveriesQuery {
+    /// "pending", "delivered" or "failed"
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+pub async fn list_deliveries(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    query: web::Query<DeliveriesQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    if let Some(status) = query.status.as_deref() {
+        if !["pending", "delivered", "failed"].contains(&status) {
+            return Err(ServiceError::ValidationError(format!("Unknown delivery status: {}", status)));
+        }
+    }
+
+    let deliveries = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        SELECT id, webhook_id, event_type, txid, status, attempts, next_attempt_at,
+               response_status, last_error, created_at, delivered_at
+        FROM monitor_webhook_deliveries
+        WHERE webhook_id = $1 AND ($2::text IS NULL OR status = $2)
+        ORDER BY created_at DESC
+        LIMIT $3
+        "#
+    )
+    .bind(id)
+    .bind(&query.status)
+    .bind(query.limit.unwrap_or(50).clamp(1, 500))
+    .fetch_all(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "webhook_id": id,
+        "total_deliveries": deliveries.len(),
+        "deliveries": deliveries
+    })))
+}
+
+/// Put a failed delivery back on the queue with a fresh attempt budget
+pub async fn retry_delivery(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    let delivery = sqlx::query_as::<_, WebhookDelivery>(
+        r#"
+        UPDATE monitor_webhook_deliveries
+        SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+        WHERE id = $1 AND status = 'failed'
+        RETURNING id, webhook_id, event_type, txid, status, attempts, next_attempt_at,
+                  response_status, last_error, created_at, delivered_at
+        "#
+    )
+    .bind(id)
+    .fetch_optional(&data.db)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::NotFoundError(format!("Failed delivery {}", id)))?;
+
+    Ok(HttpResponse::Ok().json(delivery))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_crosses_threshold_once() {
+        // Zero-conf subscriptions fire on first sight only
+        assert!(crosses_threshold(0, None, 0));
+        assert!(!crosses_threshold(0, Some(0), 1));
+
+        assert!(!crosses_threshold(6, None, 2));
+        assert!(!crosses_threshold(6, Some(2), 5));
+        assert!(crosses_threshold(6, Some(5), 6));
+        assert!(crosses_threshold(6, Some(3), 8));
+        assert!(!crosses_threshold(6, Some(6), 7));
+        assert!(crosses_threshold(1, None, 3));
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_to_a_cap() {
+        assert_eq!(retry_delay(0), Duration::from_secs(30));
+        assert_eq!(retry_delay(1), Duration::from_secs(60));
+        assert_eq!(retry_delay(3), Duration::from_secs(240));
+        assert_eq!(retry_delay(7), Duration::from_secs(3600));
+        assert_eq!(retry_delay(40), Duration::from_secs(3600));
+    }
+
+    #[test]
+    fn test_transaction_addresses() {
+        let woc_tx: WocTransaction = serde_json::from_value(serde_json::json!({
+            "txid": "ab",
+            "vin": [{ "txid": "cd", "vout": 0, "scriptSig": { "addresses": ["mSender"] } }],
+            "vout": [
+                { "value": 0.1, "n": 0, "scriptPubKey": { "addresses": ["mReceiver"] } },
+                { "value": 0.2, "n": 1, "scriptPubKey": { "addresses": ["mSender"] } },
+                { "value": 0.0, "n": 2, "scriptPubKey": {} }
+            ]
+        }))
+        .unwrap();
+
+        assert_eq!(transaction_addresses(&woc_tx), vec!["mReceiver".to_string(), "mSender".to_string()]);
+    }
+}
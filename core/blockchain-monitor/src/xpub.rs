@@ -0,0 +1,430 @@
+// core/blockchain-monitor/src/xpub.rs
+// HD wallet watching: derive P2PKH addresses from an account xpub and keep a gap of unused ones watched
+
+use actix_web::{web, HttpResponse, Result};
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use ripemd::Ripemd160;
+use secp256k1::{PublicKey, Scalar, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256, Sha512};
+use sqlx::Row;
+use uuid::Uuid;
+
+use crate::{AppState, ServiceError};
+
+const XPUB_VERSION: [u8; 4] = [0x04, 0x88, 0xb2, 0x1e];
+const TPUB_VERSION: [u8; 4] = [0x04, 0x35, 0x87, 0xcf];
+const HARDENED: u32 = 0x8000_0000;
+
+const DEFAULT_GAP_LIMIT: i32 = 20;
+const MAX_GAP_LIMIT: i32 = 500;
+
+// ============================================================================
+// KEY DERIVATION
+// ============================================================================
+
+/// BIP32 extended public key; only non-hardened children can be derived from it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtendedPubKey {
+    pub mainnet: bool,
+    pub chain_code: [u8; 32],
+    pub key: PublicKey,
+}
+
+impl ExtendedPubKey {
+    pub fn parse(encoded: &str) -> Result<Self, String> {
+        let data = bs58::decode(encoded.trim())
+            .into_vec()
+            .map_err(|e| format!("Invalid xpub encoding: {}", e))?;
+        if data.len() != 82 {
+            return Err(format!("xpub must decode to 82 bytes, got {}", data.len()));
+        }
+        let (payload, checksum) = data.split_at(78);
+        if double_sha256(payload)[..4] != *checksum {
+            return Err("Invalid xpub checksum".to_string());
+        }
+
+        let version: [u8; 4] = payload[..4].try_into().expect("four version bytes");
+        let mainnet = match version {
+            XPUB_VERSION => true,
+            TPUB_VERSION => false,
+            _ => return Err("Not an extended public key (expected xpub or tpub)".to_string()),
+        };
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&payload[13..45]);
+        let key = PublicKey::from_slice(&payload[45..78]).map_err(|e| format!("Invalid xpub key: {}", e))?;
+
+        Ok(Self { mainnet, chain_code, key })
+    }
+
+    /// CKDpub: child = parent + I_L·G, chain code = I_R
+    pub fn derive_child(&self, index: u32) -> Result<Self, String> {
+        if index >= HARDENED {
+            return Err("Hardened children cannot be derived from an xpub".to_string());
+        }
+        let mut mac = Hmac::<Sha512>::new_from_slice(&self.chain_code).expect("HMAC accepts any key length");
+        mac.update(&self.key.serialize());
+        mac.update(&index.to_be_bytes());
+        let i = mac.finalize().into_bytes();
+
+        let mut tweak = [0u8; 32];
+        tweak.copy_from_slice(&i[..32]);
+        let tweak = Scalar::from_be_bytes(tweak).map_err(|_| format!("Child {} is invalid; skip it", index))?;
+        let key = self.key
+            .add_exp_tweak(&Secp256k1::verification_only(), &tweak)
+            .map_err(|e| format!("Child {} is invalid: {}", index, e))?;
+
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&i[32..]);
+        Ok(Self { mainnet: self.mainnet, chain_code, key })
+    }
+
+    pub fn address(&self) -> String {
+        let hash = Ripemd160::digest(Sha256::digest(self.key.serialize()));
+        let mut bytes = vec![if self.mainnet { 0x00 } else { 0x6f }];
+        bytes.extend_from_slice(&hash);
+        let checksum = double_sha256(&bytes);
+        bytes.extend_from_slice(&checksum[..4]);
+        bs58::encode(bytes).into_string()
+    }
+}
+
+fn double_sha256(data: &[u8]) -> Vec<u8> {
+    Sha256::digest(Sha256::digest(data)).to_vec()
+}
+
+/// Layout of addresses below the account key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scheme {
+    /// `<account xpub>/0/i` receive and `/1/i` change, as BIP44 wallets issue them
+    Bip44,
+    /// `<xpub>/i`, a single chain directly under the key
+    Single,
+}
+
+impl Scheme {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "bip44" => Ok(Scheme::Bip44),
+            "single" => Ok(Scheme::Single),
+            other => Err(format!("Unknown derivation scheme '{}' (expected bip44 or single)", other)),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scheme::Bip44 => "bip44",
+            Scheme::Single => "single",
+        }
+    }
+
+    pub fn chains(&self) -> &'static [i16] {
+        match self {
+            Scheme::Bip44 => &[0, 1],
+            Scheme::Single => &[0],
+        }
+    }
+
+    /// The key addresses on `chain` are derived from
+    pub fn chain_key(&self, xpub: &ExtendedPubKey, chain: i16) -> Result<ExtendedPubKey, String> {
+        match self {
+            Scheme::Bip44 => xpub.derive_child(chain as u32),
+            Scheme::Single => Ok(xpub.clone()),
+        }
+    }
+}
+
+/// Indexes still to derive so `gap_limit` addresses follow the last used one
+pub fn indexes_to_derive(last_derived: i32, last_used: i32, gap_limit: i32) -> std::ops::RangeInclusive<i32> {
+    (last_derived + 1)..=(last_used + gap_limit)
+}
+
+// ============================================================================
+// WATCHING
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WatchedXpub {
+    pub id: Uuid,
+    pub xpub: String,
+    pub paymail: String,
+    pub purpose: String,
+    pub scheme: String,
+    pub gap_limit: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Derive and watch addresses until every chain has `gap_limit` unused ones past its last used address
+async fn top_up(state: &AppState, watched: &WatchedXpub) -> Result<(), ServiceError> {
+    let xpub = ExtendedPubKey::parse(&watched.xpub).map_err(ServiceError::ValidationError)?;
+    let scheme = Scheme::parse(&watched.scheme).map_err(ServiceError::ValidationError)?;
+
+    for &chain in scheme.chains() {
+        let row = sqlx::query(
+            r#"
+            SELECT COALESCE(MAX(address_index), -1) AS last_derived,
+                   COALESCE(MAX(address_index) FILTER (WHERE used), -1) AS last_used
+            FROM xpub_addresses
+            WHERE xpub_id = $1 AND chain = $2
+            "#
+        )
+        .bind(watched.id)
+        .bind(chain)
+        .fetch_one(&state.db)
+        .await
+        .map_err(db_error)?;
+
+        let range = indexes_to_derive(row.get("last_derived"), row.get("last_used"), watched.gap_limit);
+        if range.is_empty() {
+            continue;
+        }
+        let chain_key = scheme.chain_key(&xpub, chain).map_err(ServiceError::ValidationError)?;
+
+        for index in range {
+            // BIP32: an invalid child (probability < 2^-127) is skipped
+            let address = match chain_key.derive_child(index as u32) {
+                Ok(child) => child.address(),
+                Err(e) => {
+                    tracing::warn!("xpub {} chain {}: {}", watched.id, chain, e);
+                    continue;
+                }
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO xpub_addresses (address, xpub_id, chain, address_index)
+                VALUES ($1, $2, $3, $4)
+                ON CONFLICT DO NOTHING
+                "#
+            )
+            .bind(&address)
+            .bind(watched.id)
+            .bind(chain)
+            .bind(index)
+            .execute(&state.db)
+            .await
+            .map_err(db_error)?;
+
+            state.add_watched_address(&address, &watched.paymail, &watched.purpose).await?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Mark derived addresses seen in a transaction as used and extend their gap
+pub async fn note_activity(state: &AppState, addresses: &[String]) -> Result<(), ServiceError> {
+    if addresses.is_empty() {
+        return Ok(());
+    }
+
+    let touched = sqlx::query_as::<_, WatchedXpub>(
+        r#"
+        WITH marked AS (
+            UPDATE xpub_addresses
+            SET used = true, first_used_at = NOW()
+            WHERE address = ANY($1) AND NOT used
+            RETURNING xpub_id
+        )
+        SELECT * FROM watched_xpubs WHERE id IN (SELECT xpub_id FROM marked)
+        "#
+    )
+    .bind(addresses)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?;
+
+    for watched in touched {
+        tracing::info!("xpub {} received activity; extending gap limit", watched.id);
+        top_up(state, &watched).await?;
+    }
+
+    Ok(())
+}
+
+/// The lowest unused address on `chain`, i.e. the next one to hand out
+async fn next_unused(state: &AppState, xpub_id: Uuid, chain: i16) -> Result<Option<String>, ServiceError> {
+    sqlx::query_scalar(
+        r#"
+        SELECT address FROM xpub_addresses
+        WHERE xpub_id = $1 AND chain = $2 AND NOT used
+        ORDER BY address_index
+        LIMIT 1
+        "#
+    )
+    .bind(xpub_id)
+    .bind(chain)
+    .fetch_optional(&state.db)
+    .await
+    .map_err(db_error)
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct WatchXpubRequest {
+    pub xpub: String,
+    pub paymail: String,
+    pub purpose: String,
+    /// "bip44" (default) or "single"
+    pub scheme: Option<String>,
+    pub gap_limit: Option<i32>,
+}
+
+pub async fn watch_xpub(
+    data: web::Data<AppState>,
+    req: web::Json<WatchXpubRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let xpub = ExtendedPubKey::parse(&req.xpub).map_err(ServiceError::ValidationError)?;
+    if xpub.mainnet != (data.config.network == "mainnet") {
+        return Err(ServiceError::ValidationError(format!(
+            "{} key does not match network {}",
+            if xpub.mainnet { "Mainnet" } else { "Testnet" },
+            data.config.network
+        )));
+    }
+    let scheme = Scheme::parse(req.scheme.as_deref().unwrap_or("bip44")).map_err(ServiceError::ValidationError)?;
+    let gap_limit = req.gap_limit.unwrap_or(DEFAULT_GAP_LIMIT);
+    if !(1..=MAX_GAP_LIMIT).contains(&gap_limit) {
+        return Err(ServiceError::ValidationError(format!("gap_limit must be between 1 and {}", MAX_GAP_LIMIT)));
+    }
+    if req.paymail.trim().is_empty() || req.purpose.trim().is_empty() {
+        return Err(ServiceError::ValidationError("paymail and purpose are required".to_string()));
+    }
+
+    // Re-registering the same key keeps its original settings
+    let watched = sqlx::query_as::<_, WatchedXpub>(
+        r#"
+        WITH inserted AS (
+            INSERT INTO watched_xpubs (xpub, paymail, purpose, scheme, gap_limit)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (xpub) DO NOTHING
+            RETURNING *
+        )
+        SELECT * FROM inserted
+        UNION ALL
+        SELECT * FROM watched_xpubs WHERE xpub = $1
+        LIMIT 1
+        "#
+    )
+    .bind(req.xpub.trim())
+    .bind(req.paymail.trim())
+    .bind(req.purpose.trim())
+    .bind(scheme.as_str())
+    .bind(gap_limit)
+    .fetch_one(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    top_up(&data, &watched).await?;
+    tracing::info!("Watching xpub {} ({}, gap {})", watched.id, watched.scheme, watched.gap_limit);
+
+    xpub_summary(&data, watched).await
+}
+
+pub async fn get_watched_xpub(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    let watched = sqlx::query_as::<_, WatchedXpub>("SELECT * FROM watched_xpubs WHERE id = $1")
+        .bind(id)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::NotFoundError(format!("xpub {}", id)))?;
+
+    xpub_summary(&data, watched).await
+}
+
+async fn xpub_summary(state: &AppState, watched: WatchedXpub) -> Result<HttpResponse, ServiceError> {
+    let chains = sqlx::query(
+        r#"
+        SELECT chain, COUNT(*) AS derived, COUNT(*) FILTER (WHERE used) AS used
+        FROM xpub_addresses
+        WHERE xpub_id = $1
+        GROUP BY chain
+        ORDER BY chain
+        "#
+    )
+    .bind(watched.id)
+    .fetch_all(&state.db)
+    .await
+    .map_err(db_error)?
+    .into_iter()
+    .map(|row| serde_json::json!({
+        "chain": row.get::<i16, _>("chain"),
+        "derived": row.get::<i64, _>("derived"),
+        "used": row.get::<i64, _>("used"),
+    }))
+    .collect::<Vec<_>>();
+
+    let next_receive_address = next_unused(state, watched.id, 0).await?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "xpub": watched,
+        "chains": chains,
+        "next_receive_address": next_receive_address
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // BIP32 test vector 2, master key, treated as an account xpub
+    const VECTOR2_M: &str = "xpub661MyMwAqRbcFW31YEwpkMuc5THy2PSt5bDMsktWQcFF8syAmRUapSCGu8ED9W6oDMSgv6Zz8idoc4a6mr8BDzTJY47LJhkJ8UB7WEGuduB";
+    const VECTOR2_M_0: &str = "xpub69H7F5d8KSRgmmdJg2KhpAK8SR3DjMwAdkxj3ZuxV27CprR9LgpeyGmXUbC6wb7ERfvrnKZjXoUmmDznezpbZb7ap6r1D3tgFxHmwMkQTPH";
+
+    #[test]
+    fn test_public_derivation_matches_bip32_vector() {
+        let master = ExtendedPubKey::parse(VECTOR2_M).unwrap();
+        let child = ExtendedPubKey::parse(VECTOR2_M_0).unwrap();
+        assert!(master.mainnet);
+        assert_eq!(master.derive_child(0).unwrap(), child);
+    }
+
+    #[test]
+    fn test_bip44_addresses() {
+        let account = ExtendedPubKey::parse(VECTOR2_M).unwrap();
+        let receive = Scheme::Bip44.chain_key(&account, 0).unwrap();
+        let change = Scheme::Bip44.chain_key(&account, 1).unwrap();
+
+        assert_eq!(receive.derive_child(0).unwrap().address(), "1NyMg76BQxDvV6vRsQugNS4ED2hpZCJtwK");
+        assert_eq!(receive.derive_child(1).unwrap().address(), "1NK8BtPi1AkfCwa4uuCb8fEtyYUg4NFC93");
+        assert_eq!(change.derive_child(0).unwrap().address(), "1Nfmbdfh2PqgYpkixYzQLLWK2xe7pRp4f");
+    }
+
+    #[test]
+    fn test_rejects_bad_keys() {
+        assert!(ExtendedPubKey::parse("not-an-xpub").is_err());
+        // Flip a character: checksum fails
+        let corrupted = VECTOR2_M.replacen('W', "X", 1);
+        assert!(ExtendedPubKey::parse(&corrupted).is_err());
+        assert!(ExtendedPubKey::parse(VECTOR2_M).unwrap().derive_child(HARDENED).is_err());
+    }
+
+    #[test]
+    fn test_gap_window() {
+        // Fresh key: derive 0..=19
+        assert_eq!(indexes_to_derive(-1, -1, 20), 0..=19);
+        // Index 5 used: extend to 25
+        assert_eq!(indexes_to_derive(19, 5, 20), 20..=25);
+        // Usage inside the existing window derives nothing
+        assert!(indexes_to_derive(25, 3, 20).is_empty());
+    }
+
+    #[test]
+    fn test_scheme_parse() {
+        assert_eq!(Scheme::parse("bip44").unwrap().chains(), &[0, 1]);
+        assert_eq!(Scheme::parse("single").unwrap().chains(), &[0]);
+        assert!(Scheme::parse("bip49").is_err());
+    }
+}
@@ -0,0 +1,169 @@
+// core/common/src/anchor.rs
+// OP_RETURN anchoring: builds, signs and broadcasts a data transaction carrying a commitment
+// hash, then fetches its SPV proof once mined
+
+use serde::Deserialize;
+
+use crate::JwtManager;
+
+#[derive(Debug, Deserialize)]
+struct AddressUtxo {
+    txid: String,
+    vout: u32,
+    value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct AddressUtxos {
+    utxos: Vec<AddressUtxo>,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuiltInput {
+    value: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuiltReservation {
+    reservation_id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuiltDataTransaction {
+    tx_hex: String,
+    inputs: Vec<BuiltInput>,
+    reservation: BuiltReservation,
+}
+
+#[derive(Debug, Deserialize)]
+struct SignedTransaction {
+    tx_hex: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Broadcast {
+    txid: String,
+}
+
+/// Anchors commitments through transaction-builder and blockchain-monitor, funded by an
+/// address whose key is stored in transaction-builder
+#[derive(Clone)]
+pub struct AnchorClient {
+    client: reqwest::Client,
+    builder_url: String,
+    monitor_url: String,
+    spv_url: String,
+    /// First push of every anchoring OP_RETURN
+    protocol_prefix: String,
+    /// Subject of the service token transaction-builder checks before signing
+    service: String,
+    jwt: JwtManager,
+    /// Funds the anchoring fee and receives the change
+    address: Option<String>,
+    /// Key stored in transaction-builder that signs for `address`
+    key_ref: Option<String>,
+}
+
+impl AnchorClient {
+    /// `TRANSACTION_BUILDER_URL`, `BLOCKCHAIN_MONITOR_URL`, `SPV_SERVICE_URL`, and the funding
+    /// address and key from `{env_prefix}_ADDRESS` and `{env_prefix}_KEY_REF`
+    pub fn from_env(env_prefix: &str, protocol_prefix: &str, service: &str, jwt: JwtManager) -> Self {
+        let var = |name: &str| std::env::var(name).ok().filter(|v| !v.is_empty());
+        let anchor = Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            builder_url: var("TRANSACTION_BUILDER_URL").unwrap_or_else(|| "http://localhost:8085".to_string()),
+            monitor_url: var("BLOCKCHAIN_MONITOR_URL").unwrap_or_else(|| "http://localhost:8084".to_string()),
+            spv_url: var("SPV_SERVICE_URL").unwrap_or_else(|| "http://localhost:8086".to_string()),
+            protocol_prefix: protocol_prefix.to_string(),
+            service: service.to_string(),
+            jwt,
+            address: var(&format!("{}_ADDRESS", env_prefix)),
+            key_ref: var(&format!("{}_KEY_REF", env_prefix)),
+        };
+        if anchor.address.is_none() || anchor.key_ref.is_none() {
+            tracing::warn!("{0}_ADDRESS or {0}_KEY_REF not set, {1} commitments will not be anchored", env_prefix, protocol_prefix);
+        }
+        anchor
+    }
+
+    pub fn protocol_prefix(&self) -> &str {
+        &self.protocol_prefix
+    }
+
+    async fn post<T: serde::de::DeserializeOwned>(&self, url: String, body: &serde_json::Value) -> Result<T, String> {
+        let token = self.jwt
+            .create_service_token(&self.service)
+            .map_err(|e| format!("Token error: {}", e))?;
+        let response = self.client
+            .post(url)
+            .bearer_auth(token)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        response.json().await.map_err(|e| format!("Parse error: {}", e))
+    }
+
+    /// Write `commitment` (hex) and `reference` in an OP_RETURN and broadcast it; returns the txid
+    pub async fn anchor(&self, commitment: &str, reference: &str) -> Result<String, String> {
+        let (Some(address), Some(key_ref)) = (&self.address, &self.key_ref) else {
+            return Err("Anchoring address or key not configured".to_string());
+        };
+
+        let utxos: AddressUtxos = self.client
+            .get(format!("{}/address/{}/utxos", self.monitor_url, address))
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Parse error: {}", e))?;
+        let utxos: Vec<_> = utxos.utxos
+            .iter()
+            .map(|u| serde_json::json!({ "txid": u.txid, "vout": u.vout, "satoshis": u.value }))
+            .collect();
+
+        let built: BuiltDataTransaction = self.post(format!("{}/tx/build/data", self.builder_url), &serde_json::json!({
+            "protocol_prefix": { "value": self.protocol_prefix },
+            "data": [
+                { "encoding": "hex", "value": commitment },
+                { "value": reference }
+            ],
+            "utxos": utxos,
+            "change_address": address,
+            "fee_policy": "data"
+        })).await?;
+
+        let inputs: Vec<_> = built.inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| serde_json::json!({ "index": index, "value": input.value, "key_ref": key_ref }))
+            .collect();
+        let signed: SignedTransaction = self.post(format!("{}/tx/sign", self.builder_url), &serde_json::json!({
+            "tx_hex": built.tx_hex,
+            "inputs": inputs
+        })).await?;
+
+        let broadcast: Broadcast = self.post(format!("{}/broadcast", self.monitor_url), &serde_json::json!({
+            "tx_hex": signed.tx_hex
+        })).await?;
+
+        // Hand the inputs over to the broadcast transaction; they stay spent either way
+        let reservation = format!("{}/utxos/reservations/{}/broadcast", self.builder_url, built.reservation.reservation_id);
+        if let Err(e) = self.post::<serde_json::Value>(reservation, &serde_json::json!({ "spending_txid": broadcast.txid })).await {
+            tracing::warn!("Failed to mark {} reservation spent by {}: {}", self.protocol_prefix, broadcast.txid, e);
+        }
+        Ok(broadcast.txid)
+    }
+
+    /// `POST /verify/merkle-proof` on spv-service; fails until the transaction is mined
+    pub async fn proof(&self, txid: &str) -> Result<serde_json::Value, String> {
+        self.post(format!("{}/verify/merkle-proof", self.spv_url), &serde_json::json!({ "txid": txid })).await
+    }
+}
@@ -1,6 +1,7 @@
 // core/common/src/auth.rs
 // JWT Authentication and Authorization
 
+use actix_web::{HttpMessage, HttpRequest};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation}; // Algorithm, 
 use serde::{Deserialize, Serialize};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -16,10 +17,18 @@ pub enum AuthError {
     MissingAuth,
     #[error("Insufficient permissions")]
     InsufficientPermissions,
+    #[error("Token is not authorized for {0}")]
+    NotAuthorizedFor(String),
     #[error("JWT error: {0}")]
     JwtError(#[from] jsonwebtoken::errors::Error),
 }
 
+/// Permission carried by tokens services mint for calling each other's internal APIs
+pub const SERVICE_PERMISSION: &str = "service";
+
+/// Permission letting support staff act on any user's account; `admin` implies it
+pub const OPERATOR_PERMISSION: &str = "operator";
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,                    // Subject (paymail)
@@ -86,6 +95,11 @@ impl JwtManager {
         Ok(token)
     }
 
+    /// Short-lived token identifying `service` to a sibling service's internal API
+    pub fn create_service_token(&self, service: &str) -> Result<String, AuthError> {
+        self.create_token(service, vec![SERVICE_PERMISSION.to_string()], 1)
+    }
+
     // // Chatgpt attempt - aborted
     // pub fn create_token_with_expiration(
     //     &self,
@@ -159,6 +173,24 @@ pub fn extract_bearer_token(auth_header: &str) -> Result<String, AuthError> {
     Ok(auth_header.trim_start_matches("Bearer ").to_string())
 }
 
+/// Require a bearer token issued to `paymail`, or one with the `operator` permission.
+/// The paymail in a request body or path is never trusted on its own; the claims are
+/// the ones `AuthMiddleware` verified.
+pub fn require_paymail(http_req: &HttpRequest, paymail: &str) -> Result<(), AuthError> {
+    let extensions = http_req.extensions();
+    let Some(claims) = extensions.get::<Claims>() else {
+        return Err(AuthError::MissingAuth);
+    };
+    if claims.sub == paymail {
+        return Ok(());
+    }
+    if claims.has_permission(OPERATOR_PERMISSION) {
+        tracing::info!("Operator {} acting on behalf of {}: {} {}", claims.sub, paymail, http_req.method(), http_req.path());
+        return Ok(());
+    }
+    Err(AuthError::NotAuthorizedFor(paymail.to_string()))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,4 +358,16 @@ mod tests {
         assert_eq!(claims.sub, "test@bsvbank.local");
         assert_eq!(claims.permissions, vec!["read".to_string()]);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_service_token() {
+        let manager = JwtManager::new("test-secret-key".to_string());
+        
+        let token = manager.create_service_token("deposit-service").unwrap();
+        let claims = manager.verify_token(&token).unwrap();
+        
+        assert_eq!(claims.sub, "deposit-service");
+        assert!(claims.has_permission(SERVICE_PERMISSION));
+        assert!(!claims.has_permission("admin"));
+    }
+}
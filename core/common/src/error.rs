@@ -161,6 +161,7 @@ impl From<crate::auth::AuthError> for ServiceError {
             crate::auth::AuthError::TokenExpired => ServiceError::Unauthorized,
             crate::auth::AuthError::MissingAuth => ServiceError::Unauthorized,
             crate::auth::AuthError::InsufficientPermissions => ServiceError::Forbidden,
+            crate::auth::AuthError::NotAuthorizedFor(_) => ServiceError::Forbidden,
             crate::auth::AuthError::JwtError(_) => ServiceError::Unauthorized,
         }
     }
@@ -0,0 +1,201 @@
+// core/common/src/events.rs
+// Domain event publishing to a message bus (NATS JetStream or Kafka), shared by every service
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum EventBusError {
+    #[error("Event bus configuration error: {0}")]
+    Config(String),
+    #[error("Event bus connection failed: {0}")]
+    Connection(String),
+    #[error("Event publish failed: {0}")]
+    Publish(String),
+}
+
+/// Wire format of every event on the bus
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventEnvelope {
+    /// Stable across redeliveries; consumers deduplicate on it
+    pub id: Uuid,
+    /// Publishing service, e.g. `blockchain-monitor`
+    pub source: String,
+    /// e.g. `tx_seen`
+    pub event_type: String,
+    /// Ordering key, e.g. the txid; events sharing a key go to the same partition
+    pub key: String,
+    pub occurred_at: DateTime<Utc>,
+    pub payload: serde_json::Value,
+}
+
+impl EventEnvelope {
+    /// `<prefix>.<source>.<event_type>`: the NATS subject, and what consumers filter on
+    pub fn subject(&self, prefix: &str) -> String {
+        format!("{}.{}.{}", prefix, self.source, self.event_type)
+    }
+
+    /// `<prefix>.<source>`: one Kafka topic per publishing service, keyed for ordering
+    pub fn topic(&self, prefix: &str) -> String {
+        format!("{}.{}", prefix, self.source)
+    }
+}
+
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Returns once the bus has acknowledged the event
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<(), EventBusError>;
+}
+
+/// Subject/topic prefix, from `EVENT_BUS_PREFIX`
+fn prefix() -> String {
+    std::env::var("EVENT_BUS_PREFIX").ok().filter(|v| !v.is_empty()).unwrap_or_else(|| "bsvbank".to_string())
+}
+
+/// Build the publisher selected by `EVENT_BUS` (`nats`, `kafka` or `log`); None when unset or `none`
+pub async fn publisher_from_env() -> Result<Option<Box<dyn EventPublisher>>, EventBusError> {
+    let bus = std::env::var("EVENT_BUS").unwrap_or_default().to_lowercase();
+    match bus.as_str() {
+        "" | "none" => Ok(None),
+        "log" => Ok(Some(Box::new(LogPublisher { prefix: prefix() }))),
+        #[cfg(feature = "nats")]
+        "nats" => {
+            let url = std::env::var("NATS_URL").unwrap_or_else(|_| "nats://127.0.0.1:4222".to_string());
+            Ok(Some(Box::new(nats::NatsPublisher::connect(&url, prefix()).await?)))
+        }
+        #[cfg(feature = "kafka")]
+        "kafka" => {
+            let brokers = std::env::var("KAFKA_BROKERS").unwrap_or_else(|_| "127.0.0.1:9092".to_string());
+            Ok(Some(Box::new(kafka::KafkaPublisher::new(&brokers, prefix())?)))
+        }
+        other => Err(EventBusError::Config(format!(
+            "EVENT_BUS={} is not supported by this build (enable the matching cargo feature)",
+            other
+        ))),
+    }
+}
+
+/// Logs events instead of sending them; for local development without a broker
+pub struct LogPublisher {
+    prefix: String,
+}
+
+#[async_trait]
+impl EventPublisher for LogPublisher {
+    fn name(&self) -> &'static str {
+        "log"
+    }
+
+    async fn publish(&self, envelope: &EventEnvelope) -> Result<(), EventBusError> {
+        tracing::info!(event_id = %envelope.id, key = %envelope.key, "{}", envelope.subject(&self.prefix));
+        Ok(())
+    }
+}
+
+#[cfg(feature = "nats")]
+mod nats {
+    use super::*;
+
+    /// Publishes to JetStream so the event is stored by the server before it is acknowledged
+    pub struct NatsPublisher {
+        jetstream: async_nats::jetstream::Context,
+        prefix: String,
+    }
+
+    impl NatsPublisher {
+        pub async fn connect(url: &str, prefix: String) -> Result<Self, EventBusError> {
+            let client = async_nats::connect(url)
+                .await
+                .map_err(|e| EventBusError::Connection(e.to_string()))?;
+            Ok(Self { jetstream: async_nats::jetstream::new(client), prefix })
+        }
+    }
+
+    #[async_trait]
+    impl EventPublisher for NatsPublisher {
+        fn name(&self) -> &'static str {
+            "nats"
+        }
+
+        async fn publish(&self, envelope: &EventEnvelope) -> Result<(), EventBusError> {
+            let body = serde_json::to_vec(envelope).map_err(|e| EventBusError::Publish(e.to_string()))?;
+            // JetStream drops a redelivered id inside its duplicate window
+            let mut headers = async_nats::HeaderMap::new();
+            headers.insert("Nats-Msg-Id", envelope.id.to_string().as_str());
+
+            self.jetstream
+                .publish_with_headers(envelope.subject(&self.prefix), headers, body.into())
+                .await
+                .map_err(|e| EventBusError::Publish(e.to_string()))?
+                .await
+                .map_err(|e| EventBusError::Publish(e.to_string()))?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(feature = "kafka")]
+mod kafka {
+    use super::*;
+    use rdkafka::config::ClientConfig;
+    use rdkafka::producer::{FutureProducer, FutureRecord};
+    use std::time::Duration;
+
+    pub struct KafkaPublisher {
+        producer: FutureProducer,
+        prefix: String,
+    }
+
+    impl KafkaPublisher {
+        pub fn new(brokers: &str, prefix: String) -> Result<Self, EventBusError> {
+            let producer = ClientConfig::new()
+                .set("bootstrap.servers", brokers)
+                .set("enable.idempotence", "true")
+                .set("message.timeout.ms", "10000")
+                .create()
+                .map_err(|e| EventBusError::Connection(e.to_string()))?;
+            Ok(Self { producer, prefix })
+        }
+    }
+
+    #[async_trait]
+    impl EventPublisher for KafkaPublisher {
+        fn name(&self) -> &'static str {
+            "kafka"
+        }
+
+        async fn publish(&self, envelope: &EventEnvelope) -> Result<(), EventBusError> {
+            let body = serde_json::to_vec(envelope).map_err(|e| EventBusError::Publish(e.to_string()))?;
+            let topic = envelope.topic(&self.prefix);
+            self.producer
+                .send(FutureRecord::to(&topic).key(&envelope.key).payload(&body), Duration::from_secs(10))
+                .await
+                .map_err(|(e, _)| EventBusError::Publish(e.to_string()))?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_routing() {
+        let envelope = EventEnvelope {
+            id: Uuid::new_v4(),
+            source: "blockchain-monitor".to_string(),
+            event_type: "tx_seen".to_string(),
+            key: "ab".repeat(32),
+            occurred_at: Utc::now(),
+            payload: serde_json::json!({}),
+        };
+        assert_eq!(envelope.subject("bsvbank"), "bsvbank.blockchain-monitor.tx_seen");
+        assert_eq!(envelope.topic("bsvbank"), "bsvbank.blockchain-monitor");
+    }
+}
@@ -0,0 +1,130 @@
+// core/common/src/ledger.rs
+// Balanced transfers in the shared ledger behind deposit balances, used by every service that
+// moves user funds
+
+use thiserror::Error;
+use uuid::Uuid;
+
+#[derive(Debug, Error)]
+pub enum LedgerError {
+    #[error("Insufficient available balance for {paymail}: {available} available, {required} required")]
+    InsufficientFunds {
+        paymail: String,
+        available: i64,
+        required: i64,
+    },
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Password hash of a user row that cannot log in until the password is reset
+const NO_PASSWORD_HASH: &str = "INVALID_HASH_MUST_BE_SET_VIA_PASSWORD_RESET_000000000000000";
+
+/// Paymails are user accounts; internal accounts carry a `kind:` prefix
+pub fn is_user_account(account: &str) -> bool {
+    account.contains('@')
+}
+
+/// Serialize debits of `paymail` until the transaction ends; safe to take more than once
+pub async fn lock_account(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    paymail: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))")
+        .bind(paymail)
+        .execute(&mut **db_tx)
+        .await?;
+    Ok(())
+}
+
+/// Fail unless `paymail` has `amount` available, net of held funds. Locks the account first
+/// so concurrent debits see each other's entries.
+pub async fn ensure_available(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    paymail: &str,
+    amount: i64,
+) -> Result<(), LedgerError> {
+    lock_account(db_tx, paymail).await?;
+    let available: Option<i64> = sqlx::query_scalar(
+        "SELECT available_satoshis::BIGINT FROM user_balances WHERE paymail = $1"
+    )
+    .bind(paymail)
+    .fetch_optional(&mut **db_tx)
+    .await?;
+
+    let available = available.unwrap_or(0);
+    if available < amount {
+        return Err(LedgerError::InsufficientFunds {
+            paymail: paymail.to_string(),
+            available,
+            required: amount,
+        });
+    }
+    Ok(())
+}
+
+/// Move `amount` from one account to another as a debit and a credit sharing a transfer id,
+/// tagged with the loan they settle, if any
+pub async fn transfer(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    entry_type: &str,
+    from: &str,
+    to: &str,
+    amount: i64,
+    loan_id: Option<Uuid>,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    if amount <= 0 {
+        return Ok(None);
+    }
+    // Balances are reported per user, so a credited paymail needs a users row. One created
+    // here has no password until it is reset, like the accounts migration 007 backfilled.
+    if is_user_account(to) {
+        sqlx::query("INSERT INTO users (paymail, password_hash) VALUES ($1, $2) ON CONFLICT (paymail) DO NOTHING")
+            .bind(to)
+            .bind(NO_PASSWORD_HASH)
+            .execute(&mut **db_tx)
+            .await?;
+    }
+
+    let transfer_id = Uuid::new_v4();
+    sqlx::query(
+        r#"
+        INSERT INTO ledger_entries (transfer_id, account, amount_satoshis, entry_type, loan_id)
+        VALUES ($1, $2, $3, $5, $6), ($1, $4, -$3, $5, $6)
+        "#
+    )
+    .bind(transfer_id)
+    .bind(to)
+    .bind(amount)
+    .bind(from)
+    .bind(entry_type)
+    .bind(loan_id)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(Some(transfer_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_internal_accounts_are_not_users() {
+        assert!(is_user_account("alice@handcash.io"));
+        assert!(!is_user_account("fees:deposits"));
+        assert!(!is_user_account("loan:00000000-0000-0000-0000-000000000000"));
+    }
+
+    #[test]
+    fn test_insufficient_funds_message() {
+        let e = LedgerError::InsufficientFunds {
+            paymail: "alice@handcash.io".to_string(),
+            available: 5,
+            required: 10,
+        };
+        assert_eq!(
+            e.to_string(),
+            "Insufficient available balance for alice@handcash.io: 5 available, 10 required"
+        );
+    }
+}
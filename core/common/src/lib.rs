@@ -1,19 +1,27 @@
 // core/common/src/lib.rs
 // BSV Bank Common Library - Shared functionality across all services
 
+pub mod anchor;
 pub mod auth;
 pub mod validation;
 pub mod rate_limit;
 pub mod health;
+pub mod ledger;
 pub mod logging;
 pub mod metrics;
 pub mod error;
 pub mod middleware;
+pub mod webhook;
+pub mod two_factor;
+pub mod events;
+pub mod scheduler;
+pub mod transaction;
 
 // Re-export commonly used items
-pub use auth::{AuthError, Claims, JwtManager};
+pub use anchor::AnchorClient;
+pub use auth::{require_paymail, AuthError, Claims, JwtManager, OPERATOR_PERMISSION, SERVICE_PERMISSION};
 pub use validation::{
-    validate_address, validate_amount, validate_paymail, validate_txid, 
+    is_internal_ip, validate_address, validate_amount, validate_paymail, validate_txid, validate_url,
     validate_no_xss, validate_no_sql_injection, validate_max_length, ValidationError,
 };
 pub use rate_limit::{RateLimit, RateLimiter, RateLimitError, RateLimitInfo, start_cleanup_task};
@@ -30,7 +38,12 @@ pub use metrics::{
     // http_request_counter, http_request_duration ( should now be part of ServiceMetrcs(?) ) 
 };
 pub use error::{ErrorResponse, ServiceError};
-pub use middleware::{RateLimitMiddleware, configure_rate_limits};
+pub use ledger::LedgerError;
+pub use middleware::{AuthMiddleware, RateLimitMiddleware, configure_rate_limits};
+pub use webhook::{generate_webhook_secret, sign_payload, verify_signature};
+pub use two_factor::{generate_totp_secret, otpauth_uri, verify_totp};
+pub use events::{publisher_from_env, EventBusError, EventEnvelope, EventPublisher};
+pub use scheduler::{interval_from_env, run_every};
 
 #[cfg(test)]
 mod tests {
@@ -167,14 +167,21 @@ impl Default for MetricsTimer {
 }
 
 /// Deposit service specific metrics
+#[derive(Clone)]
 pub struct DepositMetrics {
     pub deposits_total: IntCounterVec,
     pub deposits_amount_satoshis: IntCounterVec,
     pub withdrawals_total: IntCounterVec,
     pub withdrawals_amount_satoshis: IntCounterVec,
     pub active_deposits: IntGauge,
+    pub time_to_confirmation_seconds: Histogram,
 }
 
+/// SPV proofs arrive within a block or two, but can lag by hours
+const CONFIRMATION_BUCKETS: [f64; 10] = [
+    1.0, 60.0, 300.0, 600.0, 1_200.0, 1_800.0, 3_600.0, 7_200.0, 21_600.0, 86_400.0,
+];
+
 impl DepositMetrics {
     pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
         let deposits_total = IntCounterVec::new(
@@ -207,24 +214,72 @@ impl DepositMetrics {
         )?;
         registry.register(Box::new(active_deposits.clone()))?;
         
+        let time_to_confirmation_seconds = Histogram::with_opts(
+            HistogramOpts::new("deposit_time_to_confirmation_seconds", "Time from deposit submission to SPV confirmation")
+                .buckets(CONFIRMATION_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(time_to_confirmation_seconds.clone()))?;
+        
         Ok(Self {
             deposits_total,
             deposits_amount_satoshis,
             withdrawals_total,
             withdrawals_amount_satoshis,
             active_deposits,
+            time_to_confirmation_seconds,
         })
     }
+    
+    /// Record a deposit reaching `status` (Pending, Confirmed, ...)
+    pub fn record_deposit_status(&self, status: &str, amount_satoshis: i64) {
+        self.deposits_total
+            .with_label_values(&[status])
+            .inc();
+        if amount_satoshis > 0 {
+            self.deposits_amount_satoshis
+                .with_label_values(&[status])
+                .inc_by(amount_satoshis as u64);
+        }
+    }
+    
+    /// Record a withdrawal reaching `status` (PendingApproval, Pending, Broadcast, Confirmed, Rejected, ...)
+    pub fn record_withdrawal_status(&self, status: &str, amount_satoshis: i64) {
+        self.withdrawals_total
+            .with_label_values(&[status])
+            .inc();
+        if amount_satoshis > 0 {
+            self.withdrawals_amount_satoshis
+                .with_label_values(&[status])
+                .inc_by(amount_satoshis as u64);
+        }
+    }
+    
+    /// Set the active deposit gauge from an authoritative count
+    pub fn set_active_deposits(&self, count: i64) {
+        self.active_deposits.set(count);
+    }
+    
+    pub fn observe_time_to_confirmation(&self, seconds: f64) {
+        self.time_to_confirmation_seconds.observe(seconds.max(0.0));
+    }
 }
 
 /// Lending service specific metrics
+#[derive(Clone)]
 pub struct LendingMetrics {
     pub loans_total: IntCounterVec,
     pub loans_amount_satoshis: IntCounterVec,
     pub active_loans: IntGauge,
     pub liquidations_total: IntCounter,
+    pub time_to_fund_seconds: Histogram,
+    pub time_to_repay_seconds: Histogram,
 }
 
+/// Loan lifetimes run from minutes to a year
+const LOAN_DURATION_BUCKETS: [f64; 10] = [
+    60.0, 600.0, 3_600.0, 21_600.0, 86_400.0, 259_200.0, 604_800.0, 2_592_000.0, 7_776_000.0, 31_536_000.0,
+];
+
 impl LendingMetrics {
     pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
         let loans_total = IntCounterVec::new(
@@ -251,16 +306,56 @@ impl LendingMetrics {
         )?;
         registry.register(Box::new(liquidations_total.clone()))?;
         
+        let time_to_fund_seconds = Histogram::with_opts(
+            HistogramOpts::new("loan_time_to_fund_seconds", "Time from loan request to full funding")
+                .buckets(LOAN_DURATION_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(time_to_fund_seconds.clone()))?;
+        
+        let time_to_repay_seconds = Histogram::with_opts(
+            HistogramOpts::new("loan_time_to_repay_seconds", "Time from funding to full repayment")
+                .buckets(LOAN_DURATION_BUCKETS.to_vec()),
+        )?;
+        registry.register(Box::new(time_to_repay_seconds.clone()))?;
+        
         Ok(Self {
             loans_total,
             loans_amount_satoshis,
             active_loans,
             liquidations_total,
+            time_to_fund_seconds,
+            time_to_repay_seconds,
         })
     }
+    
+    /// Record a loan reaching `status` (Pending, Active, Repaid, Liquidated, ...)
+    pub fn record_loan_status(&self, status: &str, amount_satoshis: i64) {
+        self.loans_total
+            .with_label_values(&[status])
+            .inc();
+        if amount_satoshis > 0 {
+            self.loans_amount_satoshis
+                .with_label_values(&[status])
+                .inc_by(amount_satoshis as u64);
+        }
+    }
+    
+    /// Set the active loan gauge from an authoritative count
+    pub fn set_active_loans(&self, count: i64) {
+        self.active_loans.set(count);
+    }
+    
+    pub fn observe_time_to_fund(&self, seconds: f64) {
+        self.time_to_fund_seconds.observe(seconds.max(0.0));
+    }
+    
+    pub fn observe_time_to_repay(&self, seconds: f64) {
+        self.time_to_repay_seconds.observe(seconds.max(0.0));
+    }
 }
 
 /// Payment channel specific metrics
+#[derive(Clone)]
 pub struct ChannelMetrics {
     pub channels_total: IntCounterVec,
     pub channel_payments_total: IntCounter,
@@ -301,6 +396,26 @@ impl ChannelMetrics {
             active_channels,
         })
     }
+    
+    /// Record a channel lifecycle transition (Open, Closed, Disputed, ...)
+    pub fn record_channel_status(&self, status: &str) {
+        self.channels_total
+            .with_label_values(&[status])
+            .inc();
+    }
+    
+    /// Record a successful payment through a channel
+    pub fn record_payment(&self, amount_satoshis: i64) {
+        self.channel_payments_total.inc();
+        if amount_satoshis > 0 {
+            self.channel_payments_amount_satoshis.inc_by(amount_satoshis as u64);
+        }
+    }
+    
+    /// Set the active channel gauge from an authoritative count
+    pub fn set_active_channels(&self, count: i64) {
+        self.active_channels.set(count);
+    }
 }
 
 #[cfg(test)]
@@ -377,6 +492,64 @@ mod tests {
         assert!(metrics.is_ok());
     }
     
+    #[test]
+    fn test_lending_metrics_recording() {
+        let registry = Registry::new();
+        let metrics = LendingMetrics::new(&registry).unwrap();
+        
+        metrics.record_loan_status("Pending", 100_000);
+        metrics.record_loan_status("Active", 100_000);
+        metrics.record_loan_status("Pending", 50_000);
+        metrics.set_active_loans(3);
+        metrics.observe_time_to_fund(3_600.0);
+        metrics.observe_time_to_repay(-1.0);
+        
+        assert_eq!(metrics.loans_total.with_label_values(&["Pending"]).get(), 2);
+        assert_eq!(metrics.loans_amount_satoshis.with_label_values(&["Pending"]).get(), 150_000);
+        assert_eq!(metrics.active_loans.get(), 3);
+        assert_eq!(metrics.time_to_fund_seconds.get_sample_count(), 1);
+        assert_eq!(metrics.time_to_repay_seconds.get_sample_sum(), 0.0);
+    }
+    
+    #[test]
+    fn test_deposit_metrics_recording() {
+        let registry = Registry::new();
+        let metrics = DepositMetrics::new(&registry).unwrap();
+        
+        metrics.record_deposit_status("Pending", 200_000);
+        metrics.record_deposit_status("Confirmed", 200_000);
+        metrics.record_withdrawal_status("Pending", 50_000);
+        metrics.record_withdrawal_status("Pending", 0);
+        metrics.set_active_deposits(4);
+        metrics.observe_time_to_confirmation(600.0);
+        metrics.observe_time_to_confirmation(-5.0);
+        
+        assert_eq!(metrics.deposits_total.with_label_values(&["Confirmed"]).get(), 1);
+        assert_eq!(metrics.deposits_amount_satoshis.with_label_values(&["Pending"]).get(), 200_000);
+        assert_eq!(metrics.withdrawals_total.with_label_values(&["Pending"]).get(), 2);
+        assert_eq!(metrics.withdrawals_amount_satoshis.with_label_values(&["Pending"]).get(), 50_000);
+        assert_eq!(metrics.active_deposits.get(), 4);
+        assert_eq!(metrics.time_to_confirmation_seconds.get_sample_count(), 2);
+        assert_eq!(metrics.time_to_confirmation_seconds.get_sample_sum(), 600.0);
+    }
+    
+    #[test]
+    fn test_channel_metrics_recording() {
+        let registry = Registry::new();
+        let metrics = ChannelMetrics::new(&registry).unwrap();
+        
+        metrics.record_channel_status("Open");
+        metrics.record_channel_status("Open");
+        metrics.record_payment(1500);
+        metrics.record_payment(0);
+        metrics.set_active_channels(7);
+        
+        assert_eq!(metrics.channels_total.with_label_values(&["Open"]).get(), 2);
+        assert_eq!(metrics.channel_payments_total.get(), 2);
+        assert_eq!(metrics.channel_payments_amount_satoshis.get(), 1500);
+        assert_eq!(metrics.active_channels.get(), 7);
+    }
+    
     #[test]
     fn test_multiple_metrics_registration() {
         let registry = Registry::new();
@@ -2,13 +2,16 @@
 
 use actix_web::{
     dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform}, 
-    Error, // HttpResponse,
+    http::Method,
+    Error, HttpMessage, // HttpResponse,
 };
 use futures_util::future::LocalBoxFuture;
 use std::future::{ready, Ready};
+use std::rc::Rc;
 use std::sync::Arc;
+use crate::auth::{extract_bearer_token, JwtManager};
 use crate::rate_limit::{RateLimiter, RateLimitError};
-// use crate::error::ServiceError;
+use crate::error::ServiceError;
 
 pub struct RateLimitMiddleware {
     limiter: Arc<RateLimiter>,
@@ -115,4 +118,184 @@ pub fn configure_rate_limits(limiter: &mut RateLimiter) {
     // Stricter limits for sensitive operations
     limiter.add_limit("/api/withdraw".to_string(), RateLimit::per_minute(5));
     limiter.add_limit("/api/admin".to_string(), RateLimit::per_minute(20));
-}
\ No newline at end of file
+}
+
+/// Decides from the method and path whether a route may be called without a token
+pub type PublicRoute = fn(&Method, &str) -> bool;
+
+/// Verifies the bearer token on every request and requires one on every route `is_public`
+/// does not accept. Verified claims are stored in the request extensions, where handlers
+/// and `auth::require_paymail` read them; a bad token on a public route is ignored.
+pub struct AuthMiddleware {
+    jwt_manager: Rc<JwtManager>,
+    is_public: PublicRoute,
+}
+
+impl AuthMiddleware {
+    pub fn new(jwt_manager: JwtManager, is_public: PublicRoute) -> Self {
+        Self {
+            jwt_manager: Rc::new(jwt_manager),
+            is_public,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AuthMiddleware
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type InitError = ();
+    type Transform = AuthMiddlewareService<S>;
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthMiddlewareService {
+            service: Rc::new(service),
+            jwt_manager: self.jwt_manager.clone(),
+            is_public: self.is_public,
+        }))
+    }
+}
+
+pub struct AuthMiddlewareService<S> {
+    service: Rc<S>,
+    jwt_manager: Rc<JwtManager>,
+    is_public: PublicRoute,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthMiddlewareService<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let jwt_manager = self.jwt_manager.clone();
+        let service = self.service.clone();
+        let public = (self.is_public)(req.method(), req.path());
+
+        Box::pin(async move {
+            let claims = req
+                .headers()
+                .get(actix_web::http::header::AUTHORIZATION)
+                .and_then(|h| h.to_str().ok())
+                .map(|header| extract_bearer_token(header).and_then(|token| jwt_manager.verify_token(&token)));
+
+            match claims {
+                Some(Ok(claims)) => {
+                    req.extensions_mut().insert(claims);
+                }
+                Some(Err(e)) if !public => return Err(ServiceError::from(e).into()),
+                None if !public => return Err(ServiceError::Unauthorized.into()),
+                _ => {}
+            }
+
+            service.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::{require_paymail, OPERATOR_PERMISSION};
+    use actix_web::http::StatusCode;
+    use actix_web::{test, web, App, HttpRequest, HttpResponse};
+
+    const SECRET: &str = "test-secret";
+    const BORROWER: &str = "borrower@example.com";
+
+    fn reads_are_public(method: &Method, path: &str) -> bool {
+        method == Method::GET || path == "/login"
+    }
+
+    async fn act_for_borrower(http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+        require_paymail(&http_req, BORROWER)?;
+        Ok(HttpResponse::Ok().finish())
+    }
+
+    fn token(sub: &str, permissions: &[&str]) -> String {
+        JwtManager::new(SECRET.to_string())
+            .create_token(sub, permissions.iter().map(|p| p.to_string()).collect(), 1)
+            .unwrap()
+    }
+
+    macro_rules! app {
+        () => {
+            test::init_service(
+                App::new()
+                    .wrap(AuthMiddleware::new(JwtManager::new(SECRET.to_string()), reads_are_public))
+                    .route("/loans/{id}/repay", web::post().to(act_for_borrower))
+                    .route("/login", web::post().to(HttpResponse::Ok))
+                    .route("/loans/available", web::get().to(HttpResponse::Ok)),
+            )
+            .await
+        };
+    }
+
+    #[actix_web::test]
+    async fn test_public_routes_need_no_token() {
+        let app = app!();
+        let resp = test::call_service(&app, test::TestRequest::get().uri("/loans/available").to_request()).await;
+        assert!(resp.status().is_success());
+        let resp = test::call_service(&app, test::TestRequest::post().uri("/login").to_request()).await;
+        assert!(resp.status().is_success());
+
+        let bad_token = test::TestRequest::post()
+            .uri("/login")
+            .insert_header(("Authorization", "Bearer not-a-token"))
+            .to_request();
+        assert!(test::call_service(&app, bad_token).await.status().is_success());
+    }
+
+    #[actix_web::test]
+    async fn test_missing_or_invalid_token_is_rejected() {
+        let app = app!();
+        let err = test::try_call_service(&app, test::TestRequest::post().uri("/loans/1/repay").to_request())
+            .await
+            .expect_err("request without a token was accepted");
+        assert_eq!(err.error_response().status(), StatusCode::UNAUTHORIZED);
+
+        let bad_token = test::TestRequest::post()
+            .uri("/loans/1/repay")
+            .insert_header(("Authorization", "Bearer not-a-token"))
+            .to_request();
+        let err = test::try_call_service(&app, bad_token)
+            .await
+            .expect_err("request with an invalid token was accepted");
+        assert_eq!(err.error_response().status(), StatusCode::UNAUTHORIZED);
+    }
+
+    #[actix_web::test]
+    async fn test_token_must_match_paymail() {
+        let app = app!();
+        let own = test::TestRequest::post()
+            .uri("/loans/1/repay")
+            .insert_header(("Authorization", format!("Bearer {}", token(BORROWER, &["read"]))))
+            .to_request();
+        assert!(test::call_service(&app, own).await.status().is_success());
+
+        let other = test::TestRequest::post()
+            .uri("/loans/1/repay")
+            .insert_header(("Authorization", format!("Bearer {}", token("mallory@example.com", &["read"]))))
+            .to_request();
+        let resp = test::call_service(&app, other).await;
+        assert_eq!(resp.status(), StatusCode::FORBIDDEN);
+
+        let operator = test::TestRequest::post()
+            .uri("/loans/1/repay")
+            .insert_header(("Authorization", format!("Bearer {}", token("support@example.com", &[OPERATOR_PERMISSION]))))
+            .to_request();
+        assert!(test::call_service(&app, operator).await.status().is_success());
+    }
+}
@@ -0,0 +1,73 @@
+// core/common/src/scheduler.rs
+// Periodic background jobs: a fixed interval read from the environment, failures logged and retried next tick
+
+use std::fmt::Display;
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Interval in seconds from `var`, or `default_secs` when unset, unparseable or zero
+pub fn interval_from_env(var: &str, default_secs: u64) -> Duration {
+    let secs = std::env::var(var)
+        .ok()
+        .and_then(|s| s.trim().parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .unwrap_or(default_secs);
+    Duration::from_secs(secs)
+}
+
+/// Run `job` now and then every `period`, forever. A tick missed because the previous
+/// run overran is delayed rather than fired in a burst, so runs never overlap.
+pub async fn run_every<F, Fut, T, E>(name: &str, period: Duration, mut job: F)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let mut interval = tokio::time::interval(period);
+    interval.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+    tracing::info!("Scheduled job {} every {}s", name, period.as_secs());
+
+    loop {
+        interval.tick().await;
+        let started = Instant::now();
+        match job().await {
+            Ok(_) => tracing::debug!("Job {} finished in {}ms", name, started.elapsed().as_millis()),
+            Err(e) => tracing::error!("Job {} failed: {}", name, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interval_from_env() {
+        std::env::set_var("SCHEDULER_TEST_INTERVAL_SECS", "30");
+        assert_eq!(interval_from_env("SCHEDULER_TEST_INTERVAL_SECS", 60), Duration::from_secs(30));
+
+        // Zero would spin; garbage falls back to the default
+        std::env::set_var("SCHEDULER_TEST_INTERVAL_SECS", "0");
+        assert_eq!(interval_from_env("SCHEDULER_TEST_INTERVAL_SECS", 60), Duration::from_secs(60));
+        std::env::set_var("SCHEDULER_TEST_INTERVAL_SECS", "soon");
+        assert_eq!(interval_from_env("SCHEDULER_TEST_INTERVAL_SECS", 60), Duration::from_secs(60));
+
+        assert_eq!(interval_from_env("SCHEDULER_TEST_UNSET_SECS", 45), Duration::from_secs(45));
+    }
+
+    #[tokio::test]
+    async fn test_run_every_keeps_running_after_failures() {
+        let runs = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = runs.clone();
+        let job = run_every("test", Duration::from_millis(10), move || {
+            let counter = counter.clone();
+            async move {
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                Err::<(), _>("always fails")
+            }
+        });
+
+        let _ = tokio::time::timeout(Duration::from_millis(100), job).await;
+        assert!(runs.load(std::sync::atomic::Ordering::SeqCst) >= 2);
+    }
+}
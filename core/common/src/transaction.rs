@@ -0,0 +1,248 @@
+// core/common/src/transaction.rs
+// Parsing of serialized transactions and blocks, and the locking scripts addresses stand for
+
+use sha2::{Digest, Sha256};
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct TxOutput {
+    pub vout: u32,
+    pub satoshis: i64,
+    pub script: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ParsedTx {
+    pub txid: String,
+    /// (txid, vout) of each input; empty for the coinbase
+    pub prevouts: Vec<(String, u32)>,
+    pub outputs: Vec<TxOutput>,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|end| *end <= self.data.len())
+            .ok_or_else(|| format!("Data truncated at byte {}", self.pos))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.bytes(4)?.try_into().unwrap()))
+    }
+
+    fn varint(&mut self) -> Result<u64, String> {
+        Ok(match self.bytes(1)?[0] {
+            0xfd => u16::from_le_bytes(self.bytes(2)?.try_into().unwrap()) as u64,
+            0xfe => self.u32()? as u64,
+            0xff => u64::from_le_bytes(self.bytes(8)?.try_into().unwrap()),
+            n => n as u64,
+        })
+    }
+
+    fn len(&mut self) -> Result<usize, String> {
+        let n = self.varint()?;
+        usize::try_from(n).ok().filter(|n| *n <= self.data.len()).ok_or_else(|| format!("Length {} out of range", n))
+    }
+
+    fn transaction(&mut self) -> Result<ParsedTx, String> {
+        let start = self.pos;
+        self.u32()?; // version
+
+        let mut prevouts = Vec::new();
+        for _ in 0..self.len()? {
+            let mut prev = self.bytes(32)?.to_vec();
+            prev.reverse();
+            let vout = self.u32()?;
+            let script_len = self.len()?;
+            self.bytes(script_len)?;
+            self.u32()?; // sequence
+            // The coinbase spends the null outpoint
+            if vout != u32::MAX || prev.iter().any(|b| *b != 0) {
+                prevouts.push((hex::encode(prev), vout));
+            }
+        }
+
+        let mut outputs = Vec::new();
+        for vout in 0..self.len()? {
+            let satoshis = u64::from_le_bytes(self.bytes(8)?.try_into().unwrap());
+            let satoshis = i64::try_from(satoshis).map_err(|_| format!("Output {} value out of range", vout))?;
+            let script_len = self.len()?;
+            outputs.push(TxOutput { vout: vout as u32, satoshis, script: self.bytes(script_len)?.to_vec() });
+        }
+        self.u32()?; // locktime
+
+        Ok(ParsedTx { txid: txid_of(&self.data[start..self.pos]), prevouts, outputs })
+    }
+}
+
+/// Display form of a txid: double SHA-256 of the raw transaction, byte-reversed
+pub fn txid_of(raw: &[u8]) -> String {
+    let mut hash = Sha256::digest(Sha256::digest(raw)).to_vec();
+    hash.reverse();
+    hex::encode(hash)
+}
+
+/// A serialized transaction; fails unless `raw` is exactly one transaction
+pub fn parse_transaction(raw: &[u8]) -> Result<ParsedTx, String> {
+    let mut reader = Reader { data: raw, pos: 0 };
+    let tx = reader.transaction()?;
+    if reader.pos != raw.len() {
+        return Err(format!("{} trailing bytes after transaction", raw.len() - reader.pos));
+    }
+    Ok(tx)
+}
+
+/// Transactions of a serialized block, in block order
+pub fn parse_block(raw: &[u8]) -> Result<Vec<ParsedTx>, String> {
+    let mut reader = Reader { data: raw, pos: 0 };
+    reader.bytes(80)?; // header
+    let count = reader.len()?;
+    let mut txs = Vec::with_capacity(count.min(100_000));
+    for _ in 0..count {
+        txs.push(reader.transaction()?);
+    }
+    Ok(txs)
+}
+
+/// Version byte and hash of a Base58Check address with a valid checksum
+fn decode_address(address: &str) -> Option<(u8, Vec<u8>)> {
+    let decoded = bs58::decode(address).into_vec().ok()?;
+    if decoded.len() != 25 {
+        return None;
+    }
+    let (payload, checksum) = decoded.split_at(21);
+    if Sha256::digest(Sha256::digest(payload))[..4] != *checksum {
+        return None;
+    }
+    Some((payload[0], payload[1..].to_vec()))
+}
+
+/// Locking script paying a Base58Check P2PKH address, mainnet or testnet
+pub fn p2pkh_script(address: &str) -> Option<Vec<u8>> {
+    match decode_address(address)? {
+        (0x00 | 0x6f, hash) => Some([&[0x76, 0xa9, 0x14][..], &hash, &[0x88, 0xac]].concat()),
+        _ => None,
+    }
+}
+
+/// Locking script paying a Base58Check P2PKH or P2SH address
+pub fn address_script(address: &str) -> Option<Vec<u8>> {
+    match decode_address(address)? {
+        (0x05 | 0xc4, hash) => Some([&[0xa9, 0x14][..], &hash, &[0x87]].concat()),
+        _ => p2pkh_script(address),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn varint(n: usize) -> Vec<u8> {
+        assert!(n < 0xfd);
+        vec![n as u8]
+    }
+
+    fn tx_bytes(prevouts: &[([u8; 32], u32)], scripts: &[Vec<u8>]) -> Vec<u8> {
+        let mut tx = 1u32.to_le_bytes().to_vec();
+        tx.extend(varint(prevouts.len()));
+        for (hash, vout) in prevouts {
+            tx.extend_from_slice(hash);
+            tx.extend(vout.to_le_bytes());
+            tx.extend(varint(0));
+            tx.extend(u32::MAX.to_le_bytes());
+        }
+        tx.extend(varint(scripts.len()));
+        for script in scripts {
+            tx.extend(1000u64.to_le_bytes());
+            tx.extend(varint(script.len()));
+            tx.extend_from_slice(script);
+        }
+        tx.extend(0u32.to_le_bytes());
+        tx
+    }
+
+    fn address_for(hash: [u8; 20], version: u8) -> String {
+        let payload = [&[version][..], &hash].concat();
+        let checksum = Sha256::digest(Sha256::digest(&payload));
+        bs58::encode([&payload[..], &checksum[..4]].concat()).into_string()
+    }
+
+    #[test]
+    fn test_parse_transaction() {
+        let raw = tx_bytes(&[([0xab; 32], 0)], &[vec![0x76, 0xa9], vec![0x00, 0x6a]]);
+        let tx = parse_transaction(&raw).unwrap();
+        assert_eq!(tx.txid, txid_of(&raw));
+        assert_eq!(tx.outputs.len(), 2);
+        assert_eq!(tx.outputs[1].vout, 1);
+        assert_eq!(tx.outputs[0].satoshis, 1000);
+        assert_eq!(tx.outputs[1].script, vec![0x00, 0x6a]);
+
+        let mut trailing = raw.clone();
+        trailing.push(0);
+        assert!(parse_transaction(&trailing).is_err());
+        assert!(parse_transaction(&raw[..40]).is_err());
+    }
+
+    #[test]
+    fn test_parses_block_transactions() {
+        let script = address_script("1NyMg76BQxDvV6vRsQugNS4ED2hpZCJtwK").unwrap();
+        let coinbase = tx_bytes(&[([0u8; 32], u32::MAX)], &[vec![0x51]]);
+        let mut parent = [0u8; 32];
+        parent[0] = 0xab;
+        let spend = tx_bytes(&[(parent, 1)], std::slice::from_ref(&script));
+
+        let mut block = vec![0u8; 80];
+        block.extend(varint(2));
+        block.extend_from_slice(&coinbase);
+        block.extend_from_slice(&spend);
+
+        let txs = parse_block(&block).unwrap();
+        assert_eq!(txs.len(), 2);
+        assert!(txs[0].prevouts.is_empty());
+        assert_eq!(txs[0].txid, txid_of(&coinbase));
+        // Outpoint hashes are displayed byte-reversed
+        assert_eq!(txs[1].prevouts, vec![(format!("{}ab", "00".repeat(31)), 1)]);
+        assert_eq!(txs[1].outputs[0].script, script);
+    }
+
+    #[test]
+    fn test_rejects_truncated_block() {
+        let mut block = vec![0u8; 80];
+        block.extend(varint(1));
+        block.extend_from_slice(&[1, 0, 0]);
+        assert!(parse_block(&block).is_err());
+    }
+
+    #[test]
+    fn test_address_scripts() {
+        let p2pkh = address_script("1NyMg76BQxDvV6vRsQugNS4ED2hpZCJtwK").unwrap();
+        assert_eq!(p2pkh.len(), 25);
+        assert_eq!(&p2pkh[..3], &[0x76, 0xa9, 0x14]);
+        assert_eq!(&p2pkh[23..], &[0x88, 0xac]);
+
+        let p2sh = address_script(&address_for([0x11; 20], 0x05)).unwrap();
+        assert_eq!(p2sh, [&[0xa9, 0x14][..], &[0x11; 20], &[0x87]].concat());
+
+        assert!(address_script("1NyMg76BQxDvV6vRsQugNS4ED2hpZCJtwL").is_none());
+        assert!(address_script("not-base58-0OIl").is_none());
+    }
+
+    #[test]
+    fn test_p2pkh_script_rejects_bad_addresses() {
+        let mut address = address_for([0x11; 20], 0x00);
+        assert!(p2pkh_script(&address).is_some());
+        assert!(p2pkh_script(&address_for([0x11; 20], 0x6f)).is_some());
+        // P2SH is not a P2PKH address
+        assert!(p2pkh_script(&address_for([0x11; 20], 0x05)).is_none());
+        let last = address.pop().unwrap();
+        address.push(if last == 'z' { 'y' } else { 'z' });
+        assert!(p2pkh_script(&address).is_none());
+        assert!(p2pkh_script("not-base58-0OIl").is_none());
+    }
+}
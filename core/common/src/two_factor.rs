@@ -0,0 +1,158 @@
+// core/common/src/two_factor.rs
+// Time-based one-time passwords (RFC 6238) for second-factor checks on sensitive operations
+
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use uuid::Uuid;
+
+type HmacSha1 = Hmac<Sha1>;
+
+/// Seconds each code is valid for
+pub const TOTP_STEP_SECS: i64 = 30;
+/// Digits in a code
+pub const TOTP_DIGITS: u32 = 6;
+/// Codes from this many steps either side of now are accepted, for clock drift
+pub const TOTP_SKEW_STEPS: i64 = 1;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// Generate a random 160-bit secret, base32-encoded as authenticator apps expect
+pub fn generate_totp_secret() -> String {
+    let mut bytes = Vec::with_capacity(32);
+    bytes.extend_from_slice(Uuid::new_v4().as_bytes());
+    bytes.extend_from_slice(Uuid::new_v4().as_bytes());
+    base32_encode(&bytes[..20])
+}
+
+/// `otpauth://` URI for enrolling `secret` in an authenticator app, usually shown as a QR code
+pub fn otpauth_uri(secret: &str, issuer: &str, account: &str) -> String {
+    let encode = |s: &str| s.replace('%', "%25").replace(' ', "%20").replace(':', "%3A").replace('&', "%26");
+    format!(
+        "otpauth://totp/{}:{}?secret={}&issuer={}&digits={}&period={}",
+        encode(issuer),
+        encode(account),
+        secret,
+        encode(issuer),
+        TOTP_DIGITS,
+        TOTP_STEP_SECS
+    )
+}
+
+/// The code for `secret` at time step `step`; `None` if the secret is not valid base32
+pub fn totp_code(secret: &str, step: i64) -> Option<String> {
+    let key = base32_decode(secret)?;
+    let mut mac = HmacSha1::new_from_slice(&key).expect("HMAC accepts keys of any length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    // Dynamic truncation, RFC 4226 section 5.3
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([digest[offset], digest[offset + 1], digest[offset + 2], digest[offset + 3]])
+        & 0x7fff_ffff;
+    Some(format!("{:0width$}", binary % 10u32.pow(TOTP_DIGITS), width = TOTP_DIGITS as usize))
+}
+
+/// Time step containing `unix_time`
+pub fn totp_step(unix_time: i64) -> i64 {
+    unix_time.div_euclid(TOTP_STEP_SECS)
+}
+
+/// Check `code` against `secret` around `unix_time`, returning the step it matched.
+/// Callers should store the step and refuse it (and earlier ones) next time, so a
+/// code cannot be replayed.
+pub fn verify_totp(secret: &str, code: &str, unix_time: i64) -> Option<i64> {
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let now = totp_step(unix_time);
+    (now - TOTP_SKEW_STEPS..=now + TOTP_SKEW_STEPS).find(|&step| {
+        totp_code(secret, step).is_some_and(|expected| constant_time_eq(expected.as_bytes(), code.as_bytes()))
+    })
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+/// RFC 4648 base32 without padding
+pub fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            out.push(BASE32_ALPHABET[((buffer >> (bits - 5)) & 31) as usize] as char);
+            bits -= 5;
+        }
+    }
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 31) as usize] as char);
+    }
+    out
+}
+
+/// Decode base32, ignoring case, spaces and padding; `None` on any other character
+pub fn base32_decode(input: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(input.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+    for c in input.chars().filter(|c| *c != ' ' && *c != '=') {
+        let value = BASE32_ALPHABET.iter().position(|&a| a as char == c.to_ascii_uppercase())? as u32;
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            out.push((buffer >> (bits - 8)) as u8);
+            bits -= 8;
+        }
+    }
+    (!out.is_empty()).then_some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// RFC 6238 appendix B SHA-1 secret, "12345678901234567890"
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn test_rfc6238_vectors() {
+        // Last six digits of the RFC's eight-digit codes
+        assert_eq!(totp_code(RFC_SECRET, totp_step(59)).unwrap(), "287082");
+        assert_eq!(totp_code(RFC_SECRET, totp_step(1_111_111_109)).unwrap(), "081804");
+        assert_eq!(totp_code(RFC_SECRET, totp_step(1_234_567_890)).unwrap(), "005924");
+        assert_eq!(totp_code(RFC_SECRET, totp_step(2_000_000_000)).unwrap(), "279037");
+    }
+
+    #[test]
+    fn test_verify_allows_skew_and_reports_step() {
+        let now = 1_234_567_890;
+        let previous = totp_code(RFC_SECRET, totp_step(now) - 1).unwrap();
+        assert_eq!(verify_totp(RFC_SECRET, &previous, now), Some(totp_step(now) - 1));
+        assert_eq!(verify_totp(RFC_SECRET, "005924", now), Some(totp_step(now)));
+
+        let stale = totp_code(RFC_SECRET, totp_step(now) - 2).unwrap();
+        assert_eq!(verify_totp(RFC_SECRET, &stale, now), None);
+        assert_eq!(verify_totp(RFC_SECRET, "00592", now), None);
+        assert_eq!(verify_totp(RFC_SECRET, "abcdef", now), None);
+    }
+
+    #[test]
+    fn test_base32_round_trip() {
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+        assert_eq!(base32_decode(RFC_SECRET).unwrap(), b"12345678901234567890");
+        assert_eq!(base32_decode("gezd gnbv").unwrap(), base32_decode("GEZDGNBV").unwrap());
+        assert!(base32_decode("not base32!").is_none());
+    }
+
+    #[test]
+    fn test_generated_secrets() {
+        let secret = generate_totp_secret();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(base32_decode(&secret).unwrap().len(), 20);
+        assert_ne!(secret, generate_totp_secret());
+    }
+}
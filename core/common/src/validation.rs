@@ -2,6 +2,7 @@
 // Comprehensive input validation
 
 use regex::Regex;
+use std::net::IpAddr;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -38,6 +39,7 @@ const MIN_SATOSHIS: i64 = 0;
 const MAX_PAYMAIL_LENGTH: usize = 255;
 const TXID_LENGTH: usize = 64;
 const MAX_ADDRESS_LENGTH: usize = 100;
+const MAX_URL_LENGTH: usize = 2048;
 
 // ============================================================================
 // SECURITY VALIDATORS (NEW - Phase 6)
@@ -192,6 +194,65 @@ pub fn validate_address(address: &str) -> Result<(), ValidationError> {
     Ok(())
 }
 
+/// Validate an outbound callback URL (http/https with a host)
+pub fn validate_url(url: &str) -> Result<(), ValidationError> {
+    validate_max_length(url, MAX_URL_LENGTH)?;
+    validate_no_xss(url)?;
+    
+    let rest = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .ok_or_else(|| ValidationError::InvalidUrl("must start with http:// or https://".to_string()))?;
+    
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(v6) => v6.split(']').next().unwrap_or(""),
+        None => host.split(':').next().unwrap_or(""),
+    };
+    if host.is_empty() || host.contains(char::is_whitespace) {
+        return Err(ValidationError::InvalidUrl("missing host".to_string()));
+    }
+    
+    // Callbacks are made from inside the bank's network; keep them out of it
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    let internal_name = host == "localhost"
+        || [".localhost", ".local", ".internal"].iter().any(|suffix| host.ends_with(suffix));
+    if internal_name || host.parse().is_ok_and(is_internal_ip) {
+        return Err(ValidationError::InvalidUrl(format!("{} is not a public host", host)));
+    }
+    
+    Ok(())
+}
+
+/// Loopback, private, link-local and other addresses that are not reachable on the public
+/// internet. Outbound callbacks check resolved addresses against this, not just the URL.
+pub fn is_internal_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (64..128).contains(&b))
+        }
+        IpAddr::V6(v6) => {
+            let first = v6.segments()[0];
+            v6.is_loopback()
+                || v6.is_unspecified()
+                // Unique local fc00::/7 and link-local fe80::/10
+                || (first & 0xfe00) == 0xfc00
+                || (first & 0xffc0) == 0xfe80
+                || v6.to_ipv4_mapped().is_some_and(|v4| is_internal_ip(IpAddr::V4(v4)))
+        }
+    }
+}
+
 /// Sanitize string input (remove control characters, limit length)
 pub fn sanitize_string(input: &str, max_length: usize) -> String {
     input
@@ -292,6 +353,38 @@ mod tests {
         assert!(validate_address("X1234567890").is_err());
     }
     
+    // URL tests
+    #[test]
+    fn test_validate_url() {
+        assert!(validate_url("https://example.com/hooks/bsv").is_ok());
+        assert!(validate_url("http://hooks.example.com:9000/cb?x=1").is_ok());
+        assert!(validate_url("ftp://example.com").is_err());
+        assert!(validate_url("https://").is_err());
+        assert!(validate_url("https:///path").is_err());
+    }
+    
+    #[test]
+    fn test_validate_url_rejects_internal_hosts() {
+        for url in [
+            "http://localhost:9000",
+            "http://LOCALHOST./hook",
+            "http://api.localhost/hook",
+            "http://127.0.0.1/hook",
+            "http://10.1.2.3:8080/hook",
+            "http://192.168.0.10/hook",
+            "http://169.254.169.254/latest/meta-data",
+            "http://user@172.16.0.1/hook",
+            "http://[::1]:9000/hook",
+            "http://[fd00::1]/hook",
+            "http://[::ffff:127.0.0.1]/hook",
+            "http://metadata.google.internal/",
+        ] {
+            assert!(validate_url(url).is_err(), "{} was accepted", url);
+        }
+        assert!(validate_url("https://93.184.216.34/hook").is_ok());
+        assert!(validate_url("https://[2606:2800:220:1::248]/hook").is_ok());
+    }
+    
     // Sanitize tests
     #[test]
     fn test_sanitize_string() {
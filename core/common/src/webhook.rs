@@ -0,0 +1,119 @@
+// core/common/src/webhook.rs
+// HMAC-SHA256 signing and SSRF-safe clients for outbound webhook deliveries
+
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::net::SocketAddr;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::validation::is_internal_ip;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Header carrying the event name (e.g. `payment.received`)
+pub const EVENT_HEADER: &str = "X-BSVBank-Event";
+/// Header carrying the unix timestamp that was included in the signature
+pub const TIMESTAMP_HEADER: &str = "X-BSVBank-Timestamp";
+/// Header carrying `sha256=<hex hmac>`
+pub const SIGNATURE_HEADER: &str = "X-BSVBank-Signature";
+
+/// Generate a random 64-character hex secret for a new webhook subscription
+pub fn generate_webhook_secret() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
+
+/// Sign `"{timestamp}.{body}"` with the subscription secret.
+///
+/// Including the timestamp lets receivers reject replayed deliveries.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Verify a signature header value produced by [`sign_payload`]
+pub fn verify_signature(secret: &str, timestamp: i64, body: &str, signature: &str) -> bool {
+    let hex_sig = match signature.strip_prefix("sha256=") {
+        Some(s) => s,
+        None => return false,
+    };
+    let expected = match hex::decode(hex_sig) {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+    
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// A client for one delivery that reaches `url`'s host only at the public address resolved
+/// here and follows no redirects, so neither DNS nor a 3xx can point a callback inside the network
+pub async fn pinned_client(url: &str) -> Result<reqwest::Client, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid URL: {}", e))?;
+    let host = parsed.host_str().ok_or("URL has no host")?;
+    let port = parsed.port_or_known_default().unwrap_or(443);
+
+    let addrs: Vec<SocketAddr> = tokio::net::lookup_host((host.trim_start_matches('[').trim_end_matches(']'), port))
+        .await
+        .map_err(|e| format!("Cannot resolve {}: {}", host, e))?
+        .collect();
+    if let Some(internal) = addrs.iter().find(|addr| is_internal_ip(addr.ip())) {
+        return Err(format!("{} resolves to internal address {}", host, internal.ip()));
+    }
+    let addr = addrs.first().ok_or_else(|| format!("{} has no addresses", host))?;
+
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(reqwest::redirect::Policy::none())
+        .resolve(host, *addr)
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn test_sign_and_verify() {
+        let secret = generate_webhook_secret();
+        let body = r#"{"event":"channel.opened"}"#;
+        let signature = sign_payload(&secret, 1_700_000_000, body);
+        
+        assert!(signature.starts_with("sha256="));
+        assert!(verify_signature(&secret, 1_700_000_000, body, &signature));
+    }
+    
+    #[test]
+    fn test_verify_rejects_tampering() {
+        let signature = sign_payload("secret", 1_700_000_000, "body");
+        
+        assert!(!verify_signature("secret", 1_700_000_001, "body", &signature));
+        assert!(!verify_signature("secret", 1_700_000_000, "body!", &signature));
+        assert!(!verify_signature("other", 1_700_000_000, "body", &signature));
+        assert!(!verify_signature("secret", 1_700_000_000, "body", "deadbeef"));
+    }
+    
+    #[test]
+    fn test_generated_secrets_are_unique() {
+        let a = generate_webhook_secret();
+        let b = generate_webhook_secret();
+        assert_eq!(a.len(), 64);
+        assert_ne!(a, b);
+    }
+
+    #[tokio::test]
+    async fn test_deliveries_never_reach_internal_addresses() {
+        for url in ["http://127.0.0.1:9000/hook", "http://[::1]/hook", "http://10.0.0.5/hook"] {
+            assert!(pinned_client(url).await.is_err(), "{} was allowed", url);
+        }
+    }
+}
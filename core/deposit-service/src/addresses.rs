@@ -0,0 +1,136 @@
+// core/deposit-service/src/addresses.rs
+// Deposit addresses: a pool of bank-controlled addresses, one assigned to each user
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::validate_paymail;
+use bsv_bank_common::transaction::p2pkh_script;
+use serde::Deserialize;
+use sqlx::{PgExecutor, PgPool};
+
+use crate::closures;
+use crate::monitor_client::MonitorClient;
+use crate::{require_admin, require_paymail, ServiceError};
+
+/// Addresses accepted per `POST /deposit-addresses`
+const MAX_ADDRESSES_PER_LOAD: usize = 1_000;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// The address assigned to `paymail`, if any
+pub async fn assigned_address<'e>(
+    executor: impl PgExecutor<'e>,
+    paymail: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT address FROM deposit_addresses WHERE paymail = $1")
+        .bind(paymail)
+        .fetch_optional(executor)
+        .await
+}
+
+/// Hand `paymail` the oldest unassigned address; `None` when the pool is empty
+async fn assign(pool: &PgPool, paymail: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar(
+        r#"
+        UPDATE deposit_addresses
+        SET paymail = $1, assigned_at = NOW()
+        WHERE address = (
+            SELECT address FROM deposit_addresses
+            WHERE paymail IS NULL
+            ORDER BY created_at, address
+            LIMIT 1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING address
+        "#
+    )
+    .bind(paymail)
+    .fetch_optional(pool)
+    .await
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `GET /deposit-address/{paymail}`: the user's deposit address, assigned on first request
+pub async fn get_deposit_address(
+    pool: web::Data<PgPool>,
+    monitor: web::Data<MonitorClient>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    closures::ensure_open(pool.get_ref(), &paymail).await?;
+
+    if let Some(address) = assigned_address(pool.get_ref(), &paymail).await.map_err(db_error)? {
+        return Ok(HttpResponse::Ok().json(serde_json::json!({ "paymail": paymail.as_str(), "address": address })));
+    }
+
+    let address = match assign(&pool, &paymail).await {
+        Ok(Some(address)) => address,
+        Ok(None) => {
+            tracing::error!("Deposit address pool is empty; {} could not be assigned one", paymail);
+            return Err(ServiceError::Unavailable("No deposit addresses available".to_string()));
+        }
+        // A concurrent request assigned this user an address first
+        Err(sqlx::Error::Database(e)) if e.is_unique_violation() => assigned_address(pool.get_ref(), &paymail)
+            .await
+            .map_err(db_error)?
+            .ok_or_else(|| ServiceError::DatabaseError("Deposit address assignment failed".to_string()))?,
+        Err(e) => return Err(db_error(e)),
+    };
+    tracing::info!("Assigned deposit address {} to {}", address, paymail);
+
+    // Deposits are still verified on submission if the monitor misses this
+    if let Err(e) = monitor.watch_address(&address, &paymail).await {
+        tracing::warn!("Failed to watch deposit address {} with blockchain-monitor: {}", address, e);
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "paymail": paymail.as_str(), "address": address })))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LoadAddressesRequest {
+    pub addresses: Vec<String>,
+}
+
+/// `POST /deposit-addresses` (admin): add bank-controlled P2PKH addresses to the pool
+pub async fn load_addresses(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    request: web::Json<LoadAddressesRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+
+    if request.addresses.is_empty() || request.addresses.len() > MAX_ADDRESSES_PER_LOAD {
+        return Err(ServiceError::ValidationError(format!(
+            "Provide between 1 and {} addresses",
+            MAX_ADDRESSES_PER_LOAD
+        )));
+    }
+    if let Some(invalid) = request.addresses.iter().find(|a| p2pkh_script(a).is_none()) {
+        return Err(ServiceError::ValidationError(format!("{} is not a P2PKH address", invalid)));
+    }
+
+    let added = sqlx::query(
+        "INSERT INTO deposit_addresses (address) SELECT * FROM UNNEST($1::VARCHAR[]) ON CONFLICT (address) DO NOTHING"
+    )
+    .bind(&request.addresses)
+    .execute(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+    let available: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM deposit_addresses WHERE paymail IS NULL")
+        .fetch_one(pool.get_ref())
+        .await
+        .map_err(db_error)?;
+    tracing::info!("Loaded {} deposit addresses ({} unassigned)", added, available);
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "added": added,
+        "available": available
+    })))
+}
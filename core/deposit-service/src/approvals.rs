@@ -0,0 +1,222 @@
+// core/deposit-service/src/approvals.rs
+// Maker-checker approval: large withdrawals wait for a second operator before they can be broadcast
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bsv_bank_common::{Claims, DepositMetrics};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::ServiceError;
+
+/// Permission an operator needs to approve or reject withdrawals. Unlike most checks,
+/// `admin` alone is not enough: approving is a separate role from running the service.
+pub const APPROVER_PERMISSION: &str = "withdrawal_approver";
+
+pub const PENDING_APPROVAL: &str = "PendingApproval";
+
+pub const EVENT_APPROVAL_REQUESTED: &str = "approval_requested";
+pub const EVENT_APPROVED: &str = "approved";
+pub const EVENT_REJECTED: &str = "rejected";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WithdrawalEvent {
+    pub id: i64,
+    pub withdrawal_id: Uuid,
+    pub event: String,
+    pub actor: String,
+    pub reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct PendingWithdrawal {
+    pub id: Uuid,
+    pub paymail: String,
+    pub amount_satoshis: i64,
+    pub destination_address: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Append an audit event for `withdrawal_id`
+pub async fn record<'e>(
+    executor: impl PgExecutor<'e>,
+    withdrawal_id: Uuid,
+    event: &str,
+    actor: &str,
+    reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO withdrawal_events (withdrawal_id, event, actor, reason) VALUES ($1, $2, $3, $4)")
+        .bind(withdrawal_id)
+        .bind(event)
+        .bind(actor)
+        .bind(reason)
+        .execute(executor)
+        .await?;
+    Ok(())
+}
+
+/// The token subject, if it carries the approver permission itself
+fn approver(http_req: &HttpRequest) -> Result<String, ServiceError> {
+    http_req
+        .extensions()
+        .get::<Claims>()
+        .filter(|claims| claims.permissions.iter().any(|p| p == APPROVER_PERMISSION))
+        .map(|claims| claims.sub.clone())
+        .ok_or_else(|| ServiceError::Forbidden(format!("{} permission required", APPROVER_PERMISSION)))
+}
+
+/// Approvers and admins may look at the queue and audit trail
+fn require_reviewer(http_req: &HttpRequest) -> Result<(), ServiceError> {
+    let allowed = http_req
+        .extensions()
+        .get::<Claims>()
+        .is_some_and(|claims| claims.has_permission(APPROVER_PERMISSION));
+    if !allowed {
+        return Err(ServiceError::Forbidden(format!("{} permission required", APPROVER_PERMISSION)));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionRequest {
+    pub reason: Option<String>,
+}
+
+/// `GET /admin/withdrawals/pending-approval`: oldest first
+pub async fn list_pending(pool: web::Data<PgPool>, http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+    require_reviewer(&http_req)?;
+
+    let pending = sqlx::query_as::<_, PendingWithdrawal>(
+        r#"
+        SELECT w.id, u.paymail, w.amount_satoshis, w.destination_address, w.created_at
+        FROM withdrawals w JOIN users u ON u.id = w.user_id
+        WHERE w.status = $1
+        ORDER BY w.created_at
+        "#
+    )
+    .bind(PENDING_APPROVAL)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "withdrawals": pending })))
+}
+
+/// `GET /admin/withdrawals/{id}/events`: the withdrawal's approval audit trail
+pub async fn list_events(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    withdrawal_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    require_reviewer(&http_req)?;
+
+    let events = sqlx::query_as::<_, WithdrawalEvent>(
+        r#"
+        SELECT id, withdrawal_id, event, actor, reason, created_at
+        FROM withdrawal_events WHERE withdrawal_id = $1
+        ORDER BY created_at, id
+        "#
+    )
+    .bind(*withdrawal_id)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "withdrawal_id": *withdrawal_id,
+        "events": events
+    })))
+}
+
+/// `POST /admin/withdrawals/{id}/approve`: release the withdrawal for broadcasting
+pub async fn approve_withdrawal(
+    pool: web::Data<PgPool>,
+    metrics: web::Data<DepositMetrics>,
+    http_req: HttpRequest,
+    withdrawal_id: web::Path<Uuid>,
+    request: web::Json<DecisionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    decide(&pool, &metrics, &http_req, *withdrawal_id, true, request.reason.as_deref()).await
+}
+
+/// `POST /admin/withdrawals/{id}/reject`: cancel the withdrawal, releasing its hold; a reason is required
+pub async fn reject_withdrawal(
+    pool: web::Data<PgPool>,
+    metrics: web::Data<DepositMetrics>,
+    http_req: HttpRequest,
+    withdrawal_id: web::Path<Uuid>,
+    request: web::Json<DecisionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let reason = request.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    if reason.is_none() {
+        return Err(ServiceError::ValidationError("A reason is required to reject a withdrawal".to_string()));
+    }
+    decide(&pool, &metrics, &http_req, *withdrawal_id, false, reason).await
+}
+
+async fn decide(
+    pool: &PgPool,
+    metrics: &DepositMetrics,
+    http_req: &HttpRequest,
+    withdrawal_id: Uuid,
+    approve: bool,
+    reason: Option<&str>,
+) -> Result<HttpResponse, ServiceError> {
+    let operator = approver(http_req)?;
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let current: Option<(String, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT w.status, u.paymail, w.amount_satoshis
+        FROM withdrawals w JOIN users u ON u.id = w.user_id
+        WHERE w.id = $1
+        FOR UPDATE OF w
+        "#
+    )
+    .bind(withdrawal_id)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    let (status, requester, amount) =
+        current.ok_or_else(|| ServiceError::ValidationError(format!("Unknown withdrawal: {}", withdrawal_id)))?;
+    if status != PENDING_APPROVAL {
+        return Err(ServiceError::Conflict(format!("Withdrawal {} is {}, not awaiting approval", withdrawal_id, status)));
+    }
+    if operator == requester {
+        return Err(ServiceError::Forbidden("Withdrawals must be approved by someone other than the requester".to_string()));
+    }
+
+    let (new_status, event) = if approve { ("Pending", EVENT_APPROVED) } else { ("Rejected", EVENT_REJECTED) };
+    sqlx::query(
+        r#"
+        UPDATE withdrawals
+        SET status = $2, completed_at = CASE WHEN $2 = 'Rejected' THEN NOW() ELSE completed_at END
+        WHERE id = $1
+        "#
+    )
+    .bind(withdrawal_id)
+    .bind(new_status)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    record(&mut *db_tx, withdrawal_id, event, &operator, reason).await.map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    metrics.record_withdrawal_status(new_status, amount);
+    tracing::info!("Withdrawal {} {} by {}", withdrawal_id, event, operator);
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "withdrawal_id": withdrawal_id,
+        "status": new_status,
+        "decided_by": operator
+    })))
+}
@@ -0,0 +1,462 @@
+// core/deposit-service/src/closures.rs
+// Account closure: deposits blocked, the remaining balance swept on chain, then the account archived
+// with a final statement
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bsv_bank_common::{validate_paymail, Claims, DepositMetrics};
+use bsv_bank_common::transaction::p2pkh_script;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::fees::{self, Operation};
+use crate::history;
+use crate::security::{self, WithdrawalPolicy};
+use crate::tiers::{self, Flow};
+use crate::withdrawals::{self, NewWithdrawal};
+use crate::{ledger, require_paymail, ServiceError};
+
+pub const CLOSING: &str = "Closing";
+pub const CLOSED: &str = "Closed";
+/// The sweep was rejected or failed; the account is open again
+pub const SWEEP_FAILED: &str = "SweepFailed";
+
+const CLOSURE_COLUMNS: &str = "id, paymail, status, destination_address, sweep_withdrawal_id, sweep_satoshis, \
+    requested_by, created_at, closed_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CloseAccountRequest {
+    /// P2PKH address receiving the remaining balance; subject to the withdrawal whitelist
+    pub destination_address: String,
+    /// Required when the sweep is at or above the two-factor threshold
+    pub totp_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AccountClosure {
+    pub id: Uuid,
+    pub paymail: String,
+    /// Closing until the sweep settles, then Closed; SweepFailed reopens the account
+    pub status: String,
+    pub destination_address: String,
+    /// The latest sweep; `None` when nothing was left to sweep
+    pub sweep_withdrawal_id: Option<Uuid>,
+    pub sweep_satoshis: i64,
+    pub requested_by: String,
+    pub created_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+}
+
+/// What still ties an account to the bank
+#[derive(Debug, Default, sqlx::FromRow)]
+struct Obligations {
+    loans_as_borrower: i64,
+    loans_as_lender: i64,
+    open_channels: i64,
+    locked_deposits: i64,
+    unconfirmed_deposits: i64,
+}
+
+impl Obligations {
+    /// Why the account cannot close yet; empty when it can
+    fn blockers(&self) -> Vec<String> {
+        [
+            (self.loans_as_borrower, "loan(s) as borrower"),
+            (self.loans_as_lender, "loan(s) as lender"),
+            (self.open_channels, "open payment channel(s)"),
+            (self.locked_deposits, "locked term deposit(s)"),
+            (self.unconfirmed_deposits, "deposit(s) awaiting confirmation"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, what)| format!("{} {}", count, what))
+        .collect()
+    }
+}
+
+async fn obligations<'e>(executor: impl PgExecutor<'e>, paymail: &str) -> Result<Obligations, sqlx::Error> {
+    sqlx::query_as::<_, Obligations>(
+        r#"
+        SELECT
+            (SELECT COUNT(*) FROM loans
+             WHERE borrower_paymail = $1 AND status IN ('Pending', 'Active', 'MarginCalled')) AS loans_as_borrower,
+            (SELECT COUNT(*) FROM loan_participations lp JOIN loans l ON l.id = lp.loan_id
+             WHERE lp.lender_paymail = $1 AND lp.status <> 'Released'
+               AND l.status IN ('Pending', 'Active', 'MarginCalled')) AS loans_as_lender,
+            (SELECT COUNT(*) FROM payment_channels
+             WHERE (party_a_paymail = $1 OR party_b_paymail = $1) AND status <> 'Closed') AS open_channels,
+            (SELECT COUNT(*) FROM deposits
+             WHERE paymail = $1 AND status IN ('Confirmed', 'Available') AND lock_until > NOW()) AS locked_deposits,
+            (SELECT COUNT(*) FROM deposits WHERE paymail = $1 AND status = 'Pending') AS unconfirmed_deposits
+        "#
+    )
+    .bind(paymail)
+    .fetch_one(executor)
+    .await
+}
+
+/// Fail if `paymail` is closing or closed, so it takes no new deposits or incoming transfers
+pub async fn ensure_open<'e>(executor: impl PgExecutor<'e>, paymail: &str) -> Result<(), ServiceError> {
+    let status: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT c.status FROM account_closures c JOIN users u ON u.id = c.user_id
+        WHERE u.paymail = $1 AND c.status IN ($2, $3)
+        "#
+    )
+    .bind(paymail)
+    .bind(CLOSING)
+    .bind(CLOSED)
+    .fetch_optional(executor)
+    .await
+    .map_err(db_error)?;
+    match status.as_deref() {
+        Some(CLOSED) => Err(ServiceError::Conflict(format!("Account {} is closed", paymail))),
+        Some(_) => Err(ServiceError::Conflict(format!("Account {} is being closed", paymail))),
+        None => Ok(()),
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `POST /accounts/{paymail}/close`: stop deposits and sweep the available balance to
+/// `destination_address`. Refused while the user has active loans, open channels, locked term
+/// deposits or unconfirmed deposits. The sweep is an ordinary withdrawal, so the whitelist, the
+/// second factor and maker-checker approval apply. The account is archived with a final statement
+/// once every withdrawal has settled.
+pub async fn close_account(
+    pool: web::Data<PgPool>,
+    policy: web::Data<WithdrawalPolicy>,
+    metrics: web::Data<DepositMetrics>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    request: web::Json<CloseAccountRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    if p2pkh_script(&request.destination_address).is_none() {
+        return Err(ServiceError::ValidationError(format!(
+            "{} is not a P2PKH address",
+            request.destination_address
+        )));
+    }
+    let requested_by = http_req
+        .extensions()
+        .get::<Claims>()
+        .map(|claims| claims.sub.clone())
+        .unwrap_or_else(|| paymail.to_string());
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    ledger::lock_account(&mut db_tx, &paymail).await.map_err(db_error)?;
+    let user_id = security::user_id(&mut *db_tx, &paymail).await?;
+    ensure_open(&mut *db_tx, &paymail).await?;
+
+    let blockers = obligations(&mut *db_tx, &paymail).await.map_err(db_error)?.blockers();
+    if !blockers.is_empty() {
+        return Err(ServiceError::BusinessError(format!(
+            "Account {} cannot be closed yet: {}",
+            paymail,
+            blockers.join(", ")
+        )));
+    }
+
+    let available: i64 = sqlx::query_scalar("SELECT available_satoshis::BIGINT FROM user_balances WHERE paymail = $1")
+        .bind(paymail.as_str())
+        .fetch_optional(&mut *db_tx)
+        .await
+        .map_err(db_error)?
+        .unwrap_or(0);
+    // The sweep's fee comes out of the balance too; a balance too small to cover it is left
+    // for archival to take as the fee
+    let fee_terms = fees::terms(&mut *db_tx, Operation::Withdrawal, &paymail, None).await.map_err(db_error)?;
+    let amount = fee_terms.withdrawable(available);
+
+    let sweep = if amount > 0 {
+        security::check_destination(&mut *db_tx, user_id, &request.destination_address).await?;
+        if policy.requires_two_factor(amount) {
+            security::verify_code(&mut db_tx, user_id, request.totp_code.as_deref()).await?;
+        }
+        tiers::usage(&mut *db_tx, &paymail)
+            .await
+            .map_err(db_error)?
+            .check(Flow::Withdrawal, amount)?;
+        let new = NewWithdrawal {
+            user_id,
+            paymail: &paymail,
+            amount_satoshis: amount,
+            fee_satoshis: fee_terms.fee(amount),
+            destination_address: &request.destination_address,
+        };
+        let withdrawal = withdrawals::insert(&mut db_tx, &policy, &new, None)
+            .await
+        .map_err(db_error)?;
+        Some(withdrawal)
+    } else {
+        None
+    };
+
+    let closure = sqlx::query_as::<_, AccountClosure>(&format!(
+        r#"
+        INSERT INTO account_closures
+            (user_id, paymail, status, destination_address, sweep_withdrawal_id, sweep_satoshis, requested_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {}
+        "#,
+        CLOSURE_COLUMNS
+    ))
+    .bind(user_id)
+    .bind(paymail.as_str())
+    .bind(CLOSING)
+    .bind(&request.destination_address)
+    .bind(sweep.as_ref().map(|w| w.id))
+    .bind(amount)
+    .bind(&requested_by)
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    if let Some(withdrawal) = &sweep {
+        metrics.record_withdrawal_status(&withdrawal.status, withdrawal.amount_satoshis);
+    }
+    tracing::info!(
+        "Closing account {} at the request of {}: sweeping {} sat to {}",
+        paymail, requested_by, closure.sweep_satoshis, closure.destination_address
+    );
+    Ok(HttpResponse::Accepted().json(serde_json::json!({
+        "closure": closure,
+        "sweep": sweep
+    })))
+}
+
+/// `GET /accounts/{paymail}/closure`: the latest closure, with the final statement once closed
+pub async fn get_closure(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+
+    let closure = sqlx::query_as::<_, AccountClosure>(&format!(
+        "SELECT {} FROM account_closures WHERE paymail = $1 ORDER BY created_at DESC LIMIT 1",
+        CLOSURE_COLUMNS
+    ))
+    .bind(paymail.as_str())
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::ValidationError(format!("No closure requested for {}", paymail)))?;
+
+    let final_statement: Option<String> =
+        sqlx::query_scalar("SELECT final_statement::TEXT FROM account_closures WHERE id = $1")
+            .bind(closure.id)
+            .fetch_one(pool.get_ref())
+            .await
+            .map_err(db_error)?;
+    let final_statement = final_statement
+        .map(|s| serde_json::from_str::<serde_json::Value>(&s))
+        .transpose()
+        .map_err(|e| ServiceError::DatabaseError(format!("Stored final statement is not JSON: {}", e)))?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "closure": closure,
+        "final_statement": final_statement
+    })))
+}
+
+// ============================================================================
+// ARCHIVAL
+// ============================================================================
+
+/// Finish closures every `ACCOUNT_ARCHIVAL_INTERVAL_SECS` (default 60): once no withdrawal is in
+/// flight and no interest is waiting to be posted, sweep whatever arrived since, or archive the
+/// account with its final statement when nothing is left
+pub async fn start_account_archival(pool: PgPool, policy: WithdrawalPolicy, metrics: DepositMetrics) {
+    let interval_secs = std::env::var("ACCOUNT_ARCHIVAL_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+        match process_closures(&pool, &policy, &metrics).await {
+            Ok((0, 0)) => {}
+            Ok((archived, failed)) => {
+                tracing::info!("Account closures: {} archived, {} sweep(s) failed", archived, failed)
+            }
+            Err(e) => tracing::error!("Account archival run failed: {}", e),
+        }
+    }
+}
+
+async fn process_closures(
+    pool: &PgPool,
+    policy: &WithdrawalPolicy,
+    metrics: &DepositMetrics,
+) -> Result<(u64, u64), ServiceError> {
+    // A rejected or failed sweep releases its hold; the user can close again
+    let failed = sqlx::query(
+        r#"
+        UPDATE account_closures c SET status = $1
+        FROM withdrawals w
+        WHERE w.id = c.sweep_withdrawal_id AND c.status = $2 AND w.status IN ('Failed', 'Rejected')
+        "#
+    )
+    .bind(SWEEP_FAILED)
+    .bind(CLOSING)
+    .execute(pool)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+
+    let ready: Vec<(Uuid, String)> = sqlx::query_as(
+        r#"
+        SELECT c.id, c.paymail FROM account_closures c
+        WHERE c.status = $1
+          AND NOT EXISTS (
+              SELECT 1 FROM withdrawals w WHERE w.user_id = c.user_id
+                AND (w.status IN ('PendingApproval', 'Pending', 'Broadcast')
+                     OR (w.status = 'Confirmed' AND w.settled_transfer_id IS NULL)))
+          AND NOT EXISTS (
+              SELECT 1 FROM interest_accruals ia
+              WHERE ia.user_id = c.user_id AND NOT ia.paid_out AND ia.amount_satoshis > 0)
+        ORDER BY c.created_at
+        LIMIT 100
+        "#
+    )
+    .bind(CLOSING)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+
+    let mut archived = 0;
+    for (closure_id, paymail) in ready {
+        match finish_closure(pool, policy, metrics, closure_id, &paymail).await {
+            Ok(true) => archived += 1,
+            Ok(false) => {}
+            Err(e) => tracing::error!("Could not finish closing {}: {}", paymail, e),
+        }
+    }
+    Ok((archived, failed))
+}
+
+/// Sweep a balance that arrived after the first sweep, or archive the account; true once archived
+async fn finish_closure(
+    pool: &PgPool,
+    policy: &WithdrawalPolicy,
+    metrics: &DepositMetrics,
+    closure_id: Uuid,
+    paymail: &str,
+) -> Result<bool, ServiceError> {
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    ledger::lock_account(&mut db_tx, paymail).await.map_err(db_error)?;
+    let closure: Option<(i32, String, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT c.user_id, c.destination_address, u.created_at
+        FROM account_closures c JOIN users u ON u.id = c.user_id
+        WHERE c.id = $1 AND c.status = $2
+        FOR UPDATE OF c
+        "#
+    )
+    .bind(closure_id)
+    .bind(CLOSING)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    let Some((user_id, destination_address, opened_at)) = closure else {
+        return Ok(false);
+    };
+
+    // Interest posted or transfers received after the first sweep go the same way, already authorized
+    let available: i64 = sqlx::query_scalar("SELECT available_satoshis::BIGINT FROM user_balances WHERE paymail = $1")
+        .bind(paymail)
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    if available > 0 {
+        let fee_terms = fees::terms(&mut *db_tx, Operation::Withdrawal, paymail, None).await.map_err(db_error)?;
+        let amount = fee_terms.withdrawable(available);
+        if amount == 0 {
+            // Too little to pay out after the fee
+            ledger::transfer(&mut db_tx, ledger::WITHDRAWAL_FEE, paymail, ledger::WITHDRAWAL_FEE_ACCOUNT, available, None)
+                .await
+                .map_err(db_error)?;
+            db_tx.commit().await.map_err(db_error)?;
+            tracing::info!("Took the last {} sat of closing account {} as the withdrawal fee", available, paymail);
+            return Ok(false);
+        }
+        let new = NewWithdrawal {
+            user_id,
+            paymail,
+            amount_satoshis: amount,
+            fee_satoshis: fee_terms.fee(amount),
+            destination_address: &destination_address,
+        };
+        let withdrawal = withdrawals::insert(&mut db_tx, policy, &new, None).await.map_err(db_error)?;
+        sqlx::query(
+            "UPDATE account_closures SET sweep_withdrawal_id = $2, sweep_satoshis = sweep_satoshis + $3 WHERE id = $1"
+        )
+        .bind(closure_id)
+        .bind(withdrawal.id)
+        .bind(amount)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+        db_tx.commit().await.map_err(db_error)?;
+        metrics.record_withdrawal_status(&withdrawal.status, withdrawal.amount_satoshis);
+        tracing::info!("Sweeping a further {} sat from closing account {}", amount, paymail);
+        return Ok(false);
+    }
+
+    let statement = history::statement(pool, paymail, opened_at, Utc::now()).await?;
+    let statement = serde_json::to_string(&statement)
+        .map_err(|e| ServiceError::DatabaseError(format!("Could not encode final statement: {}", e)))?;
+    sqlx::query("UPDATE account_closures SET status = $2, final_statement = $3::JSONB, closed_at = NOW() WHERE id = $1")
+        .bind(closure_id)
+        .bind(CLOSED)
+        .bind(statement)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    sqlx::query("UPDATE users SET archived_at = NOW(), updated_at = NOW() WHERE id = $1")
+        .bind(user_id)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Account {} closed and archived", paymail);
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_no_obligations_allows_closure() {
+        assert!(Obligations::default().blockers().is_empty());
+    }
+
+    #[test]
+    fn test_blockers_name_each_obligation() {
+        let obligations = Obligations {
+            loans_as_borrower: 1,
+            open_channels: 2,
+            locked_deposits: 1,
+            ..Default::default()
+        };
+        assert_eq!(
+            obligations.blockers(),
+            vec!["1 loan(s) as borrower", "2 open payment channel(s)", "1 locked term deposit(s)"]
+        );
+    }
+}
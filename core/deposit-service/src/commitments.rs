@@ -0,0 +1,201 @@
+// core/deposit-service/src/commitments.rs
+// Deposit commitments: each deposit's hash is anchored on chain in an OP_RETURN as proof of processing
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use bsv_bank_common::{AnchorClient, JwtManager};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{require_paymail, ServiceError};
+
+/// First push of every anchoring OP_RETURN
+pub const PROTOCOL_PREFIX: &str = "bsvbank.deposit";
+
+/// Subject of this service's tokens for transaction-builder's signing API
+const SERVICE_NAME: &str = "deposit-service";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// SHA-256 of `DEPOSIT|paymail|amount|txid|unix time`, hex
+pub fn commitment_hash(paymail: &str, amount_satoshis: i64, txid: &str, timestamp: i64) -> String {
+    let preimage = format!("DEPOSIT|{}|{}|{}|{}", paymail, amount_satoshis, txid, timestamp);
+    hex::encode(Sha256::digest(preimage.as_bytes()))
+}
+
+// ============================================================================
+// ANCHORING CLIENT
+// ============================================================================
+
+/// Anchors deposit commitments and fetches their SPV proofs
+#[derive(Clone)]
+pub struct CommitmentAnchor {
+    client: AnchorClient,
+}
+
+impl CommitmentAnchor {
+    /// Funded by `COMMITMENT_ADDRESS`, signed with `COMMITMENT_KEY_REF`
+    pub fn from_env(jwt: JwtManager) -> Self {
+        Self {
+            client: AnchorClient::from_env("COMMITMENT", PROTOCOL_PREFIX, SERVICE_NAME, jwt),
+        }
+    }
+
+    // ========================================================================
+    // SCHEDULE
+    // ========================================================================
+
+    /// Anchor and prove pending commitments every `COMMITMENT_INTERVAL_SECS` (default 300)
+    pub async fn start(self, pool: PgPool) {
+        let secs = std::env::var("COMMITMENT_INTERVAL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(300);
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(secs));
+
+        loop {
+            interval.tick().await;
+            if let Err(e) = self.run_once(&pool).await {
+                tracing::error!("Deposit commitment anchoring failed: {}", e);
+            }
+        }
+    }
+
+    async fn run_once(&self, pool: &PgPool) -> Result<(), ServiceError> {
+        let pending: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, commitment_hash FROM deposits
+            WHERE commitment_status = 'pending' AND commitment_hash IS NOT NULL
+            ORDER BY created_at
+            LIMIT 20
+            "#
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+        for (deposit_id, commitment) in pending {
+            match self.client.anchor(&commitment, &deposit_id.to_string()).await {
+                Ok(txid) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE deposits
+                        SET commitment_status = 'broadcast', commitment_txid = $2, commitment_broadcast_at = NOW(),
+                            commitment_attempts = commitment_attempts + 1, commitment_last_error = NULL
+                        WHERE id = $1
+                        "#
+                    )
+                    .bind(deposit_id)
+                    .bind(&txid)
+                    .execute(pool)
+                    .await
+                    .map_err(db_error)?;
+                    tracing::info!("Deposit {} commitment anchored in {}", deposit_id, txid);
+                }
+                Err(e) => {
+                    sqlx::query(
+                        "UPDATE deposits SET commitment_attempts = commitment_attempts + 1, commitment_last_error = $2 WHERE id = $1"
+                    )
+                    .bind(deposit_id)
+                    .bind(&e)
+                    .execute(pool)
+                    .await
+                    .map_err(db_error)?;
+                    tracing::warn!("Anchoring deposit {} commitment failed: {}", deposit_id, e);
+                }
+            }
+        }
+
+        let unproven: Vec<(Uuid, String)> = sqlx::query_as(
+            r#"
+            SELECT id, commitment_txid FROM deposits
+            WHERE commitment_status = 'broadcast' AND commitment_txid IS NOT NULL
+            ORDER BY commitment_broadcast_at
+            LIMIT 50
+            "#
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+        for (deposit_id, txid) in unproven {
+            let Ok(proof) = self.client.proof(&txid).await else { continue };
+            sqlx::query(
+                r#"
+                UPDATE deposits
+                SET commitment_status = 'anchored', commitment_spv_proof = $2, commitment_anchored_at = NOW()
+                WHERE id = $1
+                "#
+            )
+            .bind(deposit_id)
+            .bind(&proof)
+            .execute(pool)
+            .await
+            .map_err(db_error)?;
+            tracing::info!("Deposit {} commitment proven in {}", deposit_id, txid);
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DepositCommitment {
+    pub deposit_id: Uuid,
+    #[serde(skip_serializing)]
+    pub paymail: String,
+    pub commitment_hash: Option<String>,
+    /// `pending`, `broadcast` once in a transaction, `anchored` once its SPV proof is stored
+    pub commitment_status: Option<String>,
+    pub commitment_txid: Option<String>,
+    pub commitment_spv_proof: Option<serde_json::Value>,
+    pub commitment_attempts: i32,
+    pub commitment_last_error: Option<String>,
+    pub commitment_broadcast_at: Option<DateTime<Utc>>,
+    pub commitment_anchored_at: Option<DateTime<Utc>>,
+}
+
+/// `GET /deposits/{id}/commitment`: the deposit's commitment, anchoring txid and SPV proof
+pub async fn get_commitment(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    deposit_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let commitment = sqlx::query_as::<_, DepositCommitment>(
+        r#"
+        SELECT id AS deposit_id, paymail, commitment_hash, commitment_status, commitment_txid,
+            commitment_spv_proof, commitment_attempts, commitment_last_error,
+            commitment_broadcast_at, commitment_anchored_at
+        FROM deposits WHERE id = $1
+        "#
+    )
+    .bind(*deposit_id)
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::ValidationError(format!("Unknown deposit: {}", deposit_id)))?;
+    require_paymail(&http_req, &commitment.paymail)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "protocol_prefix": PROTOCOL_PREFIX,
+        "commitment": commitment
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commitment_hash() {
+        let hash = commitment_hash("alice@example.com", 50_000, &"ab".repeat(32), 1_700_000_000);
+        assert_eq!(hash.len(), 64);
+        assert_eq!(hash, commitment_hash("alice@example.com", 50_000, &"ab".repeat(32), 1_700_000_000));
+        assert_ne!(hash, commitment_hash("alice@example.com", 50_001, &"ab".repeat(32), 1_700_000_000));
+    }
+}
@@ -0,0 +1,328 @@
+// core/deposit-service/src/fees.rs
+// Fee schedule: flat plus bps fees on deposits and withdrawals, per KYC tier and term product
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bsv_bank_common::Claims;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::tiers::DEFAULT_TIER;
+use crate::{ledger, require_admin, ServiceError};
+
+pub const MAX_FEE_BPS: i32 = 10_000;
+
+const RULE_COLUMNS: &str = "id, operation, kyc_tier, term_product_code, flat_satoshis, bps, max_fee_satoshis, \
+    active, updated_by, created_at, updated_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Operation {
+    Deposit,
+    Withdrawal,
+}
+
+impl Operation {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Operation::Deposit => "deposit",
+            Operation::Withdrawal => "withdrawal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct FeeRule {
+    pub id: Uuid,
+    pub operation: String,
+    /// `None` matches every tier
+    pub kyc_tier: Option<String>,
+    /// `None` matches every deposit; withdrawals only match `None`
+    pub term_product_code: Option<String>,
+    pub flat_satoshis: i64,
+    pub bps: i32,
+    /// `None` is uncapped
+    pub max_fee_satoshis: Option<i64>,
+    pub active: bool,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// How a fee is computed from the amount
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, sqlx::FromRow)]
+pub struct FeeTerms {
+    pub flat_satoshis: i64,
+    pub bps: i32,
+    pub max_fee_satoshis: Option<i64>,
+}
+
+impl FeeTerms {
+    /// Flat plus `bps` of `amount`, rounded down and capped
+    pub fn fee(&self, amount: i64) -> i64 {
+        let fee = self
+            .flat_satoshis
+            .saturating_add((amount.max(0) as i128 * self.bps as i128 / 10_000) as i64);
+        self.max_fee_satoshis.map_or(fee, |max| fee.min(max))
+    }
+
+    /// Fee on a deposit, which never takes more than was deposited
+    pub fn deposit_fee(&self, amount: i64) -> i64 {
+        self.fee(amount).min(amount.max(0))
+    }
+
+    /// The largest amount whose withdrawal, fee included, fits in `available`
+    pub fn withdrawable(&self, available: i64) -> i64 {
+        // The fee never falls as the amount grows, so the cost does not either
+        let (mut low, mut high) = (0, available.max(0));
+        while low < high {
+            let mid = low + (high - low + 1) / 2;
+            if mid.saturating_add(self.fee(mid)) <= available {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
+        }
+        low
+    }
+}
+
+/// The terms that apply to `operation` for `paymail`; free when no rule matches. Unknown users
+/// are on the default tier.
+pub async fn terms<'e>(
+    executor: impl PgExecutor<'e>,
+    operation: Operation,
+    paymail: &str,
+    term_product_code: Option<&str>,
+) -> Result<FeeTerms, sqlx::Error> {
+    let terms = sqlx::query_as::<_, FeeTerms>(
+        r#"
+        SELECT r.flat_satoshis, r.bps, r.max_fee_satoshis
+        FROM fee_rules r
+        WHERE r.active AND r.operation = $1
+          AND (r.kyc_tier IS NULL OR r.kyc_tier =
+               COALESCE((SELECT kyc_tier FROM users WHERE paymail = $2), $4))
+          AND (r.term_product_code IS NULL OR r.term_product_code = $3)
+        ORDER BY r.term_product_code IS NULL, r.kyc_tier IS NULL
+        LIMIT 1
+        "#
+    )
+    .bind(operation.as_str())
+    .bind(paymail)
+    .bind(term_product_code)
+    .bind(DEFAULT_TIER)
+    .fetch_optional(executor)
+    .await?;
+    Ok(terms.unwrap_or_default())
+}
+
+/// Charge a credited deposit's fee once; returns the ledger transfer, or `None` if nothing was due
+pub async fn charge_deposit(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    deposit_id: Uuid,
+) -> Result<Option<Uuid>, sqlx::Error> {
+    let due: Option<(String, i64)> = sqlx::query_as(
+        r#"
+        SELECT paymail, fee_satoshis FROM deposits
+        WHERE id = $1 AND status IN ('Confirmed', 'Available') AND fee_satoshis > 0 AND fee_transfer_id IS NULL
+        FOR UPDATE
+        "#
+    )
+    .bind(deposit_id)
+    .fetch_optional(&mut **db_tx)
+    .await?;
+    let Some((paymail, fee)) = due else {
+        return Ok(None);
+    };
+
+    let transfer_id = ledger::transfer(db_tx, ledger::DEPOSIT_FEE, &paymail, ledger::DEPOSIT_FEE_ACCOUNT, fee, None).await?;
+    sqlx::query("UPDATE deposits SET fee_transfer_id = $2 WHERE id = $1")
+        .bind(deposit_id)
+        .bind(transfer_id)
+        .execute(&mut **db_tx)
+        .await?;
+    Ok(transfer_id)
+}
+
+/// Charge fees on credited deposits that missed them, e.g. after a crash between
+/// confirmation and charging; returns how many were charged
+pub async fn charge_outstanding_deposits(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let outstanding: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM deposits
+        WHERE status IN ('Confirmed', 'Available') AND fee_satoshis > 0 AND fee_transfer_id IS NULL
+        ORDER BY created_at
+        LIMIT 100
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let mut charged = 0;
+    for deposit_id in outstanding {
+        let mut db_tx = pool.begin().await?;
+        if charge_deposit(&mut db_tx, deposit_id).await?.is_some() {
+            charged += 1;
+        }
+        db_tx.commit().await?;
+    }
+    Ok(charged)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct FeeRuleRequest {
+    pub operation: Operation,
+    pub kyc_tier: Option<String>,
+    pub term_product_code: Option<String>,
+    #[serde(default)]
+    pub flat_satoshis: i64,
+    #[serde(default)]
+    pub bps: i32,
+    pub max_fee_satoshis: Option<i64>,
+}
+
+fn validate_rule(request: &FeeRuleRequest) -> Result<(), ServiceError> {
+    if request.flat_satoshis < 0 {
+        return Err(ServiceError::ValidationError("flat_satoshis cannot be negative".to_string()));
+    }
+    if !(0..=MAX_FEE_BPS).contains(&request.bps) {
+        return Err(ServiceError::ValidationError(format!("bps must be between 0 and {}", MAX_FEE_BPS)));
+    }
+    if request.max_fee_satoshis.is_some_and(|max| max < 0) {
+        return Err(ServiceError::ValidationError("max_fee_satoshis cannot be negative".to_string()));
+    }
+    if request.operation == Operation::Withdrawal && request.term_product_code.is_some() {
+        return Err(ServiceError::ValidationError("Term products only apply to deposit fees".to_string()));
+    }
+    Ok(())
+}
+
+/// `GET /fees/schedule`: active fee rules, most specific first within each operation
+pub async fn get_schedule(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
+    let rules = sqlx::query_as::<_, FeeRule>(&format!(
+        r#"
+        SELECT {} FROM fee_rules
+        WHERE active
+        ORDER BY operation, term_product_code IS NULL, kyc_tier IS NULL, term_product_code, kyc_tier
+        "#,
+        RULE_COLUMNS
+    ))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "default_tier": DEFAULT_TIER,
+        "rules": rules
+    })))
+}
+
+/// `PUT /fees/schedule` (admin): set the fee for an operation, tier and product, replacing any
+/// rule with the same scope
+pub async fn set_rule(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    request: web::Json<FeeRuleRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+    validate_rule(&request)?;
+    let admin = http_req
+        .extensions()
+        .get::<Claims>()
+        .map(|claims| claims.sub.clone())
+        .unwrap_or_default();
+
+    let rule = sqlx::query_as::<_, FeeRule>(&format!(
+        r#"
+        INSERT INTO fee_rules
+            (operation, kyc_tier, term_product_code, flat_satoshis, bps, max_fee_satoshis, updated_by)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (operation, COALESCE(kyc_tier, ''), COALESCE(term_product_code, ''))
+        DO UPDATE SET flat_satoshis = EXCLUDED.flat_satoshis, bps = EXCLUDED.bps,
+            max_fee_satoshis = EXCLUDED.max_fee_satoshis, updated_by = EXCLUDED.updated_by,
+            active = true, updated_at = NOW()
+        RETURNING {}
+        "#,
+        RULE_COLUMNS
+    ))
+    .bind(request.operation.as_str())
+    .bind(&request.kyc_tier)
+    .bind(&request.term_product_code)
+    .bind(request.flat_satoshis)
+    .bind(request.bps)
+    .bind(request.max_fee_satoshis)
+    .bind(&admin)
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(|e| match e {
+        sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+            ServiceError::ValidationError("Unknown KYC tier or term product".to_string())
+        }
+        e => db_error(e),
+    })?;
+
+    tracing::info!(
+        "{} set {} fee rule {} (tier {:?}, product {:?}): {} sat + {} bps",
+        admin, rule.operation, rule.id, rule.kyc_tier, rule.term_product_code, rule.flat_satoshis, rule.bps
+    );
+    Ok(HttpResponse::Ok().json(rule))
+}
+
+/// `DELETE /fees/schedule/{id}` (admin): stop charging under a rule
+pub async fn delete_rule(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    rule_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+
+    let deactivated = sqlx::query("UPDATE fee_rules SET active = false, updated_at = NOW() WHERE id = $1 AND active")
+        .bind(*rule_id)
+        .execute(pool.get_ref())
+        .await
+        .map_err(db_error)?
+        .rows_affected();
+    if deactivated == 0 {
+        return Err(ServiceError::ValidationError(format!("Unknown fee rule: {}", rule_id)));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_is_flat_plus_bps_capped() {
+        let terms = FeeTerms { flat_satoshis: 500, bps: 25, max_fee_satoshis: Some(10_000) };
+        assert_eq!(terms.fee(1_000_000), 3_000);
+        assert_eq!(terms.fee(100_000_000), 10_000);
+        assert_eq!(FeeTerms::default().fee(1_000_000), 0);
+    }
+
+    #[test]
+    fn test_deposit_fee_never_exceeds_deposit() {
+        let terms = FeeTerms { flat_satoshis: 1_000, bps: 0, max_fee_satoshis: None };
+        assert_eq!(terms.deposit_fee(600), 600);
+        assert_eq!(terms.deposit_fee(5_000), 1_000);
+    }
+
+    #[test]
+    fn test_withdrawable_leaves_room_for_the_fee() {
+        let terms = FeeTerms { flat_satoshis: 1_000, bps: 100, max_fee_satoshis: None };
+        let amount = terms.withdrawable(1_000_000);
+        assert!(amount + terms.fee(amount) <= 1_000_000);
+        assert!(amount + 1 + terms.fee(amount + 1) > 1_000_000);
+        assert_eq!(terms.withdrawable(999), 0);
+        assert_eq!(FeeTerms::default().withdrawable(42), 42);
+    }
+}
@@ -0,0 +1,362 @@
+// core/deposit-service/src/history.rs
+// Deposit history and account statements: line items from deposits and the ledger with running balances
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::validate_paymail;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{require_paymail, ServiceError};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 500;
+/// Statements cover at most this many days
+const MAX_STATEMENT_DAYS: i64 = 366;
+/// Beyond this the caller should ask for a narrower range
+const MAX_STATEMENT_LINES: i64 = 10_000;
+
+const DEPOSIT_COLUMNS: &str = "id, txid, vout, deposit_address, amount_satoshis, status, confirmations, \
+    block_height, spv_proof_verified, lock_until, term_product_code, auto_roll, matured_at, redeemed_at, \
+    commitment_status, commitment_txid, created_at, confirmed_at";
+
+/// Everything that moves a user's balance: credited deposits and the user's ledger legs.
+/// `$1` is the paymail. Matches how `user_balances` computes `balance_satoshis`.
+const STATEMENT_LINES: &str = r#"
+    SELECT COALESCE(d.confirmed_at, d.created_at) AS posted_at,
+        'deposit'::TEXT AS entry_type,
+        d.txid::TEXT AS reference,
+        NULL::TEXT AS counterparty,
+        NULL::TEXT AS memo,
+        d.amount_satoshis
+    FROM deposits d
+    WHERE d.user_id = (SELECT id FROM users WHERE paymail = $1)
+      AND d.status IN ('Confirmed', 'Available')
+    UNION ALL
+    SELECT le.created_at AS posted_at,
+        le.entry_type::TEXT,
+        le.transfer_id::TEXT AS reference,
+        (SELECT string_agg(o.account, ', ' ORDER BY o.account) FROM ledger_entries o
+         WHERE o.transfer_id = le.transfer_id AND o.account <> le.account) AS counterparty,
+        it.memo::TEXT AS memo,
+        le.amount_satoshis
+    FROM ledger_entries le
+    LEFT JOIN internal_transfers it ON it.id = le.transfer_id
+    WHERE le.account = $1
+"#;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DepositRecord {
+    pub id: Uuid,
+    pub txid: String,
+    pub vout: Option<i32>,
+    pub deposit_address: Option<String>,
+    pub amount_satoshis: i64,
+    pub status: String,
+    pub confirmations: Option<i32>,
+    pub block_height: Option<i64>,
+    pub spv_proof_verified: Option<bool>,
+    pub lock_until: Option<DateTime<Utc>>,
+    pub term_product_code: Option<String>,
+    pub auto_roll: bool,
+    pub matured_at: Option<DateTime<Utc>>,
+    pub redeemed_at: Option<DateTime<Utc>>,
+    pub commitment_status: Option<String>,
+    pub commitment_txid: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+struct LineRow {
+    posted_at: DateTime<Utc>,
+    entry_type: String,
+    reference: String,
+    counterparty: Option<String>,
+    memo: Option<String>,
+    amount_satoshis: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StatementLine {
+    pub posted_at: DateTime<Utc>,
+    /// `deposit` or a ledger entry type such as `internal_transfer` or `withdrawal`
+    pub entry_type: String,
+    /// Deposit txid, or the ledger transfer id
+    pub reference: String,
+    /// Other accounts in the same ledger transfer
+    pub counterparty: Option<String>,
+    pub memo: Option<String>,
+    /// Positive for credits, negative for debits
+    pub amount_satoshis: i64,
+    /// Balance after this line
+    pub balance_satoshis: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Line items in `[from, to)` with opening, running and closing balances
+#[derive(Debug, Clone, Serialize)]
+pub struct Statement {
+    pub paymail: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub opening_balance_satoshis: i64,
+    pub closing_balance_satoshis: i64,
+    pub credits_satoshis: i64,
+    pub debits_satoshis: i64,
+    pub lines: Vec<StatementLine>,
+}
+
+/// Attach running balances, starting from `opening`
+fn with_balances(opening: i64, rows: Vec<LineRow>) -> Vec<StatementLine> {
+    let mut balance = opening;
+    rows.into_iter()
+        .map(|row| {
+            balance += row.amount_satoshis;
+            StatementLine {
+                posted_at: row.posted_at,
+                entry_type: row.entry_type,
+                reference: row.reference,
+                counterparty: row.counterparty,
+                memo: row.memo,
+                amount_satoshis: row.amount_satoshis,
+                balance_satoshis: balance,
+            }
+        })
+        .collect()
+}
+
+/// Quote a field if it contains a delimiter, quote or line break (RFC 4180)
+pub fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn statement_csv(lines: &[StatementLine]) -> String {
+    let mut out = String::from("posted_at,entry_type,reference,counterparty,memo,amount_satoshis,balance_satoshis\n");
+    for line in lines {
+        let fields = [
+            line.posted_at.to_rfc3339(),
+            line.entry_type.clone(),
+            line.reference.clone(),
+            line.counterparty.clone().unwrap_or_default(),
+            line.memo.clone().unwrap_or_default(),
+            line.amount_satoshis.to_string(),
+            line.balance_satoshis.to_string(),
+        ];
+        out.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// Build `paymail`'s statement for `[from, to)`
+pub async fn statement(
+    pool: &PgPool,
+    paymail: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<Statement, ServiceError> {
+    let opening: i64 = sqlx::query_scalar(&format!(
+        "SELECT COALESCE(SUM(amount_satoshis), 0)::BIGINT FROM ({}) lines WHERE posted_at < $2",
+        STATEMENT_LINES
+    ))
+    .bind(paymail)
+    .bind(from)
+    .fetch_one(pool)
+    .await
+    .map_err(db_error)?;
+
+    let rows = sqlx::query_as::<_, LineRow>(&format!(
+        r#"
+        SELECT posted_at, entry_type, reference, counterparty, memo, amount_satoshis
+        FROM ({}) lines
+        WHERE posted_at >= $2 AND posted_at < $3
+        ORDER BY posted_at, reference
+        LIMIT $4
+        "#,
+        STATEMENT_LINES
+    ))
+    .bind(paymail)
+    .bind(from)
+    .bind(to)
+    .bind(MAX_STATEMENT_LINES + 1)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+    if rows.len() as i64 > MAX_STATEMENT_LINES {
+        return Err(ServiceError::ValidationError(format!(
+            "More than {} lines in range; request a shorter period",
+            MAX_STATEMENT_LINES
+        )));
+    }
+
+    let lines = with_balances(opening, rows);
+    Ok(Statement {
+        paymail: paymail.to_string(),
+        from,
+        to,
+        opening_balance_satoshis: opening,
+        closing_balance_satoshis: lines.last().map_or(opening, |line| line.balance_satoshis),
+        credits_satoshis: lines.iter().map(|l| l.amount_satoshis.max(0)).sum(),
+        debits_satoshis: lines.iter().map(|l| (-l.amount_satoshis).max(0)).sum(),
+        lines,
+    })
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct DepositHistoryQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+    /// Only deposits created before this, for paging
+    pub before: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StatementQuery {
+    /// Defaults to 30 days before `to`
+    pub from: Option<DateTime<Utc>>,
+    /// Defaults to now
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub format: StatementFormat,
+}
+
+/// `GET /deposits/{paymail}?status=&limit=&before=`: the user's deposits, newest first
+pub async fn list_deposits(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    query: web::Query<DepositHistoryQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ServiceError::ValidationError(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    let deposits = sqlx::query_as::<_, DepositRecord>(&format!(
+        r#"
+        SELECT {} FROM deposits
+        WHERE user_id = (SELECT id FROM users WHERE paymail = $1)
+          AND ($2::TEXT IS NULL OR status = $2)
+          AND ($3::TIMESTAMPTZ IS NULL OR created_at < $3)
+        ORDER BY created_at DESC, id
+        LIMIT $4
+        "#,
+        DEPOSIT_COLUMNS
+    ))
+    .bind(paymail.as_str())
+    .bind(&query.status)
+    .bind(query.before)
+    .bind(limit)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "count": deposits.len(),
+        "deposits": deposits
+    })))
+}
+
+/// `GET /statements/{paymail}?from=&to=&format=json|csv`: line items in `[from, to)` with
+/// opening, running and closing balances
+pub async fn get_statement(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    query: web::Query<StatementQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let to = query.to.unwrap_or_else(Utc::now);
+    let from = query.from.unwrap_or(to - Duration::days(30));
+    if from >= to {
+        return Err(ServiceError::ValidationError("from must be before to".to_string()));
+    }
+    if to - from > Duration::days(MAX_STATEMENT_DAYS) {
+        return Err(ServiceError::ValidationError(format!(
+            "Statements cover at most {} days",
+            MAX_STATEMENT_DAYS
+        )));
+    }
+
+    let statement = statement(pool.get_ref(), &paymail, from, to).await?;
+
+    match query.format {
+        StatementFormat::Csv => Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                format!(
+                    "attachment; filename=\"statement-{}-{}.csv\"",
+                    from.format("%Y%m%d"),
+                    to.format("%Y%m%d")
+                ),
+            ))
+            .body(statement_csv(&statement.lines))),
+        StatementFormat::Json => Ok(HttpResponse::Ok().json(statement)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn row(amount: i64, memo: Option<&str>) -> LineRow {
+        LineRow {
+            posted_at: Utc.with_ymd_and_hms(2025, 11, 25, 12, 0, 0).unwrap(),
+            entry_type: "internal_transfer".to_string(),
+            reference: "t1".to_string(),
+            counterparty: Some("bob@example.com".to_string()),
+            memo: memo.map(str::to_string),
+            amount_satoshis: amount,
+        }
+    }
+
+    #[test]
+    fn test_running_balances() {
+        let lines = with_balances(1_000, vec![row(500, None), row(-200, None), row(50, None)]);
+        let balances: Vec<i64> = lines.iter().map(|l| l.balance_satoshis).collect();
+        assert_eq!(balances, vec![1_500, 1_300, 1_350]);
+        assert!(with_balances(1_000, vec![]).is_empty());
+    }
+
+    #[test]
+    fn test_csv_escaping() {
+        let csv = statement_csv(&with_balances(0, vec![row(-200, Some("rent, \"March\""))]));
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "posted_at,entry_type,reference,counterparty,memo,amount_satoshis,balance_satoshis"
+        );
+        assert_eq!(
+            lines.next().unwrap(),
+            "2025-11-25T12:00:00+00:00,internal_transfer,t1,bob@example.com,\"rent, \"\"March\"\"\",-200,-200"
+        );
+        assert!(lines.next().is_none());
+    }
+}
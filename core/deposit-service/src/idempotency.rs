@@ -0,0 +1,65 @@
+// core/deposit-service/src/idempotency.rs
+// Idempotency-Key handling shared by endpoints that move money, so client retries never act twice
+
+use actix_web::HttpRequest;
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+
+use crate::ServiceError;
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+/// Set on responses that return the result of an earlier request instead of acting again
+pub const REPLAY_HEADER: &str = "Idempotent-Replay";
+
+const MAX_KEY_LEN: usize = 128;
+
+/// The request's `Idempotency-Key`, if it sent one
+pub fn key(req: &HttpRequest) -> Result<Option<String>, ServiceError> {
+    let Some(value) = req.headers().get(IDEMPOTENCY_HEADER) else { return Ok(None) };
+    let key = value
+        .to_str()
+        .map_err(|_| ServiceError::ValidationError(format!("{} must be ASCII", IDEMPOTENCY_HEADER)))?;
+    if key.is_empty() || key.len() > MAX_KEY_LEN || !key.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(ServiceError::ValidationError(format!(
+            "{} must be 1-{} printable ASCII characters",
+            IDEMPOTENCY_HEADER, MAX_KEY_LEN
+        )));
+    }
+    Ok(Some(key.to_string()))
+}
+
+/// SHA-256 of the request's JSON; a key reused with a different fingerprint is rejected
+pub fn fingerprint<T: Serialize>(request: &T) -> String {
+    let json = serde_json::to_vec(request).unwrap_or_default();
+    hex::encode(Sha256::digest(&json))
+}
+
+/// The error for a key already used with another request body
+pub fn key_reused(key: &str, what: &str) -> ServiceError {
+    ServiceError::Conflict(format!("{} {} was already used for a different {}", IDEMPOTENCY_HEADER, key, what))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    #[test]
+    fn test_key_validation() {
+        assert_eq!(key(&TestRequest::default().to_http_request()).unwrap(), None);
+        let req = TestRequest::default().insert_header((IDEMPOTENCY_HEADER, "retry-1")).to_http_request();
+        assert_eq!(key(&req).unwrap().as_deref(), Some("retry-1"));
+        let req = TestRequest::default().insert_header((IDEMPOTENCY_HEADER, "has space")).to_http_request();
+        assert!(key(&req).is_err());
+        let req = TestRequest::default().insert_header((IDEMPOTENCY_HEADER, "x".repeat(MAX_KEY_LEN + 1))).to_http_request();
+        assert!(key(&req).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_tracks_body() {
+        let a = serde_json::json!({ "amount_satoshis": 1_000 });
+        let b = serde_json::json!({ "amount_satoshis": 1_001 });
+        assert_eq!(fingerprint(&a), fingerprint(&a));
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+    }
+}
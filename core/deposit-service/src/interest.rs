@@ -0,0 +1,210 @@
+// core/deposit-service/src/interest.rs
+// Interest postings: postings written by the interest-engine are credited into depositor balances
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bsv_bank_common::{validate_paymail, Claims, SERVICE_PERMISSION};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{ledger, require_paymail, withholding, ServiceError};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+const POSTING_COLUMNS: &str =
+    "id, paymail, amount_satoshis, accrual_count, period_start, period_end, status, transfer_id, \
+     tax_jurisdiction, withheld_satoshis, created_at, settled_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct InterestPosting {
+    pub id: Uuid,
+    pub paymail: String,
+    pub amount_satoshis: i64,
+    pub accrual_count: i32,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Pending until credited to the balance, then Settled
+    pub status: String,
+    pub transfer_id: Option<Uuid>,
+    /// Jurisdiction tax was withheld under when the posting settled
+    pub tax_jurisdiction: Option<String>,
+    /// Part of `amount_satoshis` withheld for tax; negative when a reversal gave it back
+    pub withheld_satoshis: i64,
+    pub created_at: DateTime<Utc>,
+    pub settled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostingQuery {
+    pub limit: Option<i64>,
+    /// Only postings created before this time; pass the last `created_at` of the previous page
+    pub before: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, sqlx::FromRow)]
+struct PendingPosting {
+    user_id: i32,
+    paymail: String,
+    amount_satoshis: i64,
+    status: String,
+}
+
+// ============================================================================
+// SETTLEMENT
+// ============================================================================
+
+/// Credit a posting written by the interest-engine to the depositor's balance, withhold tax
+/// from it and mark its accruals paid. The posting row lock makes settling twice a no-op.
+pub async fn settle_posting(pool: &PgPool, posting_id: Uuid) -> Result<InterestPosting, ServiceError> {
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let posting = sqlx::query_as::<_, PendingPosting>(
+        "SELECT user_id, paymail, amount_satoshis, status FROM interest_postings WHERE id = $1 FOR UPDATE"
+    )
+    .bind(posting_id)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::ValidationError(format!("Interest posting {} not found", posting_id)))?;
+
+    if posting.status == "Pending" {
+        // A negative posting corrects interest credited in error. It is taken back even
+        // if that leaves the balance short, like any other correction to the ledger.
+        let (from, to) = if posting.amount_satoshis > 0 {
+            (ledger::DEPOSIT_INTEREST_ACCOUNT, posting.paymail.as_str())
+        } else {
+            ledger::lock_account(&mut db_tx, &posting.paymail).await.map_err(db_error)?;
+            (posting.paymail.as_str(), ledger::DEPOSIT_INTEREST_ACCOUNT)
+        };
+        let transfer_id = ledger::transfer(
+            &mut db_tx,
+            ledger::DEPOSIT_INTEREST,
+            from,
+            to,
+            posting.amount_satoshis.abs(),
+            None,
+        )
+        .await
+        .map_err(db_error)?;
+        let withheld = withholding::withhold(
+            &mut db_tx,
+            posting_id,
+            posting.user_id,
+            &posting.paymail,
+            posting.amount_satoshis,
+        )
+        .await
+        .map_err(db_error)?;
+        sqlx::query(
+            "UPDATE interest_postings SET status = 'Settled', transfer_id = $2, settled_at = NOW() WHERE id = $1"
+        )
+        .bind(posting_id)
+        .bind(transfer_id)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+        sqlx::query(
+            "UPDATE interest_accruals SET paid_out = true, paid_at = NOW() WHERE posting_id = $1 AND user_id = $2"
+        )
+        .bind(posting_id)
+        .bind(posting.user_id)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+        tracing::info!(
+            "Settled interest posting {}: {} sat to {}, {} sat withheld",
+            posting_id, posting.amount_satoshis, posting.paymail, withheld
+        );
+    }
+
+    let settled = sqlx::query_as::<_, InterestPosting>(&format!(
+        "SELECT {} FROM interest_postings WHERE id = $1",
+        POSTING_COLUMNS
+    ))
+    .bind(posting_id)
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+    Ok(settled)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `POST /internal/interest/postings/{id}/settle`: called by the interest-engine for each
+/// posting it writes; settling an already settled posting returns it unchanged
+pub async fn settle(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    posting_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let is_service = http_req
+        .extensions()
+        .get::<Claims>()
+        .is_some_and(|claims| claims.has_permission(SERVICE_PERMISSION));
+    if !is_service {
+        return Err(ServiceError::Forbidden("Settling interest postings requires a service token".to_string()));
+    }
+
+    let posting = settle_posting(pool.get_ref(), posting_id.into_inner()).await?;
+    Ok(HttpResponse::Ok().json(posting))
+}
+
+/// `GET /interest/postings/{paymail}`: interest credited to the user's balance, newest first
+pub async fn list_postings(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    query: web::Query<PostingQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ServiceError::ValidationError(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    let postings = sqlx::query_as::<_, InterestPosting>(&format!(
+        r#"
+        SELECT {} FROM interest_postings
+        WHERE paymail = $1 AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
+        ORDER BY created_at DESC, id
+        LIMIT $3
+        "#,
+        POSTING_COLUMNS
+    ))
+    .bind(paymail.as_str())
+    .bind(query.before)
+    .bind(limit)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    let (total_posted, accrued): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            COALESCE((SELECT SUM(amount_satoshis) FROM interest_postings
+                      WHERE paymail = $1 AND status = 'Settled'), 0)::BIGINT,
+            COALESCE((SELECT SUM(ia.amount_satoshis) FROM interest_accruals ia JOIN users u ON u.id = ia.user_id
+                      WHERE u.paymail = $1 AND NOT ia.paid_out), 0)::BIGINT
+        "#
+    )
+    .bind(paymail.as_str())
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "total_posted_satoshis": total_posted,
+        "accrued_unposted_satoshis": accrued,
+        "postings": postings
+    })))
+}
@@ -0,0 +1,58 @@
+// core/deposit-service/src/ledger.rs
+// Ledger accounts and entry types behind deposit balances; transfers go through common's ledger
+
+pub use bsv_bank_common::ledger::{ensure_available, lock_account, transfer};
+
+/// Sender balance moved to another user by `POST /transfers`
+pub const INTERNAL_TRANSFER: &str = "internal_transfer";
+
+/// Accrued deposit interest credited to the depositor
+pub const DEPOSIT_INTEREST: &str = "deposit_interest";
+
+/// User balance paid out on chain, posted once the withdrawal confirms
+pub const WITHDRAWAL: &str = "withdrawal";
+
+/// Fee charged on a deposit once it is credited
+pub const DEPOSIT_FEE: &str = "deposit_fee";
+
+/// Fee charged on a withdrawal, posted when it settles
+pub const WITHDRAWAL_FEE: &str = "withdrawal_fee";
+
+/// Depositor balance charged for redeeming a term deposit before maturity
+pub const TERM_PENALTY: &str = "term_deposit_penalty";
+
+/// Tax withheld from a depositor's interest as the posting settles
+pub const INTEREST_WITHHOLDING: &str = "interest_withholding";
+
+/// Early redemption penalties collected by the bank
+pub const TERM_PENALTY_ACCOUNT: &str = "fees:term_deposit_penalties";
+
+/// Deposit fees collected by the bank
+pub const DEPOSIT_FEE_ACCOUNT: &str = "fees:deposits";
+
+/// Withdrawal fees collected by the bank
+pub const WITHDRAWAL_FEE_ACCOUNT: &str = "fees:withdrawals";
+
+/// Interest the bank has paid out to depositors; runs negative
+pub const DEPOSIT_INTEREST_ACCOUNT: &str = "interest_expense:deposits";
+
+/// Funds that have left the bank on chain; runs negative
+pub const WITHDRAWAL_ACCOUNT: &str = "withdrawals:onchain";
+
+/// Interest withheld for one tax jurisdiction, owed to its tax authority
+pub fn withholding_account(jurisdiction: &str) -> String {
+    format!("tax_withholding:{}", jurisdiction)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bsv_bank_common::ledger::is_user_account;
+
+    #[test]
+    fn test_internal_accounts_are_not_users() {
+        assert!(is_user_account("alice@handcash.io"));
+        assert!(!is_user_account(TERM_PENALTY_ACCOUNT));
+        assert!(!is_user_account(&withholding_account("GB")));
+    }
+}
@@ -1,27 +1,44 @@
 // core/deposit-service/src/main.rs
 // Deposit Service with Phase 6 Production Hardening
 
+mod addresses;
+mod approvals;
+mod closures;
+mod commitments;
 mod database;
-mod node_integration;
+mod fees;
+mod history;
+mod idempotency;
+mod interest;
+mod ledger;
+mod monitor_client;
+mod rates_client;
+mod reconciliation;
+mod security;
+mod terms;
+mod tiers;
+mod transfers;
+mod verification;
+mod webhooks;
+mod withdrawals;
+mod withholding;
 mod handlers {
     pub mod health;     // ✅ KEEP - uses common's health but adds service-specific checks
     pub mod auth;       // ✅ KEEP - uses common's auth but with local DB
     pub mod metrics;    // ✅ KEEP - exposes Prometheus endpoint
     // pub mod deposits;   // ✅ KEEP - deposit-specific business logic
 }
-mod middleware;
 
-use actix_web::{web, App, HttpResponse, HttpServer};
+use actix_web::{web, App, HttpMessage, HttpRequest, HttpResponse, HttpServer};
 use actix_cors::Cors;
 use serde::{Deserialize, Serialize};
-use sha2::{Sha256, Digest};
 use chrono::Utc;
 use uuid::Uuid;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use bsv_bank_common::{
-    init_logging, JwtManager, RateLimit, RateLimiter, 
-    RateLimitMiddleware, configure_rate_limits, ServiceMetrics,
+    init_logging, require_paymail, AuthError, AuthMiddleware, Claims, DepositMetrics, JwtManager,
+    LedgerError, RateLimit, RateLimiter, RateLimitMiddleware, configure_rate_limits, ServiceMetrics,
     validate_paymail, validate_txid, validate_amount,
 };
 use dotenv::dotenv;
@@ -42,6 +59,42 @@ enum ServiceError {
     DatabaseError(String),
     #[error("Transaction verification failed: {0}")]
     VerificationError(String),
+    #[error("Output {vout} pays {paid} satoshis, not the {claimed} claimed")]
+    AmountMismatch { vout: u32, claimed: i64, paid: i64 },
+    #[error("Business logic error: {0}")]
+    BusinessError(String),
+    #[error("Conflict: {0}")]
+    Conflict(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    #[error("Service unavailable: {0}")]
+    Unavailable(String),
+}
+
+impl From<verification::VerifyError> for ServiceError {
+    fn from(e: verification::VerifyError) -> Self {
+        match e {
+            verification::VerifyError::Rejected(msg) => ServiceError::VerificationError(msg),
+            verification::VerifyError::AmountMismatch { vout, claimed, paid } => {
+                ServiceError::AmountMismatch { vout, claimed, paid }
+            }
+        }
+    }
+}
+
+impl From<LedgerError> for ServiceError {
+    fn from(e: LedgerError) -> Self {
+        match e {
+            LedgerError::InsufficientFunds { .. } => ServiceError::BusinessError(e.to_string()),
+            LedgerError::Database(e) => ServiceError::DatabaseError(e.to_string()),
+        }
+    }
+}
+
+impl From<AuthError> for ServiceError {
+    fn from(e: AuthError) -> Self {
+        ServiceError::Forbidden(e.to_string())
+    }
 }
 
 impl actix_web::ResponseError for ServiceError {
@@ -65,10 +118,62 @@ impl actix_web::ResponseError for ServiceError {
                     "message": msg
                 }))
             }
+            ServiceError::AmountMismatch { vout, claimed, paid } => {
+                HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                    "error": "amount_mismatch",
+                    "message": self.to_string(),
+                    "vout": vout,
+                    "claimed_satoshis": claimed,
+                    "paid_satoshis": paid
+                }))
+            }
+            ServiceError::BusinessError(msg) => {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "business_error",
+                    "message": msg
+                }))
+            }
+            ServiceError::Conflict(msg) => {
+                HttpResponse::Conflict().json(serde_json::json!({
+                    "error": "conflict",
+                    "message": msg
+                }))
+            }
+            ServiceError::Forbidden(msg) => {
+                HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "forbidden",
+                    "message": msg
+                }))
+            }
+            ServiceError::Unavailable(msg) => {
+                HttpResponse::ServiceUnavailable().json(serde_json::json!({
+                    "error": "unavailable",
+                    "message": msg
+                }))
+            }
         }
     }
 }
 
+/// Require a bearer token with the `admin` permission; `AuthMiddleware` has already verified it
+fn require_admin(http_req: &HttpRequest) -> Result<(), ServiceError> {
+    let is_admin = http_req
+        .extensions()
+        .get::<Claims>()
+        .is_some_and(|claims| claims.has_permission("admin"));
+    if !is_admin {
+        return Err(ServiceError::Forbidden("Admin permission required".to_string()));
+    }
+    Ok(())
+}
+
+/// Routes callable without a token: probes, metrics and the auth endpoints themselves
+fn is_public_route(_method: &actix_web::http::Method, path: &str) -> bool {
+    ["/health", "/liveness", "/readiness", "/metrics", "/register", "/login", "/refresh"]
+        .iter()
+        .any(|p| path.starts_with(p))
+}
+
 // ============================================================================
 // DATA TYPES
 // ============================================================================
@@ -78,7 +183,13 @@ pub struct DepositRequest {
     pub user_paymail: String,
     pub amount_satoshis: i64,
     pub txid: String,
+    /// Output paying the user's deposit address; the first such output when omitted
+    pub vout: Option<u32>,
     pub lock_duration_days: Option<i32>,
+    /// Term deposit product; locks the deposit for the product's term instead of `lock_duration_days`
+    pub term_product: Option<String>,
+    /// Overrides the product's auto-roll default
+    pub auto_roll: Option<bool>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -86,44 +197,181 @@ pub struct DepositResponse {
     pub deposit_id: String,
     pub status: String,
     pub estimated_confirmation_time: String,
+    /// Anchored on chain shortly after; see `GET /deposits/{id}/commitment`
+    pub commitment_hash: String,
+    /// Charged against the balance once the deposit is credited
+    pub fee_satoshis: i64,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BalanceResponse {
     pub paymail: String,
+    /// Everything the user owns, including funds held below
     pub balance_satoshis: i64,
+    /// What withdrawals and channel funding can draw on
+    pub available_satoshis: i64,
+    pub pending_satoshis: i64,
+    pub pending: PendingBalance,
     pub accrued_interest_satoshis: i64,
     pub total_available_satoshis: i64,
-    pub current_apy: f64,
+    /// Demand deposit APY in percent, from interest-engine; null when it cannot be reached
+    pub current_apy: Option<f64>,
     pub active_deposits: i64,
+    /// KYC tier and what is left of its deposit and withdrawal limits
+    pub limits: tiers::TierUsage,
+}
+
+/// Funds not yet available: unconfirmed deposits are outside the balance, the rest is held within it
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PendingBalance {
+    pub unconfirmed_deposits_satoshis: i64,
+    pub withdrawals_in_flight_satoshis: i64,
+    pub locked_deposits_satoshis: i64,
+    pub loan_collateral_satoshis: i64,
+    pub channel_funds_satoshis: i64,
+}
+
+impl PendingBalance {
+    pub fn total(&self) -> i64 {
+        self.unconfirmed_deposits_satoshis
+            + self.withdrawals_in_flight_satoshis
+            + self.locked_deposits_satoshis
+            + self.loan_collateral_satoshis
+            + self.channel_funds_satoshis
+    }
+}
+
+#[derive(Debug, Default, sqlx::FromRow)]
+struct BalanceRow {
+    balance_satoshis: i64,
+    accrued_interest_satoshis: i64,
+    active_deposits: i64,
+    available_satoshis: i64,
+    pending_deposits_satoshis: i64,
+    withdrawals_in_flight_satoshis: i64,
+    locked_deposits_satoshis: i64,
+    loan_collateral_satoshis: i64,
+    channel_funds_satoshis: i64,
 }
 
 // ============================================================================
 // HANDLERS (Business Logic Only - Validation via common)
 // ============================================================================
 
+#[derive(sqlx::FromRow)]
+struct StoredDeposit {
+    request_fingerprint: Option<String>,
+    id: Uuid,
+    status: String,
+    commitment_hash: Option<String>,
+    fee_satoshis: i64,
+}
+
+/// The response for the deposit `paymail` already created with `key`, if any
+async fn replay_deposit(
+    pool: &PgPool,
+    paymail: &str,
+    key: &str,
+    fingerprint: &str,
+) -> Result<Option<HttpResponse>, ServiceError> {
+    let stored: Option<StoredDeposit> = sqlx::query_as(
+        r#"
+        SELECT request_fingerprint, id, status, commitment_hash, fee_satoshis FROM deposits
+        WHERE user_id = (SELECT id FROM users WHERE paymail = $1) AND idempotency_key = $2
+        "#
+    )
+    .bind(paymail)
+    .bind(key)
+    .fetch_optional(pool)
+    .await
+    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    match stored {
+        Some(stored) if stored.request_fingerprint.as_deref() == Some(fingerprint) => {
+            tracing::info!("Replaying deposit {} for {} key {}", stored.id, paymail, key);
+            Ok(Some(HttpResponse::Ok().insert_header((idempotency::REPLAY_HEADER, "true")).json(DepositResponse {
+                deposit_id: stored.id.to_string(),
+                status: stored.status,
+                estimated_confirmation_time: "~60 seconds".to_string(),
+                commitment_hash: stored.commitment_hash.unwrap_or_default(),
+                fee_satoshis: stored.fee_satoshis,
+            })))
+        }
+        Some(_) => Err(idempotency::key_reused(key, "deposit")),
+        None => Ok(None),
+    }
+}
+
+/// `POST /deposits`. Retries carrying the same `Idempotency-Key` return the original deposit.
 async fn create_deposit(
     pool: web::Data<PgPool>,
+    monitor: web::Data<monitor_client::MonitorClient>,
+    verifier: web::Data<verification::DepositVerifier>,
+    webhooks: web::Data<webhooks::WebhookDispatcher>,
+    metrics: web::Data<DepositMetrics>,
+    http_req: HttpRequest,
     request: web::Json<DepositRequest>,
 ) -> Result<HttpResponse, ServiceError> {
     // Phase 6: Validate all inputs using common library
     validate_paymail(&request.user_paymail)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.user_paymail)?;
     validate_txid(&request.txid)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
     validate_amount(request.amount_satoshis)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    if request.term_product.is_some() && request.lock_duration_days.is_some() {
+        return Err(ServiceError::ValidationError(
+            "A term product sets its own lock; omit lock_duration_days".to_string()
+        ));
+    }
+    if request.auto_roll.is_some() && request.term_product.is_none() {
+        return Err(ServiceError::ValidationError("auto_roll applies only to term deposits".to_string()));
+    }
+    closures::ensure_open(pool.get_ref(), &request.user_paymail).await?;
+    let idempotency_key = idempotency::key(&http_req)?;
+    let fingerprint = idempotency::fingerprint(&*request);
+    if let Some(key) = &idempotency_key {
+        if let Some(replay) = replay_deposit(&pool, &request.user_paymail, key, &fingerprint).await? {
+            return Ok(replay);
+        }
+    }
+    let term = match request.term_product.as_deref() {
+        Some(code) => Some(terms::TermProduct::for_deposit(pool.get_ref(), code).await?),
+        None => None,
+    };
 
-    // Verify transaction on BSV blockchain
-    let verified = node_integration::verify_transaction_real(&request.txid)
+    let fee = fees::terms(pool.get_ref(), fees::Operation::Deposit, &request.user_paymail, request.term_product.as_deref())
         .await
-        .map_err(|e| ServiceError::VerificationError(e))?;
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+        .deposit_fee(request.amount_satoshis);
     
-    if !verified {
-        return Err(ServiceError::VerificationError("Transaction not found or invalid".to_string()));
-    }
+    let deposit_address = addresses::assigned_address(pool.get_ref(), &request.user_paymail)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+        .ok_or_else(|| ServiceError::VerificationError(
+            "No deposit address assigned; request one with GET /deposit-address/{paymail}".to_string()
+        ))?;
+    
+    // The output must pay the user's deposit address exactly; it is credited once SPV-proven
+    let verified = verifier.verify(&request.txid, &deposit_address, request.vout, request.amount_satoshis)
+        .await?;
     
-    let confirmations = 6; // Assume confirmed for now
+    // Each output is credited once, to one user
+    let already_credited: bool = sqlx::query_scalar(
+        "SELECT EXISTS (SELECT 1 FROM deposits WHERE txid = $1 AND (vout = $2 OR vout IS NULL))"
+    )
+    .bind(&request.txid)
+    .bind(verified.vout as i32)
+    .fetch_one(pool.as_ref())
+    .await
+    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    if already_credited {
+        return Err(ServiceError::Conflict(format!(
+            "Output {} of {} has already been deposited",
+            verified.vout, request.txid
+        )));
+    }
     
     // Get or create user
     let user_id = database::get_or_create_user(&pool, &request.user_paymail)
@@ -132,20 +380,44 @@ async fn create_deposit(
     
     let deposit_id = Uuid::new_v4();
     let now = Utc::now();
-    let status = if confirmations >= 6 { "Confirmed" } else { "Pending" };
+    let status = if verified.spv_verified { "Confirmed" } else { "Pending" };
     
-    let lock_until = request.lock_duration_days.map(|days| {
+    let lock_days = term.as_ref().map(|t| t.duration_days).or(request.lock_duration_days);
+    let lock_until = lock_days.map(|days| {
         now + chrono::Duration::days(days as i64)
     });
+    let auto_roll = term.as_ref().is_some_and(|t| request.auto_roll.unwrap_or(t.auto_roll));
     
-    // Insert deposit
-    sqlx::query!(
+    // Anchored on chain in the background as proof the deposit was processed
+    let commitment = commitments::commitment_hash(
+        &request.user_paymail,
+        request.amount_satoshis,
+        &request.txid,
+        now.timestamp(),
+    );
+    
+    // Insert deposit, charging its fee straight away if it is already credited
+    let mut db_tx = pool.begin()
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    // Held until commit, so concurrent deposits each count the other against the tier limits
+    ledger::lock_account(&mut db_tx, &request.user_paymail)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    tiers::usage(&mut *db_tx, &request.user_paymail)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+        .check(tiers::Flow::Deposit, request.amount_satoshis)?;
+    let inserted = sqlx::query!(
         r#"
         INSERT INTO deposits (
             id, user_id, paymail, amount_satoshis, txid, 
-            confirmations, status, lock_until, created_at, confirmed_at
+            confirmations, status, lock_until, created_at, confirmed_at,
+            block_height, spv_proof_verified, verified_at, vout, deposit_address,
+            term_product_code, term_duration_days, term_apy_boost_bps, early_withdrawal_penalty_bps, auto_roll,
+            commitment_hash, commitment_status, idempotency_key, request_fingerprint, fee_satoshis
         )
-        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $10, $13, $14, $15, $16, $17, $18, $19, $20, 'pending', $21, $22, $23)
         RETURNING id
         "#,
         deposit_id,
@@ -153,90 +425,177 @@ async fn create_deposit(
         request.user_paymail,
         request.amount_satoshis,
         request.txid,
-        confirmations as i32,
+        verified.confirmations,
         status,
         lock_until,
         now,
-        if confirmations >= 6 { Some(now) } else { None }
+        if verified.spv_verified { Some(now) } else { None },
+        verified.block_height.map(i64::from),
+        verified.spv_verified,
+        verified.vout as i32,
+        deposit_address,
+        term.as_ref().map(|t| t.code.clone()),
+        term.as_ref().map(|t| t.duration_days),
+        term.as_ref().map(|t| t.apy_boost_bps),
+        term.as_ref().map(|t| t.early_withdrawal_penalty_bps),
+        auto_roll,
+        commitment,
+        idempotency_key,
+        idempotency_key.as_ref().map(|_| fingerprint.clone()),
+        fee
     )
-    .fetch_one(pool.as_ref())
-    .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    .fetch_one(&mut *db_tx)
+    .await;
+    let inserted = match inserted {
+        // A concurrent retry with the same key got there first
+        Err(sqlx::Error::Database(db)) if db.constraint() == Some("idx_deposits_idempotency") => {
+            let key = idempotency_key.as_deref().unwrap_or_default();
+            return replay_deposit(&pool, &request.user_paymail, key, &fingerprint)
+                .await?
+                .ok_or_else(|| ServiceError::Conflict("Deposit changed concurrently, retry".to_string()));
+        }
+        other => other,
+    };
+    inserted.map_err(|e| match e {
+        // Lost a race with another submission of the same output
+        sqlx::Error::Database(db) if db.is_unique_violation() => ServiceError::Conflict(format!(
+            "Output {} of {} has already been deposited",
+            verified.vout, request.txid
+        )),
+        e => ServiceError::DatabaseError(e.to_string()),
+    })?;
+    fees::charge_deposit(&mut db_tx, deposit_id)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    db_tx.commit()
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
     
-    // Create on-chain commitment
-    let commitment_data = format!(
-        "DEPOSIT|{}|{}|{}|{}",
-        request.user_paymail,
-        request.amount_satoshis,
-        request.txid,
-        now.timestamp()
+    metrics.record_deposit_status(status, request.amount_satoshis);
+    if verified.spv_verified {
+        metrics.observe_time_to_confirmation(0.0);
+    }
+    tracing::info!(
+        "Deposit created: {} for {} ({}, output {}, commitment: {})",
+        deposit_id, request.user_paymail, status, verified.vout, commitment
     );
-    let mut hasher = Sha256::new();
-    hasher.update(commitment_data.as_bytes());
-    let hash = hex::encode(hasher.finalize());
     
-    tracing::info!("Deposit created: {} for {} (commitment: 6a{})", deposit_id, request.user_paymail, hash);
+    let event_data = serde_json::json!({
+        "deposit_id": deposit_id,
+        "txid": request.txid,
+        "vout": verified.vout,
+        "amount_satoshis": request.amount_satoshis,
+        "fee_satoshis": fee,
+        "status": status
+    });
+    webhooks.emit(&request.user_paymail, webhooks::EVENT_DEPOSIT_DETECTED, event_data.clone());
+    if verified.spv_verified {
+        webhooks.emit(&request.user_paymail, webhooks::EVENT_DEPOSIT_CONFIRMED, event_data);
+    }
+    
+    // The deposit stands without it; the monitor's address polling still finds the txid
+    if let Err(e) = monitor
+        .track_transaction(&request.txid, "deposit", &deposit_id.to_string())
+        .await
+    {
+        tracing::warn!("Failed to register deposit txid {} with blockchain-monitor: {}", request.txid, e);
+    }
     
     Ok(HttpResponse::Ok().json(DepositResponse {
         deposit_id: deposit_id.to_string(),
         status: status.to_string(),
         estimated_confirmation_time: "~60 seconds".to_string(),
+        commitment_hash: commitment,
+        fee_satoshis: fee,
     }))
 }
 
 async fn get_user_balance(
     pool: web::Data<PgPool>,
+    rates: web::Data<rates_client::RatesClient>,
+    http_req: HttpRequest,
     paymail: web::Path<String>,
 ) -> Result<HttpResponse, ServiceError> {
     // Phase 6: Validate paymail
     validate_paymail(&paymail)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
 
-    let result = sqlx::query!(
+    let balance = sqlx::query_as::<_, BalanceRow>(
         r#"
-        SELECT 
-            balance_satoshis,
-            accrued_interest_satoshis,
-            active_deposits
+        SELECT
+            balance_satoshis::BIGINT,
+            accrued_interest_satoshis::BIGINT,
+            active_deposits::BIGINT,
+            available_satoshis::BIGINT,
+            pending_deposits_satoshis::BIGINT,
+            withdrawals_in_flight_satoshis::BIGINT,
+            locked_deposits_satoshis::BIGINT,
+            loan_collateral_satoshis::BIGINT,
+            channel_funds_satoshis::BIGINT
         FROM user_balances
         WHERE paymail = $1
-        "#,
-        paymail.as_str()
+        "#
     )
+    .bind(paymail.as_str())
     .fetch_optional(pool.as_ref())
     .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
+    .unwrap_or_default();
     
-    // let balance = result.unwrap_or_else(|| {
-    //     // Return mock data if user doesn't exist yet
-    //     sqlx::query!(
-    //         "SELECT 0::BIGINT as balance_satoshis, 0::BIGINT as accrued_interest_satoshis, 0::BIGINT as active_deposits"
-    //     ).fetch_one(pool.as_ref()).await.unwrap()
-    // });
-    
-    // let bal = balance.balance_satoshis.unwrap_or(0);
-    // let interest = balance.accrued_interest_satoshis.unwrap_or(0);
-    
-    // Handle Some/None directly
-    let (bal, interest, active) = match result {
-        Some(balance) => (
-            balance.balance_satoshis.unwrap_or(0),
-            balance.accrued_interest_satoshis.unwrap_or(0),
-            balance.active_deposits.unwrap_or(0),
-        ),
-        None => (0, 0, 0),
+    let limits = tiers::usage(pool.get_ref(), &paymail)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    
+    // Demand deposit APY as a percentage; unknown while interest-engine is unreachable
+    let current_apy = match rates.product_rate(rates_client::DEMAND_PRODUCT, None).await {
+        Ok(rate) => Some(rate.supply_apy * 100.0),
+        Err(e) => {
+            tracing::warn!("Demand deposit rate unavailable: {}", e);
+            None
+        }
+    };
+    
+    let pending = PendingBalance {
+        unconfirmed_deposits_satoshis: balance.pending_deposits_satoshis,
+        withdrawals_in_flight_satoshis: balance.withdrawals_in_flight_satoshis,
+        locked_deposits_satoshis: balance.locked_deposits_satoshis,
+        loan_collateral_satoshis: balance.loan_collateral_satoshis,
+        channel_funds_satoshis: balance.channel_funds_satoshis,
     };
     
     Ok(HttpResponse::Ok().json(BalanceResponse {
         paymail: paymail.to_string(),
-        balance_satoshis: bal,
-        accrued_interest_satoshis: interest,
-        total_available_satoshis: bal + interest,
-        current_apy: 7.0,
-        active_deposits: active,
+        balance_satoshis: balance.balance_satoshis,
+        available_satoshis: balance.available_satoshis,
+        pending_satoshis: pending.total(),
+        pending,
+        accrued_interest_satoshis: balance.accrued_interest_satoshis,
+        total_available_satoshis: balance.available_satoshis + balance.accrued_interest_satoshis,
+        current_apy,
+        active_deposits: balance.active_deposits,
+        limits,
     }))
 }
 
+async fn start_active_deposits_refresh(pool: PgPool, metrics: DepositMetrics, interval_secs: u64) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+    
+    loop {
+        interval.tick().await;
+        
+        // Matches `active_deposits` in the balance view
+        let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM deposits WHERE status = 'Confirmed'")
+            .fetch_one(&pool)
+            .await;
+        
+        match count {
+            Ok(count) => metrics.set_active_deposits(count),
+            Err(e) => tracing::warn!("Failed to refresh active deposit gauge: {}", e),
+        }
+    }
+}
+
 // ============================================================================
 // MAIN
 // ============================================================================
@@ -285,10 +644,20 @@ async fn main() -> std::io::Result<()> {
     // let service_metrics = ServiceMetrics::new(&registry, "deposit_service")
     let _service_metrics = ServiceMetrics::new(&registry, "deposit_service")
         .expect("Failed to create service metrics");
-    let _deposit_metrics = bsv_bank_common::DepositMetrics::new(&registry)
+    let deposit_metrics = DepositMetrics::new(&registry)
         .expect("Failed to create deposit metrics");
     tracing::info!("Metrics initialized");
     
+    let gauge_refresh_secs = std::env::var("ACTIVE_DEPOSITS_REFRESH_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+    tokio::spawn(start_active_deposits_refresh(
+        db_pool.clone(),
+        deposit_metrics.clone(),
+        gauge_refresh_secs,
+    ));
+    
     // Phase 6: Rate limiter
     let mut rate_limiter = RateLimiter::new();
     rate_limiter.add_limit("deposits".to_string(), RateLimit::per_minute(100));
@@ -314,6 +683,48 @@ async fn main() -> std::io::Result<()> {
     });
     
     let registry_data = web::Data::new(registry);
+    let monitor_client = web::Data::new(monitor_client::MonitorClient::from_env(jwt_manager.clone()));
+    let rates_client = web::Data::new(rates_client::RatesClient::from_env());
+    
+    // Deposits are credited only once spv-service proves them; recheck the ones still Pending
+    let verifier = verification::DepositVerifier::from_env();
+    let webhook_dispatcher = webhooks::WebhookDispatcher::new(db_pool.clone());
+    tokio::spawn(verification::start_confirmation_checks(
+        db_pool.clone(),
+        verifier.clone(),
+        webhook_dispatcher.clone(),
+        deposit_metrics.clone(),
+    ));
+    tokio::spawn(withdrawals::start_withdrawal_settlement(db_pool.clone()));
+    tokio::spawn(webhooks::start_withdrawal_notifications(
+        db_pool.clone(),
+        webhook_dispatcher.clone(),
+        deposit_metrics.clone(),
+    ));
+    // On-chain holdings are checked against ledger liabilities; drift alerts service-wide webhooks
+    tokio::spawn(reconciliation::start_reconciliation(
+        db_pool.clone(),
+        monitor_client.get_ref().clone(),
+        webhook_dispatcher.clone(),
+        reconciliation::ReconciliationConfig::from_env(),
+    ));
+    let webhook_dispatcher = web::Data::new(webhook_dispatcher);
+    let verifier = web::Data::new(verifier);
+    
+    // Term deposits roll over or mature once their lock ends
+    tokio::spawn(terms::start_term_maturity(db_pool.clone()));
+    // Deposit commitments are written to OP_RETURNs and proven once mined
+    tokio::spawn(commitments::CommitmentAnchor::from_env(jwt_manager.clone()).start(db_pool.clone()));
+    let transfer_limits = web::Data::new(transfers::TransferLimits::from_env());
+    let withdrawal_policy = security::WithdrawalPolicy::from_env();
+    // Closing accounts are archived once their sweep settles
+    tokio::spawn(closures::start_account_archival(
+        db_pool.clone(),
+        withdrawal_policy.clone(),
+        deposit_metrics.clone(),
+    ));
+    let withdrawal_policy = web::Data::new(withdrawal_policy);
+    let deposit_metrics = web::Data::new(deposit_metrics);
     
     println!("✅ Service ready on http://0.0.0.0:{}", port);
     println!("📋 Health: http://0.0.0.0:{}/health", port);
@@ -338,7 +749,7 @@ async fn main() -> std::io::Result<()> {
             // // Phase 6: Metrics middleware
             // .wrap(middleware::metrics::MetricsMiddleware::new(service_metrics.clone()))
             // Phase 6: Auth middleware
-            .wrap(middleware::auth::AuthMiddleware::new(jwt_manager.clone()))
+            .wrap(AuthMiddleware::new(jwt_manager.clone(), is_public_route))
             // Add metrics middleware if you have it
             // .wrap(bsv_bank_common::MetricsMiddleware::new(service_metrics.clone()))
             // Phase 6: Security headers
@@ -354,6 +765,13 @@ async fn main() -> std::io::Result<()> {
             .app_data(health_state.clone())
             .app_data(auth_state.clone())
             .app_data(registry_data.clone())
+            .app_data(monitor_client.clone())
+            .app_data(rates_client.clone())
+            .app_data(verifier.clone())
+            .app_data(transfer_limits.clone())
+            .app_data(webhook_dispatcher.clone())
+            .app_data(withdrawal_policy.clone())
+            .app_data(deposit_metrics.clone())
             // Health endpoints (no auth)
             .route("/health", web::get().to(handlers::health::health_check))
             .route("/liveness", web::get().to(handlers::health::liveness_probe))
@@ -366,7 +784,52 @@ async fn main() -> std::io::Result<()> {
             .route("/refresh", web::post().to(handlers::auth::refresh_token))
             // Business endpoints (with auth)
             .route("/deposits", web::post().to(create_deposit))
+            .route("/deposits/{paymail}", web::get().to(history::list_deposits))
+            .route("/deposits/{id}/commitment", web::get().to(commitments::get_commitment))
+            .route("/deposits/{id}/redeem", web::post().to(terms::redeem_deposit))
+            .route("/deposits/{id}/auto-roll", web::put().to(terms::set_auto_roll))
+            .route("/transfers", web::post().to(transfers::create_transfer))
+            .route("/transfers/{paymail}", web::get().to(transfers::list_transfers))
+            .route("/interest/postings/{paymail}", web::get().to(interest::list_postings))
+            .route("/internal/interest/postings/{id}/settle", web::post().to(interest::settle))
+            .route("/webhooks", web::post().to(webhooks::register_webhook))
+            .route("/webhooks", web::get().to(webhooks::list_webhooks))
+            .route("/webhooks/{id}", web::delete().to(webhooks::delete_webhook))
+            .route("/withdrawals", web::post().to(withdrawals::create_withdrawal))
+            .route("/withdrawals/{paymail}", web::get().to(withdrawals::list_withdrawals))
+            .route("/admin/withdrawals/pending-approval", web::get().to(approvals::list_pending))
+            .route("/admin/withdrawals/{id}/approve", web::post().to(approvals::approve_withdrawal))
+            .route("/admin/withdrawals/{id}/reject", web::post().to(approvals::reject_withdrawal))
+            .route("/admin/withdrawals/{id}/events", web::get().to(approvals::list_events))
+            .route("/users/{paymail}/2fa/setup", web::post().to(security::setup_two_factor))
+            .route("/users/{paymail}/2fa/enable", web::post().to(security::enable_two_factor))
+            .route("/users/{paymail}/2fa/disable", web::post().to(security::disable_two_factor))
+            .route("/users/{paymail}/withdrawal-addresses", web::get().to(security::list_whitelist))
+            .route("/users/{paymail}/withdrawal-addresses", web::post().to(security::add_whitelist_address))
+            .route(
+                "/users/{paymail}/withdrawal-addresses/{address}",
+                web::delete().to(security::remove_whitelist_address),
+            )
+            .route("/fees/schedule", web::get().to(fees::get_schedule))
+            .route("/fees/schedule", web::put().to(fees::set_rule))
+            .route("/fees/schedule/{id}", web::delete().to(fees::delete_rule))
+            .route("/kyc-tiers", web::get().to(tiers::list_tiers))
+            .route("/users/{paymail}/kyc-tier", web::put().to(tiers::set_user_tier))
+            .route("/term-products", web::get().to(terms::list_products))
+            .route("/term-products", web::post().to(terms::create_product))
+            .route("/term-products/{code}", web::get().to(terms::get_product))
+            .route("/term-products/{code}", web::put().to(terms::update_product))
+            .route("/deposit-address/{paymail}", web::get().to(addresses::get_deposit_address))
+            .route("/deposit-addresses", web::post().to(addresses::load_addresses))
             .route("/balance/{paymail}", web::get().to(get_user_balance))
+            .route("/accounts/{paymail}/close", web::post().to(closures::close_account))
+            .route("/accounts/{paymail}/closure", web::get().to(closures::get_closure))
+            .route("/statements/{paymail}", web::get().to(history::get_statement))
+            .route("/reports/interest/{paymail}", web::get().to(withholding::get_interest_report))
+            .route("/tax-jurisdictions", web::get().to(withholding::list_jurisdictions))
+            .route("/tax-jurisdictions/{code}", web::put().to(withholding::set_jurisdiction))
+            .route("/users/{paymail}/tax-jurisdiction", web::put().to(withholding::set_user_jurisdiction))
+            .route("/reconciliation/latest", web::get().to(reconciliation::get_latest))
     })
     .bind(("0.0.0.0", port))?
     .run()
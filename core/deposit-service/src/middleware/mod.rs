@@ -1,3 +0,0 @@
-// core/deposit-service/src/middleware/mod.rs
-pub mod auth;
-// pub mod metrics;
\ No newline at end of file
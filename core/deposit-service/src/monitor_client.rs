@@ -0,0 +1,94 @@
+// core/deposit-service/src/monitor_client.rs
+// Registers deposit txids and addresses with blockchain-monitor so confirmation tracking starts immediately
+
+use bsv_bank_common::JwtManager;
+use serde::Deserialize;
+
+/// Subject of this service's tokens for blockchain-monitor's internal API
+const SERVICE_NAME: &str = "deposit-service";
+
+/// An address's balance as blockchain-monitor reports it
+#[derive(Debug, Clone, Copy, Deserialize)]
+pub struct AddressBalance {
+    pub confirmed_satoshis: i64,
+    pub unconfirmed_satoshis: i64,
+}
+
+#[derive(Clone)]
+pub struct MonitorClient {
+    client: reqwest::Client,
+    monitor_url: String,
+    jwt: JwtManager,
+}
+
+impl MonitorClient {
+    pub fn from_env(jwt: JwtManager) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            monitor_url: std::env::var("BLOCKCHAIN_MONITOR_URL")
+                .unwrap_or_else(|_| "http://localhost:8084".to_string()),
+            jwt,
+        }
+    }
+
+    async fn post(&self, path: &str, body: serde_json::Value) -> Result<(), String> {
+        let token = self.jwt
+            .create_service_token(SERVICE_NAME)
+            .map_err(|e| format!("Token error: {}", e))?;
+
+        let response = self.client
+            .post(format!("{}{}", self.monitor_url, path))
+            .bearer_auth(token)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        Ok(())
+    }
+
+    /// `GET /address/{address}/balance` on blockchain-monitor
+    pub async fn address_balance(&self, address: &str) -> Result<AddressBalance, String> {
+        let token = self.jwt
+            .create_service_token(SERVICE_NAME)
+            .map_err(|e| format!("Token error: {}", e))?;
+
+        let response = self.client
+            .get(format!("{}/address/{}/balance", self.monitor_url, address))
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        response.json().await.map_err(|e| format!("Parse error: {}", e))
+    }
+
+    /// `POST /track/tx` on blockchain-monitor
+    pub async fn track_transaction(&self, txid: &str, purpose: &str, reference: &str) -> Result<(), String> {
+        self.post("/track/tx", serde_json::json!({
+            "txid": txid,
+            "purpose": purpose,
+            "reference": reference,
+        }))
+        .await
+    }
+
+    /// `POST /watch/address` on blockchain-monitor, so payments to a deposit address are seen
+    pub async fn watch_address(&self, address: &str, paymail: &str) -> Result<(), String> {
+        self.post("/watch/address", serde_json::json!({
+            "address": address,
+            "paymail": paymail,
+            "purpose": "deposit",
+        }))
+        .await
+    }
+}
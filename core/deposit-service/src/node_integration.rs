@@ -1,49 +0,0 @@
-// core/deposit-service/src/node-integration.rs
-
-// Abortive attempt with Grok
-// Remove or comment this line:
-// use bsv_node::BsvNodeClient;
-// use std::env;
-
-// For now, create a stub:
-pub struct BsvNodeClient;
-
-impl BsvNodeClient {
-    pub fn new(_url: String, _user: String, _pass: String) -> Self {
-        Self
-    }
-
-    // pub async fn verify_transaction(&self, _txid: &str) -> Result<bool, String> {
-    //     Ok(true)
-    // }
-
-    pub async fn verify_spv(&self, _txid: &str, _confirmations: u32) -> Result<bool, String> {
-        Ok(true)
-    }
-
-    pub async fn get_transaction(&self, _txid: &str) -> Result<String, String> {
-        Ok("mock_tx_data".to_string())
-    }
-}
-
-pub async fn verify_transaction_real(txid: &str) -> Result<bool, String> {
-    let url = std::env::var("BSV_NODE_URL").unwrap_or_else(|_| "http://localhost:8332".to_string());
-    let user = std::env::var("BSV_NODE_USER").unwrap_or_else(|_| "user".to_string());
-    let pass = std::env::var("BSV_NODE_PASS").unwrap_or_else(|_| "pass".to_string());
-    
-    let client = BsvNodeClient::new(url, user, pass);
-    
-    match client.verify_spv(txid, 6).await {
-        Ok(verified) => {
-            if verified {
-                match client.get_transaction(txid).await {
-                    Ok(_tx_data) => Ok(true),
-                    Err(_) => Ok(false),
-                }
-            } else {
-                Ok(false)
-            }
-        }
-        Err(_) => Ok(false),
-    }
-}
@@ -0,0 +1,47 @@
+// core/deposit-service/src/rates_client.rs
+// Looks up product interest rates from interest-engine
+
+use serde::Deserialize;
+
+/// interest-engine product for balances outside a term deposit
+pub const DEMAND_PRODUCT: &str = "demand";
+
+/// The part of interest-engine's product rate that depositors earn
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProductRate {
+    pub supply_apy: f64,
+}
+
+#[derive(Clone)]
+pub struct RatesClient {
+    client: reqwest::Client,
+    engine_url: String,
+}
+
+impl RatesClient {
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            engine_url: std::env::var("INTEREST_ENGINE_URL")
+                .unwrap_or_else(|_| "http://localhost:8081".to_string()),
+        }
+    }
+
+    /// `GET /rates/products/{product}` on interest-engine, priced by `fallback` when the
+    /// product has no rate model of its own
+    pub async fn product_rate(&self, product: &str, fallback: Option<&str>) -> Result<ProductRate, String> {
+        let mut request = self.client.get(format!("{}/rates/products/{}", self.engine_url, product));
+        if let Some(fallback) = fallback {
+            request = request.query(&[("fallback", fallback)]);
+        }
+
+        let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        response.json().await.map_err(|e| format!("Parse error: {}", e))
+    }
+}
@@ -0,0 +1,287 @@
+// core/deposit-service/src/reconciliation.rs
+// Reconciliation: on-chain balances of bank-controlled addresses against the ledger's liabilities
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::monitor_client::MonitorClient;
+use crate::webhooks::{self, WebhookDispatcher};
+use crate::{require_admin, ServiceError};
+
+pub const STATUS_BALANCED: &str = "balanced";
+pub const STATUS_DRIFT: &str = "drift";
+/// Some address could not be read, so the totals prove nothing either way
+pub const STATUS_INCOMPLETE: &str = "incomplete";
+
+const RUN_COLUMNS: &str = "id, status, addresses_checked, addresses_failed, onchain_confirmed_satoshis, \
+    onchain_unconfirmed_satoshis, liabilities_satoshis, unsettled_withdrawals_satoshis, drift_satoshis, \
+    tolerance_satoshis, started_at, completed_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct ReconciliationConfig {
+    pub interval_secs: u64,
+    /// Drift within this many satoshis either way counts as balanced
+    pub tolerance_satoshis: i64,
+    /// Bank wallets beyond the deposit address pool, e.g. the withdrawal hot wallet
+    pub extra_addresses: Vec<String>,
+}
+
+impl ReconciliationConfig {
+    /// `RECONCILIATION_INTERVAL_SECS` (default 3600), `RECONCILIATION_TOLERANCE_SATOSHIS`
+    /// (default 0) and `RECONCILIATION_EXTRA_ADDRESSES` (comma-separated)
+    pub fn from_env() -> Self {
+        Self {
+            interval_secs: std::env::var("RECONCILIATION_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3600),
+            tolerance_satoshis: std::env::var("RECONCILIATION_TOLERANCE_SATOSHIS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(0),
+            extra_addresses: std::env::var("RECONCILIATION_EXTRA_ADDRESSES")
+                .unwrap_or_default()
+                .split(',')
+                .map(str::trim)
+                .filter(|a| !a.is_empty())
+                .map(str::to_string)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ReconciliationRun {
+    pub id: Uuid,
+    pub status: String,
+    pub addresses_checked: i32,
+    pub addresses_failed: i32,
+    pub onchain_confirmed_satoshis: i64,
+    pub onchain_unconfirmed_satoshis: i64,
+    pub liabilities_satoshis: i64,
+    pub unsettled_withdrawals_satoshis: i64,
+    pub drift_satoshis: i64,
+    pub tolerance_satoshis: i64,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Discrepancy {
+    pub kind: String,
+    pub address: Option<String>,
+    pub expected_satoshis: Option<i64>,
+    pub actual_satoshis: Option<i64>,
+    pub detail: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Outcome of comparing holdings with liabilities
+pub fn assess(onchain_satoshis: i64, liabilities_satoshis: i64, tolerance_satoshis: i64, failed: usize) -> &'static str {
+    if failed > 0 {
+        STATUS_INCOMPLETE
+    } else if (onchain_satoshis - liabilities_satoshis).abs() > tolerance_satoshis {
+        STATUS_DRIFT
+    } else {
+        STATUS_BALANCED
+    }
+}
+
+// ============================================================================
+// SCHEDULE
+// ============================================================================
+
+pub async fn start_reconciliation(
+    pool: PgPool,
+    monitor: MonitorClient,
+    webhooks: WebhookDispatcher,
+    config: ReconciliationConfig,
+) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(config.interval_secs));
+
+    loop {
+        interval.tick().await;
+        match reconcile(&pool, &monitor, &config).await {
+            Ok(run) if run.status == STATUS_DRIFT => {
+                tracing::error!(
+                    "Reconciliation drift of {} sat: {} on chain against {} of liabilities",
+                    run.drift_satoshis,
+                    run.onchain_confirmed_satoshis + run.onchain_unconfirmed_satoshis,
+                    run.liabilities_satoshis
+                );
+                webhooks.emit_service(webhooks::EVENT_RECONCILIATION_DRIFT, serde_json::json!(run));
+            }
+            Ok(run) if run.status == STATUS_INCOMPLETE => {
+                tracing::warn!("Reconciliation incomplete: {} of {} address(es) unavailable", run.addresses_failed, run.addresses_checked);
+            }
+            Ok(run) => tracing::info!("Reconciliation balanced (drift {} sat)", run.drift_satoshis),
+            Err(e) => tracing::error!("Reconciliation failed: {}", e),
+        }
+    }
+}
+
+async fn reconcile(
+    pool: &PgPool,
+    monitor: &MonitorClient,
+    config: &ReconciliationConfig,
+) -> Result<ReconciliationRun, sqlx::Error> {
+    let started_at = Utc::now();
+    let mut addresses: Vec<String> = sqlx::query_scalar("SELECT address FROM deposit_addresses ORDER BY address")
+        .fetch_all(pool)
+        .await?;
+    for extra in &config.extra_addresses {
+        if !addresses.contains(extra) {
+            addresses.push(extra.clone());
+        }
+    }
+
+    let mut confirmed = 0i64;
+    let mut unconfirmed = 0i64;
+    let mut failures: Vec<(String, String)> = Vec::new();
+    for address in &addresses {
+        match monitor.address_balance(address).await {
+            Ok(balance) => {
+                confirmed += balance.confirmed_satoshis;
+                unconfirmed += balance.unconfirmed_satoshis;
+            }
+            Err(e) => failures.push((address.clone(), e)),
+        }
+    }
+
+    // Withdrawals leave the chain balance once broadcast but the ledger only once settled
+    let (depositor_balances, unsettled_withdrawals): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT
+            (SELECT COALESCE(SUM(balance_satoshis), 0) FROM user_balances)::BIGINT,
+            (SELECT COALESCE(SUM(amount_satoshis), 0) FROM withdrawals
+             WHERE status IN ('Broadcast', 'Confirmed') AND settled_transfer_id IS NULL)::BIGINT
+        "#
+    )
+    .fetch_one(pool)
+    .await?;
+    let liabilities = depositor_balances - unsettled_withdrawals;
+    let onchain = confirmed + unconfirmed;
+    let drift = onchain - liabilities;
+    let status = assess(onchain, liabilities, config.tolerance_satoshis, failures.len());
+
+    let mut db_tx = pool.begin().await?;
+    let run = sqlx::query_as::<_, ReconciliationRun>(&format!(
+        r#"
+        INSERT INTO reconciliation_runs (
+            status, addresses_checked, addresses_failed, onchain_confirmed_satoshis, onchain_unconfirmed_satoshis,
+            liabilities_satoshis, unsettled_withdrawals_satoshis, drift_satoshis, tolerance_satoshis, started_at
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+        RETURNING {}
+        "#,
+        RUN_COLUMNS
+    ))
+    .bind(status)
+    .bind(addresses.len() as i32)
+    .bind(failures.len() as i32)
+    .bind(confirmed)
+    .bind(unconfirmed)
+    .bind(liabilities)
+    .bind(unsettled_withdrawals)
+    .bind(drift)
+    .bind(config.tolerance_satoshis)
+    .bind(started_at)
+    .fetch_one(&mut *db_tx)
+    .await?;
+
+    for (address, error) in &failures {
+        record(&mut db_tx, run.id, "address_unavailable", Some(address), None, None, error).await?;
+    }
+    if status == STATUS_DRIFT {
+        let detail = if drift < 0 {
+            format!("On-chain holdings are {} sat short of liabilities", -drift)
+        } else {
+            format!("On-chain holdings exceed liabilities by {} sat", drift)
+        };
+        record(&mut db_tx, run.id, "drift", None, Some(liabilities), Some(onchain), &detail).await?;
+    }
+    db_tx.commit().await?;
+    Ok(run)
+}
+
+async fn record(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    run_id: Uuid,
+    kind: &str,
+    address: Option<&str>,
+    expected: Option<i64>,
+    actual: Option<i64>,
+    detail: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO reconciliation_discrepancies (run_id, kind, address, expected_satoshis, actual_satoshis, detail)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        "#
+    )
+    .bind(run_id)
+    .bind(kind)
+    .bind(address)
+    .bind(expected)
+    .bind(actual)
+    .bind(detail)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `GET /reconciliation/latest` (admin): the most recent run and its discrepancies
+pub async fn get_latest(pool: web::Data<PgPool>, http_req: HttpRequest) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+
+    let run = sqlx::query_as::<_, ReconciliationRun>(&format!(
+        "SELECT {} FROM reconciliation_runs ORDER BY completed_at DESC LIMIT 1",
+        RUN_COLUMNS
+    ))
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::Unavailable("No reconciliation has run yet".to_string()))?;
+
+    let discrepancies = sqlx::query_as::<_, Discrepancy>(
+        r#"
+        SELECT kind, address, expected_satoshis, actual_satoshis, detail, created_at
+        FROM reconciliation_discrepancies WHERE run_id = $1
+        ORDER BY id
+        "#
+    )
+    .bind(run.id)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "run": run,
+        "discrepancies": discrepancies
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assess() {
+        assert_eq!(assess(1_000, 1_000, 0, 0), STATUS_BALANCED);
+        assert_eq!(assess(1_000, 1_010, 10, 0), STATUS_BALANCED);
+        assert_eq!(assess(1_000, 1_011, 10, 0), STATUS_DRIFT);
+        assert_eq!(assess(1_011, 1_000, 10, 0), STATUS_DRIFT);
+        assert_eq!(assess(0, 1_000_000, 0, 1), STATUS_INCOMPLETE);
+    }
+}
@@ -0,0 +1,402 @@
+// core/deposit-service/src/security.rs
+// Withdrawal safeguards: TOTP second factor above a threshold and a per-user address whitelist
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{generate_totp_secret, otpauth_uri, validate_paymail, verify_totp};
+use bsv_bank_common::transaction::p2pkh_script;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::{require_paymail, ServiceError};
+
+const MAX_LABEL_LEN: usize = 100;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// When withdrawals need a second factor or an approver, and how long new whitelist entries wait
+#[derive(Debug, Clone)]
+pub struct WithdrawalPolicy {
+    pub two_factor_threshold: i64,
+    pub approval_threshold: i64,
+    pub whitelist_cooldown: chrono::Duration,
+}
+
+impl WithdrawalPolicy {
+    /// `WITHDRAWAL_2FA_THRESHOLD_SATOSHIS` (default 0.1 BSV),
+    /// `WITHDRAWAL_APPROVAL_THRESHOLD_SATOSHIS` (default 1 BSV) and
+    /// `WITHDRAWAL_WHITELIST_COOLDOWN_HOURS` (default 24)
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: i64| {
+            std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            two_factor_threshold: var("WITHDRAWAL_2FA_THRESHOLD_SATOSHIS", 10_000_000),
+            approval_threshold: var("WITHDRAWAL_APPROVAL_THRESHOLD_SATOSHIS", 100_000_000),
+            whitelist_cooldown: chrono::Duration::hours(var("WITHDRAWAL_WHITELIST_COOLDOWN_HOURS", 24)),
+        }
+    }
+
+    pub fn requires_two_factor(&self, amount: i64) -> bool {
+        amount >= self.two_factor_threshold
+    }
+
+    /// Above the threshold a withdrawal waits for an approver before it can be broadcast
+    pub fn requires_approval(&self, amount: i64) -> bool {
+        amount > self.approval_threshold
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct WhitelistedAddress {
+    pub id: Uuid,
+    pub address: String,
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub active_at: DateTime<Utc>,
+}
+
+pub async fn user_id<'e>(executor: impl PgExecutor<'e>, paymail: &str) -> Result<i32, ServiceError> {
+    sqlx::query_scalar("SELECT id FROM users WHERE paymail = $1")
+        .bind(paymail)
+        .fetch_optional(executor)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::ValidationError(format!("Unknown user: {}", paymail)))
+}
+
+/// Check `code` against the user's enabled second factor and burn its time step
+pub async fn verify_code(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: i32,
+    code: Option<&str>,
+) -> Result<(), ServiceError> {
+    let factor: Option<(String, Option<i64>)> = sqlx::query_as(
+        "SELECT secret, last_used_step FROM user_two_factor WHERE user_id = $1 AND enabled FOR UPDATE"
+    )
+    .bind(user_id)
+    .fetch_optional(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+    let (secret, last_used_step) = factor.ok_or_else(|| {
+        ServiceError::Forbidden("Two-factor authentication must be enabled for this operation".to_string())
+    })?;
+    consume_code(db_tx, user_id, &secret, last_used_step, code).await
+}
+
+async fn consume_code(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    user_id: i32,
+    secret: &str,
+    last_used_step: Option<i64>,
+    code: Option<&str>,
+) -> Result<(), ServiceError> {
+    let code = code.ok_or_else(|| ServiceError::Forbidden("A two-factor code is required".to_string()))?;
+    let step = verify_totp(secret, code, Utc::now().timestamp())
+        .ok_or_else(|| ServiceError::Forbidden("Invalid two-factor code".to_string()))?;
+    if last_used_step.is_some_and(|last| step <= last) {
+        return Err(ServiceError::Forbidden("Two-factor code has already been used".to_string()));
+    }
+    sqlx::query("UPDATE user_two_factor SET last_used_step = $2 WHERE user_id = $1")
+        .bind(user_id)
+        .bind(step)
+        .execute(&mut **db_tx)
+        .await
+        .map_err(db_error)?;
+    Ok(())
+}
+
+async fn two_factor_enabled<'e>(executor: impl PgExecutor<'e>, user_id: i32) -> Result<bool, ServiceError> {
+    sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM user_two_factor WHERE user_id = $1 AND enabled)")
+        .bind(user_id)
+        .fetch_one(executor)
+        .await
+        .map_err(db_error)
+}
+
+/// Fail unless `address` may receive the user's withdrawals: any address until the user first
+/// whitelists one, from then on only entries whose cooldown has passed, even once all are removed
+pub async fn check_destination<'e>(
+    executor: impl PgExecutor<'e>,
+    user_id: i32,
+    address: &str,
+) -> Result<(), ServiceError> {
+    let entries: Vec<(String, DateTime<Utc>, bool)> = sqlx::query_as(
+        "SELECT address, active_at, removed_at IS NULL FROM withdrawal_address_whitelist WHERE user_id = $1"
+    )
+    .bind(user_id)
+    .fetch_all(executor)
+    .await
+    .map_err(db_error)?;
+    if entries.is_empty() {
+        return Ok(());
+    }
+    match entries.iter().find(|(a, _, listed)| *listed && a == address).map(|(_, active_at, _)| active_at) {
+        Some(active_at) if *active_at <= Utc::now() => Ok(()),
+        Some(active_at) => Err(ServiceError::Forbidden(format!(
+            "{} was whitelisted recently and can receive withdrawals from {}",
+            address, active_at
+        ))),
+        None => Err(ServiceError::Forbidden(format!("{} is not on the withdrawal whitelist", address))),
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CodeRequest {
+    pub code: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WhitelistRequest {
+    pub address: String,
+    pub label: Option<String>,
+    /// Required once two-factor authentication is enabled
+    pub totp_code: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RemoveWhitelistQuery {
+    /// Required once two-factor authentication is enabled
+    pub totp_code: Option<String>,
+}
+
+/// `POST /users/{paymail}/2fa/setup`: issue a new secret, active once confirmed with a code
+pub async fn setup_two_factor(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let user_id = user_id(pool.get_ref(), &paymail).await?;
+
+    let secret = generate_totp_secret();
+    let replaced = sqlx::query(
+        r#"
+        INSERT INTO user_two_factor (user_id, secret)
+        VALUES ($1, $2)
+        ON CONFLICT (user_id) DO UPDATE SET secret = EXCLUDED.secret, created_at = NOW()
+        WHERE NOT user_two_factor.enabled
+        "#
+    )
+    .bind(user_id)
+    .bind(&secret)
+    .execute(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+    if replaced == 0 {
+        return Err(ServiceError::Conflict("Two-factor authentication is already enabled".to_string()));
+    }
+
+    let issuer = std::env::var("TOTP_ISSUER").unwrap_or_else(|_| "BSV Bank".to_string());
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "secret": secret,
+        "otpauth_uri": otpauth_uri(&secret, &issuer, &paymail),
+        "message": "Add this secret to an authenticator app, then confirm with POST /users/{paymail}/2fa/enable."
+    })))
+}
+
+/// `POST /users/{paymail}/2fa/enable`
+pub async fn enable_two_factor(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    request: web::Json<CodeRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let user_id = user_id(&mut *db_tx, &paymail).await?;
+
+    let pending: Option<(String, bool, Option<i64>)> = sqlx::query_as(
+        "SELECT secret, enabled, last_used_step FROM user_two_factor WHERE user_id = $1 FOR UPDATE"
+    )
+    .bind(user_id)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    let (secret, last_used_step) = match pending {
+        None => return Err(ServiceError::ValidationError("Set up two-factor authentication first".to_string())),
+        Some((_, true, _)) => {
+            return Err(ServiceError::Conflict("Two-factor authentication is already enabled".to_string()));
+        }
+        Some((secret, false, last_used_step)) => (secret, last_used_step),
+    };
+    consume_code(&mut db_tx, user_id, &secret, last_used_step, Some(&request.code)).await?;
+    sqlx::query("UPDATE user_two_factor SET enabled = true, enabled_at = NOW() WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Two-factor authentication enabled for {}", paymail);
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "paymail": paymail.as_str(), "enabled": true })))
+}
+
+/// `POST /users/{paymail}/2fa/disable`: needs a current code, so a stolen token alone cannot remove it
+pub async fn disable_two_factor(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    request: web::Json<CodeRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let user_id = user_id(&mut *db_tx, &paymail).await?;
+    verify_code(&mut db_tx, user_id, Some(&request.code)).await?;
+    sqlx::query("DELETE FROM user_two_factor WHERE user_id = $1")
+        .bind(user_id)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Two-factor authentication disabled for {}", paymail);
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "paymail": paymail.as_str(), "enabled": false })))
+}
+
+/// `GET /users/{paymail}/withdrawal-addresses`
+pub async fn list_whitelist(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let user_id = user_id(pool.get_ref(), &paymail).await?;
+
+    let addresses = sqlx::query_as::<_, WhitelistedAddress>(
+        r#"
+        SELECT id, address, label, created_at, active_at FROM withdrawal_address_whitelist
+        WHERE user_id = $1 AND removed_at IS NULL
+        ORDER BY created_at
+        "#
+    )
+    .bind(user_id)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+    // Removing every entry does not switch the whitelist off
+    let enforced: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM withdrawal_address_whitelist WHERE user_id = $1)")
+        .bind(user_id)
+        .fetch_one(pool.get_ref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "enforced": enforced,
+        "addresses": addresses
+    })))
+}
+
+/// `POST /users/{paymail}/withdrawal-addresses`: the address can receive withdrawals once the cooldown passes
+pub async fn add_whitelist_address(
+    pool: web::Data<PgPool>,
+    policy: web::Data<WithdrawalPolicy>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    request: web::Json<WhitelistRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    if p2pkh_script(&request.address).is_none() {
+        return Err(ServiceError::ValidationError(format!("{} is not a P2PKH address", request.address)));
+    }
+    if request.label.as_ref().is_some_and(|l| l.len() > MAX_LABEL_LEN) {
+        return Err(ServiceError::ValidationError(format!("Label must be at most {} characters", MAX_LABEL_LEN)));
+    }
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let user_id = user_id(&mut *db_tx, &paymail).await?;
+    if two_factor_enabled(&mut *db_tx, user_id).await? {
+        verify_code(&mut db_tx, user_id, request.totp_code.as_deref()).await?;
+    }
+
+    let entry = sqlx::query_as::<_, WhitelistedAddress>(
+        r#"
+        INSERT INTO withdrawal_address_whitelist (user_id, address, label, active_at)
+        VALUES ($1, $2, $3, NOW() + $4 * INTERVAL '1 second')
+        ON CONFLICT (user_id, address) WHERE removed_at IS NULL DO NOTHING
+        RETURNING id, address, label, created_at, active_at
+        "#
+    )
+    .bind(user_id)
+    .bind(&request.address)
+    .bind(&request.label)
+    .bind(policy.whitelist_cooldown.num_seconds() as f64)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::Conflict(format!("{} is already whitelisted", request.address)))?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("{} whitelisted {}, usable from {}", paymail, entry.address, entry.active_at);
+    Ok(HttpResponse::Created().json(entry))
+}
+
+/// `DELETE /users/{paymail}/withdrawal-addresses/{address}?totp_code=`: removal takes effect
+/// immediately; with no entries left, withdrawals wait for a newly whitelisted address
+pub async fn remove_whitelist_address(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    path: web::Path<(String, String)>,
+    query: web::Query<RemoveWhitelistQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    let (paymail, address) = path.into_inner();
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let user_id = user_id(&mut *db_tx, &paymail).await?;
+    if two_factor_enabled(&mut *db_tx, user_id).await? {
+        verify_code(&mut db_tx, user_id, query.totp_code.as_deref()).await?;
+    }
+
+    let removed = sqlx::query(
+        r#"
+        UPDATE withdrawal_address_whitelist SET removed_at = NOW()
+        WHERE user_id = $1 AND address = $2 AND removed_at IS NULL
+        "#
+    )
+    .bind(user_id)
+    .bind(&address)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+    if removed == 0 {
+        return Err(ServiceError::ValidationError(format!("{} is not whitelisted", address)));
+    }
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("{} removed {} from the withdrawal whitelist", paymail, address);
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "address": address, "removed": true })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_two_factor_threshold() {
+        let policy = WithdrawalPolicy {
+            two_factor_threshold: 1_000,
+            approval_threshold: 5_000,
+            whitelist_cooldown: chrono::Duration::hours(24),
+        };
+        assert!(!policy.requires_two_factor(999));
+        assert!(policy.requires_two_factor(1_000));
+        assert!(!policy.requires_approval(5_000));
+        assert!(policy.requires_approval(5_001));
+    }
+}
@@ -0,0 +1,489 @@
+// core/deposit-service/src/terms.rs
+// Term deposits: products that lock a deposit for a fixed term, early redemption and maturity
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::validate_paymail;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::{ledger, require_admin, require_paymail, ServiceError};
+
+pub const MAX_DURATION_DAYS: i32 = 3_650;
+pub const MAX_APY_BOOST_BPS: i32 = 2_000;
+pub const MAX_PENALTY_BPS: i32 = 10_000;
+const MAX_CODE_LEN: usize = 50;
+const MAX_NAME_LEN: usize = 100;
+
+const PRODUCT_COLUMNS: &str = "code, name, description, duration_days, apy_boost_bps, \
+    early_withdrawal_penalty_bps, auto_roll, active, created_at, updated_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TermProduct {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub duration_days: i32,
+    /// Added to the base deposit APY for the term, in bps
+    pub apy_boost_bps: i32,
+    /// Charged on the deposited amount when redeemed before maturity, in bps
+    pub early_withdrawal_penalty_bps: i32,
+    /// Whether deposits on this product roll into a new term at maturity unless the depositor opts out
+    pub auto_roll: bool,
+    /// Inactive products stay on open terms but cannot be chosen for new deposits
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TermProduct {
+    pub async fn find<'e, E: PgExecutor<'e>>(executor: E, code: &str) -> Result<Option<TermProduct>, sqlx::Error> {
+        sqlx::query_as::<_, TermProduct>(&format!(
+            "SELECT {} FROM term_deposit_products WHERE code = $1",
+            PRODUCT_COLUMNS
+        ))
+        .bind(code)
+        .fetch_optional(executor)
+        .await
+    }
+
+    /// The active product a new deposit is opened under
+    pub async fn for_deposit<'e, E: PgExecutor<'e>>(executor: E, code: &str) -> Result<TermProduct, ServiceError> {
+        match TermProduct::find(executor, code).await.map_err(db_error)? {
+            Some(product) if product.active => Ok(product),
+            Some(_) => Err(ServiceError::ValidationError(format!("Term product {} is no longer offered", code))),
+            None => Err(ServiceError::ValidationError(format!("Unknown term product: {}", code))),
+        }
+    }
+}
+
+/// Penalty on `amount` at `bps`, rounded down
+pub fn penalty(amount: i64, bps: i32) -> i64 {
+    (amount as i128 * bps as i128 / 10_000) as i64
+}
+
+fn validate_terms(duration_days: i32, apy_boost_bps: i32, penalty_bps: i32) -> Result<(), ServiceError> {
+    if !(1..=MAX_DURATION_DAYS).contains(&duration_days) {
+        return Err(ServiceError::ValidationError(format!(
+            "Term must be between 1 and {} days",
+            MAX_DURATION_DAYS
+        )));
+    }
+    if !(0..=MAX_APY_BOOST_BPS).contains(&apy_boost_bps) {
+        return Err(ServiceError::ValidationError(format!(
+            "APY boost must be between 0 and {} bps",
+            MAX_APY_BOOST_BPS
+        )));
+    }
+    if !(0..=MAX_PENALTY_BPS).contains(&penalty_bps) {
+        return Err(ServiceError::ValidationError(format!(
+            "Early withdrawal penalty must be between 0 and {} bps",
+            MAX_PENALTY_BPS
+        )));
+    }
+    Ok(())
+}
+
+fn validate_code(code: &str) -> Result<(), ServiceError> {
+    let valid = !code.is_empty()
+        && code.len() <= MAX_CODE_LEN
+        && code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(ServiceError::ValidationError(format!(
+            "Product code must be 1-{} lowercase letters, digits, '-' or '_'",
+            MAX_CODE_LEN
+        )));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProductRequest {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub duration_days: i32,
+    #[serde(default)]
+    pub apy_boost_bps: i32,
+    #[serde(default)]
+    pub early_withdrawal_penalty_bps: i32,
+    #[serde(default)]
+    pub auto_roll: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProductRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub duration_days: Option<i32>,
+    pub apy_boost_bps: Option<i32>,
+    pub early_withdrawal_penalty_bps: Option<i32>,
+    pub auto_roll: Option<bool>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RedeemRequest {
+    pub user_paymail: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AutoRollRequest {
+    pub user_paymail: String,
+    pub auto_roll: bool,
+}
+
+/// The parts of a deposit that decide whether its term can be changed
+#[derive(Debug, sqlx::FromRow)]
+struct TermDeposit {
+    paymail: String,
+    amount_satoshis: i64,
+    status: String,
+    term_product_code: Option<String>,
+    early_withdrawal_penalty_bps: Option<i32>,
+    lock_until: Option<DateTime<Utc>>,
+    matured_at: Option<DateTime<Utc>>,
+    redeemed_at: Option<DateTime<Utc>>,
+}
+
+/// Lock `deposit_id` and check it is `paymail`'s term deposit, still inside its term
+async fn open_term(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    deposit_id: Uuid,
+    paymail: &str,
+) -> Result<TermDeposit, ServiceError> {
+    let deposit = sqlx::query_as::<_, TermDeposit>(
+        r#"
+        SELECT paymail, amount_satoshis, status, term_product_code, early_withdrawal_penalty_bps,
+               lock_until, matured_at, redeemed_at
+        FROM deposits
+        WHERE id = $1
+        FOR UPDATE
+        "#
+    )
+    .bind(deposit_id)
+    .fetch_optional(&mut **db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::ValidationError(format!("Deposit {} not found", deposit_id)))?;
+
+    if deposit.paymail != paymail {
+        return Err(ServiceError::Forbidden(format!("Deposit {} does not belong to {}", deposit_id, paymail)));
+    }
+    if deposit.term_product_code.is_none() {
+        return Err(ServiceError::Conflict(format!("Deposit {} is not a term deposit", deposit_id)));
+    }
+    if deposit.status != "Confirmed" && deposit.status != "Available" {
+        return Err(ServiceError::Conflict(format!("Deposit {} is {}", deposit_id, deposit.status)));
+    }
+    if deposit.redeemed_at.is_some() {
+        return Err(ServiceError::Conflict(format!("Deposit {} has already been redeemed", deposit_id)));
+    }
+    if deposit.matured_at.is_some() || deposit.lock_until.is_none_or(|until| until <= Utc::now()) {
+        return Err(ServiceError::Conflict(format!("Deposit {} has already matured", deposit_id)));
+    }
+    Ok(deposit)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `GET /term-products`: products depositors can choose from
+pub async fn list_products(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
+    let products = sqlx::query_as::<_, TermProduct>(&format!(
+        "SELECT {} FROM term_deposit_products WHERE active ORDER BY duration_days, code",
+        PRODUCT_COLUMNS
+    ))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "products": products })))
+}
+
+/// `GET /term-products/{code}`
+pub async fn get_product(
+    pool: web::Data<PgPool>,
+    code: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    let product = TermProduct::find(pool.get_ref(), &code)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::ValidationError(format!("Unknown term product: {}", code)))?;
+    Ok(HttpResponse::Ok().json(product))
+}
+
+/// `POST /term-products` (admin)
+pub async fn create_product(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    request: web::Json<CreateProductRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+    validate_code(&request.code)?;
+    if request.name.trim().is_empty() || request.name.len() > MAX_NAME_LEN {
+        return Err(ServiceError::ValidationError(format!("Name must be 1-{} characters", MAX_NAME_LEN)));
+    }
+    validate_terms(request.duration_days, request.apy_boost_bps, request.early_withdrawal_penalty_bps)?;
+
+    let product = sqlx::query_as::<_, TermProduct>(&format!(
+        r#"
+        INSERT INTO term_deposit_products
+            (code, name, description, duration_days, apy_boost_bps, early_withdrawal_penalty_bps, auto_roll)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        ON CONFLICT (code) DO NOTHING
+        RETURNING {}
+        "#,
+        PRODUCT_COLUMNS
+    ))
+    .bind(&request.code)
+    .bind(request.name.trim())
+    .bind(&request.description)
+    .bind(request.duration_days)
+    .bind(request.apy_boost_bps)
+    .bind(request.early_withdrawal_penalty_bps)
+    .bind(request.auto_roll)
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::Conflict(format!("Term product {} already exists", request.code)))?;
+
+    tracing::info!(
+        "Term product {} created: {} days, +{} bps APY, {} bps early withdrawal penalty",
+        product.code, product.duration_days, product.apy_boost_bps, product.early_withdrawal_penalty_bps
+    );
+    Ok(HttpResponse::Created().json(product))
+}
+
+/// `PUT /term-products/{code}` (admin). Open terms keep the terms they were opened under.
+pub async fn update_product(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    code: web::Path<String>,
+    request: web::Json<UpdateProductRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+    if request.name.as_ref().is_some_and(|n| n.trim().is_empty() || n.len() > MAX_NAME_LEN) {
+        return Err(ServiceError::ValidationError(format!("Name must be 1-{} characters", MAX_NAME_LEN)));
+    }
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let current = sqlx::query_as::<_, TermProduct>(&format!(
+        "SELECT {} FROM term_deposit_products WHERE code = $1 FOR UPDATE",
+        PRODUCT_COLUMNS
+    ))
+    .bind(code.as_str())
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::ValidationError(format!("Unknown term product: {}", code)))?;
+
+    let duration_days = request.duration_days.unwrap_or(current.duration_days);
+    let apy_boost_bps = request.apy_boost_bps.unwrap_or(current.apy_boost_bps);
+    let penalty_bps = request.early_withdrawal_penalty_bps.unwrap_or(current.early_withdrawal_penalty_bps);
+    validate_terms(duration_days, apy_boost_bps, penalty_bps)?;
+
+    let product = sqlx::query_as::<_, TermProduct>(&format!(
+        r#"
+        UPDATE term_deposit_products
+        SET name = $2, description = $3, duration_days = $4, apy_boost_bps = $5,
+            early_withdrawal_penalty_bps = $6, auto_roll = $7, active = $8, updated_at = NOW()
+        WHERE code = $1
+        RETURNING {}
+        "#,
+        PRODUCT_COLUMNS
+    ))
+    .bind(code.as_str())
+    .bind(request.name.as_deref().map(str::trim).unwrap_or(&current.name))
+    .bind(request.description.as_ref().or(current.description.as_ref()))
+    .bind(duration_days)
+    .bind(apy_boost_bps)
+    .bind(penalty_bps)
+    .bind(request.auto_roll.unwrap_or(current.auto_roll))
+    .bind(request.active.unwrap_or(current.active))
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Term product {} updated", product.code);
+    Ok(HttpResponse::Ok().json(product))
+}
+
+/// `POST /deposits/{id}/redeem`: end a term early, charging the penalty it was opened with
+pub async fn redeem_deposit(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    deposit_id: web::Path<Uuid>,
+    request: web::Json<RedeemRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.user_paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.user_paymail)?;
+    let deposit_id = deposit_id.into_inner();
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let deposit = open_term(&mut db_tx, deposit_id, &request.user_paymail).await?;
+    let penalty_satoshis = penalty(deposit.amount_satoshis, deposit.early_withdrawal_penalty_bps.unwrap_or(0));
+
+    // Unlocking releases the full deposit into the available balance, which covers the penalty
+    sqlx::query(
+        r#"
+        UPDATE deposits
+        SET lock_until = NOW(), redeemed_at = NOW(), early_withdrawal_penalty_satoshis = $2
+        WHERE id = $1
+        "#
+    )
+    .bind(deposit_id)
+    .bind(penalty_satoshis)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    ledger::transfer(
+        &mut db_tx,
+        ledger::TERM_PENALTY,
+        &deposit.paymail,
+        ledger::TERM_PENALTY_ACCOUNT,
+        penalty_satoshis,
+        None,
+    )
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!(
+        "Term deposit {} ({}) redeemed early by {}: {} sat penalty",
+        deposit_id,
+        deposit.term_product_code.as_deref().unwrap_or_default(),
+        deposit.paymail,
+        penalty_satoshis
+    );
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "deposit_id": deposit_id,
+        "penalty_satoshis": penalty_satoshis,
+        "released_satoshis": deposit.amount_satoshis - penalty_satoshis
+    })))
+}
+
+/// `PUT /deposits/{id}/auto-roll`: choose whether an open term rolls over at maturity
+pub async fn set_auto_roll(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    deposit_id: web::Path<Uuid>,
+    request: web::Json<AutoRollRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.user_paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.user_paymail)?;
+    let deposit_id = deposit_id.into_inner();
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    open_term(&mut db_tx, deposit_id, &request.user_paymail).await?;
+    sqlx::query("UPDATE deposits SET auto_roll = $2 WHERE id = $1")
+        .bind(deposit_id)
+        .bind(request.auto_roll)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "deposit_id": deposit_id,
+        "auto_roll": request.auto_roll
+    })))
+}
+
+// ============================================================================
+// MATURITY
+// ============================================================================
+
+/// Roll over or mature term deposits whose lock has ended, every
+/// `TERM_MATURITY_INTERVAL_SECS` (default 3600)
+pub async fn start_term_maturity(pool: PgPool) {
+    let interval_secs = std::env::var("TERM_MATURITY_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(3600);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+        match process_maturities(&pool).await {
+            Ok((0, 0)) => {}
+            Ok((rolled, matured)) => {
+                tracing::info!("Term deposits: {} rolled over, {} matured", rolled, matured)
+            }
+            Err(e) => tracing::error!("Term deposit maturity run failed: {}", e),
+        }
+    }
+}
+
+async fn process_maturities(pool: &PgPool) -> Result<(u64, u64), sqlx::Error> {
+    // A roll starts the next term where the last one ended, so a late run does not lengthen it
+    let rolled = sqlx::query(
+        r#"
+        UPDATE deposits
+        SET lock_until = lock_until + make_interval(days => term_duration_days),
+            roll_count = roll_count + 1
+        WHERE term_product_code IS NOT NULL
+          AND auto_roll
+          AND status IN ('Confirmed', 'Available')
+          AND matured_at IS NULL AND redeemed_at IS NULL
+          AND lock_until <= NOW()
+        "#
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    let matured = sqlx::query(
+        r#"
+        UPDATE deposits
+        SET matured_at = lock_until
+        WHERE term_product_code IS NOT NULL
+          AND NOT auto_roll
+          AND status IN ('Confirmed', 'Available')
+          AND matured_at IS NULL AND redeemed_at IS NULL
+          AND lock_until <= NOW()
+        "#
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+
+    Ok((rolled, matured))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_penalty_rounds_down() {
+        assert_eq!(penalty(1_000_000, 200), 20_000);
+        assert_eq!(penalty(99, 100), 0);
+        assert_eq!(penalty(50_000, 0), 0);
+        assert_eq!(penalty(50_000, MAX_PENALTY_BPS), 50_000);
+        assert_eq!(penalty(i64::MAX, MAX_PENALTY_BPS), i64::MAX);
+    }
+
+    #[test]
+    fn test_term_bounds() {
+        assert!(validate_terms(90, 150, 200).is_ok());
+        assert!(validate_terms(0, 150, 200).is_err());
+        assert!(validate_terms(MAX_DURATION_DAYS + 1, 0, 0).is_err());
+        assert!(validate_terms(30, -1, 0).is_err());
+        assert!(validate_terms(30, 0, MAX_PENALTY_BPS + 1).is_err());
+    }
+
+    #[test]
+    fn test_product_codes() {
+        assert!(validate_code("fixed-90_day").is_ok());
+        assert!(validate_code("").is_err());
+        assert!(validate_code("Fixed").is_err());
+    }
+}
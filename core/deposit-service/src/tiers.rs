@@ -0,0 +1,304 @@
+// core/deposit-service/src/tiers.rs
+// KYC tiers: daily and monthly deposit and withdrawal limits per user tier
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bsv_bank_common::{validate_paymail, Claims};
+use chrono::{DateTime, Datelike, Duration, DurationRound, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+
+use crate::{require_admin, ServiceError};
+
+/// Tier a user starts on
+pub const DEFAULT_TIER: &str = "basic";
+
+const TIER_COLUMNS: &str = "tier, daily_deposit_limit_satoshis, monthly_deposit_limit_satoshis, \
+    daily_withdrawal_limit_satoshis, monthly_withdrawal_limit_satoshis, updated_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct KycTier {
+    pub tier: String,
+    /// `None` is unlimited
+    pub daily_deposit_limit_satoshis: Option<i64>,
+    pub monthly_deposit_limit_satoshis: Option<i64>,
+    pub daily_withdrawal_limit_satoshis: Option<i64>,
+    pub monthly_withdrawal_limit_satoshis: Option<i64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    Deposit,
+    Withdrawal,
+}
+
+impl Flow {
+    fn noun(&self) -> &'static str {
+        match self {
+            Flow::Deposit => "deposit",
+            Flow::Withdrawal => "withdrawal",
+        }
+    }
+}
+
+/// One limit and how much of it the current window has used
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LimitUsage {
+    pub limit_satoshis: Option<i64>,
+    pub used_satoshis: i64,
+    pub remaining_satoshis: Option<i64>,
+}
+
+impl LimitUsage {
+    fn new(limit: Option<i64>, used: i64) -> Self {
+        Self {
+            limit_satoshis: limit,
+            used_satoshis: used,
+            remaining_satoshis: limit.map(|l| (l - used).max(0)),
+        }
+    }
+
+    fn allows(&self, amount: i64) -> bool {
+        self.limit_satoshis.is_none_or(|limit| self.used_satoshis.saturating_add(amount) <= limit)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct FlowUsage {
+    /// Since 00:00 UTC
+    pub daily: LimitUsage,
+    /// Since the first of the month, UTC
+    pub monthly: LimitUsage,
+}
+
+/// A user's tier and what is left of its limits
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TierUsage {
+    pub tier: String,
+    pub deposits: FlowUsage,
+    pub withdrawals: FlowUsage,
+}
+
+impl TierUsage {
+    /// Fail unless `amount` more of `flow` fits in both the daily and monthly limit
+    pub(crate) fn check(&self, flow: Flow, amount: i64) -> Result<(), ServiceError> {
+        let usage = match flow {
+            Flow::Deposit => &self.deposits,
+            Flow::Withdrawal => &self.withdrawals,
+        };
+        for (window, limit) in [("daily", &usage.daily), ("monthly", &usage.monthly)] {
+            if !limit.allows(amount) {
+                return Err(ServiceError::BusinessError(format!(
+                    "{} exceeds the {} tier's {} {} limit: {} of {} satoshis used",
+                    amount,
+                    self.tier,
+                    window,
+                    flow.noun(),
+                    limit.used_satoshis,
+                    limit.limit_satoshis.unwrap_or_default()
+                )));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Start of the current UTC day and month
+pub fn windows(now: DateTime<Utc>) -> (DateTime<Utc>, DateTime<Utc>) {
+    let day = now.duration_trunc(Duration::days(1)).unwrap_or(now);
+    let month = Utc
+        .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(day);
+    (day, month)
+}
+
+#[derive(sqlx::FromRow)]
+struct UsageRow {
+    #[sqlx(flatten)]
+    tier: KycTier,
+    deposits_today: i64,
+    deposits_month: i64,
+    withdrawals_today: i64,
+    withdrawals_month: i64,
+}
+
+/// `paymail`'s tier and limit usage; unknown users are on the default tier with nothing used
+pub async fn usage<'e>(executor: impl PgExecutor<'e>, paymail: &str) -> Result<TierUsage, sqlx::Error> {
+    let (day, month) = windows(Utc::now());
+    let row = sqlx::query_as::<_, UsageRow>(
+        r#"
+        SELECT t.tier, t.daily_deposit_limit_satoshis, t.monthly_deposit_limit_satoshis,
+            t.daily_withdrawal_limit_satoshis, t.monthly_withdrawal_limit_satoshis, t.updated_at,
+            COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                      WHERE d.user_id = u.id AND d.created_at >= $2), 0)::BIGINT AS deposits_today,
+            COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                      WHERE d.user_id = u.id AND d.created_at >= $3), 0)::BIGINT AS deposits_month,
+            COALESCE((SELECT SUM(w.amount_satoshis) FROM withdrawals w
+                      WHERE w.user_id = u.id AND w.created_at >= $2
+                        AND w.status NOT IN ('Failed', 'Rejected')), 0)::BIGINT AS withdrawals_today,
+            COALESCE((SELECT SUM(w.amount_satoshis) FROM withdrawals w
+                      WHERE w.user_id = u.id AND w.created_at >= $3
+                        AND w.status NOT IN ('Failed', 'Rejected')), 0)::BIGINT AS withdrawals_month
+        FROM kyc_tiers t
+        LEFT JOIN users u ON u.paymail = $1
+        WHERE t.tier = COALESCE(u.kyc_tier, $4)
+        "#
+    )
+    .bind(paymail)
+    .bind(day)
+    .bind(month)
+    .bind(DEFAULT_TIER)
+    .fetch_one(executor)
+    .await?;
+
+    let tier = row.tier;
+    Ok(TierUsage {
+        deposits: FlowUsage {
+            daily: LimitUsage::new(tier.daily_deposit_limit_satoshis, row.deposits_today),
+            monthly: LimitUsage::new(tier.monthly_deposit_limit_satoshis, row.deposits_month),
+        },
+        withdrawals: FlowUsage {
+            daily: LimitUsage::new(tier.daily_withdrawal_limit_satoshis, row.withdrawals_today),
+            monthly: LimitUsage::new(tier.monthly_withdrawal_limit_satoshis, row.withdrawals_month),
+        },
+        tier: tier.tier,
+    })
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct SetTierRequest {
+    pub tier: String,
+    pub reason: Option<String>,
+}
+
+/// `GET /kyc-tiers`
+pub async fn list_tiers(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
+    let tiers = sqlx::query_as::<_, KycTier>(&format!(
+        "SELECT {} FROM kyc_tiers ORDER BY daily_deposit_limit_satoshis NULLS LAST, tier",
+        TIER_COLUMNS
+    ))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "default_tier": DEFAULT_TIER,
+        "tiers": tiers
+    })))
+}
+
+/// `PUT /users/{paymail}/kyc-tier` (admin): move a user to another tier, recording who did it and why
+pub async fn set_user_tier(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    request: web::Json<SetTierRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    let admin = http_req
+        .extensions()
+        .get::<Claims>()
+        .map(|claims| claims.sub.clone())
+        .unwrap_or_default();
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let tier_exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM kyc_tiers WHERE tier = $1)")
+        .bind(&request.tier)
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    if !tier_exists {
+        return Err(ServiceError::ValidationError(format!("Unknown KYC tier: {}", request.tier)));
+    }
+
+    let current: Option<(i32, String)> = sqlx::query_as(
+        "SELECT id, kyc_tier FROM users WHERE paymail = $1 FOR UPDATE"
+    )
+    .bind(paymail.as_str())
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    let (user_id, from_tier) =
+        current.ok_or_else(|| ServiceError::ValidationError(format!("Unknown user: {}", paymail)))?;
+
+    if from_tier != request.tier {
+        sqlx::query("UPDATE users SET kyc_tier = $2, updated_at = NOW() WHERE id = $1")
+            .bind(user_id)
+            .bind(&request.tier)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(db_error)?;
+        sqlx::query(
+            r#"
+            INSERT INTO kyc_tier_changes (user_id, from_tier, to_tier, changed_by, reason)
+            VALUES ($1, $2, $3, $4, $5)
+            "#
+        )
+        .bind(user_id)
+        .bind(&from_tier)
+        .bind(&request.tier)
+        .bind(&admin)
+        .bind(&request.reason)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    }
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("{} moved {} from KYC tier {} to {}", admin, paymail, from_tier, request.tier);
+    let usage = usage(pool.get_ref(), &paymail).await.map_err(db_error)?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "previous_tier": from_tier,
+        "limits": usage
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage_with(daily: LimitUsage, monthly: LimitUsage) -> TierUsage {
+        let unlimited = LimitUsage::new(None, 0);
+        TierUsage {
+            tier: "basic".to_string(),
+            deposits: FlowUsage { daily, monthly },
+            withdrawals: FlowUsage { daily: unlimited, monthly: unlimited },
+        }
+    }
+
+    #[test]
+    fn test_limits_apply_per_window() {
+        let usage = usage_with(LimitUsage::new(Some(1_000), 600), LimitUsage::new(Some(5_000), 4_500));
+        assert!(usage.check(Flow::Deposit, 400).is_ok());
+        assert!(usage.check(Flow::Deposit, 401).is_err());
+        assert!(usage.check(Flow::Withdrawal, i64::MAX).is_ok());
+
+        let monthly_bound = usage_with(LimitUsage::new(Some(1_000), 0), LimitUsage::new(Some(5_000), 4_800));
+        assert!(monthly_bound.check(Flow::Deposit, 300).is_err());
+    }
+
+    #[test]
+    fn test_remaining_never_negative() {
+        assert_eq!(LimitUsage::new(Some(100), 150).remaining_satoshis, Some(0));
+        assert_eq!(LimitUsage::new(None, 150).remaining_satoshis, None);
+    }
+
+    #[test]
+    fn test_windows() {
+        let now = Utc.with_ymd_and_hms(2025, 11, 25, 14, 30, 0).unwrap();
+        let (day, month) = windows(now);
+        assert_eq!(day, Utc.with_ymd_and_hms(2025, 11, 25, 0, 0, 0).unwrap());
+        assert_eq!(month, Utc.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap());
+    }
+}
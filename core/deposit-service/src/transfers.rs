@@ -0,0 +1,298 @@
+// core/deposit-service/src/transfers.rs
+// Instant transfers between users, settled on the ledger without a chain transaction
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{validate_amount, validate_paymail};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::closures;
+use crate::idempotency::{self, REPLAY_HEADER};
+use crate::{ledger, require_paymail, ServiceError};
+
+const MAX_MEMO_LEN: usize = 280;
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+const TRANSFER_COLUMNS: &str = "id, from_paymail, to_paymail, amount_satoshis, memo, created_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Caps on what one sender can move between users
+#[derive(Debug, Clone)]
+pub struct TransferLimits {
+    pub max_per_transfer: i64,
+    /// Over any rolling 24 hours
+    pub daily: i64,
+}
+
+impl TransferLimits {
+    /// `INTERNAL_TRANSFER_MAX_SATOSHIS` (default 10 BSV) and
+    /// `INTERNAL_TRANSFER_DAILY_LIMIT_SATOSHIS` (default 50 BSV)
+    pub fn from_env() -> Self {
+        let var = |name: &str, default: i64| {
+            std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            max_per_transfer: var("INTERNAL_TRANSFER_MAX_SATOSHIS", 1_000_000_000),
+            daily: var("INTERNAL_TRANSFER_DAILY_LIMIT_SATOSHIS", 5_000_000_000),
+        }
+    }
+
+    fn check(&self, amount: i64, sent_today: i64) -> Result<(), ServiceError> {
+        if amount > self.max_per_transfer {
+            return Err(ServiceError::BusinessError(format!(
+                "Transfers are limited to {} satoshis",
+                self.max_per_transfer
+            )));
+        }
+        if sent_today.saturating_add(amount) > self.daily {
+            return Err(ServiceError::BusinessError(format!(
+                "Daily transfer limit of {} satoshis reached: {} sent in the last 24 hours",
+                self.daily, sent_today
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TransferRequest {
+    pub from_paymail: String,
+    pub to_paymail: String,
+    pub amount_satoshis: i64,
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct InternalTransfer {
+    pub id: Uuid,
+    pub from_paymail: String,
+    pub to_paymail: String,
+    pub amount_satoshis: i64,
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub limit: Option<i64>,
+    /// Only transfers created before this time; pass the last `created_at` of the previous page
+    pub before: Option<DateTime<Utc>>,
+}
+
+fn validate_request(request: &TransferRequest) -> Result<(), ServiceError> {
+    validate_paymail(&request.from_paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_paymail(&request.to_paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_amount(request.amount_satoshis).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    if request.from_paymail == request.to_paymail {
+        return Err(ServiceError::ValidationError("Cannot transfer to yourself".to_string()));
+    }
+    if request.memo.as_ref().is_some_and(|m| m.chars().count() > MAX_MEMO_LEN) {
+        return Err(ServiceError::ValidationError(format!("Memo must be at most {} characters", MAX_MEMO_LEN)));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `POST /transfers`: move available balance from one user to another. Retries carrying the same
+/// `Idempotency-Key` return the original transfer instead of sending again.
+pub async fn create_transfer(
+    pool: web::Data<PgPool>,
+    limits: web::Data<TransferLimits>,
+    http_req: HttpRequest,
+    request: web::Json<TransferRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_request(&request)?;
+    require_paymail(&http_req, &request.from_paymail)?;
+    let key = idempotency::key(&http_req)?;
+    let fingerprint = idempotency::fingerprint(&*request);
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    // Held until commit, so a retry waits for the first attempt and then sees its row
+    ledger::lock_account(&mut db_tx, &request.from_paymail).await.map_err(db_error)?;
+
+    if let Some(key) = &key {
+        let stored: Option<(String, Uuid)> = sqlx::query_as(
+            "SELECT request_fingerprint, id FROM internal_transfers WHERE from_paymail = $1 AND idempotency_key = $2"
+        )
+        .bind(&request.from_paymail)
+        .bind(key)
+        .fetch_optional(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+        match stored {
+            Some((stored_fingerprint, id)) if stored_fingerprint == fingerprint => {
+                let transfer = sqlx::query_as::<_, InternalTransfer>(&format!(
+                    "SELECT {} FROM internal_transfers WHERE id = $1",
+                    TRANSFER_COLUMNS
+                ))
+                .bind(id)
+                .fetch_one(&mut *db_tx)
+                .await
+                .map_err(db_error)?;
+                tracing::info!("Replaying transfer {} for {} key {}", id, request.from_paymail, key);
+                return Ok(HttpResponse::Ok().insert_header((REPLAY_HEADER, "true")).json(transfer));
+            }
+            Some(_) => return Err(idempotency::key_reused(key, "transfer")),
+            None => {}
+        }
+    }
+
+    // Only registered users can receive, so a mistyped paymail does not strand funds
+    let recipient_exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM users WHERE paymail = $1)")
+        .bind(&request.to_paymail)
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    if !recipient_exists {
+        return Err(ServiceError::ValidationError(format!("Unknown recipient: {}", request.to_paymail)));
+    }
+    closures::ensure_open(&mut *db_tx, &request.to_paymail).await?;
+
+    let sent_today: i64 = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(SUM(amount_satoshis), 0)::BIGINT FROM internal_transfers
+        WHERE from_paymail = $1 AND created_at > NOW() - INTERVAL '24 hours'
+        "#
+    )
+    .bind(&request.from_paymail)
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    limits.check(request.amount_satoshis, sent_today)?;
+    ledger::ensure_available(&mut db_tx, &request.from_paymail, request.amount_satoshis).await?;
+
+    let transfer_id = ledger::transfer(
+        &mut db_tx,
+        ledger::INTERNAL_TRANSFER,
+        &request.from_paymail,
+        &request.to_paymail,
+        request.amount_satoshis,
+        None,
+    )
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::ValidationError("Amount must be positive".to_string()))?;
+
+    let transfer = sqlx::query_as::<_, InternalTransfer>(&format!(
+        r#"
+        INSERT INTO internal_transfers
+            (id, from_paymail, to_paymail, amount_satoshis, memo, idempotency_key, request_fingerprint)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        RETURNING {}
+        "#,
+        TRANSFER_COLUMNS
+    ))
+    .bind(transfer_id)
+    .bind(&request.from_paymail)
+    .bind(&request.to_paymail)
+    .bind(request.amount_satoshis)
+    .bind(&request.memo)
+    .bind(&key)
+    .bind(key.as_ref().map(|_| &fingerprint))
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!(
+        "Transfer {}: {} sat from {} to {}",
+        transfer.id, transfer.amount_satoshis, transfer.from_paymail, transfer.to_paymail
+    );
+    Ok(HttpResponse::Created().json(transfer))
+}
+
+/// `GET /transfers/{paymail}`: transfers sent and received, newest first
+pub async fn list_transfers(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    query: web::Query<HistoryQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ServiceError::ValidationError(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    let transfers = sqlx::query_as::<_, InternalTransfer>(&format!(
+        r#"
+        SELECT {} FROM internal_transfers
+        WHERE (from_paymail = $1 OR to_paymail = $1)
+          AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
+        ORDER BY created_at DESC, id
+        LIMIT $3
+        "#,
+        TRANSFER_COLUMNS
+    ))
+    .bind(paymail.as_str())
+    .bind(query.before)
+    .bind(limit)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    let transfers: Vec<_> = transfers
+        .into_iter()
+        .map(|t| {
+            let direction = if t.from_paymail == *paymail { "sent" } else { "received" };
+            serde_json::json!({
+                "id": t.id,
+                "direction": direction,
+                "from_paymail": t.from_paymail,
+                "to_paymail": t.to_paymail,
+                "amount_satoshis": t.amount_satoshis,
+                "memo": t.memo,
+                "created_at": t.created_at
+            })
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "transfers": transfers
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(amount: i64, memo: Option<&str>) -> TransferRequest {
+        TransferRequest {
+            from_paymail: "alice@handcash.io".to_string(),
+            to_paymail: "bob@handcash.io".to_string(),
+            amount_satoshis: amount,
+            memo: memo.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_limits() {
+        let limits = TransferLimits { max_per_transfer: 1_000, daily: 2_500 };
+        assert!(limits.check(1_000, 0).is_ok());
+        assert!(limits.check(1_001, 0).is_err());
+        assert!(limits.check(1_000, 1_500).is_ok());
+        assert!(limits.check(1_000, 1_501).is_err());
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert!(validate_request(&request(1_000, Some("lunch"))).is_ok());
+        assert!(validate_request(&request(0, None)).is_err());
+        assert!(validate_request(&request(1_000, Some(&"x".repeat(MAX_MEMO_LEN + 1)))).is_err());
+
+        let mut to_self = request(1_000, None);
+        to_self.to_paymail = to_self.from_paymail.clone();
+        assert!(validate_request(&to_self).is_err());
+    }
+}
@@ -0,0 +1,289 @@
+// core/deposit-service/src/verification.rs
+// SPV verification of deposits: the transaction must pay the user's deposit address and be proven in a block
+
+use bsv_bank_common::transaction::{p2pkh_script, parse_transaction, txid_of, TxOutput};
+use bsv_bank_common::DepositMetrics;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::fees;
+use crate::webhooks::{WebhookDispatcher, EVENT_DEPOSIT_CONFIRMED};
+
+// ============================================================================
+// OUTPUT SELECTION
+// ============================================================================
+
+/// The output being deposited: `vout` when given, which must pay `script`,
+/// otherwise the first output paying it
+pub fn select_output<'a>(outputs: &'a [TxOutput], script: &[u8], vout: Option<u32>) -> Option<&'a TxOutput> {
+    match vout {
+        Some(vout) => outputs.get(vout as usize).filter(|o| o.script == script),
+        None => outputs.iter().find(|o| o.script == script),
+    }
+}
+
+// ============================================================================
+// VERIFIER
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct MonitoredTransaction {
+    confirmations: i32,
+    block_height: Option<i32>,
+    raw_tx: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpvResult {
+    merkle_verified: bool,
+}
+
+/// Why a deposit transaction was refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyError {
+    Rejected(String),
+    /// The output pays the deposit address, but not the amount claimed
+    AmountMismatch { vout: u32, claimed: i64, paid: i64 },
+}
+
+/// Outcome of checking a deposit transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifiedDeposit {
+    /// Output paying the deposit address
+    pub vout: u32,
+    pub confirmations: i32,
+    pub block_height: Option<i32>,
+    /// A merkle proof places the transaction in a block; only then is the deposit Confirmed
+    pub spv_verified: bool,
+}
+
+/// Fetches deposit transactions from blockchain-monitor, checks their outputs
+/// against the user's deposit address, and proves inclusion with spv-service
+#[derive(Clone)]
+pub struct DepositVerifier {
+    client: reqwest::Client,
+    monitor_url: String,
+    spv_url: String,
+}
+
+impl DepositVerifier {
+    /// `BLOCKCHAIN_MONITOR_URL` and `SPV_SERVICE_URL`
+    pub fn from_env() -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            monitor_url: std::env::var("BLOCKCHAIN_MONITOR_URL")
+                .unwrap_or_else(|_| "http://localhost:8084".to_string()),
+            spv_url: std::env::var("SPV_SERVICE_URL")
+                .unwrap_or_else(|_| "http://localhost:8086".to_string()),
+        }
+    }
+
+    /// Check that an output of `txid` pays exactly `amount` to `address`, then try
+    /// to prove it. A transaction that is valid but not yet mined comes back with
+    /// `spv_verified: false`.
+    pub async fn verify(
+        &self,
+        txid: &str,
+        address: &str,
+        vout: Option<u32>,
+        amount: i64,
+    ) -> Result<VerifiedDeposit, VerifyError> {
+        let rejected = VerifyError::Rejected;
+        let deposit_script = p2pkh_script(address)
+            .ok_or_else(|| rejected(format!("Deposit address {} is not a P2PKH address", address)))?;
+
+        let response = self.client
+            .get(format!("{}/tx/{}", self.monitor_url, txid))
+            .query(&[("include_raw", "true")])
+            .send()
+            .await
+            .map_err(|e| rejected(format!("Could not fetch transaction: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(rejected(format!("Transaction {} not found (status {})", txid, response.status())));
+        }
+        let tx: MonitoredTransaction = response
+            .json()
+            .await
+            .map_err(|e| rejected(format!("Could not fetch transaction: {}", e)))?;
+
+        let raw = tx.raw_tx
+            .as_deref()
+            .and_then(|raw| hex::decode(raw).ok())
+            .ok_or_else(|| rejected(format!("Raw transaction {} is not available yet", txid)))?;
+        if !txid_of(&raw).eq_ignore_ascii_case(txid) {
+            return Err(rejected(format!("Raw transaction does not hash to {}", txid)));
+        }
+        let outputs = parse_transaction(&raw).map_err(rejected)?.outputs;
+        let output = select_output(&outputs, &deposit_script, vout).ok_or_else(|| match vout {
+            Some(vout) => rejected(format!("Output {} of {} does not pay your deposit address {}", vout, txid, address)),
+            None => rejected(format!("Transaction {} does not pay your deposit address {}", txid, address)),
+        })?;
+        if output.satoshis != amount {
+            return Err(VerifyError::AmountMismatch { vout: output.vout, claimed: amount, paid: output.satoshis });
+        }
+
+        Ok(VerifiedDeposit {
+            vout: output.vout,
+            confirmations: tx.confirmations,
+            block_height: tx.block_height,
+            spv_verified: self.prove(txid).await,
+        })
+    }
+
+    /// `POST /verify/tx` on spv-service; false until a merkle proof for `txid` checks out
+    pub async fn prove(&self, txid: &str) -> bool {
+        let response = self.client
+            .post(format!("{}/verify/tx", self.spv_url))
+            .json(&serde_json::json!({ "txid": txid }))
+            .send()
+            .await;
+        match response {
+            Ok(response) if response.status().is_success() => response
+                .json::<SpvResult>()
+                .await
+                .map(|r| r.merkle_verified)
+                .unwrap_or(false),
+            Ok(response) => {
+                tracing::debug!("No SPV proof for {} yet (status {})", txid, response.status());
+                false
+            }
+            Err(e) => {
+                tracing::warn!("spv-service unavailable verifying {}: {}", txid, e);
+                false
+            }
+        }
+    }
+}
+
+// ============================================================================
+// CONFIRMATION CHECKS
+// ============================================================================
+
+/// Confirm Pending deposits once spv-service can prove them, every
+/// `DEPOSIT_CONFIRMATION_INTERVAL_SECS` (default 60)
+pub async fn start_confirmation_checks(
+    pool: PgPool,
+    verifier: DepositVerifier,
+    webhooks: WebhookDispatcher,
+    metrics: DepositMetrics,
+) {
+    let interval_secs = std::env::var("DEPOSIT_CONFIRMATION_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(60);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+        match confirm_pending(&pool, &verifier, &webhooks, &metrics).await {
+            Ok(confirmed) if confirmed > 0 => tracing::info!("Confirmed {} deposit(s) by SPV proof", confirmed),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Deposit confirmation check failed: {}", e),
+        }
+    }
+}
+
+async fn confirm_pending(
+    pool: &PgPool,
+    verifier: &DepositVerifier,
+    webhooks: &WebhookDispatcher,
+    metrics: &DepositMetrics,
+) -> Result<usize, sqlx::Error> {
+    let pending: Vec<(Uuid, String, String, i64, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT id, txid, paymail, amount_satoshis, created_at FROM deposits
+        WHERE status = 'Pending' ORDER BY created_at LIMIT 100
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    let charged = fees::charge_outstanding_deposits(pool).await?;
+    if charged > 0 {
+        tracing::warn!("Charged {} deposit fee(s) missed at confirmation", charged);
+    }
+
+    let mut confirmed = 0;
+    for (deposit_id, txid, paymail, amount, created_at) in pending {
+        if !verifier.prove(&txid).await {
+            continue;
+        }
+        let now = Utc::now();
+        // Credited and charged its fee together
+        let mut db_tx = pool.begin().await?;
+        let updated = sqlx::query(
+            r#"
+            UPDATE deposits
+            SET status = 'Confirmed', confirmed_at = $2, spv_proof_verified = true, verified_at = $2
+            WHERE id = $1 AND status = 'Pending'
+            "#
+        )
+        .bind(deposit_id)
+        .bind(now)
+        .execute(&mut *db_tx)
+        .await?
+        .rows_affected();
+        fees::charge_deposit(&mut db_tx, deposit_id).await?;
+        db_tx.commit().await?;
+        if updated > 0 {
+            metrics.record_deposit_status("Confirmed", amount);
+            metrics.observe_time_to_confirmation((now - created_at).num_milliseconds() as f64 / 1000.0);
+            tracing::info!("Deposit {} confirmed, {} proven in a block", deposit_id, txid);
+            webhooks.emit(&paymail, EVENT_DEPOSIT_CONFIRMED, serde_json::json!({
+                "deposit_id": deposit_id,
+                "txid": txid,
+                "amount_satoshis": amount,
+                "status": "Confirmed"
+            }));
+            confirmed += 1;
+        }
+    }
+    Ok(confirmed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// One input, two outputs: 50,000 sats to P2PKH hash 0x11.., 1,000 sats to an OP_RETURN
+    fn sample_tx() -> Vec<u8> {
+        let mut tx = Vec::new();
+        tx.extend_from_slice(&1u32.to_le_bytes());
+        tx.push(1);
+        tx.extend_from_slice(&[0xab; 32]);
+        tx.extend_from_slice(&0u32.to_le_bytes());
+        tx.push(2);
+        tx.extend_from_slice(&[0x51, 0x51]);
+        tx.extend_from_slice(&u32::MAX.to_le_bytes());
+        tx.push(2);
+        tx.extend_from_slice(&50_000u64.to_le_bytes());
+        tx.push(25);
+        tx.extend_from_slice(&[0x76, 0xa9, 0x14]);
+        tx.extend_from_slice(&[0x11; 20]);
+        tx.extend_from_slice(&[0x88, 0xac]);
+        tx.extend_from_slice(&1_000u64.to_le_bytes());
+        tx.push(2);
+        tx.extend_from_slice(&[0x00, 0x6a]);
+        tx.extend_from_slice(&0u32.to_le_bytes());
+        tx
+    }
+
+    #[test]
+    fn test_output_must_pay_deposit_address() {
+        let outputs = parse_transaction(&sample_tx()).unwrap().outputs;
+        let deposit = [&[0x76, 0xa9, 0x14][..], &[0x11; 20], &[0x88, 0xac]].concat();
+        let other = [&[0x76, 0xa9, 0x14][..], &[0x22; 20], &[0x88, 0xac]].concat();
+
+        assert_eq!(select_output(&outputs, &deposit, None).map(|o| o.satoshis), Some(50_000));
+        assert_eq!(select_output(&outputs, &deposit, Some(0)).map(|o| o.vout), Some(0));
+        // The named output belongs to someone else, or does not exist
+        assert!(select_output(&outputs, &deposit, Some(1)).is_none());
+        assert!(select_output(&outputs, &deposit, Some(7)).is_none());
+        assert!(select_output(&outputs, &other, None).is_none());
+    }
+}
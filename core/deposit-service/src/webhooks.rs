@@ -0,0 +1,440 @@
+// core/deposit-service/src/webhooks.rs
+// Deposit and withdrawal event webhooks: per-user and service-wide subscriptions, HMAC-signed delivery
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bsv_bank_common::{
+    generate_webhook_secret, sign_payload, validate_paymail, validate_url, webhook, Claims, DepositMetrics,
+    SERVICE_PERMISSION,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::{require_paymail, ServiceError};
+
+pub const EVENT_DEPOSIT_DETECTED: &str = "deposit.detected";
+pub const EVENT_DEPOSIT_CONFIRMED: &str = "deposit.confirmed";
+pub const EVENT_WITHDRAWAL_BROADCAST: &str = "withdrawal.broadcast";
+pub const EVENT_WITHDRAWAL_CONFIRMED: &str = "withdrawal.confirmed";
+/// Service-wide only: on-chain holdings and ledger liabilities have drifted apart
+pub const EVENT_RECONCILIATION_DRIFT: &str = "reconciliation.drift";
+
+const ALL_EVENTS: [&str; 5] = [
+    EVENT_DEPOSIT_DETECTED,
+    EVENT_DEPOSIT_CONFIRMED,
+    EVENT_WITHDRAWAL_BROADCAST,
+    EVENT_WITHDRAWAL_CONFIRMED,
+    EVENT_RECONCILIATION_DRIFT,
+];
+
+const WEBHOOK_COLUMNS: &str = "id, paymail, url, secret, events, active, created_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DepositWebhook {
+    pub id: Uuid,
+    /// `None` for service subscriptions receiving every user's events
+    pub paymail: Option<String>,
+    pub url: String,
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterWebhookRequest {
+    /// Omit to subscribe to all users' events (admin or service tokens only)
+    pub paymail: Option<String>,
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookEnvelope<'a> {
+    id: Uuid,
+    event: &'a str,
+    paymail: &'a str,
+    timestamp: DateTime<Utc>,
+    data: &'a serde_json::Value,
+}
+
+// ============================================================================
+// DISPATCHER
+// ============================================================================
+
+/// Delivers events to the user's subscriptions and to service-wide ones.
+/// Deliveries run in the background so handlers never wait on callbacks.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    pool: PgPool,
+    max_attempts: u32,
+}
+
+impl WebhookDispatcher {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            max_attempts: std::env::var("WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+        }
+    }
+
+    /// Queue `event` about `paymail`'s funds for every matching subscription
+    pub fn emit(&self, paymail: &str, event: &'static str, data: serde_json::Value) {
+        let dispatcher = self.clone();
+        let paymail = paymail.to_string();
+
+        tokio::spawn(async move {
+            if let Err(e) = dispatcher.dispatch(&paymail, event, data).await {
+                tracing::error!("Webhook dispatch failed for {} ({}): {}", paymail, event, e);
+            }
+        });
+    }
+
+    /// Queue a service-wide `event`; only subscriptions without a paymail receive it
+    pub fn emit_service(&self, event: &'static str, data: serde_json::Value) {
+        self.emit("", event, data);
+    }
+
+    async fn dispatch(&self, paymail: &str, event: &str, data: serde_json::Value) -> Result<(), sqlx::Error> {
+        let hooks = sqlx::query_as::<_, DepositWebhook>(&format!(
+            r#"
+            SELECT {} FROM deposit_webhooks
+            WHERE (paymail = $1 OR paymail IS NULL)
+              AND active
+              AND (cardinality(events) = 0 OR $2 = ANY(events))
+            "#,
+            WEBHOOK_COLUMNS
+        ))
+        .bind(paymail)
+        .bind(event)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for hook in hooks {
+            let envelope = WebhookEnvelope {
+                id: Uuid::new_v4(),
+                event,
+                paymail,
+                timestamp: Utc::now(),
+                data: &data,
+            };
+            let body = serde_json::to_string(&envelope).unwrap_or_default();
+
+            sqlx::query(
+                r#"
+                INSERT INTO deposit_webhook_deliveries (id, webhook_id, event_type, payload)
+                VALUES ($1, $2, $3, $4::jsonb)
+                "#
+            )
+            .bind(envelope.id)
+            .bind(hook.id)
+            .bind(event)
+            .bind(&body)
+            .execute(&self.pool)
+            .await?;
+
+            self.deliver(&hook, envelope.id, event, &body).await;
+        }
+
+        Ok(())
+    }
+
+    async fn deliver(&self, hook: &DepositWebhook, delivery_id: Uuid, event: &str, body: &str) {
+        let mut last_error: Option<String> = None;
+        let mut last_status: Option<i32> = None;
+
+        for attempt in 1..=self.max_attempts {
+            let timestamp = Utc::now().timestamp();
+            let signature = sign_payload(&hook.secret, timestamp, body);
+
+            // Resolved on every attempt, so a host re-pointed inside the network is caught
+            let result = match webhook::pinned_client(&hook.url).await {
+                Ok(client) => client
+                    .post(&hook.url)
+                    .header("Content-Type", "application/json")
+                    .header(webhook::EVENT_HEADER, event)
+                    .header(webhook::TIMESTAMP_HEADER, timestamp.to_string())
+                    .header(webhook::SIGNATURE_HEADER, signature)
+                    .body(body.to_string())
+                    .send()
+                    .await
+                    .map_err(|e| e.to_string()),
+                Err(e) => Err(e),
+            };
+
+            match result {
+                Ok(response) if response.status().is_success() => {
+                    let _ = sqlx::query(
+                        r#"
+                        UPDATE deposit_webhook_deliveries
+                        SET delivered = true, attempts = $1, response_status = $2,
+                            delivered_at = NOW(), last_error = NULL
+                        WHERE id = $3
+                        "#
+                    )
+                    .bind(attempt as i32)
+                    .bind(response.status().as_u16() as i32)
+                    .bind(delivery_id)
+                    .execute(&self.pool)
+                    .await;
+                    return;
+                }
+                Ok(response) => {
+                    last_status = Some(response.status().as_u16() as i32);
+                    last_error = Some(format!("Status: {}", response.status()));
+                }
+                Err(e) => {
+                    last_error = Some(e);
+                }
+            }
+
+            if attempt < self.max_attempts {
+                // Exponential backoff: 1s, 2s, 4s, ...
+                tokio::time::sleep(Duration::from_secs(1 << (attempt - 1).min(6))).await;
+            }
+        }
+
+        tracing::warn!("Webhook {} to {} failed after {} attempts: {:?}",
+            delivery_id, hook.url, self.max_attempts, last_error);
+
+        let _ = sqlx::query(
+            r#"
+            UPDATE deposit_webhook_deliveries
+            SET attempts = $1, response_status = $2, last_error = $3
+            WHERE id = $4
+            "#
+        )
+        .bind(self.max_attempts as i32)
+        .bind(last_status)
+        .bind(last_error)
+        .bind(delivery_id)
+        .execute(&self.pool)
+        .await;
+    }
+}
+
+// ============================================================================
+// WITHDRAWAL NOTIFICATIONS
+// ============================================================================
+
+/// Announce withdrawals reaching Broadcast or Confirmed, whichever process moved them,
+/// every `WITHDRAWAL_NOTIFY_INTERVAL_SECS` (default 30)
+pub async fn start_withdrawal_notifications(pool: PgPool, dispatcher: WebhookDispatcher, metrics: DepositMetrics) {
+    let interval_secs = std::env::var("WITHDRAWAL_NOTIFY_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+    let mut interval = tokio::time::interval(Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = notify_withdrawals(&pool, &dispatcher, &metrics).await {
+            tracing::error!("Withdrawal notification run failed: {}", e);
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct WithdrawalChange {
+    id: Uuid,
+    paymail: String,
+    amount_satoshis: i64,
+    destination_address: String,
+    txid: Option<String>,
+    status: String,
+}
+
+async fn notify_withdrawals(
+    pool: &PgPool,
+    dispatcher: &WebhookDispatcher,
+    metrics: &DepositMetrics,
+) -> Result<(), sqlx::Error> {
+    // Claiming the status first means a crash skips a notification rather than repeating it
+    let changes = sqlx::query_as::<_, WithdrawalChange>(
+        r#"
+        WITH changed AS (
+            SELECT id FROM withdrawals
+            WHERE status IN ('Broadcast', 'Confirmed') AND notified_status IS DISTINCT FROM status
+            ORDER BY created_at
+            LIMIT 100
+            FOR UPDATE SKIP LOCKED
+        )
+        UPDATE withdrawals w
+        SET notified_status = w.status
+        FROM changed, users u
+        WHERE w.id = changed.id AND u.id = w.user_id
+        RETURNING w.id, u.paymail, w.amount_satoshis, w.destination_address, w.txid, w.status
+        "#
+    )
+    .fetch_all(pool)
+    .await?;
+
+    for change in changes {
+        metrics.record_withdrawal_status(&change.status, change.amount_satoshis);
+        let event = if change.status == "Confirmed" { EVENT_WITHDRAWAL_CONFIRMED } else { EVENT_WITHDRAWAL_BROADCAST };
+        dispatcher.emit(&change.paymail, event, serde_json::json!({
+            "withdrawal_id": change.id,
+            "amount_satoshis": change.amount_satoshis,
+            "destination_address": change.destination_address,
+            "txid": change.txid,
+            "status": change.status
+        }));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+fn caller(http_req: &HttpRequest) -> Result<Claims, ServiceError> {
+    http_req
+        .extensions()
+        .get::<Claims>()
+        .cloned()
+        .ok_or_else(|| ServiceError::Forbidden("Authentication required".to_string()))
+}
+
+fn validate_events(events: &[String]) -> Result<(), ServiceError> {
+    match events.iter().find(|e| !ALL_EVENTS.contains(&e.as_str())) {
+        Some(unknown) => Err(ServiceError::ValidationError(format!("Unknown event type: {}", unknown))),
+        None => Ok(()),
+    }
+}
+
+/// `POST /webhooks`: subscribe a URL to a user's events, or to everyone's for services.
+/// Re-registering the same URL rotates its secret.
+pub async fn register_webhook(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    request: web::Json<RegisterWebhookRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = caller(&http_req)?;
+    validate_url(&request.url).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_events(&request.events)?;
+    match &request.paymail {
+        Some(paymail) => {
+            validate_paymail(paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+            require_paymail(&http_req, paymail)?;
+        }
+        None if claims.has_permission(SERVICE_PERMISSION) => {}
+        None => {
+            return Err(ServiceError::Forbidden(
+                "Subscribing to all users' events requires a service token".to_string()
+            ));
+        }
+    }
+
+    let secret = generate_webhook_secret();
+    let hook = sqlx::query_as::<_, DepositWebhook>(&format!(
+        r#"
+        INSERT INTO deposit_webhooks (paymail, url, secret, events)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT ((COALESCE(paymail, '')), url)
+        DO UPDATE SET secret = EXCLUDED.secret, events = EXCLUDED.events, active = true
+        RETURNING {}
+        "#,
+        WEBHOOK_COLUMNS
+    ))
+    .bind(&request.paymail)
+    .bind(&request.url)
+    .bind(&secret)
+    .bind(&request.events)
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!(
+        "Deposit webhook registered by {} for {}: {}",
+        claims.sub, hook.paymail.as_deref().unwrap_or("all users"), hook.url
+    );
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "webhook": hook,
+        "secret": secret,
+        "message": "Store this secret; it is used to verify the X-BSVBank-Signature header and will not be shown again."
+    })))
+}
+
+/// `GET /webhooks`: the caller's active subscriptions, plus service-wide ones for services
+pub async fn list_webhooks(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = caller(&http_req)?;
+    let webhooks = sqlx::query_as::<_, DepositWebhook>(&format!(
+        r#"
+        SELECT {} FROM deposit_webhooks
+        WHERE active AND (paymail = $1 OR ($2 AND paymail IS NULL))
+        ORDER BY created_at DESC
+        "#,
+        WEBHOOK_COLUMNS
+    ))
+    .bind(&claims.sub)
+    .bind(claims.has_permission(SERVICE_PERMISSION))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "total_webhooks": webhooks.len(),
+        "webhooks": webhooks
+    })))
+}
+
+/// `DELETE /webhooks/{id}`
+pub async fn delete_webhook(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    webhook_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = caller(&http_req)?;
+    let deactivated = sqlx::query(
+        r#"
+        UPDATE deposit_webhooks
+        SET active = false
+        WHERE id = $1 AND (paymail = $2 OR $3)
+        "#
+    )
+    .bind(*webhook_id)
+    .bind(&claims.sub)
+    .bind(claims.has_permission(SERVICE_PERMISSION))
+    .execute(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .rows_affected();
+    if deactivated == 0 {
+        return Err(ServiceError::ValidationError(format!("Webhook {} not found", webhook_id)));
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "webhook_id": *webhook_id,
+        "active": false
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_events() {
+        assert!(validate_events(&[]).is_ok());
+        assert!(validate_events(&[EVENT_DEPOSIT_CONFIRMED.to_string()]).is_ok());
+        assert!(validate_events(&["deposit.refunded".to_string()]).is_err());
+    }
+}
@@ -0,0 +1,297 @@
+// core/deposit-service/src/withdrawals.rs
+// Withdrawal requests: held against the available balance until confirmed on chain, then debited
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{validate_amount, validate_paymail, DepositMetrics};
+use bsv_bank_common::transaction::p2pkh_script;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::approvals;
+use crate::fees::{self, Operation};
+use crate::idempotency::{self, REPLAY_HEADER};
+use crate::security::{self, WithdrawalPolicy};
+use crate::tiers::{self, Flow};
+use crate::{ledger, require_paymail, ServiceError};
+
+const WITHDRAWAL_COLUMNS: &str =
+    "id, amount_satoshis, fee_satoshis, destination_address, txid, status, created_at, completed_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WithdrawalRequest {
+    pub user_paymail: String,
+    pub amount_satoshis: i64,
+    pub destination_address: String,
+    /// Required at or above the two-factor threshold
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub totp_code: Option<String>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Withdrawal {
+    pub id: Uuid,
+    pub amount_satoshis: i64,
+    /// Held with the amount and charged when the withdrawal settles
+    pub fee_satoshis: i64,
+    pub destination_address: String,
+    pub txid: Option<String>,
+    /// PendingApproval for large amounts, Pending until broadcast, then Broadcast and Confirmed;
+    /// Failed or Rejected releases the hold
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+/// A withdrawal whose balance, limits and authorization have been checked
+pub struct NewWithdrawal<'a> {
+    pub user_id: i32,
+    pub paymail: &'a str,
+    pub amount_satoshis: i64,
+    pub fee_satoshis: i64,
+    pub destination_address: &'a str,
+}
+
+/// Record a checked withdrawal. Above the approval threshold it starts as PendingApproval.
+pub async fn insert(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    policy: &WithdrawalPolicy,
+    new: &NewWithdrawal<'_>,
+    idempotency: Option<(&str, &str)>,
+) -> Result<Withdrawal, sqlx::Error> {
+    let needs_approval = policy.requires_approval(new.amount_satoshis);
+    let status = if needs_approval { approvals::PENDING_APPROVAL } else { "Pending" };
+
+    let withdrawal = sqlx::query_as::<_, Withdrawal>(&format!(
+        r#"
+        INSERT INTO withdrawals
+            (id, user_id, amount_satoshis, fee_satoshis, destination_address, status, idempotency_key, request_fingerprint)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING {}
+        "#,
+        WITHDRAWAL_COLUMNS
+    ))
+    .bind(Uuid::new_v4())
+    .bind(new.user_id)
+    .bind(new.amount_satoshis)
+    .bind(new.fee_satoshis)
+    .bind(new.destination_address)
+    .bind(status)
+    .bind(idempotency.map(|(key, _)| key))
+    .bind(idempotency.map(|(_, fingerprint)| fingerprint))
+    .fetch_one(&mut **db_tx)
+    .await?;
+    if needs_approval {
+        approvals::record(&mut **db_tx, withdrawal.id, approvals::EVENT_APPROVAL_REQUESTED, new.paymail, None).await?;
+    }
+    Ok(withdrawal)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `POST /withdrawals`: hold the amount and its fee for payout to `destination_address`. Limits and the
+/// available balance are checked under the user's account lock, along with the second factor
+/// for large amounts and the user's address whitelist. Above the approval threshold the
+/// withdrawal waits for an approver before it can be broadcast. Retries carrying the same
+/// `Idempotency-Key` return the original withdrawal.
+pub async fn create_withdrawal(
+    pool: web::Data<PgPool>,
+    policy: web::Data<WithdrawalPolicy>,
+    metrics: web::Data<DepositMetrics>,
+    http_req: HttpRequest,
+    request: web::Json<WithdrawalRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.user_paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_amount(request.amount_satoshis).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.user_paymail)?;
+    if p2pkh_script(&request.destination_address).is_none() {
+        return Err(ServiceError::ValidationError(format!(
+            "{} is not a P2PKH address",
+            request.destination_address
+        )));
+    }
+
+    let key = idempotency::key(&http_req)?;
+    // The TOTP code changes between retries, so it is left out
+    let fingerprint = idempotency::fingerprint(&serde_json::json!({
+        "user_paymail": request.user_paymail,
+        "amount_satoshis": request.amount_satoshis,
+        "destination_address": request.destination_address
+    }));
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    // Held until commit, so a retry waits for the first attempt and then sees its row
+    ledger::lock_account(&mut db_tx, &request.user_paymail).await.map_err(db_error)?;
+    let user_id = security::user_id(&mut *db_tx, &request.user_paymail).await?;
+
+    if let Some(key) = &key {
+        let stored: Option<(Option<String>, Uuid)> = sqlx::query_as(
+            "SELECT request_fingerprint, id FROM withdrawals WHERE user_id = $1 AND idempotency_key = $2"
+        )
+        .bind(user_id)
+        .bind(key)
+        .fetch_optional(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+        match stored {
+            Some((stored_fingerprint, id)) if stored_fingerprint.as_deref() == Some(fingerprint.as_str()) => {
+                let withdrawal = sqlx::query_as::<_, Withdrawal>(&format!(
+                    "SELECT {} FROM withdrawals WHERE id = $1",
+                    WITHDRAWAL_COLUMNS
+                ))
+                .bind(id)
+                .fetch_one(&mut *db_tx)
+                .await
+                .map_err(db_error)?;
+                tracing::info!("Replaying withdrawal {} for {} key {}", id, request.user_paymail, key);
+                return Ok(HttpResponse::Ok().insert_header((REPLAY_HEADER, "true")).json(withdrawal));
+            }
+            Some(_) => return Err(idempotency::key_reused(key, "withdrawal")),
+            None => {}
+        }
+    }
+
+    security::check_destination(&mut *db_tx, user_id, &request.destination_address).await?;
+    if policy.requires_two_factor(request.amount_satoshis) {
+        security::verify_code(&mut db_tx, user_id, request.totp_code.as_deref()).await?;
+    }
+
+    tiers::usage(&mut *db_tx, &request.user_paymail)
+        .await
+        .map_err(db_error)?
+        .check(Flow::Withdrawal, request.amount_satoshis)?;
+    let fee = fees::terms(&mut *db_tx, Operation::Withdrawal, &request.user_paymail, None)
+        .await
+        .map_err(db_error)?
+        .fee(request.amount_satoshis);
+    ledger::ensure_available(&mut db_tx, &request.user_paymail, request.amount_satoshis + fee).await?;
+
+    let new = NewWithdrawal {
+        user_id,
+        paymail: &request.user_paymail,
+        amount_satoshis: request.amount_satoshis,
+        fee_satoshis: fee,
+        destination_address: &request.destination_address,
+    };
+    let withdrawal = insert(&mut db_tx, &policy, &new, key.as_deref().map(|key| (key, fingerprint.as_str())))
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    metrics.record_withdrawal_status(&withdrawal.status, withdrawal.amount_satoshis);
+    tracing::info!(
+        "Withdrawal {} requested: {} sat from {} to {} ({})",
+        withdrawal.id, withdrawal.amount_satoshis, request.user_paymail, withdrawal.destination_address, withdrawal.status
+    );
+    Ok(HttpResponse::Created().json(withdrawal))
+}
+
+/// `GET /withdrawals/{paymail}`: the user's withdrawals, newest first
+pub async fn list_withdrawals(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+
+    let withdrawals = sqlx::query_as::<_, Withdrawal>(&format!(
+        r#"
+        SELECT {} FROM withdrawals
+        WHERE user_id = (SELECT id FROM users WHERE paymail = $1)
+        ORDER BY created_at DESC
+        LIMIT 200
+        "#,
+        WITHDRAWAL_COLUMNS
+    ))
+    .bind(paymail.as_str())
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "withdrawals": withdrawals
+    })))
+}
+
+// ============================================================================
+// SETTLEMENT
+// ============================================================================
+
+/// Debit confirmed withdrawals and their fees from the ledger, every `WITHDRAWAL_SETTLEMENT_INTERVAL_SECS` (default 30).
+/// Until then the balance view holds them as in flight.
+pub async fn start_withdrawal_settlement(pool: PgPool) {
+    let interval_secs = std::env::var("WITHDRAWAL_SETTLEMENT_INTERVAL_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(30);
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+
+    loop {
+        interval.tick().await;
+        match settle_confirmed(&pool).await {
+            Ok(settled) if settled > 0 => tracing::info!("Settled {} confirmed withdrawal(s)", settled),
+            Ok(_) => {}
+            Err(e) => tracing::error!("Withdrawal settlement failed: {}", e),
+        }
+    }
+}
+
+async fn settle_confirmed(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let mut db_tx = pool.begin().await?;
+    let confirmed: Vec<(Uuid, String, i64, i64)> = sqlx::query_as(
+        r#"
+        SELECT w.id, u.paymail, w.amount_satoshis, w.fee_satoshis
+        FROM withdrawals w JOIN users u ON u.id = w.user_id
+        WHERE w.status = 'Confirmed' AND w.settled_transfer_id IS NULL
+        ORDER BY w.created_at
+        LIMIT 100
+        FOR UPDATE OF w SKIP LOCKED
+        "#
+    )
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    for (withdrawal_id, paymail, amount, fee) in &confirmed {
+        let transfer_id = ledger::transfer(
+            &mut db_tx,
+            ledger::WITHDRAWAL,
+            paymail,
+            ledger::WITHDRAWAL_ACCOUNT,
+            *amount,
+            None,
+        )
+        .await?;
+        let fee_transfer_id = ledger::transfer(
+            &mut db_tx,
+            ledger::WITHDRAWAL_FEE,
+            paymail,
+            ledger::WITHDRAWAL_FEE_ACCOUNT,
+            *fee,
+            None,
+        )
+        .await?;
+        sqlx::query(
+            r#"
+            UPDATE withdrawals
+            SET settled_transfer_id = $2, fee_transfer_id = $3, completed_at = COALESCE(completed_at, NOW())
+            WHERE id = $1
+            "#
+        )
+        .bind(withdrawal_id)
+        .bind(transfer_id)
+        .bind(fee_transfer_id)
+        .execute(&mut *db_tx)
+        .await?;
+    }
+    db_tx.commit().await?;
+    Ok(confirmed.len())
+}
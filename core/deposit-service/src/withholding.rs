@@ -0,0 +1,448 @@
+// core/deposit-service/src/withholding.rs
+// Interest tax withholding per jurisdiction, and yearly interest income reports for statements
+
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use bsv_bank_common::{validate_paymail, Claims};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::history::{csv_field, StatementFormat};
+use crate::{ledger, require_admin, require_paymail, ServiceError};
+
+/// Jurisdiction of users who have not declared one
+pub const DEFAULT_JURISDICTION: &str = "DEFAULT";
+
+const MAX_CODE_LEN: usize = 10;
+const MAX_NAME_LEN: usize = 100;
+/// Bitcoin's first year; nothing can have settled before it
+const FIRST_YEAR: i32 = 2009;
+
+const JURISDICTION_COLUMNS: &str = "code, name, withholding_bps, updated_by, created_at, updated_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TaxJurisdiction {
+    pub code: String,
+    pub name: String,
+    pub withholding_bps: i32,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// `bps` of `amount`, truncated toward zero so a reversal gives back no more than was withheld
+pub fn withheld(amount: i64, bps: i32) -> i64 {
+    (amount as i128 * bps as i128 / 10_000) as i64
+}
+
+/// Withhold tax from a posting that has just been credited, at the rate of the user's
+/// jurisdiction, and record it on the posting. A negative posting gives withholding back.
+pub async fn withhold(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    posting_id: Uuid,
+    user_id: i32,
+    paymail: &str,
+    amount: i64,
+) -> Result<i64, sqlx::Error> {
+    let (jurisdiction, bps): (String, i32) = sqlx::query_as(
+        r#"
+        SELECT j.code, j.withholding_bps
+        FROM users u
+        JOIN tax_jurisdictions j ON j.code = COALESCE(u.tax_jurisdiction, $2)
+        WHERE u.id = $1
+        "#
+    )
+    .bind(user_id)
+    .bind(DEFAULT_JURISDICTION)
+    .fetch_one(&mut **db_tx)
+    .await?;
+
+    let amount_withheld = withheld(amount, bps);
+    let account = ledger::withholding_account(&jurisdiction);
+    let transfer_id = if amount_withheld > 0 {
+        ledger::transfer(db_tx, ledger::INTEREST_WITHHOLDING, paymail, &account, amount_withheld, None).await?
+    } else {
+        ledger::transfer(db_tx, ledger::INTEREST_WITHHOLDING, &account, paymail, -amount_withheld, None).await?
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE interest_postings
+        SET tax_jurisdiction = $2, withholding_bps = $3, withheld_satoshis = $4, withholding_transfer_id = $5
+        WHERE id = $1
+        "#
+    )
+    .bind(posting_id)
+    .bind(&jurisdiction)
+    .bind(bps)
+    .bind(amount_withheld)
+    .bind(transfer_id)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(amount_withheld)
+}
+
+// ============================================================================
+// REPORTS
+// ============================================================================
+
+/// One settled posting in a yearly report
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct ReportLine {
+    pub posting_id: Uuid,
+    pub settled_at: DateTime<Utc>,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub gross_satoshis: i64,
+    /// None for postings settled before withholding existed
+    pub tax_jurisdiction: Option<String>,
+    pub withholding_bps: Option<i32>,
+    pub withheld_satoshis: i64,
+    pub net_satoshis: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+pub struct IncomeTotals {
+    pub gross_satoshis: i64,
+    pub withheld_satoshis: i64,
+    pub net_satoshis: i64,
+    pub posting_count: i64,
+}
+
+impl IncomeTotals {
+    fn add(&mut self, line: &ReportLine) {
+        self.gross_satoshis += line.gross_satoshis;
+        self.withheld_satoshis += line.withheld_satoshis;
+        self.net_satoshis += line.net_satoshis;
+        self.posting_count += 1;
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthIncome {
+    pub month: u32,
+    #[serde(flatten)]
+    pub totals: IncomeTotals,
+}
+
+/// Interest income for a calendar year (UTC), counted when it was credited to the balance
+#[derive(Debug, Clone, Serialize)]
+pub struct InterestReport {
+    pub paymail: String,
+    pub year: i32,
+    /// The user's jurisdiction today; each line carries the one it was withheld under
+    pub tax_jurisdiction: String,
+    #[serde(flatten)]
+    pub totals: IncomeTotals,
+    /// Months with at least one posting
+    pub months: Vec<MonthIncome>,
+    pub lines: Vec<ReportLine>,
+}
+
+/// Totals for the year and per month over `lines`, which are in settlement order
+pub fn summarize(paymail: &str, year: i32, tax_jurisdiction: String, lines: Vec<ReportLine>) -> InterestReport {
+    let mut totals = IncomeTotals::default();
+    let mut months: Vec<MonthIncome> = Vec::new();
+    for line in &lines {
+        totals.add(line);
+        let month = line.settled_at.month();
+        match months.last_mut() {
+            Some(last) if last.month == month => last.totals.add(line),
+            _ => {
+                let mut month_totals = IncomeTotals::default();
+                month_totals.add(line);
+                months.push(MonthIncome { month, totals: month_totals });
+            }
+        }
+    }
+    InterestReport { paymail: paymail.to_string(), year, tax_jurisdiction, totals, months, lines }
+}
+
+pub fn report_csv(lines: &[ReportLine]) -> String {
+    let mut out = String::from(
+        "settled_at,posting_id,period_start,period_end,gross_satoshis,tax_jurisdiction,withholding_bps,withheld_satoshis,net_satoshis\n",
+    );
+    for line in lines {
+        let fields = [
+            line.settled_at.to_rfc3339(),
+            line.posting_id.to_string(),
+            line.period_start.to_rfc3339(),
+            line.period_end.to_rfc3339(),
+            line.gross_satoshis.to_string(),
+            line.tax_jurisdiction.clone().unwrap_or_default(),
+            line.withholding_bps.map(|bps| bps.to_string()).unwrap_or_default(),
+            line.withheld_satoshis.to_string(),
+            line.net_satoshis.to_string(),
+        ];
+        out.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        out.push('\n');
+    }
+    out
+}
+
+/// `[Jan 1, Jan 1 of the next year)` in UTC, for years from 2009 to the current one
+pub fn year_range(year: i32, now: DateTime<Utc>) -> Result<(DateTime<Utc>, DateTime<Utc>), ServiceError> {
+    if !(FIRST_YEAR..=now.year()).contains(&year) {
+        return Err(ServiceError::ValidationError(format!(
+            "year must be between {} and {}",
+            FIRST_YEAR,
+            now.year()
+        )));
+    }
+    let start = |y: i32| Utc.with_ymd_and_hms(y, 1, 1, 0, 0, 0).single();
+    match (start(year), start(year + 1)) {
+        (Some(from), Some(to)) => Ok((from, to)),
+        _ => Err(ServiceError::ValidationError(format!("Invalid year: {}", year))),
+    }
+}
+
+fn validate_jurisdiction(code: &str) -> Result<(), ServiceError> {
+    let valid = !code.is_empty()
+        && code.len() <= MAX_CODE_LEN
+        && code.chars().all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '-');
+    if !valid {
+        return Err(ServiceError::ValidationError(format!(
+            "Jurisdiction codes are 1-{} uppercase letters, digits or '-', e.g. GB or US-CA",
+            MAX_CODE_LEN
+        )));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct JurisdictionRequest {
+    pub name: String,
+    pub withholding_bps: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetUserJurisdictionRequest {
+    pub jurisdiction: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InterestReportQuery {
+    pub year: i32,
+    #[serde(default)]
+    pub format: StatementFormat,
+}
+
+/// `GET /tax-jurisdictions`
+pub async fn list_jurisdictions(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
+    let jurisdictions = sqlx::query_as::<_, TaxJurisdiction>(&format!(
+        "SELECT {} FROM tax_jurisdictions ORDER BY code",
+        JURISDICTION_COLUMNS
+    ))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "default_jurisdiction": DEFAULT_JURISDICTION,
+        "jurisdictions": jurisdictions
+    })))
+}
+
+/// `PUT /tax-jurisdictions/{code}` (admin): create a jurisdiction or change its rate. The
+/// rate applies to postings settled from now on.
+pub async fn set_jurisdiction(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    code: web::Path<String>,
+    request: web::Json<JurisdictionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+    validate_jurisdiction(&code)?;
+    if request.name.trim().is_empty() || request.name.len() > MAX_NAME_LEN {
+        return Err(ServiceError::ValidationError(format!("Name must be 1-{} characters", MAX_NAME_LEN)));
+    }
+    if !(0..=10_000).contains(&request.withholding_bps) {
+        return Err(ServiceError::ValidationError("withholding_bps must be between 0 and 10000".to_string()));
+    }
+    let admin = http_req
+        .extensions()
+        .get::<Claims>()
+        .map(|claims| claims.sub.clone())
+        .unwrap_or_default();
+
+    let jurisdiction = sqlx::query_as::<_, TaxJurisdiction>(&format!(
+        r#"
+        INSERT INTO tax_jurisdictions (code, name, withholding_bps, updated_by)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (code) DO UPDATE
+        SET name = EXCLUDED.name, withholding_bps = EXCLUDED.withholding_bps,
+            updated_by = EXCLUDED.updated_by, updated_at = NOW()
+        RETURNING {}
+        "#,
+        JURISDICTION_COLUMNS
+    ))
+    .bind(code.as_str())
+    .bind(request.name.trim())
+    .bind(request.withholding_bps)
+    .bind(&admin)
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!("{} set interest withholding for {} to {} bps", admin, jurisdiction.code, jurisdiction.withholding_bps);
+    Ok(HttpResponse::Ok().json(jurisdiction))
+}
+
+/// `PUT /users/{paymail}/tax-jurisdiction` (admin): the jurisdiction the user's interest is
+/// withheld under from now on
+pub async fn set_user_jurisdiction(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    request: web::Json<SetUserJurisdictionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req)?;
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_jurisdiction(&request.jurisdiction)?;
+
+    let updated = sqlx::query("UPDATE users SET tax_jurisdiction = $2, updated_at = NOW() WHERE paymail = $1")
+        .bind(paymail.as_str())
+        .bind(&request.jurisdiction)
+        .execute(pool.get_ref())
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_foreign_key_violation() => {
+                ServiceError::ValidationError(format!("Unknown tax jurisdiction: {}", request.jurisdiction))
+            }
+            e => db_error(e),
+        })?
+        .rows_affected();
+    if updated == 0 {
+        return Err(ServiceError::ValidationError(format!("Unknown user: {}", paymail)));
+    }
+
+    tracing::info!("Interest for {} is now withheld under {}", paymail, request.jurisdiction);
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "tax_jurisdiction": request.jurisdiction
+    })))
+}
+
+/// `GET /reports/interest/{paymail}?year=&format=json|csv`: interest income credited in the
+/// year with tax withheld, for the user's statements
+pub async fn get_interest_report(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    query: web::Query<InterestReportQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &paymail)?;
+    let (from, to) = year_range(query.year, Utc::now())?;
+
+    let lines = sqlx::query_as::<_, ReportLine>(
+        r#"
+        SELECT id AS posting_id, settled_at, period_start, period_end, amount_satoshis AS gross_satoshis,
+               tax_jurisdiction, withholding_bps, withheld_satoshis,
+               amount_satoshis - withheld_satoshis AS net_satoshis
+        FROM interest_postings
+        WHERE paymail = $1 AND status = 'Settled' AND settled_at >= $2 AND settled_at < $3
+        ORDER BY settled_at, id
+        "#
+    )
+    .bind(paymail.as_str())
+    .bind(from)
+    .bind(to)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    let jurisdiction: Option<String> = sqlx::query_scalar("SELECT tax_jurisdiction FROM users WHERE paymail = $1")
+        .bind(paymail.as_str())
+        .fetch_optional(pool.get_ref())
+        .await
+        .map_err(db_error)?
+        .flatten();
+    let jurisdiction = jurisdiction.unwrap_or_else(|| DEFAULT_JURISDICTION.to_string());
+
+    match query.format {
+        StatementFormat::Csv => Ok(HttpResponse::Ok()
+            .content_type("text/csv; charset=utf-8")
+            .insert_header((
+                "Content-Disposition",
+                format!("attachment; filename=\"interest-{}.csv\"", query.year),
+            ))
+            .body(report_csv(&lines))),
+        StatementFormat::Json => Ok(HttpResponse::Ok().json(summarize(&paymail, query.year, jurisdiction, lines))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(settled_at: DateTime<Utc>, gross: i64, withheld: i64) -> ReportLine {
+        ReportLine {
+            posting_id: Uuid::new_v4(),
+            settled_at,
+            period_start: settled_at,
+            period_end: settled_at,
+            gross_satoshis: gross,
+            tax_jurisdiction: Some("GB".to_string()),
+            withholding_bps: Some(2_000),
+            withheld_satoshis: withheld,
+            net_satoshis: gross - withheld,
+        }
+    }
+
+    #[test]
+    fn test_withheld_truncates_toward_zero() {
+        assert_eq!(withheld(1_000, 2_000), 200);
+        assert_eq!(withheld(999, 2_000), 199);
+        // A reversal gives back no more than the original posting withheld
+        assert_eq!(withheld(-999, 2_000), -199);
+        assert_eq!(withheld(1_000, 0), 0);
+    }
+
+    #[test]
+    fn test_summarize_by_month() {
+        let jan = Utc.with_ymd_and_hms(2025, 1, 31, 12, 0, 0).unwrap();
+        let mar = Utc.with_ymd_and_hms(2025, 3, 1, 0, 0, 0).unwrap();
+        let report = summarize(
+            "alice@handcash.io",
+            2025,
+            "GB".to_string(),
+            vec![line(jan, 1_000, 200), line(jan, 500, 100), line(mar, -100, -20)],
+        );
+        assert_eq!(report.totals, IncomeTotals { gross_satoshis: 1_400, withheld_satoshis: 280, net_satoshis: 1_120, posting_count: 3 });
+        assert_eq!(report.months.len(), 2);
+        assert_eq!((report.months[0].month, report.months[0].totals.gross_satoshis), (1, 1_500));
+        assert_eq!((report.months[1].month, report.months[1].totals.net_satoshis), (3, -80));
+        assert_eq!(report_csv(&report.lines).lines().count(), 4);
+    }
+
+    #[test]
+    fn test_year_range() {
+        let now = Utc.with_ymd_and_hms(2025, 6, 1, 0, 0, 0).unwrap();
+        let (from, to) = year_range(2024, now).unwrap();
+        assert_eq!(from, Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap());
+        assert_eq!(to, Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+        assert!(year_range(2025, now).is_ok());
+        assert!(year_range(2026, now).is_err());
+        assert!(year_range(2008, now).is_err());
+    }
+
+    #[test]
+    fn test_validate_jurisdiction() {
+        assert!(validate_jurisdiction("GB").is_ok());
+        assert!(validate_jurisdiction("US-CA").is_ok());
+        assert!(validate_jurisdiction(DEFAULT_JURISDICTION).is_ok());
+        assert!(validate_jurisdiction("gb").is_err());
+        assert!(validate_jurisdiction("").is_err());
+        assert!(validate_jurisdiction("ABCDEFGHIJK").is_err());
+    }
+}
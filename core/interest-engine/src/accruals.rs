@@ -0,0 +1,539 @@
+// core/interest-engine/src/accruals.rs
+// Depositor interest accruals, kept in interest_accruals until the posting job groups them into postings
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{interval_from_env, run_every, validate_paymail, JwtManager};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{self, RateModelConfig};
+use crate::outbox::{self, InterestEvent};
+use crate::rates::utilization;
+use crate::reserves;
+use crate::utilization::PoolTotals;
+use crate::{require_admin_or_service, require_paymail, ServiceError};
+
+/// Depositor APY when neither the product nor demand deposits have a rate model
+const FALLBACK_DEPOSIT_APY: f64 = 0.07;
+
+/// Product for balances that are not in a term deposit
+pub const DEMAND_PRODUCT: &str = "demand";
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+/// Oldest missed day the job will still accrue after an outage
+const MAX_CATCH_UP_DAYS: i64 = 31;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Compounding {
+    /// Interest on principal only
+    Simple,
+    /// Unposted interest earns interest, compounded once a day
+    Daily,
+    /// Unposted interest earns interest, compounded continuously
+    Continuous,
+}
+
+impl Compounding {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "simple" => Some(Self::Simple),
+            "daily" => Some(Self::Daily),
+            "continuous" => Some(Self::Continuous),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Simple => "simple",
+            Self::Daily => "daily",
+            Self::Continuous => "continuous",
+        }
+    }
+
+    /// Interest over `days` on `principal`, plus `unposted` earlier interest where it
+    /// compounds; rounded down to whole satoshis
+    pub fn interest(&self, principal: i64, unposted: i64, apy: f64, days: f64) -> i64 {
+        let principal = principal.max(0) as f64;
+        let base = principal + unposted.max(0) as f64;
+        let interest = match self {
+            Self::Simple => principal * apy * days / 365.0,
+            Self::Daily => base * ((1.0 + apy / 365.0).powf(days) - 1.0),
+            Self::Continuous => base * (apy * days / 365.0).exp_m1(),
+        };
+        interest.max(0.0) as i64
+    }
+}
+
+/// A balance accruing as one unit: a user's demand balance, or one open term deposit
+#[derive(Debug, sqlx::FromRow)]
+pub struct AccrualUnit {
+    pub user_id: i32,
+    pub paymail: String,
+    pub deposit_id: Option<Uuid>,
+    pub product_code: String,
+    pub apy_boost_bps: i32,
+    pub principal: i64,
+    pub unposted: i64,
+    pub compounding: String,
+}
+
+impl AccrualUnit {
+    /// Compounding, APY and one day's interest at `utilization`. Products without a model
+    /// of their own earn the demand rate.
+    pub fn price(&self, rate_models: &HashMap<String, RateModelConfig>, utilization: f64) -> (Compounding, f64, i64) {
+        let compounding = Compounding::parse(&self.compounding).unwrap_or(Compounding::Simple);
+        let supply_apy = rate_models
+            .get(&self.product_code)
+            .or_else(|| rate_models.get(DEMAND_PRODUCT))
+            .map(|config| config.model().rates(utilization).supply_apy)
+            .unwrap_or(FALLBACK_DEPOSIT_APY);
+        let apy = supply_apy + self.apy_boost_bps as f64 / 10_000.0;
+        (compounding, apy, compounding.interest(self.principal, self.unposted, apy, 1.0))
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct AccrualRun {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Accruals written by this run; balances already accrued for the period are skipped
+    pub accrual_count: i32,
+    pub accrued_satoshis: i64,
+    /// Moved into the treasury as the bank's share of pool interest; full runs only
+    pub reserved_satoshis: i64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AccrualQuery {
+    /// Accruals for periods starting at or after this time
+    pub from: Option<DateTime<Utc>>,
+    /// Accruals for periods starting before this time
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    /// `next_cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+/// One day's accrual on one balance, or an adjustment to it
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AccrualDetail {
+    pub id: i32,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// The term deposit that accrued; null for the demand balance
+    pub deposit_id: Option<Uuid>,
+    pub product_code: String,
+    pub compounding: Option<String>,
+    pub rate_apy: f64,
+    pub amount_satoshis: i64,
+    pub paid_out: bool,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub posting_id: Option<Uuid>,
+    /// Set when this row corrects an earlier accrual for the period
+    pub adjustment_id: Option<Uuid>,
+}
+
+/// Accruals on one balance over the requested range
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct BalanceAccruals {
+    pub deposit_id: Option<Uuid>,
+    pub product_code: String,
+    pub accrual_count: i64,
+    pub amount_satoshis: i64,
+    pub average_rate_apy: f64,
+    pub first_period_start: DateTime<Utc>,
+    pub last_period_end: DateTime<Utc>,
+}
+
+/// Position after the last accrual of a page; opaque to clients
+#[derive(Debug, PartialEq, Eq)]
+struct AccrualCursor {
+    period_start: DateTime<Utc>,
+    id: i32,
+}
+
+impl AccrualCursor {
+    fn encode(&self) -> String {
+        hex::encode(format!("{}:{}", self.period_start.timestamp_micros(), self.id))
+    }
+
+    fn decode(value: &str) -> Result<Self, ServiceError> {
+        let invalid = || ServiceError::ValidationError("Invalid cursor".to_string());
+        let raw = String::from_utf8(hex::decode(value).map_err(|_| invalid())?).map_err(|_| invalid())?;
+        let (micros, id) = raw.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            period_start: DateTime::from_timestamp_micros(micros.parse().map_err(|_| invalid())?).ok_or_else(invalid)?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DistributeQuery {
+    pub paymail: Option<String>, // Optional: distribute to specific user
+}
+
+/// Accrual periods are UTC days; this is the start of the current one
+pub fn day_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.duration_trunc(Duration::days(1)).unwrap_or(now)
+}
+
+// ============================================================================
+// ACCRUAL JOB
+// ============================================================================
+
+/// Accrue every completed day not yet run, checking every `INTEREST_ACCRUAL_INTERVAL_SECS`
+/// (default hourly)
+pub async fn start_accruals(pool: PgPool) {
+    let period = interval_from_env("INTEREST_ACCRUAL_INTERVAL_SECS", 3600);
+    run_every("interest-accrual", period, move || {
+        let pool = pool.clone();
+        async move { accrue_due(&pool, Utc::now()).await }
+    })
+    .await
+}
+
+/// Run each completed day since the last run, oldest first; returns the number of days run
+async fn accrue_due(pool: &PgPool, now: DateTime<Utc>) -> Result<usize, sqlx::Error> {
+    let today = day_start(now);
+    let last: Option<DateTime<Utc>> = sqlx::query_scalar("SELECT MAX(period_end) FROM interest_accrual_runs")
+        .fetch_one(pool)
+        .await?;
+
+    let earliest = today - Duration::days(MAX_CATCH_UP_DAYS);
+    let mut period_start = last.unwrap_or(today - Duration::days(1));
+    if period_start < earliest {
+        tracing::warn!("Interest accrual last ran through {}; accruing from {} only", period_start, earliest);
+        period_start = earliest;
+    }
+
+    let mut days = 0;
+    while period_start + Duration::days(1) <= today {
+        let run = accrue_period(pool, period_start, None).await?;
+        tracing::info!(
+            "Accrued {} sat of interest over {} balance(s) and reserved {} sat for {}",
+            run.accrued_satoshis,
+            run.accrual_count,
+            run.reserved_satoshis,
+            run.period_start.date_naive()
+        );
+        period_start = run.period_end;
+        days += 1;
+    }
+    Ok(days)
+}
+
+/// Accrue one day for every balance, or only `paymail`'s. Everything is written in one
+/// transaction, and a balance already accrued for the period is left alone, so rerunning
+/// a period never credits twice. Only a full run records the period as done, reserves
+/// the bank's share of the day's pool interest and publishes `accrual_completed`.
+pub async fn accrue_period(
+    pool: &PgPool,
+    period_start: DateTime<Utc>,
+    paymail: Option<&str>,
+) -> Result<AccrualRun, sqlx::Error> {
+    let period_end = period_start + Duration::days(1);
+    let totals = PoolTotals::query(pool).await?;
+    let utilization = utilization(totals.total_deposits, totals.total_borrowed);
+    let mut db_tx = pool.begin().await?;
+    let rate_models = models::load_all(&mut *db_tx).await?;
+
+    let units = sqlx::query_as::<_, AccrualUnit>(
+        r#"
+        WITH term AS (
+            SELECT d.id, d.user_id, d.amount_satoshis, d.term_product_code,
+                   COALESCE(d.term_apy_boost_bps, 0) AS apy_boost_bps
+            FROM deposits d
+            WHERE d.term_product_code IS NOT NULL
+              AND d.status IN ('Confirmed', 'Available')
+              AND d.matured_at IS NULL AND d.redeemed_at IS NULL
+        ),
+        units AS (
+            SELECT b.user_id, NULL::uuid AS deposit_id, $2::text AS product_code, 0 AS apy_boost_bps,
+                   b.balance_satoshis - COALESCE((SELECT SUM(t.amount_satoshis) FROM term t
+                                                  WHERE t.user_id = b.user_id), 0) AS principal
+            FROM user_balances b
+            UNION ALL
+            SELECT t.user_id, t.id, t.term_product_code, t.apy_boost_bps, t.amount_satoshis
+            FROM term t
+        )
+        SELECT x.user_id, u.paymail, x.deposit_id, x.product_code, x.apy_boost_bps,
+               x.principal::BIGINT AS principal,
+               COALESCE((SELECT SUM(ia.amount_satoshis) FROM interest_accruals ia
+                         WHERE ia.user_id = x.user_id AND ia.deposit_id IS NOT DISTINCT FROM x.deposit_id
+                           AND NOT ia.paid_out), 0)::BIGINT AS unposted,
+               COALESCE(p.compounding, d.compounding, 'simple') AS compounding
+        FROM units x
+        JOIN users u ON u.id = x.user_id
+        LEFT JOIN interest_products p ON p.code = x.product_code
+        LEFT JOIN interest_products d ON d.code = $2
+        WHERE x.principal > 0
+          AND u.archived_at IS NULL
+          AND ($1::text IS NULL OR u.paymail = $1)
+        ORDER BY x.user_id, x.deposit_id NULLS FIRST
+        "#
+    )
+    .bind(paymail)
+    .bind(DEMAND_PRODUCT)
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    let mut accrual_count = 0;
+    let mut accrued_satoshis = 0i64;
+    for unit in units {
+        let (compounding, apy, interest) = unit.price(&rate_models, utilization);
+        if interest == 0 {
+            continue;
+        }
+
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO interest_accruals
+                (user_id, deposit_id, amount_satoshis, rate_apy, period_start, period_end, product_code, compounding)
+            VALUES ($1, $2, $3, $4::FLOAT8, $5, $6, $7, $8)
+            ON CONFLICT (user_id, COALESCE(deposit_id, '00000000-0000-0000-0000-000000000000'::uuid), period_start)
+                WHERE product_code IS NOT NULL AND adjustment_id IS NULL
+                DO NOTHING
+            "#
+        )
+        .bind(unit.user_id)
+        .bind(unit.deposit_id)
+        .bind(interest)
+        .bind(apy)
+        .bind(period_start)
+        .bind(period_end)
+        .bind(&unit.product_code)
+        .bind(compounding.as_str())
+        .execute(&mut *db_tx)
+        .await?
+        .rows_affected();
+
+        if inserted > 0 {
+            accrual_count += 1;
+            accrued_satoshis += interest;
+            tracing::debug!("  {} earned {} satoshis on {}", unit.paymail, interest, unit.product_code);
+        }
+    }
+
+    let mut reserved_satoshis = 0;
+    if paymail.is_none() {
+        reserved_satoshis = reserves::accrue_reserve(&mut db_tx, &rate_models, &totals, period_start, period_end).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO interest_accrual_runs (period_start, period_end, accrual_count, accrued_satoshis)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (period_start) DO UPDATE
+            SET accrual_count = interest_accrual_runs.accrual_count + EXCLUDED.accrual_count,
+                accrued_satoshis = interest_accrual_runs.accrued_satoshis + EXCLUDED.accrued_satoshis,
+                completed_at = NOW()
+            "#
+        )
+        .bind(period_start)
+        .bind(period_end)
+        .bind(accrual_count)
+        .bind(accrued_satoshis)
+        .execute(&mut *db_tx)
+        .await?;
+
+        outbox::record(&mut *db_tx, &InterestEvent::AccrualCompleted {
+            period_start,
+            period_end,
+            accrual_count,
+            accrued_satoshis,
+            reserved_satoshis,
+            timestamp: Utc::now(),
+        })
+        .await?;
+    }
+    db_tx.commit().await?;
+
+    Ok(AccrualRun { period_start, period_end, accrual_count, accrued_satoshis, reserved_satoshis })
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// Accrue the previous UTC day now rather than waiting for the job; admins and sibling services only
+pub async fn distribute_interest(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    query: web::Query<DistributeQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin_or_service(&http_req, &jwt)?;
+    if let Some(ref paymail) = query.paymail {
+        validate_paymail(paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+
+    tracing::info!("Running interest distribution...");
+
+    let period_start = day_start(Utc::now()) - Duration::days(1);
+    let run = accrue_period(&pool, period_start, query.paymail.as_deref())
+        .await
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "success",
+        "distributed_at": Utc::now(),
+        "filter": query.paymail,
+        "run": run
+    })))
+}
+
+/// `GET /interest/{paymail}?from=&to=&limit=&cursor=`: accrued and paid-out totals with the
+/// accruals behind them, newest day first, and a summary per balance over the same range.
+/// The user's own token or an admin's.
+pub async fn get_accrued_interest(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    query: web::Query<AccrualQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &jwt, &paymail)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ServiceError::ValidationError(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+    let cursor = query.cursor.as_deref().map(AccrualCursor::decode).transpose()?;
+
+    // Unknown users have simply accrued nothing
+    let (accrued, paid_out): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(SUM(ia.amount_satoshis) FILTER (WHERE NOT ia.paid_out), 0)::BIGINT,
+               COALESCE(SUM(ia.amount_satoshis) FILTER (WHERE ia.paid_out), 0)::BIGINT
+        FROM interest_accruals ia
+        JOIN users u ON u.id = ia.user_id
+        WHERE u.paymail = $1
+        "#
+    )
+    .bind(paymail.as_str())
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    let mut accruals = sqlx::query_as::<_, AccrualDetail>(
+        r#"
+        SELECT ia.id, ia.period_start, ia.period_end, ia.deposit_id,
+               COALESCE(ia.product_code, $5) AS product_code, ia.compounding,
+               ia.rate_apy::FLOAT8 AS rate_apy, ia.amount_satoshis, COALESCE(ia.paid_out, false) AS paid_out,
+               ia.paid_at, ia.posting_id, ia.adjustment_id
+        FROM interest_accruals ia
+        JOIN users u ON u.id = ia.user_id
+        WHERE u.paymail = $1
+          AND ($2::TIMESTAMPTZ IS NULL OR ia.period_start >= $2)
+          AND ($3::TIMESTAMPTZ IS NULL OR ia.period_start < $3)
+          AND ($6::TIMESTAMPTZ IS NULL OR (ia.period_start, ia.id) < ($6, $7))
+        ORDER BY ia.period_start DESC, ia.id DESC
+        LIMIT $4 + 1
+        "#
+    )
+    .bind(paymail.as_str())
+    .bind(query.from)
+    .bind(query.to)
+    .bind(limit)
+    .bind(DEMAND_PRODUCT)
+    .bind(cursor.as_ref().map(|c| c.period_start))
+    .bind(cursor.as_ref().map(|c| c.id))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+    let next_cursor = if accruals.len() as i64 > limit {
+        accruals.truncate(limit as usize);
+        accruals.last().map(|a| AccrualCursor { period_start: a.period_start, id: a.id }.encode())
+    } else {
+        None
+    };
+
+    let balances = sqlx::query_as::<_, BalanceAccruals>(
+        r#"
+        SELECT ia.deposit_id, COALESCE(ia.product_code, $4) AS product_code,
+               COUNT(*) AS accrual_count,
+               SUM(ia.amount_satoshis)::BIGINT AS amount_satoshis,
+               AVG(ia.rate_apy)::FLOAT8 AS average_rate_apy,
+               MIN(ia.period_start) AS first_period_start,
+               MAX(ia.period_end) AS last_period_end
+        FROM interest_accruals ia
+        JOIN users u ON u.id = ia.user_id
+        WHERE u.paymail = $1
+          AND ($2::TIMESTAMPTZ IS NULL OR ia.period_start >= $2)
+          AND ($3::TIMESTAMPTZ IS NULL OR ia.period_start < $3)
+        GROUP BY ia.deposit_id, 2
+        ORDER BY ia.deposit_id NULLS FIRST, 2
+        "#
+    )
+    .bind(paymail.as_str())
+    .bind(query.from)
+    .bind(query.to)
+    .bind(DEMAND_PRODUCT)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "accrued_interest_satoshis": accrued,
+        "paid_out_interest_satoshis": paid_out,
+        "balances": balances,
+        "accruals": accruals,
+        "next_cursor": next_cursor,
+        "timestamp": Utc::now()
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simple_interest_ignores_unposted() {
+        // 100,000 sat at 7% is 19.17 sat a day
+        assert_eq!(Compounding::Simple.interest(100_000, 0, 0.07, 1.0), 19);
+        assert_eq!(Compounding::Simple.interest(100_000, 50_000, 0.07, 1.0), 19);
+        assert_eq!(Compounding::Simple.interest(1_000, 0, 0.07, 1.0), 0);
+        assert_eq!(Compounding::Simple.interest(-5_000, 0, 0.07, 1.0), 0);
+    }
+
+    #[test]
+    fn test_compounding_modes() {
+        // Over one day, daily compounding is simple interest on principal plus unposted interest
+        assert_eq!(Compounding::Daily.interest(100_000_000, 0, 0.07, 1.0), 19_178);
+        assert_eq!(Compounding::Daily.interest(100_000_000, 1_000_000, 0.07, 1.0), 19_369);
+        // Continuous compounding earns slightly more than daily
+        let continuous = Compounding::Continuous.interest(100_000_000, 0, 0.07, 365.0);
+        let daily = Compounding::Daily.interest(100_000_000, 0, 0.07, 365.0);
+        let simple = Compounding::Simple.interest(100_000_000, 0, 0.07, 365.0);
+        assert_eq!(simple, 7_000_000);
+        assert!(continuous > daily && daily > simple);
+        assert_eq!(continuous, 7_250_818);
+    }
+
+    #[test]
+    fn test_accrual_cursor_round_trips() {
+        let cursor = AccrualCursor { period_start: day_start(Utc::now()), id: 42 };
+        assert_eq!(AccrualCursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(AccrualCursor::decode("not-hex").is_err());
+        assert!(AccrualCursor::decode(&hex::encode("42")).is_err());
+    }
+
+    #[test]
+    fn test_parse_compounding() {
+        for mode in [Compounding::Simple, Compounding::Daily, Compounding::Continuous] {
+            assert_eq!(Compounding::parse(mode.as_str()), Some(mode));
+        }
+        assert_eq!(Compounding::parse("monthly"), None);
+    }
+}
@@ -0,0 +1,470 @@
+// core/interest-engine/src/corrections.rs
+// Accrual corrections: recompute past days and record the differences as signed adjustment accruals
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{validate_paymail, JwtManager};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Postgres, Transaction};
+use std::collections::{BTreeMap, HashMap};
+use uuid::Uuid;
+
+use crate::accruals::{day_start, AccrualUnit, Compounding, DEMAND_PRODUCT};
+use crate::models::{self, RateModelConfig};
+use crate::rates::utilization;
+use crate::utilization::PoolTotals;
+use crate::{require_admin, ServiceError};
+
+/// Most days one recompute covers
+const MAX_RECOMPUTE_DAYS: i64 = 31;
+
+const MAX_REASON_LEN: usize = 500;
+
+const ADJUSTMENT_COLUMNS: &str =
+    "id, period_start, period_end, paymail, reason, requested_by, adjustment_count, net_satoshis, created_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecomputeRequest {
+    /// First day to recompute; truncated to the start of its UTC day
+    pub from: DateTime<Utc>,
+    /// End of the range, exclusive; one day after `from` when omitted
+    pub to: Option<DateTime<Utc>>,
+    /// Only this depositor's accruals
+    pub paymail: Option<String>,
+    /// Required unless this is a dry run
+    pub reason: Option<String>,
+    /// Report the differences without recording them (default)
+    pub dry_run: Option<bool>,
+}
+
+/// One balance-day whose recorded accruals differ from the recomputed amount
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AccrualDiff {
+    #[serde(skip)]
+    pub user_id: i32,
+    pub paymail: String,
+    pub deposit_id: Option<Uuid>,
+    pub product_code: String,
+    pub compounding: String,
+    pub period_start: DateTime<Utc>,
+    pub principal_satoshis: i64,
+    pub rate_apy: f64,
+    /// Accruals and earlier adjustments already recorded for the day
+    pub recorded_satoshis: i64,
+    pub expected_satoshis: i64,
+    pub adjustment_satoshis: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct AccrualAdjustment {
+    pub id: Uuid,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub paymail: Option<String>,
+    pub reason: String,
+    pub requested_by: String,
+    pub adjustment_count: i32,
+    pub net_satoshis: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Accruals recorded for one balance on one day
+#[derive(Debug, sqlx::FromRow)]
+struct Recorded {
+    user_id: i32,
+    paymail: String,
+    deposit_id: Option<Uuid>,
+    product_code: String,
+    compounding: String,
+    rate_apy: f64,
+    amount_satoshis: i64,
+}
+
+/// The recompute range as whole UTC days, ending no later than the last completed run
+pub fn recompute_range(
+    from: DateTime<Utc>,
+    to: Option<DateTime<Utc>>,
+    last_run_end: Option<DateTime<Utc>>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let from = day_start(from);
+    let to = to.map(day_start).unwrap_or(from + Duration::days(1));
+    if to <= from {
+        return Err("to must be at least one day after from".to_string());
+    }
+    if to - from > Duration::days(MAX_RECOMPUTE_DAYS) {
+        return Err(format!("At most {} days can be recomputed at once", MAX_RECOMPUTE_DAYS));
+    }
+    // Later days belong to the accrual job, which would accrue them again
+    match last_run_end {
+        Some(end) if to <= end => Ok((from, to)),
+        Some(end) => Err(format!("Accruals have only run through {}; recompute up to then", end)),
+        None => Err("No accrual run has completed yet".to_string()),
+    }
+}
+
+/// Differences between what was recorded and what `expected` units earn; units that
+/// earned nothing but have recorded accruals are corrected down to zero
+fn diff(
+    period_start: DateTime<Utc>,
+    expected: Vec<(AccrualUnit, Compounding, f64, i64)>,
+    recorded: Vec<Recorded>,
+) -> Vec<AccrualDiff> {
+    let mut diffs: BTreeMap<(i32, Option<Uuid>), AccrualDiff> = BTreeMap::new();
+    for (unit, compounding, apy, interest) in expected {
+        diffs.insert(
+            (unit.user_id, unit.deposit_id),
+            AccrualDiff {
+                user_id: unit.user_id,
+                paymail: unit.paymail,
+                deposit_id: unit.deposit_id,
+                product_code: unit.product_code,
+                compounding: compounding.as_str().to_string(),
+                period_start,
+                principal_satoshis: unit.principal,
+                rate_apy: apy,
+                recorded_satoshis: 0,
+                expected_satoshis: interest,
+                adjustment_satoshis: 0,
+            },
+        );
+    }
+    for row in recorded {
+        diffs
+            .entry((row.user_id, row.deposit_id))
+            .or_insert_with(|| AccrualDiff {
+                user_id: row.user_id,
+                paymail: row.paymail,
+                deposit_id: row.deposit_id,
+                product_code: row.product_code,
+                compounding: row.compounding,
+                period_start,
+                principal_satoshis: 0,
+                rate_apy: row.rate_apy,
+                recorded_satoshis: 0,
+                expected_satoshis: 0,
+                adjustment_satoshis: 0,
+            })
+            .recorded_satoshis = row.amount_satoshis;
+    }
+
+    diffs
+        .into_values()
+        .map(|mut d| {
+            d.adjustment_satoshis = d.expected_satoshis - d.recorded_satoshis;
+            d
+        })
+        .filter(|d| d.adjustment_satoshis != 0)
+        .collect()
+}
+
+/// Recompute one day: balances as they stood at its start, rebuilt from confirmed deposits
+/// and ledger entries, priced by the rate models in force when the day ended at the pool
+/// utilization last snapshotted before then
+async fn recompute_day(
+    db_tx: &mut Transaction<'_, Postgres>,
+    rate_models: &HashMap<String, RateModelConfig>,
+    current_utilization: f64,
+    period_start: DateTime<Utc>,
+    paymail: Option<&str>,
+) -> Result<Vec<AccrualDiff>, sqlx::Error> {
+    let period_end = period_start + Duration::days(1);
+    let snapshot: Option<f64> = sqlx::query_scalar(
+        r#"
+        SELECT utilization_rate::FLOAT8 FROM interest_rates
+        WHERE created_at <= $1
+        ORDER BY created_at DESC
+        LIMIT 1
+        "#
+    )
+    .bind(period_end)
+    .fetch_optional(&mut **db_tx)
+    .await?;
+    let utilization = snapshot.unwrap_or(current_utilization);
+
+    let units = sqlx::query_as::<_, AccrualUnit>(
+        r#"
+        WITH confirmed AS (
+            SELECT d.id, d.user_id, d.amount_satoshis, d.term_product_code, d.matured_at, d.redeemed_at,
+                   COALESCE(d.term_apy_boost_bps, 0) AS apy_boost_bps
+            FROM deposits d
+            WHERE d.status IN ('Confirmed', 'Available')
+              AND COALESCE(d.confirmed_at, d.created_at) < $3
+        ),
+        term AS (
+            SELECT c.id, c.user_id, c.amount_satoshis, c.term_product_code, c.apy_boost_bps
+            FROM confirmed c
+            WHERE c.term_product_code IS NOT NULL
+              AND (c.matured_at IS NULL OR c.matured_at >= $3)
+              AND (c.redeemed_at IS NULL OR c.redeemed_at >= $3)
+        ),
+        balances AS (
+            SELECT u.id AS user_id,
+                   COALESCE((SELECT SUM(c.amount_satoshis) FROM confirmed c WHERE c.user_id = u.id), 0)
+                 + COALESCE((SELECT SUM(le.amount_satoshis) FROM ledger_entries le
+                             WHERE le.account = u.paymail AND le.created_at < $3), 0) AS balance_satoshis
+            FROM users u
+        ),
+        units AS (
+            SELECT b.user_id, NULL::uuid AS deposit_id, $2::text AS product_code, 0 AS apy_boost_bps,
+                   b.balance_satoshis - COALESCE((SELECT SUM(t.amount_satoshis) FROM term t
+                                                  WHERE t.user_id = b.user_id), 0) AS principal
+            FROM balances b
+            UNION ALL
+            SELECT t.user_id, t.id, t.term_product_code, t.apy_boost_bps, t.amount_satoshis
+            FROM term t
+        )
+        SELECT x.user_id, u.paymail, x.deposit_id, x.product_code, x.apy_boost_bps,
+               x.principal::BIGINT AS principal,
+               COALESCE((SELECT SUM(ia.amount_satoshis) FROM interest_accruals ia
+                         WHERE ia.user_id = x.user_id AND ia.deposit_id IS NOT DISTINCT FROM x.deposit_id
+                           AND ia.period_end <= $3
+                           AND (NOT ia.paid_out OR ia.paid_at >= $3)), 0)::BIGINT AS unposted,
+               COALESCE(p.compounding, d.compounding, 'simple') AS compounding
+        FROM units x
+        JOIN users u ON u.id = x.user_id
+        LEFT JOIN interest_products p ON p.code = x.product_code
+        LEFT JOIN interest_products d ON d.code = $2
+        WHERE x.principal > 0
+          AND (u.archived_at IS NULL OR u.archived_at >= $3)
+          AND ($1::text IS NULL OR u.paymail = $1)
+        "#
+    )
+    .bind(paymail)
+    .bind(DEMAND_PRODUCT)
+    .bind(period_start)
+    .fetch_all(&mut **db_tx)
+    .await?;
+
+    let recorded = sqlx::query_as::<_, Recorded>(
+        r#"
+        SELECT ia.user_id, u.paymail, ia.deposit_id,
+               COALESCE(MAX(ia.product_code), $3) AS product_code,
+               COALESCE(MAX(ia.compounding), 'simple') AS compounding,
+               MAX(ia.rate_apy)::FLOAT8 AS rate_apy,
+               SUM(ia.amount_satoshis)::BIGINT AS amount_satoshis
+        FROM interest_accruals ia
+        JOIN users u ON u.id = ia.user_id
+        WHERE ia.period_start = $1
+          AND ($2::text IS NULL OR u.paymail = $2)
+        GROUP BY ia.user_id, u.paymail, ia.deposit_id
+        "#
+    )
+    .bind(period_start)
+    .bind(paymail)
+    .bind(DEMAND_PRODUCT)
+    .fetch_all(&mut **db_tx)
+    .await?;
+
+    let expected = units
+        .into_iter()
+        .map(|unit| {
+            let (compounding, apy, interest) = unit.price(rate_models, utilization);
+            (unit, compounding, apy, interest)
+        })
+        .collect();
+    Ok(diff(period_start, expected, recorded))
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `POST /interest/accruals/recompute` (admin): recompute accruals for past days. A dry
+/// run (the default) returns the differences; otherwise each difference is recorded as a
+/// signed adjustment accrual, posted with the depositor's next posting.
+pub async fn recompute_accruals(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    request: web::Json<RecomputeRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = require_admin(&http_req, &jwt)?;
+    let request = request.into_inner();
+    if let Some(ref paymail) = request.paymail {
+        validate_paymail(paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+    let dry_run = request.dry_run.unwrap_or(true);
+    let reason = request.reason.as_deref().map(str::trim).filter(|r| !r.is_empty());
+    if reason.is_some_and(|r| r.len() > MAX_REASON_LEN) {
+        return Err(ServiceError::ValidationError(format!("Reason must be at most {} characters", MAX_REASON_LEN)));
+    }
+    if !dry_run && reason.is_none() {
+        return Err(ServiceError::ValidationError("A reason is required to record adjustments".to_string()));
+    }
+
+    let totals = PoolTotals::query(&pool).await.map_err(db_error)?;
+    let current_utilization = utilization(totals.total_deposits, totals.total_borrowed);
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    // One recompute at a time, so two cannot both correct the same difference
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext('interest_accrual_adjustments'))")
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+
+    let last_run_end: Option<DateTime<Utc>> = sqlx::query_scalar("SELECT MAX(period_end) FROM interest_accrual_runs")
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    let (from, to) = recompute_range(request.from, request.to, last_run_end).map_err(ServiceError::ValidationError)?;
+
+    let mut diffs = Vec::new();
+    let mut day = from;
+    while day < to {
+        let rate_models = models::load_as_of(&mut db_tx, day + Duration::days(1)).await.map_err(db_error)?;
+        diffs.extend(
+            recompute_day(&mut db_tx, &rate_models, current_utilization, day, request.paymail.as_deref())
+                .await
+                .map_err(db_error)?,
+        );
+        day += Duration::days(1);
+    }
+    let net: i64 = diffs.iter().map(|d| d.adjustment_satoshis).sum();
+
+    let mut adjustment_id = None;
+    if !dry_run && !diffs.is_empty() {
+        let id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO interest_accrual_adjustments
+                (period_start, period_end, paymail, reason, requested_by, adjustment_count, net_satoshis)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
+            "#
+        )
+        .bind(from)
+        .bind(to)
+        .bind(request.paymail.as_deref())
+        .bind(reason)
+        .bind(&claims.sub)
+        .bind(diffs.len() as i32)
+        .bind(net)
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+
+        for d in &diffs {
+            sqlx::query(
+                r#"
+                INSERT INTO interest_accruals
+                    (user_id, deposit_id, amount_satoshis, rate_apy, period_start, period_end,
+                     product_code, compounding, adjustment_id)
+                VALUES ($1, $2, $3, $4::FLOAT8, $5, $6, $7, $8, $9)
+                "#
+            )
+            .bind(d.user_id)
+            .bind(d.deposit_id)
+            .bind(d.adjustment_satoshis)
+            .bind(d.rate_apy)
+            .bind(d.period_start)
+            .bind(d.period_start + Duration::days(1))
+            .bind(&d.product_code)
+            .bind(&d.compounding)
+            .bind(id)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(db_error)?;
+        }
+        db_tx.commit().await.map_err(db_error)?;
+        tracing::info!(
+            "{} recorded {} accrual adjustment(s) netting {} sat for {} to {}",
+            claims.sub, diffs.len(), net, from, to
+        );
+        adjustment_id = Some(id);
+    }
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "dry_run": dry_run,
+        "period_start": from,
+        "period_end": to,
+        "paymail": request.paymail,
+        "adjustment_id": adjustment_id,
+        "adjustment_count": diffs.len(),
+        "net_adjustment_satoshis": net,
+        "adjustments": diffs
+    })))
+}
+
+/// `GET /interest/adjustments` (admin): recorded recomputes, newest first
+pub async fn list_adjustments(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req, &jwt)?;
+    let adjustments = sqlx::query_as::<_, AccrualAdjustment>(&format!(
+        "SELECT {} FROM interest_accrual_adjustments ORDER BY created_at DESC LIMIT 200",
+        ADJUSTMENT_COLUMNS
+    ))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "adjustments": adjustments })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn unit(user_id: i32, deposit_id: Option<Uuid>, principal: i64) -> AccrualUnit {
+        AccrualUnit {
+            user_id,
+            paymail: format!("user{}@example.com", user_id),
+            deposit_id,
+            product_code: DEMAND_PRODUCT.to_string(),
+            apy_boost_bps: 0,
+            principal,
+            unposted: 0,
+            compounding: "simple".to_string(),
+        }
+    }
+
+    fn recorded(user_id: i32, amount: i64) -> Recorded {
+        Recorded {
+            user_id,
+            paymail: format!("user{}@example.com", user_id),
+            deposit_id: None,
+            product_code: DEMAND_PRODUCT.to_string(),
+            compounding: "simple".to_string(),
+            rate_apy: 0.07,
+            amount_satoshis: amount,
+        }
+    }
+
+    #[test]
+    fn test_recompute_range() {
+        let day = Utc.with_ymd_and_hms(2025, 11, 20, 0, 0, 0).unwrap();
+        let last_run = Some(Utc.with_ymd_and_hms(2025, 11, 25, 0, 0, 0).unwrap());
+
+        // A time within the day covers the whole day
+        let (from, to) = recompute_range(day + Duration::hours(5), None, last_run).unwrap();
+        assert_eq!((from, to), (day, day + Duration::days(1)));
+
+        assert!(recompute_range(day, Some(day), last_run).is_err());
+        assert!(recompute_range(day, Some(day + Duration::days(10)), last_run).is_err());
+        assert!(recompute_range(day - Duration::days(40), Some(day), last_run).is_err());
+        assert!(recompute_range(day, None, None).is_err());
+    }
+
+    #[test]
+    fn test_diff_records_signed_corrections() {
+        let day = Utc.with_ymd_and_hms(2025, 11, 20, 0, 0, 0).unwrap();
+        let expected = vec![
+            (unit(1, None, 1_000_000), Compounding::Simple, 0.07, 191),
+            (unit(2, None, 1_000_000), Compounding::Simple, 0.07, 191),
+            (unit(3, None, 1_000_000), Compounding::Simple, 0.07, 191),
+        ];
+        // User 1 was accrued correctly, 2 too much, 3 missed; 4 had no balance that day
+        let diffs = diff(day, expected, vec![recorded(1, 191), recorded(2, 250), recorded(4, 40)]);
+
+        let adjustments: Vec<(i32, i64)> = diffs.iter().map(|d| (d.user_id, d.adjustment_satoshis)).collect();
+        assert_eq!(adjustments, vec![(2, -59), (3, 191), (4, -40)]);
+        assert_eq!(diffs[2].expected_satoshis, 0);
+        assert_eq!(diffs[2].recorded_satoshis, 40);
+    }
+}
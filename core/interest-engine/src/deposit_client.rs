@@ -0,0 +1,49 @@
+// core/interest-engine/src/deposit_client.rs
+// Pushes interest postings to deposit-service, which credits them to depositor balances
+
+use bsv_bank_common::JwtManager;
+use uuid::Uuid;
+
+/// Subject of this service's tokens for deposit-service's internal API
+const SERVICE_NAME: &str = "interest-engine";
+
+#[derive(Clone)]
+pub struct DepositClient {
+    client: reqwest::Client,
+    deposit_url: String,
+    jwt: JwtManager,
+}
+
+impl DepositClient {
+    pub fn from_env(jwt: JwtManager) -> Self {
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            deposit_url: std::env::var("DEPOSIT_SERVICE_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            jwt,
+        }
+    }
+
+    /// `POST /internal/interest/postings/{id}/settle` on deposit-service; settling an
+    /// already settled posting succeeds without crediting it twice
+    pub async fn settle_posting(&self, posting_id: Uuid) -> Result<(), String> {
+        let token = self.jwt
+            .create_service_token(SERVICE_NAME)
+            .map_err(|e| format!("Token error: {}", e))?;
+
+        let response = self.client
+            .post(format!("{}/internal/interest/postings/{}/settle", self.deposit_url, posting_id))
+            .bearer_auth(token)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        Ok(())
+    }
+}
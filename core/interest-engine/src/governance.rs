@@ -0,0 +1,592 @@
+// core/interest-engine/src/governance.rs
+// Rate model changes: proposed by an admin, optionally approved by a second one, applied at their effective time
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{interval_from_env, run_every, JwtManager};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool, Postgres, Transaction};
+use uuid::Uuid;
+
+use crate::models::{self, ProductRateModel, RateModelConfig};
+use crate::{require_admin, ServiceError};
+
+pub const PROPOSED: &str = "Proposed";
+pub const APPROVED: &str = "Approved";
+pub const APPLIED: &str = "Applied";
+pub const REJECTED: &str = "Rejected";
+
+const EVENT_PROPOSED: &str = "proposed";
+const EVENT_APPROVED: &str = "approved";
+const EVENT_REJECTED: &str = "rejected";
+const EVENT_APPLIED: &str = "applied";
+
+/// Actor recorded when the scheduled job applies a change
+const SYSTEM_ACTOR: &str = "system";
+
+const MAX_REASON_LEN: usize = 500;
+
+const CHANGE_COLUMNS: &str = "id, product_code, product_name, rate_model::TEXT AS rate_model, \
+     previous_model::TEXT AS previous_model, effective_at, requires_approval, status, proposed_by, \
+     approved_by, applied_at, created_at, updated_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Whether every change needs a second admin (`RATE_CHANGE_DUAL_APPROVAL=true`); without
+/// it a proposer may still ask for approval on a single change
+pub fn dual_approval_required() -> bool {
+    std::env::var("RATE_CHANGE_DUAL_APPROVAL")
+        .map(|v| matches!(v.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateModelChange {
+    pub id: Uuid,
+    pub product_code: String,
+    pub product_name: Option<String>,
+    pub rate_model: RateModelConfig,
+    pub previous_model: Option<RateModelConfig>,
+    pub effective_at: DateTime<Utc>,
+    pub requires_approval: bool,
+    /// Proposed (awaiting approval), Approved (awaiting effective_at), Applied or Rejected
+    pub status: String,
+    pub proposed_by: String,
+    pub approved_by: Option<String>,
+    pub applied_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ChangeRow {
+    id: Uuid,
+    product_code: String,
+    product_name: Option<String>,
+    rate_model: String,
+    previous_model: Option<String>,
+    effective_at: DateTime<Utc>,
+    requires_approval: bool,
+    status: String,
+    proposed_by: String,
+    approved_by: Option<String>,
+    applied_at: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<ChangeRow> for RateModelChange {
+    type Error = sqlx::Error;
+
+    fn try_from(row: ChangeRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            rate_model: RateModelConfig::parse(&row.product_code, &row.rate_model)?,
+            previous_model: row
+                .previous_model
+                .as_deref()
+                .map(|json| RateModelConfig::parse(&row.product_code, json))
+                .transpose()?,
+            id: row.id,
+            product_code: row.product_code,
+            product_name: row.product_name,
+            effective_at: row.effective_at,
+            requires_approval: row.requires_approval,
+            status: row.status,
+            proposed_by: row.proposed_by,
+            approved_by: row.approved_by,
+            applied_at: row.applied_at,
+            created_at: row.created_at,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct RateModelChangeEvent {
+    pub id: i64,
+    pub change_id: Uuid,
+    pub product_code: String,
+    pub event: String,
+    pub actor: String,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct EventRow {
+    id: i64,
+    change_id: Uuid,
+    product_code: String,
+    event: String,
+    actor: String,
+    details: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<EventRow> for RateModelChangeEvent {
+    fn from(row: EventRow) -> Self {
+        Self {
+            id: row.id,
+            change_id: row.change_id,
+            product_code: row.product_code,
+            event: row.event,
+            actor: row.actor,
+            details: serde_json::from_str(&row.details).unwrap_or_default(),
+            created_at: row.created_at,
+        }
+    }
+}
+
+async fn record<'e>(
+    executor: impl PgExecutor<'e>,
+    change_id: Uuid,
+    product: &str,
+    event: &str,
+    actor: &str,
+    details: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO rate_model_change_events (change_id, product_code, event, actor, details)
+        VALUES ($1, $2, $3, $4, $5::JSONB)
+        "#
+    )
+    .bind(change_id)
+    .bind(product)
+    .bind(event)
+    .bind(actor)
+    .bind(details.to_string())
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+async fn find_for_update(db_tx: &mut Transaction<'_, Postgres>, change_id: Uuid) -> Result<RateModelChange, ServiceError> {
+    let row = sqlx::query_as::<_, ChangeRow>(&format!(
+        "SELECT {} FROM rate_model_changes WHERE id = $1 FOR UPDATE",
+        CHANGE_COLUMNS
+    ))
+    .bind(change_id)
+    .fetch_optional(&mut **db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::NotFound(format!("Rate model change {} not found", change_id)))?;
+    RateModelChange::try_from(row).map_err(db_error)
+}
+
+/// Record a proposed change; without `requires_approval` it is approved as proposed and
+/// only waits for its effective time
+pub async fn propose(
+    db_tx: &mut Transaction<'_, Postgres>,
+    proposer: &str,
+    product: &str,
+    name: Option<&str>,
+    rate_model: &RateModelConfig,
+    effective_at: DateTime<Utc>,
+    requires_approval: bool,
+) -> Result<Uuid, ServiceError> {
+    let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM interest_products WHERE code = $1)")
+        .bind(product)
+        .fetch_one(&mut **db_tx)
+        .await
+        .map_err(db_error)?;
+    if !exists && name.is_none() {
+        return Err(ServiceError::ValidationError(format!("Unknown product {}; include a name to create it", product)));
+    }
+
+    let json = serde_json::to_string(rate_model).map_err(|_| ServiceError::InternalError)?;
+    let status = if requires_approval { PROPOSED } else { APPROVED };
+    let change_id: Uuid = sqlx::query_scalar(
+        r#"
+        INSERT INTO rate_model_changes
+            (product_code, product_name, rate_model, effective_at, requires_approval, status, proposed_by)
+        VALUES ($1, $2, $3::JSONB, $4, $5, $6, $7)
+        RETURNING id
+        "#
+    )
+    .bind(product)
+    .bind(name)
+    .bind(&json)
+    .bind(effective_at)
+    .bind(requires_approval)
+    .bind(status)
+    .bind(proposer)
+    .fetch_one(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+
+    record(
+        &mut **db_tx,
+        change_id,
+        product,
+        EVENT_PROPOSED,
+        proposer,
+        serde_json::json!({
+            "rate_model": rate_model,
+            "effective_at": effective_at,
+            "requires_approval": requires_approval
+        }),
+    )
+    .await
+    .map_err(db_error)?;
+    Ok(change_id)
+}
+
+/// Apply an approved change to its product, keeping the model it replaces
+pub async fn apply(
+    db_tx: &mut Transaction<'_, Postgres>,
+    change_id: Uuid,
+    actor: &str,
+) -> Result<ProductRateModel, ServiceError> {
+    let change = find_for_update(db_tx, change_id).await?;
+    if change.status != APPROVED {
+        return Err(ServiceError::ValidationError(format!(
+            "Rate model change {} is {}, not approved",
+            change_id, change.status
+        )));
+    }
+
+    let previous = models::resolve(&mut **db_tx, &change.product_code, None)
+        .await
+        .map_err(db_error)?
+        .map(|(_, config)| config);
+    let mut rate_model = change.rate_model;
+    if let RateModelConfig::Governance(ref mut rate) = rate_model {
+        rate.set_by = Some(change.approved_by.clone().unwrap_or_else(|| change.proposed_by.clone()));
+        rate.set_at = Some(Utc::now());
+    }
+    let updated = models::store(&mut **db_tx, &change.product_code, change.product_name.as_deref(), &rate_model)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::ValidationError(format!("Unknown product {}", change.product_code)))?;
+
+    let previous_json = previous
+        .as_ref()
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|_| ServiceError::InternalError)?;
+    sqlx::query(
+        r#"
+        UPDATE rate_model_changes
+        SET status = $2, previous_model = $3::JSONB, applied_at = NOW(), updated_at = NOW()
+        WHERE id = $1
+        "#
+    )
+    .bind(change_id)
+    .bind(APPLIED)
+    .bind(previous_json)
+    .execute(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+    record(
+        &mut **db_tx,
+        change_id,
+        &change.product_code,
+        EVENT_APPLIED,
+        actor,
+        serde_json::json!({ "previous_model": previous, "rate_model": rate_model }),
+    )
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!("Rate model for {} set to {} by change {}", change.product_code, rate_model.model().name(), change_id);
+    Ok(updated)
+}
+
+// ============================================================================
+// SCHEDULED APPLY
+// ============================================================================
+
+/// Apply approved changes once their effective time has passed, checking every
+/// `RATE_CHANGE_INTERVAL_SECS` (default every minute)
+pub async fn start_rate_changes(pool: PgPool) {
+    let period = interval_from_env("RATE_CHANGE_INTERVAL_SECS", 60);
+    run_every("rate-model-changes", period, move || {
+        let pool = pool.clone();
+        async move { apply_due(&pool, Utc::now()).await }
+    })
+    .await
+}
+
+/// Apply due changes in effective order, so the later of two changes to a product wins;
+/// returns the number applied
+async fn apply_due(pool: &PgPool, now: DateTime<Utc>) -> Result<usize, ServiceError> {
+    let due: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM rate_model_changes
+        WHERE status = $1 AND effective_at <= $2
+        ORDER BY effective_at, created_at
+        "#
+    )
+    .bind(APPROVED)
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+
+    let mut applied = 0;
+    for change_id in due {
+        let mut db_tx = pool.begin().await.map_err(db_error)?;
+        match apply(&mut db_tx, change_id, SYSTEM_ACTOR).await {
+            Ok(_) => {
+                db_tx.commit().await.map_err(db_error)?;
+                applied += 1;
+            }
+            Err(e) => tracing::error!("Applying rate model change {} failed: {}", change_id, e),
+        }
+    }
+    Ok(applied)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ProposeChangeRequest {
+    pub product: String,
+    /// Required when the product does not exist yet
+    pub name: Option<String>,
+    pub rate_model: RateModelConfig,
+    /// When the change takes effect; immediately once approved when omitted
+    pub effective_at: Option<DateTime<Utc>>,
+    /// Ask for a second admin's approval; always on with `RATE_CHANGE_DUAL_APPROVAL`
+    #[serde(default)]
+    pub require_approval: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ChangeQuery {
+    pub product: Option<String>,
+    pub status: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionRequest {
+    pub reason: Option<String>,
+}
+
+async fn fetch_change(pool: &PgPool, change_id: Uuid) -> Result<RateModelChange, ServiceError> {
+    let row = sqlx::query_as::<_, ChangeRow>(&format!("SELECT {} FROM rate_model_changes WHERE id = $1", CHANGE_COLUMNS))
+        .bind(change_id)
+        .fetch_optional(pool)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::NotFound(format!("Rate model change {} not found", change_id)))?;
+    RateModelChange::try_from(row).map_err(db_error)
+}
+
+/// `POST /rates/changes` (admin): propose a rate model change. Changes that need no
+/// approval and are already effective apply at once.
+pub async fn propose_change(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    request: web::Json<ProposeChangeRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = require_admin(&http_req, &jwt)?;
+    let request = request.into_inner();
+    models::validate_code(&request.product)?;
+    models::validate_name(request.name.as_deref())?;
+    request.rate_model.model().validate().map_err(ServiceError::ValidationError)?;
+
+    let now = Utc::now();
+    let effective_at = request.effective_at.unwrap_or(now);
+    let requires_approval = request.require_approval || dual_approval_required();
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let change_id = propose(
+        &mut db_tx,
+        &claims.sub,
+        &request.product,
+        request.name.as_deref().map(str::trim),
+        &request.rate_model,
+        effective_at,
+        requires_approval,
+    )
+    .await?;
+    if !requires_approval && effective_at <= now {
+        apply(&mut db_tx, change_id, &claims.sub).await?;
+    }
+    db_tx.commit().await.map_err(db_error)?;
+
+    Ok(HttpResponse::Created().json(fetch_change(&pool, change_id).await?))
+}
+
+/// `POST /rates/changes/{id}/approve` (admin): a second admin approves a proposed change;
+/// it applies at once if already effective
+pub async fn approve_change(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    change_id: web::Path<Uuid>,
+    request: web::Json<DecisionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = require_admin(&http_req, &jwt)?;
+    let reason = validate_reason(request.reason.as_deref())?;
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let change = find_for_update(&mut db_tx, *change_id).await?;
+    if change.status != PROPOSED {
+        return Err(ServiceError::ValidationError(format!(
+            "Rate model change {} is {}, not awaiting approval",
+            change.id, change.status
+        )));
+    }
+    if change.proposed_by == claims.sub {
+        return Err(ServiceError::Forbidden(
+            "Rate model changes must be approved by someone other than the proposer".to_string(),
+        ));
+    }
+
+    sqlx::query("UPDATE rate_model_changes SET status = $2, approved_by = $3, updated_at = NOW() WHERE id = $1")
+        .bind(change.id)
+        .bind(APPROVED)
+        .bind(&claims.sub)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    record(&mut *db_tx, change.id, &change.product_code, EVENT_APPROVED, &claims.sub, serde_json::json!({ "reason": reason }))
+        .await
+        .map_err(db_error)?;
+    if change.effective_at <= Utc::now() {
+        apply(&mut db_tx, change.id, &claims.sub).await?;
+    }
+    db_tx.commit().await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(fetch_change(&pool, change.id).await?))
+}
+
+/// `POST /rates/changes/{id}/reject` (admin): withdraw or turn down a change that has
+/// not been applied; a reason is required
+pub async fn reject_change(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    change_id: web::Path<Uuid>,
+    request: web::Json<DecisionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = require_admin(&http_req, &jwt)?;
+    let reason = validate_reason(request.reason.as_deref())?
+        .ok_or_else(|| ServiceError::ValidationError("A reason is required to reject a rate model change".to_string()))?;
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let change = find_for_update(&mut db_tx, *change_id).await?;
+    if change.status != PROPOSED && change.status != APPROVED {
+        return Err(ServiceError::ValidationError(format!(
+            "Rate model change {} is already {}",
+            change.id, change.status
+        )));
+    }
+
+    sqlx::query("UPDATE rate_model_changes SET status = $2, updated_at = NOW() WHERE id = $1")
+        .bind(change.id)
+        .bind(REJECTED)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    record(&mut *db_tx, change.id, &change.product_code, EVENT_REJECTED, &claims.sub, serde_json::json!({ "reason": reason }))
+        .await
+        .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(fetch_change(&pool, change.id).await?))
+}
+
+/// `GET /rates/changes?product=&status=` (admin): newest first
+pub async fn list_changes(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    query: web::Query<ChangeQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req, &jwt)?;
+    if let Some(status) = query.status.as_deref() {
+        if ![PROPOSED, APPROVED, APPLIED, REJECTED].contains(&status) {
+            return Err(ServiceError::ValidationError(format!("Unknown status: {}", status)));
+        }
+    }
+
+    let rows = sqlx::query_as::<_, ChangeRow>(&format!(
+        r#"
+        SELECT {} FROM rate_model_changes
+        WHERE ($1::TEXT IS NULL OR product_code = $1) AND ($2::TEXT IS NULL OR status = $2)
+        ORDER BY created_at DESC
+        LIMIT 200
+        "#,
+        CHANGE_COLUMNS
+    ))
+    .bind(query.product.as_deref())
+    .bind(query.status.as_deref())
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+    let changes = rows
+        .into_iter()
+        .map(RateModelChange::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "changes": changes })))
+}
+
+/// `GET /rates/changes/{id}` (admin): the change with its audit trail
+pub async fn get_change(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    change_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req, &jwt)?;
+    let change = fetch_change(&pool, *change_id).await?;
+
+    let events: Vec<RateModelChangeEvent> = sqlx::query_as::<_, EventRow>(
+        r#"
+        SELECT id, change_id, product_code, event, actor, details::TEXT AS details, created_at
+        FROM rate_model_change_events WHERE change_id = $1
+        ORDER BY id
+        "#
+    )
+    .bind(change.id)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .into_iter()
+    .map(RateModelChangeEvent::from)
+    .collect();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "change": change, "events": events })))
+}
+
+fn validate_reason(reason: Option<&str>) -> Result<Option<&str>, ServiceError> {
+    let reason = reason.map(str::trim).filter(|r| !r.is_empty());
+    if reason.is_some_and(|r| r.len() > MAX_REASON_LEN) {
+        return Err(ServiceError::ValidationError(format!("Reason must be at most {} characters", MAX_REASON_LEN)));
+    }
+    Ok(reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_reason() {
+        assert_eq!(validate_reason(None).unwrap(), None);
+        assert_eq!(validate_reason(Some("   ")).unwrap(), None);
+        assert_eq!(validate_reason(Some(" curve too steep ")).unwrap(), Some("curve too steep"));
+        assert!(validate_reason(Some(&"x".repeat(MAX_REASON_LEN + 1))).is_err());
+    }
+
+    #[test]
+    fn test_dual_approval_flag() {
+        std::env::set_var("RATE_CHANGE_DUAL_APPROVAL", "true");
+        assert!(dual_approval_required());
+        std::env::set_var("RATE_CHANGE_DUAL_APPROVAL", "off");
+        assert!(!dual_approval_required());
+        std::env::remove_var("RATE_CHANGE_DUAL_APPROVAL");
+        assert!(!dual_approval_required());
+    }
+}
@@ -1,19 +1,25 @@
 // core/interest-engine/src/main.rs
 // Interest Engine with Phase 6 Production Hardening (Minimal Edition)
 
-use actix_web::{web, App, HttpResponse, HttpServer, Responder, middleware};
+mod accruals;
+mod corrections;
+mod deposit_client;
+mod governance;
+mod models;
+mod outbox;
+mod postings;
+mod projections;
+mod rates;
+mod reserves;
+mod utilization;
+
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder, middleware};
 use actix_cors::Cors;
-use serde::{Deserialize, Serialize};
-use chrono::{DateTime, Utc};
-use sha2::{Sha256, Digest};
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use chrono::Utc;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
-use bsv_bank_common::{
-    init_logging, ServiceMetrics,
-    validate_paymail, // Import validators we actually use
-};
+use bsv_bank_common::auth::extract_bearer_token;
+use bsv_bank_common::{init_logging, publisher_from_env, Claims, JwtManager, ServiceMetrics, SERVICE_PERMISSION};
 use dotenv::dotenv;
 use prometheus::Registry;
 use std::time::SystemTime;
@@ -29,6 +35,12 @@ enum ServiceError {
     ValidationError(String),
     #[error("Database error: {0}")]
     DatabaseError(String),
+    #[error("Not found: {0}")]
+    NotFound(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
     #[error("Internal server error")]
     InternalError,
 }
@@ -48,6 +60,24 @@ impl actix_web::ResponseError for ServiceError {
                     "message": msg
                 }))
             }
+            ServiceError::NotFound(msg) => {
+                HttpResponse::NotFound().json(serde_json::json!({
+                    "error": "not_found",
+                    "message": msg
+                }))
+            }
+            ServiceError::Unauthorized(msg) => {
+                HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "unauthorized",
+                    "message": msg
+                }))
+            }
+            ServiceError::Forbidden(msg) => {
+                HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "forbidden",
+                    "message": msg
+                }))
+            }
             ServiceError::InternalError => {
                 HttpResponse::InternalServerError().json(serde_json::json!({
                     "error": "internal_error"
@@ -57,165 +87,53 @@ impl actix_web::ResponseError for ServiceError {
     }
 }
 
-// ============================================================================
-// DATA TYPES
-// ============================================================================
-
-#[derive(Debug, Clone, Serialize, Deserialize)]
-struct InterestRate {
-    timestamp: DateTime<Utc>,
-    utilization_rate: f64,
-    borrow_apy: f64,
-    supply_apy: f64,
-    total_deposits: u64,
-    total_borrowed: u64,
+/// Verify the bearer token on the request and return its claims
+fn bearer_claims(req: &HttpRequest, jwt: &JwtManager) -> Result<Claims, ServiceError> {
+    let header = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .ok_or_else(|| ServiceError::Unauthorized("Missing authorization header".to_string()))?;
+    let token = extract_bearer_token(header).map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+    jwt.verify_token(&token).map_err(|e| ServiceError::Unauthorized(e.to_string()))
 }
 
-struct AppState {
-    rates: Arc<Mutex<Vec<InterestRate>>>,
-    user_accruals: Arc<Mutex<HashMap<String, u64>>>,
-    db_pool: PgPool,
-    start_time: SystemTime,
+/// Require a bearer token with the `admin` permission, or a sibling service's token, for
+/// operations jobs and other services trigger; returns its claims
+fn require_admin_or_service(req: &HttpRequest, jwt: &JwtManager) -> Result<Claims, ServiceError> {
+    let claims = bearer_claims(req, jwt)?;
+    if !claims.has_permission(SERVICE_PERMISSION) {
+        return Err(ServiceError::Forbidden("Requires admin or service permission".to_string()));
+    }
+    Ok(claims)
 }
 
-#[derive(Debug, Deserialize)]
-struct DistributeQuery {
-    paymail: Option<String>, // Optional: distribute to specific user
+/// Require a bearer token with the `admin` permission; returns its claims
+fn require_admin(req: &HttpRequest, jwt: &JwtManager) -> Result<Claims, ServiceError> {
+    let claims = bearer_claims(req, jwt)?;
+    if !claims.has_permission("admin") {
+        return Err(ServiceError::Forbidden("Managing interest rates requires admin permission".to_string()));
+    }
+    Ok(claims)
 }
 
-// ============================================================================
-// BUSINESS LOGIC
-// ============================================================================
-
-fn calculate_rates(total_deposits: u64, total_borrowed: u64) -> (f64, f64) {
-    let utilization = if total_deposits == 0 {
-        0.0
-    } else {
-        total_borrowed as f64 / total_deposits as f64
-    };
-    
-    let base_rate = 0.02;
-    let optimal_util = 0.80;
-    
-    let borrow_apy = if utilization <= optimal_util {
-        base_rate + (utilization * 0.10)
-    } else {
-        base_rate + (optimal_util * 0.10) + ((utilization - optimal_util) * 1.00)
-    };
-    
-    let supply_apy = borrow_apy * utilization * 0.90; // 90% goes to suppliers
-    
-    (borrow_apy, supply_apy)
+/// Require a bearer token issued to `paymail` itself or with the `admin` permission, for
+/// reads of one user's interest; returns its claims
+fn require_paymail(req: &HttpRequest, jwt: &JwtManager, paymail: &str) -> Result<Claims, ServiceError> {
+    let claims = bearer_claims(req, jwt)?;
+    if claims.sub != paymail && !claims.has_permission("admin") {
+        return Err(ServiceError::Forbidden(format!("Token is not authorized for {}", paymail)));
+    }
+    Ok(claims)
 }
 
 // ============================================================================
-// HANDLERS
+// DATA TYPES
 // ============================================================================
 
-async fn get_current_rates(data: web::Data<AppState>) -> impl Responder {
-    // TODO: Query real totals from database instead of hardcoded values
-    let total_deposits = 10000000u64;
-    let total_borrowed = 7000000u64;
-    
-    let (borrow_apy, supply_apy) = calculate_rates(total_deposits, total_borrowed);
-    
-    let rate = InterestRate {
-        timestamp: Utc::now(),
-        utilization_rate: total_borrowed as f64 / total_deposits as f64,
-        borrow_apy,
-        supply_apy,
-        total_deposits,
-        total_borrowed,
-    };
-    
-    let mut rates = data.rates.lock().unwrap();
-    rates.push(rate.clone());
-    
-    // Create OP_RETURN commitment for BSV blockchain
-    let commitment_data = format!(
-        "RATE|{}|{}|{}",
-        rate.utilization_rate,
-        rate.borrow_apy,
-        rate.timestamp.timestamp()
-    );
-    let mut hasher = Sha256::new();
-    hasher.update(commitment_data.as_bytes());
-    let hash = hex::encode(hasher.finalize());
-    
-    tracing::info!("Interest rate commitment: 6a{}", hash);
-    
-    HttpResponse::Ok().json(rate)
-}
-
-async fn distribute_interest(
-    data: web::Data<AppState>,
-    query: web::Query<DistributeQuery>,
-) -> impl Responder {
-    // Phase 6: Validate paymail if provided (using if-let like deposit-service)
-    if let Some(ref paymail) = query.paymail {
-        if let Err(e) = validate_paymail(paymail) {
-            return HttpResponse::BadRequest().json(serde_json::json!({
-                "error": "Invalid paymail",
-                "error_code": "validation_error",
-                "message": e.to_string()
-            }));
-        }
-    }
-    
-    tracing::info!("Running interest distribution...");
-    
-    // TODO: Query actual user balances from deposit service database
-    // For now, simulate with test data
-    let users = vec![
-        ("test@handcash.io", 100000u64),
-        ("user@handcash.io", 500000u64),
-    ];
-    
-    let daily_rate = 0.07 / 365.0; // 7% APY divided by days
-    
-    let mut accruals = data.user_accruals.lock().unwrap();
-    
-    for (paymail, balance) in users {
-        // If specific paymail requested, only process that one
-        if let Some(ref filter_paymail) = query.paymail {
-            if paymail != filter_paymail.as_str() {
-                continue;
-            }
-        }
-        
-        let interest = (balance as f64 * daily_rate) as u64;
-        *accruals.entry(paymail.to_string()).or_insert(0) += interest;
-        tracing::info!("  {} earned {} satoshis", paymail, interest);
-    }
-    
-    HttpResponse::Ok().json(serde_json::json!({
-        "status": "success",
-        "distributed_at": Utc::now(),
-        "filter": query.paymail
-    }))
-}
-
-async fn get_accrued_interest(
-    data: web::Data<AppState>,
-    paymail: web::Path<String>,
-) -> impl Responder {
-    // Phase 6: Validate paymail (using if-let like deposit-service)
-    if let Err(e) = validate_paymail(&paymail) {
-        return HttpResponse::BadRequest().json(serde_json::json!({
-            "error": "Invalid paymail",
-            "error_code": "validation_error",
-            "message": e.to_string()
-        }));
-    }
-    
-    let accruals = data.user_accruals.lock().unwrap();
-    let interest = accruals.get(paymail.as_str()).copied().unwrap_or(0);
-    
-    HttpResponse::Ok().json(serde_json::json!({
-        "paymail": paymail.as_str(),
-        "accrued_interest_satoshis": interest,
-        "timestamp": Utc::now()
-    }))
+struct AppState {
+    db_pool: PgPool,
+    start_time: SystemTime,
 }
 
 // ============================================================================
@@ -242,22 +160,16 @@ async fn liveness_check() -> impl Responder {
 }
 
 async fn readiness_check(data: web::Data<AppState>) -> impl Responder {
-    // Check if we can acquire locks (basic readiness)
-    let rates_accessible = data.rates.try_lock().is_ok();
-    let accruals_accessible = data.user_accruals.try_lock().is_ok();
-    
-    // Check database connection
+    // Rates and accruals live in the database, so it is the only dependency
     let db_ok = sqlx::query("SELECT 1")
         .fetch_optional(&data.db_pool)
         .await
         .is_ok();
     
-    if rates_accessible && accruals_accessible && db_ok {
+    if db_ok {
         HttpResponse::Ok().json(serde_json::json!({
             "status": "ready",
             "checks": {
-                "rates_store": "ok",
-                "accruals_store": "ok",
                 "database": "ok"
             }
         }))
@@ -265,9 +177,7 @@ async fn readiness_check(data: web::Data<AppState>) -> impl Responder {
         HttpResponse::ServiceUnavailable().json(serde_json::json!({
             "status": "not_ready",
             "checks": {
-                "rates_store": if rates_accessible { "ok" } else { "locked" },
-                "accruals_store": if accruals_accessible { "ok" } else { "locked" },
-                "database": if db_ok { "ok" } else { "error" }
+                "database": "error"
             }
         }))
     }
@@ -329,13 +239,42 @@ async fn main() -> std::io::Result<()> {
     
     // Application state
     let app_state = web::Data::new(AppState {
-        rates: Arc::new(Mutex::new(Vec::new())),
-        user_accruals: Arc::new(Mutex::new(HashMap::new())),
         db_pool: db_pool.clone(),
         start_time: SystemTime::now(),
     });
     
     let registry_data = web::Data::new(registry);
+
+    // Apply approved rate model changes once they take effect
+    tokio::spawn(governance::start_rate_changes(db_pool.clone()));
+
+    // Snapshot product rates on a schedule; reads of /rates/current only serve them
+    tokio::spawn(rates::start_rate_snapshots(db_pool.clone()));
+
+    // Accrue interest for each completed UTC day
+    tokio::spawn(accruals::start_accruals(db_pool.clone()));
+    let utilization_cache = web::Data::new(utilization::UtilizationCache::from_env());
+
+    // Admin endpoints verify bearer tokens issued by the auth service
+    let jwt_secret = std::env::var("JWT_SECRET")
+        .unwrap_or_else(|_| {
+            println!("⚠️  JWT_SECRET not set, using development default");
+            "development-secret-change-in-production".to_string()
+        });
+    let jwt_manager = web::Data::new(JwtManager::new(jwt_secret));
+
+    // Post due accruals on the posting schedule; deposit-service credits them to balances
+    let deposit_client = deposit_client::DepositClient::from_env(jwt_manager.get_ref().clone());
+    tokio::spawn(postings::start_postings(db_pool.clone(), deposit_client));
+
+    // Publish rate updates and completed accrual runs for the other services
+    match publisher_from_env().await.expect("Failed to set up event bus") {
+        Some(publisher) => {
+            tracing::info!("Publishing interest events via {}", publisher.name());
+            tokio::spawn(outbox::start_outbox_worker(db_pool.clone(), publisher));
+        }
+        None => tracing::info!("EVENT_BUS not set; interest events are not published to a message bus"),
+    }
     
     println!("✅ Service ready on http://0.0.0.0:{}", port);
     println!("📋 Health: http://0.0.0.0:{}/health", port);
@@ -347,7 +286,7 @@ async fn main() -> std::io::Result<()> {
         let cors = Cors::default()
             .allowed_origin("http://localhost:3000")
             .allowed_origin("http://localhost:5173")
-            .allowed_methods(vec!["GET", "POST"])
+            .allowed_methods(vec!["GET", "POST", "PUT"])
             .allowed_headers(vec![
                 actix_web::http::header::AUTHORIZATION,
                 actix_web::http::header::CONTENT_TYPE,
@@ -365,7 +304,10 @@ async fn main() -> std::io::Result<()> {
                 .add(("Content-Security-Policy", "default-src 'self'"))
                 .add(("X-XSS-Protection", "1; mode=block"))
             )
+            .app_data(web::Data::new(db_pool.clone()))
             .app_data(app_state.clone())
+            .app_data(utilization_cache.clone())
+            .app_data(jwt_manager.clone())
             .app_data(registry_data.clone())
             // Health endpoints (no auth)
             .route("/health", web::get().to(health_check))
@@ -374,9 +316,26 @@ async fn main() -> std::io::Result<()> {
             // Metrics endpoint (no auth)
             .route("/metrics", web::get().to(metrics_handler))
             // Business endpoints
-            .route("/rates/current", web::get().to(get_current_rates))
-            .route("/interest/distribute", web::post().to(distribute_interest))
-            .route("/interest/{paymail}", web::get().to(get_accrued_interest))
+            .route("/rates/current", web::get().to(rates::get_current_rates))
+            .route("/rates/products", web::get().to(rates::list_product_rates))
+            .route("/rates/products/{product}", web::get().to(rates::get_product_rate))
+            .route("/rates/history", web::get().to(rates::get_rate_history))
+            .route("/rates/at", web::get().to(rates::get_rate_at))
+            .route("/rates/models", web::get().to(models::list_models))
+            .route("/rates/models/{product}", web::put().to(models::set_model))
+            .route("/rates/changes", web::get().to(governance::list_changes))
+            .route("/rates/changes", web::post().to(governance::propose_change))
+            .route("/rates/changes/{id}", web::get().to(governance::get_change))
+            .route("/rates/changes/{id}/approve", web::post().to(governance::approve_change))
+            .route("/rates/changes/{id}/reject", web::post().to(governance::reject_change))
+            .route("/interest/distribute", web::post().to(accruals::distribute_interest))
+            .route("/interest/project", web::post().to(projections::project_earnings))
+            .route("/interest/accruals/recompute", web::post().to(corrections::recompute_accruals))
+            .route("/interest/adjustments", web::get().to(corrections::list_adjustments))
+            .route("/interest/{paymail}", web::get().to(accruals::get_accrued_interest))
+            .route("/postings/{paymail}", web::get().to(postings::list_postings))
+            .route("/reserves", web::get().to(reserves::get_reserves))
+            .route("/reserves/accruals", web::get().to(reserves::list_reserve_accruals))
     })
     .bind(("0.0.0.0", port))?
     .run()
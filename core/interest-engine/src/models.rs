@@ -0,0 +1,474 @@
+// core/interest-engine/src/models.rs
+// Interest rate models: how a product's borrow and supply APY follow pool utilization
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use std::collections::HashMap;
+
+use crate::governance;
+use crate::{require_admin, ServiceError};
+
+/// Product whose model prices the lending pool, as served by `/rates/current`
+pub const POOL_PRODUCT: &str = "lending-pool";
+const MAX_APY: f64 = 10.0;
+const MAX_CODE_LEN: usize = 50;
+const MAX_NAME_LEN: usize = 100;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ModelRates {
+    pub borrow_apy: f64,
+    pub supply_apy: f64,
+}
+
+pub trait InterestRateModel: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Borrow and supply APY at `utilization` (0.0 to 1.0 and beyond)
+    fn rates(&self, utilization: f64) -> ModelRates;
+
+    /// Share of the interest borrowers pay at `utilization` that the bank keeps as reserves
+    /// rather than passing on to suppliers
+    fn reserve_factor(&self, utilization: f64) -> f64 {
+        let ModelRates { borrow_apy, supply_apy } = self.rates(utilization);
+        let paid = borrow_apy * utilization.max(0.0);
+        if paid <= 0.0 {
+            0.0
+        } else {
+            (1.0 - supply_apy / paid).clamp(0.0, 1.0)
+        }
+    }
+
+    /// Reject parameters that would produce negative or runaway rates
+    fn validate(&self) -> Result<(), String>;
+}
+
+/// Borrow APY rises gently up to the optimal utilization, then steeply beyond it.
+/// The bank keeps `reserve_factor` of the interest borrowers pay; suppliers get the rest.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KinkedCurve {
+    pub base_rate: f64,
+    pub optimal_utilization: f64,
+    /// Borrow APY added per unit of utilization below the kink
+    pub slope_low: f64,
+    /// Borrow APY added per unit of utilization above the kink
+    pub slope_high: f64,
+    pub reserve_factor: f64,
+}
+
+impl Default for KinkedCurve {
+    fn default() -> Self {
+        Self { base_rate: 0.02, optimal_utilization: 0.80, slope_low: 0.10, slope_high: 1.00, reserve_factor: 0.10 }
+    }
+}
+
+impl InterestRateModel for KinkedCurve {
+    fn name(&self) -> &'static str {
+        "kinked"
+    }
+
+    fn rates(&self, utilization: f64) -> ModelRates {
+        let utilization = utilization.max(0.0);
+        let borrow_apy = if utilization <= self.optimal_utilization {
+            self.base_rate + utilization * self.slope_low
+        } else {
+            self.base_rate
+                + self.optimal_utilization * self.slope_low
+                + (utilization - self.optimal_utilization) * self.slope_high
+        };
+        ModelRates { borrow_apy, supply_apy: borrow_apy * utilization * (1.0 - self.reserve_factor) }
+    }
+
+    fn reserve_factor(&self, _utilization: f64) -> f64 {
+        self.reserve_factor
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validate_apy("base_rate", self.base_rate)?;
+        validate_apy("slope_low", self.slope_low)?;
+        validate_apy("slope_high", self.slope_high)?;
+        if !(self.optimal_utilization > 0.0 && self.optimal_utilization <= 1.0) {
+            return Err("optimal_utilization must be above 0 and at most 1".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.reserve_factor) {
+            return Err("reserve_factor must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Rates fixed when the product is defined, whatever the utilization
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FixedRate {
+    pub borrow_apy: f64,
+    pub supply_apy: f64,
+}
+
+impl InterestRateModel for FixedRate {
+    fn name(&self) -> &'static str {
+        "fixed"
+    }
+
+    fn rates(&self, _utilization: f64) -> ModelRates {
+        ModelRates { borrow_apy: self.borrow_apy, supply_apy: self.supply_apy }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validate_apy("borrow_apy", self.borrow_apy)?;
+        validate_apy("supply_apy", self.supply_apy)
+    }
+}
+
+/// Rates set directly by governance; records who set them and when
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GovernanceRate {
+    pub borrow_apy: f64,
+    pub supply_apy: f64,
+    #[serde(default)]
+    pub set_by: Option<String>,
+    #[serde(default)]
+    pub set_at: Option<DateTime<Utc>>,
+}
+
+impl InterestRateModel for GovernanceRate {
+    fn name(&self) -> &'static str {
+        "governance"
+    }
+
+    fn rates(&self, _utilization: f64) -> ModelRates {
+        ModelRates { borrow_apy: self.borrow_apy, supply_apy: self.supply_apy }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        validate_apy("borrow_apy", self.borrow_apy)?;
+        validate_apy("supply_apy", self.supply_apy)
+    }
+}
+
+fn validate_apy(field: &str, value: f64) -> Result<(), String> {
+    if !value.is_finite() || !(0.0..=MAX_APY).contains(&value) {
+        return Err(format!("{} must be between 0 and {}", field, MAX_APY));
+    }
+    Ok(())
+}
+
+pub fn validate_code(code: &str) -> Result<(), ServiceError> {
+    let valid = !code.is_empty()
+        && code.len() <= MAX_CODE_LEN
+        && code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(ServiceError::ValidationError(format!(
+            "Product code must be 1-{} lowercase letters, digits, '-' or '_'",
+            MAX_CODE_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// A model with its parameters, as stored in `interest_products.rate_model`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "model", rename_all = "snake_case")]
+pub enum RateModelConfig {
+    Kinked(KinkedCurve),
+    Fixed(FixedRate),
+    Governance(GovernanceRate),
+}
+
+impl RateModelConfig {
+    pub fn model(&self) -> &dyn InterestRateModel {
+        match self {
+            Self::Kinked(curve) => curve,
+            Self::Fixed(rate) => rate,
+            Self::Governance(rate) => rate,
+        }
+    }
+
+    pub fn parse(product: &str, json: &str) -> Result<Self, sqlx::Error> {
+        serde_json::from_str(json).map_err(|e| {
+            sqlx::Error::Decode(format!("Invalid rate model for product {}: {}", product, e).into())
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ProductRateModel {
+    pub code: String,
+    pub name: String,
+    pub rate_model: RateModelConfig,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct ProductRow {
+    code: String,
+    name: String,
+    rate_model: String,
+    updated_at: DateTime<Utc>,
+}
+
+impl TryFrom<ProductRow> for ProductRateModel {
+    type Error = sqlx::Error;
+
+    fn try_from(row: ProductRow) -> Result<Self, sqlx::Error> {
+        Ok(Self {
+            rate_model: RateModelConfig::parse(&row.code, &row.rate_model)?,
+            code: row.code,
+            name: row.name,
+            updated_at: row.updated_at,
+        })
+    }
+}
+
+const PRODUCT_COLUMNS: &str = "code, name, rate_model::TEXT AS rate_model, updated_at";
+
+/// Every product's model, keyed by product code
+pub async fn load_all<'e, E: PgExecutor<'e>>(executor: E) -> Result<HashMap<String, RateModelConfig>, sqlx::Error> {
+    let rows = sqlx::query_as::<_, ProductRow>(&format!("SELECT {} FROM interest_products ORDER BY code", PRODUCT_COLUMNS))
+        .fetch_all(executor)
+        .await?;
+    rows.into_iter()
+        .map(|row| ProductRateModel::try_from(row).map(|p| (p.code, p.rate_model)))
+        .collect()
+}
+
+/// Every product's model as it stood at `at`: today's models with each change applied since
+/// rolled back to the model it replaced. Products created since are left out.
+pub async fn load_as_of(
+    conn: &mut sqlx::PgConnection,
+    at: DateTime<Utc>,
+) -> Result<HashMap<String, RateModelConfig>, sqlx::Error> {
+    let current = load_all(&mut *conn).await?;
+    // The first change applied after `at` replaced the model then in force
+    let replaced: Vec<(String, Option<String>)> = sqlx::query_as(
+        r#"
+        SELECT DISTINCT ON (product_code) product_code, previous_model::TEXT
+        FROM rate_model_changes
+        WHERE status = 'Applied' AND applied_at > $1
+        ORDER BY product_code, applied_at
+        "#
+    )
+    .bind(at)
+    .fetch_all(&mut *conn)
+    .await?;
+    rewind(current, replaced)
+}
+
+fn rewind(
+    mut models: HashMap<String, RateModelConfig>,
+    replaced: Vec<(String, Option<String>)>,
+) -> Result<HashMap<String, RateModelConfig>, sqlx::Error> {
+    for (code, previous) in replaced {
+        match previous {
+            Some(json) => {
+                let config = RateModelConfig::parse(&code, &json)?;
+                models.insert(code, config);
+            }
+            None => {
+                models.remove(&code);
+            }
+        }
+    }
+    Ok(models)
+}
+
+/// The model for `product`, else for `fallback`, with the code it was found under;
+/// None when neither product exists
+pub async fn resolve<'e, E: PgExecutor<'e>>(
+    executor: E,
+    product: &str,
+    fallback: Option<&str>,
+) -> Result<Option<(String, RateModelConfig)>, sqlx::Error> {
+    let row: Option<(String, String)> = sqlx::query_as(
+        r#"
+        SELECT code, rate_model::TEXT FROM interest_products
+        WHERE code = $1 OR code = $2
+        ORDER BY code = $1 DESC
+        LIMIT 1
+        "#
+    )
+    .bind(product)
+    .bind(fallback)
+    .fetch_optional(executor)
+    .await?;
+    row.map(|(code, json)| RateModelConfig::parse(&code, &json).map(|config| (code, config)))
+        .transpose()
+}
+
+pub fn validate_name(name: Option<&str>) -> Result<(), ServiceError> {
+    if name.is_some_and(|n| n.trim().is_empty() || n.len() > MAX_NAME_LEN) {
+        return Err(ServiceError::ValidationError(format!("Name must be 1-{} characters", MAX_NAME_LEN)));
+    }
+    Ok(())
+}
+
+/// Set `code`'s model, creating the product when `name` is given; None when the product
+/// does not exist and no name was given
+pub async fn store<'e, E: PgExecutor<'e>>(
+    executor: E,
+    code: &str,
+    name: Option<&str>,
+    rate_model: &RateModelConfig,
+) -> Result<Option<ProductRateModel>, sqlx::Error> {
+    let json = serde_json::to_string(rate_model)
+        .map_err(|e| sqlx::Error::Protocol(format!("Unserializable rate model: {}", e)))?;
+    let row = sqlx::query_as::<_, ProductRow>(&format!(
+        r#"
+        INSERT INTO interest_products (code, name, rate_model)
+        SELECT $1, COALESCE($2, $1), $3::JSONB
+        WHERE $2 IS NOT NULL OR EXISTS (SELECT 1 FROM interest_products WHERE code = $1)
+        ON CONFLICT (code) DO UPDATE
+        SET rate_model = EXCLUDED.rate_model,
+            name = COALESCE($2, interest_products.name),
+            updated_at = NOW()
+        RETURNING {}
+        "#,
+        PRODUCT_COLUMNS
+    ))
+    .bind(code)
+    .bind(name)
+    .bind(&json)
+    .fetch_optional(executor)
+    .await?;
+    row.map(ProductRateModel::try_from).transpose()
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct SetRateModelRequest {
+    /// Required when the product does not exist yet
+    pub name: Option<String>,
+    pub rate_model: RateModelConfig,
+}
+
+/// `GET /rates/models`
+pub async fn list_models(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
+    let rows = sqlx::query_as::<_, ProductRow>(&format!("SELECT {} FROM interest_products ORDER BY code", PRODUCT_COLUMNS))
+        .fetch_all(pool.get_ref())
+        .await
+        .map_err(db_error)?;
+    let products = rows
+        .into_iter()
+        .map(ProductRateModel::try_from)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(db_error)?;
+    Ok(HttpResponse::Ok().json(products))
+}
+
+/// `PUT /rates/models/{product}` (admin): select a product's model and parameters,
+/// creating the product if needed. Applies from the next rate snapshot and accrual, and is
+/// recorded in the rate change audit trail; refused while changes need a second approval.
+pub async fn set_model(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    product: web::Path<String>,
+    request: web::Json<SetRateModelRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let claims = require_admin(&http_req, &jwt)?;
+    if governance::dual_approval_required() {
+        return Err(ServiceError::Forbidden(
+            "Rate model changes need a second approval; propose them via POST /rates/changes".to_string(),
+        ));
+    }
+    validate_code(&product)?;
+    let request = request.into_inner();
+    validate_name(request.name.as_deref())?;
+    request.rate_model.model().validate().map_err(ServiceError::ValidationError)?;
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let change_id = governance::propose(
+        &mut db_tx,
+        &claims.sub,
+        &product,
+        request.name.as_deref().map(str::trim),
+        &request.rate_model,
+        Utc::now(),
+        false,
+    )
+    .await?;
+    let updated = governance::apply(&mut db_tx, change_id, &claims.sub).await?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(updated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_kinked_curve_matches_previous_constants() {
+        let curve = KinkedCurve::default();
+        let rates = curve.rates(0.7);
+        assert!((rates.borrow_apy - 0.09).abs() < 1e-9);
+        assert!((rates.supply_apy - 0.09 * 0.7 * 0.9).abs() < 1e-9);
+
+        // Above the kink the slope steepens to 100%
+        assert!((curve.rates(0.9).borrow_apy - 0.20).abs() < 1e-9);
+        assert_eq!(curve.rates(0.0), ModelRates { borrow_apy: 0.02, supply_apy: 0.0 });
+    }
+
+    #[test]
+    fn test_rewind_restores_replaced_models() {
+        let fixed = |apy: f64| RateModelConfig::Fixed(FixedRate { borrow_apy: apy, supply_apy: apy / 2.0 });
+        let current = HashMap::from([
+            ("demand".to_string(), fixed(0.08)),
+            ("savings".to_string(), fixed(0.05)),
+            ("promo".to_string(), fixed(0.12)),
+        ]);
+        let previous = serde_json::to_string(&fixed(0.06)).unwrap();
+        let rewound = rewind(current, vec![
+            ("demand".to_string(), Some(previous)),
+            // Created after the day in question
+            ("promo".to_string(), None),
+        ])
+        .unwrap();
+
+        assert_eq!(rewound.len(), 2);
+        assert_eq!(rewound["demand"], fixed(0.06));
+        assert_eq!(rewound["savings"], fixed(0.05));
+    }
+
+    #[test]
+    fn test_reserve_factor() {
+        // The kinked curve keeps its configured share whatever the utilization
+        assert_eq!(KinkedCurve::default().reserve_factor(0.7), 0.10);
+        // Fixed rates keep whatever suppliers are not paid: borrowers pay 10% on 50% utilization
+        let fixed = FixedRate { borrow_apy: 0.10, supply_apy: 0.04 };
+        assert!((fixed.reserve_factor(0.5) - 0.2).abs() < 1e-9);
+        assert_eq!(fixed.reserve_factor(0.0), 0.0);
+        // Paying suppliers more than borrowers pay keeps nothing
+        assert_eq!(FixedRate { borrow_apy: 0.01, supply_apy: 0.07 }.reserve_factor(0.5), 0.0);
+    }
+
+    #[test]
+    fn test_config_round_trips_through_json() {
+        let json = r#"{"model":"fixed","borrow_apy":0.05,"supply_apy":0.07}"#;
+        let config: RateModelConfig = serde_json::from_str(json).unwrap();
+        assert_eq!(config, RateModelConfig::Fixed(FixedRate { borrow_apy: 0.05, supply_apy: 0.07 }));
+        assert_eq!(config.model().rates(0.99).supply_apy, 0.07);
+
+        let governance: RateModelConfig =
+            serde_json::from_str(r#"{"model":"governance","borrow_apy":0.1,"supply_apy":0.04}"#).unwrap();
+        assert_eq!(governance.model().name(), "governance");
+        assert!(serde_json::from_str::<RateModelConfig>(r#"{"model":"oracle"}"#).is_err());
+    }
+
+    #[test]
+    fn test_validation() {
+        assert!(KinkedCurve::default().validate().is_ok());
+        assert!(KinkedCurve { optimal_utilization: 0.0, ..KinkedCurve::default() }.validate().is_err());
+        assert!(KinkedCurve { reserve_factor: 1.5, ..KinkedCurve::default() }.validate().is_err());
+        assert!(FixedRate { borrow_apy: -0.01, supply_apy: 0.0 }.validate().is_err());
+        assert!(FixedRate { borrow_apy: f64::NAN, supply_apy: 0.0 }.validate().is_err());
+    }
+}
@@ -0,0 +1,253 @@
+// core/interest-engine/src/outbox.rs
+// Transactional outbox: rate updates and completed accrual runs are stored with the state
+// change they describe, then published to the message bus for the other services
+
+use bsv_bank_common::{EventEnvelope, EventPublisher};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgExecutor, PgPool};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// `source` of every envelope this service publishes
+const SOURCE: &str = "interest-engine";
+/// Events claimed per worker pass
+const PUBLISH_BATCH: i64 = 100;
+/// How long claimed events are hidden from other workers
+const PUBLISH_LEASE_SECS: i64 = 60;
+const PUBLISH_POLL: Duration = Duration::from_secs(1);
+/// Published events are kept this long for replay and auditing
+const RETENTION_DAYS: i32 = 7;
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum InterestEvent {
+    /// A product's rates moved since its previous snapshot, or it has its first snapshot
+    RateUpdated {
+        product_code: String,
+        snapshot_id: i32,
+        utilization_rate: f64,
+        borrow_apy: f64,
+        supply_apy: f64,
+        previous_borrow_apy: Option<f64>,
+        previous_supply_apy: Option<f64>,
+        commitment_hash: Option<String>,
+        timestamp: DateTime<Utc>,
+    },
+    /// The accrual job finished a day for every balance
+    AccrualCompleted {
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+        accrual_count: i32,
+        accrued_satoshis: i64,
+        reserved_satoshis: i64,
+        timestamp: DateTime<Utc>,
+    },
+}
+
+impl InterestEvent {
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            Self::RateUpdated { .. } => "rate_updated",
+            Self::AccrualCompleted { .. } => "accrual_completed",
+        }
+    }
+
+    /// Ordering key: rate updates are ordered per product, accrual runs by their day
+    pub fn key(&self) -> String {
+        match self {
+            Self::RateUpdated { product_code, .. } => product_code.clone(),
+            Self::AccrualCompleted { period_start, .. } => period_start.date_naive().to_string(),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct PendingEvent {
+    id: Uuid,
+    seq: i64,
+    event_type: String,
+    event_key: String,
+    payload: String,
+    created_at: DateTime<Utc>,
+}
+
+/// Events are only stored when `EVENT_BUS` selects a bus to publish them to
+pub fn enabled() -> bool {
+    std::env::var("EVENT_BUS")
+        .map(|v| !v.is_empty() && v != "none")
+        .unwrap_or(false)
+}
+
+/// Store `event` for publication. Pass the transaction that makes the state change so the
+/// event exists exactly when the change does. A no-op when no event bus is configured.
+pub async fn record<'e, E: PgExecutor<'e>>(executor: E, event: &InterestEvent) -> Result<(), sqlx::Error> {
+    if !enabled() {
+        return Ok(());
+    }
+
+    let payload = serde_json::to_string(event)
+        .map_err(|e| sqlx::Error::Protocol(format!("Unserializable event: {}", e)))?;
+    sqlx::query("INSERT INTO interest_event_outbox (event_type, event_key, payload) VALUES ($1, $2, $3::JSONB)")
+        .bind(event.event_type())
+        .bind(event.key())
+        .bind(payload)
+        .execute(executor)
+        .await?;
+
+    Ok(())
+}
+
+/// Backoff before retrying a failed publish: 30s doubling per attempt, capped at an hour
+fn retry_delay(attempts: i32) -> Duration {
+    Duration::from_secs((30u64 << attempts.clamp(0, 7) as u32).min(3600))
+}
+
+// ============================================================================
+// PUBLISHER WORKER
+// ============================================================================
+
+/// Publishes stored events in order. At-least-once: an event published just before a
+/// crash is sent again, with the same envelope id.
+pub async fn start_outbox_worker(pool: PgPool, publisher: Box<dyn EventPublisher>) {
+    loop {
+        let published = match publish_due(&pool, publisher.as_ref()).await {
+            Ok(n) => n,
+            Err(e) => {
+                tracing::error!("Outbox publishing failed: {}", e);
+                0
+            }
+        };
+        if let Err(e) = purge_published(&pool).await {
+            tracing::error!("Failed to purge published outbox events: {}", e);
+        }
+        // Keep draining while there is a backlog
+        if published < PUBLISH_BATCH as usize {
+            tokio::time::sleep(PUBLISH_POLL).await;
+        }
+    }
+}
+
+async fn publish_due(pool: &PgPool, publisher: &dyn EventPublisher) -> Result<usize, sqlx::Error> {
+    let mut due = sqlx::query_as::<_, PendingEvent>(
+        r#"
+        UPDATE interest_event_outbox
+        SET next_attempt_at = NOW() + make_interval(secs => $2)
+        WHERE id IN (
+            SELECT id FROM interest_event_outbox
+            WHERE published_at IS NULL AND next_attempt_at <= NOW()
+            ORDER BY seq
+            LIMIT $1
+            FOR UPDATE SKIP LOCKED
+        )
+        RETURNING id, seq, event_type, event_key, payload::TEXT AS payload, created_at
+        "#
+    )
+    .bind(PUBLISH_BATCH)
+    .bind(PUBLISH_LEASE_SECS as f64)
+    .fetch_all(pool)
+    .await?;
+    due.sort_by_key(|event| event.seq);
+
+    let mut published = Vec::with_capacity(due.len());
+    let mut failure = None;
+    for event in &due {
+        let payload = match serde_json::from_str(&event.payload) {
+            Ok(payload) => payload,
+            Err(e) => {
+                failure = Some((event.id, format!("Unreadable payload: {}", e)));
+                break;
+            }
+        };
+        let envelope = EventEnvelope {
+            id: event.id,
+            source: SOURCE.to_string(),
+            event_type: event.event_type.clone(),
+            key: event.event_key.clone(),
+            occurred_at: event.created_at,
+            payload,
+        };
+        match publisher.publish(&envelope).await {
+            Ok(()) => published.push(event.id),
+            Err(e) => {
+                failure = Some((event.id, e.to_string()));
+                break;
+            }
+        }
+    }
+
+    sqlx::query("UPDATE interest_event_outbox SET published_at = NOW(), last_error = NULL WHERE id = ANY($1)")
+        .bind(&published)
+        .execute(pool)
+        .await?;
+
+    if let Some((failed_id, error)) = failure {
+        // The rest of the batch waits with the failed event so the bus sees them in order
+        let attempts: i32 = sqlx::query_scalar(
+            "UPDATE interest_event_outbox SET attempts = attempts + 1, last_error = $2 WHERE id = $1 RETURNING attempts"
+        )
+        .bind(failed_id)
+        .bind(&error)
+        .fetch_one(pool)
+        .await?;
+
+        let held: Vec<Uuid> = due.iter().map(|event| event.id).filter(|id| !published.contains(id)).collect();
+        sqlx::query(
+            "UPDATE interest_event_outbox SET next_attempt_at = NOW() + make_interval(secs => $2) WHERE id = ANY($1)"
+        )
+        .bind(&held)
+        .bind(retry_delay(attempts - 1).as_secs_f64())
+        .execute(pool)
+        .await?;
+
+        tracing::warn!("Publishing event {} via {} failed (attempt {}): {}", failed_id, publisher.name(), attempts, error);
+    }
+
+    Ok(published.len())
+}
+
+async fn purge_published(pool: &PgPool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        DELETE FROM interest_event_outbox
+        WHERE published_at IS NOT NULL AND published_at < NOW() - make_interval(days => $1)
+        "#
+    )
+    .bind(RETENTION_DAYS)
+    .execute(pool)
+    .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_event_routing() {
+        let day = Utc.with_ymd_and_hms(2025, 11, 26, 0, 0, 0).unwrap();
+        let accrual = InterestEvent::AccrualCompleted {
+            period_start: day,
+            period_end: day + chrono::Duration::days(1),
+            accrual_count: 3,
+            accrued_satoshis: 1_200,
+            reserved_satoshis: 40,
+            timestamp: day,
+        };
+        assert_eq!(accrual.event_type(), "accrual_completed");
+        assert_eq!(accrual.key(), "2025-11-26");
+
+        let payload = serde_json::to_value(&accrual).unwrap();
+        assert_eq!(payload["type"], "accrual_completed");
+        assert_eq!(payload["accrued_satoshis"], 1_200);
+    }
+
+    #[test]
+    fn test_retry_delay_is_capped() {
+        assert_eq!(retry_delay(0), Duration::from_secs(30));
+        assert_eq!(retry_delay(1), Duration::from_secs(60));
+        assert_eq!(retry_delay(20), Duration::from_secs(3600));
+    }
+}
@@ -0,0 +1,326 @@
+// core/interest-engine/src/postings.rs
+// Interest postings: accruals grouped per user on the posting schedule and pushed to deposit-service
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{interval_from_env, run_every, validate_paymail, JwtManager};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::accruals::day_start;
+use crate::deposit_client::DepositClient;
+use crate::{require_paymail, ServiceError};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+
+/// Pending postings pushed per run; the rest wait for the next run
+const PUSH_BATCH: i64 = 100;
+
+const POSTING_COLUMNS: &str = "id, paymail, amount_satoshis, accrual_count, period_start, period_end, \
+     status, transfer_id, push_attempts, last_push_error, created_at, settled_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PostingSchedule {
+    /// Accruals are posted once their UTC day has ended
+    Daily,
+    /// Accruals are posted once their calendar month (UTC) has ended
+    Monthly,
+}
+
+impl PostingSchedule {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "daily" => Some(Self::Daily),
+            "monthly" => Some(Self::Monthly),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Daily => "daily",
+            Self::Monthly => "monthly",
+        }
+    }
+
+    /// `INTEREST_POSTING_SCHEDULE` (daily | monthly), monthly when unset or unknown
+    pub fn from_env() -> Self {
+        std::env::var("INTEREST_POSTING_SCHEDULE")
+            .ok()
+            .and_then(|s| Self::parse(s.trim()))
+            .unwrap_or(Self::Monthly)
+    }
+
+    /// Accruals whose period ends by the cutoff are due for posting, so a user gets at
+    /// most one posting per period however often the job runs
+    pub fn cutoff(&self, now: DateTime<Utc>) -> DateTime<Utc> {
+        match self {
+            Self::Daily => day_start(now),
+            Self::Monthly => Utc
+                .with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+                .single()
+                .unwrap_or_else(|| day_start(now)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct InterestPosting {
+    pub id: Uuid,
+    pub paymail: String,
+    pub amount_satoshis: i64,
+    pub accrual_count: i32,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    /// Pending until deposit-service has credited it, then Settled
+    pub status: String,
+    pub transfer_id: Option<Uuid>,
+    pub push_attempts: i32,
+    pub last_push_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub settled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PostingQuery {
+    pub limit: Option<i64>,
+    /// Only postings created before this time; pass the last `created_at` of the previous page
+    pub before: Option<DateTime<Utc>>,
+}
+
+// ============================================================================
+// POSTING JOB
+// ============================================================================
+
+/// Write postings for due accruals and push pending ones to deposit-service every
+/// `INTEREST_POSTING_INTERVAL_SECS` (default hourly)
+pub async fn start_postings(pool: PgPool, deposits: DepositClient) {
+    let schedule = PostingSchedule::from_env();
+    tracing::info!("Posting accrued interest {}", schedule.as_str());
+    let period = interval_from_env("INTEREST_POSTING_INTERVAL_SECS", 3600);
+    run_every("interest-posting", period, move || {
+        let pool = pool.clone();
+        let deposits = deposits.clone();
+        async move {
+            let written = write_postings(&pool, schedule.cutoff(Utc::now())).await?;
+            if written > 0 {
+                tracing::info!("Wrote {} interest posting(s)", written);
+            }
+            push_pending(&pool, &deposits).await
+        }
+    })
+    .await
+}
+
+/// Group every user's unposted accruals up to the cutoff into a posting; returns the
+/// number of postings written
+async fn write_postings(pool: &PgPool, cutoff: DateTime<Utc>) -> Result<usize, sqlx::Error> {
+    let due: Vec<i32> = sqlx::query_scalar(
+        r#"
+        SELECT DISTINCT user_id FROM interest_accruals
+        WHERE NOT paid_out AND posting_id IS NULL AND period_end <= $1
+        "#
+    )
+    .bind(cutoff)
+    .fetch_all(pool)
+    .await?;
+
+    let mut written = 0;
+    for user_id in due {
+        if write_user_posting(pool, user_id, cutoff).await? {
+            written += 1;
+        }
+    }
+    Ok(written)
+}
+
+/// One user's posting. The accrual row locks keep a concurrent run from grouping them twice.
+async fn write_user_posting(pool: &PgPool, user_id: i32, cutoff: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+    let mut db_tx = pool.begin().await?;
+    let accruals: Vec<(i32, i64, DateTime<Utc>, DateTime<Utc>)> = sqlx::query_as(
+        r#"
+        SELECT id, amount_satoshis, period_start, period_end FROM interest_accruals
+        WHERE user_id = $1 AND NOT paid_out AND posting_id IS NULL AND period_end <= $2
+        FOR UPDATE SKIP LOCKED
+        "#
+    )
+    .bind(user_id)
+    .bind(cutoff)
+    .fetch_all(&mut *db_tx)
+    .await?;
+    if accruals.is_empty() {
+        return Ok(false);
+    }
+
+    let ids: Vec<i32> = accruals.iter().map(|a| a.0).collect();
+    let amount: i64 = accruals.iter().map(|a| a.1).sum();
+
+    // Accruals netting to zero are closed out without a posting; a negative net, from
+    // downward adjustments, is posted as a debit
+    if amount == 0 {
+        sqlx::query("UPDATE interest_accruals SET paid_out = true, paid_at = NOW() WHERE id = ANY($1)")
+            .bind(&ids)
+            .execute(&mut *db_tx)
+            .await?;
+        db_tx.commit().await?;
+        return Ok(false);
+    }
+
+    let posting_id = Uuid::new_v4();
+    let period_start = accruals.iter().map(|a| a.2).min().unwrap_or(cutoff);
+    let period_end = accruals.iter().map(|a| a.3).max().unwrap_or(cutoff);
+    sqlx::query(
+        r#"
+        INSERT INTO interest_postings
+            (id, user_id, paymail, amount_satoshis, accrual_count, period_start, period_end, status)
+        SELECT $1, u.id, u.paymail, $3, $4, $5, $6, 'Pending'
+        FROM users u WHERE u.id = $2
+        "#
+    )
+    .bind(posting_id)
+    .bind(user_id)
+    .bind(amount)
+    .bind(ids.len() as i32)
+    .bind(period_start)
+    .bind(period_end)
+    .execute(&mut *db_tx)
+    .await?;
+    sqlx::query("UPDATE interest_accruals SET posting_id = $2 WHERE id = ANY($1)")
+        .bind(&ids)
+        .bind(posting_id)
+        .execute(&mut *db_tx)
+        .await?;
+    db_tx.commit().await?;
+
+    tracing::info!(
+        "Posting {} of {} sat for user {} from {} accrual(s) through {}",
+        posting_id, amount, user_id, ids.len(), period_end
+    );
+    Ok(true)
+}
+
+/// Push pending postings to deposit-service, oldest first. A failed push is recorded and
+/// retried on the next run; deposit-service settles each posting at most once.
+async fn push_pending(pool: &PgPool, deposits: &DepositClient) -> Result<usize, sqlx::Error> {
+    let pending: Vec<Uuid> = sqlx::query_scalar(
+        "SELECT id FROM interest_postings WHERE status = 'Pending' ORDER BY created_at LIMIT $1"
+    )
+    .bind(PUSH_BATCH)
+    .fetch_all(pool)
+    .await?;
+
+    let mut settled = 0;
+    for posting_id in pending {
+        match deposits.settle_posting(posting_id).await {
+            Ok(()) => settled += 1,
+            Err(e) => {
+                tracing::warn!("Pushing interest posting {} to deposit-service failed: {}", posting_id, e);
+                sqlx::query(
+                    r#"
+                    UPDATE interest_postings
+                    SET push_attempts = push_attempts + 1, last_push_error = $2
+                    WHERE id = $1 AND status = 'Pending'
+                    "#
+                )
+                .bind(posting_id)
+                .bind(&e)
+                .execute(pool)
+                .await?;
+            }
+        }
+    }
+    Ok(settled)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `GET /postings/{paymail}`: the user's postings, pending and settled, newest first; the
+/// user's own token or an admin's
+pub async fn list_postings(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    paymail: web::Path<String>,
+    query: web::Query<PostingQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &jwt, &paymail)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ServiceError::ValidationError(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    let postings = sqlx::query_as::<_, InterestPosting>(&format!(
+        r#"
+        SELECT {} FROM interest_postings
+        WHERE paymail = $1 AND ($2::TIMESTAMPTZ IS NULL OR created_at < $2)
+        ORDER BY created_at DESC, id
+        LIMIT $3
+        "#,
+        POSTING_COLUMNS
+    ))
+    .bind(paymail.as_str())
+    .bind(query.before)
+    .bind(limit)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    let (pending, settled): (i64, i64) = sqlx::query_as(
+        r#"
+        SELECT COALESCE(SUM(amount_satoshis) FILTER (WHERE status = 'Pending'), 0)::BIGINT,
+               COALESCE(SUM(amount_satoshis) FILTER (WHERE status = 'Settled'), 0)::BIGINT
+        FROM interest_postings
+        WHERE paymail = $1
+        "#
+    )
+    .bind(paymail.as_str())
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "paymail": paymail.as_str(),
+        "schedule": PostingSchedule::from_env().as_str(),
+        "pending_satoshis": pending,
+        "settled_satoshis": settled,
+        "postings": postings
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_cutoff_is_start_of_day() {
+        let now = Utc.with_ymd_and_hms(2025, 11, 25, 14, 30, 0).unwrap();
+        assert_eq!(PostingSchedule::Daily.cutoff(now), Utc.with_ymd_and_hms(2025, 11, 25, 0, 0, 0).unwrap());
+        let midnight = Utc.with_ymd_and_hms(2025, 11, 26, 0, 0, 0).unwrap();
+        assert_eq!(PostingSchedule::Daily.cutoff(midnight), midnight);
+    }
+
+    #[test]
+    fn test_monthly_cutoff_is_start_of_month() {
+        let now = Utc.with_ymd_and_hms(2025, 11, 25, 14, 30, 0).unwrap();
+        assert_eq!(PostingSchedule::Monthly.cutoff(now), Utc.with_ymd_and_hms(2025, 11, 1, 0, 0, 0).unwrap());
+        let new_year = Utc.with_ymd_and_hms(2026, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(PostingSchedule::Monthly.cutoff(new_year), new_year);
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        for schedule in [PostingSchedule::Daily, PostingSchedule::Monthly] {
+            assert_eq!(PostingSchedule::parse(schedule.as_str()), Some(schedule));
+        }
+        assert_eq!(PostingSchedule::parse("weekly"), None);
+    }
+}
@@ -0,0 +1,185 @@
+// core/interest-engine/src/projections.rs
+// Earnings projections for a prospective deposit, priced the way the accrual job would price it
+
+use actix_web::{web, HttpResponse};
+use chrono::{Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::accruals::{Compounding, DEMAND_PRODUCT};
+use crate::models;
+use crate::rates::utilization;
+use crate::utilization::UtilizationCache;
+use crate::ServiceError;
+
+/// Longest horizon a projection covers, ten years
+const MAX_HORIZON_DAYS: i64 = 3650;
+
+/// Default and longest window the historical average rate is taken over
+const DEFAULT_HISTORY_DAYS: i64 = 30;
+const MAX_HISTORY_DAYS: i64 = 365;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ProjectionRequest {
+    pub principal_satoshis: i64,
+    /// Interest product or term deposit product; demand deposits when omitted
+    pub product: Option<String>,
+    pub horizon_days: i64,
+    /// Window for the historical average rate, in days (default 30)
+    pub history_days: Option<i64>,
+}
+
+/// Earnings on the principal at one APY
+#[derive(Debug, PartialEq, Serialize)]
+pub struct Projection {
+    /// Product supply APY plus any term boost
+    pub apy: f64,
+    pub earnings_satoshis: i64,
+    pub ending_balance_satoshis: i64,
+}
+
+/// Projected earnings over `days` at `apy`; posted interest is not reinvested under
+/// simple compounding
+pub fn project(principal: i64, compounding: Compounding, apy: f64, days: i64) -> Projection {
+    let earnings = compounding.interest(principal, 0, apy, days as f64);
+    Projection {
+        apy,
+        earnings_satoshis: earnings,
+        ending_balance_satoshis: principal.saturating_add(earnings),
+    }
+}
+
+fn validate(req: &ProjectionRequest) -> Result<i64, ServiceError> {
+    if req.principal_satoshis <= 0 {
+        return Err(ServiceError::ValidationError("principal_satoshis must be positive".to_string()));
+    }
+    if !(1..=MAX_HORIZON_DAYS).contains(&req.horizon_days) {
+        return Err(ServiceError::ValidationError(format!(
+            "horizon_days must be between 1 and {}",
+            MAX_HORIZON_DAYS
+        )));
+    }
+    let history_days = req.history_days.unwrap_or(DEFAULT_HISTORY_DAYS);
+    if !(1..=MAX_HISTORY_DAYS).contains(&history_days) {
+        return Err(ServiceError::ValidationError(format!(
+            "history_days must be between 1 and {}",
+            MAX_HISTORY_DAYS
+        )));
+    }
+    Ok(history_days)
+}
+
+/// `POST /interest/project`: earnings on a principal over a horizon at the product's
+/// current rate and at its average rate over the last `history_days`
+pub async fn project_earnings(
+    pool: web::Data<PgPool>,
+    cache: web::Data<UtilizationCache>,
+    req: web::Json<ProjectionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let history_days = validate(&req)?;
+    let product = req.product.as_deref().unwrap_or(DEMAND_PRODUCT);
+
+    // Products without a rate model of their own earn the demand rate, as in accruals
+    let (priced_by, config) = models::resolve(pool.get_ref(), product, Some(DEMAND_PRODUCT))
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::NotFound(format!("No rate model for product {}", product)))?;
+
+    let compounding: Option<String> = sqlx::query_scalar(
+        r#"
+        SELECT COALESCE(
+            (SELECT compounding FROM interest_products WHERE code = $1),
+            (SELECT compounding FROM interest_products WHERE code = $2)
+        )
+        "#
+    )
+    .bind(product)
+    .bind(DEMAND_PRODUCT)
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+    let compounding = compounding
+        .as_deref()
+        .and_then(Compounding::parse)
+        .unwrap_or(Compounding::Simple);
+
+    // Term deposit products add their boost on top of the supply rate
+    let boost_bps: Option<i32> = sqlx::query_scalar("SELECT apy_boost_bps FROM term_deposit_products WHERE code = $1")
+        .bind(product)
+        .fetch_optional(pool.get_ref())
+        .await
+        .map_err(db_error)?;
+    let boost = boost_bps.unwrap_or(0) as f64 / 10_000.0;
+
+    let totals = cache.totals(&pool).await.map_err(db_error)?;
+    let utilization_rate = utilization(totals.totals.total_deposits, totals.totals.total_borrowed);
+    let current_apy = config.model().rates(utilization_rate).supply_apy + boost;
+
+    let (average_apy, samples): (Option<f64>, i64) = sqlx::query_as(
+        r#"
+        SELECT AVG(supply_apy)::FLOAT8, COUNT(*)
+        FROM interest_rates
+        WHERE product_code = $1 AND created_at >= $2
+        "#
+    )
+    .bind(&priced_by)
+    .bind(Utc::now() - Duration::days(history_days))
+    .fetch_one(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    let principal = req.principal_satoshis;
+    let horizon = req.horizon_days;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "product": product,
+        "priced_by": priced_by,
+        "compounding": compounding.as_str(),
+        "principal_satoshis": principal,
+        "horizon_days": horizon,
+        "current": project(principal, compounding, current_apy, horizon),
+        // Null until the product has rate snapshots in the window
+        "historical_average": average_apy.map(|apy| serde_json::json!({
+            "window_days": history_days,
+            "snapshot_count": samples,
+            "projection": project(principal, compounding, apy + boost, horizon)
+        })),
+        "stale": totals.stale,
+        "timestamp": Utc::now()
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_earnings() {
+        let year = project(100_000_000, Compounding::Simple, 0.07, 365);
+        assert_eq!(year.earnings_satoshis, 7_000_000);
+        assert_eq!(year.ending_balance_satoshis, 107_000_000);
+
+        let daily = project(100_000_000, Compounding::Daily, 0.07, 365);
+        assert!(daily.earnings_satoshis > year.earnings_satoshis);
+        assert_eq!(project(100_000_000, Compounding::Daily, 0.0, 365).earnings_satoshis, 0);
+    }
+
+    #[test]
+    fn test_validate_request() {
+        let req = |principal, horizon, history| ProjectionRequest {
+            principal_satoshis: principal,
+            product: None,
+            horizon_days: horizon,
+            history_days: history,
+        };
+        assert_eq!(validate(&req(1_000, 30, None)).unwrap(), DEFAULT_HISTORY_DAYS);
+        assert_eq!(validate(&req(1_000, 30, Some(90))).unwrap(), 90);
+        assert!(validate(&req(0, 30, None)).is_err());
+        assert!(validate(&req(1_000, 0, None)).is_err());
+        assert!(validate(&req(1_000, MAX_HORIZON_DAYS + 1, None)).is_err());
+        assert!(validate(&req(1_000, 30, Some(0))).is_err());
+    }
+}
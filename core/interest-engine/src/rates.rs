@@ -0,0 +1,429 @@
+// core/interest-engine/src/rates.rs
+// Interest rate snapshots per product: computed from pool utilization on a schedule and kept in interest_rates
+
+use actix_web::{web, HttpResponse};
+use bsv_bank_common::{interval_from_env, run_every};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::{PgExecutor, PgPool};
+
+use crate::models::{self, InterestRateModel, ModelRates, RateModelConfig, POOL_PRODUCT};
+use crate::outbox::{self, InterestEvent};
+use crate::utilization::{PoolTotals, TotalsView, UtilizationCache};
+use crate::ServiceError;
+
+/// Default seconds between rate snapshots
+const DEFAULT_SNAPSHOT_SECS: u64 = 300;
+
+const RATE_COLUMNS: &str = "id, product_code, utilization_rate::FLOAT8 AS utilization_rate, borrow_apy::FLOAT8 AS borrow_apy, \
+    supply_apy::FLOAT8 AS supply_apy, total_deposits, total_borrowed, commitment_hash, created_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct InterestRate {
+    pub id: i32,
+    pub product_code: String,
+    pub utilization_rate: f64,
+    pub borrow_apy: f64,
+    pub supply_apy: f64,
+    pub total_deposits: i64,
+    pub total_borrowed: i64,
+    pub commitment_hash: Option<String>,
+    #[serde(rename = "timestamp")]
+    pub created_at: DateTime<Utc>,
+}
+
+/// Bucket width for `/rates/history`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Resolution {
+    Hour,
+    Day,
+}
+
+impl Resolution {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Hour => "hour",
+            Self::Day => "day",
+        }
+    }
+
+    /// Longest range served at this resolution, keeping a response to about 750 buckets
+    fn max_range(&self) -> Duration {
+        match self {
+            Self::Hour => Duration::days(31),
+            Self::Day => Duration::days(731),
+        }
+    }
+}
+
+/// Which product's rates to serve; `fallback` is used when `product` has no rate model
+#[derive(Debug, Deserialize)]
+pub struct ProductQuery {
+    pub product: Option<String>,
+    pub fallback: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct HistoryQuery {
+    pub product: Option<String>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub resolution: Option<Resolution>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AtQuery {
+    pub product: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Snapshots within one hour or day, averaged
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct RateBucket {
+    pub bucket_start: DateTime<Utc>,
+    pub snapshot_count: i64,
+    pub utilization_rate: f64,
+    pub borrow_apy: f64,
+    pub supply_apy: f64,
+    pub min_borrow_apy: f64,
+    pub max_borrow_apy: f64,
+    pub total_deposits: i64,
+    pub total_borrowed: i64,
+}
+
+/// History range: `to` defaults to now and `from` to the longest range before it
+pub fn history_range(
+    query: &HistoryQuery,
+    resolution: Resolution,
+    now: DateTime<Utc>,
+) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let to = query.to.unwrap_or(now);
+    let from = query.from.unwrap_or(to - resolution.max_range());
+    if from >= to {
+        return Err("from must be before to".to_string());
+    }
+    if to - from > resolution.max_range() {
+        return Err(format!(
+            "At {} resolution the range may span at most {} days",
+            resolution.as_str(),
+            resolution.max_range().num_days()
+        ));
+    }
+    Ok((from, to))
+}
+
+/// A product's rates at the current utilization, computed without storing a snapshot
+#[derive(Debug, Serialize)]
+pub struct ProductRate {
+    /// The product whose model priced this; the fallback when the requested one has none
+    pub product_code: String,
+    pub model: &'static str,
+    pub utilization_rate: f64,
+    pub borrow_apy: f64,
+    pub supply_apy: f64,
+    pub totals_observed_at: DateTime<Utc>,
+    pub stale: bool,
+}
+
+impl ProductRate {
+    fn new(product_code: String, config: &RateModelConfig, totals: &TotalsView) -> Self {
+        let utilization_rate = utilization(totals.totals.total_deposits, totals.totals.total_borrowed);
+        let ModelRates { borrow_apy, supply_apy } = config.model().rates(utilization_rate);
+        Self {
+            product_code,
+            model: config.model().name(),
+            utilization_rate,
+            borrow_apy,
+            supply_apy,
+            totals_observed_at: totals.totals.observed_at,
+            stale: totals.stale,
+        }
+    }
+}
+
+/// A snapshot with the age of the pool totals it was computed from
+#[derive(Debug, Serialize)]
+pub struct CurrentRate {
+    #[serde(flatten)]
+    pub rate: InterestRate,
+    pub totals_observed_at: DateTime<Utc>,
+    pub totals_age_seconds: i64,
+    /// The snapshot job has missed two runs, so the rates may be out of date
+    pub stale: bool,
+}
+
+pub fn utilization(total_deposits: i64, total_borrowed: i64) -> f64 {
+    if total_deposits <= 0 {
+        0.0
+    } else {
+        total_borrowed as f64 / total_deposits as f64
+    }
+}
+
+/// SHA-256 of the rate snapshot, published as an OP_RETURN commitment on BSV
+pub fn commitment_hash(product: &str, utilization_rate: f64, borrow_apy: f64, at: DateTime<Utc>) -> String {
+    let commitment_data = format!("RATE|{}|{}|{}|{}", product, utilization_rate, borrow_apy, at.timestamp());
+    let mut hasher = Sha256::new();
+    hasher.update(commitment_data.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Price the given pool totals with `product`'s model and store the snapshot
+pub async fn record<'e, E: PgExecutor<'e>>(
+    executor: E,
+    product: &str,
+    model: &dyn InterestRateModel,
+    total_deposits: i64,
+    total_borrowed: i64,
+) -> Result<InterestRate, sqlx::Error> {
+    let now = Utc::now();
+    let utilization_rate = utilization(total_deposits, total_borrowed);
+    let ModelRates { borrow_apy, supply_apy } = model.rates(utilization_rate);
+    let hash = commitment_hash(product, utilization_rate, borrow_apy, now);
+
+    let rate = sqlx::query_as::<_, InterestRate>(&format!(
+        r#"
+        INSERT INTO interest_rates
+            (product_code, utilization_rate, borrow_apy, supply_apy, total_deposits, total_borrowed,
+             commitment_hash, created_at)
+        VALUES ($1, $2::FLOAT8, $3::FLOAT8, $4::FLOAT8, $5, $6, $7, $8)
+        RETURNING {}
+        "#,
+        RATE_COLUMNS
+    ))
+    .bind(product)
+    .bind(utilization_rate)
+    .bind(borrow_apy)
+    .bind(supply_apy)
+    .bind(total_deposits)
+    .bind(total_borrowed)
+    .bind(&hash)
+    .bind(now)
+    .fetch_one(executor)
+    .await?;
+
+    tracing::info!("Interest rate commitment: 6a{}", hash);
+    Ok(rate)
+}
+
+/// Latest stored snapshot for `product`
+async fn latest<'e, E: PgExecutor<'e>>(executor: E, product: &str) -> Result<Option<InterestRate>, sqlx::Error> {
+    sqlx::query_as::<_, InterestRate>(&format!(
+        "SELECT {} FROM interest_rates WHERE product_code = $1 ORDER BY created_at DESC, id DESC LIMIT 1",
+        RATE_COLUMNS
+    ))
+    .bind(product)
+    .fetch_optional(executor)
+    .await
+}
+
+// ============================================================================
+// SNAPSHOT JOB
+// ============================================================================
+
+fn snapshot_interval() -> std::time::Duration {
+    interval_from_env("RATE_SNAPSHOT_INTERVAL_SECS", DEFAULT_SNAPSHOT_SECS)
+}
+
+/// Snapshot every product's rates every `RATE_SNAPSHOT_INTERVAL_SECS` (default 5 minutes)
+pub async fn start_rate_snapshots(pool: PgPool) {
+    run_every("rate-snapshots", snapshot_interval(), move || {
+        let pool = pool.clone();
+        async move { snapshot_all(&pool).await }
+    })
+    .await
+}
+
+/// Store a snapshot per product from freshly read pool totals; returns the number of
+/// products whose rates moved. A product's event is stored with its snapshot.
+async fn snapshot_all(pool: &PgPool) -> Result<usize, sqlx::Error> {
+    let totals = PoolTotals::query(pool).await?;
+    let mut db_tx = pool.begin().await?;
+    let rate_models = models::load_all(&mut *db_tx).await?;
+
+    let mut updated = 0;
+    for (product, config) in &rate_models {
+        let previous = latest(&mut *db_tx, product).await?;
+        let rate = record(&mut *db_tx, product, config.model(), totals.total_deposits, totals.total_borrowed).await?;
+        if previous.as_ref().is_some_and(|p| p.borrow_apy == rate.borrow_apy && p.supply_apy == rate.supply_apy) {
+            continue;
+        }
+
+        outbox::record(&mut *db_tx, &InterestEvent::RateUpdated {
+            product_code: rate.product_code.clone(),
+            snapshot_id: rate.id,
+            utilization_rate: rate.utilization_rate,
+            borrow_apy: rate.borrow_apy,
+            supply_apy: rate.supply_apy,
+            previous_borrow_apy: previous.as_ref().map(|p| p.borrow_apy),
+            previous_supply_apy: previous.as_ref().map(|p| p.supply_apy),
+            commitment_hash: rate.commitment_hash.clone(),
+            timestamp: rate.created_at,
+        })
+        .await?;
+        updated += 1;
+    }
+    db_tx.commit().await?;
+    Ok(updated)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// The model for the requested product (the lending pool by default)
+async fn resolve_model(pool: &PgPool, query: &ProductQuery) -> Result<(String, RateModelConfig), ServiceError> {
+    let product = query.product.as_deref().unwrap_or(POOL_PRODUCT);
+    models::resolve(pool, product, query.fallback.as_deref())
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::NotFound(format!("No rate model for product {}", product)))
+}
+
+/// `GET /rates/current?product=`: the product's latest snapshot. Read-only; snapshots
+/// are taken by the snapshot job.
+pub async fn get_current_rates(
+    pool: web::Data<PgPool>,
+    query: web::Query<ProductQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    let (product, _) = resolve_model(&pool, &query).await?;
+    let rate = latest(pool.get_ref(), &product)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::NotFound(format!("No rate snapshot for product {} yet", product)))?;
+
+    // Totals are read when the snapshot is taken; two missed snapshots make it stale
+    let age = (Utc::now() - rate.created_at).num_seconds().max(0);
+    let stale = age as u64 > 2 * snapshot_interval().as_secs();
+    Ok(HttpResponse::Ok().json(CurrentRate {
+        totals_observed_at: rate.created_at,
+        totals_age_seconds: age,
+        stale,
+        rate,
+    }))
+}
+
+/// `GET /rates/products`: every product's rates at the current utilization
+pub async fn list_product_rates(
+    pool: web::Data<PgPool>,
+    cache: web::Data<UtilizationCache>,
+) -> Result<HttpResponse, ServiceError> {
+    let totals = cache.totals(&pool).await.map_err(db_error)?;
+    let rates: Vec<ProductRate> = models::load_all(pool.get_ref())
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|(code, config)| ProductRate::new(code, &config, &totals))
+        .collect();
+    Ok(HttpResponse::Ok().json(rates))
+}
+
+/// `GET /rates/products/{product}?fallback=`: one product's rates, as deposit-service and
+/// lending-service look them up
+pub async fn get_product_rate(
+    pool: web::Data<PgPool>,
+    cache: web::Data<UtilizationCache>,
+    product: web::Path<String>,
+    query: web::Query<ProductQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    let query = ProductQuery { product: Some(product.into_inner()), fallback: query.into_inner().fallback };
+    let (code, config) = resolve_model(&pool, &query).await?;
+    let totals = cache.totals(&pool).await.map_err(db_error)?;
+    Ok(HttpResponse::Ok().json(ProductRate::new(code, &config, &totals)))
+}
+
+/// `GET /rates/history?from=&to=&resolution=hour|day`: snapshots in [from, to) averaged
+/// per UTC hour or day; empty buckets are omitted
+pub async fn get_rate_history(
+    pool: web::Data<PgPool>,
+    query: web::Query<HistoryQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    let resolution = query.resolution.unwrap_or(Resolution::Hour);
+    let (from, to) = history_range(&query, resolution, Utc::now()).map_err(ServiceError::ValidationError)?;
+
+    let buckets = sqlx::query_as::<_, RateBucket>(
+        r#"
+        SELECT date_trunc($1, created_at AT TIME ZONE 'UTC') AT TIME ZONE 'UTC' AS bucket_start,
+               COUNT(*) AS snapshot_count,
+               AVG(utilization_rate)::FLOAT8 AS utilization_rate,
+               AVG(borrow_apy)::FLOAT8 AS borrow_apy,
+               AVG(supply_apy)::FLOAT8 AS supply_apy,
+               MIN(borrow_apy)::FLOAT8 AS min_borrow_apy,
+               MAX(borrow_apy)::FLOAT8 AS max_borrow_apy,
+               AVG(total_deposits)::BIGINT AS total_deposits,
+               AVG(total_borrowed)::BIGINT AS total_borrowed
+        FROM interest_rates
+        WHERE product_code = $4 AND created_at >= $2 AND created_at < $3
+        GROUP BY 1
+        ORDER BY 1
+        "#
+    )
+    .bind(resolution.as_str())
+    .bind(from)
+    .bind(to)
+    .bind(query.product.as_deref().unwrap_or(POOL_PRODUCT))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "product": query.product.as_deref().unwrap_or(POOL_PRODUCT),
+        "from": from,
+        "to": to,
+        "resolution": resolution.as_str(),
+        "buckets": buckets
+    })))
+}
+
+/// `GET /rates/at?timestamp=`: the snapshot in force at `timestamp`, i.e. the last one
+/// taken at or before it
+pub async fn get_rate_at(
+    pool: web::Data<PgPool>,
+    query: web::Query<AtQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    let rate = sqlx::query_as::<_, InterestRate>(&format!(
+        "SELECT {} FROM interest_rates WHERE product_code = $2 AND created_at <= $1 ORDER BY created_at DESC, id DESC LIMIT 1",
+        RATE_COLUMNS
+    ))
+    .bind(query.timestamp)
+    .bind(query.product.as_deref().unwrap_or(POOL_PRODUCT))
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::NotFound(format!("No rate snapshot at or before {}", query.timestamp)))?;
+
+    Ok(HttpResponse::Ok().json(rate))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utilization() {
+        assert_eq!(utilization(10_000_000, 7_000_000), 0.7);
+        assert_eq!(utilization(0, 0), 0.0);
+        // Borrowing can briefly exceed deposits after withdrawals
+        assert_eq!(utilization(1_000, 1_500), 1.5);
+    }
+
+    #[test]
+    fn test_history_range() {
+        let now = Utc::now();
+        let query = HistoryQuery { product: None, from: None, to: None, resolution: None };
+        assert_eq!(history_range(&query, Resolution::Hour, now), Ok((now - Duration::days(31), now)));
+
+        let query = HistoryQuery { product: None, from: Some(now - Duration::days(90)), to: Some(now), resolution: None };
+        assert!(history_range(&query, Resolution::Hour, now).is_err());
+        assert!(history_range(&query, Resolution::Day, now).is_ok());
+
+        let query = HistoryQuery { product: None, from: Some(now), to: Some(now), resolution: None };
+        assert!(history_range(&query, Resolution::Day, now).is_err());
+    }
+}
@@ -0,0 +1,271 @@
+// core/interest-engine/src/reserves.rs
+// Bank reserves: the reserve factor's share of lending pool interest, accrued daily into the treasury
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{ledger, JwtManager};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::models::{self, RateModelConfig, POOL_PRODUCT};
+use crate::rates::utilization;
+use crate::utilization::{PoolTotals, UtilizationCache};
+use crate::{require_admin, ServiceError};
+
+/// Reserve share of a day's pool interest moved into the treasury
+pub const RESERVE_ACCRUAL: &str = "interest_reserve_accrual";
+
+/// Reserves the bank has kept from pool interest
+pub const TREASURY_RESERVE_ACCOUNT: &str = "treasury:reserves";
+
+/// Reserve share of pool interest not yet paid by borrowers; runs negative. Nothing settles
+/// it yet: lending-service passes borrower interest through to the funding lenders in full,
+/// so its balance is the reserve the bank has booked but not collected.
+pub const RESERVE_RECEIVABLE_ACCOUNT: &str = "reserve_receivable:lending_pool";
+
+const DEFAULT_LIMIT: i64 = 30;
+const MAX_LIMIT: i64 = 366;
+
+const RESERVE_COLUMNS: &str = "product_code, period_start, period_end, total_borrowed, \
+     utilization_rate::FLOAT8 AS utilization_rate, borrow_apy::FLOAT8 AS borrow_apy, \
+     reserve_factor::FLOAT8 AS reserve_factor, amount_satoshis, transfer_id, created_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ReserveAccrual {
+    pub product_code: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub total_borrowed: i64,
+    pub utilization_rate: f64,
+    pub borrow_apy: f64,
+    pub reserve_factor: f64,
+    pub amount_satoshis: i64,
+    pub transfer_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A product's reserve factor at the current utilization
+#[derive(Debug, Serialize)]
+pub struct ReserveConfig {
+    pub product_code: String,
+    pub model: &'static str,
+    pub reserve_factor: f64,
+    /// Only the lending pool's borrower interest is reserved; deposit products report the
+    /// spread their model implies
+    pub accrues_reserves: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReserveQuery {
+    pub limit: Option<i64>,
+    /// Only days starting before this time; pass the last `period_start` of the previous page
+    pub before: Option<DateTime<Utc>>,
+}
+
+/// A day's reserve on `total_borrowed` at `borrow_apy`, rounded down to whole satoshis
+pub fn daily_reserve(total_borrowed: i64, borrow_apy: f64, reserve_factor: f64) -> i64 {
+    (total_borrowed.max(0) as f64 * borrow_apy * reserve_factor / 365.0).floor() as i64
+}
+
+/// Reserve the pool model's share of a day's borrower interest, inside the accrual run's
+/// transaction. A day already reserved is left alone; returns the satoshis reserved.
+pub async fn accrue_reserve(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    rate_models: &HashMap<String, RateModelConfig>,
+    totals: &PoolTotals,
+    period_start: DateTime<Utc>,
+    period_end: DateTime<Utc>,
+) -> Result<i64, sqlx::Error> {
+    let Some(config) = rate_models.get(POOL_PRODUCT) else {
+        return Ok(0);
+    };
+    let utilization_rate = utilization(totals.total_deposits, totals.total_borrowed);
+    let model = config.model();
+    let borrow_apy = model.rates(utilization_rate).borrow_apy;
+    let reserve_factor = model.reserve_factor(utilization_rate);
+    let amount = daily_reserve(totals.total_borrowed, borrow_apy, reserve_factor);
+
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO interest_reserve_accruals
+            (product_code, period_start, period_end, total_borrowed, utilization_rate, borrow_apy,
+             reserve_factor, amount_satoshis)
+        VALUES ($1, $2, $3, $4, $5::FLOAT8, $6::FLOAT8, $7::FLOAT8, $8)
+        ON CONFLICT (product_code, period_start) DO NOTHING
+        "#
+    )
+    .bind(POOL_PRODUCT)
+    .bind(period_start)
+    .bind(period_end)
+    .bind(totals.total_borrowed)
+    .bind(utilization_rate)
+    .bind(borrow_apy)
+    .bind(reserve_factor)
+    .bind(amount)
+    .execute(&mut **db_tx)
+    .await?
+    .rows_affected();
+    if inserted == 0 {
+        return Ok(0);
+    }
+
+    let Some(transfer_id) =
+        ledger::transfer(db_tx, RESERVE_ACCRUAL, RESERVE_RECEIVABLE_ACCOUNT, TREASURY_RESERVE_ACCOUNT, amount, None).await?
+    else {
+        return Ok(0);
+    };
+    sqlx::query("UPDATE interest_reserve_accruals SET transfer_id = $3 WHERE product_code = $1 AND period_start = $2")
+        .bind(POOL_PRODUCT)
+        .bind(period_start)
+        .bind(transfer_id)
+        .execute(&mut **db_tx)
+        .await?;
+    Ok(amount)
+}
+
+/// Sum of every entry on `account`
+async fn balance<'e, E: PgExecutor<'e>>(executor: E, account: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(SUM(amount_satoshis), 0)::BIGINT FROM ledger_entries WHERE account = $1")
+        .bind(account)
+        .fetch_one(executor)
+        .await
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `GET /reserves` (admin): treasury reserve balance and each product's reserve factor.
+/// Reserve factors are set with the rest of a product's rate model, via `/rates/changes`.
+pub async fn get_reserves(
+    pool: web::Data<PgPool>,
+    cache: web::Data<UtilizationCache>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req, &jwt)?;
+
+    let treasury = balance(pool.get_ref(), TREASURY_RESERVE_ACCOUNT).await.map_err(db_error)?;
+    let receivable = balance(pool.get_ref(), RESERVE_RECEIVABLE_ACCOUNT).await.map_err(db_error)?;
+    let (days, last_period_end): (i64, Option<DateTime<Utc>>) =
+        sqlx::query_as("SELECT COUNT(*), MAX(period_end) FROM interest_reserve_accruals")
+            .fetch_one(pool.get_ref())
+            .await
+            .map_err(db_error)?;
+
+    let totals = cache.totals(&pool).await.map_err(db_error)?;
+    let utilization_rate = utilization(totals.totals.total_deposits, totals.totals.total_borrowed);
+    let mut config: Vec<ReserveConfig> = models::load_all(pool.get_ref())
+        .await
+        .map_err(db_error)?
+        .into_iter()
+        .map(|(product_code, rate_model)| ReserveConfig {
+            model: rate_model.model().name(),
+            reserve_factor: rate_model.model().reserve_factor(utilization_rate),
+            accrues_reserves: product_code == POOL_PRODUCT,
+            product_code,
+        })
+        .collect();
+    config.sort_by(|a, b| a.product_code.cmp(&b.product_code));
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "treasury_account": TREASURY_RESERVE_ACCOUNT,
+        "balance_satoshis": treasury,
+        "receivable_account": RESERVE_RECEIVABLE_ACCOUNT,
+        "receivable_satoshis": receivable,
+        "accrued_days": days,
+        "accrued_through": last_period_end,
+        "utilization_rate": utilization_rate,
+        "products": config,
+        "timestamp": Utc::now()
+    })))
+}
+
+/// `GET /reserves/accruals` (admin): daily reserve accruals, newest first
+pub async fn list_reserve_accruals(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    query: web::Query<ReserveQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req, &jwt)?;
+    let limit = query.limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(ServiceError::ValidationError(format!("limit must be between 1 and {}", MAX_LIMIT)));
+    }
+
+    let accruals = sqlx::query_as::<_, ReserveAccrual>(&format!(
+        r#"
+        SELECT {} FROM interest_reserve_accruals
+        WHERE $1::TIMESTAMPTZ IS NULL OR period_start < $1
+        ORDER BY period_start DESC, product_code
+        LIMIT $2
+        "#,
+        RESERVE_COLUMNS
+    ))
+    .bind(query.before)
+    .bind(limit)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({ "accruals": accruals })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_reserve() {
+        // 10% of a day's interest on 365 BTC borrowed at 10%
+        assert_eq!(daily_reserve(36_500_000_000, 0.10, 0.10), 1_000_000);
+        assert_eq!(daily_reserve(36_500_000_000, 0.10, 0.0), 0);
+        assert_eq!(daily_reserve(0, 0.10, 0.10), 0);
+        // Rounds down rather than reserving satoshis nobody paid
+        assert_eq!(daily_reserve(1_000, 0.10, 0.10), 0);
+    }
+
+    #[sqlx::test(migrations = "../../db/migrations")]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_reserves_book_against_an_open_receivable(pool: PgPool) {
+        let rate_models = HashMap::from([(POOL_PRODUCT.to_string(), RateModelConfig::Kinked(models::KinkedCurve::default()))]);
+        let totals = PoolTotals { total_deposits: 50_000_000_000, total_borrowed: 36_500_000_000, observed_at: Utc::now() };
+        let day = Utc::now() - chrono::Duration::days(2);
+        let model = rate_models[POOL_PRODUCT].model();
+        let u = utilization(totals.total_deposits, totals.total_borrowed);
+        let expected = daily_reserve(totals.total_borrowed, model.rates(u).borrow_apy, model.reserve_factor(u));
+        assert!(expected > 0);
+
+        let mut db_tx = pool.begin().await.unwrap();
+        assert_eq!(accrue_reserve(&mut db_tx, &rate_models, &totals, day, day + chrono::Duration::days(1)).await.unwrap(), expected);
+        // A day is reserved once
+        assert_eq!(accrue_reserve(&mut db_tx, &rate_models, &totals, day, day + chrono::Duration::days(1)).await.unwrap(), 0);
+        db_tx.commit().await.unwrap();
+
+        assert_eq!(balance(&pool, TREASURY_RESERVE_ACCOUNT).await.unwrap(), expected);
+        // Borrower interest reaches lenders in full, so no entry ever settles the receivable
+        assert_eq!(balance(&pool, RESERVE_RECEIVABLE_ACCOUNT).await.unwrap(), -expected);
+        let settling: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM ledger_entries WHERE account = $1 AND amount_satoshis > 0"
+        )
+        .bind(RESERVE_RECEIVABLE_ACCOUNT)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(settling, 0);
+
+        let transfer_id: Option<Uuid> = sqlx::query_scalar("SELECT transfer_id FROM interest_reserve_accruals WHERE period_start = $1")
+            .bind(day)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert!(transfer_id.is_some());
+    }
+}
@@ -0,0 +1,109 @@
+// core/interest-engine/src/utilization.rs
+// Pool utilization from confirmed deposits and outstanding loan principal, cached between reads
+
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use tokio::sync::Mutex;
+
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct PoolTotals {
+    pub total_deposits: i64,
+    pub total_borrowed: i64,
+    pub observed_at: DateTime<Utc>,
+}
+
+/// Totals as served, with how old they are. `stale` means a refresh was due but the
+/// database could not be read, so the last good totals were used instead.
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct TotalsView {
+    #[serde(flatten)]
+    pub totals: PoolTotals,
+    pub age_seconds: i64,
+    pub stale: bool,
+}
+
+impl PoolTotals {
+    /// Confirmed deposit principal (deposit-service) against principal still owed on
+    /// open loans (lending-service); both services share this database
+    pub async fn query(pool: &PgPool) -> Result<Self, sqlx::Error> {
+        let (total_deposits, total_borrowed): (i64, i64) = sqlx::query_as(
+            r#"
+            SELECT
+                COALESCE((SELECT SUM(amount_satoshis) FROM deposits
+                          WHERE status IN ('Confirmed', 'Available')), 0)::BIGINT,
+                COALESCE((SELECT SUM(principal_outstanding) FROM loans
+                          WHERE status IN ('Active', 'MarginCalled')), 0)::BIGINT
+            "#
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(Self { total_deposits, total_borrowed, observed_at: Utc::now() })
+    }
+
+    pub fn view(self, now: DateTime<Utc>, stale: bool) -> TotalsView {
+        TotalsView { totals: self, age_seconds: (now - self.observed_at).num_seconds().max(0), stale }
+    }
+}
+
+pub struct UtilizationCache {
+    last: Mutex<Option<PoolTotals>>,
+    ttl: Duration,
+}
+
+impl UtilizationCache {
+    /// Totals are re-queried once older than `UTILIZATION_CACHE_TTL_SECS` (default 60)
+    pub fn from_env() -> Self {
+        let ttl_secs = std::env::var("UTILIZATION_CACHE_TTL_SECS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(60);
+        Self { last: Mutex::new(None), ttl: Duration::seconds(ttl_secs) }
+    }
+
+    /// Cached totals while fresh, otherwise a new query. A failed refresh falls back to
+    /// the last good totals, marked stale; with nothing cached the error is returned.
+    pub async fn totals(&self, pool: &PgPool) -> Result<TotalsView, sqlx::Error> {
+        // Held across the query so concurrent readers wait for one refresh
+        let mut last = self.last.lock().await;
+        let now = Utc::now();
+        if let Some(totals) = *last {
+            if now - totals.observed_at < self.ttl {
+                return Ok(totals.view(now, false));
+            }
+        }
+
+        match PoolTotals::query(pool).await {
+            Ok(totals) => {
+                *last = Some(totals);
+                Ok(totals.view(now, false))
+            }
+            Err(e) => match *last {
+                Some(totals) => {
+                    tracing::warn!("Pool totals refresh failed, serving totals from {}: {}", totals.observed_at, e);
+                    Ok(totals.view(now, true))
+                }
+                None => Err(e),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_view_reports_age() {
+        let observed_at = Utc::now();
+        let totals = PoolTotals { total_deposits: 10_000_000, total_borrowed: 7_000_000, observed_at };
+
+        let view = totals.view(observed_at + Duration::seconds(90), true);
+        assert_eq!(view.age_seconds, 90);
+        assert!(view.stale);
+
+        // Clock skew never reports a negative age
+        assert_eq!(totals.view(observed_at - Duration::seconds(5), false).age_seconds, 0);
+    }
+}
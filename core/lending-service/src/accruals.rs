@@ -0,0 +1,276 @@
+// core/lending-service/src/accruals.rs
+// Interest postings: lenders are credited their share of loan interest as it accrues
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::{interval_from_env, run_every, validate_paymail};
+use chrono::{DateTime, Duration, DurationRound, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::events;
+use crate::repayments::{accrue, LoanBalance, BALANCE_COLUMNS};
+use crate::settlement;
+use crate::syndication::pro_rata;
+use crate::ServiceError;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(sqlx::FromRow)]
+struct PostableLoan {
+    #[sqlx(flatten)]
+    balance: LoanBalance,
+    interest_posted_through: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct InterestPosting {
+    pub id: Uuid,
+    pub loan_id: Uuid,
+    pub participation_id: Uuid,
+    pub lender_paymail: String,
+    pub accrued_from: DateTime<Utc>,
+    pub accrued_to: DateTime<Utc>,
+    pub amount_satoshis: i64,
+    pub transfer_id: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Postings run up to the start of the current UTC day, so each loan gets at most one
+/// posting per day however often the job runs
+pub fn posting_cutoff(now: DateTime<Utc>) -> DateTime<Utc> {
+    now.duration_trunc(Duration::days(1)).unwrap_or(now)
+}
+
+/// Interval still to post: from the last posting (or origination, where interest
+/// starts) to the cutoff, stopping at the due date where late fees take over
+pub fn posting_period(
+    posted_through: Option<DateTime<Utc>>,
+    created_at: DateTime<Utc>,
+    due_date: DateTime<Utc>,
+    cutoff: DateTime<Utc>,
+) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let from = posted_through.unwrap_or(created_at);
+    let to = cutoff.min(due_date);
+    (to > from).then_some((from, to))
+}
+
+// ============================================================================
+// POSTING JOB
+// ============================================================================
+
+/// Post accrued interest every `INTEREST_POSTING_INTERVAL_SECS` (default hourly)
+pub async fn start_interest_postings(pool: PgPool) {
+    let period = interval_from_env("INTEREST_POSTING_INTERVAL_SECS", 3600);
+    run_every("loan-interest-posting", period, move || {
+        let pool = pool.clone();
+        async move {
+            let posted = post_interest(&pool, Utc::now()).await?;
+            if posted > 0 {
+                tracing::info!("Posted accrued interest on {} loan(s)", posted);
+            }
+            Ok::<_, sqlx::Error>(())
+        }
+    })
+    .await
+}
+
+/// Post interest on every open loan behind the cutoff; returns the number of loans posted
+async fn post_interest(pool: &PgPool, now: DateTime<Utc>) -> Result<usize, sqlx::Error> {
+    let cutoff = posting_cutoff(now);
+    let due: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT id FROM loans
+        WHERE status IN ('Active', 'MarginCalled')
+          AND COALESCE(interest_posted_through, created_at) < LEAST(due_date, $1)
+        "#
+    )
+    .bind(cutoff)
+    .fetch_all(pool)
+    .await?;
+
+    let mut posted = 0;
+    for loan_id in due {
+        if post_loan(pool, loan_id, cutoff).await? {
+            posted += 1;
+        }
+    }
+    Ok(posted)
+}
+
+/// Credit each active participation its share of the interest accrued on one loan.
+/// The loan row lock orders this against repayments, and the unique posting per
+/// participation and period makes a rerun a no-op.
+async fn post_loan(pool: &PgPool, loan_id: Uuid, cutoff: DateTime<Utc>) -> Result<bool, sqlx::Error> {
+    let mut db_tx = pool.begin().await?;
+    let loan = sqlx::query_as::<_, PostableLoan>(&format!(
+        r#"
+        SELECT {}, interest_posted_through FROM loans
+        WHERE id = $1 AND status IN ('Active', 'MarginCalled')
+        FOR UPDATE SKIP LOCKED
+        "#,
+        BALANCE_COLUMNS
+    ))
+    .bind(loan_id)
+    .fetch_optional(&mut *db_tx)
+    .await?;
+    let Some(PostableLoan { balance: loan, interest_posted_through }) = loan else { return Ok(false) };
+    let Some((from, to)) = posting_period(interest_posted_through, loan.created_at, loan.due_date, cutoff) else {
+        return Ok(false);
+    };
+
+    let participations: Vec<(Uuid, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, lender_paymail, amount_satoshis FROM loan_participations
+        WHERE loan_id = $1 AND status = 'Active'
+        ORDER BY created_at, id
+        "#
+    )
+    .bind(loan.id)
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    let accrued = accrue(loan.principal_outstanding, loan.interest_rate_bps, from, to);
+    let shares: Vec<i64> = participations.iter().map(|(_, _, amount)| *amount).collect();
+    let parts = pro_rata(accrued, &shares);
+    for ((participation_id, lender, _), amount) in participations.iter().zip(parts) {
+        if amount <= 0 {
+            continue;
+        }
+        let posting_id: Option<Uuid> = sqlx::query_scalar(
+            r#"
+            INSERT INTO loan_interest_postings
+                (loan_id, participation_id, lender_paymail, accrued_from, accrued_to, amount_satoshis)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (participation_id, accrued_to) DO NOTHING
+            RETURNING id
+            "#
+        )
+        .bind(loan.id)
+        .bind(participation_id)
+        .bind(lender)
+        .bind(from)
+        .bind(to)
+        .bind(amount)
+        .fetch_optional(&mut *db_tx)
+        .await?;
+        let Some(posting_id) = posting_id else { continue };
+
+        let receivable = settlement::interest_receivable_account(*participation_id);
+        let transfer_id = settlement::transfer(&mut db_tx, settlement::INTEREST_ACCRUAL, &receivable, lender, amount, Some(loan.id))
+            .await?;
+        sqlx::query("UPDATE loan_interest_postings SET transfer_id = $2 WHERE id = $1")
+            .bind(posting_id)
+            .bind(transfer_id)
+            .execute(&mut *db_tx)
+            .await?;
+        sqlx::query("UPDATE loan_participations SET interest_received = interest_received + $2 WHERE id = $1")
+            .bind(participation_id)
+            .bind(amount)
+            .execute(&mut *db_tx)
+            .await?;
+    }
+
+    sqlx::query("UPDATE loans SET interest_posted_through = $2 WHERE id = $1")
+        .bind(loan.id)
+        .bind(to)
+        .execute(&mut *db_tx)
+        .await?;
+    events::record(&mut *db_tx, loan.id, events::INTEREST_POSTED, events::ACTOR_SYSTEM, Some(accrued), serde_json::json!({
+        "from": from,
+        "to": to,
+        "lenders": participations.len()
+    }))
+    .await?;
+    db_tx.commit().await?;
+    Ok(true)
+}
+
+/// Interest up to `paid_at` was paid by a repayment; stop postings from crediting that
+/// period again. Called with the loan row locked.
+pub async fn mark_paid_through(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan_id: Uuid,
+    paid_at: DateTime<Utc>,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        UPDATE loans
+        SET interest_posted_through = LEAST(due_date, GREATEST(COALESCE(interest_posted_through, created_at), $2))
+        WHERE id = $1
+        "#
+    )
+    .bind(loan_id)
+    .bind(paid_at)
+    .execute(&mut **db_tx)
+    .await?;
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `GET /loans/interest-postings/{paymail}`: interest credited to a lender, newest first
+pub async fn get_lender_postings(
+    pool: web::Data<PgPool>,
+    paymail: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    let postings = sqlx::query_as::<_, InterestPosting>(
+        r#"
+        SELECT id, loan_id, participation_id, lender_paymail, accrued_from, accrued_to,
+               amount_satoshis, transfer_id, created_at
+        FROM loan_interest_postings
+        WHERE lender_paymail = $1
+        ORDER BY accrued_to DESC, loan_id
+        LIMIT 500
+        "#
+    )
+    .bind(paymail.as_str())
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+    let total: i64 = postings.iter().map(|p| p.amount_satoshis).sum();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "lender_paymail": paymail.as_str(),
+        "total_posted_satoshis": total,
+        "postings": postings
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(secs: i64) -> DateTime<Utc> {
+        DateTime::<Utc>::from_timestamp(secs, 0).unwrap()
+    }
+
+    #[test]
+    fn test_cutoff_is_start_of_day() {
+        let now = at(1_700_000_000);
+        let cutoff = posting_cutoff(now);
+        assert!(cutoff <= now && now - cutoff < Duration::days(1));
+        assert_eq!(posting_cutoff(cutoff), cutoff);
+    }
+
+    #[test]
+    fn test_posting_period() {
+        let created = at(1_700_000_000);
+        let due = created + Duration::days(30);
+        let cutoff = posting_cutoff(created + Duration::days(3));
+
+        assert_eq!(posting_period(None, created, due, cutoff), Some((created, cutoff)));
+        // Already posted through the cutoff
+        assert_eq!(posting_period(Some(cutoff), created, due, cutoff), None);
+        // Interest stops at the due date
+        let late = posting_cutoff(due + Duration::days(5));
+        assert_eq!(posting_period(Some(cutoff), created, due, late), Some((cutoff, due)));
+        assert_eq!(posting_period(Some(due), created, due, late), None);
+    }
+}
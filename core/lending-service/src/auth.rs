@@ -0,0 +1,31 @@
+// core/lending-service/src/auth.rs
+// Which lending routes need a bearer token. Common's AuthMiddleware verifies tokens and
+// `require_paymail` checks their subject against the paymail a handler acts for.
+
+use actix_web::http::Method;
+
+pub use bsv_bank_common::require_paymail;
+
+/// State-changing routes anyone may call: a quote reads prices and moves nothing
+const PUBLIC_POST_PATHS: [&str; 1] = ["/loans/quote"];
+
+/// Reads are public; everything else but the listed routes needs a token
+pub fn is_public_route(method: &Method, path: &str) -> bool {
+    if method == Method::GET || method == Method::HEAD || method == Method::OPTIONS {
+        return true;
+    }
+    PUBLIC_POST_PATHS.contains(&path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_and_quotes_are_public() {
+        assert!(is_public_route(&Method::GET, "/loans/available"));
+        assert!(is_public_route(&Method::POST, "/loans/quote"));
+        assert!(!is_public_route(&Method::POST, "/loans/1/repay"));
+        assert!(!is_public_route(&Method::DELETE, "/loans/1"));
+    }
+}
@@ -0,0 +1,490 @@
+// core/lending-service/src/collateral.rs
+// Collateral top-ups and partial releases on open loans, moved through escrow when configured
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{validate_address, validate_amount, validate_paymail};
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::auth::require_paymail;
+use crate::events;
+use crate::idempotency::Idempotency;
+use crate::ltv::{self, LtvBand};
+use crate::margin::{self, MarginPolicy};
+use crate::repayments;
+use crate::settlement;
+use crate::{ServiceError, MIN_COLLATERAL_RATIO};
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+// ============================================================================
+// ESCROW
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct MonitoredTransaction {
+    to_address: Option<String>,
+    amount_satoshis: i64,
+    status: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct BuiltTransaction {
+    txid: String,
+    tx_hex: String,
+}
+
+/// On-chain side of collateral changes. Without `COLLATERAL_ESCROW_ADDRESS` collateral
+/// is tracked in the database only and top-ups and releases are not checked on chain.
+#[derive(Clone)]
+pub struct Escrow {
+    client: reqwest::Client,
+    address: Option<String>,
+    builder_url: String,
+    monitor_url: String,
+}
+
+impl Escrow {
+    /// `COLLATERAL_ESCROW_ADDRESS`, `TRANSACTION_BUILDER_URL` and `BLOCKCHAIN_MONITOR_URL`
+    pub fn from_env() -> Self {
+        let escrow = Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            address: std::env::var("COLLATERAL_ESCROW_ADDRESS").ok().filter(|a| !a.is_empty()),
+            builder_url: std::env::var("TRANSACTION_BUILDER_URL")
+                .unwrap_or_else(|_| "http://localhost:8085".to_string()),
+            monitor_url: std::env::var("BLOCKCHAIN_MONITOR_URL")
+                .unwrap_or_else(|_| "http://localhost:8084".to_string()),
+        };
+        if escrow.address.is_none() {
+            tracing::warn!("COLLATERAL_ESCROW_ADDRESS not set, collateral changes are not moved on chain");
+        }
+        escrow
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.address.is_some()
+    }
+
+    /// Check with blockchain-monitor that `txid` paid at least `amount` into escrow
+    async fn verify_deposit(&self, txid: &str, amount: i64) -> Result<(), ServiceError> {
+        let Some(escrow) = &self.address else { return Ok(()) };
+        let response = self.client
+            .get(format!("{}/tx/{}", self.monitor_url, txid))
+            .send()
+            .await
+            .map_err(|e| ServiceError::BusinessError(format!("Could not verify collateral transaction: {}", e)))?;
+        if !response.status().is_success() {
+            return Err(ServiceError::BusinessError(format!(
+                "Collateral transaction {} not found (status {})",
+                txid,
+                response.status()
+            )));
+        }
+        let tx: MonitoredTransaction = response
+            .json()
+            .await
+            .map_err(|e| ServiceError::BusinessError(format!("Could not verify collateral transaction: {}", e)))?;
+
+        if tx.status == "failed" || tx.to_address.as_deref() != Some(escrow.as_str()) {
+            return Err(ServiceError::BusinessError(format!("Transaction {} does not pay the collateral escrow", txid)));
+        }
+        if tx.amount_satoshis < amount {
+            return Err(ServiceError::BusinessError(format!(
+                "Transaction {} pays {} satoshis into escrow, less than the {} added",
+                txid, tx.amount_satoshis, amount
+            )));
+        }
+        Ok(())
+    }
+
+    /// Build the unsigned transaction returning `amount` from escrow to `to_address`
+    async fn build_release(
+        &self,
+        collateral_txid: Option<&str>,
+        collateral: i64,
+        amount: i64,
+        to_address: &str,
+    ) -> Result<BuiltTransaction, ServiceError> {
+        let Some(escrow) = &self.address else {
+            return Err(ServiceError::BusinessError("Collateral escrow is not configured".to_string()));
+        };
+        let mut request = serde_json::json!({
+            "from_address": escrow,
+            "to_address": to_address,
+            "amount_satoshis": amount,
+        });
+        if let Some(txid) = collateral_txid {
+            // Collateral is locked in output 0 of its escrow transaction
+            request["utxos"] = serde_json::json!([{ "txid": txid, "vout": 0, "satoshis": collateral }]);
+        }
+
+        let build_error = |e: String| ServiceError::BusinessError(format!("Could not build release transaction: {}", e));
+        let response = self.client
+            .post(format!("{}/tx/build/p2pkh", self.builder_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| build_error(e.to_string()))?;
+        if !response.status().is_success() {
+            return Err(build_error(format!("status {}", response.status())));
+        }
+        response.json().await.map_err(|e| build_error(e.to_string()))
+    }
+}
+
+/// Collateral that can be released while keeping the origination requirement of
+/// `MIN_COLLATERAL_RATIO` times the current debt
+pub fn releasable(collateral: i64, debt: i64) -> i64 {
+    let required = (debt as f64 * MIN_COLLATERAL_RATIO).ceil() as i64;
+    (collateral - required).max(0)
+}
+
+/// Where released collateral goes: the address registered at origination, which a request
+/// may repeat but not change. Loans originated without one use the requested address.
+fn return_address(registered: Option<String>, requested: Option<String>) -> Result<Option<String>, ServiceError> {
+    match (registered, requested) {
+        (Some(registered), Some(requested)) if registered != requested => Err(ServiceError::BusinessError(format!(
+            "Collateral can only be returned to {}, the address registered at origination",
+            registered
+        ))),
+        (Some(registered), _) => Ok(Some(registered)),
+        (None, requested) => Ok(requested),
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AddCollateralRequest {
+    pub borrower_paymail: String,
+    pub amount_satoshis: i64,
+    /// Transaction that moved the extra collateral into escrow; required when escrow is enabled
+    pub txid: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReleaseCollateralRequest {
+    pub borrower_paymail: String,
+    pub amount_satoshis: i64,
+    /// Where the released collateral is sent; defaults to, and must match, the return address
+    /// registered at origination. Required when escrow is enabled and none was registered.
+    pub to_address: Option<String>,
+}
+
+/// `POST /loans/{id}/collateral/add`: top up collateral, curing an LTV margin call
+pub async fn add_collateral(
+    pool: web::Data<sqlx::PgPool>,
+    policy: web::Data<MarginPolicy>,
+    escrow: web::Data<Escrow>,
+    http_req: HttpRequest,
+    loan_id: web::Path<Uuid>,
+    request: web::Json<AddCollateralRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.borrower_paymail)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_amount(request.amount_satoshis)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    if let Some(txid) = &request.txid {
+        if txid.len() != 64 || !txid.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ServiceError::ValidationError("txid must be 64 hex characters".to_string()));
+        }
+    } else if escrow.enabled() {
+        return Err(ServiceError::ValidationError("txid of the escrow deposit is required".to_string()));
+    }
+    let idempotency = Idempotency::from_request(&http_req, "collateral_add", &*request)?;
+
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let loan = repayments::load_balance(&mut *db_tx, *loan_id, true).await?;
+    require_paymail(&http_req, &loan.borrower_paymail)?;
+    if let Some(response) = idempotency.replay(&mut db_tx, loan.id).await? {
+        return Ok(response);
+    }
+
+    if loan.borrower_paymail != request.borrower_paymail {
+        return Err(ServiceError::BusinessError("Only the borrower can add collateral".to_string()));
+    }
+    if !loan.is_open() {
+        return Err(ServiceError::BusinessError(format!("Loan is not active (status: {})", loan.status)));
+    }
+    if let Some(txid) = &request.txid {
+        let used: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM loan_collateral_topups WHERE txid = $1)")
+            .bind(txid)
+            .fetch_one(&mut *db_tx)
+            .await
+            .map_err(db_error)?;
+        if used {
+            return Err(ServiceError::BusinessError(format!("Transaction {} was already applied as collateral", txid)));
+        }
+        escrow.verify_deposit(txid, request.amount_satoshis).await?;
+    }
+
+    let reason: Option<String> = sqlx::query_scalar("SELECT margin_call_reason FROM loans WHERE id = $1")
+        .bind(loan.id)
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+
+    // Without escrow the top-up is held out of the borrower's ledger balance
+    if !escrow.enabled() {
+        settlement::ensure_available(&mut db_tx, &loan.borrower_paymail, request.amount_satoshis).await?;
+    }
+
+    let collateral = loan.collateral_satoshis + request.amount_satoshis;
+    sqlx::query("UPDATE loans SET collateral_satoshis = $2 WHERE id = $1")
+        .bind(loan.id)
+        .bind(collateral)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO loan_collateral_topups (loan_id, amount_satoshis, txid, collateral_after)
+        VALUES ($1, $2, $3, $4)
+        "#
+    )
+    .bind(loan.id)
+    .bind(request.amount_satoshis)
+    .bind(&request.txid)
+    .bind(collateral)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    events::record(&mut *db_tx, loan.id, events::COLLATERAL_ADDED, &loan.borrower_paymail, Some(request.amount_satoshis), serde_json::json!({
+        "txid": request.txid,
+        "collateral_after": collateral
+    }))
+    .await
+    .map_err(db_error)?;
+
+    // Debt and collateral are both BSV, so the price cancels out of the ratio
+    let debt = loan.principal_outstanding + loan.interest_to(now);
+    let ltv = ltv::ltv_bps(debt, 1.0, collateral, 1.0);
+    let band = policy.thresholds.band(ltv);
+    let cured = loan.status == "MarginCalled" && margin::is_cured(reason.as_deref(), band);
+    if cured {
+        policy.cure(&mut db_tx, &loan, ltv, &loan.borrower_paymail).await.map_err(db_error)?;
+    }
+    let response = idempotency.respond(&mut db_tx, loan.id, serde_json::json!({
+        "status": "success",
+        "loan_id": loan.id,
+        "loan_status": if cured { "Active" } else { loan.status.as_str() },
+        "collateral_satoshis": collateral,
+        "ltv_bps": ltv,
+        "ltv_band": band.as_str(),
+        "margin_call_cured": cured
+    })).await?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Loan {} collateral topped up by {} to {}", loan.id, request.amount_satoshis, collateral);
+
+    Ok(response)
+}
+
+/// `POST /loans/{id}/collateral/release`: return collateral above the origination
+/// requirement to the borrower. Margin-called loans cannot release collateral.
+pub async fn release_collateral(
+    pool: web::Data<sqlx::PgPool>,
+    policy: web::Data<MarginPolicy>,
+    escrow: web::Data<Escrow>,
+    http_req: HttpRequest,
+    loan_id: web::Path<Uuid>,
+    request: web::Json<ReleaseCollateralRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.borrower_paymail)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_amount(request.amount_satoshis)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    if let Some(address) = &request.to_address {
+        validate_address(address).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+    let idempotency = Idempotency::from_request(&http_req, "collateral_release", &*request)?;
+
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let loan = repayments::load_balance(&mut *db_tx, *loan_id, true).await?;
+    require_paymail(&http_req, &loan.borrower_paymail)?;
+    if let Some(response) = idempotency.replay(&mut db_tx, loan.id).await? {
+        return Ok(response);
+    }
+
+    if loan.borrower_paymail != request.borrower_paymail {
+        return Err(ServiceError::BusinessError("Only the borrower can release collateral".to_string()));
+    }
+    let (collateral_txid, registered): (Option<String>, Option<String>) =
+        sqlx::query_as("SELECT collateral_txid, collateral_return_address FROM loans WHERE id = $1")
+            .bind(loan.id)
+            .fetch_one(&mut *db_tx)
+            .await
+            .map_err(db_error)?;
+    let to_address = return_address(registered, request.to_address.clone())?;
+    if to_address.is_none() && escrow.enabled() {
+        return Err(ServiceError::ValidationError("to_address is required to release collateral".to_string()));
+    }
+    if loan.status != "Active" {
+        return Err(ServiceError::BusinessError(format!(
+            "Collateral can only be released from an active loan (status: {})",
+            loan.status
+        )));
+    }
+
+    let debt = loan.principal_outstanding + loan.interest_to(now);
+    let max_release = releasable(loan.collateral_satoshis, debt);
+    if request.amount_satoshis > max_release {
+        return Err(ServiceError::BusinessError(format!(
+            "At most {} satoshis can be released while keeping {:.0}% collateral",
+            max_release,
+            MIN_COLLATERAL_RATIO * 100.0
+        )));
+    }
+    let collateral = loan.collateral_satoshis - request.amount_satoshis;
+    let ltv = ltv::ltv_bps(debt, 1.0, collateral, 1.0);
+    let band = policy.thresholds.band(ltv);
+    if band != LtvBand::Healthy {
+        return Err(ServiceError::BusinessError(format!(
+            "Releasing {} satoshis would leave the loan at {} bps LTV ({})",
+            request.amount_satoshis, ltv, band.as_str()
+        )));
+    }
+
+    let release = match &to_address {
+        Some(to_address) if escrow.enabled() => {
+            Some(escrow.build_release(collateral_txid.as_deref(), loan.collateral_satoshis, request.amount_satoshis, to_address).await?)
+        }
+        _ => None,
+    };
+
+    sqlx::query("UPDATE loans SET collateral_satoshis = $2 WHERE id = $1")
+        .bind(loan.id)
+        .bind(collateral)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+    sqlx::query(
+        r#"
+        INSERT INTO loan_collateral_releases
+            (loan_id, amount_satoshis, to_address, txid, tx_hex, collateral_after, ltv_bps_after)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#
+    )
+    .bind(loan.id)
+    .bind(request.amount_satoshis)
+    .bind(&to_address)
+    .bind(release.as_ref().map(|r| &r.txid))
+    .bind(release.as_ref().map(|r| &r.tx_hex))
+    .bind(collateral)
+    .bind(ltv as i32)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    let txid = release.map(|r| r.txid);
+    events::record(&mut *db_tx, loan.id, events::COLLATERAL_RELEASED, &loan.borrower_paymail, Some(request.amount_satoshis), serde_json::json!({
+        "to_address": to_address,
+        "txid": txid,
+        "collateral_after": collateral,
+        "ltv_bps": ltv
+    }))
+    .await
+    .map_err(db_error)?;
+
+    let response = idempotency.respond(&mut db_tx, loan.id, serde_json::json!({
+        "status": "success",
+        "loan_id": loan.id,
+        "released_satoshis": request.amount_satoshis,
+        "collateral_satoshis": collateral,
+        "release_txid": txid,
+        "ltv_bps": ltv,
+        "ltv_band": band.as_str()
+    })).await?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Loan {} released {} collateral, {} remaining", loan.id, request.amount_satoshis, collateral);
+
+    Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::HttpMessage;
+
+    #[test]
+    fn test_releasable_keeps_origination_ratio() {
+        assert_eq!(releasable(2_000_000, 1_000_000), 500_000);
+        assert_eq!(releasable(1_500_000, 1_000_000), 0);
+        // Under-collateralized loans release nothing
+        assert_eq!(releasable(1_200_000, 1_000_000), 0);
+        assert_eq!(releasable(900, 0), 900);
+    }
+
+    #[test]
+    fn test_release_goes_to_registered_address() {
+        let registered = Some("1BoatSLRHtKNngkdXEeobR76b53LETtpyT".to_string());
+        assert_eq!(return_address(registered.clone(), None).unwrap(), registered);
+        assert_eq!(return_address(registered.clone(), registered.clone()).unwrap(), registered);
+        assert!(return_address(registered, Some("1A1zP1eP5QGefi2DMPTfTL5SLmv7DivfNa".to_string())).is_err());
+        assert_eq!(return_address(None, None).unwrap(), None);
+    }
+
+    /// An active loan of 1,000,000 against 1,500,000 collateral held on alice's ledger balance
+    async fn open_loan(pool: &sqlx::PgPool) -> Uuid {
+        let loan_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO loans (
+                id, borrower_paymail, principal_satoshis, collateral_satoshis, interest_rate_bps, status,
+                created_at, due_date, funded_at, funded_satoshis, principal_outstanding, interest_accrued_through
+            )
+            VALUES ($1, 'alice@example.com', 1000000, 1500000, 0, 'Active',
+                    NOW(), NOW() + INTERVAL '30 days', NOW(), 1000000, 1000000, NOW())
+            "#
+        )
+        .bind(loan_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        loan_id
+    }
+
+    async fn top_up(pool: &sqlx::PgPool, loan_id: Uuid, amount: i64) -> Result<HttpResponse, ServiceError> {
+        let http_req = actix_web::test::TestRequest::default().to_http_request();
+        http_req.extensions_mut().insert(bsv_bank_common::Claims::new("alice@example.com".to_string(), vec![], 1));
+        add_collateral(
+            web::Data::new(pool.clone()),
+            web::Data::new(MarginPolicy::from_env()),
+            web::Data::new(Escrow { address: None, ..Escrow::from_env() }),
+            http_req,
+            web::Path::from(loan_id),
+            web::Json(AddCollateralRequest { borrower_paymail: "alice@example.com".to_string(), amount_satoshis: amount, txid: None }),
+        )
+        .await
+    }
+
+    #[sqlx::test(migrations = "../../db/migrations")]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_top_up_must_be_covered_by_the_borrowers_balance(pool: sqlx::PgPool) {
+        let loan_id = open_loan(&pool).await;
+        let mut db_tx = pool.begin().await.unwrap();
+        settlement::transfer(&mut db_tx, "test_funding", "funding:test", "alice@example.com", 1_600_000, None).await.unwrap();
+        db_tx.commit().await.unwrap();
+
+        // 100,000 of the 1,600,000 is free beyond the collateral already held
+        assert!(matches!(top_up(&pool, loan_id, 200_000).await, Err(ServiceError::BusinessError(_))));
+        top_up(&pool, loan_id, 100_000).await.unwrap();
+
+        let collateral: i64 = sqlx::query_scalar("SELECT collateral_satoshis FROM loans WHERE id = $1")
+            .bind(loan_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(collateral, 1_600_000);
+    }
+}
@@ -0,0 +1,89 @@
+// core/lending-service/src/events.rs
+// Append-only loan lifecycle log: every transition with its actor and amounts
+
+use actix_web::{web, HttpResponse, Result};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::ServiceError;
+
+pub const LOAN_CREATED: &str = "created";
+pub const LOAN_CANCELLED: &str = "cancelled";
+pub const OFFER_EXPIRED: &str = "expired";
+pub const FUNDING_COMMITTED: &str = "funding_committed";
+pub const LOAN_FUNDED: &str = "funded";
+pub const FUNDING_EXPIRED: &str = "funding_expired";
+pub const INTEREST_ACCRUED: &str = "interest_accrued";
+pub const INTEREST_POSTED: &str = "interest_posted";
+pub const REPAYMENT: &str = "repayment";
+pub const LOAN_REPAID: &str = "repaid";
+pub const COLLATERAL_ADDED: &str = "collateral_added";
+pub const COLLATERAL_RELEASED: &str = "collateral_released";
+pub const MARGIN_CALL: &str = "margin_call";
+pub const MARGIN_CURED: &str = "margin_cured";
+pub const LIQUIDATED: &str = "liquidated";
+
+/// Actor for transitions made by the service's background tasks
+pub const ACTOR_SYSTEM: &str = "system";
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct LoanEvent {
+    pub id: Uuid,
+    pub loan_id: Uuid,
+    pub event_type: String,
+    /// Paymail of the borrower or lender, or `system`
+    pub actor: String,
+    pub amount_satoshis: Option<i64>,
+    pub details: serde_json::Value,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Append an event; pass the transaction making the transition so both commit together
+pub async fn record<'e, E: PgExecutor<'e>>(
+    executor: E,
+    loan_id: Uuid,
+    event_type: &str,
+    actor: &str,
+    amount_satoshis: Option<i64>,
+    details: serde_json::Value,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO loan_events (loan_id, event_type, actor, amount_satoshis, details)
+        VALUES ($1, $2, $3, $4, $5)
+        "#
+    )
+    .bind(loan_id)
+    .bind(event_type)
+    .bind(actor)
+    .bind(amount_satoshis)
+    .bind(details)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// `GET /loans/{id}/events`: the loan's full history, oldest first
+pub async fn get_loan_events(
+    pool: web::Data<PgPool>,
+    loan_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let events = sqlx::query_as::<_, LoanEvent>(
+        "SELECT * FROM loan_events WHERE loan_id = $1 ORDER BY created_at, seq"
+    )
+    .bind(*loan_id)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+
+    if events.is_empty() {
+        return Err(ServiceError::BusinessError("Loan not found".to_string()));
+    }
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "loan_id": loan_id.as_ref(),
+        "count": events.len(),
+        "events": events
+    })))
+}
@@ -0,0 +1,140 @@
+// core/lending-service/src/idempotency.rs
+// Idempotency-Key handling for money-moving loan requests
+
+use actix_web::{HttpRequest, HttpResponse};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::ServiceError;
+
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+
+const MAX_KEY_LEN: usize = 128;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// A request's optional idempotency key and what it was sent with
+pub struct Idempotency {
+    key: Option<String>,
+    operation: &'static str,
+    fingerprint: String,
+}
+
+impl Idempotency {
+    /// Read the `Idempotency-Key` header; `operation` scopes keys per endpoint
+    pub fn from_request<T: Serialize>(req: &HttpRequest, operation: &'static str, body: &T) -> Result<Self, ServiceError> {
+        let key = match req.headers().get(IDEMPOTENCY_HEADER) {
+            Some(value) => {
+                let key = value
+                    .to_str()
+                    .map_err(|_| ServiceError::ValidationError(format!("{} must be ASCII", IDEMPOTENCY_HEADER)))?;
+                validate_key(key)?;
+                Some(key.to_string())
+            }
+            None => None,
+        };
+        Ok(Self { key, operation, fingerprint: fingerprint(body) })
+    }
+
+    /// The stored response when this key already completed on `loan_id`. Call it
+    /// under the loan's row lock so a concurrent retry waits for the first to commit.
+    pub async fn replay(
+        &self,
+        db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        loan_id: Uuid,
+    ) -> Result<Option<HttpResponse>, ServiceError> {
+        let Some(key) = &self.key else { return Ok(None) };
+        let stored: Option<(Uuid, String, serde_json::Value)> = sqlx::query_as(
+            r#"
+            SELECT loan_id, request_fingerprint, response
+            FROM loan_idempotency_keys
+            WHERE operation = $1 AND idempotency_key = $2
+            "#
+        )
+        .bind(self.operation)
+        .bind(key)
+        .fetch_optional(&mut **db_tx)
+        .await
+        .map_err(db_error)?;
+
+        match stored {
+            None => Ok(None),
+            Some((stored_loan, stored_fingerprint, response))
+                if stored_loan == loan_id && stored_fingerprint == self.fingerprint =>
+            {
+                tracing::info!("Replaying {} {} for loan {}", self.operation, key, loan_id);
+                Ok(Some(HttpResponse::Ok().insert_header(("Idempotent-Replay", "true")).json(response)))
+            }
+            Some(_) => Err(ServiceError::BusinessError(format!(
+                "{} {} was already used for a different request",
+                IDEMPOTENCY_HEADER, key
+            ))),
+        }
+    }
+
+    /// Store `response` in the transaction that made the change, then return it
+    pub async fn respond(
+        &self,
+        db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        loan_id: Uuid,
+        response: serde_json::Value,
+    ) -> Result<HttpResponse, ServiceError> {
+        if let Some(key) = &self.key {
+            sqlx::query(
+                r#"
+                INSERT INTO loan_idempotency_keys
+                    (operation, idempotency_key, loan_id, request_fingerprint, response)
+                VALUES ($1, $2, $3, $4, $5)
+                "#
+            )
+            .bind(self.operation)
+            .bind(key)
+            .bind(loan_id)
+            .bind(&self.fingerprint)
+            .bind(&response)
+            .execute(&mut **db_tx)
+            .await
+            .map_err(db_error)?;
+        }
+        Ok(HttpResponse::Ok().json(response))
+    }
+}
+
+fn validate_key(key: &str) -> Result<(), ServiceError> {
+    if key.is_empty() || key.len() > MAX_KEY_LEN || !key.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(ServiceError::ValidationError(format!(
+            "{} must be 1-{} printable ASCII characters",
+            IDEMPOTENCY_HEADER, MAX_KEY_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn fingerprint<T: Serialize>(body: &T) -> String {
+    let json = serde_json::to_vec(body).unwrap_or_default();
+    hex::encode(Sha256::digest(&json))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_key() {
+        assert!(validate_key("3f2c9a1e-retry").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("has space").is_err());
+        assert!(validate_key(&"k".repeat(MAX_KEY_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let a = serde_json::json!({ "amount_satoshis": 1_000 });
+        let b = serde_json::json!({ "amount_satoshis": 1_001 });
+        assert_eq!(fingerprint(&a), fingerprint(&a));
+        assert_ne!(fingerprint(&a), fingerprint(&b));
+    }
+}
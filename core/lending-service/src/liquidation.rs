@@ -0,0 +1,665 @@
+// core/lending-service/src/liquidation.rs
+// Scheduled liquidation of lapsed margin calls, with on-chain collateral seizure
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::{interval_from_env, run_every, LendingMetrics};
+use chrono::{DateTime, Utc};
+use prometheus::{IntCounter, IntCounterVec, Opts, Registry};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+use uuid::Uuid;
+
+use crate::events;
+use crate::ltv;
+use crate::margin::{self, MarginPolicy, MarginReason};
+use crate::repayments::{self, Allocation, LoanBalance, BALANCE_COLUMNS};
+use crate::settlement;
+use crate::ServiceError;
+
+/// Seizure builds are retried on later passes up to this many times
+const MAX_SEIZURE_ATTEMPTS: i32 = 5;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone)]
+pub struct LiquidationPolicy {
+    /// Share of collateral seized when an LTV margin call lapses; 100 closes the loan
+    pub partial_percent: i64,
+    pub interval: Duration,
+}
+
+impl LiquidationPolicy {
+    /// `LIQUIDATION_PARTIAL_PERCENT` and `LIQUIDATION_INTERVAL_SECS`. The overdue grace
+    /// period is per loan, set by its product at origination.
+    pub fn from_env() -> Self {
+        Self {
+            partial_percent: env_or("LIQUIDATION_PARTIAL_PERCENT", 100).clamp(1, 100),
+            interval: interval_from_env("LIQUIDATION_INTERVAL_SECS", 300),
+        }
+    }
+
+    /// Collateral to seize from a lapsed margin call; all of it unless a partial
+    /// seizure of an LTV call leaves debt behind
+    pub fn seizure(&self, reason: MarginReason, collateral: i64, payoff: i64) -> (LiquidationKind, i64) {
+        if reason == MarginReason::Ltv && self.partial_percent < 100 {
+            let partial = collateral * self.partial_percent / 100;
+            if partial < payoff {
+                return (LiquidationKind::Partial, partial);
+            }
+        }
+        (LiquidationKind::Full, collateral)
+    }
+}
+
+fn env_or(name: &str, default: i64) -> i64 {
+    std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LiquidationKind {
+    /// Collateral seized and the loan closed
+    Full,
+    /// Part of the collateral applied to the debt; the loan stays open
+    Partial,
+}
+
+impl LiquidationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Full => "full",
+            Self::Partial => "partial",
+        }
+    }
+}
+
+// ============================================================================
+// COLLATERAL SEIZURE
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+struct BuiltTransaction {
+    txid: String,
+    tx_hex: String,
+}
+
+/// Builds the transaction moving seized collateral from escrow to the liquidation address
+#[derive(Clone)]
+pub struct CollateralSeizer {
+    client: reqwest::Client,
+    builder_url: String,
+    escrow_address: Option<String>,
+    liquidation_address: Option<String>,
+}
+
+impl CollateralSeizer {
+    /// `TRANSACTION_BUILDER_URL`, `COLLATERAL_ESCROW_ADDRESS` and `LIQUIDATION_ADDRESS`
+    pub fn from_env() -> Self {
+        let address = |name: &str| std::env::var(name).ok().filter(|a| !a.is_empty());
+        let seizer = Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            builder_url: std::env::var("TRANSACTION_BUILDER_URL")
+                .unwrap_or_else(|_| "http://localhost:8085".to_string()),
+            escrow_address: address("COLLATERAL_ESCROW_ADDRESS"),
+            liquidation_address: address("LIQUIDATION_ADDRESS"),
+        };
+        if seizer.escrow_address.is_none() || seizer.liquidation_address.is_none() {
+            tracing::warn!("COLLATERAL_ESCROW_ADDRESS or LIQUIDATION_ADDRESS not set, seizures will not be built");
+        }
+        seizer
+    }
+
+    /// `POST /tx/build/p2pkh` on transaction-builder, spending the collateral output when known
+    async fn build(&self, collateral_txid: Option<&str>, collateral: i64, amount: i64) -> Result<BuiltTransaction, String> {
+        let (Some(from), Some(to)) = (&self.escrow_address, &self.liquidation_address) else {
+            return Err("Seizure addresses not configured".to_string());
+        };
+        let mut request = serde_json::json!({
+            "from_address": from,
+            "to_address": to,
+            "amount_satoshis": amount,
+        });
+        if let Some(txid) = collateral_txid {
+            // Collateral is locked in output 0 of its escrow transaction
+            request["utxos"] = serde_json::json!([{ "txid": txid, "vout": 0, "satoshis": collateral }]);
+        }
+
+        let response = self.client
+            .post(format!("{}/tx/build/p2pkh", self.builder_url))
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        response.json().await.map_err(|e| format!("Parse error: {}", e))
+    }
+}
+
+// ============================================================================
+// METRICS
+// ============================================================================
+
+#[derive(Clone)]
+pub struct LiquidationMetrics {
+    /// Service-wide loan counters
+    lending: LendingMetrics,
+    liquidations: IntCounterVec,
+    seized_satoshis: IntCounter,
+    seizure_failures: IntCounter,
+}
+
+impl LiquidationMetrics {
+    pub fn new(registry: &Registry, lending: LendingMetrics) -> Result<Self, prometheus::Error> {
+        let liquidations = IntCounterVec::new(
+            Opts::new("lending_liquidations_by_kind_total", "Liquidations by kind and margin call reason"),
+            &["kind", "reason"],
+        )?;
+        let seized_satoshis = IntCounter::new(
+            "lending_collateral_seized_satoshis_total",
+            "Collateral seized by liquidations",
+        )?;
+        let seizure_failures = IntCounter::new(
+            "lending_seizure_failures_total",
+            "Failed attempts to build a collateral seizure transaction",
+        )?;
+        registry.register(Box::new(liquidations.clone()))?;
+        registry.register(Box::new(seized_satoshis.clone()))?;
+        registry.register(Box::new(seizure_failures.clone()))?;
+        Ok(Self { lending, liquidations, seized_satoshis, seizure_failures })
+    }
+}
+
+// ============================================================================
+// ENGINE
+// ============================================================================
+
+#[derive(sqlx::FromRow)]
+struct LapsedLoan {
+    #[sqlx(flatten)]
+    balance: LoanBalance,
+    collateral_txid: Option<String>,
+    margin_call_reason: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct PendingSeizure {
+    id: Uuid,
+    loan_id: Uuid,
+    collateral_txid: Option<String>,
+    collateral_satoshis: i64,
+    collateral_seized: i64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct LiquidationReport {
+    pub margin_calls: Vec<serde_json::Value>,
+    pub liquidations: Vec<serde_json::Value>,
+}
+
+#[derive(Clone)]
+pub struct LiquidationEngine {
+    pool: PgPool,
+    policy: LiquidationPolicy,
+    margin: MarginPolicy,
+    seizer: CollateralSeizer,
+    metrics: LiquidationMetrics,
+}
+
+impl LiquidationEngine {
+    pub fn new(pool: PgPool, margin: MarginPolicy, metrics: LiquidationMetrics) -> Self {
+        Self {
+            pool,
+            policy: LiquidationPolicy::from_env(),
+            margin,
+            seizer: CollateralSeizer::from_env(),
+            metrics,
+        }
+    }
+
+    /// Run a pass every `LIQUIDATION_INTERVAL_SECS`
+    pub async fn start(self) {
+        let engine = &self;
+        run_every("liquidation", self.policy.interval, || async move {
+            let report = engine.run_once().await?;
+            if !report.margin_calls.is_empty() || !report.liquidations.is_empty() {
+                tracing::info!(
+                    "Liquidation pass: {} margin calls, {} liquidations",
+                    report.margin_calls.len(),
+                    report.liquidations.len()
+                );
+            }
+            Ok::<_, ServiceError>(())
+        })
+        .await
+    }
+
+    /// Margin call overdue loans, liquidate lapsed margin calls and retry failed seizures
+    pub async fn run_once(&self) -> Result<LiquidationReport, ServiceError> {
+        let now = Utc::now();
+        let mut report = LiquidationReport::default();
+
+        let overdue = sqlx::query_as::<_, LoanBalance>(&format!(
+            "SELECT {} FROM loans WHERE status = 'Active' AND due_date + overdue_grace_days * INTERVAL '1 day' < $1",
+            BALANCE_COLUMNS
+        ))
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        for loan in overdue {
+            let debt = loan.principal_outstanding + loan.interest_to(now);
+            let ltv = ltv::ltv_bps(debt, 1.0, loan.collateral_satoshis, 1.0);
+            let mut db_tx = self.pool.begin().await.map_err(db_error)?;
+            let deadline = self.margin.call(&mut db_tx, &loan, MarginReason::Overdue, ltv, now).await.map_err(db_error)?;
+            db_tx.commit().await.map_err(db_error)?;
+            let Some(deadline) = deadline else { continue };
+
+            report.margin_calls.push(serde_json::json!({
+                "loan_id": loan.id,
+                "borrower": loan.borrower_paymail,
+                "days_overdue": (now - loan.due_date).num_days(),
+                "deadline": deadline
+            }));
+        }
+
+        let lapsed: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM loans WHERE status = 'MarginCalled' AND margin_call_deadline < $1"
+        )
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        for loan_id in lapsed {
+            if let Some(liquidation) = self.liquidate(loan_id, now).await? {
+                report.liquidations.push(liquidation);
+            }
+        }
+
+        self.retry_seizures().await?;
+        Ok(report)
+    }
+
+    async fn liquidate(&self, loan_id: Uuid, now: DateTime<Utc>) -> Result<Option<serde_json::Value>, ServiceError> {
+        let mut db_tx = self.pool.begin().await.map_err(db_error)?;
+
+        // Re-check under the row lock: a repayment or top-up may have cured it meanwhile
+        let lapsed = sqlx::query_as::<_, LapsedLoan>(&format!(
+            r#"
+            SELECT {}, collateral_txid, margin_call_reason FROM loans
+            WHERE id = $1 AND status = 'MarginCalled' AND margin_call_deadline < $2
+            FOR UPDATE SKIP LOCKED
+            "#,
+            BALANCE_COLUMNS
+        ))
+        .bind(loan_id)
+        .bind(now)
+        .fetch_optional(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+        let Some(LapsedLoan { balance: loan, collateral_txid, margin_call_reason }) = lapsed else {
+            return Ok(None);
+        };
+
+        let reason = if margin_call_reason == MarginReason::Ltv.as_str() { MarginReason::Ltv } else { MarginReason::Overdue };
+        let interest_due = loan.interest_to(now);
+        let payoff = loan.payoff(now);
+        let (mut kind, mut seized) = self.policy.seizure(reason, loan.collateral_satoshis, payoff.total_satoshis);
+
+        let partial = match repayments::allocate(seized, interest_due, loan.principal_outstanding, payoff.total_satoshis) {
+            Ok(Allocation::Partial { interest, principal }) if kind == LiquidationKind::Partial => Some((interest, principal)),
+            _ => None,
+        };
+        if partial.is_none() {
+            // Too little would be left to keep the loan open
+            kind = LiquidationKind::Full;
+            seized = loan.collateral_satoshis;
+        }
+
+        // Seized collateral pays the debt from the loan's collateral account: collateral held
+        // on the ledger leaves the borrower's balance, escrowed collateral is swept on-chain
+        let collateral_account = settlement::collateral_account(loan.id);
+        let source = if collateral_txid.is_none() { loan.borrower_paymail.as_str() } else { settlement::LIQUIDATION_PROCEEDS_ACCOUNT };
+        settlement::transfer(&mut db_tx, settlement::COLLATERAL_SEIZURE, source, &collateral_account, seized, Some(loan.id))
+            .await
+            .map_err(db_error)?;
+
+        let (debt_repaid, shortfall) = if let Some((interest, principal)) = partial {
+            repayments::apply_partial(&mut db_tx, &loan, interest_due, interest, principal, now, events::ACTOR_SYSTEM).await?;
+            sqlx::query(
+                r#"
+                UPDATE loans
+                SET collateral_satoshis = collateral_satoshis - $2, status = 'Active',
+                    margin_called_at = NULL, margin_call_deadline = NULL, margin_call_reason = NULL
+                WHERE id = $1
+                "#
+            )
+            .bind(loan.id)
+            .bind(seized)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(db_error)?;
+            (seized, 0)
+        } else {
+            let repaid = payoff.total_satoshis.min(seized);
+            repayments::settle_in_full(&mut db_tx, &loan, &payoff.capped(repaid), now, events::ACTOR_SYSTEM).await?;
+            // Collateral beyond the debt goes back to the borrower
+            settlement::transfer(
+                &mut db_tx,
+                settlement::COLLATERAL_RETURN,
+                &collateral_account,
+                &loan.borrower_paymail,
+                seized - repaid,
+                Some(loan.id),
+            )
+            .await
+            .map_err(db_error)?;
+            (repaid, payoff.total_satoshis - repaid)
+        };
+
+        let debt = loan.principal_outstanding + interest_due;
+        let liquidation_id: Uuid = sqlx::query_scalar(
+            r#"
+            INSERT INTO loan_liquidations
+                (loan_id, kind, reason, ltv_bps, collateral_seized, debt_repaid, shortfall_satoshis, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id
+            "#
+        )
+        .bind(loan.id)
+        .bind(kind.as_str())
+        .bind(reason.as_str())
+        .bind(ltv::ltv_bps(debt, 1.0, loan.collateral_satoshis, 1.0).min(i32::MAX as i64) as i32)
+        .bind(seized)
+        .bind(debt_repaid)
+        .bind(shortfall)
+        .bind(now)
+        .fetch_one(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+
+        let details = serde_json::json!({
+            "liquidation_id": liquidation_id,
+            "kind": kind.as_str(),
+            "reason": reason.as_str(),
+            "collateral_seized": seized,
+            "debt_repaid": debt_repaid,
+            "shortfall": shortfall
+        });
+        sqlx::query(
+            r#"
+            INSERT INTO audit_log (user_paymail, action, resource_type, resource_id, details)
+            VALUES ($1, 'loan.liquidated', 'loan', $2, $3)
+            "#
+        )
+        .bind(&loan.borrower_paymail)
+        .bind(loan.id.to_string())
+        .bind(&details)
+        .execute(&mut *db_tx)
+        .await
+        .map_err(db_error)?;
+        events::record(&mut *db_tx, loan.id, events::LIQUIDATED, events::ACTOR_SYSTEM, Some(seized), details.clone())
+            .await
+            .map_err(db_error)?;
+        db_tx.commit().await.map_err(db_error)?;
+
+        tracing::warn!("Loan {} liquidated ({}, {} margin call): {} seized", loan.id, kind.as_str(), reason.as_str(), seized);
+        self.metrics.lending.liquidations_total.inc();
+        if kind == LiquidationKind::Full {
+            self.metrics.lending.record_loan_status("Liquidated", loan.principal_outstanding);
+        }
+        self.metrics.liquidations.with_label_values(&[kind.as_str(), reason.as_str()]).inc();
+        self.metrics.seized_satoshis.inc_by(seized as u64);
+        self.margin.notifier.notify(margin::EVENT_LIQUIDATED, &loan, details);
+
+        let seizure = PendingSeizure {
+            id: liquidation_id,
+            loan_id: loan.id,
+            collateral_txid,
+            collateral_satoshis: loan.collateral_satoshis,
+            collateral_seized: seized,
+        };
+        let seizure_txid = self.seize(&seizure).await?;
+
+        Ok(Some(serde_json::json!({
+            "loan_id": loan.id,
+            "borrower": loan.borrower_paymail,
+            "kind": kind.as_str(),
+            "reason": reason.as_str(),
+            "collateral_seized": seized,
+            "debt_repaid": debt_repaid,
+            "seizure_txid": seizure_txid
+        })))
+    }
+
+    /// Build the seizure transaction and record the outcome on the liquidation
+    async fn seize(&self, seizure: &PendingSeizure) -> Result<Option<String>, ServiceError> {
+        let result = self.seizer
+            .build(seizure.collateral_txid.as_deref(), seizure.collateral_satoshis, seizure.collateral_seized)
+            .await;
+
+        match result {
+            Ok(built) => {
+                sqlx::query(
+                    r#"
+                    UPDATE loan_liquidations
+                    SET seizure_txid = $2, seizure_tx_hex = $3, seizure_attempts = seizure_attempts + 1,
+                        seizure_error = NULL
+                    WHERE id = $1
+                    "#
+                )
+                .bind(seizure.id)
+                .bind(&built.txid)
+                .bind(&built.tx_hex)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+                tracing::info!("Seizure {} built for loan {}", built.txid, seizure.loan_id);
+                Ok(Some(built.txid))
+            }
+            Err(e) => {
+                sqlx::query(
+                    r#"
+                    UPDATE loan_liquidations
+                    SET seizure_attempts = seizure_attempts + 1, seizure_error = $2
+                    WHERE id = $1
+                    "#
+                )
+                .bind(seizure.id)
+                .bind(&e)
+                .execute(&self.pool)
+                .await
+                .map_err(db_error)?;
+                self.metrics.seizure_failures.inc();
+                tracing::error!("Seizure for loan {} failed: {}", seizure.loan_id, e);
+                Ok(None)
+            }
+        }
+    }
+
+    async fn retry_seizures(&self) -> Result<(), ServiceError> {
+        let pending = sqlx::query_as::<_, PendingSeizure>(
+            r#"
+            SELECT l.id, l.loan_id, loans.collateral_txid,
+                   l.collateral_seized + CASE WHEN l.kind = 'partial' THEN loans.collateral_satoshis ELSE 0 END
+                       AS collateral_satoshis,
+                   l.collateral_seized
+            FROM loan_liquidations l
+            JOIN loans ON loans.id = l.loan_id
+            WHERE l.seizure_txid IS NULL AND l.seizure_attempts > 0 AND l.seizure_attempts < $1
+            "#
+        )
+        .bind(MAX_SEIZURE_ATTEMPTS)
+        .fetch_all(&self.pool)
+        .await
+        .map_err(db_error)?;
+
+        for seizure in pending {
+            self.seize(&seizure).await?;
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `POST /loans/liquidations/check`: run a pass now instead of waiting for the schedule
+pub async fn check_liquidations(engine: web::Data<LiquidationEngine>) -> Result<HttpResponse, ServiceError> {
+    let report = engine.run_once().await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "checked_at": Utc::now(),
+        "margin_call_count": report.margin_calls.len(),
+        "margin_calls": report.margin_calls,
+        "liquidated_count": report.liquidations.len(),
+        "liquidations": report.liquidations
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(partial_percent: i64) -> LiquidationPolicy {
+        LiquidationPolicy { partial_percent, interval: Duration::from_secs(300) }
+    }
+
+    #[test]
+    fn test_full_seizure_by_default() {
+        assert_eq!(policy(100).seizure(MarginReason::Ltv, 1_200_000, 1_000_000), (LiquidationKind::Full, 1_200_000));
+    }
+
+    #[test]
+    fn test_partial_seizure() {
+        assert_eq!(policy(40).seizure(MarginReason::Ltv, 1_200_000, 1_000_000), (LiquidationKind::Partial, 480_000));
+        // A partial share that covers the debt closes the loan
+        assert_eq!(policy(90).seizure(MarginReason::Ltv, 1_200_000, 1_000_000), (LiquidationKind::Full, 1_200_000));
+        // Overdue loans are always closed
+        assert_eq!(policy(40).seizure(MarginReason::Overdue, 1_200_000, 1_000_000), (LiquidationKind::Full, 1_200_000));
+    }
+
+    fn engine(pool: PgPool, partial_percent: i64) -> LiquidationEngine {
+        let registry = Registry::new();
+        let lending = LendingMetrics::new(&registry).unwrap();
+        LiquidationEngine {
+            pool,
+            policy: policy(partial_percent),
+            margin: MarginPolicy::from_env(),
+            seizer: CollateralSeizer::from_env(),
+            metrics: LiquidationMetrics::new(&registry, lending).unwrap(),
+        }
+    }
+
+    /// An interest-free loan of 1,000,000 funded 60/40 by bob and carol, its LTV margin call lapsed
+    async fn lapsed_loan(pool: &PgPool, collateral: i64, collateral_txid: Option<&str>) -> Uuid {
+        let loan_id = Uuid::new_v4();
+        sqlx::query(
+            r#"
+            INSERT INTO loans (
+                id, borrower_paymail, principal_satoshis, collateral_satoshis, interest_rate_bps,
+                status, created_at, due_date, funded_at, funded_satoshis, principal_outstanding,
+                interest_accrued_through, collateral_txid, margin_called_at, margin_call_deadline, margin_call_reason
+            )
+            VALUES ($1, 'alice@example.com', 1000000, $2, 0, 'MarginCalled', NOW(), NOW() + INTERVAL '30 days',
+                    NOW(), 1000000, 1000000, NOW(), $3, NOW() - INTERVAL '4 days', NOW() - INTERVAL '1 hour', 'ltv')
+            "#
+        )
+        .bind(loan_id)
+        .bind(collateral)
+        .bind(collateral_txid)
+        .execute(pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO loan_participations (loan_id, lender_paymail, amount_satoshis, status)
+            VALUES ($1, 'bob@example.com', 600000, 'Active'), ($1, 'carol@example.com', 400000, 'Active')
+            "#
+        )
+        .bind(loan_id)
+        .execute(pool)
+        .await
+        .unwrap();
+        loan_id
+    }
+
+    async fn balance(pool: &PgPool, account: &str) -> i64 {
+        settlement::balance(pool, account).await.unwrap()
+    }
+
+    #[sqlx::test(migrations = "../../db/migrations")]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_partial_liquidation_pays_lenders_from_seized_collateral(pool: PgPool) {
+        let loan_id = lapsed_loan(&pool, 1_200_000, None).await;
+        let liquidation = engine(pool.clone(), 40).liquidate(loan_id, Utc::now()).await.unwrap().unwrap();
+        assert_eq!(liquidation["kind"], "partial");
+        assert_eq!(liquidation["collateral_seized"], 480_000);
+
+        assert_eq!(balance(&pool, "alice@example.com").await, -480_000);
+        assert_eq!(balance(&pool, &settlement::collateral_account(loan_id)).await, 0);
+        assert_eq!(balance(&pool, &settlement::loan_account(loan_id)).await, 0);
+        assert_eq!(balance(&pool, "bob@example.com").await, 288_000);
+        assert_eq!(balance(&pool, "carol@example.com").await, 192_000);
+
+        let (status, outstanding, collateral): (String, i64, i64) =
+            sqlx::query_as("SELECT status, principal_outstanding, collateral_satoshis FROM loans WHERE id = $1")
+                .bind(loan_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!((status.as_str(), outstanding, collateral), ("Active", 520_000, 720_000));
+    }
+
+    #[sqlx::test(migrations = "../../db/migrations")]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_full_liquidation_settles_the_loan(pool: PgPool) {
+        let engine = engine(pool.clone(), 100);
+
+        // Collateral held on the ledger falls short of the debt: the rest is written off
+        let short = lapsed_loan(&pool, 800_000, None).await;
+        let liquidation = engine.liquidate(short, Utc::now()).await.unwrap().unwrap();
+        assert_eq!(liquidation["kind"], "full");
+        assert_eq!(liquidation["debt_repaid"], 800_000);
+
+        assert_eq!(balance(&pool, "alice@example.com").await, -800_000);
+        assert_eq!(balance(&pool, &settlement::collateral_account(short)).await, 0);
+        assert_eq!(balance(&pool, "bob@example.com").await, 480_000);
+        assert_eq!(balance(&pool, "carol@example.com").await, 320_000);
+
+        let written_off: Vec<(String, i64)> = sqlx::query_as(
+            "SELECT status, principal_written_off FROM loan_participations WHERE loan_id = $1 ORDER BY amount_satoshis DESC"
+        )
+        .bind(short)
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(written_off, vec![("Closed".to_string(), 120_000), ("Closed".to_string(), 80_000)]);
+        let (status, shortfall): (String, i64) = sqlx::query_as(
+            "SELECT loans.status, l.shortfall_satoshis FROM loans JOIN loan_liquidations l ON l.loan_id = loans.id WHERE loans.id = $1"
+        )
+        .bind(short)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!((status.as_str(), shortfall), ("Liquidated", 200_000));
+
+        // Escrowed collateral is swept on-chain; what the debt does not take goes back to the borrower
+        let covered = lapsed_loan(&pool, 1_500_000, Some(&"ab".repeat(32))).await;
+        engine.liquidate(covered, Utc::now()).await.unwrap().unwrap();
+        assert_eq!(balance(&pool, settlement::LIQUIDATION_PROCEEDS_ACCOUNT).await, -1_500_000);
+        assert_eq!(balance(&pool, "alice@example.com").await, -800_000 + 500_000);
+        assert_eq!(balance(&pool, &settlement::collateral_account(covered)).await, 0);
+        assert_eq!(balance(&pool, "bob@example.com").await, 480_000 + 600_000);
+        assert_eq!(balance(&pool, "carol@example.com").await, 320_000 + 400_000);
+    }
+}
@@ -0,0 +1,336 @@
+// core/lending-service/src/listings.rs
+// Paginated, filtered loan listings: open requests for lenders and a user's own loans
+
+use actix_web::{web, HttpResponse, Result};
+use bsv_bank_common::validate_paymail;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::loans::{Loan, LOAN_COLUMNS};
+use crate::{bps_to_rate, calculate_collateral_ratio, ServiceError};
+
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+/// Pagination travels in headers so both listings stay plain JSON arrays
+pub const NEXT_CURSOR_HEADER: &str = "X-Next-Cursor";
+pub const PAGE_LIMIT_HEADER: &str = "X-Page-Limit";
+const STATUSES: [&str; 7] = ["Pending", "Active", "MarginCalled", "Repaid", "Liquidated", "Cancelled", "Expired"];
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LoanSort {
+    #[default]
+    Newest,
+    Oldest,
+    RateDesc,
+    RateAsc,
+    AmountDesc,
+    AmountAsc,
+    DueSoonest,
+}
+
+impl LoanSort {
+    /// Integer the listing is ordered by, ties broken by id; timestamps are epoch microseconds
+    fn key(&self) -> &'static str {
+        match self {
+            Self::Newest | Self::Oldest => "(EXTRACT(EPOCH FROM created_at) * 1000000)::BIGINT",
+            Self::RateDesc | Self::RateAsc => "interest_rate_bps::BIGINT",
+            Self::AmountDesc | Self::AmountAsc => "principal_satoshis",
+            Self::DueSoonest => "(EXTRACT(EPOCH FROM due_date) * 1000000)::BIGINT",
+        }
+    }
+
+    fn descending(&self) -> bool {
+        matches!(self, Self::Newest | Self::RateDesc | Self::AmountDesc)
+    }
+
+    /// ORDER BY clause and the keyset condition on `(sort_key, id)` past the cursor at `$cursor`
+    fn clauses(&self, cursor: usize) -> (String, String) {
+        let (dir, cmp) = if self.descending() { ("DESC", "<") } else { ("ASC", ">") };
+        let key = self.key();
+        (
+            format!("{key} {dir}, id {dir}"),
+            format!("(${cursor}::BIGINT IS NULL OR ({key}, id) {cmp} (${cursor}, ${}))", cursor + 1),
+        )
+    }
+}
+
+/// Position after the last row of a page; opaque to clients
+#[derive(Debug, PartialEq, Eq)]
+struct Cursor {
+    key: i64,
+    id: Uuid,
+}
+
+impl Cursor {
+    fn encode(&self) -> String {
+        hex::encode(format!("{}:{}", self.key, self.id))
+    }
+
+    fn decode(value: &str) -> Result<Self, ServiceError> {
+        let invalid = || ServiceError::ValidationError("Invalid cursor".to_string());
+        let raw = String::from_utf8(hex::decode(value).map_err(|_| invalid())?).map_err(|_| invalid())?;
+        let (key, id) = raw.split_once(':').ok_or_else(invalid)?;
+        Ok(Self {
+            key: key.parse().map_err(|_| invalid())?,
+            id: id.parse().map_err(|_| invalid())?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListingQuery {
+    /// Comma-separated loan statuses; ignored by `/loans/available`, which lists Pending only
+    pub status: Option<String>,
+    pub min_rate_bps: Option<i32>,
+    pub max_rate_bps: Option<i32>,
+    pub min_duration_days: Option<i32>,
+    pub max_duration_days: Option<i32>,
+    #[serde(default)]
+    pub sort: LoanSort,
+    pub limit: Option<i64>,
+    /// `X-Next-Cursor` from the previous page
+    pub cursor: Option<String>,
+}
+
+impl ListingQuery {
+    fn validate(&self) -> Result<(), ServiceError> {
+        for status in self.statuses() {
+            if !STATUSES.contains(&status.as_str()) {
+                return Err(ServiceError::ValidationError(format!(
+                    "status must be one of {}",
+                    STATUSES.join(", ")
+                )));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_rate_bps, self.max_rate_bps) {
+            if min > max {
+                return Err(ServiceError::ValidationError("min_rate_bps must not exceed max_rate_bps".to_string()));
+            }
+        }
+        if let (Some(min), Some(max)) = (self.min_duration_days, self.max_duration_days) {
+            if min > max {
+                return Err(ServiceError::ValidationError(
+                    "min_duration_days must not exceed max_duration_days".to_string()
+                ));
+            }
+        }
+        if let Some(cursor) = &self.cursor {
+            Cursor::decode(cursor)?;
+        }
+        Ok(())
+    }
+
+    fn statuses(&self) -> Vec<String> {
+        self.status
+            .as_deref()
+            .map(|s| s.split(',').map(|s| s.trim().to_string()).filter(|s| !s.is_empty()).collect())
+            .unwrap_or_default()
+    }
+
+    fn limit(&self) -> i64 {
+        self.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)
+    }
+
+    fn cursor(&self) -> Option<Cursor> {
+        self.cursor.as_deref().and_then(|c| Cursor::decode(c).ok())
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct ListedLoan {
+    #[sqlx(flatten)]
+    loan: Loan,
+    funding_deadline: Option<DateTime<Utc>>,
+    sort_key: i64,
+}
+
+/// Run a listing: `scope` is the WHERE condition specific to the endpoint, with `$1` bound
+/// to `scope_arg`. Fetches one extra row to tell whether another page follows.
+async fn fetch_page(
+    pool: &PgPool,
+    scope: &str,
+    scope_arg: Option<&str>,
+    query: &ListingQuery,
+) -> Result<(Vec<ListedLoan>, Option<String>), ServiceError> {
+    let (order_by, after_cursor) = query.sort.clauses(8);
+    let sql = format!(
+        r#"
+        SELECT {columns}, funding_deadline, {key} AS sort_key
+        FROM loans
+        WHERE {scope}
+          AND (cardinality($2::text[]) = 0 OR status = ANY($2))
+          AND ($3::int IS NULL OR interest_rate_bps >= $3)
+          AND ($4::int IS NULL OR interest_rate_bps <= $4)
+          AND ($5::int IS NULL OR due_date - created_at >= make_interval(days => $5))
+          AND ($6::int IS NULL OR due_date - created_at <= make_interval(days => $6))
+          AND {after_cursor}
+        ORDER BY {order_by}
+        LIMIT $7 + 1
+        "#,
+        columns = LOAN_COLUMNS,
+        key = query.sort.key(),
+    );
+
+    let cursor = query.cursor();
+    let mut rows = sqlx::query_as::<_, ListedLoan>(&sql)
+        .bind(scope_arg)
+        .bind(query.statuses())
+        .bind(query.min_rate_bps)
+        .bind(query.max_rate_bps)
+        .bind(query.min_duration_days)
+        .bind(query.max_duration_days)
+        .bind(query.limit())
+        .bind(cursor.as_ref().map(|c| c.key))
+        .bind(cursor.as_ref().map(|c| c.id))
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+
+    let next_cursor = if rows.len() as i64 > query.limit() {
+        rows.truncate(query.limit() as usize);
+        rows.last().map(|row| Cursor { key: row.sort_key, id: row.loan.id }.encode())
+    } else {
+        None
+    };
+    Ok((rows, next_cursor))
+}
+
+/// One page as a JSON array, with the page size and the cursor for the next page, if any, in headers
+fn page_response(loans: Vec<serde_json::Value>, limit: i64, next_cursor: Option<String>) -> HttpResponse {
+    let mut response = HttpResponse::Ok();
+    response.insert_header((PAGE_LIMIT_HEADER, limit.to_string()));
+    if let Some(cursor) = next_cursor {
+        response.insert_header((NEXT_CURSOR_HEADER, cursor));
+    }
+    response.json(loans)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+/// `GET /loans/available`: Pending requests open to lenders
+pub async fn get_available_loans(
+    pool: web::Data<PgPool>,
+    query: web::Query<ListingQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    query.validate()?;
+    // Partly funded requests stay listed past the offer window until their funding deadline.
+    // No scope argument here, but $1 still has to appear for its type to be inferred.
+    let scope = "status = 'Pending' AND $1::text IS NULL \
+                 AND (offer_expires_at IS NULL OR offer_expires_at > NOW() OR funded_satoshis > 0)";
+    let listing = ListingQuery { status: None, ..query.into_inner() };
+    let (rows, next_cursor) = fetch_page(&pool, scope, None, &listing).await?;
+
+    let loans: Vec<_> = rows.iter().map(|row| {
+        let loan = &row.loan;
+        serde_json::json!({
+            "loan_id": loan.id,
+            "borrower": loan.borrower_paymail,
+            "amount": loan.principal_satoshis,
+            "funded": loan.funded_satoshis,
+            "remaining": loan.principal_satoshis - loan.funded_satoshis,
+            "funding_deadline": row.funding_deadline,
+            "offer_expires_at": loan.offer_expires_at,
+            "collateral": loan.collateral_satoshis,
+            "collateral_ratio": calculate_collateral_ratio(loan.collateral_satoshis, loan.principal_satoshis),
+            "interest_rate_bps": loan.interest_rate_bps,
+            "interest_rate_percent": bps_to_rate(loan.interest_rate_bps) * 100.0,
+            "duration_days": loan.duration_days(),
+            "due_date": loan.due_date
+        })
+    }).collect();
+
+    Ok(page_response(loans, listing.limit(), next_cursor))
+}
+
+/// `GET /loans/my-loans/{paymail}`: loans the paymail borrowed or leads as lender
+pub async fn get_user_loans(
+    pool: web::Data<PgPool>,
+    paymail: web::Path<String>,
+    query: web::Query<ListingQuery>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    query.validate()?;
+
+    let scope = "(borrower_paymail = $1 OR lender_paymail = $1)";
+    let (rows, next_cursor) = fetch_page(&pool, scope, Some(paymail.as_str()), &query).await?;
+
+    let loans: Vec<_> = rows.iter().map(|row| {
+        let loan = &row.loan;
+        serde_json::json!({
+            "loan_id": loan.id,
+            "borrower": loan.borrower_paymail,
+            "lender": loan.lender_paymail,
+            "principal": loan.principal_satoshis,
+            "interest": loan.interest_accrued,
+            "total_due": loan.principal_satoshis + loan.interest_accrued,
+            "principal_outstanding": loan.principal_outstanding,
+            "interest_paid": loan.interest_paid,
+            "collateral": loan.collateral_satoshis,
+            "interest_rate_bps": loan.interest_rate_bps,
+            "duration_days": loan.duration_days(),
+            "status": loan.status,
+            "created_at": loan.created_at,
+            "due_date": loan.due_date,
+            "repaid_at": loan.repaid_at
+        })
+    }).collect();
+
+    Ok(page_response(loans, query.limit(), next_cursor))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(json: serde_json::Value) -> ListingQuery {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = Cursor { key: 1_700_000_000_123_456, id: Uuid::new_v4() };
+        assert_eq!(Cursor::decode(&cursor.encode()).unwrap(), cursor);
+        assert!(Cursor::decode("not-a-cursor").is_err());
+        assert!(Cursor::decode(&hex::encode("12:nope")).is_err());
+    }
+
+    #[test]
+    fn test_filters_validated() {
+        assert!(query(serde_json::json!({ "status": "Active,MarginCalled" })).validate().is_ok());
+        assert!(query(serde_json::json!({ "status": "Open" })).validate().is_err());
+        assert!(query(serde_json::json!({ "min_rate_bps": 900, "max_rate_bps": 500 })).validate().is_err());
+        assert!(query(serde_json::json!({ "min_duration_days": 90, "max_duration_days": 30 })).validate().is_err());
+        assert_eq!(query(serde_json::json!({ "limit": 5000 })).limit(), MAX_LIMIT);
+        assert_eq!(query(serde_json::json!({})).limit(), DEFAULT_LIMIT);
+    }
+
+    #[test]
+    fn test_keyset_follows_sort_direction() {
+        let (order, after) = LoanSort::Newest.clauses(8);
+        assert!(order.ends_with("DESC, id DESC"));
+        assert!(after.contains(") < ($8, $9)"));
+        let (order, after) = LoanSort::RateAsc.clauses(8);
+        assert_eq!(order, "interest_rate_bps::BIGINT ASC, id ASC");
+        assert!(after.contains(") > ($8, $9)"));
+    }
+
+    #[test]
+    fn test_page_metadata_in_headers() {
+        let last = page_response(vec![], 50, None);
+        assert_eq!(last.headers().get(PAGE_LIMIT_HEADER).unwrap(), "50");
+        assert!(last.headers().get(NEXT_CURSOR_HEADER).is_none());
+
+        let more = page_response(vec![serde_json::json!({ "loan_id": 1 })], 1, Some("abc".to_string()));
+        assert_eq!(more.headers().get(NEXT_CURSOR_HEADER).unwrap(), "abc");
+    }
+}
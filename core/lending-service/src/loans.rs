@@ -0,0 +1,268 @@
+// core/lending-service/src/loans.rs
+// The loans row as stored, shared by origination and the history and stats handlers
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+/// Columns read into `Loan`; select with this rather than listing them per query
+pub const LOAN_COLUMNS: &str = "id, borrower_paymail, lender_paymail, principal_satoshis, collateral_satoshis, \
+    interest_rate_bps, interest_accrued, status, created_at, due_date, funded_at, repaid_at, liquidated_at, \
+    principal_outstanding, interest_paid, funded_satoshis, prepayment_option, prepayment_penalty_bps, offer_expires_at, \
+    product_code, late_fee_daily_bps, overdue_grace_days, collateral_return_address";
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Loan {
+    pub id: Uuid,
+    pub borrower_paymail: String,
+    /// Lead lender; every lender is in `loan_participations`
+    pub lender_paymail: Option<String>,
+    pub principal_satoshis: i64,
+    pub collateral_satoshis: i64,
+    pub interest_rate_bps: i32,
+    /// Interest quoted for the full term at origination
+    pub interest_accrued: i64,
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+    pub due_date: DateTime<Utc>,
+    pub funded_at: Option<DateTime<Utc>>,
+    pub repaid_at: Option<DateTime<Utc>>,
+    pub liquidated_at: Option<DateTime<Utc>>,
+    pub principal_outstanding: i64,
+    pub interest_paid: i64,
+    pub funded_satoshis: i64,
+    pub prepayment_option: String,
+    pub prepayment_penalty_bps: i32,
+    pub offer_expires_at: Option<DateTime<Utc>>,
+    /// Product the loan was originated under; its policy is copied into the two fields below
+    pub product_code: Option<String>,
+    pub late_fee_daily_bps: i32,
+    pub overdue_grace_days: i32,
+    /// Registered at origination; released collateral is only ever sent here
+    #[serde(skip_serializing)]
+    pub collateral_return_address: Option<String>,
+}
+
+impl Loan {
+    /// Term in whole days, as requested at origination
+    pub fn duration_days(&self) -> i64 {
+        (self.due_date - self.created_at).num_days()
+    }
+
+    /// Insert a new loan; outstanding principal starts at the full amount
+    pub async fn insert<'e, E: PgExecutor<'e>>(&self, executor: E) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r#"
+            INSERT INTO loans (
+                id, borrower_paymail, lender_paymail, principal_satoshis,
+                collateral_satoshis, interest_rate_bps, interest_accrued,
+                status, created_at, due_date, principal_outstanding, interest_accrued_through,
+                prepayment_option, prepayment_penalty_bps, offer_expires_at,
+                product_code, late_fee_daily_bps, overdue_grace_days, collateral_return_address
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $9, $12, $13, $14, $15, $16, $17, $18)
+            "#
+        )
+        .bind(self.id)
+        .bind(&self.borrower_paymail)
+        .bind(&self.lender_paymail)
+        .bind(self.principal_satoshis)
+        .bind(self.collateral_satoshis)
+        .bind(self.interest_rate_bps)
+        .bind(self.interest_accrued)
+        .bind(&self.status)
+        .bind(self.created_at)
+        .bind(self.due_date)
+        .bind(self.principal_outstanding)
+        .bind(&self.prepayment_option)
+        .bind(self.prepayment_penalty_bps)
+        .bind(self.offer_expires_at)
+        .bind(&self.product_code)
+        .bind(self.late_fee_daily_bps)
+        .bind(self.overdue_grace_days)
+        .bind(&self.collateral_return_address)
+        .execute(executor)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn find<'e, E: PgExecutor<'e>>(executor: E, loan_id: Uuid) -> Result<Option<Loan>, sqlx::Error> {
+        sqlx::query_as::<_, Loan>(&format!("SELECT {} FROM loans WHERE id = $1", LOAN_COLUMNS))
+            .bind(loan_id)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// Loans `paymail` borrowed, newest first
+    pub async fn for_borrower<'e, E: PgExecutor<'e>>(executor: E, paymail: &str) -> Result<Vec<Loan>, sqlx::Error> {
+        sqlx::query_as::<_, Loan>(&format!(
+            "SELECT {} FROM loans WHERE borrower_paymail = $1 ORDER BY created_at DESC",
+            LOAN_COLUMNS
+        ))
+        .bind(paymail)
+        .fetch_all(executor)
+        .await
+    }
+
+    /// Loans `paymail` has a live or settled participation in, newest first
+    pub async fn for_lender<'e, E: PgExecutor<'e>>(executor: E, paymail: &str) -> Result<Vec<Loan>, sqlx::Error> {
+        sqlx::query_as::<_, Loan>(&format!(
+            r#"
+            SELECT {} FROM loans
+            WHERE id IN (
+                SELECT loan_id FROM loan_participations
+                WHERE lender_paymail = $1 AND status <> 'Released'
+            )
+            ORDER BY created_at DESC
+            "#,
+            LOAN_COLUMNS
+        ))
+        .bind(paymail)
+        .fetch_all(executor)
+        .await
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize, sqlx::FromRow)]
+pub struct LoanStats {
+    pub total_borrowed: i64,
+    pub total_lent: i64,
+    pub active_borrowed_count: i64,
+    pub active_lent_count: i64,
+    pub pending_count: i64,
+    pub repaid_count: i64,
+    pub liquidated_count: i64,
+    pub total_interest_earned: i64,
+    pub total_interest_paid: i64,
+}
+
+impl LoanStats {
+    /// Totals over the loans `paymail` borrowed and the participations it lent
+    pub async fn for_user<'e, E: PgExecutor<'e>>(executor: E, paymail: &str) -> Result<LoanStats, sqlx::Error> {
+        sqlx::query_as::<_, LoanStats>(
+            r#"
+            WITH borrowed AS (
+                SELECT status, principal_satoshis, interest_paid FROM loans WHERE borrower_paymail = $1
+            ),
+            lent AS (
+                SELECT l.status, p.amount_satoshis, p.interest_received
+                FROM loan_participations p
+                JOIN loans l ON l.id = p.loan_id
+                WHERE p.lender_paymail = $1 AND p.status <> 'Released'
+            ),
+            involved AS (
+                SELECT status FROM borrowed
+                UNION ALL
+                SELECT status FROM lent
+            )
+            SELECT
+                (SELECT COALESCE(SUM(principal_satoshis), 0)::BIGINT FROM borrowed) AS total_borrowed,
+                (SELECT COALESCE(SUM(amount_satoshis), 0)::BIGINT FROM lent) AS total_lent,
+                (SELECT COUNT(*) FROM borrowed WHERE status IN ('Active', 'MarginCalled')) AS active_borrowed_count,
+                (SELECT COUNT(*) FROM lent WHERE status IN ('Active', 'MarginCalled')) AS active_lent_count,
+                (SELECT COUNT(*) FROM involved WHERE status = 'Pending') AS pending_count,
+                (SELECT COUNT(*) FROM involved WHERE status = 'Repaid') AS repaid_count,
+                (SELECT COUNT(*) FROM involved WHERE status = 'Liquidated') AS liquidated_count,
+                (SELECT COALESCE(SUM(interest_received), 0)::BIGINT FROM lent) AS total_interest_earned,
+                (SELECT COALESCE(SUM(interest_paid), 0)::BIGINT FROM borrowed) AS total_interest_paid
+            "#
+        )
+        .bind(paymail)
+        .fetch_one(executor)
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use sqlx::PgPool;
+
+    fn pending(borrower: &str, principal: i64) -> Loan {
+        let now = Utc::now();
+        Loan {
+            id: Uuid::new_v4(),
+            borrower_paymail: borrower.to_string(),
+            lender_paymail: None,
+            principal_satoshis: principal,
+            collateral_satoshis: principal * 2,
+            interest_rate_bps: 1_000,
+            interest_accrued: principal / 100,
+            status: "Pending".to_string(),
+            created_at: now,
+            due_date: now + Duration::days(30),
+            funded_at: None,
+            repaid_at: None,
+            liquidated_at: None,
+            principal_outstanding: principal,
+            interest_paid: 0,
+            funded_satoshis: 0,
+            prepayment_option: "standard".to_string(),
+            prepayment_penalty_bps: 0,
+            offer_expires_at: Some(now + Duration::days(14)),
+            product_code: Some("standard".to_string()),
+            late_fee_daily_bps: 100,
+            overdue_grace_days: 7,
+            collateral_return_address: None,
+        }
+    }
+
+    #[test]
+    fn test_duration_days() {
+        assert_eq!(pending("alice@example.com", 100_000).duration_days(), 30);
+    }
+
+    #[sqlx::test(migrations = "../../db/migrations")]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_created_loans_read_back_through_history(pool: PgPool) {
+        let loan = pending("alice@example.com", 100_000);
+        loan.insert(&pool).await.unwrap();
+
+        let borrowed = Loan::for_borrower(&pool, "alice@example.com").await.unwrap();
+        assert_eq!(borrowed.len(), 1);
+        assert_eq!(borrowed[0].id, loan.id);
+        assert_eq!(borrowed[0].principal_satoshis, 100_000);
+        assert_eq!(borrowed[0].interest_rate_bps, 1_000);
+        assert_eq!(borrowed[0].principal_outstanding, 100_000);
+        assert_eq!(borrowed[0].duration_days(), 30);
+        assert!(Loan::for_lender(&pool, "bob@example.com").await.unwrap().is_empty());
+    }
+
+    #[sqlx::test(migrations = "../../db/migrations")]
+    #[ignore = "requires DATABASE_URL"]
+    async fn test_stats_follow_participations(pool: PgPool) {
+        let loan = pending("alice@example.com", 100_000);
+        loan.insert(&pool).await.unwrap();
+        sqlx::query(
+            r#"
+            INSERT INTO loan_participations (loan_id, lender_paymail, amount_satoshis, status, interest_received)
+            VALUES ($1, 'bob@example.com', 60000, 'Active', 600), ($1, 'carol@example.com', 40000, 'Active', 400)
+            "#
+        )
+        .bind(loan.id)
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("UPDATE loans SET status = 'Active', funded_satoshis = principal_satoshis, funded_at = NOW() WHERE id = $1")
+            .bind(loan.id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let lent = Loan::for_lender(&pool, "carol@example.com").await.unwrap();
+        assert_eq!(lent.len(), 1);
+        assert!(lent[0].funded_at.is_some());
+
+        let stats = LoanStats::for_user(&pool, "bob@example.com").await.unwrap();
+        assert_eq!(stats.total_lent, 60_000);
+        assert_eq!(stats.active_lent_count, 1);
+        assert_eq!(stats.total_interest_earned, 600);
+        assert_eq!(stats.total_borrowed, 0);
+
+        let stats = LoanStats::for_user(&pool, "alice@example.com").await.unwrap();
+        assert_eq!(stats.total_borrowed, 100_000);
+        assert_eq!(stats.active_borrowed_count, 1);
+    }
+}
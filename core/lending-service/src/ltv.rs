@@ -0,0 +1,205 @@
+// core/lending-service/src/ltv.rs
+// Loan-to-value monitoring of active loans against configurable thresholds
+
+use bsv_bank_common::{interval_from_env, run_every};
+use chrono::Utc;
+use serde::Serialize;
+use sqlx::PgPool;
+
+use crate::events;
+use crate::margin::{self, MarginPolicy, MarginReason};
+use crate::oracle::{PriceOracle, PriceQuote};
+use crate::repayments::{LoanBalance, BALANCE_COLUMNS};
+
+#[derive(sqlx::FromRow)]
+struct MonitoredLoan {
+    #[sqlx(flatten)]
+    balance: LoanBalance,
+    ltv_band: String,
+    margin_call_reason: Option<String>,
+}
+
+/// Where a loan's LTV sits relative to the thresholds; stored as `loans.ltv_band`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LtvBand {
+    Healthy,
+    Warning,
+    MarginCall,
+    Liquidation,
+}
+
+impl LtvBand {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Healthy => "healthy",
+            Self::Warning => "warning",
+            Self::MarginCall => "margin_call",
+            Self::Liquidation => "liquidation",
+        }
+    }
+}
+
+/// LTV thresholds in basis points. Origination requires 150% collateral, an LTV of 6,667.
+#[derive(Debug, Clone, Copy)]
+pub struct LtvThresholds {
+    pub warning_bps: i64,
+    pub margin_call_bps: i64,
+    pub liquidation_bps: i64,
+}
+
+impl LtvThresholds {
+    /// `LTV_WARNING_BPS`, `LTV_MARGIN_CALL_BPS` and `LTV_LIQUIDATION_BPS`
+    pub fn from_env() -> Self {
+        let env = |name: &str, default: i64| {
+            std::env::var(name).ok().and_then(|s| s.parse().ok()).unwrap_or(default)
+        };
+        Self {
+            warning_bps: env("LTV_WARNING_BPS", 7_000),
+            margin_call_bps: env("LTV_MARGIN_CALL_BPS", 7_500),
+            // 120% collateral
+            liquidation_bps: env("LTV_LIQUIDATION_BPS", 8_333),
+        }
+    }
+
+    pub fn band(&self, ltv_bps: i64) -> LtvBand {
+        if ltv_bps >= self.liquidation_bps {
+            LtvBand::Liquidation
+        } else if ltv_bps >= self.margin_call_bps {
+            LtvBand::MarginCall
+        } else if ltv_bps >= self.warning_bps {
+            LtvBand::Warning
+        } else {
+            LtvBand::Healthy
+        }
+    }
+}
+
+/// Debt over collateral in basis points, both valued at `debt_price` and `collateral_price`.
+/// Principal and collateral are both BSV today, so the prices cancel; LTV moves with
+/// accrued interest and repayments, and the quote values the position in fiat.
+pub fn ltv_bps(debt: i64, debt_price: f64, collateral: i64, collateral_price: f64) -> i64 {
+    let collateral_value = collateral as f64 * collateral_price;
+    if collateral_value <= 0.0 {
+        return i64::MAX;
+    }
+    (debt as f64 * debt_price / collateral_value * 10_000.0).round() as i64
+}
+
+// ============================================================================
+// BACKGROUND MONITOR
+// ============================================================================
+
+/// Revalue every open loan each `LTV_CHECK_INTERVAL_SECS`, margin calling or curing as bands change
+pub async fn start_ltv_monitor(pool: PgPool, oracle: PriceOracle, policy: MarginPolicy) {
+    let period = interval_from_env("LTV_CHECK_INTERVAL_SECS", 300);
+    let (pool, oracle, policy) = (&pool, &oracle, &policy);
+    run_every("ltv-check", period, || async move {
+        let quote = match oracle.quote().await {
+            Ok(quote) => quote,
+            Err(e) => {
+                tracing::warn!("Skipping LTV check, no {} price: {}", oracle.currency(), e);
+                return Ok(());
+            }
+        };
+        check_loans(pool, &quote, policy).await
+    })
+    .await
+}
+
+async fn check_loans(pool: &PgPool, quote: &PriceQuote, policy: &MarginPolicy) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO collateral_prices (currency, price, sources, observed_at) VALUES ($1, $2, $3, $4)")
+        .bind(&quote.currency)
+        .bind(quote.price)
+        .bind(&quote.sources)
+        .bind(quote.observed_at)
+        .execute(pool)
+        .await?;
+
+    let loans = sqlx::query_as::<_, MonitoredLoan>(&format!(
+        "SELECT {}, ltv_band, margin_call_reason FROM loans WHERE status IN ('Active', 'MarginCalled')",
+        BALANCE_COLUMNS
+    ))
+    .fetch_all(pool)
+    .await?;
+
+    let now = Utc::now();
+    for MonitoredLoan { balance: loan, ltv_band: previous, margin_call_reason } in loans {
+        let debt = loan.principal_outstanding + loan.interest_to(now);
+        let ltv = ltv_bps(debt, quote.price, loan.collateral_satoshis, quote.price);
+        let band = policy.thresholds.band(ltv);
+        let collateral_value = loan.collateral_satoshis as f64 / 100_000_000.0 * quote.price;
+
+        let mut db_tx = pool.begin().await?;
+        sqlx::query(
+            r#"
+            UPDATE loans
+            SET ltv_bps = $2, ltv_band = $3, collateral_value = $4, ltv_checked_at = $5
+            WHERE id = $1
+            "#
+        )
+        .bind(loan.id)
+        .bind(ltv.min(i32::MAX as i64) as i32)
+        .bind(band.as_str())
+        .bind(collateral_value)
+        .bind(now)
+        .execute(&mut *db_tx)
+        .await?;
+
+        if previous != band.as_str() {
+            sqlx::query(
+                r#"
+                INSERT INTO loan_margin_events (loan_id, previous_band, band, ltv_bps, price, currency)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                "#
+            )
+            .bind(loan.id)
+            .bind(&previous)
+            .bind(band.as_str())
+            .bind(ltv.min(i32::MAX as i64) as i32)
+            .bind(quote.price)
+            .bind(&quote.currency)
+            .execute(&mut *db_tx)
+            .await?;
+
+            if band > LtvBand::Warning {
+                tracing::warn!("Loan {} LTV {} bps: {} -> {}", loan.id, ltv, previous, band.as_str());
+            } else {
+                tracing::info!("Loan {} LTV {} bps: {} -> {}", loan.id, ltv, previous, band.as_str());
+            }
+        }
+
+        if loan.status == "Active" && band >= LtvBand::MarginCall {
+            policy.call(&mut db_tx, &loan, MarginReason::Ltv, ltv, now).await?;
+        } else if loan.status == "MarginCalled" && margin::is_cured(margin_call_reason.as_deref(), band) {
+            policy.cure(&mut db_tx, &loan, ltv, events::ACTOR_SYSTEM).await?;
+        }
+        db_tx.commit().await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ltv_bps() {
+        // Minimum origination collateral
+        assert_eq!(ltv_bps(1_000_000, 40.0, 1_500_000, 40.0), 6_667);
+        assert_eq!(ltv_bps(1_000_000, 40.0, 0, 40.0), i64::MAX);
+        // A debt asset that doubles in price doubles the LTV
+        assert_eq!(ltv_bps(1_000, 2.0, 2_000, 1.0), 10_000);
+    }
+
+    #[test]
+    fn test_bands() {
+        let thresholds = LtvThresholds { warning_bps: 7_000, margin_call_bps: 7_500, liquidation_bps: 8_333 };
+        assert_eq!(thresholds.band(6_667), LtvBand::Healthy);
+        assert_eq!(thresholds.band(7_000), LtvBand::Warning);
+        assert_eq!(thresholds.band(8_000), LtvBand::MarginCall);
+        assert_eq!(thresholds.band(9_000), LtvBand::Liquidation);
+        assert!(LtvBand::MarginCall > LtvBand::Warning);
+    }
+}
@@ -1,7 +1,27 @@
 // core/lending-service/src/main.rs
 // Lending Service with Phase 6 Production Hardening
 
-use actix_web::{web, App, HttpResponse, HttpServer, Responder, Result, middleware};
+mod accruals;
+mod auth;
+mod collateral;
+mod events;
+mod idempotency;
+mod liquidation;
+mod listings;
+mod loans;
+mod ltv;
+mod margin;
+mod notary;
+mod offers;
+mod products;
+mod quote;
+mod rates_client;
+mod oracle;
+mod repayments;
+mod settlement;
+mod syndication;
+
+use actix_web::{web, App, HttpRequest, HttpResponse, HttpServer, Responder, Result, middleware};
 use actix_cors::Cors;
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc, Duration};
@@ -9,8 +29,8 @@ use uuid::Uuid;
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use bsv_bank_common::{
-    init_logging, ServiceMetrics,
-    validate_paymail, validate_amount,
+    init_logging, interval_from_env, run_every, AuthError, AuthMiddleware, JwtManager, LedgerError, LendingMetrics, ServiceMetrics,
+    validate_address, validate_paymail, validate_amount,
 };
 use dotenv::dotenv;
 use prometheus::Registry;
@@ -29,6 +49,28 @@ enum ServiceError {
     DatabaseError(String),
     #[error("Business logic error: {0}")]
     BusinessError(String),
+    #[error("Unauthorized: {0}")]
+    Unauthorized(String),
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+}
+
+impl From<AuthError> for ServiceError {
+    fn from(e: AuthError) -> Self {
+        match e {
+            AuthError::InsufficientPermissions | AuthError::NotAuthorizedFor(_) => ServiceError::Forbidden(e.to_string()),
+            _ => ServiceError::Unauthorized(e.to_string()),
+        }
+    }
+}
+
+impl From<LedgerError> for ServiceError {
+    fn from(e: LedgerError) -> Self {
+        match e {
+            LedgerError::InsufficientFunds { .. } => ServiceError::BusinessError(e.to_string()),
+            LedgerError::Database(e) => ServiceError::DatabaseError(e.to_string()),
+        }
+    }
 }
 
 impl actix_web::ResponseError for ServiceError {
@@ -52,6 +94,18 @@ impl actix_web::ResponseError for ServiceError {
                     "message": msg
                 }))
             }
+            ServiceError::Unauthorized(msg) => {
+                HttpResponse::Unauthorized().json(serde_json::json!({
+                    "error": "unauthorized",
+                    "message": msg
+                }))
+            }
+            ServiceError::Forbidden(msg) => {
+                HttpResponse::Forbidden().json(serde_json::json!({
+                    "error": "forbidden",
+                    "message": msg
+                }))
+            }
         }
     }
 }
@@ -67,6 +121,13 @@ pub struct LoanRequest {
     pub collateral_satoshis: i64,
     pub duration_days: i32,
     pub interest_rate_bps: i32,
+    #[serde(default)]
+    pub prepayment_option: repayments::PrepaymentOption,
+    pub prepayment_penalty_bps: Option<i32>,
+    /// Loan product setting the late fee and grace period; the default product when omitted
+    pub product: Option<String>,
+    /// Where released collateral is returned; releases cannot be sent anywhere else
+    pub collateral_return_address: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -77,6 +138,10 @@ pub struct LoanResponse {
     pub total_repayment_satoshis: i64,
     pub interest_satoshis: i64,
     pub due_date: DateTime<Utc>,
+    pub offer_expires_at: DateTime<Utc>,
+    pub product: String,
+    pub late_fee_daily_bps: i32,
+    pub overdue_grace_days: i32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -84,35 +149,6 @@ pub struct RepaymentRequest {
     pub borrower_paymail: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, sqlx::FromRow)]
-pub struct LoanHistory {
-    pub id: String,
-    pub borrower_paymail: String,
-    pub lender_paymail: Option<String>,
-    pub amount_satoshis: i64,
-    pub collateral_satoshis: i64,
-    pub interest_rate: f64,
-    pub duration_days: i32,
-    pub status: String,
-    pub created_at: DateTime<Utc>,
-    pub funded_at: Option<DateTime<Utc>>,
-    pub repaid_at: Option<DateTime<Utc>>,
-    pub liquidated_at: Option<DateTime<Utc>>,
-}
-
-#[derive(Debug, Serialize)]
-pub struct LoanStats {
-    pub total_borrowed: i64,
-    pub total_lent: i64,
-    pub active_borrowed_count: i64,
-    pub active_lent_count: i64,
-    pub pending_count: i64,
-    pub repaid_count: i64,
-    pub liquidated_count: i64,
-    pub total_interest_earned: i64,
-    pub total_interest_paid: i64,
-}
-
 struct AppState {
     db_pool: PgPool,
     start_time: SystemTime,
@@ -129,6 +165,11 @@ fn calculate_collateral_ratio(collateral: i64, principal: i64) -> f64 {
     collateral as f64 / principal as f64
 }
 
+/// Collateral required at origination, as a multiple of principal
+const MIN_COLLATERAL_RATIO: f64 = 1.5;
+/// Cap on the early payoff penalty: 10% of the outstanding principal
+const MAX_PREPAYMENT_PENALTY_BPS: i32 = 1_000;
+
 fn bps_to_rate(bps: i32) -> f64 {
     bps as f64 / 10000.0
 }
@@ -138,21 +179,39 @@ fn validate_loan_request(request: &LoanRequest) -> Result<(), ServiceError> {
     validate_paymail(&request.borrower_paymail)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
     
+    validate_loan_terms(
+        request.amount_satoshis,
+        request.collateral_satoshis,
+        request.duration_days,
+        request.interest_rate_bps,
+    )?;
+    if let Some(address) = &request.collateral_return_address {
+        validate_address(address).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+    validate_prepayment_terms(request.prepayment_option, request.prepayment_penalty_bps)
+}
+
+fn validate_loan_terms(
+    amount_satoshis: i64,
+    collateral_satoshis: i64,
+    duration_days: i32,
+    interest_rate_bps: i32,
+) -> Result<(), ServiceError> {
     // Phase 6: Validate amounts
-    validate_amount(request.amount_satoshis)
+    validate_amount(amount_satoshis)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
-    validate_amount(request.collateral_satoshis)
+    validate_amount(collateral_satoshis)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
     
     // Validate duration
-    if request.duration_days < 1 || request.duration_days > 365 {
+    if !(1..=365).contains(&duration_days) {
         return Err(ServiceError::ValidationError(
             "Duration must be between 1 and 365 days".to_string()
         ));
     }
     
     // Validate interest rate (0 to 100% APR = 0-10000 bps)
-    if request.interest_rate_bps < 0 || request.interest_rate_bps > 10000 {
+    if !(0..=10000).contains(&interest_rate_bps) {
         return Err(ServiceError::ValidationError(
             "Interest rate must be between 0 and 10000 bps (0-100% APR)".to_string()
         ));
@@ -161,63 +220,112 @@ fn validate_loan_request(request: &LoanRequest) -> Result<(), ServiceError> {
     Ok(())
 }
 
+fn validate_prepayment_terms(
+    option: repayments::PrepaymentOption,
+    penalty_bps: Option<i32>,
+) -> Result<(), ServiceError> {
+    match (option, penalty_bps) {
+        (repayments::PrepaymentOption::Penalty, Some(bps)) if (1..=MAX_PREPAYMENT_PENALTY_BPS).contains(&bps) => Ok(()),
+        (repayments::PrepaymentOption::Penalty, _) => Err(ServiceError::ValidationError(format!(
+            "Prepayment penalty must be between 1 and {} bps",
+            MAX_PREPAYMENT_PENALTY_BPS
+        ))),
+        (_, Some(_)) => Err(ServiceError::ValidationError(
+            "prepayment_penalty_bps is only allowed with the penalty option".to_string()
+        )),
+        (_, None) => Ok(()),
+    }
+}
+
 // ============================================================================
 // HANDLERS
 // ============================================================================
 
 async fn create_loan_request(
     pool: web::Data<PgPool>,
+    metrics: web::Data<LendingMetrics>,
+    http_req: HttpRequest,
     request: web::Json<LoanRequest>,
 ) -> Result<HttpResponse, ServiceError> {
     // Phase 6: Validate all inputs
     validate_loan_request(&request)?;
+    auth::require_paymail(&http_req, &request.borrower_paymail)?;
     
     let collateral_ratio = calculate_collateral_ratio(
         request.collateral_satoshis,
         request.amount_satoshis
     );
     
-    if collateral_ratio < 1.5 {
+    if collateral_ratio < MIN_COLLATERAL_RATIO {
         return Err(ServiceError::BusinessError(format!(
             "Insufficient collateral. Minimum 150% required. Required: {}, Provided: {}",
-            (request.amount_satoshis as f64 * 1.5) as i64,
+            (request.amount_satoshis as f64 * MIN_COLLATERAL_RATIO) as i64,
             request.collateral_satoshis
         )));
     }
     
-    let loan_id = Uuid::new_v4();
     let now = Utc::now();
     let due_date = now + Duration::days(request.duration_days as i64);
+    let offer_expires_at = now + offers::offer_window();
     let annual_rate = bps_to_rate(request.interest_rate_bps);
     let daily_rate = annual_rate / 365.0;
     let total_interest = (request.amount_satoshis as f64
         * daily_rate
         * request.duration_days as f64) as i64;
     
-    let result = sqlx::query!(
-        r#"
-        INSERT INTO loans (
-            id, borrower_paymail, lender_paymail, principal_satoshis,
-            collateral_satoshis, interest_rate_bps, interest_accrued,
-            status, created_at, due_date
-        )
-        VALUES ($1, $2, NULL, $3, $4, $5, $6, $7, $8, $9)
-        RETURNING id
-        "#,
-        loan_id,
-        request.borrower_paymail,
-        request.amount_satoshis,
-        request.collateral_satoshis,
-        request.interest_rate_bps,
-        total_interest,
-        "Pending",
-        now,
-        due_date
-    )
-    .fetch_one(pool.as_ref())
+    let mut db_tx = pool.begin().await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    // The product's policy is copied onto the loan so later product edits don't change it
+    let product = products::LoanProduct::for_origination(&mut *db_tx, request.product.as_deref()).await?;
+    // Collateral is held out of the borrower's balance for as long as the loan is open
+    settlement::ensure_available(&mut db_tx, &request.borrower_paymail, request.collateral_satoshis).await?;
+    let loan = loans::Loan {
+        id: Uuid::new_v4(),
+        borrower_paymail: request.borrower_paymail.clone(),
+        lender_paymail: None,
+        principal_satoshis: request.amount_satoshis,
+        collateral_satoshis: request.collateral_satoshis,
+        interest_rate_bps: request.interest_rate_bps,
+        interest_accrued: total_interest,
+        status: "Pending".to_string(),
+        created_at: now,
+        due_date,
+        funded_at: None,
+        repaid_at: None,
+        liquidated_at: None,
+        principal_outstanding: request.amount_satoshis,
+        interest_paid: 0,
+        funded_satoshis: 0,
+        prepayment_option: request.prepayment_option.as_str().to_string(),
+        prepayment_penalty_bps: request.prepayment_penalty_bps.unwrap_or(0),
+        offer_expires_at: Some(offer_expires_at),
+        product_code: Some(product.code.clone()),
+        late_fee_daily_bps: product.late_fee_daily_bps,
+        overdue_grace_days: product.overdue_grace_days,
+        collateral_return_address: request.collateral_return_address.clone(),
+    };
+    let loan_id = loan.id;
+    loan.insert(&mut *db_tx)
+        .await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    events::record(&mut *db_tx, loan_id, events::LOAN_CREATED, &request.borrower_paymail, Some(request.amount_satoshis), serde_json::json!({
+        "collateral_satoshis": request.collateral_satoshis,
+        "interest_rate_bps": request.interest_rate_bps,
+        "duration_days": request.duration_days,
+        "due_date": due_date,
+        "prepayment_option": request.prepayment_option,
+        "prepayment_penalty_bps": request.prepayment_penalty_bps.unwrap_or(0),
+        "offer_expires_at": offer_expires_at,
+        "product": product.code,
+        "late_fee_daily_bps": product.late_fee_daily_bps,
+        "overdue_grace_days": product.overdue_grace_days
+    }))
     .await
     .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    db_tx.commit().await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
     
+    metrics.record_loan_status("Pending", request.amount_satoshis);
     tracing::info!("Loan created: {} for {}", loan_id, request.borrower_paymail);
     
     Ok(HttpResponse::Ok().json(LoanResponse {
@@ -227,151 +335,34 @@ async fn create_loan_request(
         total_repayment_satoshis: request.amount_satoshis + total_interest,
         interest_satoshis: total_interest,
         due_date,
+        offer_expires_at,
+        product: product.code,
+        late_fee_daily_bps: product.late_fee_daily_bps,
+        overdue_grace_days: product.overdue_grace_days,
     }))
 }
 
-async fn get_available_loans(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
-    let result = sqlx::query!(
-        r#"
-        SELECT
-            id, borrower_paymail, principal_satoshis,
-            collateral_satoshis, interest_rate_bps, due_date
-        FROM loans
-        WHERE status = 'Pending'
-        ORDER BY created_at DESC
-        LIMIT 50
-        "#,
-    )
-    .fetch_all(pool.as_ref())
-    .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
-    
-    let loan_list: Vec<_> = result.iter().map(|loan| {
-        serde_json::json!({
-            "loan_id": loan.id,
-            "borrower": loan.borrower_paymail,
-            "amount": loan.principal_satoshis,
-            "collateral": loan.collateral_satoshis,
-            "collateral_ratio": calculate_collateral_ratio(
-                loan.collateral_satoshis,
-                loan.principal_satoshis
-            ),
-            "interest_rate_percent": bps_to_rate(loan.interest_rate_bps) * 100.0,
-            "due_date": loan.due_date
-        })
-    }).collect();
-    
-    Ok(HttpResponse::Ok().json(loan_list))
-}
-
-async fn get_user_loans(
-    pool: web::Data<PgPool>,
-    paymail: web::Path<String>,
-) -> Result<HttpResponse, ServiceError> {
-    // Phase 6: Validate paymail
-    validate_paymail(&paymail)
-        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
-    
-    let result = sqlx::query!(
-        r#"
-        SELECT
-            id, borrower_paymail, lender_paymail, principal_satoshis,
-            collateral_satoshis, interest_rate_bps, interest_accrued,
-            status, created_at, due_date, repaid_at
-        FROM loans
-        WHERE borrower_paymail = $1 OR lender_paymail = $1
-        ORDER BY created_at DESC
-        "#,
-        paymail.as_str()
-    )
-    .fetch_all(pool.as_ref())
-    .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
-    
-    let loan_list: Vec<_> = result.iter().map(|loan| {
-        let total_due = loan.principal_satoshis + loan.interest_accrued;
-        serde_json::json!({
-            "loan_id": loan.id,
-            "borrower": loan.borrower_paymail,
-            "lender": loan.lender_paymail,
-            "principal": loan.principal_satoshis,
-            "interest": loan.interest_accrued,
-            "total_due": total_due,
-            "collateral": loan.collateral_satoshis,
-            "status": loan.status,
-            "created_at": loan.created_at,
-            "due_date": loan.due_date,
-            "repaid_at": loan.repaid_at
-        })
-    }).collect();
-    
-    Ok(HttpResponse::Ok().json(loan_list))
-}
-
-async fn fund_loan(
-    pool: web::Data<PgPool>,
-    loan_id: web::Path<Uuid>,
-    lender: web::Json<serde_json::Value>,
-) -> Result<HttpResponse, ServiceError> {
-    let lender_paymail = lender.get("lender_paymail")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ServiceError::ValidationError("lender_paymail required".to_string()))?;
-    
-    // Phase 6: Validate lender paymail
-    validate_paymail(lender_paymail)
-        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
-    
-    let result = sqlx::query!(
-        r#"
-        UPDATE loans
-        SET lender_paymail = $1, status = 'Active'
-        WHERE id = $2 AND status = 'Pending'
-        RETURNING id
-        "#,
-        lender_paymail,
-        loan_id.as_ref()
-    )
-    .fetch_optional(pool.as_ref())
-    .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
-    
-    match result {
-        Some(_) => {
-            tracing::info!("Loan {} funded by {}", loan_id, lender_paymail);
-            Ok(HttpResponse::Ok().json(serde_json::json!({
-                "status": "success",
-                "message": "Loan funded successfully",
-                "loan_id": loan_id.as_ref()
-            })))
-        }
-        None => Err(ServiceError::BusinessError("Loan not found or already funded".to_string()))
-    }
-}
-
 async fn repay_loan(
     pool: web::Data<PgPool>,
+    metrics: web::Data<LendingMetrics>,
+    http_req: HttpRequest,
     loan_id: web::Path<Uuid>,
     request: web::Json<RepaymentRequest>,
 ) -> Result<HttpResponse, ServiceError> {
     // Phase 6: Validate borrower paymail
     validate_paymail(&request.borrower_paymail)
         .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    auth::require_paymail(&http_req, &request.borrower_paymail)?;
+    let idempotency = idempotency::Idempotency::from_request(&http_req, "repay", &*request)?;
     
-    // Get loan details
-    let loan = sqlx::query!(
-        r#"
-        SELECT
-            borrower_paymail, lender_paymail, principal_satoshis,
-            interest_accrued, collateral_satoshis, status, due_date
-        FROM loans
-        WHERE id = $1
-        "#,
-        loan_id.as_ref()
-    )
-    .fetch_optional(pool.as_ref())
-    .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?
-    .ok_or_else(|| ServiceError::BusinessError("Loan not found".to_string()))?;
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
+    // The row lock serializes concurrent repayments; a retry finds the loan repaid or its stored response
+    let loan = repayments::load_balance(&mut *db_tx, *loan_id, true).await?;
+    if let Some(response) = idempotency.replay(&mut db_tx, loan.id).await? {
+        return Ok(response);
+    }
     
     // Verify borrower
     if loan.borrower_paymail != request.borrower_paymail {
@@ -379,108 +370,31 @@ async fn repay_loan(
     }
     
     // Check if loan is active
-    if loan.status != "Active" {
+    if !loan.is_open() {
         return Err(ServiceError::BusinessError(format!("Loan is not active (status: {})", loan.status)));
     }
     
-    let total_due = loan.principal_satoshis + loan.interest_accrued;
-    let now = Utc::now();
-    
-    // Calculate late fee if overdue
-    let late_fee = if now > loan.due_date {
-        let days_late = (now - loan.due_date).num_days();
-        (loan.principal_satoshis as f64 * 0.01 * days_late as f64) as i64 // 1% per day
-    } else {
-        0
-    };
-    
-    let total_with_fees = total_due + late_fee;
-    
-    // Update loan status
-    sqlx::query!(
-        r#"
-        UPDATE loans
-        SET status = 'Repaid', repaid_at = $1
-        WHERE id = $2
-        RETURNING id
-        "#,
-        now,
-        loan_id.as_ref()
-    )
-    .fetch_one(pool.as_ref())
-    .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
-    
-    tracing::info!("Loan {} repaid by {}", loan_id, request.borrower_paymail);
-    
-    Ok(HttpResponse::Ok().json(serde_json::json!({
+    // Outstanding principal, interest per the loan's prepayment terms and any late fee
+    let payoff = loan.payoff(now);
+    repayments::settle_in_full(&mut db_tx, &loan, &payoff, now, &request.borrower_paymail).await?;
+    let response = idempotency.respond(&mut db_tx, loan.id, serde_json::json!({
         "status": "success",
         "message": "Loan repaid successfully",
-        "principal": loan.principal_satoshis,
-        "interest": loan.interest_accrued,
-        "late_fee": late_fee,
-        "total_paid": total_with_fees,
+        "principal": payoff.principal_satoshis,
+        "interest": payoff.interest_satoshis,
+        "late_fee": payoff.late_fee_satoshis,
+        "prepayment_penalty": payoff.prepayment_penalty_satoshis,
+        "total_paid": payoff.total_satoshis,
         "collateral_released": loan.collateral_satoshis,
         "repaid_at": now
-    })))
-}
-
-async fn check_liquidations(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
-    let now = Utc::now();
-    
-    // Find overdue loans
-    let overdue = sqlx::query!(
-        r#"
-        SELECT
-            id, borrower_paymail, lender_paymail, principal_satoshis,
-            collateral_satoshis, due_date
-        FROM loans
-        WHERE status = 'Active' AND due_date < $1
-        "#,
-        now
-    )
-    .fetch_all(pool.as_ref())
-    .await
-    .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
-    
-    let mut liquidated = Vec::new();
+    })).await?;
+    db_tx.commit().await
+        .map_err(|e| ServiceError::DatabaseError(e.to_string()))?;
     
-    for loan in overdue {
-        let days_overdue = (now - loan.due_date).num_days();
-        
-        // Liquidate if more than 7 days overdue
-        if days_overdue > 7 {
-            let result = sqlx::query!(
-                r#"
-                UPDATE loans
-                SET status = 'Liquidated', liquidated_at = $1
-                WHERE id = $2
-                RETURNING id
-                "#,
-                now,
-                loan.id
-            )
-            .fetch_optional(pool.as_ref())
-            .await;
-            
-            if result.is_ok() {
-                tracing::warn!("Loan {} liquidated - {} days overdue", loan.id, days_overdue);
-                liquidated.push(serde_json::json!({
-                    "loan_id": loan.id,
-                    "borrower": loan.borrower_paymail,
-                    "lender": loan.lender_paymail,
-                    "collateral_seized": loan.collateral_satoshis,
-                    "days_overdue": days_overdue
-                }));
-            }
-        }
-    }
+    repayments::record_repaid(&metrics, &loan, now);
+    tracing::info!("Loan {} repaid by {}", loan_id, request.borrower_paymail);
     
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "checked_at": now,
-        "liquidated_count": liquidated.len(),
-        "liquidations": liquidated
-    })))
+    Ok(response)
 }
 
 // Get all loans for a borrower
@@ -497,27 +411,17 @@ async fn get_borrower_loans(
         })));
     }
     
-    let loans = sqlx::query_as::<_, LoanHistory>(
-        r#"
-        SELECT id, borrower_paymail, lender_paymail, amount_satoshis, collateral_satoshis, 
-               interest_rate, duration_days, status, created_at, funded_at, repaid_at, liquidated_at
-        FROM loans
-        WHERE borrower_paymail = $1
-        ORDER BY created_at DESC
-        "#
-    )
-    .bind(paymail.as_str())
-    .fetch_all(pool.get_ref())
-    .await
-    .map_err(|e| {
-        tracing::error!("Database error fetching borrower loans: {}", e);
-        actix_web::error::ErrorInternalServerError("Failed to fetch loans")
-    })?;
+    let loans = loans::Loan::for_borrower(pool.get_ref(), paymail.as_str())
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error fetching borrower loans: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to fetch loans")
+        })?;
     
     Ok(HttpResponse::Ok().json(loans))
 }
 
-// Get all loans funded by a lender
+// Get all loans a lender has funded or committed to
 #[actix_web::get("/loans/lender/{paymail}")]
 async fn get_lender_loans(
     pool: web::Data<PgPool>,
@@ -531,22 +435,12 @@ async fn get_lender_loans(
         })));
     }
     
-    let loans = sqlx::query_as::<_, LoanHistory>(
-        r#"
-        SELECT id, borrower_paymail, lender_paymail, amount_satoshis, collateral_satoshis, 
-               interest_rate, duration_days, status, created_at, funded_at, repaid_at, liquidated_at
-        FROM loans
-        WHERE lender_paymail = $1
-        ORDER BY created_at DESC
-        "#
-    )
-    .bind(paymail.as_str())
-    .fetch_all(pool.get_ref())
-    .await
-    .map_err(|e| {
-        tracing::error!("Database error fetching lender loans: {}", e);
-        actix_web::error::ErrorInternalServerError("Failed to fetch loans")
-    })?;
+    let loans = loans::Loan::for_lender(pool.get_ref(), paymail.as_str())
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error fetching lender loans: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to fetch loans")
+        })?;
     
     Ok(HttpResponse::Ok().json(loans))
 }
@@ -565,43 +459,12 @@ async fn get_loan_stats(
         })));
     }
     
-    let stats = sqlx::query_as::<_, (i64, i64, i64, i64, i64, i64, i64, i64, i64)>(
-        r#"
-        SELECT
-            COALESCE(SUM(CASE WHEN borrower_paymail = $1 THEN amount_satoshis ELSE 0 END), 0) as total_borrowed,
-            COALESCE(SUM(CASE WHEN lender_paymail = $1 THEN amount_satoshis ELSE 0 END), 0) as total_lent,
-            COALESCE(COUNT(CASE WHEN borrower_paymail = $1 AND status = 'Active' THEN 1 END), 0) as active_borrowed_count,
-            COALESCE(COUNT(CASE WHEN lender_paymail = $1 AND status = 'Active' THEN 1 END), 0) as active_lent_count,
-            COALESCE(COUNT(CASE WHEN status = 'Pending' THEN 1 END), 0) as pending_count,
-            COALESCE(COUNT(CASE WHEN status = 'Repaid' THEN 1 END), 0) as repaid_count,
-            COALESCE(COUNT(CASE WHEN status = 'Liquidated' THEN 1 END), 0) as liquidated_count,
-            COALESCE(SUM(CASE WHEN lender_paymail = $1 AND status IN ('Repaid', 'Active') 
-                THEN CAST(amount_satoshis * interest_rate AS BIGINT) ELSE 0 END), 0) as total_interest_earned,
-            COALESCE(SUM(CASE WHEN borrower_paymail = $1 AND status = 'Repaid' 
-                THEN CAST(amount_satoshis * interest_rate AS BIGINT) ELSE 0 END), 0) as total_interest_paid
-        FROM loans
-        WHERE borrower_paymail = $1 OR lender_paymail = $1
-        "#
-    )
-    .bind(paymail.as_str())
-    .fetch_one(pool.get_ref())
-    .await
-    .map_err(|e| {
-        tracing::error!("Database error fetching loan stats: {}", e);
-        actix_web::error::ErrorInternalServerError("Failed to fetch statistics")
-    })?;
-    
-    let stats = LoanStats {
-        total_borrowed: stats.0,
-        total_lent: stats.1,
-        active_borrowed_count: stats.2,
-        active_lent_count: stats.3,
-        pending_count: stats.4,
-        repaid_count: stats.5,
-        liquidated_count: stats.6,
-        total_interest_earned: stats.7,
-        total_interest_paid: stats.8,
-    };
+    let stats = loans::LoanStats::for_user(pool.get_ref(), paymail.as_str())
+        .await
+        .map_err(|e| {
+            tracing::error!("Database error fetching loan stats: {}", e);
+            actix_web::error::ErrorInternalServerError("Failed to fetch statistics")
+        })?;
     
     Ok(HttpResponse::Ok().json(stats))
 }
@@ -613,6 +476,19 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
         .service(get_loan_stats);
 }
 
+async fn start_active_loans_refresh(pool: PgPool, metrics: LendingMetrics, period: std::time::Duration) {
+    run_every("active-loans-gauge", period, || async {
+        let count = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM loans WHERE status IN ('Active', 'MarginCalled')"
+        )
+        .fetch_one(&pool)
+        .await?;
+        metrics.set_active_loans(count);
+        Ok::<_, sqlx::Error>(())
+    })
+    .await
+}
+
 // ============================================================================
 // HEALTH & METRICS HANDLERS
 // ============================================================================
@@ -627,7 +503,7 @@ async fn health_check(data: web::Data<AppState>) -> impl Responder {
         "service": "lending-service",
         "status": "healthy",
         "version": "0.2.0",
-        "features": ["repayment", "liquidation", "phase6-validation"],
+        "features": ["quotes", "repayment", "partial-repayment", "ltv-monitoring", "margin-calls", "collateral-release", "syndication", "cancellation", "offer-expiry", "loan-products", "ledger-settlement", "interest-postings", "notarization", "loan-events", "liquidation", "phase6-validation"],
         "uptime_seconds": uptime
     }))
 }
@@ -666,7 +542,7 @@ async fn metrics_handler(registry: web::Data<Registry>) -> Result<HttpResponse,
     let metric_families = registry.gather();
     let mut buffer = vec![];
     encoder.encode(&metric_families, &mut buffer)
-        .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        .map_err(actix_web::error::ErrorInternalServerError)?;
     
     Ok(HttpResponse::Ok()
         .content_type("text/plain; version=0.0.4")
@@ -712,10 +588,51 @@ async fn main() -> std::io::Result<()> {
     let registry = Registry::new();
     let _service_metrics = ServiceMetrics::new(&registry, "lending_service")
         .expect("Failed to create service metrics");
-    let _lending_metrics = bsv_bank_common::LendingMetrics::new(&registry)
+    let lending_metrics = LendingMetrics::new(&registry)
         .expect("Failed to create lending metrics");
+    let liquidation_metrics = liquidation::LiquidationMetrics::new(&registry, lending_metrics.clone())
+        .expect("Failed to create liquidation metrics");
     tracing::info!("Metrics initialized");
     
+    tokio::spawn(start_active_loans_refresh(
+        db_pool.clone(),
+        lending_metrics.clone(),
+        interval_from_env("ACTIVE_LOANS_REFRESH_SECS", 30),
+    ));
+    
+    // Revalue active loans against the collateral price
+    let oracle = oracle::PriceOracle::from_env();
+    tracing::info!("LTV monitor pricing collateral in {}", oracle.currency());
+    let margin_policy = margin::MarginPolicy::from_env();
+    tokio::spawn(ltv::start_ltv_monitor(db_pool.clone(), oracle, margin_policy.clone()));
+    
+    // Liquidate lapsed margin calls on a schedule
+    let liquidation_engine = liquidation::LiquidationEngine::new(db_pool.clone(), margin_policy.clone(), liquidation_metrics);
+    tokio::spawn(liquidation_engine.clone().start());
+    let margin_policy = web::Data::new(margin_policy);
+    
+    // Release commitments to loans that did not fill in time, and expire requests nobody funded
+    tokio::spawn(syndication::start_funding_expiry(db_pool.clone(), lending_metrics.clone()));
+    tokio::spawn(offers::start_offer_expiry(db_pool.clone(), lending_metrics.clone()));
+    
+    // Credit lenders the interest their loans accrue
+    tokio::spawn(accruals::start_interest_postings(db_pool.clone()));
+    
+    // Requests that move funds carry bearer tokens issued by the auth service
+    let jwt_secret = std::env::var("JWT_SECRET")
+        .unwrap_or_else(|_| {
+            println!("⚠️  JWT_SECRET not set, using development default");
+            "development-secret-change-in-production".to_string()
+        });
+    let jwt_manager = JwtManager::new(jwt_secret);
+    
+    // Anchor agreements every party has signed
+    tokio::spawn(notary::Notary::from_env(jwt_manager.clone()).start(db_pool.clone()));
+    let liquidation_engine = web::Data::new(liquidation_engine);
+    let escrow = web::Data::new(collateral::Escrow::from_env());
+    let rates_client = web::Data::new(rates_client::RatesClient::from_env());
+    let lending_metrics = web::Data::new(lending_metrics);
+    
     // Application state
     let app_state = web::Data::new(AppState {
         db_pool: db_pool.clone(),
@@ -724,10 +641,12 @@ async fn main() -> std::io::Result<()> {
     
     let registry_data = web::Data::new(registry);
     
+    let jwt_manager = web::Data::new(jwt_manager);
+    
     println!("✅ Service ready on http://0.0.0.0:{}", port);
     println!("📋 Health: http://0.0.0.0:{}/health", port);
     println!("📊 Metrics: http://0.0.0.0:{}/metrics", port);
-    println!("📋 Endpoints: /loans/request, /loans/available, /loans/{{id}}/fund, /loans/{{id}}/repay");
+    println!("📋 Endpoints: /loans/request, /loans/available, /loans/{{id}}/fund, /loans/{{id}}/repay, /loans/{{id}}/repayments, /loans/{{id}}/schedule, /loans/{{id}}/collateral/add, /loans/{{id}}/collateral/release");
     tracing::info!("Starting HTTP server...");
     
     HttpServer::new(move || {
@@ -740,9 +659,11 @@ async fn main() -> std::io::Result<()> {
                 actix_web::http::header::AUTHORIZATION,
                 actix_web::http::header::CONTENT_TYPE,
             ])
+            .expose_headers(vec![listings::NEXT_CURSOR_HEADER, listings::PAGE_LIMIT_HEADER])
             .max_age(3600);
         
         App::new()
+            .wrap(AuthMiddleware::new(jwt_manager.get_ref().clone(), auth::is_public_route))
             .wrap(cors)
             // Phase 6: Request logging
             .wrap(middleware::Logger::default())
@@ -756,6 +677,12 @@ async fn main() -> std::io::Result<()> {
             .app_data(web::Data::new(db_pool.clone()))
             .app_data(app_state.clone())
             .app_data(registry_data.clone())
+            .app_data(margin_policy.clone())
+            .app_data(liquidation_engine.clone())
+            .app_data(escrow.clone())
+            .app_data(rates_client.clone())
+            .app_data(lending_metrics.clone())
+            .app_data(jwt_manager.clone())
             // Health endpoints (no auth)
             .route("/health", web::get().to(health_check))
             .route("/liveness", web::get().to(liveness_check))
@@ -764,11 +691,27 @@ async fn main() -> std::io::Result<()> {
             .route("/metrics", web::get().to(metrics_handler))
             // Business endpoints
             .route("/loans/request", web::post().to(create_loan_request))
-            .route("/loans/available", web::get().to(get_available_loans))
-            .route("/loans/my-loans/{paymail}", web::get().to(get_user_loans))
-            .route("/loans/{id}/fund", web::post().to(fund_loan))
+            .route("/loans/quote", web::post().to(quote::quote_loan))
+            .route("/loans/available", web::get().to(listings::get_available_loans))
+            .route("/loans/products", web::get().to(products::list_products))
+            .route("/loans/products", web::post().to(products::create_product))
+            .route("/loans/products/{code}", web::get().to(products::get_product))
+            .route("/loans/products/{code}", web::put().to(products::update_product))
+            .route("/loans/my-loans/{paymail}", web::get().to(listings::get_user_loans))
+            .route("/loans/{id}/cancel", web::post().to(offers::cancel_loan))
+            .route("/loans/{id}/fund", web::post().to(syndication::fund_loan))
+            .route("/loans/{id}/participations", web::get().to(syndication::get_participations))
+            .route("/loans/participations/{paymail}", web::get().to(syndication::get_lender_participations))
+            .route("/loans/interest-postings/{paymail}", web::get().to(accruals::get_lender_postings))
             .route("/loans/{id}/repay", web::post().to(repay_loan))
-            .route("/loans/liquidations/check", web::post().to(check_liquidations))
+            .route("/loans/{id}/repayments", web::post().to(repayments::record_repayment))
+            .route("/loans/{id}/schedule", web::get().to(repayments::get_schedule))
+            .route("/loans/{id}/events", web::get().to(events::get_loan_events))
+            .route("/loans/{id}/terms", web::get().to(notary::get_terms))
+            .route("/loans/{id}/accept", web::post().to(notary::accept_terms))
+            .route("/loans/{id}/collateral/add", web::post().to(collateral::add_collateral))
+            .route("/loans/{id}/collateral/release", web::post().to(collateral::release_collateral))
+            .route("/loans/liquidations/check", web::post().to(liquidation::check_liquidations))
             .configure(configure_routes)
     })
     .bind(("0.0.0.0", port))?
@@ -0,0 +1,235 @@
+// core/lending-service/src/margin.rs
+// Margin calls: cure deadlines and borrower notifications
+
+use bsv_bank_common::{sign_payload, webhook};
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::events;
+use crate::ltv::{LtvBand, LtvThresholds};
+use crate::repayments::LoanBalance;
+
+pub const EVENT_MARGIN_CALL: &str = "loan.margin_call";
+pub const EVENT_MARGIN_CURED: &str = "loan.margin_cured";
+pub const EVENT_LIQUIDATED: &str = "loan.liquidated";
+
+/// Why a loan was margin called; stored as `loans.margin_call_reason`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarginReason {
+    /// LTV crossed the margin call threshold; cured by collateral or repayment
+    Ltv,
+    /// Past the overdue grace period; cured only by repaying in full
+    Overdue,
+}
+
+impl MarginReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Ltv => "ltv",
+            Self::Overdue => "overdue",
+        }
+    }
+}
+
+// ============================================================================
+// NOTIFICATIONS
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+struct NotificationEnvelope<'a> {
+    id: Uuid,
+    event: &'a str,
+    loan_id: Uuid,
+    borrower_paymail: &'a str,
+    timestamp: DateTime<Utc>,
+    data: &'a serde_json::Value,
+}
+
+/// Posts signed loan events to the notification service, which reaches the borrower
+#[derive(Clone)]
+pub struct LoanNotifier {
+    client: reqwest::Client,
+    url: Option<String>,
+    secret: String,
+}
+
+impl LoanNotifier {
+    /// `NOTIFICATION_WEBHOOK_URL` and `NOTIFICATION_WEBHOOK_SECRET`; unset means log only
+    pub fn from_env() -> Self {
+        let url = std::env::var("NOTIFICATION_WEBHOOK_URL").ok().filter(|url| !url.is_empty());
+        if url.is_none() {
+            tracing::warn!("NOTIFICATION_WEBHOOK_URL not set, borrower notifications are only logged");
+        }
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            url,
+            secret: std::env::var("NOTIFICATION_WEBHOOK_SECRET").unwrap_or_default(),
+        }
+    }
+
+    /// Deliver in the background so callers never wait on the receiver
+    pub fn notify(&self, event: &'static str, loan: &LoanBalance, data: serde_json::Value) {
+        tracing::info!("Notifying {} of {} on loan {}", loan.borrower_paymail, event, loan.id);
+        let Some(url) = self.url.clone() else { return };
+
+        let body = serde_json::to_string(&NotificationEnvelope {
+            id: Uuid::new_v4(),
+            event,
+            loan_id: loan.id,
+            borrower_paymail: &loan.borrower_paymail,
+            timestamp: Utc::now(),
+            data: &data,
+        })
+        .unwrap_or_default();
+        let notifier = self.clone();
+        let loan_id = loan.id;
+
+        tokio::spawn(async move {
+            let timestamp = Utc::now().timestamp();
+            let result = notifier
+                .client
+                .post(&url)
+                .header("Content-Type", "application/json")
+                .header(webhook::EVENT_HEADER, event)
+                .header(webhook::TIMESTAMP_HEADER, timestamp.to_string())
+                .header(webhook::SIGNATURE_HEADER, sign_payload(&notifier.secret, timestamp, &body))
+                .body(body)
+                .send()
+                .await;
+            match result {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => tracing::warn!("{} notification for loan {}: status {}", event, loan_id, response.status()),
+                Err(e) => tracing::warn!("{} notification for loan {} failed: {}", event, loan_id, e),
+            }
+        });
+    }
+}
+
+// ============================================================================
+// POLICY
+// ============================================================================
+
+#[derive(Clone)]
+pub struct MarginPolicy {
+    pub thresholds: LtvThresholds,
+    /// Time the borrower has to cure a margin call before liquidation
+    pub cure_period: Duration,
+    pub notifier: LoanNotifier,
+}
+
+impl MarginPolicy {
+    /// `MARGIN_CALL_CURE_HOURS` (default 72) plus the LTV thresholds and notifier settings
+    pub fn from_env() -> Self {
+        let cure_hours = std::env::var("MARGIN_CALL_CURE_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(72);
+        Self {
+            thresholds: LtvThresholds::from_env(),
+            cure_period: Duration::hours(cure_hours),
+            notifier: LoanNotifier::from_env(),
+        }
+    }
+
+    /// Move an active loan to `MarginCalled` with a cure deadline. Returns None when
+    /// the loan left `Active` since it was read, e.g. repaid by a concurrent request.
+    pub async fn call(
+        &self,
+        db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        loan: &LoanBalance,
+        reason: MarginReason,
+        ltv_bps: i64,
+        now: DateTime<Utc>,
+    ) -> Result<Option<DateTime<Utc>>, sqlx::Error> {
+        let deadline = now + self.cure_period;
+        let called = sqlx::query(
+            r#"
+            UPDATE loans
+            SET status = 'MarginCalled', margin_called_at = $2,
+                margin_call_deadline = $3, margin_call_reason = $4
+            WHERE id = $1 AND status = 'Active'
+            "#
+        )
+        .bind(loan.id)
+        .bind(now)
+        .bind(deadline)
+        .bind(reason.as_str())
+        .execute(&mut **db_tx)
+        .await?
+        .rows_affected();
+        if called == 0 {
+            return Ok(None);
+        }
+        events::record(&mut **db_tx, loan.id, events::MARGIN_CALL, events::ACTOR_SYSTEM, None, serde_json::json!({
+            "reason": reason.as_str(),
+            "ltv_bps": ltv_bps,
+            "deadline": deadline
+        }))
+        .await?;
+
+        tracing::warn!("Loan {} margin called ({}), cure by {}", loan.id, reason.as_str(), deadline);
+        self.notifier.notify(EVENT_MARGIN_CALL, loan, serde_json::json!({
+            "reason": reason.as_str(),
+            "ltv_bps": ltv_bps,
+            "margin_call_ltv_bps": self.thresholds.margin_call_bps,
+            "principal_outstanding": loan.principal_outstanding,
+            "collateral_satoshis": loan.collateral_satoshis,
+            "deadline": deadline
+        }));
+        Ok(Some(deadline))
+    }
+
+    /// Return a margin-called loan to `Active`
+    pub async fn cure(
+        &self,
+        db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        loan: &LoanBalance,
+        ltv_bps: i64,
+        actor: &str,
+    ) -> Result<(), sqlx::Error> {
+        let cured = sqlx::query(
+            r#"
+            UPDATE loans
+            SET status = 'Active', margin_called_at = NULL,
+                margin_call_deadline = NULL, margin_call_reason = NULL
+            WHERE id = $1 AND status = 'MarginCalled'
+            "#
+        )
+        .bind(loan.id)
+        .execute(&mut **db_tx)
+        .await?
+        .rows_affected();
+        if cured == 0 {
+            return Ok(());
+        }
+        events::record(&mut **db_tx, loan.id, events::MARGIN_CURED, actor, None, serde_json::json!({ "ltv_bps": ltv_bps }))
+            .await?;
+
+        tracing::info!("Loan {} margin call cured at {} bps", loan.id, ltv_bps);
+        self.notifier.notify(EVENT_MARGIN_CURED, loan, serde_json::json!({ "ltv_bps": ltv_bps }));
+        Ok(())
+    }
+}
+
+/// Whether collateral or repayments can cure a margin call for `reason` at this LTV
+pub fn is_cured(reason: Option<&str>, band: LtvBand) -> bool {
+    reason == Some(MarginReason::Ltv.as_str()) && band < LtvBand::MarginCall
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_cured() {
+        assert!(is_cured(Some("ltv"), LtvBand::Warning));
+        assert!(!is_cured(Some("ltv"), LtvBand::MarginCall));
+        // Collateral does not cure an overdue loan
+        assert!(!is_cured(Some("overdue"), LtvBand::Healthy));
+        assert!(!is_cured(None, LtvBand::Healthy));
+    }
+}
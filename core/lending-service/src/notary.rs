@@ -0,0 +1,451 @@
+// core/lending-service/src/notary.rs
+// Loan agreement notarization: signed acceptances anchored on chain in an OP_RETURN commitment
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{interval_from_env, run_every, validate_paymail, AnchorClient, JwtManager};
+use chrono::{DateTime, Utc};
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::require_paymail;
+use crate::loans::Loan;
+use crate::ServiceError;
+
+/// First push of every anchoring OP_RETURN
+pub const PROTOCOL_PREFIX: &str = "bsvbank.loan";
+
+/// Subject of this service's tokens for transaction-builder's signing API
+const SERVICE_NAME: &str = "lending-service";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+// ============================================================================
+// TERMS AND COMMITMENT
+// ============================================================================
+
+/// What each party signs. Field order is the canonical serialization order.
+#[derive(Debug, Serialize)]
+pub struct LoanTerms<'a> {
+    pub loan_id: Uuid,
+    pub borrower_paymail: &'a str,
+    pub principal_satoshis: i64,
+    pub collateral_satoshis: i64,
+    pub interest_rate_bps: i32,
+    pub prepayment_option: &'a str,
+    pub prepayment_penalty_bps: i32,
+    pub late_fee_daily_bps: i32,
+    pub overdue_grace_days: i32,
+    pub created_at: i64,
+    pub due_date: i64,
+}
+
+impl<'a> LoanTerms<'a> {
+    pub fn of(loan: &'a Loan) -> Self {
+        Self {
+            loan_id: loan.id,
+            borrower_paymail: &loan.borrower_paymail,
+            principal_satoshis: loan.principal_satoshis,
+            collateral_satoshis: loan.collateral_satoshis,
+            interest_rate_bps: loan.interest_rate_bps,
+            prepayment_option: &loan.prepayment_option,
+            prepayment_penalty_bps: loan.prepayment_penalty_bps,
+            late_fee_daily_bps: loan.late_fee_daily_bps,
+            overdue_grace_days: loan.overdue_grace_days,
+            created_at: loan.created_at.timestamp(),
+            due_date: loan.due_date.timestamp(),
+        }
+    }
+
+    pub fn canonical(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+
+    /// SHA-256 of the canonical JSON; the 32-byte digest parties sign
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.canonical().as_bytes()))
+    }
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct Acceptance {
+    pub paymail: String,
+    /// `borrower` or `lender`
+    pub role: String,
+    pub public_key: String,
+    pub signature: String,
+    pub terms_hash: String,
+    pub accepted_at: DateTime<Utc>,
+}
+
+/// SHA-256 over the terms hash and every acceptance, ordered by role then paymail
+pub fn commitment(terms_hash: &str, acceptances: &[Acceptance]) -> String {
+    let mut parts: Vec<&Acceptance> = acceptances.iter().collect();
+    parts.sort_by(|a, b| a.role.cmp(&b.role).then(a.paymail.cmp(&b.paymail)));
+    let mut preimage = terms_hash.to_string();
+    for a in parts {
+        preimage.push_str(&format!("|{}:{}:{}:{}", a.role, a.paymail, a.public_key, a.signature));
+    }
+    hex::encode(Sha256::digest(preimage.as_bytes()))
+}
+
+/// Verify a hex DER signature over a hex terms hash with a hex-encoded pubkey
+pub fn verify_acceptance(terms_hash: &str, signature_hex: &str, pubkey_hex: &str) -> bool {
+    let Some(message) = hex::decode(terms_hash).ok().and_then(|d| Message::from_digest_slice(&d).ok()) else {
+        return false;
+    };
+    let Some(mut signature) = hex::decode(signature_hex).ok().and_then(|b| Signature::from_der(&b).ok()) else {
+        return false;
+    };
+    signature.normalize_s();
+    let Some(pubkey) = hex::decode(pubkey_hex).ok().and_then(|b| PublicKey::from_slice(&b).ok()) else {
+        return false;
+    };
+    Secp256k1::verification_only().verify_ecdsa(&message, &signature, &pubkey).is_ok()
+}
+
+// ============================================================================
+// ANCHORING CLIENT
+// ============================================================================
+
+/// Anchors loan agreements and fetches their SPV proofs
+#[derive(Clone)]
+pub struct Notary {
+    client: AnchorClient,
+}
+
+impl Notary {
+    /// Funded by `NOTARY_ADDRESS`, signed with `NOTARY_KEY_REF`
+    pub fn from_env(jwt: JwtManager) -> Self {
+        Self {
+            client: AnchorClient::from_env("NOTARY", PROTOCOL_PREFIX, SERVICE_NAME, jwt),
+        }
+    }
+
+    // ========================================================================
+    // SCHEDULE
+    // ========================================================================
+
+    pub async fn start(self, pool: PgPool) {
+        let period = interval_from_env("NOTARY_INTERVAL_SECS", 300);
+        run_every("loan-notary", period, || self.run_once(&pool)).await
+    }
+
+    async fn run_once(&self, pool: &PgPool) -> Result<(), ServiceError> {
+        enqueue(pool).await?;
+
+        let pending: Vec<(Uuid, String)> = sqlx::query_as(
+            "SELECT loan_id, commitment FROM loan_notarizations WHERE status = 'pending' ORDER BY created_at LIMIT 20"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+        for (loan_id, commitment) in pending {
+            match self.client.anchor(&commitment, &loan_id.to_string()).await {
+                Ok(txid) => {
+                    sqlx::query(
+                        r#"
+                        UPDATE loan_notarizations
+                        SET status = 'broadcast', txid = $2, broadcast_at = NOW(),
+                            attempts = attempts + 1, last_error = NULL
+                        WHERE loan_id = $1
+                        "#
+                    )
+                    .bind(loan_id)
+                    .bind(&txid)
+                    .execute(pool)
+                    .await
+                    .map_err(db_error)?;
+                    tracing::info!("Loan {} agreement anchored in {}", loan_id, txid);
+                }
+                Err(e) => {
+                    sqlx::query("UPDATE loan_notarizations SET attempts = attempts + 1, last_error = $2 WHERE loan_id = $1")
+                        .bind(loan_id)
+                        .bind(&e)
+                        .execute(pool)
+                        .await
+                        .map_err(db_error)?;
+                    tracing::warn!("Anchoring loan {} agreement failed: {}", loan_id, e);
+                }
+            }
+        }
+
+        let unproven: Vec<(Uuid, String)> = sqlx::query_as(
+            "SELECT loan_id, txid FROM loan_notarizations WHERE status = 'broadcast' ORDER BY broadcast_at LIMIT 50"
+        )
+        .fetch_all(pool)
+        .await
+        .map_err(db_error)?;
+        for (loan_id, txid) in unproven {
+            let Ok(proof) = self.client.proof(&txid).await else { continue };
+            let mut db_tx = pool.begin().await.map_err(db_error)?;
+            sqlx::query(
+                r#"
+                UPDATE loan_notarizations
+                SET status = 'anchored', spv_proof = $2, anchored_at = NOW()
+                WHERE loan_id = $1
+                "#
+            )
+            .bind(loan_id)
+            .bind(&proof)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(db_error)?;
+            sqlx::query("UPDATE loans SET notarization_txid = $2 WHERE id = $1")
+                .bind(loan_id)
+                .bind(&txid)
+                .execute(&mut *db_tx)
+                .await
+                .map_err(db_error)?;
+            db_tx.commit().await.map_err(db_error)?;
+            tracing::info!("Loan {} agreement proven in {}", loan_id, txid);
+        }
+        Ok(())
+    }
+}
+
+/// Parties whose acceptance the agreement needs: the borrower and every funding lender
+async fn parties<'e, E: sqlx::PgExecutor<'e>>(executor: E, loan: &Loan) -> Result<Vec<(String, &'static str)>, sqlx::Error> {
+    let lenders: Vec<String> = sqlx::query_scalar(
+        "SELECT lender_paymail FROM loan_participations WHERE loan_id = $1 AND status <> 'Released' ORDER BY lender_paymail"
+    )
+    .bind(loan.id)
+    .fetch_all(executor)
+    .await?;
+    let mut parties = vec![(loan.borrower_paymail.clone(), "borrower")];
+    parties.extend(lenders.into_iter().map(|l| (l, "lender")));
+    Ok(parties)
+}
+
+/// The key a party registered with their account; acceptances must verify against it
+async fn party_public_key<'e, E: sqlx::PgExecutor<'e>>(executor: E, paymail: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<String>>("SELECT public_key FROM users WHERE paymail = $1")
+        .bind(paymail)
+        .fetch_optional(executor)
+        .await
+        .map(Option::flatten)
+}
+
+async fn acceptances<'e, E: sqlx::PgExecutor<'e>>(executor: E, loan_id: Uuid) -> Result<Vec<Acceptance>, sqlx::Error> {
+    sqlx::query_as::<_, Acceptance>(
+        r#"
+        SELECT paymail, role, public_key, signature, terms_hash, accepted_at
+        FROM loan_acceptances WHERE loan_id = $1 ORDER BY accepted_at
+        "#
+    )
+    .bind(loan_id)
+    .fetch_all(executor)
+    .await
+}
+
+/// Queue funded loans whose parties have all accepted the current terms
+async fn enqueue(pool: &PgPool) -> Result<(), ServiceError> {
+    let candidates: Vec<Uuid> = sqlx::query_scalar(
+        r#"
+        SELECT l.id FROM loans l
+        WHERE l.funded_at IS NOT NULL
+          AND NOT EXISTS (SELECT 1 FROM loan_notarizations n WHERE n.loan_id = l.id)
+          AND EXISTS (SELECT 1 FROM loan_acceptances a WHERE a.loan_id = l.id AND a.role = 'borrower')
+        "#
+    )
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)?;
+
+    for loan_id in candidates {
+        let Some(loan) = Loan::find(pool, loan_id).await.map_err(db_error)? else { continue };
+        let terms_hash = LoanTerms::of(&loan).hash();
+        let accepted: Vec<Acceptance> = acceptances(pool, loan_id)
+            .await
+            .map_err(db_error)?
+            .into_iter()
+            .filter(|a| a.terms_hash == terms_hash)
+            .collect();
+        let parties = parties(pool, &loan).await.map_err(db_error)?;
+        if !parties.iter().all(|(paymail, _)| accepted.iter().any(|a| &a.paymail == paymail)) {
+            continue;
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO loan_notarizations (loan_id, terms_hash, commitment)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (loan_id) DO NOTHING
+            "#
+        )
+        .bind(loan_id)
+        .bind(&terms_hash)
+        .bind(commitment(&terms_hash, &accepted))
+        .execute(pool)
+        .await
+        .map_err(db_error)?;
+        tracing::info!("Loan {} agreement accepted by all {} parties, queued for anchoring", loan_id, parties.len());
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Notarization {
+    pub terms_hash: String,
+    pub commitment: String,
+    /// `pending`, `broadcast` once in a transaction, `anchored` once its SPV proof is stored
+    pub status: String,
+    pub txid: Option<String>,
+    pub spv_proof: Option<serde_json::Value>,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub anchored_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AcceptTermsRequest {
+    pub paymail: String,
+    /// DER ECDSA signature over the 32-byte `terms_hash` by the key registered for `paymail`, hex
+    pub signature: String,
+}
+
+/// `GET /loans/{id}/terms`: the canonical terms to sign, acceptances so far and the anchor
+pub async fn get_terms(
+    pool: web::Data<PgPool>,
+    loan_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let loan = Loan::find(pool.get_ref(), *loan_id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::BusinessError("Loan not found".to_string()))?;
+    let terms = LoanTerms::of(&loan);
+    let acceptances = acceptances(pool.get_ref(), loan.id).await.map_err(db_error)?;
+    let parties = parties(pool.get_ref(), &loan).await.map_err(db_error)?;
+    let notarization = sqlx::query_as::<_, Notarization>(
+        r#"
+        SELECT terms_hash, commitment, status, txid, spv_proof, attempts, last_error, created_at, anchored_at
+        FROM loan_notarizations WHERE loan_id = $1
+        "#
+    )
+    .bind(loan.id)
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "loan_id": loan.id,
+        "terms": terms,
+        "canonical": terms.canonical(),
+        "terms_hash": terms.hash(),
+        "parties": parties.iter().map(|(paymail, role)| serde_json::json!({ "paymail": paymail, "role": role })).collect::<Vec<_>>(),
+        "acceptances": acceptances,
+        "notarization": notarization
+    })))
+}
+
+/// `POST /loans/{id}/accept`: a party's signature over the current terms hash
+pub async fn accept_terms(
+    pool: web::Data<PgPool>,
+    http_req: HttpRequest,
+    loan_id: web::Path<Uuid>,
+    request: web::Json<AcceptTermsRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.paymail)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.paymail)?;
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let loan = Loan::find(&mut *db_tx, *loan_id)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::BusinessError("Loan not found".to_string()))?;
+    let parties = parties(&mut *db_tx, &loan).await.map_err(db_error)?;
+    let Some((_, role)) = parties.iter().find(|(paymail, _)| paymail == &request.paymail) else {
+        return Err(ServiceError::BusinessError("Only the borrower or a funding lender can accept the terms".to_string()));
+    };
+
+    let public_key = party_public_key(&mut *db_tx, &request.paymail)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::BusinessError(format!("No public key on record for {}", request.paymail)))?;
+
+    let terms_hash = LoanTerms::of(&loan).hash();
+    if !verify_acceptance(&terms_hash, &request.signature, &public_key) {
+        return Err(ServiceError::ValidationError(format!("Signature does not verify against terms hash {}", terms_hash)));
+    }
+
+    let acceptance = sqlx::query_as::<_, Acceptance>(
+        r#"
+        INSERT INTO loan_acceptances (loan_id, paymail, role, public_key, signature, terms_hash)
+        VALUES ($1, $2, $3, $4, $5, $6)
+        ON CONFLICT (loan_id, paymail) DO UPDATE
+        SET public_key = EXCLUDED.public_key, signature = EXCLUDED.signature,
+            terms_hash = EXCLUDED.terms_hash, accepted_at = NOW()
+        WHERE NOT EXISTS (SELECT 1 FROM loan_notarizations n WHERE n.loan_id = EXCLUDED.loan_id)
+        RETURNING paymail, role, public_key, signature, terms_hash, accepted_at
+        "#
+    )
+    .bind(loan.id)
+    .bind(&request.paymail)
+    .bind(role)
+    .bind(public_key.to_lowercase())
+    .bind(request.signature.to_lowercase())
+    .bind(&terms_hash)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::BusinessError("The agreement has already been notarized".to_string()))?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Loan {} terms accepted by {} ({})", loan.id, request.paymail, role);
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "success",
+        "loan_id": loan.id,
+        "acceptance": acceptance
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::SecretKey;
+
+    fn acceptance(role: &str, paymail: &str) -> Acceptance {
+        Acceptance {
+            paymail: paymail.to_string(),
+            role: role.to_string(),
+            public_key: "02aa".to_string(),
+            signature: "3044".to_string(),
+            terms_hash: "00".repeat(32),
+            accepted_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_commitment_ignores_acceptance_order() {
+        let hash = "11".repeat(32);
+        let a = vec![acceptance("lender", "bob@example.com"), acceptance("borrower", "alice@example.com")];
+        let b = vec![acceptance("borrower", "alice@example.com"), acceptance("lender", "bob@example.com")];
+        assert_eq!(commitment(&hash, &a), commitment(&hash, &b));
+        assert_ne!(commitment(&hash, &a), commitment(&"22".repeat(32), &a));
+    }
+
+    #[test]
+    fn test_verify_acceptance() {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&[7u8; 32]).unwrap();
+        let pubkey = hex::encode(PublicKey::from_secret_key(&secp, &secret).serialize());
+        let hash = hex::encode(Sha256::digest(b"terms"));
+        let message = Message::from_digest_slice(&hex::decode(&hash).unwrap()).unwrap();
+        let signature = hex::encode(secp.sign_ecdsa(&message, &secret).serialize_der());
+
+        assert!(verify_acceptance(&hash, &signature, &pubkey));
+        assert!(!verify_acceptance(&hex::encode(Sha256::digest(b"other")), &signature, &pubkey));
+        assert!(!verify_acceptance(&hash, "zz", &pubkey));
+    }
+}
@@ -0,0 +1,205 @@
+// core/lending-service/src/offers.rs
+// Pending loan requests: borrower cancellation and expiry of requests nobody funds
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{interval_from_env, run_every, validate_paymail, LendingMetrics};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::auth::require_paymail;
+use crate::events;
+use crate::settlement;
+use crate::ServiceError;
+
+const MAX_REASON_LEN: usize = 500;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Days a request stays on `/loans/available` without any funding before it expires
+pub fn offer_window() -> Duration {
+    Duration::days(
+        std::env::var("LOAN_OFFER_EXPIRY_DAYS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(14),
+    )
+}
+
+/// Close a Pending loan as `status`, releasing its collateral and returning any
+/// lender commitments from the loan account. Returns `None` when the loan is no
+/// longer Pending.
+async fn close(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan_id: Uuid,
+    status: &str,
+    event_type: &str,
+    actor: &str,
+    reason: Option<&str>,
+    now: DateTime<Utc>,
+) -> Result<Option<serde_json::Value>, sqlx::Error> {
+    let collateral: Option<i64> = sqlx::query_scalar(
+        r#"
+        UPDATE loans
+        SET status = $2, closed_at = $3, collateral_released_at = $3
+        WHERE id = $1 AND status = 'Pending'
+        RETURNING collateral_satoshis
+        "#
+    )
+    .bind(loan_id)
+    .bind(status)
+    .bind(now)
+    .fetch_optional(&mut **db_tx)
+    .await?;
+    let Some(collateral) = collateral else { return Ok(None) };
+
+    let released = sqlx::query_as::<_, (String, i64)>(
+        r#"
+        UPDATE loan_participations
+        SET status = 'Released', released_at = $2
+        WHERE loan_id = $1 AND status = 'Committed'
+        RETURNING lender_paymail, amount_satoshis
+        "#
+    )
+    .bind(loan_id)
+    .bind(now)
+    .fetch_all(&mut **db_tx)
+    .await?;
+    for (lender, amount) in &released {
+        settlement::transfer(db_tx, settlement::COMMITMENT_RELEASE, &settlement::loan_account(loan_id), lender, *amount, Some(loan_id))
+            .await?;
+    }
+    let commitments: Vec<_> = released
+        .iter()
+        .map(|(lender, amount)| serde_json::json!({ "lender_paymail": lender, "amount_satoshis": amount }))
+        .collect();
+
+    let details = serde_json::json!({
+        "reason": reason,
+        "collateral_released": collateral,
+        "commitments_released": commitments
+    });
+    events::record(&mut **db_tx, loan_id, event_type, actor, Some(collateral), details.clone()).await?;
+    Ok(Some(details))
+}
+
+// ============================================================================
+// OFFER EXPIRY
+// ============================================================================
+
+/// Expire requests that received no funding before `offer_expires_at`. Partly
+/// funded requests are left to the funding window in `syndication`.
+pub async fn start_offer_expiry(pool: PgPool, metrics: LendingMetrics) {
+    let period = interval_from_env("OFFER_EXPIRY_INTERVAL_SECS", 300);
+    run_every("loan-offer-expiry", period, || expire_offers(&pool, &metrics)).await
+}
+
+async fn expire_offers(pool: &PgPool, metrics: &LendingMetrics) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await?;
+    let stale = sqlx::query_as::<_, (Uuid, i64)>(
+        r#"
+        SELECT id, principal_satoshis FROM loans
+        WHERE status = 'Pending' AND funded_satoshis = 0 AND offer_expires_at < $1
+        FOR UPDATE SKIP LOCKED
+        "#
+    )
+    .bind(now)
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    let mut expired = Vec::new();
+    for (loan_id, principal) in stale {
+        if close(&mut db_tx, loan_id, "Expired", events::OFFER_EXPIRED, events::ACTOR_SYSTEM, None, now).await?.is_some() {
+            tracing::info!("Loan request {} expired unfunded", loan_id);
+            expired.push(principal);
+        }
+    }
+    db_tx.commit().await?;
+
+    for principal in expired {
+        metrics.record_loan_status("Expired", principal);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CancelLoanRequest {
+    pub borrower_paymail: String,
+    pub reason: Option<String>,
+}
+
+/// `POST /loans/{id}/cancel`: withdraw a Pending request before it is fully funded
+pub async fn cancel_loan(
+    pool: web::Data<PgPool>,
+    metrics: web::Data<LendingMetrics>,
+    http_req: HttpRequest,
+    loan_id: web::Path<Uuid>,
+    request: web::Json<CancelLoanRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.borrower_paymail)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.borrower_paymail)?;
+    if request.reason.as_ref().is_some_and(|r| r.len() > MAX_REASON_LEN) {
+        return Err(ServiceError::ValidationError(format!(
+            "Reason must be at most {} characters",
+            MAX_REASON_LEN
+        )));
+    }
+
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let loan = sqlx::query_as::<_, (String, String, i64)>(
+        "SELECT borrower_paymail, status, principal_satoshis FROM loans WHERE id = $1 FOR UPDATE"
+    )
+    .bind(*loan_id)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    let Some((borrower, status, principal)) = loan else {
+        return Err(ServiceError::BusinessError("Loan not found".to_string()));
+    };
+
+    if borrower != request.borrower_paymail {
+        return Err(ServiceError::BusinessError("Only the borrower can cancel the loan request".to_string()));
+    }
+    if status != "Pending" {
+        return Err(ServiceError::BusinessError(format!(
+            "Only pending loan requests can be cancelled (status: {})",
+            status
+        )));
+    }
+
+    let details = close(
+        &mut db_tx,
+        *loan_id,
+        "Cancelled",
+        events::LOAN_CANCELLED,
+        &request.borrower_paymail,
+        request.reason.as_deref(),
+        now,
+    )
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::BusinessError("Loan request is no longer pending".to_string()))?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    metrics.record_loan_status("Cancelled", principal);
+    tracing::info!("Loan request {} cancelled by {}", loan_id, request.borrower_paymail);
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "status": "success",
+        "loan_id": loan_id.as_ref(),
+        "loan_status": "Cancelled",
+        "collateral_released": details["collateral_released"],
+        "commitments_released": details["commitments_released"],
+        "cancelled_at": now
+    })))
+}
@@ -0,0 +1,185 @@
+// core/lending-service/src/oracle.rs
+// BSV price oracle: the median of several public price feeds
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+
+/// A public source of the BSV exchange rate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriceFeed {
+    WhatsOnChain,
+    CoinGecko,
+    CoinPaprika,
+}
+
+impl PriceFeed {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "whatsonchain" => Some(Self::WhatsOnChain),
+            "coingecko" => Some(Self::CoinGecko),
+            "coinpaprika" => Some(Self::CoinPaprika),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::WhatsOnChain => "whatsonchain",
+            Self::CoinGecko => "coingecko",
+            Self::CoinPaprika => "coinpaprika",
+        }
+    }
+
+    /// Price of one BSV in `currency`
+    async fn fetch(&self, client: &reqwest::Client, currency: &str) -> Result<f64, String> {
+        let currency = currency.to_lowercase();
+        let url = match self {
+            // WoC only quotes USD
+            Self::WhatsOnChain if currency == "usd" => "https://api.whatsonchain.com/v1/bsv/main/exchangerate".to_string(),
+            Self::WhatsOnChain => return Err(format!("{} is not quoted", currency)),
+            Self::CoinGecko => format!(
+                "https://api.coingecko.com/api/v3/simple/price?ids=bitcoin-cash-sv&vs_currencies={}",
+                currency
+            ),
+            Self::CoinPaprika => format!(
+                "https://api.coinpaprika.com/v1/tickers/bsv-bitcoin-sv?quotes={}",
+                currency.to_uppercase()
+            ),
+        };
+
+        let response = client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        let body: Value = response.json().await.map_err(|e| format!("Parse error: {}", e))?;
+
+        let price = match self {
+            Self::WhatsOnChain => body.get("rate").and_then(Value::as_f64),
+            Self::CoinGecko => body.pointer(&format!("/bitcoin-cash-sv/{}", currency)).and_then(Value::as_f64),
+            Self::CoinPaprika => body
+                .pointer(&format!("/quotes/{}/price", currency.to_uppercase()))
+                .and_then(Value::as_f64),
+        };
+        price
+            .filter(|p| p.is_finite() && *p > 0.0)
+            .ok_or_else(|| "No price in response".to_string())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceQuote {
+    pub currency: String,
+    /// Median of the feeds that answered
+    pub price: f64,
+    pub sources: Vec<String>,
+    pub observed_at: DateTime<Utc>,
+}
+
+pub struct PriceOracle {
+    client: reqwest::Client,
+    feeds: Vec<PriceFeed>,
+    currency: String,
+    /// Feeds that must answer for a quote to be trusted
+    min_sources: usize,
+}
+
+impl PriceOracle {
+    /// `PRICE_FEEDS` (comma separated), `PRICE_CURRENCY` and `PRICE_MIN_SOURCES`
+    pub fn from_env() -> Self {
+        let feeds: Vec<PriceFeed> = std::env::var("PRICE_FEEDS")
+            .unwrap_or_else(|_| "whatsonchain,coingecko,coinpaprika".to_string())
+            .split(',')
+            .map(|name| name.trim().to_lowercase())
+            .filter(|name| !name.is_empty())
+            .filter_map(|name| {
+                let feed = PriceFeed::parse(&name);
+                if feed.is_none() {
+                    tracing::warn!("Unknown price feed '{}' ignored", name);
+                }
+                feed
+            })
+            .collect();
+
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            min_sources: std::env::var("PRICE_MIN_SOURCES")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(2)
+                .clamp(1, feeds.len().max(1)),
+            feeds,
+            currency: std::env::var("PRICE_CURRENCY").unwrap_or_else(|_| "USD".to_string()).to_uppercase(),
+        }
+    }
+
+    pub fn currency(&self) -> &str {
+        &self.currency
+    }
+
+    /// Ask every feed and take the median, so one bad feed cannot move the price
+    pub async fn quote(&self) -> Result<PriceQuote, String> {
+        let mut prices = Vec::new();
+        let mut sources = Vec::new();
+        for feed in &self.feeds {
+            match feed.fetch(&self.client, &self.currency).await {
+                Ok(price) => {
+                    prices.push(price);
+                    sources.push(feed.name().to_string());
+                }
+                Err(e) => tracing::warn!("Price feed {} failed: {}", feed.name(), e),
+            }
+        }
+
+        if prices.len() < self.min_sources {
+            return Err(format!(
+                "{} of {} price feeds answered, {} required",
+                prices.len(),
+                self.feeds.len(),
+                self.min_sources
+            ));
+        }
+        Ok(PriceQuote {
+            currency: self.currency.clone(),
+            price: median(&mut prices).unwrap_or_default(),
+            sources,
+            observed_at: Utc::now(),
+        })
+    }
+}
+
+pub fn median(values: &mut [f64]) -> Option<f64> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    Some(if values.len().is_multiple_of(2) { (values[mid - 1] + values[mid]) / 2.0 } else { values[mid] })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median() {
+        assert_eq!(median(&mut []), None);
+        assert_eq!(median(&mut [50.0]), Some(50.0));
+        // An outlier feed does not move the price
+        assert_eq!(median(&mut [51.0, 5_000.0, 49.0]), Some(51.0));
+        assert_eq!(median(&mut [48.0, 52.0]), Some(50.0));
+    }
+
+    #[test]
+    fn test_parse_feed() {
+        assert_eq!(PriceFeed::parse("coingecko"), Some(PriceFeed::CoinGecko));
+        assert_eq!(PriceFeed::parse("binance"), None);
+    }
+}
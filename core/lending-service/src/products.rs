@@ -0,0 +1,281 @@
+// core/lending-service/src/products.rs
+// Loan products: the late fee and overdue grace policy a loan is originated under
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::auth::extract_bearer_token;
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+
+use crate::ServiceError;
+
+/// Product used when a request or quote does not name one
+pub const DEFAULT_PRODUCT: &str = "standard";
+pub const MAX_LATE_FEE_DAILY_BPS: i32 = 1_000;
+pub const MAX_OVERDUE_GRACE_DAYS: i32 = 365;
+const MAX_CODE_LEN: usize = 50;
+const MAX_NAME_LEN: usize = 100;
+
+const PRODUCT_COLUMNS: &str =
+    "code, name, description, late_fee_daily_bps, overdue_grace_days, active, created_at, updated_at";
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct LoanProduct {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Charged per day past the due date, in bps of the outstanding principal
+    pub late_fee_daily_bps: i32,
+    /// Days past the due date before an unpaid loan is margin called
+    pub overdue_grace_days: i32,
+    /// Inactive products stay on existing loans but cannot be chosen for new ones
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl LoanProduct {
+    pub async fn find<'e, E: PgExecutor<'e>>(executor: E, code: &str) -> Result<Option<LoanProduct>, sqlx::Error> {
+        sqlx::query_as::<_, LoanProduct>(&format!("SELECT {} FROM loan_products WHERE code = $1", PRODUCT_COLUMNS))
+            .bind(code)
+            .fetch_optional(executor)
+            .await
+    }
+
+    /// The active product a new loan is originated under; the default when none is named
+    pub async fn for_origination<'e, E: PgExecutor<'e>>(
+        executor: E,
+        code: Option<&str>,
+    ) -> Result<LoanProduct, ServiceError> {
+        let code = code.unwrap_or(DEFAULT_PRODUCT);
+        match LoanProduct::find(executor, code).await.map_err(db_error)? {
+            Some(product) if product.active => Ok(product),
+            Some(_) => Err(ServiceError::BusinessError(format!("Loan product {} is no longer offered", code))),
+            None => Err(ServiceError::ValidationError(format!("Unknown loan product: {}", code))),
+        }
+    }
+
+    /// Late fee and grace terms as shown in quotes
+    pub fn policy(&self) -> serde_json::Value {
+        serde_json::json!({
+            "product": self.code,
+            "late_fee_daily_bps": self.late_fee_daily_bps,
+            "overdue_grace_days": self.overdue_grace_days
+        })
+    }
+}
+
+fn validate_policy(late_fee_daily_bps: i32, overdue_grace_days: i32) -> Result<(), ServiceError> {
+    if !(0..=MAX_LATE_FEE_DAILY_BPS).contains(&late_fee_daily_bps) {
+        return Err(ServiceError::ValidationError(format!(
+            "Late fee must be between 0 and {} bps per day",
+            MAX_LATE_FEE_DAILY_BPS
+        )));
+    }
+    if !(0..=MAX_OVERDUE_GRACE_DAYS).contains(&overdue_grace_days) {
+        return Err(ServiceError::ValidationError(format!(
+            "Overdue grace period must be between 0 and {} days",
+            MAX_OVERDUE_GRACE_DAYS
+        )));
+    }
+    Ok(())
+}
+
+fn validate_code(code: &str) -> Result<(), ServiceError> {
+    let valid = !code.is_empty()
+        && code.len() <= MAX_CODE_LEN
+        && code.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(ServiceError::ValidationError(format!(
+            "Product code must be 1-{} lowercase letters, digits, '-' or '_'",
+            MAX_CODE_LEN
+        )));
+    }
+    Ok(())
+}
+
+/// Require a bearer token with the `admin` permission
+fn require_admin(req: &HttpRequest, jwt: &JwtManager) -> Result<(), ServiceError> {
+    let header = req
+        .headers()
+        .get(actix_web::http::header::AUTHORIZATION)
+        .and_then(|h| h.to_str().ok())
+        .ok_or_else(|| ServiceError::Unauthorized("Missing authorization header".to_string()))?;
+    let token = extract_bearer_token(header).map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+    let claims = jwt.verify_token(&token).map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+    if !claims.has_permission("admin") {
+        return Err(ServiceError::Forbidden("Managing loan products requires admin permission".to_string()));
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct CreateProductRequest {
+    pub code: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub late_fee_daily_bps: i32,
+    pub overdue_grace_days: i32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateProductRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub late_fee_daily_bps: Option<i32>,
+    pub overdue_grace_days: Option<i32>,
+    pub active: Option<bool>,
+}
+
+/// `POST /loans/products` (admin)
+pub async fn create_product(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    request: web::Json<CreateProductRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req, &jwt)?;
+    validate_code(&request.code)?;
+    if request.name.trim().is_empty() || request.name.len() > MAX_NAME_LEN {
+        return Err(ServiceError::ValidationError(format!("Name must be 1-{} characters", MAX_NAME_LEN)));
+    }
+    validate_policy(request.late_fee_daily_bps, request.overdue_grace_days)?;
+
+    let product = sqlx::query_as::<_, LoanProduct>(&format!(
+        r#"
+        INSERT INTO loan_products (code, name, description, late_fee_daily_bps, overdue_grace_days)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (code) DO NOTHING
+        RETURNING {}
+        "#,
+        PRODUCT_COLUMNS
+    ))
+    .bind(&request.code)
+    .bind(request.name.trim())
+    .bind(&request.description)
+    .bind(request.late_fee_daily_bps)
+    .bind(request.overdue_grace_days)
+    .fetch_optional(pool.get_ref())
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::BusinessError(format!("Loan product {} already exists", request.code)))?;
+
+    tracing::info!(
+        "Loan product {} created: late fee {} bps/day, {} grace days",
+        product.code, product.late_fee_daily_bps, product.overdue_grace_days
+    );
+    Ok(HttpResponse::Created().json(product))
+}
+
+/// `PUT /loans/products/{code}` (admin). Loans already originated keep the policy
+/// they were written under.
+pub async fn update_product(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    code: web::Path<String>,
+    request: web::Json<UpdateProductRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    require_admin(&http_req, &jwt)?;
+    if request.name.as_ref().is_some_and(|n| n.trim().is_empty() || n.len() > MAX_NAME_LEN) {
+        return Err(ServiceError::ValidationError(format!("Name must be 1-{} characters", MAX_NAME_LEN)));
+    }
+
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let current = sqlx::query_as::<_, LoanProduct>(&format!(
+        "SELECT {} FROM loan_products WHERE code = $1 FOR UPDATE",
+        PRODUCT_COLUMNS
+    ))
+    .bind(code.as_str())
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::BusinessError("Loan product not found".to_string()))?;
+
+    let late_fee_daily_bps = request.late_fee_daily_bps.unwrap_or(current.late_fee_daily_bps);
+    let overdue_grace_days = request.overdue_grace_days.unwrap_or(current.overdue_grace_days);
+    validate_policy(late_fee_daily_bps, overdue_grace_days)?;
+
+    let product = sqlx::query_as::<_, LoanProduct>(&format!(
+        r#"
+        UPDATE loan_products
+        SET name = $2, description = $3, late_fee_daily_bps = $4, overdue_grace_days = $5,
+            active = $6, updated_at = NOW()
+        WHERE code = $1
+        RETURNING {}
+        "#,
+        PRODUCT_COLUMNS
+    ))
+    .bind(code.as_str())
+    .bind(request.name.as_deref().map(str::trim).unwrap_or(&current.name))
+    .bind(request.description.as_ref().or(current.description.as_ref()))
+    .bind(late_fee_daily_bps)
+    .bind(overdue_grace_days)
+    .bind(request.active.unwrap_or(current.active))
+    .fetch_one(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    tracing::info!("Loan product {} updated", product.code);
+    Ok(HttpResponse::Ok().json(product))
+}
+
+/// `GET /loans/products`: products borrowers can choose from
+pub async fn list_products(pool: web::Data<PgPool>) -> Result<HttpResponse, ServiceError> {
+    let products = sqlx::query_as::<_, LoanProduct>(&format!(
+        "SELECT {} FROM loan_products WHERE active ORDER BY code",
+        PRODUCT_COLUMNS
+    ))
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "default_product": DEFAULT_PRODUCT,
+        "products": products
+    })))
+}
+
+/// `GET /loans/products/{code}`
+pub async fn get_product(
+    pool: web::Data<PgPool>,
+    code: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    let product = LoanProduct::find(pool.get_ref(), &code)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::BusinessError("Loan product not found".to_string()))?;
+    Ok(HttpResponse::Ok().json(product))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_policy_bounds() {
+        assert!(validate_policy(100, 7).is_ok());
+        assert!(validate_policy(0, 0).is_ok());
+        assert!(validate_policy(MAX_LATE_FEE_DAILY_BPS + 1, 7).is_err());
+        assert!(validate_policy(-1, 7).is_err());
+        assert!(validate_policy(100, MAX_OVERDUE_GRACE_DAYS + 1).is_err());
+    }
+
+    #[test]
+    fn test_product_codes() {
+        assert!(validate_code("smb-30_day").is_ok());
+        assert!(validate_code("").is_err());
+        assert!(validate_code("Standard").is_err());
+        assert!(validate_code("a b").is_err());
+    }
+}
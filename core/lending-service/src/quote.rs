@@ -0,0 +1,159 @@
+// core/lending-service/src/quote.rs
+// Cost breakdown for proposed loan terms, computed exactly as origination would
+
+use actix_web::{web, HttpResponse, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+use crate::ltv;
+use crate::margin::MarginPolicy;
+use crate::products::LoanProduct;
+use crate::rates_client::{RatesClient, POOL_PRODUCT};
+use crate::repayments::{accrue, LoanBalance, PrepaymentOption};
+use crate::{
+    bps_to_rate, calculate_collateral_ratio, validate_loan_terms, validate_prepayment_terms, ServiceError,
+    MIN_COLLATERAL_RATIO,
+};
+
+#[derive(Debug, Deserialize)]
+pub struct LoanQuoteRequest {
+    pub amount_satoshis: i64,
+    pub collateral_satoshis: i64,
+    pub duration_days: i32,
+    pub interest_rate_bps: i32,
+    #[serde(default)]
+    pub prepayment_option: PrepaymentOption,
+    pub prepayment_penalty_bps: Option<i32>,
+    pub product: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, Serialize)]
+pub struct DailyInterest {
+    pub day: i64,
+    pub date: DateTime<Utc>,
+    pub interest_satoshis: i64,
+    pub cumulative_satoshis: i64,
+}
+
+/// Interest for each day of the term. Each day is the difference of cumulative
+/// accruals, so the days sum to exactly the quoted total.
+pub fn interest_by_day(principal: i64, rate_bps: i32, start: DateTime<Utc>, days: i64) -> Vec<DailyInterest> {
+    let mut previous = 0;
+    (1..=days)
+        .map(|day| {
+            let date = start + Duration::days(day);
+            let cumulative = accrue(principal, rate_bps, start, date);
+            let interest = cumulative - previous;
+            previous = cumulative;
+            DailyInterest { day, date, interest_satoshis: interest, cumulative_satoshis: cumulative }
+        })
+        .collect()
+}
+
+/// `POST /loans/quote`: what the proposed terms would cost, without creating a loan
+pub async fn quote_loan(
+    pool: web::Data<PgPool>,
+    margin: web::Data<MarginPolicy>,
+    rates: web::Data<RatesClient>,
+    request: web::Json<LoanQuoteRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_loan_terms(
+        request.amount_satoshis,
+        request.collateral_satoshis,
+        request.duration_days,
+        request.interest_rate_bps,
+    )?;
+    validate_prepayment_terms(request.prepayment_option, request.prepayment_penalty_bps)?;
+    let product = LoanProduct::for_origination(pool.get_ref(), request.product.as_deref()).await?;
+
+    let now = Utc::now();
+    let due_date = now + Duration::days(request.duration_days as i64);
+    let mut loan = LoanBalance::proposed(
+        request.amount_satoshis,
+        request.collateral_satoshis,
+        request.interest_rate_bps,
+        now,
+        due_date,
+        &product,
+    );
+    loan.prepayment_option = request.prepayment_option.as_str().to_string();
+    loan.prepayment_penalty_bps = request.prepayment_penalty_bps.unwrap_or(0);
+    let payoff = loan.payoff(now);
+    let collateral_ratio = calculate_collateral_ratio(request.collateral_satoshis, request.amount_satoshis);
+    let required_collateral = (request.amount_satoshis as f64 * MIN_COLLATERAL_RATIO) as i64;
+    let thresholds = &margin.thresholds;
+    // Debt at which the loan crosses a threshold; debt and collateral are both BSV
+    let debt_at = |ltv_bps: i64| request.collateral_satoshis * ltv_bps / 10_000;
+    // The product's current borrow rate, for comparison with the proposed one; null while
+    // interest-engine is unreachable
+    let market_rate = match rates.product_rate(&product.code, Some(POOL_PRODUCT)).await {
+        Ok(rate) => Some(serde_json::json!({
+            "product": rate.product_code,
+            "borrow_apy": rate.borrow_apy,
+            "borrow_rate_bps": (rate.borrow_apy * 10_000.0).round() as i64,
+            "stale": rate.stale
+        })),
+        Err(e) => {
+            tracing::warn!("Market rate for {} unavailable: {}", product.code, e);
+            None
+        }
+    };
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "eligible": collateral_ratio >= MIN_COLLATERAL_RATIO,
+        "required_collateral_satoshis": required_collateral,
+        "terms": {
+            "amount_satoshis": request.amount_satoshis,
+            "collateral_satoshis": request.collateral_satoshis,
+            "duration_days": request.duration_days,
+            "interest_rate_bps": request.interest_rate_bps,
+            "interest_rate_percent": bps_to_rate(request.interest_rate_bps) * 100.0,
+            "due_date": due_date,
+            "prepayment_option": request.prepayment_option,
+            "prepayment_penalty_bps": loan.prepayment_penalty_bps,
+            "product": product.code
+        },
+        "collateral_ratio": collateral_ratio,
+        "market_rate": market_rate,
+        "interest": {
+            "total_satoshis": payoff.interest_satoshis,
+            "daily": interest_by_day(request.amount_satoshis, request.interest_rate_bps, now, request.duration_days as i64)
+        },
+        "fees": {
+            "origination_satoshis": 0,
+            "late_fee_daily_bps": loan.late_fee_daily_bps,
+            "late_fee_per_day_satoshis": request.amount_satoshis * loan.late_fee_daily_bps as i64 / 10_000,
+            "prepayment_penalty_satoshis": request.amount_satoshis * loan.prepayment_penalty_bps as i64 / 10_000
+        },
+        "total_repayment_satoshis": payoff.total_satoshis,
+        "liquidation": {
+            "origination_ltv_bps": ltv::ltv_bps(request.amount_satoshis, 1.0, request.collateral_satoshis, 1.0),
+            "warning_ltv_bps": thresholds.warning_bps,
+            "margin_call_ltv_bps": thresholds.margin_call_bps,
+            "liquidation_ltv_bps": thresholds.liquidation_bps,
+            "margin_call_debt_satoshis": debt_at(thresholds.margin_call_bps),
+            "liquidation_debt_satoshis": debt_at(thresholds.liquidation_bps),
+            "cure_period_hours": margin.cure_period.num_hours(),
+            "overdue_grace_days": loan.overdue_grace_days
+        },
+        "policy": product.policy(),
+        "schedule": loan.schedule(now),
+        "quoted_at": now
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_interest_sums_to_total() {
+        let start = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        let days = interest_by_day(1_234_567, 1_250, start, 45);
+        assert_eq!(days.len(), 45);
+        let total: i64 = days.iter().map(|d| d.interest_satoshis).sum();
+        assert_eq!(total, accrue(1_234_567, 1_250, start, start + Duration::days(45)));
+        assert_eq!(days.last().unwrap().cumulative_satoshis, total);
+    }
+}
@@ -0,0 +1,731 @@
+// core/lending-service/src/repayments.rs
+// Partial repayments and the amortization schedule of the remaining balance
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgExecutor, PgPool};
+use uuid::Uuid;
+use bsv_bank_common::{validate_amount, validate_paymail, LendingMetrics};
+
+use crate::auth::require_paymail;
+use crate::idempotency::Idempotency;
+use crate::products::LoanProduct;
+use crate::{bps_to_rate, events, settlement, syndication, ServiceError};
+
+/// Installments fall due every 30 days from origination, and at the due date
+pub const INSTALLMENT_DAYS: i64 = 30;
+
+/// Early payoff terms, chosen at origination; stored as `loans.prepayment_option`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PrepaymentOption {
+    /// Interest to the due date is owed whenever the loan is paid off
+    #[default]
+    Standard,
+    /// Paying off early owes only the interest accrued to date
+    Rebate,
+    /// Interest accrued to date plus `prepayment_penalty_bps` of the outstanding principal
+    Penalty,
+}
+
+impl PrepaymentOption {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Standard => "standard",
+            Self::Rebate => "rebate",
+            Self::Penalty => "penalty",
+        }
+    }
+
+    fn parse(value: &str) -> Self {
+        match value {
+            "rebate" => Self::Rebate,
+            "penalty" => Self::Penalty,
+            _ => Self::Standard,
+        }
+    }
+}
+
+/// Balance of a loan as far as repayment is concerned
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LoanBalance {
+    pub id: Uuid,
+    pub borrower_paymail: String,
+    pub status: String,
+    pub principal_satoshis: i64,
+    pub principal_outstanding: i64,
+    pub collateral_satoshis: i64,
+    pub interest_rate_bps: i32,
+    pub interest_carried: i64,
+    pub interest_paid: i64,
+    pub interest_accrued_through: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub funded_at: Option<DateTime<Utc>>,
+    pub due_date: DateTime<Utc>,
+    pub prepayment_option: String,
+    pub prepayment_penalty_bps: i32,
+    /// Late fee per day past the due date, in bps of outstanding principal
+    pub late_fee_daily_bps: i32,
+    pub overdue_grace_days: i32,
+}
+
+pub(crate) const BALANCE_COLUMNS: &str = "id, borrower_paymail, status, principal_satoshis, principal_outstanding, \
+    collateral_satoshis, interest_rate_bps, interest_carried, interest_paid, interest_accrued_through, \
+    created_at, funded_at, due_date, prepayment_option, prepayment_penalty_bps, late_fee_daily_bps, overdue_grace_days";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Payoff {
+    pub principal_satoshis: i64,
+    pub interest_satoshis: i64,
+    pub late_fee_satoshis: i64,
+    pub prepayment_penalty_satoshis: i64,
+    pub total_satoshis: i64,
+}
+
+impl Payoff {
+    /// The parts `amount` covers when it falls short of the payoff: interest first, then
+    /// principal, the late fee and the prepayment penalty
+    pub fn capped(&self, amount: i64) -> Payoff {
+        let mut left = amount.clamp(0, self.total_satoshis);
+        let mut take = |part: i64| {
+            let taken = part.min(left);
+            left -= taken;
+            taken
+        };
+        let interest = take(self.interest_satoshis);
+        let principal = take(self.principal_satoshis);
+        let late_fee = take(self.late_fee_satoshis);
+        let penalty = take(self.prepayment_penalty_satoshis);
+        Payoff {
+            principal_satoshis: principal,
+            interest_satoshis: interest,
+            late_fee_satoshis: late_fee,
+            prepayment_penalty_satoshis: penalty,
+            total_satoshis: interest + principal + late_fee + penalty,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Installment {
+    pub number: i64,
+    pub due_date: DateTime<Utc>,
+    pub principal_satoshis: i64,
+    pub interest_satoshis: i64,
+    pub total_satoshis: i64,
+    pub balance_after_satoshis: i64,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Repayment {
+    pub id: Uuid,
+    pub amount_satoshis: i64,
+    pub interest_satoshis: i64,
+    pub principal_satoshis: i64,
+    pub late_fee_satoshis: i64,
+    pub prepayment_penalty_satoshis: i64,
+    pub principal_outstanding_after: i64,
+    pub paid_at: DateTime<Utc>,
+}
+
+/// How a payment is applied
+#[derive(Debug, PartialEq, Eq)]
+pub enum Allocation {
+    Partial { interest: i64, principal: i64 },
+    PayOff,
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+// ============================================================================
+// INTEREST AND SCHEDULE
+// ============================================================================
+
+/// Simple interest on `principal` between two instants, at an annual rate in bps
+pub fn accrue(principal: i64, rate_bps: i32, from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    if to <= from {
+        return 0;
+    }
+    let daily_rate = bps_to_rate(rate_bps) / 365.0;
+    let days = (to - from).num_seconds() as f64 / 86_400.0;
+    (principal as f64 * daily_rate * days) as i64
+}
+
+impl LoanBalance {
+    /// Terms that have not been booked yet, as they would stand at origination under `product`
+    pub fn proposed(
+        principal: i64,
+        collateral: i64,
+        rate_bps: i32,
+        created_at: DateTime<Utc>,
+        due_date: DateTime<Utc>,
+        product: &LoanProduct,
+    ) -> Self {
+        Self {
+            id: Uuid::nil(),
+            borrower_paymail: String::new(),
+            status: "Pending".to_string(),
+            principal_satoshis: principal,
+            principal_outstanding: principal,
+            collateral_satoshis: collateral,
+            interest_rate_bps: rate_bps,
+            interest_carried: 0,
+            interest_paid: 0,
+            interest_accrued_through: created_at,
+            created_at,
+            funded_at: None,
+            due_date,
+            prepayment_option: PrepaymentOption::Standard.as_str().to_string(),
+            prepayment_penalty_bps: 0,
+            late_fee_daily_bps: product.late_fee_daily_bps,
+            overdue_grace_days: product.overdue_grace_days,
+        }
+    }
+
+    pub fn prepayment(&self) -> PrepaymentOption {
+        PrepaymentOption::parse(&self.prepayment_option)
+    }
+
+    /// Still owed: active, or margin called and awaiting a cure
+    pub fn is_open(&self) -> bool {
+        matches!(self.status.as_str(), "Active" | "MarginCalled")
+    }
+
+    /// Unpaid interest as of `at`; interest stops at the due date, where late fees take over
+    pub fn interest_to(&self, at: DateTime<Utc>) -> i64 {
+        self.interest_carried
+            + accrue(self.principal_outstanding, self.interest_rate_bps, self.interest_accrued_through, at.min(self.due_date))
+    }
+
+    /// Amount that closes the loan at `now`. Under the standard option paying off early still
+    /// owes interest to the due date, so with no partial repayments this is principal plus the
+    /// quoted interest; the rebate and penalty options charge interest only to `now`.
+    pub fn payoff(&self, now: DateTime<Utc>) -> Payoff {
+        let early = now < self.due_date;
+        let option = self.prepayment();
+        let interest = match option {
+            PrepaymentOption::Rebate | PrepaymentOption::Penalty if early => self.interest_to(now),
+            _ => self.interest_to(self.due_date),
+        };
+        let penalty = if early && option == PrepaymentOption::Penalty {
+            self.principal_outstanding * self.prepayment_penalty_bps as i64 / 10_000
+        } else {
+            0
+        };
+        let late_fee = if now > self.due_date {
+            let days_late = (now - self.due_date).num_days();
+            (self.principal_outstanding as f64 * bps_to_rate(self.late_fee_daily_bps) * days_late as f64) as i64
+        } else {
+            0
+        };
+        Payoff {
+            principal_satoshis: self.principal_outstanding,
+            interest_satoshis: interest,
+            late_fee_satoshis: late_fee,
+            prepayment_penalty_satoshis: penalty,
+            total_satoshis: self.principal_outstanding + interest + late_fee + penalty,
+        }
+    }
+
+    /// Remaining installments after `now`: the outstanding principal in equal parts, each
+    /// with the interest accrued on the balance since the previous installment
+    pub fn schedule(&self, now: DateTime<Utc>) -> Vec<Installment> {
+        if self.principal_outstanding == 0 {
+            return Vec::new();
+        }
+
+        let mut dates = Vec::new();
+        let mut number = 1;
+        loop {
+            let date = self.created_at + Duration::days(INSTALLMENT_DAYS * number);
+            if date >= self.due_date {
+                break;
+            }
+            if date > now {
+                dates.push((number, date));
+            }
+            number += 1;
+        }
+        // The due date always closes the schedule, even once it has passed
+        dates.push((number, self.due_date));
+
+        let count = dates.len() as i64;
+        let part = self.principal_outstanding / count;
+        let mut balance = self.principal_outstanding;
+        let mut from = self.interest_accrued_through;
+        let mut carried = self.interest_carried;
+
+        dates
+            .into_iter()
+            .enumerate()
+            .map(|(i, (number, due_date))| {
+                // The last installment takes the rounding remainder
+                let principal = if i as i64 == count - 1 { balance } else { part };
+                let interest = carried + accrue(balance, self.interest_rate_bps, from, due_date);
+                carried = 0;
+                from = from.max(due_date);
+                balance -= principal;
+                Installment {
+                    number,
+                    due_date,
+                    principal_satoshis: principal,
+                    interest_satoshis: interest,
+                    total_satoshis: principal + interest,
+                    balance_after_satoshis: balance,
+                }
+            })
+            .collect()
+    }
+}
+
+/// Apply `amount` to interest due now, then principal. A payment that would retire the
+/// principal must be the full payoff, which includes interest to the due date.
+pub fn allocate(amount: i64, interest_due: i64, principal_outstanding: i64, payoff: i64) -> Result<Allocation, String> {
+    if amount > payoff {
+        return Err(format!("Amount exceeds the payoff of {} satoshis", payoff));
+    }
+    if amount == payoff {
+        return Ok(Allocation::PayOff);
+    }
+    let interest = amount.min(interest_due);
+    let principal = amount - interest;
+    if principal >= principal_outstanding {
+        return Err(format!(
+            "Amount would retire the principal; pay the full payoff of {} satoshis",
+            payoff
+        ));
+    }
+    Ok(Allocation::Partial { interest, principal })
+}
+
+// ============================================================================
+// PERSISTENCE
+// ============================================================================
+
+pub async fn load_balance<'e, E: PgExecutor<'e>>(
+    executor: E,
+    loan_id: Uuid,
+    for_update: bool,
+) -> Result<LoanBalance, ServiceError> {
+    let lock = if for_update { " FOR UPDATE" } else { "" };
+    sqlx::query_as::<_, LoanBalance>(&format!("SELECT {} FROM loans WHERE id = $1{}", BALANCE_COLUMNS, lock))
+        .bind(loan_id)
+        .fetch_optional(executor)
+        .await
+        .map_err(db_error)?
+        .ok_or_else(|| ServiceError::BusinessError("Loan not found".to_string()))
+}
+
+/// Insert the repayment of `parts`, collect it from the payer and pass it through to
+/// the lenders. The borrower pays from their balance; repayments made by the system
+/// on liquidation come out of the seized collateral.
+async fn record(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan: &LoanBalance,
+    parts: &Payoff,
+    outstanding_after: i64,
+    paid_at: DateTime<Utc>,
+    actor: &str,
+) -> Result<Repayment, ServiceError> {
+    let payer = if actor == events::ACTOR_SYSTEM {
+        settlement::collateral_account(loan.id)
+    } else {
+        settlement::ensure_available(db_tx, &loan.borrower_paymail, parts.total_satoshis).await?;
+        loan.borrower_paymail.clone()
+    };
+    let loan_id = loan.id;
+    let repayment = sqlx::query_as::<_, Repayment>(
+        r#"
+        INSERT INTO loan_repayments
+            (loan_id, amount_satoshis, interest_satoshis, principal_satoshis,
+             late_fee_satoshis, prepayment_penalty_satoshis, principal_outstanding_after, paid_at)
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+        RETURNING id, amount_satoshis, interest_satoshis, principal_satoshis,
+                  late_fee_satoshis, prepayment_penalty_satoshis, principal_outstanding_after, paid_at
+        "#
+    )
+    .bind(loan_id)
+    .bind(parts.total_satoshis)
+    .bind(parts.interest_satoshis)
+    .bind(parts.principal_satoshis)
+    .bind(parts.late_fee_satoshis)
+    .bind(parts.prepayment_penalty_satoshis)
+    .bind(outstanding_after)
+    .bind(paid_at)
+    .fetch_one(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+
+    settlement::transfer(db_tx, settlement::REPAYMENT, &payer, &settlement::loan_account(loan_id), repayment.amount_satoshis, Some(loan_id))
+        .await
+        .map_err(db_error)?;
+    syndication::distribute(db_tx, loan_id, &repayment).await?;
+    Ok(repayment)
+}
+
+/// Close the loan with `payoff`; the caller holds the row lock. The system settles a loan
+/// only when liquidating it, with whatever part of the payoff the seized collateral
+/// covers: the loan closes as `Liquidated` and the principal left unpaid is written off.
+pub async fn settle_in_full(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan: &LoanBalance,
+    payoff: &Payoff,
+    now: DateTime<Utc>,
+    actor: &str,
+) -> Result<Repayment, ServiceError> {
+    let liquidation = actor == events::ACTOR_SYSTEM;
+    sqlx::query(
+        r#"
+        UPDATE loans
+        SET status = CASE WHEN $4 THEN 'Liquidated' ELSE 'Repaid' END,
+            repaid_at = CASE WHEN $4 THEN repaid_at ELSE $2 END,
+            liquidated_at = CASE WHEN $4 THEN $2 ELSE liquidated_at END,
+            principal_outstanding = 0, interest_carried = 0,
+            interest_paid = interest_paid + $3, interest_accrued_through = $2
+        WHERE id = $1
+        "#
+    )
+    .bind(loan.id)
+    .bind(now)
+    .bind(payoff.interest_satoshis)
+    .bind(liquidation)
+    .execute(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+
+    let repayment = record(db_tx, loan, payoff, 0, now, actor).await?;
+    syndication::close(db_tx, loan.id, loan.principal_outstanding - payoff.principal_satoshis, now).await?;
+    log_repayment(db_tx, loan, &repayment, payoff.interest_satoshis, actor).await?;
+    if !liquidation {
+        events::record(&mut **db_tx, loan.id, events::LOAN_REPAID, actor, Some(repayment.amount_satoshis), serde_json::json!({
+            "collateral_released": loan.collateral_satoshis
+        }))
+        .await
+        .map_err(db_error)?;
+    }
+    Ok(repayment)
+}
+
+/// Count a loan settled in full; call once the repayment has committed
+pub fn record_repaid(metrics: &LendingMetrics, loan: &LoanBalance, now: DateTime<Utc>) {
+    metrics.record_loan_status("Repaid", loan.principal_satoshis);
+    if let Some(funded_at) = loan.funded_at {
+        metrics.observe_time_to_repay((now - funded_at).num_seconds() as f64);
+    }
+}
+
+/// Pay `interest` of `interest_due` and `principal` without closing the loan; the caller holds the row lock
+pub async fn apply_partial(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan: &LoanBalance,
+    interest_due: i64,
+    interest: i64,
+    principal: i64,
+    now: DateTime<Utc>,
+    actor: &str,
+) -> Result<Repayment, ServiceError> {
+    let outstanding = loan.principal_outstanding - principal;
+    sqlx::query(
+        r#"
+        UPDATE loans
+        SET principal_outstanding = $2, interest_carried = $3,
+            interest_paid = interest_paid + $4, interest_accrued_through = $5
+        WHERE id = $1
+        "#
+    )
+    .bind(loan.id)
+    .bind(outstanding)
+    .bind(interest_due - interest)
+    .bind(interest)
+    .bind(now)
+    .execute(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+
+    let parts = Payoff {
+        principal_satoshis: principal,
+        interest_satoshis: interest,
+        late_fee_satoshis: 0,
+        prepayment_penalty_satoshis: 0,
+        total_satoshis: principal + interest,
+    };
+    let repayment = record(db_tx, loan, &parts, outstanding, now, actor).await?;
+    log_repayment(db_tx, loan, &repayment, interest_due, actor).await?;
+    Ok(repayment)
+}
+
+/// Interest crystallised up to the payment, then the payment itself
+async fn log_repayment(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan: &LoanBalance,
+    repayment: &Repayment,
+    interest_due: i64,
+    actor: &str,
+) -> Result<(), ServiceError> {
+    let accrued = interest_due - loan.interest_carried;
+    if accrued > 0 {
+        events::record(&mut **db_tx, loan.id, events::INTEREST_ACCRUED, events::ACTOR_SYSTEM, Some(accrued), serde_json::json!({
+            "from": loan.interest_accrued_through,
+            "to": repayment.paid_at
+        }))
+        .await
+        .map_err(db_error)?;
+    }
+    events::record(&mut **db_tx, loan.id, events::REPAYMENT, actor, Some(repayment.amount_satoshis), serde_json::json!({
+        "repayment_id": repayment.id,
+        "interest": repayment.interest_satoshis,
+        "principal": repayment.principal_satoshis,
+        "late_fee": repayment.late_fee_satoshis,
+        "prepayment_penalty": repayment.prepayment_penalty_satoshis,
+        "principal_outstanding_after": repayment.principal_outstanding_after
+    }))
+    .await
+    .map_err(db_error)
+}
+
+async fn list_repayments(pool: &PgPool, loan_id: Uuid) -> Result<Vec<Repayment>, ServiceError> {
+    sqlx::query_as::<_, Repayment>(
+        r#"
+        SELECT id, amount_satoshis, interest_satoshis, principal_satoshis,
+               late_fee_satoshis, prepayment_penalty_satoshis, principal_outstanding_after, paid_at
+        FROM loan_repayments
+        WHERE loan_id = $1
+        ORDER BY paid_at
+        "#
+    )
+    .bind(loan_id)
+    .fetch_all(pool)
+    .await
+    .map_err(db_error)
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PartialRepaymentRequest {
+    pub borrower_paymail: String,
+    pub amount_satoshis: i64,
+}
+
+/// `POST /loans/{id}/repayments`: pay any amount up to the payoff before the due date
+pub async fn record_repayment(
+    pool: web::Data<PgPool>,
+    metrics: web::Data<LendingMetrics>,
+    http_req: HttpRequest,
+    loan_id: web::Path<Uuid>,
+    request: web::Json<PartialRepaymentRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.borrower_paymail)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    validate_amount(request.amount_satoshis)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.borrower_paymail)?;
+    let idempotency = Idempotency::from_request(&http_req, "repayment", &*request)?;
+
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    let loan = load_balance(&mut *db_tx, *loan_id, true).await?;
+    if let Some(response) = idempotency.replay(&mut db_tx, loan.id).await? {
+        return Ok(response);
+    }
+
+    if loan.borrower_paymail != request.borrower_paymail {
+        return Err(ServiceError::BusinessError("Only the borrower can repay this loan".to_string()));
+    }
+    if !loan.is_open() {
+        return Err(ServiceError::BusinessError(format!("Loan is not active (status: {})", loan.status)));
+    }
+    if now > loan.due_date {
+        return Err(ServiceError::BusinessError(
+            "Loan is overdue; repay it in full with POST /loans/{id}/repay".to_string()
+        ));
+    }
+
+    let interest_due = loan.interest_to(now);
+    let payoff = loan.payoff(now);
+    let allocation = allocate(request.amount_satoshis, interest_due, loan.principal_outstanding, payoff.total_satoshis)
+        .map_err(ServiceError::BusinessError)?;
+
+    let repayment = match allocation {
+        Allocation::PayOff => settle_in_full(&mut db_tx, &loan, &payoff, now, &request.borrower_paymail).await?,
+        Allocation::Partial { interest, principal } => {
+            apply_partial(&mut db_tx, &loan, interest_due, interest, principal, now, &request.borrower_paymail).await?
+        }
+    };
+
+    let updated = load_balance(&mut *db_tx, loan.id, false).await?;
+    let response = idempotency.respond(&mut db_tx, loan.id, serde_json::json!({
+        "status": "success",
+        "loan_id": loan.id,
+        "loan_status": updated.status,
+        "repayment": repayment,
+        "principal_outstanding": updated.principal_outstanding,
+        "schedule": updated.schedule(now)
+    })).await?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    if allocation == Allocation::PayOff {
+        record_repaid(&metrics, &loan, now);
+    }
+    tracing::info!(
+        "Loan {} repayment of {} ({} interest, {} principal, {} outstanding)",
+        loan.id, repayment.amount_satoshis, repayment.interest_satoshis,
+        repayment.principal_satoshis, repayment.principal_outstanding_after
+    );
+
+    Ok(response)
+}
+
+/// `GET /loans/{id}/schedule`: remaining amortization table and past repayments
+pub async fn get_schedule(
+    pool: web::Data<PgPool>,
+    loan_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let now = Utc::now();
+    let loan = load_balance(pool.get_ref(), *loan_id, false).await?;
+    let repayments = list_repayments(&pool, loan.id).await?;
+    let open = loan.is_open();
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "loan_id": loan.id,
+        "status": loan.status,
+        "principal_satoshis": loan.principal_satoshis,
+        "principal_outstanding": loan.principal_outstanding,
+        "interest_rate_bps": loan.interest_rate_bps,
+        "interest_paid": loan.interest_paid,
+        "interest_due_now": if open { loan.interest_to(now) } else { 0 },
+        "payoff": if open { Some(loan.payoff(now)) } else { None },
+        "due_date": loan.due_date,
+        "installments": if open { loan.schedule(now) } else { Vec::new() },
+        "repayments": repayments
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn loan(principal: i64, rate_bps: i32, days: i64) -> LoanBalance {
+        let created_at = DateTime::<Utc>::from_timestamp(1_700_000_000, 0).unwrap();
+        LoanBalance {
+            id: Uuid::new_v4(),
+            borrower_paymail: "borrower@bsvbank.local".to_string(),
+            status: "Active".to_string(),
+            principal_satoshis: principal,
+            principal_outstanding: principal,
+            collateral_satoshis: principal * 2,
+            interest_rate_bps: rate_bps,
+            interest_carried: 0,
+            interest_paid: 0,
+            interest_accrued_through: created_at,
+            created_at,
+            funded_at: Some(created_at),
+            due_date: created_at + Duration::days(days),
+            prepayment_option: "standard".to_string(),
+            prepayment_penalty_bps: 0,
+            late_fee_daily_bps: 100,
+            overdue_grace_days: 7,
+        }
+    }
+
+    #[test]
+    fn test_payoff_matches_origination_quote() {
+        let loan = loan(1_000_000, 1_000, 90);
+        // Same arithmetic as create_loan_request
+        let quoted = (1_000_000f64 * (bps_to_rate(1_000) / 365.0) * 90.0) as i64;
+        let payoff = loan.payoff(loan.created_at + Duration::days(10));
+        assert_eq!(payoff.interest_satoshis, quoted);
+        assert_eq!(payoff.total_satoshis, 1_000_000 + quoted);
+        assert_eq!(payoff.late_fee_satoshis, 0);
+
+        let late = loan.payoff(loan.due_date + Duration::days(2));
+        assert_eq!(late.late_fee_satoshis, 20_000);
+    }
+
+    #[test]
+    fn test_late_fee_follows_loan_policy() {
+        let mut loan = loan(1_000_000, 1_000, 90);
+        loan.late_fee_daily_bps = 25;
+        let late = loan.payoff(loan.due_date + Duration::days(4));
+        assert_eq!(late.late_fee_satoshis, 10_000);
+
+        loan.late_fee_daily_bps = 0;
+        assert_eq!(loan.payoff(loan.due_date + Duration::days(4)).late_fee_satoshis, 0);
+    }
+
+    #[test]
+    fn test_schedule_amortizes_outstanding_principal() {
+        let loan = loan(1_000_000, 3_650, 90);
+        let schedule = loan.schedule(loan.created_at);
+        // Days 30 and 60, then the due date at day 90
+        assert_eq!(schedule.len(), 3);
+        assert_eq!(schedule.iter().map(|i| i.principal_satoshis).sum::<i64>(), 1_000_000);
+        assert_eq!(schedule.last().unwrap().due_date, loan.due_date);
+        assert_eq!(schedule.last().unwrap().balance_after_satoshis, 0);
+        // 36.5% a year is 0.1% of the balance a day
+        assert_eq!(schedule[0].interest_satoshis, 30_000);
+        assert!(schedule[1].interest_satoshis < schedule[0].interest_satoshis);
+
+        // Past installments drop out
+        let later = loan.schedule(loan.created_at + Duration::days(45));
+        assert_eq!(later.iter().map(|i| i.number).collect::<Vec<_>>(), vec![2, 3]);
+    }
+
+    #[test]
+    fn test_allocate_interest_then_principal() {
+        assert_eq!(allocate(300, 500, 10_000, 11_000), Ok(Allocation::Partial { interest: 300, principal: 0 }));
+        assert_eq!(allocate(2_000, 500, 10_000, 11_000), Ok(Allocation::Partial { interest: 500, principal: 1_500 }));
+        assert_eq!(allocate(11_000, 500, 10_000, 11_000), Ok(Allocation::PayOff));
+        assert!(allocate(11_001, 500, 10_000, 11_000).is_err());
+        // Clears the principal without the interest still to run
+        assert!(allocate(10_500, 500, 10_000, 11_000).is_err());
+    }
+
+    #[test]
+    fn test_capped_payoff_takes_interest_first() {
+        let payoff = Payoff {
+            principal_satoshis: 1_000_000,
+            interest_satoshis: 50_000,
+            late_fee_satoshis: 20_000,
+            prepayment_penalty_satoshis: 0,
+            total_satoshis: 1_070_000,
+        };
+        let capped = payoff.capped(600_000);
+        assert_eq!(capped.interest_satoshis, 50_000);
+        assert_eq!(capped.principal_satoshis, 550_000);
+        assert_eq!(capped.late_fee_satoshis, 0);
+        assert_eq!(capped.total_satoshis, 600_000);
+
+        assert_eq!(payoff.capped(2_000_000), payoff);
+        assert_eq!(payoff.capped(1_060_000).late_fee_satoshis, 10_000);
+    }
+
+    #[test]
+    fn test_early_payoff_options() {
+        let mut loan = loan(1_000_000, 3_650, 90);
+        let early = loan.created_at + Duration::days(10);
+
+        loan.prepayment_option = PrepaymentOption::Rebate.as_str().to_string();
+        let rebate = loan.payoff(early);
+        assert_eq!(rebate.interest_satoshis, 10_000);
+        assert_eq!(rebate.prepayment_penalty_satoshis, 0);
+        assert_eq!(rebate.total_satoshis, 1_010_000);
+
+        loan.prepayment_option = PrepaymentOption::Penalty.as_str().to_string();
+        loan.prepayment_penalty_bps = 200;
+        let penalty = loan.payoff(early);
+        assert_eq!(penalty.interest_satoshis, 10_000);
+        assert_eq!(penalty.prepayment_penalty_satoshis, 20_000);
+        assert_eq!(penalty.total_satoshis, 1_030_000);
+
+        // At maturity every option owes the full term's interest and no penalty
+        let due = loan.payoff(loan.due_date);
+        assert_eq!(due.interest_satoshis, 90_000);
+        assert_eq!(due.prepayment_penalty_satoshis, 0);
+    }
+}
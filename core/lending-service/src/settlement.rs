@@ -0,0 +1,78 @@
+// core/lending-service/src/settlement.rs
+// Money movement for loans: ledger accounts and entry types for loan transfers, which go
+// through common's ledger tagged with their loan
+
+use sqlx::PgExecutor;
+use uuid::Uuid;
+
+pub use bsv_bank_common::ledger::{ensure_available, transfer};
+
+/// Lender balance moved into the loan account when a participation is committed
+pub const COMMITMENT: &str = "loan_commitment";
+/// Loan account paid out to the borrower once fully funded
+pub const DISBURSEMENT: &str = "loan_disbursement";
+/// Commitment returned to the lender when funding expires or the request is cancelled
+pub const COMMITMENT_RELEASE: &str = "loan_commitment_release";
+/// Borrower balance (or seized collateral) moved into the loan account
+pub const REPAYMENT: &str = "loan_repayment";
+/// Collateral seized on liquidation, moved into the loan's collateral account
+pub const COLLATERAL_SEIZURE: &str = "loan_collateral_seizure";
+/// Seized collateral left over once a liquidation has settled the debt
+pub const COLLATERAL_RETURN: &str = "loan_collateral_return";
+/// Interest credited to a lender ahead of payment that a liquidation left unpaid
+pub const WRITE_OFF: &str = "loan_write_off";
+/// Loan account paid out to the lenders' shares of a repayment
+pub const DISTRIBUTION: &str = "loan_distribution";
+/// Interest credited to a lender as it accrues, ahead of the borrower paying it
+pub const INTEREST_ACCRUAL: &str = "loan_interest_accrual";
+/// Borrower interest from the loan account settling interest already credited to a lender
+pub const INTEREST_SETTLEMENT: &str = "loan_interest_settlement";
+
+/// Holds commitments until disbursement and repayments until distribution; nets to zero
+pub fn loan_account(loan_id: Uuid) -> String {
+    format!("loan:{}", loan_id)
+}
+
+/// Collateral seized on liquidation, until it repays the loan and any surplus goes back
+/// to the borrower
+pub fn collateral_account(loan_id: Uuid) -> String {
+    format!("collateral:{}", loan_id)
+}
+
+/// Escrowed collateral swept to the liquidation address. Seizing it credits the loan's
+/// collateral account, so this runs negative by what the seizure transactions hold on chain.
+pub const LIQUIDATION_PROCEEDS_ACCOUNT: &str = "liquidation:proceeds";
+
+/// Losses the platform absorbs: interest credited to lenders that a liquidation never collected
+pub const LOAN_LOSSES_ACCOUNT: &str = "losses:loans";
+
+/// Interest credited to one participation and not yet paid by the borrower, held as a
+/// negative balance until repayments settle it
+pub fn interest_receivable_account(participation_id: Uuid) -> String {
+    format!("interest_receivable:{}", participation_id)
+}
+
+/// Sum of every entry on `account`
+pub async fn balance<'e, E: PgExecutor<'e>>(executor: E, account: &str) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar("SELECT COALESCE(SUM(amount_satoshis), 0)::BIGINT FROM ledger_entries WHERE account = $1")
+        .bind(account)
+        .fetch_one(executor)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bsv_bank_common::ledger::is_user_account;
+
+    #[test]
+    fn test_internal_accounts_are_not_users() {
+        let loan_id = Uuid::nil();
+        assert!(is_user_account("alice@handcash.io"));
+        assert!(!is_user_account(&loan_account(loan_id)));
+        assert!(!is_user_account(&collateral_account(loan_id)));
+        assert!(!is_user_account(&interest_receivable_account(loan_id)));
+        assert!(!is_user_account(LIQUIDATION_PROCEEDS_ACCOUNT));
+        assert!(!is_user_account(LOAN_LOSSES_ACCOUNT));
+    }
+}
@@ -0,0 +1,487 @@
+// core/lending-service/src/syndication.rs
+// Syndicated funding: lender participations, pro rata distribution and funding expiry
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{interval_from_env, run_every, validate_amount, validate_paymail, LendingMetrics};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::accruals;
+use crate::auth::require_paymail;
+use crate::events;
+use crate::idempotency::Idempotency;
+use crate::repayments::Repayment;
+use crate::settlement;
+use crate::ServiceError;
+
+/// Smallest share a lender may take unless it completes the loan
+pub const MIN_PARTICIPATION_SATOSHIS: i64 = 10_000;
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    ServiceError::DatabaseError(e.to_string())
+}
+
+/// Hours a partly funded loan waits for the rest before commitments are released
+fn funding_window() -> Duration {
+    Duration::hours(
+        std::env::var("FUNDING_WINDOW_HOURS")
+            .ok()
+            .and_then(|s| s.parse().ok())
+            .unwrap_or(72),
+    )
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct Participation {
+    pub id: Uuid,
+    pub loan_id: Uuid,
+    pub lender_paymail: String,
+    pub amount_satoshis: i64,
+    /// `Committed` while the loan is still funding, then `Active` until it settles and is
+    /// `Closed`, or `Released` on expiry
+    pub status: String,
+    pub principal_repaid: i64,
+    pub interest_received: i64,
+    /// Principal a liquidation left unpaid, once the participation is `Closed`
+    pub principal_written_off: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(sqlx::FromRow)]
+struct FundingState {
+    borrower_paymail: String,
+    principal_satoshis: i64,
+    funded_satoshis: i64,
+    status: String,
+    created_at: DateTime<Utc>,
+    collateral_txid: Option<String>,
+}
+
+/// Split `amount` in proportion to `shares`, handing leftover satoshis to the
+/// largest remainders so the parts always sum to `amount`
+pub fn pro_rata(amount: i64, shares: &[i64]) -> Vec<i64> {
+    let total: i128 = shares.iter().map(|s| *s as i128).sum();
+    if total <= 0 {
+        return vec![0; shares.len()];
+    }
+    let mut parts: Vec<i64> = shares.iter().map(|s| (amount as i128 * *s as i128 / total) as i64).collect();
+    let mut remainders: Vec<(usize, i128)> = shares
+        .iter()
+        .enumerate()
+        .map(|(i, s)| (i, amount as i128 * *s as i128 % total))
+        .collect();
+    remainders.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    let leftover = amount - parts.iter().sum::<i64>();
+    for (i, _) in remainders.into_iter().take(leftover.max(0) as usize) {
+        parts[i] += 1;
+    }
+    parts
+}
+
+// ============================================================================
+// DISTRIBUTION
+// ============================================================================
+
+/// Credit each active participation with its share of `repayment` and pay it out
+/// of the loan account. Interest already credited by `accruals` settles the
+/// participation's receivable instead of being paid to the lender twice.
+pub async fn distribute(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan_id: Uuid,
+    repayment: &Repayment,
+) -> Result<(), ServiceError> {
+    let participations: Vec<(Uuid, String, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, lender_paymail, amount_satoshis FROM loan_participations
+        WHERE loan_id = $1 AND status = 'Active'
+        ORDER BY created_at, id
+        "#
+    )
+    .bind(loan_id)
+    .fetch_all(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+    if participations.is_empty() {
+        return Ok(());
+    }
+
+    let shares: Vec<i64> = participations.iter().map(|(_, _, amount)| *amount).collect();
+    let principal = pro_rata(repayment.principal_satoshis, &shares);
+    let interest = pro_rata(repayment.interest_satoshis, &shares);
+    let late_fee = pro_rata(repayment.late_fee_satoshis, &shares);
+    let penalty = pro_rata(repayment.prepayment_penalty_satoshis, &shares);
+
+    if repayment.interest_satoshis > 0 {
+        accruals::mark_paid_through(db_tx, loan_id, repayment.paid_at).await.map_err(db_error)?;
+    }
+
+    let account = settlement::loan_account(loan_id);
+    for (i, (participation_id, lender, _)) in participations.iter().enumerate() {
+        sqlx::query(
+            r#"
+            INSERT INTO loan_distributions
+                (repayment_id, participation_id, principal_satoshis, interest_satoshis,
+                 late_fee_satoshis, prepayment_penalty_satoshis)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#
+        )
+        .bind(repayment.id)
+        .bind(participation_id)
+        .bind(principal[i])
+        .bind(interest[i])
+        .bind(late_fee[i])
+        .bind(penalty[i])
+        .execute(&mut **db_tx)
+        .await
+        .map_err(db_error)?;
+
+        let receivable = settlement::interest_receivable_account(*participation_id);
+        let credited = -settlement::balance(&mut **db_tx, &receivable).await.map_err(db_error)?;
+        let settled = interest[i].min(credited.max(0));
+        settlement::transfer(db_tx, settlement::INTEREST_SETTLEMENT, &account, &receivable, settled, Some(loan_id))
+            .await
+            .map_err(db_error)?;
+        let interest_paid = interest[i] - settled;
+
+        sqlx::query(
+            r#"
+            UPDATE loan_participations
+            SET principal_repaid = principal_repaid + $2, interest_received = interest_received + $3
+            WHERE id = $1
+            "#
+        )
+        .bind(participation_id)
+        .bind(principal[i])
+        .bind(interest_paid + late_fee[i] + penalty[i])
+        .execute(&mut **db_tx)
+        .await
+        .map_err(db_error)?;
+
+        let share = principal[i] + interest_paid + late_fee[i] + penalty[i];
+        settlement::transfer(db_tx, settlement::DISTRIBUTION, &account, lender, share, Some(loan_id))
+            .await
+            .map_err(db_error)?;
+    }
+    Ok(())
+}
+
+/// Close the active participations of a loan that has just settled, writing off each
+/// one's share of `shortfall` unpaid principal. Interest credited ahead of a payment
+/// that never came is absorbed as a platform loss, clearing the receivable.
+pub async fn close(
+    db_tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+    loan_id: Uuid,
+    shortfall: i64,
+    now: DateTime<Utc>,
+) -> Result<(), ServiceError> {
+    let participations: Vec<(Uuid, i64)> = sqlx::query_as(
+        r#"
+        SELECT id, amount_satoshis FROM loan_participations
+        WHERE loan_id = $1 AND status = 'Active'
+        ORDER BY created_at, id
+        "#
+    )
+    .bind(loan_id)
+    .fetch_all(&mut **db_tx)
+    .await
+    .map_err(db_error)?;
+
+    let shares: Vec<i64> = participations.iter().map(|(_, amount)| *amount).collect();
+    let written_off = pro_rata(shortfall.max(0), &shares);
+    for ((participation_id, _), written_off) in participations.iter().zip(written_off) {
+        let receivable = settlement::interest_receivable_account(*participation_id);
+        let uncollected = -settlement::balance(&mut **db_tx, &receivable).await.map_err(db_error)?;
+        settlement::transfer(db_tx, settlement::WRITE_OFF, settlement::LOAN_LOSSES_ACCOUNT, &receivable, uncollected, Some(loan_id))
+            .await
+            .map_err(db_error)?;
+
+        sqlx::query(
+            r#"
+            UPDATE loan_participations
+            SET status = 'Closed', closed_at = $3, principal_written_off = $2
+            WHERE id = $1
+            "#
+        )
+        .bind(participation_id)
+        .bind(written_off)
+        .bind(now)
+        .execute(&mut **db_tx)
+        .await
+        .map_err(db_error)?;
+    }
+    Ok(())
+}
+
+// ============================================================================
+// FUNDING EXPIRY
+// ============================================================================
+
+/// Release commitments to loans that did not fill within the funding window
+pub async fn start_funding_expiry(pool: PgPool, metrics: LendingMetrics) {
+    let period = interval_from_env("FUNDING_EXPIRY_INTERVAL_SECS", 300);
+    run_every("loan-funding-expiry", period, || expire_funding(&pool, &metrics)).await
+}
+
+async fn expire_funding(pool: &PgPool, metrics: &LendingMetrics) -> Result<(), sqlx::Error> {
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await?;
+    let expired = sqlx::query_as::<_, (Uuid, i64)>(
+        r#"
+        UPDATE loans SET status = 'Expired', closed_at = $1, collateral_released_at = $1
+        WHERE status = 'Pending' AND funding_deadline < $1
+        RETURNING id, principal_satoshis
+        "#
+    )
+    .bind(now)
+    .fetch_all(&mut *db_tx)
+    .await?;
+    if expired.is_empty() {
+        return Ok(());
+    }
+    let expired_ids: Vec<Uuid> = expired.iter().map(|(id, _)| *id).collect();
+    let released = sqlx::query_as::<_, (Uuid, String, i64)>(
+        r#"
+        UPDATE loan_participations
+        SET status = 'Released', released_at = $1
+        WHERE loan_id = ANY($2) AND status = 'Committed'
+        RETURNING loan_id, lender_paymail, amount_satoshis
+        "#
+    )
+    .bind(now)
+    .bind(&expired_ids)
+    .fetch_all(&mut *db_tx)
+    .await?;
+
+    for (loan_id, lender, amount) in released {
+        settlement::transfer(&mut db_tx, settlement::COMMITMENT_RELEASE, &settlement::loan_account(loan_id), &lender, amount, Some(loan_id))
+            .await?;
+        events::record(&mut *db_tx, loan_id, events::FUNDING_EXPIRED, events::ACTOR_SYSTEM, Some(amount), serde_json::json!({
+            "released_to": lender
+        }))
+        .await?;
+        tracing::info!("Loan {} funding expired, released {} committed by {}", loan_id, amount, lender);
+    }
+    db_tx.commit().await?;
+
+    for (_, principal) in expired {
+        metrics.record_loan_status("Expired", principal);
+    }
+    Ok(())
+}
+
+// ============================================================================
+// HANDLERS
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FundLoanRequest {
+    pub lender_paymail: String,
+    /// Share to fund; the remaining amount when omitted
+    pub amount_satoshis: Option<i64>,
+}
+
+/// `POST /loans/{id}/fund`: commit all or part of the principal; the loan goes
+/// active once fully funded
+pub async fn fund_loan(
+    pool: web::Data<PgPool>,
+    metrics: web::Data<LendingMetrics>,
+    http_req: HttpRequest,
+    loan_id: web::Path<Uuid>,
+    request: web::Json<FundLoanRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&request.lender_paymail)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    require_paymail(&http_req, &request.lender_paymail)?;
+    if let Some(amount) = request.amount_satoshis {
+        validate_amount(amount).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+    }
+    let idempotency = Idempotency::from_request(&http_req, "fund", &*request)?;
+
+    let now = Utc::now();
+    let mut db_tx = pool.begin().await.map_err(db_error)?;
+    // Concurrent lenders queue on the row lock, so each sees what the previous one funded
+    let loan = sqlx::query_as::<_, FundingState>(
+        "SELECT borrower_paymail, principal_satoshis, funded_satoshis, status, created_at, collateral_txid FROM loans WHERE id = $1 FOR UPDATE"
+    )
+    .bind(*loan_id)
+    .fetch_optional(&mut *db_tx)
+    .await
+    .map_err(db_error)?
+    .ok_or_else(|| ServiceError::BusinessError("Loan not found or already funded".to_string()))?;
+    if let Some(response) = idempotency.replay(&mut db_tx, *loan_id).await? {
+        return Ok(response);
+    }
+    if loan.status != "Pending" {
+        return Err(ServiceError::BusinessError("Loan not found or already funded".to_string()));
+    }
+
+    if loan.borrower_paymail == request.lender_paymail {
+        return Err(ServiceError::BusinessError("Borrowers cannot fund their own loan".to_string()));
+    }
+    let remaining = loan.principal_satoshis - loan.funded_satoshis;
+    let amount = request.amount_satoshis.unwrap_or(remaining);
+    if amount > remaining {
+        return Err(ServiceError::BusinessError(format!("Only {} satoshis remain to be funded", remaining)));
+    }
+    if amount < remaining && amount < MIN_PARTICIPATION_SATOSHIS {
+        return Err(ServiceError::BusinessError(format!(
+            "Minimum participation is {} satoshis",
+            MIN_PARTICIPATION_SATOSHIS
+        )));
+    }
+
+    // The lender's balance moves into the loan account until the loan is fully funded
+    settlement::ensure_available(&mut db_tx, &request.lender_paymail, amount).await?;
+    settlement::transfer(&mut db_tx, settlement::COMMITMENT, &request.lender_paymail, &settlement::loan_account(*loan_id), amount, Some(*loan_id))
+        .await
+        .map_err(db_error)?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO loan_participations (loan_id, lender_paymail, amount_satoshis, status)
+        VALUES ($1, $2, $3, 'Committed')
+        ON CONFLICT (loan_id, lender_paymail)
+        DO UPDATE SET amount_satoshis = loan_participations.amount_satoshis + EXCLUDED.amount_satoshis
+        "#
+    )
+    .bind(*loan_id)
+    .bind(&request.lender_paymail)
+    .bind(amount)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+
+    let funded = loan.funded_satoshis + amount;
+    let fully_funded = funded == loan.principal_satoshis;
+    // The first lender leads the syndicate and is the loan's lender of record
+    sqlx::query(
+        r#"
+        UPDATE loans
+        SET funded_satoshis = $2,
+            lender_paymail = COALESCE(lender_paymail, $3),
+            status = CASE WHEN $4 THEN 'Active' ELSE status END,
+            funding_deadline = CASE WHEN $4 THEN NULL ELSE COALESCE(funding_deadline, $5) END,
+            funded_at = CASE WHEN $4 THEN $6 ELSE funded_at END
+        WHERE id = $1
+        "#
+    )
+    .bind(*loan_id)
+    .bind(funded)
+    .bind(&request.lender_paymail)
+    .bind(fully_funded)
+    .bind(now + funding_window())
+    .bind(now)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+
+    events::record(&mut *db_tx, *loan_id, events::FUNDING_COMMITTED, &request.lender_paymail, Some(amount), serde_json::json!({
+        "funded_satoshis": funded,
+        "principal_satoshis": loan.principal_satoshis
+    }))
+    .await
+    .map_err(db_error)?;
+
+    if fully_funded {
+        let lenders = sqlx::query("UPDATE loan_participations SET status = 'Active' WHERE loan_id = $1 AND status = 'Committed'")
+            .bind(*loan_id)
+            .execute(&mut *db_tx)
+            .await
+            .map_err(db_error)?
+            .rows_affected();
+        // Collateral held on the ledger is already netted out of the borrower's available
+        // balance, so it is still covered as long as that balance is not negative
+        if loan.collateral_txid.is_none() {
+            settlement::ensure_available(&mut db_tx, &loan.borrower_paymail, 0).await?;
+        }
+        settlement::transfer(&mut db_tx, settlement::DISBURSEMENT, &settlement::loan_account(*loan_id), &loan.borrower_paymail, funded, Some(*loan_id))
+            .await
+            .map_err(db_error)?;
+        events::record(&mut *db_tx, *loan_id, events::LOAN_FUNDED, &request.lender_paymail, Some(funded), serde_json::json!({
+            "lenders": lenders
+        }))
+        .await
+        .map_err(db_error)?;
+    }
+
+    let response = idempotency.respond(&mut db_tx, *loan_id, serde_json::json!({
+        "status": "success",
+        "message": if fully_funded { "Loan funded successfully" } else { "Participation committed" },
+        "loan_id": loan_id.as_ref(),
+        "amount_satoshis": amount,
+        "funded_satoshis": funded,
+        "remaining_satoshis": loan.principal_satoshis - funded,
+        "loan_status": if fully_funded { "Active" } else { "Pending" }
+    })).await?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    if fully_funded {
+        metrics.record_loan_status("Active", funded);
+        metrics.observe_time_to_fund((now - loan.created_at).num_seconds() as f64);
+    }
+    tracing::info!("Loan {} funded {} by {} ({}/{})", loan_id, amount, request.lender_paymail, funded, loan.principal_satoshis);
+
+    Ok(response)
+}
+
+/// `GET /loans/{id}/participations`: the syndicate and what each lender has received
+pub async fn get_participations(
+    pool: web::Data<PgPool>,
+    loan_id: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let participations = sqlx::query_as::<_, Participation>(
+        "SELECT * FROM loan_participations WHERE loan_id = $1 ORDER BY created_at, id"
+    )
+    .bind(*loan_id)
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    let funded: i64 = participations.iter().filter(|p| p.status != "Released").map(|p| p.amount_satoshis).sum();
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "loan_id": loan_id.as_ref(),
+        "funded_satoshis": funded,
+        "participations": participations
+    })))
+}
+
+/// `GET /loans/participations/{paymail}`: every loan a lender has a share in
+pub async fn get_lender_participations(
+    pool: web::Data<PgPool>,
+    paymail: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_paymail(&paymail).map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    let participations = sqlx::query_as::<_, Participation>(
+        "SELECT * FROM loan_participations WHERE lender_paymail = $1 ORDER BY created_at DESC"
+    )
+    .bind(paymail.as_str())
+    .fetch_all(pool.get_ref())
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(participations))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pro_rata_sums_to_amount() {
+        assert_eq!(pro_rata(1_000, &[600_000, 400_000]), vec![600, 400]);
+        assert_eq!(pro_rata(100, &[1, 1, 1]), vec![34, 33, 33]);
+        let parts = pro_rata(99_999, &[333_333, 250_000, 416_667]);
+        assert_eq!(parts.iter().sum::<i64>(), 99_999);
+    }
+
+    #[test]
+    fn test_pro_rata_edges() {
+        assert_eq!(pro_rata(0, &[5, 5]), vec![0, 0]);
+        assert_eq!(pro_rata(10, &[]), Vec::<i64>::new());
+        assert_eq!(pro_rata(7, &[1_000_000]), vec![7]);
+    }
+}
@@ -0,0 +1,209 @@
+// core/payment-channel-service/src/archive.rs
+// Retention: move payments of long-closed channels into archive tables
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{authenticated_claims, create_error_response, forbidden_response};
+
+/// Channels archived per retention pass
+const ARCHIVE_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone)]
+pub struct RetentionConfig {
+    pub archive_after_days: i32,
+    pub interval_secs: u64,
+}
+
+impl RetentionConfig {
+    pub fn from_env() -> Self {
+        Self {
+            archive_after_days: std::env::var("CHANNEL_ARCHIVE_AFTER_DAYS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(30),
+            interval_secs: std::env::var("CHANNEL_ARCHIVE_INTERVAL_SECS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(3600),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ArchivedPayment {
+    pub id: Uuid,
+    pub channel_id: String,
+    pub from_paymail: String,
+    pub to_paymail: String,
+    pub amount_satoshis: i64,
+    pub sequence_number: i64,
+    pub memo: Option<String>,
+    pub balance_a_after: i64,
+    pub balance_b_after: i64,
+    pub processing_time_ms: Option<i32>,
+    pub created_at: NaiveDateTime,
+    pub archived_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct ArchiveRun {
+    pub channels_archived: usize,
+    pub payments_archived: u64,
+}
+
+/// Archive a single closed channel atomically
+async fn archive_channel(pool: &PgPool, channel_id: &str) -> Result<u64, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+
+    let moved = sqlx::query(
+        r#"
+        WITH moved AS (
+            DELETE FROM channel_payments
+            WHERE channel_id = $1
+            RETURNING id, channel_id, from_paymail, to_paymail, amount_satoshis,
+                      sequence_number, memo, balance_a_after, balance_b_after,
+                      processing_time_ms, created_at
+        )
+        INSERT INTO channel_payments_archive (
+            id, channel_id, from_paymail, to_paymail, amount_satoshis,
+            sequence_number, memo, balance_a_after, balance_b_after,
+            processing_time_ms, created_at
+        )
+        SELECT * FROM moved
+        ON CONFLICT (id) DO NOTHING
+        "#
+    )
+    .bind(channel_id)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+
+    sqlx::query(
+        "UPDATE payment_channels SET archived_at = NOW() WHERE channel_id = $1 AND status = 'Closed'"
+    )
+    .bind(channel_id)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+    Ok(moved)
+}
+
+/// Archive every channel closed longer than the retention window
+pub async fn run_retention(pool: &PgPool, config: &RetentionConfig) -> Result<ArchiveRun, sqlx::Error> {
+    let mut run = ArchiveRun::default();
+
+    loop {
+        let candidates: Vec<String> = sqlx::query_scalar(
+            r#"
+            SELECT channel_id FROM payment_channels
+            WHERE status = 'Closed'
+              AND archived_at IS NULL
+              AND closed_at < NOW() - make_interval(days => $1)
+            ORDER BY closed_at
+            LIMIT $2
+            "#
+        )
+        .bind(config.archive_after_days)
+        .bind(ARCHIVE_BATCH_SIZE)
+        .fetch_all(pool)
+        .await?;
+
+        if candidates.is_empty() {
+            break;
+        }
+
+        for channel_id in &candidates {
+            run.payments_archived += archive_channel(pool, channel_id).await?;
+            run.channels_archived += 1;
+        }
+
+        if (candidates.len() as i64) < ARCHIVE_BATCH_SIZE {
+            break;
+        }
+    }
+
+    Ok(run)
+}
+
+pub async fn start_retention_task(pool: PgPool, config: RetentionConfig) {
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(config.interval_secs));
+
+    loop {
+        interval.tick().await;
+
+        match run_retention(&pool, &config).await {
+            Ok(run) if run.channels_archived > 0 => {
+                tracing::info!("Archived {} payments from {} closed channels",
+                    run.payments_archived, run.channels_archived);
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Channel archive pass failed: {}", e),
+        }
+    }
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+pub async fn get_channel_archive(
+    pool: web::Data<PgPool>,
+    channel_id: web::Path<String>,
+) -> Result<HttpResponse> {
+    let payments = sqlx::query_as::<_, ArchivedPayment>(
+        r#"
+        SELECT * FROM channel_payments_archive
+        WHERE channel_id = $1
+        ORDER BY sequence_number DESC
+        "#
+    )
+    .bind(channel_id.as_str())
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match payments {
+        Ok(payments) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "channel_id": channel_id.as_str(),
+            "total_payments": payments.len(),
+            "payments": payments
+        }))),
+        Err(e) => {
+            tracing::error!("Error fetching archive: {}", e);
+            Ok(create_error_response("DatabaseError", "Failed to fetch archived payments"))
+        }
+    }
+}
+
+/// Run a retention pass on demand (admin only)
+pub async fn trigger_archive(
+    pool: web::Data<PgPool>,
+    config: web::Data<RetentionConfig>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+) -> Result<HttpResponse> {
+    let claims = match authenticated_claims(&http_req, &jwt) {
+        Ok(claims) => claims,
+        Err(response) => return Ok(response),
+    };
+    if !claims.has_permission("admin") {
+        return Ok(forbidden_response("Archiving requires admin permission"));
+    }
+
+    match run_retention(pool.get_ref(), &config).await {
+        Ok(run) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "archive_after_days": config.archive_after_days,
+            "result": run,
+            "timestamp": Utc::now()
+        }))),
+        Err(e) => {
+            tracing::error!("Manual archive pass failed: {}", e);
+            Ok(create_error_response("DatabaseError", "Failed to archive channels"))
+        }
+    }
+}
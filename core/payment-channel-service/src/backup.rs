@@ -0,0 +1,758 @@
+// core/payment-channel-service/src/backup.rs
+// Channel state export/backup and restore
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::disputes::{party_public_key, verify_state_signature};
+use crate::{
+    authenticated_claims, authenticated_paymail, create_error_response, forbidden_response,
+    ChannelPayment, ErrorResponse, PaymentChannel,
+};
+
+/// Bumped whenever the bundle layout changes incompatibly
+pub const BUNDLE_FORMAT_VERSION: u32 = 1;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct ChannelStateRecord {
+    pub id: Uuid,
+    pub channel_id: String,
+    pub sequence_number: i64,
+    pub balance_a: i64,
+    pub balance_b: i64,
+    pub state_hash: Option<String>,
+    pub signature_a: Option<String>,
+    pub signature_b: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct TemplateSignature {
+    pub id: Uuid,
+    pub paymail: String,
+    pub signature: String,
+    pub pubkey: String,
+    pub sighash_type: Option<i32>,
+    pub signed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct CommitmentTemplate {
+    pub id: Uuid,
+    pub template_type: String,
+    pub tx_hex: String,
+    pub txid: Option<String>,
+    pub status: Option<String>,
+    pub party_a_signed: Option<bool>,
+    pub party_b_signed: Option<bool>,
+    pub sequence_number: Option<i32>,
+    pub timelock_blocks: Option<i32>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub broadcast_at: Option<DateTime<Utc>>,
+    pub confirmed_at: Option<DateTime<Utc>>,
+    #[sqlx(skip)]
+    #[serde(default)]
+    pub signatures: Vec<TemplateSignature>,
+}
+
+/// Portable, self-verifying snapshot of everything needed to contest a close
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChannelBundle {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub channel: PaymentChannel,
+    pub states: Vec<ChannelStateRecord>,
+    pub payments: Vec<ChannelPayment>,
+    pub commitment_txs: Vec<CommitmentTemplate>,
+    /// SHA-256 over the bundle serialized with this field empty
+    #[serde(default)]
+    pub bundle_hash: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct BundleVerification {
+    pub valid: bool,
+    pub latest_sequence: i64,
+    pub states_checked: usize,
+    pub payments_checked: usize,
+    pub signed_states: usize,
+    /// Highest state both parties' signatures verify for; restores never advance past it
+    pub latest_signed_sequence: Option<i64>,
+    pub errors: Vec<String>,
+}
+
+// ============================================================================
+// HASHING & VERIFICATION
+// ============================================================================
+
+/// Canonical hash of a channel state, shared with signature verification
+pub fn compute_state_hash(channel_id: &str, sequence_number: i64, balance_a: i64, balance_b: i64) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}:{}:{}:{}", channel_id, sequence_number, balance_a, balance_b).as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn compute_bundle_hash(bundle: &ChannelBundle) -> Result<String, String> {
+    let mut unsigned = serde_json::to_value(bundle).map_err(|e| e.to_string())?;
+    unsigned["bundle_hash"] = serde_json::Value::String(String::new());
+    let bytes = serde_json::to_vec(&unsigned).map_err(|e| e.to_string())?;
+
+    let mut hasher = Sha256::new();
+    hasher.update(&bytes);
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+/// Check bundle integrity, that the state history is internally consistent, and that every
+/// state signature was made by the party's key on record (`users.public_key`)
+pub fn verify_bundle_contents(
+    bundle: &ChannelBundle,
+    pubkey_a: Option<&str>,
+    pubkey_b: Option<&str>,
+) -> BundleVerification {
+    let mut result = BundleVerification {
+        states_checked: bundle.states.len(),
+        payments_checked: bundle.payments.len(),
+        ..Default::default()
+    };
+    let channel = &bundle.channel;
+
+    if bundle.format_version != BUNDLE_FORMAT_VERSION {
+        result.errors.push(format!("Unsupported bundle format version {}", bundle.format_version));
+    }
+
+    match compute_bundle_hash(bundle) {
+        Ok(hash) if hash == bundle.bundle_hash => {}
+        Ok(_) => result.errors.push("Bundle hash mismatch".to_string()),
+        Err(e) => result.errors.push(format!("Cannot hash bundle: {}", e)),
+    }
+
+    let capacity = channel.initial_balance_a + channel.initial_balance_b;
+    let mut states = bundle.states.clone();
+    states.sort_by_key(|s| s.sequence_number);
+
+    for (expected_seq, state) in states.iter().enumerate() {
+        if state.channel_id != channel.channel_id {
+            result.errors.push(format!("State {} belongs to another channel", state.sequence_number));
+        }
+        if state.sequence_number != expected_seq as i64 {
+            result.errors.push(format!(
+                "Sequence gap: expected {}, found {}", expected_seq, state.sequence_number
+            ));
+            break;
+        }
+        if state.balance_a < 0 || state.balance_b < 0 || state.balance_a + state.balance_b != capacity {
+            result.errors.push(format!("State {} violates balance conservation", state.sequence_number));
+        }
+        let expected = compute_state_hash(
+            &channel.channel_id, state.sequence_number, state.balance_a, state.balance_b,
+        );
+        if state.state_hash.as_ref().is_some_and(|hash| *hash != expected) {
+            result.errors.push(format!("State {} hash mismatch", state.sequence_number));
+        }
+
+        let signatures = [
+            (&state.signature_a, pubkey_a, &channel.party_a_paymail),
+            (&state.signature_b, pubkey_b, &channel.party_b_paymail),
+        ];
+        let mut verified = 0;
+        for (signature, pubkey, party) in signatures {
+            let Some(signature) = signature else { continue };
+            match pubkey {
+                Some(pubkey) if verify_state_signature(&expected, signature, pubkey) => verified += 1,
+                Some(_) => result.errors.push(format!(
+                    "State {} has an invalid signature from {}", state.sequence_number, party
+                )),
+                None => result.errors.push(format!(
+                    "State {} is signed by {}, who has no public key on record", state.sequence_number, party
+                )),
+            }
+        }
+        if verified == signatures.len() {
+            result.signed_states += 1;
+            result.latest_signed_sequence = Some(state.sequence_number);
+        }
+        result.latest_sequence = state.sequence_number;
+    }
+
+    if let Some(first) = states.first() {
+        if first.balance_a != channel.initial_balance_a || first.balance_b != channel.initial_balance_b {
+            result.errors.push("Initial state does not match channel opening balances".to_string());
+        }
+    }
+
+    for payment in &bundle.payments {
+        match states.iter().find(|s| s.sequence_number == payment.sequence_number) {
+            Some(state) if state.balance_a == payment.balance_a_after
+                && state.balance_b == payment.balance_b_after => {}
+            Some(_) => result.errors.push(format!(
+                "Payment {} balances disagree with state {}", payment.id, payment.sequence_number
+            )),
+            None => result.errors.push(format!(
+                "Payment {} references missing state {}", payment.id, payment.sequence_number
+            )),
+        }
+    }
+
+    result.valid = result.errors.is_empty();
+    result
+}
+
+/// Both parties' registered public keys
+async fn party_keys(pool: &PgPool, channel: &PaymentChannel) -> Result<(Option<String>, Option<String>), sqlx::Error> {
+    Ok((
+        party_public_key(pool, &channel.party_a_paymail).await?,
+        party_public_key(pool, &channel.party_b_paymail).await?,
+    ))
+}
+
+async fn verify_against_keys(pool: &PgPool, bundle: &ChannelBundle) -> Result<BundleVerification, sqlx::Error> {
+    let (pubkey_a, pubkey_b) = party_keys(pool, &bundle.channel).await?;
+    Ok(verify_bundle_contents(bundle, pubkey_a.as_deref(), pubkey_b.as_deref()))
+}
+
+// ============================================================================
+// EXPORT
+// ============================================================================
+
+pub async fn build_bundle(pool: &PgPool, channel: PaymentChannel) -> Result<ChannelBundle, sqlx::Error> {
+    let states = sqlx::query_as::<_, ChannelStateRecord>(
+        "SELECT * FROM channel_states WHERE channel_id = $1 ORDER BY sequence_number"
+    )
+    .bind(&channel.channel_id)
+    .fetch_all(pool)
+    .await?;
+
+    // Include payments already moved to the archive by the retention job
+    let payments = sqlx::query_as::<_, ChannelPayment>(
+        r#"
+        SELECT id, channel_id, from_paymail, to_paymail, amount_satoshis, sequence_number,
+               memo, balance_a_after, balance_b_after, created_at, processing_time_ms
+        FROM channel_payments WHERE channel_id = $1
+        UNION ALL
+        SELECT id, channel_id, from_paymail, to_paymail, amount_satoshis, sequence_number,
+               memo, balance_a_after, balance_b_after, created_at, processing_time_ms
+        FROM channel_payments_archive WHERE channel_id = $1
+        ORDER BY sequence_number
+        "#
+    )
+    .bind(&channel.channel_id)
+    .fetch_all(pool)
+    .await?;
+
+    let mut commitment_txs = sqlx::query_as::<_, CommitmentTemplate>(
+        r#"
+        SELECT id, template_type, tx_hex, txid, status, party_a_signed, party_b_signed,
+               sequence_number, timelock_blocks, created_at, broadcast_at, confirmed_at
+        FROM transaction_templates
+        WHERE channel_id = $1
+        ORDER BY created_at
+        "#
+    )
+    .bind(channel.id)
+    .fetch_all(pool)
+    .await?;
+
+    for template in commitment_txs.iter_mut() {
+        template.signatures = sqlx::query_as::<_, TemplateSignature>(
+            r#"
+            SELECT id, paymail, signature, pubkey, sighash_type, signed_at
+            FROM transaction_signatures
+            WHERE tx_template_id = $1
+            ORDER BY signed_at
+            "#
+        )
+        .bind(template.id)
+        .fetch_all(pool)
+        .await?;
+    }
+
+    let mut bundle = ChannelBundle {
+        format_version: BUNDLE_FORMAT_VERSION,
+        exported_at: Utc::now(),
+        channel,
+        states,
+        payments,
+        commitment_txs,
+        bundle_hash: String::new(),
+    };
+    bundle.bundle_hash = compute_bundle_hash(&bundle).unwrap_or_default();
+
+    Ok(bundle)
+}
+
+pub async fn export_channel(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    channel_id: web::Path<String>,
+) -> Result<HttpResponse> {
+    let caller = match authenticated_paymail(&http_req, &jwt) {
+        Ok(paymail) => paymail,
+        Err(response) => return Ok(response),
+    };
+
+    let channel = sqlx::query_as::<_, PaymentChannel>(
+        "SELECT * FROM payment_channels WHERE channel_id = $1"
+    )
+    .bind(channel_id.as_str())
+    .fetch_optional(pool.get_ref())
+    .await;
+
+    let channel = match channel {
+        Ok(Some(channel)) => channel,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(ErrorResponse {
+                error: "NotFound".to_string(),
+                message: "Channel not found".to_string(),
+                timestamp: Utc::now(),
+            }));
+        }
+        Err(e) => {
+            tracing::error!("Error fetching channel: {}", e);
+            return Ok(create_error_response("DatabaseError", "Failed to fetch channel"));
+        }
+    };
+
+    if channel.party_a_paymail != caller && channel.party_b_paymail != caller {
+        return Ok(forbidden_response("Only channel parties can export channel state"));
+    }
+
+    match build_bundle(pool.get_ref(), channel).await {
+        Ok(bundle) => {
+            tracing::info!("Channel {} exported by {} ({} states)",
+                channel_id, caller, bundle.states.len());
+            Ok(HttpResponse::Ok()
+                .insert_header((
+                    "Content-Disposition",
+                    format!("attachment; filename=\"channel-{}.json\"", channel_id.as_str()),
+                ))
+                .json(bundle))
+        }
+        Err(e) => {
+            tracing::error!("Error exporting channel {}: {}", channel_id, e);
+            Ok(create_error_response("DatabaseError", "Failed to export channel"))
+        }
+    }
+}
+
+// ============================================================================
+// RESTORE
+// ============================================================================
+
+pub async fn verify_bundle(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    bundle: web::Json<ChannelBundle>,
+) -> Result<HttpResponse> {
+    if let Err(response) = authenticated_claims(&http_req, &jwt) {
+        return Ok(response);
+    }
+
+    match verify_against_keys(pool.get_ref(), &bundle).await {
+        Ok(verification) => Ok(HttpResponse::Ok().json(verification)),
+        Err(e) => {
+            tracing::error!("Error loading party keys for {}: {}", bundle.channel.channel_id, e);
+            Ok(create_error_response("DatabaseError", "Failed to load party keys"))
+        }
+    }
+}
+
+pub async fn restore_channel(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    bundle: web::Json<ChannelBundle>,
+) -> Result<HttpResponse> {
+    let claims = match authenticated_claims(&http_req, &jwt) {
+        Ok(claims) => claims,
+        Err(response) => return Ok(response),
+    };
+    if !claims.has_permission("admin") {
+        return Ok(forbidden_response("Channel restore requires admin permission"));
+    }
+
+    let verification = match verify_against_keys(pool.get_ref(), &bundle).await {
+        Ok(verification) => verification,
+        Err(e) => {
+            tracing::error!("Error loading party keys for {}: {}", bundle.channel.channel_id, e);
+            return Ok(create_error_response("DatabaseError", "Failed to load party keys"));
+        }
+    };
+    if !verification.valid {
+        return Ok(HttpResponse::UnprocessableEntity().json(serde_json::json!({
+            "error": "InvalidBundle",
+            "verification": verification,
+            "timestamp": Utc::now()
+        })));
+    }
+
+    match apply_bundle(pool.get_ref(), &bundle, &verification).await {
+        Ok(summary) => {
+            tracing::warn!("Channel {} restored from backup by {}: {}",
+                bundle.channel.channel_id, claims.sub, summary);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "channel_id": bundle.channel.channel_id,
+                "verification": verification,
+                "restore": summary
+            })))
+        }
+        Err(e) => {
+            tracing::error!("Error restoring channel {}: {}", bundle.channel.channel_id, e);
+            Ok(create_error_response("RestoreError", &format!("Failed to restore channel: {}", e)))
+        }
+    }
+}
+
+async fn apply_bundle(
+    pool: &PgPool,
+    bundle: &ChannelBundle,
+    verification: &BundleVerification,
+) -> Result<serde_json::Value, sqlx::Error> {
+    let channel = &bundle.channel;
+    let mut tx = pool.begin().await?;
+
+    let existing = sqlx::query_as::<_, PaymentChannel>(
+        "SELECT * FROM payment_channels WHERE channel_id = $1 FOR UPDATE"
+    )
+    .bind(&channel.channel_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    // Only a state both parties signed can move the channel forward
+    let latest = verification
+        .latest_signed_sequence
+        .and_then(|seq| bundle.states.iter().find(|s| s.sequence_number == seq));
+    let mut channel_action = "unchanged";
+
+    match &existing {
+        None => {
+            sqlx::query(
+                r#"
+                INSERT INTO payment_channels (
+                    id, channel_id, party_a_paymail, party_b_paymail,
+                    initial_balance_a, initial_balance_b, current_balance_a, current_balance_b,
+                    status, sequence_number, opened_at, closed_at, last_payment_at,
+                    settlement_txid, timeout_blocks, blockchain_enabled, funding_txid,
+                    funding_address, funding_vout, multisig_script, redeem_script,
+                    current_commitment_txid, dispute_started_at, dispute_start_height,
+                    archived_at, settlement_fee_policy, party_a_payout_address, party_b_payout_address
+                ) VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14,
+                    $15, $16, $17, $18, $19, $20, $21, $22, $23, $24, $25, $26, $27, $28
+                )
+                "#
+            )
+            .bind(channel.id)
+            .bind(&channel.channel_id)
+            .bind(&channel.party_a_paymail)
+            .bind(&channel.party_b_paymail)
+            .bind(channel.initial_balance_a)
+            .bind(channel.initial_balance_b)
+            .bind(channel.current_balance_a)
+            .bind(channel.current_balance_b)
+            .bind(&channel.status)
+            .bind(channel.sequence_number)
+            .bind(channel.opened_at)
+            .bind(channel.closed_at)
+            .bind(channel.last_payment_at)
+            .bind(&channel.settlement_txid)
+            .bind(channel.timeout_blocks)
+            .bind(channel.blockchain_enabled)
+            .bind(&channel.funding_txid)
+            .bind(&channel.funding_address)
+            .bind(channel.funding_vout)
+            .bind(&channel.multisig_script)
+            .bind(&channel.redeem_script)
+            .bind(&channel.current_commitment_txid)
+            .bind(channel.dispute_started_at)
+            .bind(channel.dispute_start_height)
+            .bind(channel.archived_at)
+            .bind(&channel.settlement_fee_policy)
+            .bind(&channel.party_a_payout_address)
+            .bind(&channel.party_b_payout_address)
+            .execute(&mut *tx)
+            .await?;
+            channel_action = "created";
+        }
+        Some(current) => {
+            // Only move forward: a newer signed state in the backup supersedes
+            // what the (possibly rolled back) database holds. The signatures were
+            // checked against the bundle's parties, so they must be this channel's.
+            let same_parties = current.party_a_paymail == channel.party_a_paymail
+                && current.party_b_paymail == channel.party_b_paymail;
+            if let Some(latest) = latest.filter(|_| same_parties) {
+                if latest.sequence_number > current.sequence_number && current.status != "Closed" {
+                    sqlx::query(
+                        r#"
+                        UPDATE payment_channels
+                        SET current_balance_a = $1,
+                            current_balance_b = $2,
+                            sequence_number = $3,
+                            updated_at = NOW()
+                        WHERE channel_id = $4
+                        "#
+                    )
+                    .bind(latest.balance_a)
+                    .bind(latest.balance_b)
+                    .bind(latest.sequence_number)
+                    .bind(&channel.channel_id)
+                    .execute(&mut *tx)
+                    .await?;
+                    channel_action = "advanced";
+                }
+            }
+        }
+    }
+
+    let mut states_restored = 0u64;
+    for state in &bundle.states {
+        states_restored += sqlx::query(
+            r#"
+            INSERT INTO channel_states (
+                id, channel_id, sequence_number, balance_a, balance_b,
+                state_hash, signature_a, signature_b, created_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (channel_id, sequence_number) DO UPDATE
+            SET signature_a = COALESCE(channel_states.signature_a, EXCLUDED.signature_a),
+                signature_b = COALESCE(channel_states.signature_b, EXCLUDED.signature_b),
+                state_hash = COALESCE(channel_states.state_hash, EXCLUDED.state_hash)
+            WHERE channel_states.balance_a = EXCLUDED.balance_a
+              AND channel_states.balance_b = EXCLUDED.balance_b
+            "#
+        )
+        .bind(state.id)
+        .bind(&state.channel_id)
+        .bind(state.sequence_number)
+        .bind(state.balance_a)
+        .bind(state.balance_b)
+        .bind(&state.state_hash)
+        .bind(&state.signature_a)
+        .bind(&state.signature_b)
+        .bind(state.created_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    let mut payments_restored = 0u64;
+    for payment in &bundle.payments {
+        payments_restored += sqlx::query(
+            r#"
+            INSERT INTO channel_payments (
+                id, channel_id, from_paymail, to_paymail, amount_satoshis,
+                sequence_number, memo, balance_a_after, balance_b_after,
+                created_at, processing_time_ms
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            ON CONFLICT (id) DO NOTHING
+            "#
+        )
+        .bind(payment.id)
+        .bind(&payment.channel_id)
+        .bind(&payment.from_paymail)
+        .bind(&payment.to_paymail)
+        .bind(payment.amount_satoshis)
+        .bind(payment.sequence_number)
+        .bind(&payment.memo)
+        .bind(payment.balance_a_after)
+        .bind(payment.balance_b_after)
+        .bind(payment.created_at)
+        .bind(payment.processing_time_ms)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+    }
+
+    let channel_uuid = existing.as_ref().map(|c| c.id).unwrap_or(channel.id);
+    let mut templates_restored = 0u64;
+    for template in &bundle.commitment_txs {
+        let inserted = sqlx::query(
+            r#"
+            INSERT INTO transaction_templates (
+                id, template_type, channel_id, tx_hex, txid, status,
+                party_a_signed, party_b_signed, sequence_number, timelock_blocks,
+                created_at, broadcast_at, confirmed_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (id) DO NOTHING
+            "#
+        )
+        .bind(template.id)
+        .bind(&template.template_type)
+        .bind(channel_uuid)
+        .bind(&template.tx_hex)
+        .bind(&template.txid)
+        .bind(&template.status)
+        .bind(template.party_a_signed)
+        .bind(template.party_b_signed)
+        .bind(template.sequence_number)
+        .bind(template.timelock_blocks)
+        .bind(template.created_at)
+        .bind(template.broadcast_at)
+        .bind(template.confirmed_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        templates_restored += inserted;
+
+        for signature in &template.signatures {
+            sqlx::query(
+                r#"
+                INSERT INTO transaction_signatures (
+                    id, tx_template_id, paymail, signature, pubkey, sighash_type, signed_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (id) DO NOTHING
+                "#
+            )
+            .bind(signature.id)
+            .bind(template.id)
+            .bind(&signature.paymail)
+            .bind(&signature.signature)
+            .bind(&signature.pubkey)
+            .bind(signature.sighash_type)
+            .bind(signature.signed_at)
+            .execute(&mut *tx)
+            .await?;
+        }
+    }
+
+    tx.commit().await?;
+
+    Ok(serde_json::json!({
+        "channel": channel_action,
+        "latest_sequence": verification.latest_sequence,
+        "database_sequence_before": existing.as_ref().map(|c| c.sequence_number),
+        "states_restored": states_restored,
+        "payments_restored": payments_restored,
+        "commitment_txs_restored": templates_restored
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Message, PublicKey, Secp256k1, SecretKey};
+
+    fn keypair(byte: u8) -> (SecretKey, String) {
+        let secret = SecretKey::from_slice(&[byte; 32]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&Secp256k1::new(), &secret);
+        (secret, hex::encode(pubkey.serialize()))
+    }
+
+    fn sign(secret: &SecretKey, state_hash: &str) -> String {
+        let message = Message::from_digest_slice(&hex::decode(state_hash).unwrap()).unwrap();
+        hex::encode(Secp256k1::new().sign_ecdsa(&message, secret).serialize_der())
+    }
+
+    fn state(seq: i64, a: i64, b: i64, signers: &[&SecretKey]) -> ChannelStateRecord {
+        let hash = compute_state_hash("0xabc", seq, a, b);
+        ChannelStateRecord {
+            id: Uuid::new_v4(),
+            channel_id: "0xabc".to_string(),
+            sequence_number: seq,
+            balance_a: a,
+            balance_b: b,
+            signature_a: signers.first().map(|key| sign(key, &hash)),
+            signature_b: signers.get(1).map(|key| sign(key, &hash)),
+            state_hash: Some(hash),
+            created_at: Utc::now().naive_utc(),
+        }
+    }
+
+    fn bundle(states: Vec<ChannelStateRecord>) -> ChannelBundle {
+        let channel: PaymentChannel = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "channel_id": "0xabc",
+            "party_a_paymail": "alice@bsvbank.local",
+            "party_b_paymail": "bob@bsvbank.local",
+            "initial_balance_a": 1000,
+            "initial_balance_b": 0,
+            "current_balance_a": 1000,
+            "current_balance_b": 0,
+            "status": "Open",
+            "sequence_number": 0,
+            "opened_at": Utc::now(),
+            "timeout_blocks": 144,
+            "created_at": Utc::now(),
+            "updated_at": Utc::now(),
+        }))
+        .unwrap();
+        let mut bundle = ChannelBundle {
+            format_version: BUNDLE_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            channel,
+            states,
+            payments: Vec::new(),
+            commitment_txs: Vec::new(),
+            bundle_hash: String::new(),
+        };
+        bundle.bundle_hash = compute_bundle_hash(&bundle).unwrap();
+        bundle
+    }
+
+    #[test]
+    fn test_signatures_are_checked_against_party_keys() {
+        let (alice, alice_pub) = keypair(1);
+        let (bob, bob_pub) = keypair(2);
+        let bundle = bundle(vec![
+            state(0, 1000, 0, &[&alice, &bob]),
+            state(1, 900, 100, &[&alice, &bob]),
+            state(2, 800, 200, &[&alice]),
+        ]);
+
+        let verification = verify_bundle_contents(&bundle, Some(&alice_pub), Some(&bob_pub));
+        assert!(verification.valid, "{:?}", verification.errors);
+        assert_eq!(verification.signed_states, 2);
+        assert_eq!(verification.latest_sequence, 2);
+        assert_eq!(verification.latest_signed_sequence, Some(1));
+    }
+
+    #[test]
+    fn test_bundle_round_trip_keeps_settlement_details() {
+        let (alice, alice_pub) = keypair(1);
+        let (bob, bob_pub) = keypair(2);
+        let mut original = bundle(vec![state(0, 1000, 0, &[&alice, &bob])]);
+        original.channel.status = "Closed".to_string();
+        original.channel.archived_at = Some(Utc::now());
+        original.channel.settlement_fee_policy = "proportional".to_string();
+        original.channel.party_a_payout_address = Some("1NyMg76BQxDvV6vRsQugNS4ED2hpZCJtwK".to_string());
+        original.channel.party_b_payout_address = Some("1BoatSLRHtKNngkdXEeobR76b53LETtpyT".to_string());
+        original.bundle_hash = compute_bundle_hash(&original).unwrap();
+
+        let restored: ChannelBundle = serde_json::from_str(&serde_json::to_string(&original).unwrap()).unwrap();
+        assert_eq!(restored.channel.archived_at, original.channel.archived_at);
+        assert_eq!(restored.channel.settlement_fee_policy, "proportional");
+        assert_eq!(restored.channel.party_a_payout_address, original.channel.party_a_payout_address);
+        assert_eq!(restored.channel.party_b_payout_address, original.channel.party_b_payout_address);
+        let verification = verify_bundle_contents(&restored, Some(&alice_pub), Some(&bob_pub));
+        assert!(verification.valid, "{:?}", verification.errors);
+    }
+
+    #[test]
+    fn test_forged_signatures_are_rejected() {
+        let (alice, alice_pub) = keypair(1);
+        let (_, bob_pub) = keypair(2);
+        let (mallory, _) = keypair(3);
+        // A rolled-forward state signed by someone holding neither party's key
+        let bundle = bundle(vec![
+            state(0, 1000, 0, &[&alice, &mallory]),
+            state(1, 0, 1000, &[&mallory, &mallory]),
+        ]);
+
+        let verification = verify_bundle_contents(&bundle, Some(&alice_pub), Some(&bob_pub));
+        assert!(!verification.valid);
+        assert_eq!(verification.signed_states, 0);
+        assert_eq!(verification.latest_signed_sequence, None);
+
+        let verification = verify_bundle_contents(&bundle, Some(&alice_pub), None);
+        assert!(verification.errors.iter().any(|e| e.contains("no public key on record")));
+    }
+}
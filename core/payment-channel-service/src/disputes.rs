@@ -0,0 +1,506 @@
+// core/payment-channel-service/src/disputes.rs
+// Dispute evidence: co-signed channel states submitted during the timeout window
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, Utc};
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::backup::compute_state_hash;
+use crate::webhooks::{self, WebhookDispatcher};
+use crate::{
+    authenticated_paymail, create_error_response, forbidden_response, ChainClient, ErrorResponse,
+    PaymentChannel,
+};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// A channel state signed by both parties. Signatures are hex DER ECDSA over
+/// the 32-byte state hash from `compute_state_hash`.
+#[derive(Debug, Deserialize)]
+pub struct SubmitEvidenceRequest {
+    pub sequence_number: i64,
+    pub balance_a: i64,
+    pub balance_b: i64,
+    pub signature_a: String,
+    pub signature_b: String,
+}
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct DisputeEvidence {
+    pub id: Uuid,
+    pub channel_id: String,
+    pub submitted_by: String,
+    pub sequence_number: i64,
+    pub balance_a: i64,
+    pub balance_b: i64,
+    pub state_hash: String,
+    pub signature_a: String,
+    pub signature_b: String,
+    pub submitted_at: DateTime<Utc>,
+    pub submitted_at_height: i32,
+}
+
+/// The state a dispute settles with
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SettlementState {
+    pub sequence_number: i64,
+    pub balance_a: i64,
+    pub balance_b: i64,
+    pub from_evidence: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum EvidenceError {
+    #[error("Channel is not in dispute (status: {0})")]
+    NotDisputed(String),
+    #[error("Dispute window closed at height {0}")]
+    WindowClosed(i32),
+    #[error("Balances must be non-negative and sum to channel capacity ({0})")]
+    BalanceMismatch(i64),
+    #[error("Sequence {submitted} does not exceed the latest known state {best}")]
+    StaleSequence { submitted: i64, best: i64 },
+    #[error("No public key on record for {0}")]
+    MissingPublicKey(String),
+    #[error("Invalid signature from {0}")]
+    InvalidSignature(String),
+}
+
+impl EvidenceError {
+    fn error_code(&self) -> &'static str {
+        match self {
+            EvidenceError::NotDisputed(_) => "NotDisputed",
+            EvidenceError::WindowClosed(_) => "DisputeWindowClosed",
+            EvidenceError::BalanceMismatch(_) => "BalanceMismatch",
+            EvidenceError::StaleSequence { .. } => "StaleSequence",
+            EvidenceError::MissingPublicKey(_) => "MissingPublicKey",
+            EvidenceError::InvalidSignature(_) => "InvalidSignature",
+        }
+    }
+}
+
+// ============================================================================
+// VERIFICATION
+// ============================================================================
+
+/// Verify a hex DER signature over a hex state hash with a hex-encoded pubkey
+pub fn verify_state_signature(state_hash: &str, signature_hex: &str, pubkey_hex: &str) -> bool {
+    let digest = match hex::decode(state_hash) {
+        Ok(bytes) => bytes,
+        Err(_) => return false,
+    };
+    let message = match Message::from_digest_slice(&digest) {
+        Ok(message) => message,
+        Err(_) => return false,
+    };
+    let signature = match hex::decode(signature_hex).ok().and_then(|b| Signature::from_der(&b).ok()) {
+        Some(mut signature) => {
+            signature.normalize_s();
+            signature
+        }
+        None => return false,
+    };
+    let pubkey = match hex::decode(pubkey_hex).ok().and_then(|b| PublicKey::from_slice(&b).ok()) {
+        Some(pubkey) => pubkey,
+        None => return false,
+    };
+
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, &pubkey)
+        .is_ok()
+}
+
+/// Check a submitted state against the channel and both parties' keys. It must be newer
+/// than both the best evidence so far and the state the service recorded.
+/// Returns the state hash on success.
+pub fn verify_evidence(
+    channel: &PaymentChannel,
+    evidence: &SubmitEvidenceRequest,
+    best_sequence: Option<i64>,
+    pubkey_a: &str,
+    pubkey_b: &str,
+) -> Result<String, EvidenceError> {
+    let capacity = channel.initial_balance_a + channel.initial_balance_b;
+    if evidence.balance_a < 0
+        || evidence.balance_b < 0
+        || evidence.balance_a + evidence.balance_b != capacity
+    {
+        return Err(EvidenceError::BalanceMismatch(capacity));
+    }
+
+    let best = best_sequence.map_or(channel.sequence_number, |best| best.max(channel.sequence_number));
+    if evidence.sequence_number <= best {
+        return Err(EvidenceError::StaleSequence { submitted: evidence.sequence_number, best });
+    }
+
+    let state_hash = compute_state_hash(
+        &channel.channel_id, evidence.sequence_number, evidence.balance_a, evidence.balance_b,
+    );
+
+    if !verify_state_signature(&state_hash, &evidence.signature_a, pubkey_a) {
+        return Err(EvidenceError::InvalidSignature(channel.party_a_paymail.clone()));
+    }
+    if !verify_state_signature(&state_hash, &evidence.signature_b, pubkey_b) {
+        return Err(EvidenceError::InvalidSignature(channel.party_b_paymail.clone()));
+    }
+
+    Ok(state_hash)
+}
+
+/// Settle with the highest verified evidence; without any newer than the balances the
+/// service itself recorded, fall back to those.
+pub fn select_settlement_state(channel: &PaymentChannel, best: Option<&DisputeEvidence>) -> SettlementState {
+    match best.filter(|evidence| evidence.sequence_number > channel.sequence_number) {
+        Some(evidence) => SettlementState {
+            sequence_number: evidence.sequence_number,
+            balance_a: evidence.balance_a,
+            balance_b: evidence.balance_b,
+            from_evidence: true,
+        },
+        None => SettlementState {
+            sequence_number: channel.sequence_number,
+            balance_a: channel.current_balance_a,
+            balance_b: channel.current_balance_b,
+            from_evidence: false,
+        },
+    }
+}
+
+pub async fn best_evidence(pool: &PgPool, channel_id: &str) -> Result<Option<DisputeEvidence>, sqlx::Error> {
+    sqlx::query_as::<_, DisputeEvidence>(
+        r#"
+        SELECT * FROM channel_dispute_evidence
+        WHERE channel_id = $1
+        ORDER BY sequence_number DESC
+        LIMIT 1
+        "#
+    )
+    .bind(channel_id)
+    .fetch_optional(pool)
+    .await
+}
+
+pub async fn party_public_key(pool: &PgPool, paymail: &str) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar::<_, Option<String>>("SELECT public_key FROM users WHERE paymail = $1")
+        .bind(paymail)
+        .fetch_optional(pool)
+        .await
+        .map(Option::flatten)
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+pub async fn submit_evidence(
+    pool: web::Data<PgPool>,
+    chain: web::Data<ChainClient>,
+    dispatcher: web::Data<WebhookDispatcher>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    channel_id: web::Path<String>,
+    request: web::Json<SubmitEvidenceRequest>,
+) -> Result<HttpResponse> {
+    let caller = match authenticated_paymail(&http_req, &jwt) {
+        Ok(paymail) => paymail,
+        Err(response) => return Ok(response),
+    };
+
+    let channel = match sqlx::query_as::<_, PaymentChannel>(
+        "SELECT * FROM payment_channels WHERE channel_id = $1"
+    )
+    .bind(channel_id.as_str())
+    .fetch_optional(pool.get_ref())
+    .await
+    {
+        Ok(Some(channel)) => channel,
+        Ok(None) => {
+            return Ok(HttpResponse::NotFound().json(ErrorResponse {
+                error: "NotFound".to_string(),
+                message: "Channel not found".to_string(),
+                timestamp: Utc::now(),
+            }))
+        }
+        Err(e) => {
+            tracing::error!("Error fetching channel: {}", e);
+            return Ok(create_error_response("DatabaseError", "Failed to fetch channel"));
+        }
+    };
+
+    if channel.party_a_paymail != caller && channel.party_b_paymail != caller {
+        return Ok(forbidden_response("Only channel parties can submit evidence"));
+    }
+
+    let reject = |e: EvidenceError| create_error_response(e.error_code(), &e.to_string());
+
+    if channel.status != "Disputed" {
+        return Ok(reject(EvidenceError::NotDisputed(channel.status.clone())));
+    }
+
+    let current_height = match chain.current_height().await {
+        Ok(height) => height,
+        Err(e) => {
+            tracing::error!("Cannot determine chain height for evidence: {}", e);
+            return Ok(HttpResponse::ServiceUnavailable().json(ErrorResponse {
+                error: "ChainUnavailable".to_string(),
+                message: "Unable to determine current block height".to_string(),
+                timestamp: Utc::now(),
+            }));
+        }
+    };
+    if let Some(start) = channel.dispute_start_height {
+        let deadline = start + channel.timeout_blocks;
+        if current_height >= deadline {
+            return Ok(reject(EvidenceError::WindowClosed(deadline)));
+        }
+    }
+
+    let best = match best_evidence(pool.get_ref(), &channel.channel_id).await {
+        Ok(best) => best,
+        Err(e) => {
+            tracing::error!("Error fetching evidence: {}", e);
+            return Ok(create_error_response("DatabaseError", "Failed to fetch evidence"));
+        }
+    };
+
+    let mut pubkeys = Vec::with_capacity(2);
+    for paymail in [&channel.party_a_paymail, &channel.party_b_paymail] {
+        match party_public_key(pool.get_ref(), paymail).await {
+            Ok(Some(key)) => pubkeys.push(key),
+            Ok(None) => return Ok(reject(EvidenceError::MissingPublicKey(paymail.clone()))),
+            Err(e) => {
+                tracing::error!("Error fetching public key: {}", e);
+                return Ok(create_error_response("DatabaseError", "Failed to fetch public keys"));
+            }
+        }
+    }
+
+    let state_hash = match verify_evidence(
+        &channel, &request, best.as_ref().map(|b| b.sequence_number), &pubkeys[0], &pubkeys[1],
+    ) {
+        Ok(hash) => hash,
+        Err(e) => {
+            tracing::warn!("Rejected dispute evidence for {} from {}: {}", channel.channel_id, caller, e);
+            return Ok(reject(e));
+        }
+    };
+
+    let stored = sqlx::query_as::<_, DisputeEvidence>(
+        r#"
+        INSERT INTO channel_dispute_evidence (
+            channel_id, submitted_by, sequence_number, balance_a, balance_b,
+            state_hash, signature_a, signature_b, submitted_at_height
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING *
+        "#
+    )
+    .bind(&channel.channel_id)
+    .bind(&caller)
+    .bind(request.sequence_number)
+    .bind(request.balance_a)
+    .bind(request.balance_b)
+    .bind(&state_hash)
+    .bind(&request.signature_a)
+    .bind(&request.signature_b)
+    .bind(current_height)
+    .fetch_one(pool.get_ref())
+    .await;
+
+    match stored {
+        Ok(evidence) => {
+            tracing::info!("Accepted dispute evidence for {} at sequence {} from {}",
+                channel.channel_id, evidence.sequence_number, caller);
+            dispatcher.emit(&channel.channel_id, webhooks::EVENT_DISPUTE_EVIDENCE, serde_json::json!({
+                "submitted_by": caller,
+                "sequence_number": evidence.sequence_number,
+                "balance_a": evidence.balance_a,
+                "balance_b": evidence.balance_b,
+            }));
+            Ok(HttpResponse::Created().json(evidence))
+        }
+        Err(e) => {
+            tracing::error!("Error storing evidence: {}", e);
+            Ok(create_error_response("DatabaseError", "Failed to store evidence"))
+        }
+    }
+}
+
+pub async fn list_evidence(
+    pool: web::Data<PgPool>,
+    channel_id: web::Path<String>,
+) -> Result<HttpResponse> {
+    let evidence = sqlx::query_as::<_, DisputeEvidence>(
+        r#"
+        SELECT * FROM channel_dispute_evidence
+        WHERE channel_id = $1
+        ORDER BY sequence_number DESC
+        "#
+    )
+    .bind(channel_id.as_str())
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match evidence {
+        Ok(evidence) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "channel_id": channel_id.as_str(),
+            "total": evidence.len(),
+            "evidence": evidence
+        }))),
+        Err(e) => {
+            tracing::error!("Error fetching evidence: {}", e);
+            Ok(create_error_response("DatabaseError", "Failed to fetch evidence"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::SecretKey;
+
+    fn channel() -> PaymentChannel {
+        let mut channel: PaymentChannel = serde_json::from_value(serde_json::json!({
+            "id": Uuid::nil(),
+            "channel_id": "0xabc",
+            "party_a_paymail": "alice@bsvbank.local",
+            "party_b_paymail": "bob@bsvbank.local",
+            "initial_balance_a": 1000,
+            "initial_balance_b": 0,
+            "current_balance_a": 1000,
+            "current_balance_b": 0,
+            "status": "Disputed",
+            "sequence_number": 0,
+            "opened_at": Utc::now(),
+            "timeout_blocks": 144,
+            "created_at": Utc::now(),
+            "updated_at": Utc::now(),
+        }))
+        .unwrap();
+        channel.dispute_start_height = Some(800_000);
+        channel
+    }
+
+    fn keypair(byte: u8) -> (SecretKey, String) {
+        let secp = Secp256k1::new();
+        let secret = SecretKey::from_slice(&[byte; 32]).unwrap();
+        let pubkey = PublicKey::from_secret_key(&secp, &secret);
+        (secret, hex::encode(pubkey.serialize()))
+    }
+
+    fn sign(secret: &SecretKey, state_hash: &str) -> String {
+        let digest = hex::decode(state_hash).unwrap();
+        let message = Message::from_digest_slice(&digest).unwrap();
+        hex::encode(Secp256k1::new().sign_ecdsa(&message, secret).serialize_der())
+    }
+
+    fn evidence(seq: i64, a: i64, b: i64, key_a: &SecretKey, key_b: &SecretKey) -> SubmitEvidenceRequest {
+        let hash = compute_state_hash("0xabc", seq, a, b);
+        SubmitEvidenceRequest {
+            sequence_number: seq,
+            balance_a: a,
+            balance_b: b,
+            signature_a: sign(key_a, &hash),
+            signature_b: sign(key_b, &hash),
+        }
+    }
+
+    #[test]
+    fn test_accepts_cosigned_state() {
+        let (sk_a, pk_a) = keypair(1);
+        let (sk_b, pk_b) = keypair(2);
+        let ev = evidence(5, 400, 600, &sk_a, &sk_b);
+
+        let hash = verify_evidence(&channel(), &ev, Some(3), &pk_a, &pk_b).unwrap();
+        assert_eq!(hash, compute_state_hash("0xabc", 5, 400, 600));
+    }
+
+    #[test]
+    fn test_rejects_bad_signature_and_stale_sequence() {
+        let (sk_a, pk_a) = keypair(1);
+        let (_, pk_b) = keypair(2);
+        let (sk_mallory, _) = keypair(3);
+
+        let forged = evidence(5, 400, 600, &sk_a, &sk_mallory);
+        assert!(matches!(
+            verify_evidence(&channel(), &forged, None, &pk_a, &pk_b),
+            Err(EvidenceError::InvalidSignature(_))
+        ));
+
+        let (sk_b, _) = keypair(2);
+        let stale = evidence(3, 400, 600, &sk_a, &sk_b);
+        assert!(matches!(
+            verify_evidence(&channel(), &stale, Some(3), &pk_a, &pk_b),
+            Err(EvidenceError::StaleSequence { submitted: 3, best: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_unbalanced_state() {
+        let (sk_a, pk_a) = keypair(1);
+        let (sk_b, pk_b) = keypair(2);
+        let inflated = evidence(5, 900, 600, &sk_a, &sk_b);
+        assert!(matches!(
+            verify_evidence(&channel(), &inflated, None, &pk_a, &pk_b),
+            Err(EvidenceError::BalanceMismatch(1000))
+        ));
+    }
+
+    #[test]
+    fn test_settlement_prefers_evidence() {
+        let mut channel = channel();
+        assert_eq!(
+            select_settlement_state(&channel, None),
+            SettlementState { sequence_number: 0, balance_a: 1000, balance_b: 0, from_evidence: false }
+        );
+
+        let best = DisputeEvidence {
+            id: Uuid::nil(),
+            channel_id: "0xabc".to_string(),
+            submitted_by: "bob@bsvbank.local".to_string(),
+            sequence_number: 7,
+            balance_a: 300,
+            balance_b: 700,
+            state_hash: String::new(),
+            signature_a: String::new(),
+            signature_b: String::new(),
+            submitted_at: Utc::now(),
+            submitted_at_height: 800_010,
+        };
+        let state = select_settlement_state(&channel, Some(&best));
+        assert!(state.from_evidence);
+        assert_eq!((state.balance_a, state.balance_b), (300, 700));
+
+        // Evidence older than the recorded state cannot roll it back
+        channel.sequence_number = 9;
+        channel.current_balance_a = 100;
+        channel.current_balance_b = 900;
+        let state = select_settlement_state(&channel, Some(&best));
+        assert!(!state.from_evidence);
+        assert_eq!((state.sequence_number, state.balance_a, state.balance_b), (9, 100, 900));
+    }
+
+    #[test]
+    fn test_rejects_evidence_behind_channel_state() {
+        let (sk_a, pk_a) = keypair(1);
+        let (sk_b, pk_b) = keypair(2);
+        let mut channel = channel();
+        channel.sequence_number = 6;
+
+        let stale = evidence(5, 400, 600, &sk_a, &sk_b);
+        assert!(matches!(
+            verify_evidence(&channel, &stale, None, &pk_a, &pk_b),
+            Err(EvidenceError::StaleSequence { submitted: 5, best: 6 })
+        ));
+        assert!(matches!(
+            verify_evidence(&channel, &stale, Some(2), &pk_a, &pk_b),
+            Err(EvidenceError::StaleSequence { submitted: 5, best: 6 })
+        ));
+        assert!(verify_evidence(&channel, &evidence(7, 400, 600, &sk_a, &sk_b), Some(2), &pk_a, &pk_b).is_ok());
+    }
+}
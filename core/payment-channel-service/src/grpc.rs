@@ -0,0 +1,306 @@
+// core/payment-channel-service/src/grpc.rs
+// gRPC front end: OpenChannel, SendPayment and streaming WatchChannel
+
+use bsv_bank_common::{auth::extract_bearer_token, ChannelMetrics, Claims, JwtManager};
+use chrono::Utc;
+use sqlx::PgPool;
+use std::net::SocketAddr;
+use std::time::Instant;
+use tokio::sync::{broadcast, mpsc};
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status};
+
+use crate::payments::{self, PaymentError};
+use crate::webhooks::{self, WebhookDispatcher};
+use crate::{authorize_open, insert_channel, OpenChannelRequest, PaymentChannel, ServiceError};
+
+pub mod pb {
+    tonic::include_proto!("bsvbank.channels.v1");
+}
+
+use pb::channel_service_server::{ChannelService, ChannelServiceServer};
+use pb::FeePolicy;
+
+/// Buffered updates per watcher before the stream applies back-pressure
+const WATCH_BUFFER: usize = 64;
+
+#[derive(Clone)]
+pub struct ChannelGrpcService {
+    pool: PgPool,
+    metrics: ChannelMetrics,
+    dispatcher: WebhookDispatcher,
+    jwt: JwtManager,
+}
+
+impl ChannelGrpcService {
+    pub fn new(pool: PgPool, metrics: ChannelMetrics, dispatcher: WebhookDispatcher, jwt: JwtManager) -> Self {
+        Self { pool, metrics, dispatcher, jwt }
+    }
+
+    /// Same bearer-token rules as the HTTP API, read from `authorization` metadata
+    #[allow(clippy::result_large_err)] // tonic handlers return `Status` unboxed
+    fn authenticated_claims<T>(&self, request: &Request<T>) -> Result<Claims, Status> {
+        let header = request
+            .metadata()
+            .get("authorization")
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| Status::unauthenticated("Missing authorization metadata"))?;
+
+        let token = extract_bearer_token(header)
+            .map_err(|e| Status::unauthenticated(e.to_string()))?;
+
+        self.jwt
+            .verify_token(&token)
+            .map_err(|e| Status::unauthenticated(e.to_string()))
+    }
+
+    #[allow(clippy::result_large_err)]
+    fn authenticated_paymail<T>(&self, request: &Request<T>) -> Result<String, Status> {
+        self.authenticated_claims(request).map(|claims| claims.sub)
+    }
+
+    async fn load_channel(&self, channel_id: &str) -> Result<PaymentChannel, Status> {
+        sqlx::query_as::<_, PaymentChannel>("SELECT * FROM payment_channels WHERE channel_id = $1")
+            .bind(channel_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|e| {
+                tracing::error!("Error fetching channel: {}", e);
+                Status::internal("Failed to fetch channel")
+            })?
+            .ok_or_else(|| Status::not_found("Channel not found"))
+    }
+}
+
+fn fee_policy_name(policy: FeePolicy) -> &'static str {
+    match policy {
+        FeePolicy::Payer => "payer",
+        FeePolicy::Proportional => "proportional",
+        FeePolicy::Split | FeePolicy::Unspecified => "split",
+    }
+}
+
+fn to_proto(channel: &PaymentChannel) -> pb::Channel {
+    pb::Channel {
+        channel_id: channel.channel_id.clone(),
+        party_a_paymail: channel.party_a_paymail.clone(),
+        party_b_paymail: channel.party_b_paymail.clone(),
+        balance_a: channel.current_balance_a,
+        balance_b: channel.current_balance_b,
+        sequence_number: channel.sequence_number,
+        status: channel.status.clone(),
+        timeout_blocks: channel.timeout_blocks,
+        settlement_fee_policy: channel.settlement_fee_policy.clone(),
+    }
+}
+
+fn service_status(e: ServiceError) -> Status {
+    match e {
+        ServiceError::ValidationError(msg) => Status::invalid_argument(msg),
+        ServiceError::BusinessError(msg) => Status::failed_precondition(msg),
+        ServiceError::DatabaseError(msg) => {
+            tracing::error!("Database error: {}", msg);
+            Status::internal("Database error")
+        }
+    }
+}
+
+fn payment_status(e: PaymentError) -> Status {
+    match e {
+        PaymentError::ChannelNotFound => Status::not_found(e.to_string()),
+        PaymentError::NotAParty(_) => Status::permission_denied(e.to_string()),
+        PaymentError::LimitExceeded(_) => Status::resource_exhausted(e.to_string()),
+        PaymentError::ChannelInactive(_) | PaymentError::InsufficientBalance { .. } => {
+            Status::failed_precondition(e.to_string())
+        }
+        PaymentError::SelfPayment | PaymentError::InvalidAmount => Status::invalid_argument(e.to_string()),
+        PaymentError::Database(ref db) => {
+            tracing::error!("Payment database error: {}", db);
+            Status::internal("Failed to process payment")
+        }
+    }
+}
+
+fn update(event: &str, channel: &PaymentChannel) -> pb::ChannelUpdate {
+    pb::ChannelUpdate {
+        event: event.to_string(),
+        channel: Some(to_proto(channel)),
+        timestamp_ms: Utc::now().timestamp_millis(),
+    }
+}
+
+#[tonic::async_trait]
+impl ChannelService for ChannelGrpcService {
+    async fn open_channel(
+        &self,
+        request: Request<pb::OpenChannelRequest>,
+    ) -> Result<Response<pb::Channel>, Status> {
+        let claims = self.authenticated_claims(&request)?;
+        let req = request.into_inner();
+        let open = OpenChannelRequest {
+            settlement_fee_policy: fee_policy_name(req.settlement_fee_policy()).to_string(),
+            party_a_paymail: req.party_a_paymail,
+            party_b_paymail: req.party_b_paymail,
+            initial_balance_a: req.initial_balance_a,
+            initial_balance_b: req.initial_balance_b,
+            timeout_blocks: if req.timeout_blocks == 0 { 144 } else { req.timeout_blocks },
+            webhook_url: None,
+            party_a_payout_address: Some(req.party_a_payout_address).filter(|a| !a.is_empty()),
+        };
+        authorize_open(&claims, &open).map_err(Status::permission_denied)?;
+
+        let channel = insert_channel(&self.pool, &open).await.map_err(service_status)?;
+        self.metrics.record_channel_status("Open");
+
+        self.dispatcher.emit(&channel.channel_id, webhooks::EVENT_CHANNEL_OPENED, serde_json::json!({
+            "party_a_paymail": channel.party_a_paymail,
+            "party_b_paymail": channel.party_b_paymail,
+            "balance_a": channel.current_balance_a,
+            "balance_b": channel.current_balance_b,
+            "timeout_blocks": channel.timeout_blocks,
+        }));
+
+        Ok(Response::new(to_proto(&channel)))
+    }
+
+    async fn send_payment(
+        &self,
+        request: Request<pb::SendPaymentRequest>,
+    ) -> Result<Response<pb::PaymentReceipt>, Status> {
+        let start_time = Instant::now();
+        let caller = self.authenticated_paymail(&request)?;
+        let req = request.into_inner();
+
+        if caller != req.from_paymail {
+            return Err(Status::permission_denied("from_paymail does not match authenticated user"));
+        }
+        for paymail in [&req.from_paymail, &req.to_paymail] {
+            bsv_bank_common::validate_paymail(paymail)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+        }
+        bsv_bank_common::validate_amount(req.amount_satoshis)
+            .map_err(|e| Status::invalid_argument(e.to_string()))?;
+
+        let memo = Some(req.memo.as_str()).filter(|m| !m.is_empty());
+        let payment = payments::process_payment(
+            &self.pool,
+            &req.channel_id,
+            &req.from_paymail,
+            &req.to_paymail,
+            req.amount_satoshis,
+            memo,
+            || start_time.elapsed().as_millis() as i32,
+        )
+        .await
+        .map_err(payment_status)?;
+
+        let processing_time = start_time.elapsed().as_millis() as i32;
+        self.metrics.record_payment(req.amount_satoshis);
+
+        self.dispatcher.emit(&req.channel_id, webhooks::EVENT_PAYMENT_RECEIVED, serde_json::json!({
+            "payment_id": payment.payment_id,
+            "from_paymail": req.from_paymail,
+            "to_paymail": req.to_paymail,
+            "amount_satoshis": req.amount_satoshis,
+            "sequence_number": payment.sequence_number,
+            "balance_a": payment.balance_a,
+            "balance_b": payment.balance_b,
+        }));
+
+        Ok(Response::new(pb::PaymentReceipt {
+            payment_id: payment.payment_id.to_string(),
+            channel_id: req.channel_id,
+            sequence_number: payment.sequence_number,
+            balance_a: payment.balance_a,
+            balance_b: payment.balance_b,
+            processing_time_ms: processing_time,
+        }))
+    }
+
+    type WatchChannelStream = ReceiverStream<Result<pb::ChannelUpdate, Status>>;
+
+    async fn watch_channel(
+        &self,
+        request: Request<pb::WatchChannelRequest>,
+    ) -> Result<Response<Self::WatchChannelStream>, Status> {
+        let caller = self.authenticated_paymail(&request)?;
+        let channel_id = request.into_inner().channel_id;
+
+        // Subscribe before the snapshot so nothing between the two is missed
+        let mut events = self.dispatcher.subscribe();
+        let channel = self.load_channel(&channel_id).await?;
+        if channel.party_a_paymail != caller && channel.party_b_paymail != caller {
+            return Err(Status::permission_denied("Only channel parties can watch a channel"));
+        }
+
+        let (tx, rx) = mpsc::channel(WATCH_BUFFER);
+        let service = self.clone();
+
+        tokio::spawn(async move {
+            let mut closed = channel.status == "Closed";
+            if tx.send(Ok(update("snapshot", &channel))).await.is_err() {
+                return;
+            }
+
+            while !closed {
+                let event = match events.recv().await {
+                    Ok(event) if event.channel_id == channel_id => event.event,
+                    Ok(_) => continue,
+                    // Fell behind: the next re-read still yields the latest state
+                    Err(broadcast::error::RecvError::Lagged(_)) => "resync",
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+
+                let message = match service.load_channel(&channel_id).await {
+                    Ok(channel) => {
+                        closed = channel.status == "Closed";
+                        Ok(update(event, &channel))
+                    }
+                    Err(status) => {
+                        closed = true;
+                        Err(status)
+                    }
+                };
+                if tx.send(message).await.is_err() {
+                    break; // watcher went away
+                }
+            }
+        });
+
+        Ok(Response::new(ReceiverStream::new(rx)))
+    }
+}
+
+pub async fn serve(addr: SocketAddr, service: ChannelGrpcService) {
+    tracing::info!("Starting gRPC server on {}", addr);
+
+    let result = tonic::transport::Server::builder()
+        .add_service(ChannelServiceServer::new(service))
+        .serve(addr)
+        .await;
+
+    if let Err(e) = result {
+        tracing::error!("gRPC server stopped: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_policy_mapping() {
+        assert_eq!(fee_policy_name(FeePolicy::Unspecified), "split");
+        assert_eq!(fee_policy_name(FeePolicy::Payer), "payer");
+        assert_eq!(fee_policy_name(FeePolicy::Proportional), "proportional");
+    }
+
+    #[test]
+    fn test_payment_error_codes() {
+        assert_eq!(payment_status(PaymentError::NotAParty("x".into())).code(), tonic::Code::PermissionDenied);
+        assert_eq!(
+            payment_status(PaymentError::InsufficientBalance { needed: 2, available: 1 }).code(),
+            tonic::Code::FailedPrecondition
+        );
+    }
+}
@@ -0,0 +1,454 @@
+// core/payment-channel-service/src/limits.rs
+// Per-channel spending limits and velocity controls
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{validate_amount, JwtManager};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, PgExecutor, PgPool};
+use uuid::Uuid;
+
+use crate::{
+    authenticated_paymail, create_error_response, forbidden_response, ErrorResponse,
+    PaymentChannel,
+};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct SpendingLimits {
+    pub id: Uuid,
+    pub channel_id: String,
+    pub paymail: String,
+    pub max_payment_satoshis: Option<i64>,
+    pub daily_volume_satoshis: Option<i64>,
+    pub cooldown_seconds: Option<i32>,
+    pub effective_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct SetLimitsRequest {
+    pub max_payment_satoshis: Option<i64>,
+    pub daily_volume_satoshis: Option<i64>,
+    pub cooldown_seconds: Option<i32>,
+}
+
+/// Reason a payment was rejected by the sender's limits
+#[derive(Debug)]
+pub enum LimitViolation {
+    MaxPaymentExceeded { limit: i64 },
+    DailyVolumeExceeded { limit: i64, used: i64 },
+    CooldownActive { retry_after_secs: i64 },
+}
+
+impl LimitViolation {
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            LimitViolation::MaxPaymentExceeded { .. } => "PaymentLimitExceeded",
+            LimitViolation::DailyVolumeExceeded { .. } => "DailyLimitExceeded",
+            LimitViolation::CooldownActive { .. } => "CooldownActive",
+        }
+    }
+
+    pub fn message(&self) -> String {
+        match self {
+            LimitViolation::MaxPaymentExceeded { limit } => {
+                format!("Payment exceeds the per-payment limit of {} satoshis", limit)
+            }
+            LimitViolation::DailyVolumeExceeded { limit, used } => {
+                format!("Payment exceeds the 24h volume cap of {} satoshis ({} already sent)", limit, used)
+            }
+            LimitViolation::CooldownActive { retry_after_secs } => {
+                format!("Cool-down period active; retry in {} seconds", retry_after_secs)
+            }
+        }
+    }
+
+    pub fn to_response(&self) -> HttpResponse {
+        let body = ErrorResponse {
+            error: self.error_code().to_string(),
+            message: self.message(),
+            timestamp: Utc::now(),
+        };
+        match self {
+            LimitViolation::CooldownActive { retry_after_secs } => HttpResponse::TooManyRequests()
+                .insert_header(("Retry-After", retry_after_secs.to_string()))
+                .json(body),
+            _ => HttpResponse::UnprocessableEntity().json(body),
+        }
+    }
+}
+
+fn delay_for_relaxation() -> Duration {
+    let secs = std::env::var("LIMIT_RELAXATION_DELAY_SECS")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(86_400);
+    Duration::seconds(secs)
+}
+
+/// A change relaxes limits if any dimension becomes less restrictive
+/// (a higher cap, a shorter cool-down, or a limit removed entirely).
+pub fn is_relaxation(current: Option<&SpendingLimits>, new: &SetLimitsRequest) -> bool {
+    immediate_limits(current, new) != *new
+}
+
+/// The part of a change that applies at once: every dimension it tightens or leaves alone,
+/// with dimensions it relaxes held at their current value until the delay runs out.
+pub fn immediate_limits(current: Option<&SpendingLimits>, new: &SetLimitsRequest) -> SetLimitsRequest {
+    let current = match current {
+        Some(current) => current,
+        None => return new.clone(),
+    };
+
+    fn looser<T: PartialOrd>(old: Option<T>, new: Option<T>) -> bool {
+        match (old, new) {
+            (Some(_), None) => true,
+            (Some(old), Some(new)) => new > old,
+            _ => false,
+        }
+    }
+    fn tighter_of<T: PartialOrd + Copy>(old: Option<T>, new: Option<T>) -> Option<T> {
+        if looser(old, new) { old } else { new }
+    }
+
+    SetLimitsRequest {
+        max_payment_satoshis: tighter_of(current.max_payment_satoshis, new.max_payment_satoshis),
+        daily_volume_satoshis: tighter_of(current.daily_volume_satoshis, new.daily_volume_satoshis),
+        // A longer cool-down is the tighter one
+        cooldown_seconds: match (current.cooldown_seconds, new.cooldown_seconds) {
+            (Some(old), None) => Some(old),
+            (Some(old), Some(new)) => Some(old.max(new)),
+            (None, new) => new,
+        },
+    }
+}
+
+// ============================================================================
+// ENFORCEMENT
+// ============================================================================
+
+/// The most recently set limits that are in effect. Ordered by when they were set, not when
+/// they took effect, so a delayed relaxation never overrides a tightening made after it.
+async fn effective_limits<'e, E: PgExecutor<'e>>(
+    executor: E,
+    channel_id: &str,
+    paymail: &str,
+) -> Result<Option<SpendingLimits>, sqlx::Error> {
+    sqlx::query_as::<_, SpendingLimits>(
+        r#"
+        SELECT * FROM channel_spending_limits
+        WHERE channel_id = $1 AND paymail = $2 AND effective_at <= NOW()
+        ORDER BY created_at DESC, effective_at DESC
+        LIMIT 1
+        "#
+    )
+    .bind(channel_id)
+    .bind(paymail)
+    .fetch_optional(executor)
+    .await
+}
+
+/// Check `amount` against the sender's effective limits on this channel. Run it on the
+/// payment's transaction with the channel row locked, so concurrent payments cannot each
+/// see the volume and cool-down from before the other.
+pub async fn check_payment(
+    conn: &mut PgConnection,
+    channel_id: &str,
+    paymail: &str,
+    amount: i64,
+) -> Result<Option<LimitViolation>, sqlx::Error> {
+    let limits = match effective_limits(&mut *conn, channel_id, paymail).await? {
+        Some(limits) => limits,
+        None => return Ok(None),
+    };
+
+    if let Some(limit) = limits.max_payment_satoshis {
+        if amount > limit {
+            return Ok(Some(LimitViolation::MaxPaymentExceeded { limit }));
+        }
+    }
+
+    if limits.daily_volume_satoshis.is_none() && limits.cooldown_seconds.is_none() {
+        return Ok(None);
+    }
+
+    let (used_24h, secs_since_last): (i64, Option<i64>) = sqlx::query_as(
+        r#"
+        SELECT
+            COALESCE(SUM(amount_satoshis) FILTER (
+                WHERE created_at > NOW() - INTERVAL '24 hours'
+            ), 0)::BIGINT,
+            EXTRACT(EPOCH FROM (NOW() - MAX(created_at)))::BIGINT
+        FROM channel_payments
+        WHERE channel_id = $1 AND from_paymail = $2
+        "#
+    )
+    .bind(channel_id)
+    .bind(paymail)
+    .fetch_one(conn)
+    .await?;
+
+    if let (Some(cooldown), Some(elapsed)) = (limits.cooldown_seconds, secs_since_last) {
+        let cooldown = cooldown as i64;
+        if elapsed < cooldown {
+            return Ok(Some(LimitViolation::CooldownActive {
+                retry_after_secs: cooldown - elapsed,
+            }));
+        }
+    }
+
+    if let Some(limit) = limits.daily_volume_satoshis {
+        if used_24h + amount > limit {
+            return Ok(Some(LimitViolation::DailyVolumeExceeded { limit, used: used_24h }));
+        }
+    }
+
+    Ok(None)
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+async fn load_party_channel(
+    pool: &PgPool,
+    channel_id: &str,
+    paymail: &str,
+) -> Result<PaymentChannel, HttpResponse> {
+    let channel = sqlx::query_as::<_, PaymentChannel>(
+        "SELECT * FROM payment_channels WHERE channel_id = $1"
+    )
+    .bind(channel_id)
+    .fetch_optional(pool)
+    .await;
+
+    match channel {
+        Ok(Some(channel)) => {
+            if channel.party_a_paymail != paymail && channel.party_b_paymail != paymail {
+                return Err(forbidden_response("Only channel parties can manage spending limits"));
+            }
+            Ok(channel)
+        }
+        Ok(None) => Err(HttpResponse::NotFound().json(ErrorResponse {
+            error: "NotFound".to_string(),
+            message: "Channel not found".to_string(),
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Error fetching channel: {}", e);
+            Err(create_error_response("DatabaseError", "Failed to fetch channel"))
+        }
+    }
+}
+
+pub async fn set_limits(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    channel_id: web::Path<String>,
+    request: web::Json<SetLimitsRequest>,
+) -> Result<HttpResponse> {
+    let caller = match authenticated_paymail(&http_req, &jwt) {
+        Ok(paymail) => paymail,
+        Err(response) => return Ok(response),
+    };
+    if let Err(response) = load_party_channel(&pool, &channel_id, &caller).await {
+        return Ok(response);
+    }
+
+    for amount in [request.max_payment_satoshis, request.daily_volume_satoshis].into_iter().flatten() {
+        if let Err(e) = validate_amount(amount) {
+            return Ok(create_error_response("ValidationError", &e.to_string()));
+        }
+    }
+    if request.cooldown_seconds.map(|c| c < 0).unwrap_or(false) {
+        return Ok(create_error_response("ValidationError", "cooldown_seconds must be non-negative"));
+    }
+
+    let current = match effective_limits(pool.get_ref(), &channel_id, &caller).await {
+        Ok(current) => current,
+        Err(e) => {
+            tracing::error!("Error fetching limits: {}", e);
+            return Ok(create_error_response("DatabaseError", "Failed to fetch limits"));
+        }
+    };
+
+    // A mixed change tightens at once and relaxes after the delay: both rows share the
+    // transaction's created_at, and the delayed one wins the tie once it takes effect
+    let immediate = immediate_limits(current.as_ref(), &request);
+    let relaxed = immediate != *request;
+    let tightened = relaxed && current.as_ref().map(requested_limits) != Some(immediate.clone());
+
+    let result = async {
+        let mut tx = pool.begin().await?;
+        let applied = if tightened {
+            Some(insert_limits(&mut tx, &channel_id, &caller, &immediate, Utc::now()).await?)
+        } else {
+            None
+        };
+        let effective_at = if relaxed { Utc::now() + delay_for_relaxation() } else { Utc::now() };
+        let limits = insert_limits(&mut tx, &channel_id, &caller, &request, effective_at).await?;
+        tx.commit().await?;
+        Ok::<_, sqlx::Error>((limits, applied))
+    }
+    .await;
+
+    match result {
+        Ok((limits, applied)) => {
+            tracing::info!("Spending limits updated on {} for {} (relaxed: {})", channel_id, caller, relaxed);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "limits": limits,
+                "applied": applied,
+                "pending": relaxed,
+                "message": if tightened {
+                    "Tightened limits are in effect; relaxed limits take effect after the safety delay"
+                } else if relaxed {
+                    "Relaxed limits take effect after the safety delay"
+                } else {
+                    "Limits are in effect"
+                }
+            })))
+        }
+        Err(e) => {
+            tracing::error!("Error saving limits: {}", e);
+            Ok(create_error_response("DatabaseError", "Failed to save limits"))
+        }
+    }
+}
+
+fn requested_limits(limits: &SpendingLimits) -> SetLimitsRequest {
+    SetLimitsRequest {
+        max_payment_satoshis: limits.max_payment_satoshis,
+        daily_volume_satoshis: limits.daily_volume_satoshis,
+        cooldown_seconds: limits.cooldown_seconds,
+    }
+}
+
+async fn insert_limits(
+    conn: &mut PgConnection,
+    channel_id: &str,
+    paymail: &str,
+    limits: &SetLimitsRequest,
+    effective_at: DateTime<Utc>,
+) -> Result<SpendingLimits, sqlx::Error> {
+    sqlx::query_as::<_, SpendingLimits>(
+        r#"
+        INSERT INTO channel_spending_limits (
+            channel_id, paymail, max_payment_satoshis, daily_volume_satoshis,
+            cooldown_seconds, effective_at
+        ) VALUES ($1, $2, $3, $4, $5, $6)
+        RETURNING *
+        "#
+    )
+    .bind(channel_id)
+    .bind(paymail)
+    .bind(limits.max_payment_satoshis)
+    .bind(limits.daily_volume_satoshis)
+    .bind(limits.cooldown_seconds)
+    .bind(effective_at)
+    .fetch_one(conn)
+    .await
+}
+
+pub async fn get_limits(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    channel_id: web::Path<String>,
+) -> Result<HttpResponse> {
+    let caller = match authenticated_paymail(&http_req, &jwt) {
+        Ok(paymail) => paymail,
+        Err(response) => return Ok(response),
+    };
+    if let Err(response) = load_party_channel(&pool, &channel_id, &caller).await {
+        return Ok(response);
+    }
+
+    let effective = effective_limits(pool.get_ref(), &channel_id, &caller).await;
+    // Changes still waiting out the delay, less those a later change already in effect supersedes
+    let pending = sqlx::query_as::<_, SpendingLimits>(
+        r#"
+        SELECT * FROM channel_spending_limits l
+        WHERE l.channel_id = $1 AND l.paymail = $2 AND l.effective_at > NOW()
+          AND NOT EXISTS (
+              SELECT 1 FROM channel_spending_limits later
+              WHERE later.channel_id = l.channel_id AND later.paymail = l.paymail
+                AND later.created_at > l.created_at AND later.effective_at <= NOW()
+          )
+        ORDER BY l.effective_at
+        "#
+    )
+    .bind(channel_id.as_str())
+    .bind(&caller)
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match (effective, pending) {
+        (Ok(effective), Ok(pending)) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "channel_id": channel_id.as_str(),
+            "paymail": caller,
+            "effective": effective,
+            "pending": pending
+        }))),
+        _ => Ok(create_error_response("DatabaseError", "Failed to fetch limits")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits(max: Option<i64>, daily: Option<i64>, cooldown: Option<i32>) -> SpendingLimits {
+        SpendingLimits {
+            id: Uuid::new_v4(),
+            channel_id: "0xabc".to_string(),
+            paymail: "alice@bsvbank.local".to_string(),
+            max_payment_satoshis: max,
+            daily_volume_satoshis: daily,
+            cooldown_seconds: cooldown,
+            effective_at: Utc::now(),
+            created_at: Utc::now(),
+        }
+    }
+
+    fn request(max: Option<i64>, daily: Option<i64>, cooldown: Option<i32>) -> SetLimitsRequest {
+        SetLimitsRequest {
+            max_payment_satoshis: max,
+            daily_volume_satoshis: daily,
+            cooldown_seconds: cooldown,
+        }
+    }
+
+    #[test]
+    fn test_first_limits_are_not_relaxation() {
+        assert!(!is_relaxation(None, &request(None, None, None)));
+    }
+
+    #[test]
+    fn test_tightening_is_immediate() {
+        let current = limits(Some(1000), Some(10_000), Some(10));
+        assert!(!is_relaxation(Some(&current), &request(Some(500), Some(5_000), Some(60))));
+    }
+
+    #[test]
+    fn test_relaxation_detected() {
+        let current = limits(Some(1000), Some(10_000), Some(10));
+        assert!(is_relaxation(Some(&current), &request(Some(2000), Some(10_000), Some(10))));
+        assert!(is_relaxation(Some(&current), &request(Some(1000), None, Some(10))));
+        assert!(is_relaxation(Some(&current), &request(Some(1000), Some(10_000), Some(5))));
+    }
+
+    #[test]
+    fn test_mixed_change_tightens_immediately() {
+        let current = limits(Some(1000), Some(10_000), Some(10));
+        let new = request(Some(5_000), Some(2_000), None);
+        assert!(is_relaxation(Some(&current), &new));
+        // The lower daily cap applies now; the higher payment cap and dropped cool-down wait
+        assert_eq!(immediate_limits(Some(&current), &new), request(Some(1000), Some(2_000), Some(10)));
+        assert_eq!(immediate_limits(None, &new), new);
+    }
+}
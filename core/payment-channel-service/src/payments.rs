@@ -0,0 +1,288 @@
+// core/payment-channel-service/src/payments.rs
+// Payment processing under a row lock on the channel
+
+use actix_web::HttpResponse;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::limits::{self, LimitViolation};
+use crate::{create_error_response, ErrorResponse, PaymentChannel};
+
+#[derive(Debug, Error)]
+pub enum PaymentError {
+    #[error("Channel not found")]
+    ChannelNotFound,
+    #[error("Channel is not active (status: {0})")]
+    ChannelInactive(String),
+    #[error("{0} is not a party to this channel")]
+    NotAParty(String),
+    #[error("Cannot pay yourself")]
+    SelfPayment,
+    #[error("Amount must be positive")]
+    InvalidAmount,
+    #[error("Insufficient balance (needed: {needed}, available: {available})")]
+    InsufficientBalance { needed: i64, available: i64 },
+    #[error("{}", .0.message())]
+    LimitExceeded(LimitViolation),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl PaymentError {
+    pub fn to_response(&self) -> HttpResponse {
+        let body = |error: &str| ErrorResponse {
+            error: error.to_string(),
+            message: self.to_string(),
+            timestamp: Utc::now(),
+        };
+        match self {
+            PaymentError::ChannelNotFound => HttpResponse::NotFound().json(body("ChannelNotFound")),
+            PaymentError::NotAParty(_) => HttpResponse::Forbidden().json(body("Unauthorized")),
+            PaymentError::LimitExceeded(violation) => violation.to_response(),
+            PaymentError::ChannelInactive(_) => create_error_response("ChannelInactive", &self.to_string()),
+            PaymentError::InsufficientBalance { .. } => {
+                create_error_response("InsufficientBalance", &self.to_string())
+            }
+            PaymentError::SelfPayment | PaymentError::InvalidAmount => {
+                create_error_response("InvalidRequest", &self.to_string())
+            }
+            PaymentError::Database(e) => create_error_response(
+                "PaymentError",
+                &format!("Failed to process payment: {}", e),
+            ),
+        }
+    }
+}
+
+/// Outcome of a committed payment
+#[derive(Debug)]
+pub struct ProcessedPayment {
+    pub payment_id: Uuid,
+    pub sequence_number: i64,
+    pub balance_a: i64,
+    pub balance_b: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Compute the balances after `from` pays `to` `amount` satoshis.
+///
+/// Pure so the channel rules can be tested without a database.
+pub fn apply_payment(
+    channel: &PaymentChannel,
+    from: &str,
+    to: &str,
+    amount: i64,
+) -> Result<(i64, i64), PaymentError> {
+    if channel.status != "Open" && channel.status != "Active" {
+        return Err(PaymentError::ChannelInactive(channel.status.clone()));
+    }
+
+    let is_party = |p: &str| p == channel.party_a_paymail || p == channel.party_b_paymail;
+    if !is_party(from) {
+        return Err(PaymentError::NotAParty(from.to_string()));
+    }
+    if !is_party(to) {
+        return Err(PaymentError::NotAParty(to.to_string()));
+    }
+    if from == to {
+        return Err(PaymentError::SelfPayment);
+    }
+    if amount <= 0 {
+        return Err(PaymentError::InvalidAmount);
+    }
+
+    let (available, new_a, new_b) = if from == channel.party_a_paymail {
+        (
+            channel.current_balance_a,
+            channel.current_balance_a.checked_sub(amount),
+            channel.current_balance_b.checked_add(amount),
+        )
+    } else {
+        (
+            channel.current_balance_b,
+            channel.current_balance_a.checked_add(amount),
+            channel.current_balance_b.checked_sub(amount),
+        )
+    };
+
+    match (new_a, new_b) {
+        (Some(a), Some(b)) if a >= 0 && b >= 0 => Ok((a, b)),
+        _ => Err(PaymentError::InsufficientBalance { needed: amount, available }),
+    }
+}
+
+/// Process a payment inside an explicit transaction. The channel row is locked
+/// with `SELECT ... FOR UPDATE`, so concurrent payments on a channel run one at
+/// a time against the state the previous one committed.
+pub async fn process_payment(
+    pool: &PgPool,
+    channel_id: &str,
+    from: &str,
+    to: &str,
+    amount: i64,
+    memo: Option<&str>,
+    processing_time_ms: impl Fn() -> i32,
+) -> Result<ProcessedPayment, PaymentError> {
+    let mut tx = pool.begin().await?;
+
+    let channel = sqlx::query_as::<_, PaymentChannel>(
+        "SELECT * FROM payment_channels WHERE channel_id = $1 FOR UPDATE"
+    )
+    .bind(channel_id)
+    .fetch_optional(&mut *tx)
+    .await?
+    .ok_or(PaymentError::ChannelNotFound)?;
+
+    let (balance_a, balance_b) = apply_payment(&channel, from, to, amount)?;
+    // Payments on this channel queue behind the lock, so the volume and cool-down
+    // read here include every payment committed before this one
+    if let Some(violation) = limits::check_payment(&mut tx, channel_id, from, amount).await? {
+        return Err(PaymentError::LimitExceeded(violation));
+    }
+    let new_sequence = channel.sequence_number + 1;
+
+    sqlx::query(
+        r#"
+        UPDATE payment_channels
+        SET current_balance_a = $1,
+            current_balance_b = $2,
+            sequence_number = $3,
+            last_payment_at = NOW(),
+            updated_at = NOW(),
+            status = 'Active'
+        WHERE channel_id = $4
+        "#
+    )
+    .bind(balance_a)
+    .bind(balance_b)
+    .bind(new_sequence)
+    .bind(channel_id)
+    .execute(&mut *tx)
+    .await?;
+
+    let (payment_id, created_at): (Uuid, chrono::NaiveDateTime) = sqlx::query_as(
+        r#"
+        INSERT INTO channel_payments (
+            channel_id, from_paymail, to_paymail, amount_satoshis,
+            sequence_number, memo, balance_a_after, balance_b_after,
+            processing_time_ms
+        ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+        RETURNING id, created_at
+        "#
+    )
+    .bind(channel_id)
+    .bind(from)
+    .bind(to)
+    .bind(amount)
+    .bind(new_sequence)
+    .bind(memo)
+    .bind(balance_a)
+    .bind(balance_b)
+    .bind(processing_time_ms())
+    .fetch_one(&mut *tx)
+    .await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO channel_states (channel_id, sequence_number, balance_a, balance_b)
+        VALUES ($1, $2, $3, $4)
+        "#
+    )
+    .bind(channel_id)
+    .bind(new_sequence)
+    .bind(balance_a)
+    .bind(balance_b)
+    .execute(&mut *tx)
+    .await?;
+
+    tx.commit().await?;
+
+    Ok(ProcessedPayment {
+        payment_id,
+        sequence_number: new_sequence,
+        balance_a,
+        balance_b,
+        created_at: created_at.and_utc(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(status: &str, balance_a: i64, balance_b: i64) -> PaymentChannel {
+        let now = Utc::now();
+        PaymentChannel {
+            id: Uuid::new_v4(),
+            channel_id: "0xabc".to_string(),
+            party_a_paymail: "alice@bsvbank.local".to_string(),
+            party_b_paymail: "bob@bsvbank.local".to_string(),
+            initial_balance_a: balance_a,
+            initial_balance_b: balance_b,
+            current_balance_a: balance_a,
+            current_balance_b: balance_b,
+            status: status.to_string(),
+            sequence_number: 0,
+            opened_at: now,
+            closed_at: None,
+            last_payment_at: None,
+            settlement_txid: None,
+            timeout_blocks: 144,
+            created_at: now,
+            updated_at: now,
+            blockchain_enabled: None,
+            funding_txid: None,
+            funding_address: None,
+            funding_vout: None,
+            funding_confirmations: None,
+            settlement_confirmations: None,
+            spv_verified: None,
+            multisig_script: None,
+            redeem_script: None,
+            current_commitment_txid: None,
+            dispute_started_at: None,
+            dispute_start_height: None,
+            archived_at: None,
+            settlement_fee_policy: "split".to_string(),
+            party_a_payout_address: None,
+            party_b_payout_address: None,
+        }
+    }
+
+    #[test]
+    fn test_payment_moves_balance() {
+        let c = channel("Open", 1000, 500);
+        assert_eq!(apply_payment(&c, "alice@bsvbank.local", "bob@bsvbank.local", 300).unwrap(), (700, 800));
+        assert_eq!(apply_payment(&c, "bob@bsvbank.local", "alice@bsvbank.local", 500).unwrap(), (1500, 0));
+    }
+
+    #[test]
+    fn test_insufficient_balance() {
+        let c = channel("Active", 100, 100);
+        assert!(matches!(
+            apply_payment(&c, "alice@bsvbank.local", "bob@bsvbank.local", 101),
+            Err(PaymentError::InsufficientBalance { needed: 101, available: 100 })
+        ));
+    }
+
+    #[test]
+    fn test_rejects_outsiders_and_inactive_channels() {
+        let c = channel("Open", 100, 100);
+        assert!(matches!(
+            apply_payment(&c, "mallory@bsvbank.local", "bob@bsvbank.local", 1),
+            Err(PaymentError::NotAParty(_))
+        ));
+        assert!(matches!(
+            apply_payment(&c, "alice@bsvbank.local", "alice@bsvbank.local", 1),
+            Err(PaymentError::SelfPayment)
+        ));
+
+        let closed = channel("Disputed", 100, 100);
+        assert!(matches!(
+            apply_payment(&closed, "alice@bsvbank.local", "bob@bsvbank.local", 1),
+            Err(PaymentError::ChannelInactive(_))
+        ));
+    }
+}
@@ -0,0 +1,496 @@
+// core/payment-channel-service/src/webhooks.rs
+// Channel event webhooks: per-channel subscriptions and HMAC-signed delivery
+
+use actix_web::{web, HttpRequest, HttpResponse, Result};
+use bsv_bank_common::{
+    generate_webhook_secret, sign_payload, validate_url, webhook, JwtManager,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::time::Duration;
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+use crate::{
+    authenticated_paymail, create_error_response, forbidden_response, ErrorResponse,
+    PaymentChannel,
+};
+
+pub const EVENT_CHANNEL_OPENED: &str = "channel.opened";
+pub const EVENT_PAYMENT_RECEIVED: &str = "payment.received";
+pub const EVENT_DISPUTE_STARTED: &str = "dispute.started";
+pub const EVENT_DISPUTE_EVIDENCE: &str = "dispute.evidence";
+pub const EVENT_CHANNEL_CLOSED: &str = "channel.closed";
+
+const ALL_EVENTS: [&str; 5] = [
+    EVENT_CHANNEL_OPENED,
+    EVENT_PAYMENT_RECEIVED,
+    EVENT_DISPUTE_STARTED,
+    EVENT_DISPUTE_EVIDENCE,
+    EVENT_CHANNEL_CLOSED,
+];
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, sqlx::FromRow)]
+pub struct ChannelWebhook {
+    pub id: Uuid,
+    pub channel_id: String,
+    pub paymail: String,
+    pub url: String,
+    pub events: Vec<String>,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RegisterWebhookRequest {
+    pub url: String,
+    #[serde(default)]
+    pub events: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct WebhookEnvelope<'a> {
+    id: Uuid,
+    event: &'a str,
+    channel_id: &'a str,
+    timestamp: DateTime<Utc>,
+    data: &'a serde_json::Value,
+}
+
+// ============================================================================
+// DISPATCHER
+// ============================================================================
+
+/// Deliveries claimed per worker pass
+const DELIVERY_BATCH: i64 = 50;
+/// How long a claimed delivery is hidden from other workers
+const DELIVERY_LEASE_SECS: i64 = 60;
+const DELIVERY_POLL: Duration = Duration::from_secs(5);
+
+/// In-process notification that something happened on a channel; used by
+/// streaming watchers, which re-read the channel rather than trust payloads.
+#[derive(Debug, Clone)]
+pub struct ChannelEvent {
+    pub channel_id: String,
+    pub event: &'static str,
+}
+
+#[derive(sqlx::FromRow)]
+struct DueDelivery {
+    id: Uuid,
+    event_type: String,
+    payload: String,
+    attempts: i32,
+    url: String,
+    secret: String,
+    active: bool,
+}
+
+/// Queues channel events for every active subscription on the channel.
+/// The delivery worker sends them, so handlers never wait on callbacks.
+#[derive(Clone)]
+pub struct WebhookDispatcher {
+    pool: PgPool,
+    max_attempts: i32,
+    local: broadcast::Sender<ChannelEvent>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(pool: PgPool) -> Self {
+        Self {
+            pool,
+            max_attempts: std::env::var("WEBHOOK_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|s| s.parse().ok())
+                .unwrap_or(5),
+            local: broadcast::channel(1024).0,
+        }
+    }
+
+    /// Receive every event emitted from now on, across all channels
+    pub fn subscribe(&self) -> broadcast::Receiver<ChannelEvent> {
+        self.local.subscribe()
+    }
+
+    /// Queue `event` for all subscribers of `channel_id`
+    pub fn emit(&self, channel_id: &str, event: &'static str, data: serde_json::Value) {
+        // No in-process listeners is the common case; ignore the send error
+        let _ = self.local.send(ChannelEvent {
+            channel_id: channel_id.to_string(),
+            event,
+        });
+
+        let dispatcher = self.clone();
+        let channel_id = channel_id.to_string();
+
+        tokio::spawn(async move {
+            if let Err(e) = dispatcher.enqueue(&channel_id, event, data).await {
+                tracing::error!("Webhook dispatch failed for {} ({}): {}", channel_id, event, e);
+            }
+        });
+    }
+
+    async fn enqueue(
+        &self,
+        channel_id: &str,
+        event: &str,
+        data: serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        let hooks = sqlx::query_as::<_, ChannelWebhook>(
+            r#"
+            SELECT * FROM channel_webhooks
+            WHERE channel_id = $1
+              AND active
+              AND (cardinality(events) = 0 OR $2 = ANY(events))
+            "#
+        )
+        .bind(channel_id)
+        .bind(event)
+        .fetch_all(&self.pool)
+        .await?;
+
+        for hook in hooks {
+            let envelope = WebhookEnvelope {
+                id: Uuid::new_v4(),
+                event,
+                channel_id,
+                timestamp: Utc::now(),
+                data: &data,
+            };
+            let body = serde_json::to_string(&envelope).unwrap_or_default();
+
+            sqlx::query(
+                r#"
+                INSERT INTO channel_webhook_deliveries (id, webhook_id, event_type, payload)
+                VALUES ($1, $2, $3, $4::jsonb)
+                "#
+            )
+            .bind(envelope.id)
+            .bind(hook.id)
+            .bind(event)
+            .bind(&body)
+            .execute(&self.pool)
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends due deliveries until they succeed or run out of attempts. Deliveries are
+    /// leased from the database, so retries survive restarts and each delivery is sent
+    /// by one replica at a time.
+    pub async fn start_delivery_worker(self) {
+        loop {
+            match self.claim_due().await {
+                Ok(due) => {
+                    for delivery in due {
+                        self.deliver(delivery).await;
+                    }
+                }
+                Err(e) => tracing::error!("Failed to load webhook deliveries: {}", e),
+            }
+            tokio::time::sleep(DELIVERY_POLL).await;
+        }
+    }
+
+    async fn claim_due(&self) -> Result<Vec<DueDelivery>, sqlx::Error> {
+        sqlx::query_as::<_, DueDelivery>(
+            r#"
+            UPDATE channel_webhook_deliveries d
+            SET next_attempt_at = NOW() + make_interval(secs => $2)
+            FROM channel_webhooks w
+            WHERE d.webhook_id = w.id
+              AND d.id IN (
+                  SELECT id FROM channel_webhook_deliveries
+                  WHERE NOT delivered AND next_attempt_at <= NOW()
+                  ORDER BY next_attempt_at
+                  LIMIT $1
+                  FOR UPDATE SKIP LOCKED
+              )
+            RETURNING d.id, d.event_type, d.payload::text AS payload, d.attempts, w.url, w.secret, w.active
+            "#
+        )
+        .bind(DELIVERY_BATCH)
+        .bind(DELIVERY_LEASE_SECS as f64)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    async fn send(&self, delivery: &DueDelivery) -> Result<reqwest::StatusCode, String> {
+        let client = webhook::pinned_client(&delivery.url).await?;
+        let timestamp = Utc::now().timestamp();
+        let signature = sign_payload(&delivery.secret, timestamp, &delivery.payload);
+
+        client
+            .post(&delivery.url)
+            .header("Content-Type", "application/json")
+            .header(webhook::EVENT_HEADER, &delivery.event_type)
+            .header(webhook::TIMESTAMP_HEADER, timestamp.to_string())
+            .header(webhook::SIGNATURE_HEADER, signature)
+            .body(delivery.payload.clone())
+            .send()
+            .await
+            .map(|response| response.status())
+            .map_err(|e| e.to_string())
+    }
+
+    async fn deliver(&self, delivery: DueDelivery) {
+        let attempts = delivery.attempts + 1;
+        let result = if delivery.active {
+            self.send(&delivery).await
+        } else {
+            Err("Subscription was removed".to_string())
+        };
+
+        let (status, error) = match result {
+            Ok(status) if status.is_success() => {
+                let _ = sqlx::query(
+                    r#"
+                    UPDATE channel_webhook_deliveries
+                    SET delivered = true, attempts = $1, response_status = $2,
+                        delivered_at = NOW(), last_error = NULL, next_attempt_at = NULL
+                    WHERE id = $3
+                    "#
+                )
+                .bind(attempts)
+                .bind(status.as_u16() as i32)
+                .bind(delivery.id)
+                .execute(&self.pool)
+                .await;
+                return;
+            }
+            Ok(status) => (Some(status.as_u16() as i32), format!("Status: {}", status)),
+            Err(e) => (None, e),
+        };
+
+        let exhausted = !delivery.active || attempts >= self.max_attempts;
+        if exhausted {
+            tracing::warn!("Webhook {} to {} failed after {} attempts: {}",
+                delivery.id, delivery.url, attempts, error);
+        }
+
+        let _ = sqlx::query(
+            r#"
+            UPDATE channel_webhook_deliveries
+            SET attempts = $1, response_status = $2, last_error = $3,
+                next_attempt_at = CASE WHEN $4 THEN NULL ELSE NOW() + make_interval(secs => $5) END
+            WHERE id = $6
+            "#
+        )
+        .bind(attempts)
+        .bind(status)
+        .bind(error)
+        .bind(exhausted)
+        .bind(retry_delay(attempts).as_secs_f64())
+        .bind(delivery.id)
+        .execute(&self.pool)
+        .await;
+    }
+}
+
+/// Delay before retry number `attempts` + 1: 30s doubling up to an hour
+pub fn retry_delay(attempts: i32) -> Duration {
+    Duration::from_secs((30u64 << attempts.clamp(0, 7) as u32).min(3600))
+}
+
+// ============================================================================
+// SUBSCRIPTION MANAGEMENT
+// ============================================================================
+
+/// Store a new subscription and return it together with its signing secret
+pub async fn create_subscription(
+    pool: &PgPool,
+    channel_id: &str,
+    paymail: &str,
+    request: &RegisterWebhookRequest,
+) -> Result<(ChannelWebhook, String), String> {
+    validate_url(&request.url).map_err(|e| e.to_string())?;
+
+    if let Some(unknown) = request.events.iter().find(|e| !ALL_EVENTS.contains(&e.as_str())) {
+        return Err(format!("Unknown event type: {}", unknown));
+    }
+
+    let secret = generate_webhook_secret();
+
+    let webhook = sqlx::query_as::<_, ChannelWebhook>(
+        r#"
+        INSERT INTO channel_webhooks (channel_id, paymail, url, secret, events)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (channel_id, paymail, url)
+        DO UPDATE SET secret = EXCLUDED.secret, events = EXCLUDED.events, active = true
+        RETURNING *
+        "#
+    )
+    .bind(channel_id)
+    .bind(paymail)
+    .bind(&request.url)
+    .bind(&secret)
+    .bind(&request.events)
+    .fetch_one(pool)
+    .await
+    .map_err(|e| e.to_string())?;
+
+    Ok((webhook, secret))
+}
+
+async fn load_channel_for_party(
+    pool: &PgPool,
+    channel_id: &str,
+    paymail: &str,
+) -> Result<PaymentChannel, HttpResponse> {
+    let channel = sqlx::query_as::<_, PaymentChannel>(
+        "SELECT * FROM payment_channels WHERE channel_id = $1"
+    )
+    .bind(channel_id)
+    .fetch_optional(pool)
+    .await;
+
+    match channel {
+        Ok(Some(channel)) => {
+            if channel.party_a_paymail != paymail && channel.party_b_paymail != paymail {
+                return Err(forbidden_response("Only channel parties can manage webhooks"));
+            }
+            Ok(channel)
+        }
+        Ok(None) => Err(HttpResponse::NotFound().json(ErrorResponse {
+            error: "NotFound".to_string(),
+            message: "Channel not found".to_string(),
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Error fetching channel: {}", e);
+            Err(create_error_response("DatabaseError", "Failed to fetch channel"))
+        }
+    }
+}
+
+// ============================================================================
+// API ENDPOINTS
+// ============================================================================
+
+pub async fn register_webhook(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    channel_id: web::Path<String>,
+    request: web::Json<RegisterWebhookRequest>,
+) -> Result<HttpResponse> {
+    let caller = match authenticated_paymail(&http_req, &jwt) {
+        Ok(paymail) => paymail,
+        Err(response) => return Ok(response),
+    };
+    if let Err(response) = load_channel_for_party(&pool, &channel_id, &caller).await {
+        return Ok(response);
+    }
+
+    match create_subscription(&pool, &channel_id, &caller, &request).await {
+        Ok((webhook, secret)) => {
+            tracing::info!("Webhook registered on {} by {}: {}", channel_id, caller, webhook.url);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "webhook": webhook,
+                "secret": secret,
+                "message": "Store this secret; it is used to verify the X-BSVBank-Signature header and will not be shown again."
+            })))
+        }
+        Err(e) => Ok(create_error_response("WebhookError", &e)),
+    }
+}
+
+pub async fn list_webhooks(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    channel_id: web::Path<String>,
+) -> Result<HttpResponse> {
+    let caller = match authenticated_paymail(&http_req, &jwt) {
+        Ok(paymail) => paymail,
+        Err(response) => return Ok(response),
+    };
+    if let Err(response) = load_channel_for_party(&pool, &channel_id, &caller).await {
+        return Ok(response);
+    }
+
+    let webhooks = sqlx::query_as::<_, ChannelWebhook>(
+        r#"
+        SELECT * FROM channel_webhooks
+        WHERE channel_id = $1 AND paymail = $2 AND active
+        ORDER BY created_at DESC
+        "#
+    )
+    .bind(channel_id.as_str())
+    .bind(&caller)
+    .fetch_all(pool.get_ref())
+    .await;
+
+    match webhooks {
+        Ok(webhooks) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "channel_id": channel_id.as_str(),
+            "total_webhooks": webhooks.len(),
+            "webhooks": webhooks
+        }))),
+        Err(e) => {
+            tracing::error!("Error fetching webhooks: {}", e);
+            Ok(create_error_response("DatabaseError", "Failed to fetch webhooks"))
+        }
+    }
+}
+
+pub async fn delete_webhook(
+    pool: web::Data<PgPool>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    path: web::Path<(String, Uuid)>,
+) -> Result<HttpResponse> {
+    let (channel_id, webhook_id) = path.into_inner();
+
+    let caller = match authenticated_paymail(&http_req, &jwt) {
+        Ok(paymail) => paymail,
+        Err(response) => return Ok(response),
+    };
+
+    let result = sqlx::query(
+        r#"
+        UPDATE channel_webhooks
+        SET active = false
+        WHERE id = $1 AND channel_id = $2 AND paymail = $3
+        "#
+    )
+    .bind(webhook_id)
+    .bind(&channel_id)
+    .bind(&caller)
+    .execute(pool.get_ref())
+    .await;
+
+    match result {
+        Ok(r) if r.rows_affected() > 0 => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "webhook_id": webhook_id,
+            "active": false
+        }))),
+        Ok(_) => Ok(HttpResponse::NotFound().json(ErrorResponse {
+            error: "NotFound".to_string(),
+            message: "Webhook not found".to_string(),
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Error deleting webhook: {}", e);
+            Ok(create_error_response("DatabaseError", "Failed to delete webhook"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay_backs_off_to_a_cap() {
+        assert_eq!(retry_delay(1), Duration::from_secs(60));
+        assert_eq!(retry_delay(3), Duration::from_secs(240));
+        assert_eq!(retry_delay(40), Duration::from_secs(3600));
+    }
+}
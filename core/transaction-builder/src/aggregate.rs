@@ -0,0 +1,1313 @@
+// core/transaction-builder/src/aggregate.rs
+// Aggregated-key 2-of-2 channels: one P2PKH key held jointly with a client and signed by two-party ECDSA
+//
+// The service is the second party of a Lindell-style protocol. The client keeps its share x1 and a
+// Paillier key pair and sends us Enc(x1); our share x2 is derived from a stored key, so only the
+// client's public material is persisted. The joint key is Q = x1·x2·G, which spends like any P2PKH
+// output, so a settlement input is ~148 bytes instead of the ~300 of a 2-of-2 CHECKMULTISIG.
+//
+// A malicious client could use our partial signatures to learn x2, so it proves that N is a valid
+// Paillier modulus and that Enc(x1) holds the discrete log of x1·G within a bounded range. Each key
+// is bound to one channel and its two parties: a session only signs a transaction paying out a state
+// both parties signed, never older than a state we already signed for. Only the sibling services
+// allowed to use the stored key may drive any of it.
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, Duration, Utc};
+use num_bigint::{BigUint, RandBigInt};
+use secp256k1::ecdsa::Signature;
+use secp256k1::{Message, PublicKey, Scalar, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::fees::{FeeKind, FeeOracle};
+use crate::signing::{self, KeyStore};
+use crate::timelock::Timelock;
+use crate::{size, AddressKind, AddressUtils, AppState, ChannelParty, Network, ScriptBuilder, ServiceError, SettlementFeePolicy, Transaction};
+
+/// Paillier moduli below this leave the encrypted share open to factoring
+pub const MIN_PAILLIER_BITS: u64 = 2048;
+/// Odd divisors of N below this are ruled out before the correct-key proof is checked
+const PAILLIER_SMALL_FACTOR_BOUND: u32 = 6370;
+/// N-th roots in the correct-key proof; with no factor below the bound, soundness is 2^-128
+pub const KEY_PROOF_ROUNDS: usize = 11;
+/// Binary challenges in the share proof, each halving a cheating client's chance
+pub const SHARE_PROOF_ROUNDS: usize = 80;
+/// Size of the share proof's masks: a 256-bit share plus statistical hiding
+const SHARE_PROOF_MASK_BITS: u64 = 256 + SHARE_PROOF_ROUNDS as u64;
+/// A payout's fee may run to this multiple of the current rate, so a rate refresh between
+/// building a settlement and signing it does not strand the channel
+const FEE_RATE_HEADROOM: u64 = 2;
+/// How long a signing session may wait for the client's nonce
+const SESSION_TTL_SECS: i64 = 600;
+/// secp256k1 group order
+const CURVE_ORDER_HEX: &str = "fffffffffffffffffffffffffffffffebaaedce6af48a03bbfd25e8cd0364141";
+
+// ============================================================================
+// SCALARS
+// ============================================================================
+
+fn curve_order() -> BigUint {
+    BigUint::parse_bytes(CURVE_ORDER_HEX.as_bytes(), 16).expect("curve order is valid hex")
+}
+
+fn to_biguint(key: &SecretKey) -> BigUint {
+    BigUint::from_bytes_be(&key.secret_bytes())
+}
+
+fn to_secret_key(value: &BigUint) -> Option<SecretKey> {
+    let bytes = value.to_bytes_be();
+    if bytes.len() > 32 {
+        return None;
+    }
+    let mut padded = [0u8; 32];
+    padded[32 - bytes.len()..].copy_from_slice(&bytes);
+    SecretKey::from_slice(&padded).ok()
+}
+
+/// Inverse modulo the (prime) curve order
+fn inverse_mod_order(value: &BigUint, order: &BigUint) -> BigUint {
+    value.modpow(&(order - 2u32), order)
+}
+
+/// Fresh single-use nonce
+pub fn random_scalar() -> SecretKey {
+    let order = curve_order();
+    let mut rng = rand::thread_rng();
+    loop {
+        // Zero is the only value below the order that is not a valid key
+        if let Some(key) = to_secret_key(&rng.gen_biguint_below(&order)) {
+            return key;
+        }
+    }
+}
+
+fn parse_hex_uint(value: &str, field: &str) -> Result<BigUint, String> {
+    BigUint::parse_bytes(value.trim().as_bytes(), 16).ok_or_else(|| format!("{} must be hex", field))
+}
+
+fn parse_point(value: &str, field: &str) -> Result<PublicKey, String> {
+    let bytes = hex::decode(value).map_err(|_| format!("{} must be hex", field))?;
+    PublicKey::from_slice(&bytes).map_err(|_| format!("{} is not a valid secp256k1 point", field))
+}
+
+// ============================================================================
+// PAILLIER
+// ============================================================================
+
+/// The client's Paillier public key, with generator N + 1
+pub struct PaillierPublicKey {
+    n: BigUint,
+    n_squared: BigUint,
+}
+
+impl PaillierPublicKey {
+    pub fn from_hex(n_hex: &str) -> Result<Self, String> {
+        let n = parse_hex_uint(n_hex, "paillier_n")?;
+        if n.bits() < MIN_PAILLIER_BITS {
+            return Err(format!("paillier_n must be at least {} bits", MIN_PAILLIER_BITS));
+        }
+        if &n % 2u32 == BigUint::from(0u32) {
+            return Err("paillier_n must be odd".to_string());
+        }
+        Ok(Self { n_squared: &n * &n, n })
+    }
+
+    pub fn check_ciphertext(&self, ciphertext: &BigUint) -> Result<(), String> {
+        if *ciphertext == BigUint::from(0u32) || *ciphertext >= self.n_squared {
+            return Err("Ciphertext is outside the Paillier group".to_string());
+        }
+        Ok(())
+    }
+
+    pub fn encrypt(&self, message: &BigUint) -> BigUint {
+        let r = rand::thread_rng().gen_biguint_range(&BigUint::from(1u32), &self.n);
+        self.encrypt_with_nonce(message, &r)
+    }
+
+    /// (N + 1)^m · r^N mod N²
+    pub fn encrypt_with_nonce(&self, message: &BigUint, nonce: &BigUint) -> BigUint {
+        let g_m = (BigUint::from(1u32) + message * &self.n) % &self.n_squared;
+        g_m * nonce.modpow(&self.n, &self.n_squared) % &self.n_squared
+    }
+
+    /// Ciphertext of the sum of the plaintexts
+    pub fn add(&self, a: &BigUint, b: &BigUint) -> BigUint {
+        a * b % &self.n_squared
+    }
+
+    /// Ciphertext of the plaintext times `factor`
+    pub fn mul(&self, ciphertext: &BigUint, factor: &BigUint) -> BigUint {
+        ciphertext.modpow(factor, &self.n_squared)
+    }
+
+    /// Check the client's N-th roots σᵢ of hashed ρᵢ. Every ρᵢ has one only when gcd(N, φ(N)) = 1,
+    /// which with small factors ruled out makes N a valid Paillier modulus.
+    pub fn verify_key_proof(&self, sigmas: &[BigUint]) -> Result<(), String> {
+        let zero = BigUint::from(0u32);
+        if let Some(divisor) = (3..PAILLIER_SMALL_FACTOR_BOUND).step_by(2).find(|d| &self.n % *d == zero) {
+            return Err(format!("paillier_n is divisible by {}", divisor));
+        }
+        if sigmas.len() != KEY_PROOF_ROUNDS {
+            return Err(format!("key_proof must have {} roots, got {}", KEY_PROOF_ROUNDS, sigmas.len()));
+        }
+        for (round, sigma) in sigmas.iter().enumerate() {
+            if *sigma == zero || *sigma >= self.n || sigma.modpow(&self.n, &self.n) != key_proof_challenge(&self.n, round) {
+                return Err(format!("key_proof root {} does not verify", round));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check that `ciphertext` encrypts the discrete log of `client_pubkey`. From two answers to one
+    /// commitment the plaintext is z₁ − z₀, so it is x1 mod n and below 2^(mask + 1) in magnitude.
+    pub fn verify_share_proof(
+        &self,
+        ciphertext: &BigUint,
+        client_pubkey: &PublicKey,
+        rounds: &[ShareProofStep],
+    ) -> Result<(), String> {
+        if rounds.len() != SHARE_PROOF_ROUNDS {
+            return Err(format!("share_proof must have {} rounds, got {}", SHARE_PROOF_ROUNDS, rounds.len()));
+        }
+        let order = curve_order();
+        let challenges = share_proof_challenges(self, ciphertext, client_pubkey, rounds);
+        for (index, (round, challenge)) in rounds.iter().zip(challenges).enumerate() {
+            let invalid = || format!("share_proof round {} does not verify", index);
+            self.check_ciphertext(&round.a).map_err(|_| invalid())?;
+            if round.z.bits() > SHARE_PROOF_MASK_BITS + 1 || round.w == BigUint::from(0u32) || round.w >= self.n {
+                return Err(invalid());
+            }
+
+            let expected = if challenge { self.add(&round.a, ciphertext) } else { round.a.clone() };
+            if self.encrypt_with_nonce(&round.z, &round.w) != expected {
+                return Err(invalid());
+            }
+
+            let z_point = to_secret_key(&(&round.z % &order))
+                .map(|z| PublicKey::from_secret_key(&Secp256k1::signing_only(), &z))
+                .ok_or_else(invalid)?;
+            let expected = if challenge { round.y.combine(client_pubkey).map_err(|_| invalid())? } else { round.y };
+            if z_point != expected {
+                return Err(invalid());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Hash `value` with its length so adjacent values cannot run into each other
+fn absorb(hasher: &mut Sha256, value: &[u8]) {
+    hasher.update((value.len() as u32).to_be_bytes());
+    hasher.update(value);
+}
+
+/// ρᵢ of the correct-key proof: a hash of N and i stretched past N's length, reduced mod N
+fn key_proof_challenge(n: &BigUint, round: usize) -> BigUint {
+    // The extra bytes keep the reduction close to uniform
+    let len = (n.bits() as usize).div_ceil(8) + 16;
+    let mut bytes = Vec::with_capacity(len + 32);
+    let mut block = 0u32;
+    while bytes.len() < len {
+        let mut hasher = Sha256::new();
+        absorb(&mut hasher, b"bsv-bank/paillier-key");
+        absorb(&mut hasher, &n.to_bytes_be());
+        hasher.update((round as u32).to_be_bytes());
+        hasher.update(block.to_be_bytes());
+        bytes.extend_from_slice(&hasher.finalize());
+        block += 1;
+    }
+    BigUint::from_bytes_be(&bytes[..len]) % n
+}
+
+/// One round of the share proof: commitments A = Enc(α; β) and Y = α·G, and responses
+/// z = α + e·x1 and w = β·rᵉ mod N to challenge bit e, where r is Enc(x1)'s nonce
+pub struct ShareProofStep {
+    pub a: BigUint,
+    pub y: PublicKey,
+    pub z: BigUint,
+    pub w: BigUint,
+}
+
+/// Fiat-Shamir challenge bits over the statement and every commitment
+fn share_proof_challenges(
+    paillier: &PaillierPublicKey,
+    ciphertext: &BigUint,
+    client_pubkey: &PublicKey,
+    rounds: &[ShareProofStep],
+) -> Vec<bool> {
+    let mut hasher = Sha256::new();
+    absorb(&mut hasher, b"bsv-bank/paillier-share");
+    absorb(&mut hasher, &paillier.n.to_bytes_be());
+    absorb(&mut hasher, &ciphertext.to_bytes_be());
+    absorb(&mut hasher, &client_pubkey.serialize());
+    for round in rounds {
+        absorb(&mut hasher, &round.a.to_bytes_be());
+        absorb(&mut hasher, &round.y.serialize());
+    }
+    let digest = hasher.finalize();
+    (0..rounds.len()).map(|i| digest[i / 8] >> (i % 8) & 1 == 1).collect()
+}
+
+// ============================================================================
+// PROTOCOL
+// ============================================================================
+
+/// Our share of aggregated key `key_id`, derived from a stored key so it never needs storing
+pub fn service_share(master: &SecretKey, key_id: Uuid) -> SecretKey {
+    let mut counter = 0u32;
+    loop {
+        let digest = Sha256::new()
+            .chain_update(b"bsv-bank/aggregate-key")
+            .chain_update(master.secret_bytes())
+            .chain_update(key_id.as_bytes())
+            .chain_update(counter.to_be_bytes())
+            .finalize();
+        if let Ok(share) = SecretKey::from_slice(&digest) {
+            return share;
+        }
+        counter += 1;
+    }
+}
+
+/// Q = x2·(x1·G); the client computes the same point as x1·(x2·G)
+pub fn aggregate_pubkey(share: &SecretKey, client_pubkey: &PublicKey) -> Result<PublicKey, String> {
+    client_pubkey
+        .mul_tweak(&Secp256k1::verification_only(), &Scalar::from(*share))
+        .map_err(|e| format!("Key aggregation failed: {}", e))
+}
+
+/// P2PKH locking script of an aggregated key
+pub fn aggregate_script(aggregated: &PublicKey) -> Vec<u8> {
+    ScriptBuilder::p2pkh(&AddressUtils::hash160(&aggregated.serialize()))
+}
+
+/// The joint nonce's r and our encrypted contribution ρ·n + k2⁻¹·z + k2⁻¹·r·x2·x1.
+/// The client decrypts it, reduces mod n and multiplies by k1⁻¹ to get s.
+pub fn partial_signature(
+    paillier: &PaillierPublicKey,
+    encrypted_client_share: &BigUint,
+    share: &SecretKey,
+    nonce: &SecretKey,
+    client_nonce: &PublicKey,
+    sighash: &[u8; 32],
+) -> Result<(BigUint, BigUint), String> {
+    let order = curve_order();
+    let joint_nonce = client_nonce
+        .mul_tweak(&Secp256k1::verification_only(), &Scalar::from(*nonce))
+        .map_err(|e| format!("Invalid client nonce: {}", e))?;
+    let r = BigUint::from_bytes_be(&joint_nonce.serialize()[1..]) % &order;
+    if r == BigUint::from(0u32) {
+        return Err("Joint nonce has r = 0; open a new session".to_string());
+    }
+
+    let z = BigUint::from_bytes_be(sighash) % &order;
+    let nonce_inv = inverse_mod_order(&to_biguint(nonce), &order);
+    // ρ masks everything but the value mod n from the client. Its floor keeps the plaintext
+    // positive for any share the share proof admits, so it never wraps mod N.
+    let rho_floor = BigUint::from(1u32) << (SHARE_PROOF_MASK_BITS + 2);
+    let rho = rand::thread_rng().gen_biguint_range(&rho_floor, &(&order * &order));
+
+    let c1 = paillier.encrypt(&(rho * &order + &nonce_inv * z % &order));
+    let v = &nonce_inv * &r % &order * to_biguint(share) % &order;
+    let c2 = paillier.mul(encrypted_client_share, &v);
+    Ok((r, paillier.add(&c1, &c2)))
+}
+
+/// Check the client's final signature under the aggregated key, normalised to low-S
+pub fn verify_joint_signature(aggregated: &PublicKey, sighash: &[u8; 32], der: &[u8]) -> Result<Signature, String> {
+    let mut signature = Signature::from_der(der).map_err(|_| "signature must be DER encoded".to_string())?;
+    signature.normalize_s();
+    let message = Message::from_digest_slice(sighash).expect("sighash is 32 bytes");
+    Secp256k1::verification_only()
+        .verify_ecdsa(&message, &signature, aggregated)
+        .map_err(|_| "Signature does not verify against the aggregated key".to_string())?;
+    Ok(signature)
+}
+
+fn hex32(value: &BigUint) -> String {
+    format!("{:0>64}", value.to_str_radix(16))
+}
+
+// ============================================================================
+// CHANNEL BINDING
+// ============================================================================
+
+/// A channel state both parties signed, as payment-channel-service stores it
+#[derive(Deserialize)]
+pub struct ChannelState {
+    pub sequence_number: i64,
+    pub balance_a: i64,
+    pub balance_b: i64,
+    /// Each party's DER signature over the state hash, hex
+    pub signature_a: String,
+    pub signature_b: String,
+}
+
+/// sha256("{channel_id}:{sequence}:{balance_a}:{balance_b}"), the digest channel parties sign
+fn state_hash(channel_id: &str, state: &ChannelState) -> [u8; 32] {
+    Sha256::digest(format!("{}:{}:{}:{}", channel_id, state.sequence_number, state.balance_a, state.balance_b)).into()
+}
+
+fn verify_state_signature(digest: &[u8; 32], signature_hex: &str, pubkey: &PublicKey) -> bool {
+    let Some(mut signature) = hex::decode(signature_hex).ok().and_then(|b| Signature::from_der(&b).ok()) else {
+        return false;
+    };
+    signature.normalize_s();
+    let message = Message::from_digest_slice(digest).expect("state hash is 32 bytes");
+    Secp256k1::verification_only().verify_ecdsa(&message, &signature, pubkey).is_ok()
+}
+
+/// The fee divisions a payout may use under the channel's stored fee split. Under "payer"
+/// whoever closes pays, and the service cannot tell which party that was.
+fn fee_divisions(fee_split: &str) -> Result<Vec<SettlementFeePolicy>, String> {
+    match fee_split {
+        "payer" => Ok(vec![SettlementFeePolicy::Payer(ChannelParty::A), SettlementFeePolicy::Payer(ChannelParty::B)]),
+        other => SettlementFeePolicy::parse(Some(other), None).map(|policy| vec![policy]),
+    }
+}
+
+/// The channel an aggregated key locks and the parties its states are signed by
+pub struct ChannelBinding {
+    pub channel_id: String,
+    pub party_a_pubkey: PublicKey,
+    pub party_b_pubkey: PublicKey,
+    /// Hash160 of each party's P2PKH payout address
+    pub party_a_hash: Vec<u8>,
+    pub party_b_hash: Vec<u8>,
+    /// Divisions of the fee the channel's stored fee split allows
+    pub fee_divisions: Vec<SettlementFeePolicy>,
+}
+
+impl ChannelBinding {
+    fn from_row(row: &AggregateKeyRow) -> Result<Self, String> {
+        let (Some(channel_id), Some(pubkey_a), Some(pubkey_b), Some(address_a), Some(address_b)) = (
+            &row.channel_id,
+            &row.party_a_pubkey,
+            &row.party_b_pubkey,
+            &row.party_a_address,
+            &row.party_b_address,
+        ) else {
+            return Err(format!("Aggregate key {} is not bound to a channel", row.id));
+        };
+        Ok(Self {
+            channel_id: channel_id.clone(),
+            party_a_pubkey: parse_point(pubkey_a, "party_a_pubkey")?,
+            party_b_pubkey: parse_point(pubkey_b, "party_b_pubkey")?,
+            party_a_hash: AddressUtils::decode_address(address_a)?,
+            party_b_hash: AddressUtils::decode_address(address_b)?,
+            fee_divisions: fee_divisions(&row.fee_split)?,
+        })
+    }
+
+    /// Both parties signed `state` for this channel
+    pub fn verify_state(&self, state: &ChannelState) -> Result<(), String> {
+        if state.balance_a < 0 || state.balance_b < 0 {
+            return Err("State balances cannot be negative".to_string());
+        }
+        let digest = state_hash(&self.channel_id, state);
+        if !verify_state_signature(&digest, &state.signature_a, &self.party_a_pubkey) {
+            return Err(format!("Party A's signature on state {} does not verify", state.sequence_number));
+        }
+        if !verify_state_signature(&digest, &state.signature_b, &self.party_b_pubkey) {
+            return Err(format!("Party B's signature on state {} does not verify", state.sequence_number));
+        }
+        Ok(())
+    }
+
+    /// Whether `script` pays `hash`, directly or behind a timelock as commitment outputs do
+    fn pays(script: &[u8], hash: &[u8]) -> bool {
+        script == ScriptBuilder::p2pkh(hash).as_slice() || Timelock::parse(script).is_some_and(|(_, locked)| locked == hash)
+    }
+
+    /// Most `tx` may pay in fees at `fee_per_byte`, sized as the settlement builder estimates it:
+    /// P2PKH inputs and at least two outputs
+    fn max_fee(tx: &Transaction, fee_per_byte: u64) -> u64 {
+        let outputs = tx.outputs.len().max(2);
+        let output_bytes = tx.outputs.iter().map(|o| size::output_size(o.script_pubkey.len())).sum::<usize>()
+            + (outputs - tx.outputs.len()) * size::P2PKH_OUTPUT_SIZE;
+        let estimated_size = size::overhead_size(tx.inputs.len(), outputs)
+            + tx.inputs.len() * size::P2PKH_INPUT_SIZE
+            + output_bytes;
+        estimated_size as u64 * fee_per_byte * FEE_RATE_HEADROOM
+    }
+
+    /// `tx` pays out `state` from a channel output of `value`: every output goes to a party, neither
+    /// party gets more than its balance, the fee withheld between them is within the current rate
+    /// and each party's share of it follows the channel's fee split
+    pub fn check_payout(&self, tx: &Transaction, state: &ChannelState, value: u64, fee_per_byte: u64) -> Result<(), String> {
+        let (balance_a, balance_b) = (state.balance_a as u64, state.balance_b as u64);
+        if balance_a + balance_b > value {
+            return Err(format!("State balances exceed the {} sat channel output", value));
+        }
+
+        let (mut paid_a, mut paid_b) = (0u64, 0u64);
+        for (index, output) in tx.outputs.iter().enumerate() {
+            if Self::pays(&output.script_pubkey, &self.party_a_hash) {
+                paid_a = paid_a.saturating_add(output.value);
+            } else if Self::pays(&output.script_pubkey, &self.party_b_hash) {
+                paid_b = paid_b.saturating_add(output.value);
+            } else {
+                return Err(format!("Output {} pays neither channel party", index));
+            }
+        }
+        if paid_a > balance_a {
+            return Err(format!("Transaction pays party A {} sat, above its {} sat balance", paid_a, balance_a));
+        }
+        if paid_b > balance_b {
+            return Err(format!("Transaction pays party B {} sat, above its {} sat balance", paid_b, balance_b));
+        }
+        let fee = balance_a + balance_b - paid_a - paid_b;
+        let max_fee = Self::max_fee(tx, fee_per_byte);
+        if fee > max_fee {
+            return Err(format!("Transaction withholds {} sat from the parties, above the {} sat the fee rate allows", fee, max_fee));
+        }
+        let withheld = (balance_a - paid_a, balance_b - paid_b);
+        for division in &self.fee_divisions {
+            if division.split_fee(fee, balance_a, balance_b)? == withheld {
+                return Ok(());
+            }
+        }
+        Err(format!(
+            "Transaction withholds {} sat from party A and {} sat from party B, not as the channel's fee split divides {} sat",
+            withheld.0, withheld.1, fee
+        ))
+    }
+}
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct CreateAggregateKeyRequest {
+    /// Stored key the service's share is derived from
+    pub key_ref: String,
+    /// x1·G, compressed SEC hex
+    pub client_pubkey: String,
+    /// Client's Paillier modulus N, hex
+    pub paillier_n: String,
+    /// Enc(x1) under N, hex
+    pub encrypted_share: String,
+    /// σᵢ for the `KEY_PROOF_ROUNDS` hashed challenges, hex
+    pub key_proof: Vec<String>,
+    /// `SHARE_PROOF_ROUNDS` rounds proving `encrypted_share` holds the discrete log of `client_pubkey`
+    pub share_proof: Vec<ShareProofRound>,
+    /// Channel the key locks; sessions only sign states of this channel
+    pub channel_id: String,
+    /// Keys each party signs channel states with, compressed SEC hex
+    pub party_a_pubkey: String,
+    pub party_b_pubkey: String,
+    /// P2PKH addresses the parties are paid out to
+    pub party_a_address: String,
+    pub party_b_address: String,
+    /// How payouts divide the fee between the parties: "payer", "split" (default) or "proportional",
+    /// as stored for the channel
+    pub fee_split: Option<String>,
+}
+
+/// One round of the share proof, hex
+#[derive(Deserialize)]
+pub struct ShareProofRound {
+    pub a: String,
+    pub y: String,
+    pub z: String,
+    pub w: String,
+}
+
+impl ShareProofRound {
+    fn parse(&self) -> Result<ShareProofStep, String> {
+        Ok(ShareProofStep {
+            a: parse_hex_uint(&self.a, "share_proof a")?,
+            y: parse_point(&self.y, "share_proof y")?,
+            z: parse_hex_uint(&self.z, "share_proof z")?,
+            w: parse_hex_uint(&self.w, "share_proof w")?,
+        })
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct AggregateKeyRow {
+    id: Uuid,
+    service_pubkey: String,
+    aggregated_pubkey: String,
+    address: String,
+    paillier_n: String,
+    encrypted_client_share: String,
+    key_ref: String,
+    channel_id: Option<String>,
+    party_a_pubkey: Option<String>,
+    party_b_pubkey: Option<String>,
+    party_a_address: Option<String>,
+    party_b_address: Option<String>,
+    last_signed_sequence: Option<i64>,
+    fee_split: String,
+    created_at: DateTime<Utc>,
+}
+
+#[derive(Serialize)]
+pub struct AggregateKeyResponse {
+    pub key_id: Uuid,
+    pub channel_id: Option<String>,
+    pub service_pubkey: String,
+    pub aggregated_pubkey: String,
+    /// Fund the channel to this P2PKH address (funding with `aggregated_key: true`)
+    pub address: String,
+    pub script_pubkey: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<AggregateKeyRow> for AggregateKeyResponse {
+    fn from(row: AggregateKeyRow) -> Self {
+        let script_pubkey = parse_point(&row.aggregated_pubkey, "aggregated_pubkey")
+            .map(|q| hex::encode(aggregate_script(&q)))
+            .unwrap_or_default();
+        Self {
+            key_id: row.id,
+            channel_id: row.channel_id,
+            service_pubkey: row.service_pubkey,
+            aggregated_pubkey: row.aggregated_pubkey,
+            address: row.address,
+            script_pubkey,
+            created_at: row.created_at,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct OpenSessionRequest {
+    /// Unsigned transaction spending the aggregated key's output
+    pub tx_hex: String,
+    pub input_index: usize,
+    /// Value of the output being spent
+    pub value: u64,
+    /// Must be ALL|FORKID so the outputs checked against `state` are the ones signed
+    pub sighash: Option<String>,
+    /// Channel state the transaction pays out
+    pub state: ChannelState,
+}
+
+#[derive(Serialize)]
+pub struct OpenSessionResponse {
+    pub session_id: Uuid,
+    /// Digest being signed, hex
+    pub sighash: String,
+    /// k2·G, compressed SEC hex
+    pub service_nonce: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct PartialRequest {
+    /// k1·G, compressed SEC hex
+    pub client_nonce: String,
+}
+
+#[derive(Serialize)]
+pub struct PartialResponse {
+    pub session_id: Uuid,
+    /// x-coordinate of k1·k2·G mod n, hex
+    pub r: String,
+    /// Paillier ciphertext of our half of s, hex
+    pub encrypted_partial: String,
+}
+
+#[derive(Deserialize)]
+pub struct CompleteRequest {
+    /// Final DER signature (without the sighash byte)
+    pub signature: String,
+}
+
+#[derive(Serialize)]
+pub struct CompleteResponse {
+    pub session_id: Uuid,
+    /// DER signature with the sighash byte, as it appears in the scriptSig
+    pub signature: String,
+    pub script_sig: String,
+    pub tx_hex: String,
+    pub txid: String,
+}
+
+#[derive(sqlx::FromRow)]
+struct SessionRow {
+    key_id: Uuid,
+    tx_hex: String,
+    input_index: i32,
+    sighash_type: i32,
+    sighash: String,
+    status: String,
+    sequence_number: Option<i64>,
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    tracing::error!("Aggregate key database error: {}", e);
+    ServiceError::DatabaseError("Failed to access aggregate keys".to_string())
+}
+
+fn not_found(what: &str, id: Uuid) -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "not_found",
+        "message": format!("{} {} not found", what, id)
+    }))
+}
+
+async fn fetch_key(db: &sqlx::PgPool, id: Uuid) -> Result<Option<AggregateKeyRow>, ServiceError> {
+    sqlx::query_as::<_, AggregateKeyRow>("SELECT * FROM aggregate_keys WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .map_err(db_error)
+}
+
+async fn fetch_session(db: &sqlx::PgPool, id: Uuid) -> Result<Option<SessionRow>, ServiceError> {
+    sqlx::query_as::<_, SessionRow>("SELECT * FROM aggregate_sign_sessions WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .map_err(db_error)
+}
+
+fn sighash_bytes(hex_digest: &str) -> Result<[u8; 32], ServiceError> {
+    hex::decode(hex_digest)
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| ServiceError::BuildError("Stored sighash is corrupt".to_string()))
+}
+
+/// A party's payout address, which must be P2PKH so timelocked commitment outputs can match it
+fn party_address(address: &str, field: &str, network: Network) -> Result<String, ServiceError> {
+    let script = AddressUtils::script_for_address(address, network)
+        .map_err(|e| ServiceError::ValidationError(format!("{}: {}", field, e)))?;
+    if script.len() != 25 {
+        return Err(ServiceError::ValidationError(format!("{} must be a P2PKH address", field)));
+    }
+    Ok(address.to_string())
+}
+
+pub async fn create_aggregate_key(
+    http_req: HttpRequest,
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    jwt: web::Data<JwtManager>,
+    req: web::Json<CreateAggregateKeyRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    signing::require_key_access(&http_req, &jwt, &keys, [req.key_ref.as_str()])?;
+    let master = keys
+        .get(&req.key_ref)
+        .ok_or_else(|| ServiceError::ValidationError(format!("Unknown key reference: {}", req.key_ref)))?;
+    let client_pubkey = parse_point(&req.client_pubkey, "client_pubkey").map_err(ServiceError::ValidationError)?;
+    let paillier = PaillierPublicKey::from_hex(&req.paillier_n).map_err(ServiceError::ValidationError)?;
+    let encrypted_share = parse_hex_uint(&req.encrypted_share, "encrypted_share").map_err(ServiceError::ValidationError)?;
+    paillier.check_ciphertext(&encrypted_share).map_err(ServiceError::ValidationError)?;
+
+    if req.channel_id.trim().is_empty() || req.channel_id.len() > 66 {
+        return Err(ServiceError::ValidationError("channel_id must be 1 to 66 characters".to_string()));
+    }
+    let party_a_pubkey = parse_point(&req.party_a_pubkey, "party_a_pubkey").map_err(ServiceError::ValidationError)?;
+    let party_b_pubkey = parse_point(&req.party_b_pubkey, "party_b_pubkey").map_err(ServiceError::ValidationError)?;
+    let party_a_address = party_address(&req.party_a_address, "party_a_address", data.config.network)?;
+    let party_b_address = party_address(&req.party_b_address, "party_b_address", data.config.network)?;
+    let fee_split = req.fee_split.as_deref().unwrap_or("split");
+    fee_divisions(fee_split).map_err(ServiceError::ValidationError)?;
+
+    let sigmas = req.key_proof
+        .iter()
+        .map(|sigma| parse_hex_uint(sigma, "key_proof"))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ServiceError::ValidationError)?;
+    let rounds = req.share_proof
+        .iter()
+        .map(ShareProofRound::parse)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(ServiceError::ValidationError)?;
+    // Thousands of modular exponentiations under N²; keep them off the async workers
+    let (paillier, encrypted_share) = web::block(move || {
+        paillier.verify_key_proof(&sigmas)?;
+        paillier.verify_share_proof(&encrypted_share, &client_pubkey, &rounds)?;
+        Ok::<_, String>((paillier, encrypted_share))
+    })
+    .await
+    .map_err(|e| ServiceError::BuildError(format!("Proof verification failed to run: {}", e)))?
+    .map_err(ServiceError::ValidationError)?;
+
+    let key_id = Uuid::new_v4();
+    let share = service_share(&master.secret, key_id);
+    let service_pubkey = PublicKey::from_secret_key(&Secp256k1::signing_only(), &share);
+    let aggregated = aggregate_pubkey(&share, &client_pubkey).map_err(ServiceError::BuildError)?;
+    let address = AddressUtils::encode_address(
+        &AddressUtils::hash160(&aggregated.serialize()),
+        data.config.network,
+        AddressKind::P2pkh,
+    );
+
+    let row = sqlx::query_as::<_, AggregateKeyRow>(
+        "INSERT INTO aggregate_keys \
+         (id, key_ref, client_pubkey, service_pubkey, aggregated_pubkey, address, paillier_n, encrypted_client_share, \
+          channel_id, party_a_pubkey, party_b_pubkey, party_a_address, party_b_address, fee_split) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) RETURNING *"
+    )
+    .bind(key_id)
+    .bind(&req.key_ref)
+    .bind(hex::encode(client_pubkey.serialize()))
+    .bind(hex::encode(service_pubkey.serialize()))
+    .bind(hex::encode(aggregated.serialize()))
+    .bind(&address)
+    .bind(paillier.n.to_str_radix(16))
+    .bind(encrypted_share.to_str_radix(16))
+    .bind(req.channel_id.trim())
+    .bind(hex::encode(party_a_pubkey.serialize()))
+    .bind(hex::encode(party_b_pubkey.serialize()))
+    .bind(&party_a_address)
+    .bind(&party_b_address)
+    .bind(fee_split)
+    .fetch_one(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!("Created aggregate key {} at {} for channel {}", key_id, address, req.channel_id.trim());
+    Ok(HttpResponse::Created().json(AggregateKeyResponse::from(row)))
+}
+
+pub async fn get_aggregate_key(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    match fetch_key(&data.db, id).await? {
+        Some(row) => Ok(HttpResponse::Ok().json(AggregateKeyResponse::from(row))),
+        None => Ok(not_found("Aggregate key", id)),
+    }
+}
+
+/// Round 1: check the transaction pays out a co-signed channel state, fix the digest and
+/// publish our nonce point
+pub async fn open_session(
+    http_req: HttpRequest,
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    jwt: web::Data<JwtManager>,
+    fees: web::Data<FeeOracle>,
+    path: web::Path<Uuid>,
+    req: web::Json<OpenSessionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let key_id = path.into_inner();
+    let Some(key) = fetch_key(&data.db, key_id).await? else {
+        return Ok(not_found("Aggregate key", key_id));
+    };
+    signing::require_key_access(&http_req, &jwt, &keys, [key.key_ref.as_str()])?;
+    let aggregated = parse_point(&key.aggregated_pubkey, "aggregated_pubkey").map_err(ServiceError::BuildError)?;
+    let channel = ChannelBinding::from_row(&key).map_err(ServiceError::Conflict)?;
+
+    let tx = Transaction::from_hex(&req.tx_hex)
+        .map_err(|e| ServiceError::ValidationError(format!("Invalid tx_hex: {}", e)))?;
+    if req.input_index >= tx.inputs.len() {
+        return Err(ServiceError::ValidationError(format!("Input index {} out of range", req.input_index)));
+    }
+    let sighash_type = signing::parse_sighash(req.sighash.as_deref()).map_err(ServiceError::ValidationError)?;
+    if sighash_type != signing::SIGHASH_ALL | signing::SIGHASH_FORKID {
+        return Err(ServiceError::ValidationError("Aggregate keys only sign with sighash ALL|FORKID".to_string()));
+    }
+
+    channel.verify_state(&req.state).map_err(ServiceError::Forbidden)?;
+    if let Some(last) = key.last_signed_sequence.filter(|last| req.state.sequence_number < *last) {
+        return Err(ServiceError::Conflict(format!(
+            "State {} is older than state {}, which was already signed",
+            req.state.sequence_number, last
+        )));
+    }
+    let fee_per_byte = fees.fee_per_byte(None, None, FeeKind::Standard).await;
+    channel.check_payout(&tx, &req.state, req.value, fee_per_byte).map_err(ServiceError::ValidationError)?;
+    let digest = signing::sighash(&tx, req.input_index, &aggregate_script(&aggregated), req.value, sighash_type);
+
+    let nonce = random_scalar();
+    let nonce_point = PublicKey::from_secret_key(&Secp256k1::signing_only(), &nonce);
+    let expires_at = Utc::now() + Duration::seconds(SESSION_TTL_SECS);
+
+    let session_id: Uuid = sqlx::query_scalar(
+        "INSERT INTO aggregate_sign_sessions \
+         (key_id, tx_hex, input_index, input_value, sighash_type, sighash, service_nonce, service_nonce_point, expires_at, \
+          sequence_number) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) RETURNING id"
+    )
+    .bind(key_id)
+    .bind(req.tx_hex.trim())
+    .bind(req.input_index as i32)
+    .bind(req.value as i64)
+    .bind(sighash_type as i32)
+    .bind(hex::encode(digest))
+    .bind(hex::encode(nonce.secret_bytes()))
+    .bind(hex::encode(nonce_point.serialize()))
+    .bind(expires_at)
+    .bind(req.state.sequence_number)
+    .fetch_one(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    Ok(HttpResponse::Ok().json(OpenSessionResponse {
+        session_id,
+        sighash: hex::encode(digest),
+        service_nonce: hex::encode(nonce_point.serialize()),
+        expires_at,
+    }))
+}
+
+/// Round 2: take the client's nonce point and return our encrypted half of the signature
+pub async fn partial_sign(
+    http_req: HttpRequest,
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    jwt: web::Data<JwtManager>,
+    path: web::Path<Uuid>,
+    req: web::Json<PartialRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let session_id = path.into_inner();
+    let client_nonce = parse_point(&req.client_nonce, "client_nonce").map_err(ServiceError::ValidationError)?;
+    let Some(session) = fetch_session(&data.db, session_id).await? else {
+        return Ok(not_found("Signing session", session_id));
+    };
+    let Some(key) = fetch_key(&data.db, session.key_id).await? else {
+        return Ok(not_found("Aggregate key", session.key_id));
+    };
+    signing::require_key_access(&http_req, &jwt, &keys, [key.key_ref.as_str()])?;
+    let sequence_number = session
+        .sequence_number
+        .ok_or_else(|| ServiceError::Conflict(format!("Session {} is not bound to a channel state", session_id)))?;
+    let master = keys
+        .get(&key.key_ref)
+        .ok_or_else(|| ServiceError::BuildError(format!("Key {} is no longer loaded", key.key_ref)))?;
+
+    // Once we release a partial signature the client can finish it alone, so the key's newest
+    // signed state moves forward here, under its row lock, before the nonce is taken
+    let mut tx = data.db.begin().await.map_err(db_error)?;
+    let last_signed: Option<i64> = sqlx::query_scalar("SELECT last_signed_sequence FROM aggregate_keys WHERE id = $1 FOR UPDATE")
+        .bind(key.id)
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    if let Some(last) = last_signed.filter(|last| sequence_number < *last) {
+        return Err(ServiceError::Conflict(format!(
+            "State {} is older than state {}, which was already signed",
+            sequence_number, last
+        )));
+    }
+
+    // Take the nonce exactly once: reusing it with a second client nonce would reveal the joint key
+    let nonce_hex: Option<Option<String>> = sqlx::query_scalar(
+        "UPDATE aggregate_sign_sessions s SET status = 'partial', service_nonce = NULL, client_nonce_point = $2 \
+         FROM (SELECT id, service_nonce FROM aggregate_sign_sessions WHERE id = $1 FOR UPDATE) old \
+         WHERE s.id = old.id AND s.status = 'nonce' AND s.expires_at > NOW() \
+         RETURNING old.service_nonce"
+    )
+    .bind(session_id)
+    .bind(hex::encode(client_nonce.serialize()))
+    .fetch_optional(&mut *tx)
+    .await
+    .map_err(db_error)?;
+    let nonce = nonce_hex
+        .flatten()
+        .and_then(|h| hex::decode(h).ok())
+        .and_then(|bytes| SecretKey::from_slice(&bytes).ok())
+        .ok_or_else(|| ServiceError::Conflict(format!("Session {} is expired or its nonce was already used", session_id)))?;
+
+    sqlx::query("UPDATE aggregate_keys SET last_signed_sequence = $2 WHERE id = $1")
+        .bind(key.id)
+        .bind(sequence_number)
+        .execute(&mut *tx)
+        .await
+        .map_err(db_error)?;
+    tx.commit().await.map_err(db_error)?;
+
+    let paillier = PaillierPublicKey::from_hex(&key.paillier_n).map_err(ServiceError::BuildError)?;
+    let encrypted_share = parse_hex_uint(&key.encrypted_client_share, "encrypted_share").map_err(ServiceError::BuildError)?;
+    let share = service_share(&master.secret, key.id);
+    let (r, encrypted_partial) = partial_signature(
+        &paillier,
+        &encrypted_share,
+        &share,
+        &nonce,
+        &client_nonce,
+        &sighash_bytes(&session.sighash)?,
+    )
+    .map_err(ServiceError::BuildError)?;
+
+    Ok(HttpResponse::Ok().json(PartialResponse {
+        session_id,
+        r: hex32(&r),
+        encrypted_partial: encrypted_partial.to_str_radix(16),
+    }))
+}
+
+/// Round 3: check the client's combined signature and return the signed input
+pub async fn complete_session(
+    http_req: HttpRequest,
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    jwt: web::Data<JwtManager>,
+    path: web::Path<Uuid>,
+    req: web::Json<CompleteRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let session_id = path.into_inner();
+    let Some(session) = fetch_session(&data.db, session_id).await? else {
+        return Ok(not_found("Signing session", session_id));
+    };
+    if session.status != "partial" {
+        return Err(ServiceError::Conflict(format!("Session {} is {}, not awaiting a signature", session_id, session.status)));
+    }
+    let Some(key) = fetch_key(&data.db, session.key_id).await? else {
+        return Ok(not_found("Aggregate key", session.key_id));
+    };
+    signing::require_key_access(&http_req, &jwt, &keys, [key.key_ref.as_str()])?;
+    let aggregated = parse_point(&key.aggregated_pubkey, "aggregated_pubkey").map_err(ServiceError::BuildError)?;
+
+    let der = hex::decode(&req.signature)
+        .map_err(|_| ServiceError::ValidationError("signature must be hex".to_string()))?;
+    let signature = verify_joint_signature(&aggregated, &sighash_bytes(&session.sighash)?, &der)
+        .map_err(ServiceError::ValidationError)?;
+    let mut signature_bytes = signature.serialize_der().to_vec();
+    signature_bytes.push(session.sighash_type as u8);
+
+    let mut tx = Transaction::from_hex(&session.tx_hex).map_err(|e| ServiceError::BuildError(e.to_string()))?;
+    let script_sig = ScriptBuilder::p2pkh_script_sig(&signature_bytes, &aggregated.serialize());
+    tx.inputs[session.input_index as usize].script_sig = script_sig.clone();
+
+    let result = sqlx::query(
+        "UPDATE aggregate_sign_sessions SET status = 'completed', signature = $2, completed_at = NOW() \
+         WHERE id = $1 AND status = 'partial'"
+    )
+    .bind(session_id)
+    .bind(hex::encode(&signature_bytes))
+    .execute(&data.db)
+    .await
+    .map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Err(ServiceError::Conflict(format!("Session {} was completed concurrently", session_id)));
+    }
+
+    tracing::info!("Completed aggregate signature for key {} (session {})", key.id, session_id);
+    Ok(HttpResponse::Ok().json(CompleteResponse {
+        session_id,
+        signature: hex::encode(&signature_bytes),
+        script_sig: hex::encode(script_sig),
+        tx_hex: tx.to_hex(),
+        txid: tx.calculate_txid(),
+    }))
+}
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.route("/tx/aggregate-keys", web::post().to(create_aggregate_key))
+        .route("/tx/aggregate-keys/{id}", web::get().to(get_aggregate_key))
+        .route("/tx/aggregate-keys/{id}/sessions", web::post().to(open_session))
+        .route("/tx/aggregate-keys/sessions/{id}/partial", web::post().to(partial_sign))
+        .route("/tx/aggregate-keys/sessions/{id}/complete", web::post().to(complete_session));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TxOutput;
+
+    // 1024-bit primes for a test Paillier key
+    const P: &str = "f7e5c25a3df2a381da47c22a24be1dd0842cc22dc813a7eae06dac727374ecc1d720786f61cb0a7104ae3aea069d84d923d6101089789d5c626014b1ce367730d431e67ad4048ab57b4056ba0b3d48dbfc8c3cda7e3f53696564c244edd9829b0d098874c67bd90baf9a6e6ee2ffdeed09e8483c388b693972bdac43152d81d7";
+    const Q: &str = "f6ad95d7b44f0eae3208534ac148789d10c71365996954b2ca5afe1309f38b9c7bdbefbe197005b69fe2b842ab7ba424ea819116df516204baa4465ea4bcde05b1eaa8dcbdc098faaf04affab94168780286ec9c62dd035e4aeaf89339d3723f1b6bcd2166f496de8a2979056619f0fd756638ef5c80eb5c222b3e0adaf30af9";
+
+    // 256-bit primes for proof tests, which are slow under a full-size modulus in debug builds
+    const SMALL_P: &str = "ce971352b10080f7fd720c3da16f879f6dbe6b965a1a53a73919e52e3211e189";
+    const SMALL_Q: &str = "d3f24eabc11b1770899505463ef6a2705aaec01869bc6389c2b7caea0f791431";
+
+    /// The client's side of the Paillier key
+    struct PaillierSecret {
+        public: PaillierPublicKey,
+        phi: BigUint,
+        lambda: BigUint,
+        mu: BigUint,
+    }
+
+    impl PaillierSecret {
+        fn test_key() -> Self {
+            let n = Self::modulus(P, Q);
+            Self::from_primes(P, Q, PaillierPublicKey::from_hex(&n.to_str_radix(16)).unwrap())
+        }
+
+        /// Below `MIN_PAILLIER_BITS`, so built without `from_hex`
+        fn small_test_key() -> Self {
+            let n = Self::modulus(SMALL_P, SMALL_Q);
+            Self::from_primes(SMALL_P, SMALL_Q, PaillierPublicKey { n_squared: &n * &n, n })
+        }
+
+        fn modulus(p: &str, q: &str) -> BigUint {
+            BigUint::parse_bytes(p.as_bytes(), 16).unwrap() * BigUint::parse_bytes(q.as_bytes(), 16).unwrap()
+        }
+
+        fn from_primes(p: &str, q: &str, public: PaillierPublicKey) -> Self {
+            let p1 = BigUint::parse_bytes(p.as_bytes(), 16).unwrap() - 1u32;
+            let q1 = BigUint::parse_bytes(q.as_bytes(), 16).unwrap() - 1u32;
+            let n = &public.n;
+            let phi = &p1 * &q1;
+            let lambda = &phi / gcd(&p1, &q1);
+            // With g = N + 1, L(g^λ mod N²) = λ mod N
+            let mu = (&lambda % n).modinv(n).unwrap();
+            Self { public, phi, lambda, mu }
+        }
+
+        /// σᵢ = ρᵢ^(N⁻¹ mod φ(N)) mod N
+        fn prove_key(&self) -> Vec<BigUint> {
+            let n = &self.public.n;
+            let root = n.modinv(&self.phi).unwrap();
+            (0..KEY_PROOF_ROUNDS).map(|round| key_proof_challenge(n, round).modpow(&root, n)).collect()
+        }
+
+        /// Rounds proving `Enc(share; nonce)` holds the discrete log of share·G
+        fn prove_share(&self, share: &SecretKey, nonce: &BigUint) -> Vec<ShareProofStep> {
+            let public = &self.public;
+            let ciphertext = public.encrypt_with_nonce(&to_biguint(share), nonce);
+            let client_pubkey = PublicKey::from_secret_key(&Secp256k1::signing_only(), share);
+            let mut rng = rand::thread_rng();
+
+            let masks: Vec<(BigUint, BigUint)> = (0..SHARE_PROOF_ROUNDS)
+                .map(|_| (rng.gen_biguint(SHARE_PROOF_MASK_BITS), rng.gen_biguint_range(&BigUint::from(1u32), &public.n)))
+                .collect();
+            let mut rounds: Vec<ShareProofStep> = masks
+                .iter()
+                .map(|(alpha, beta)| ShareProofStep {
+                    a: public.encrypt_with_nonce(alpha, beta),
+                    y: PublicKey::from_secret_key(&Secp256k1::signing_only(), &to_secret_key(&(alpha % curve_order())).unwrap()),
+                    z: BigUint::from(0u32),
+                    w: BigUint::from(0u32),
+                })
+                .collect();
+            let challenges = share_proof_challenges(public, &ciphertext, &client_pubkey, &rounds);
+            for ((round, (alpha, beta)), challenge) in rounds.iter_mut().zip(&masks).zip(challenges) {
+                round.z = if challenge { alpha + to_biguint(share) } else { alpha.clone() };
+                round.w = if challenge { beta * nonce % &public.n } else { beta.clone() };
+            }
+            rounds
+        }
+
+        fn decrypt(&self, ciphertext: &BigUint) -> BigUint {
+            let n = &self.public.n;
+            let u = ciphertext.modpow(&self.lambda, &self.public.n_squared);
+            (u - 1u32) / n * &self.mu % n
+        }
+    }
+
+    fn gcd(a: &BigUint, b: &BigUint) -> BigUint {
+        let (mut a, mut b) = (a.clone(), b.clone());
+        while b != BigUint::from(0u32) {
+            let t = &a % &b;
+            a = b;
+            b = t;
+        }
+        a
+    }
+
+    fn key(byte: u8) -> SecretKey {
+        SecretKey::from_slice(&[byte; 32]).unwrap()
+    }
+
+    fn pubkey(byte: u8) -> PublicKey {
+        PublicKey::from_secret_key(&Secp256k1::signing_only(), &key(byte))
+    }
+
+    fn channel() -> ChannelBinding {
+        ChannelBinding {
+            channel_id: "chan-1".to_string(),
+            party_a_pubkey: pubkey(7),
+            party_b_pubkey: pubkey(8),
+            party_a_hash: vec![0xaa; 20],
+            party_b_hash: vec![0xbb; 20],
+            fee_divisions: fee_divisions("split").unwrap(),
+        }
+    }
+
+    /// State `sequence_number` of `channel()`, signed by both parties
+    fn signed_state(sequence_number: i64, balance_a: i64, balance_b: i64) -> ChannelState {
+        let mut state = ChannelState {
+            sequence_number,
+            balance_a,
+            balance_b,
+            signature_a: String::new(),
+            signature_b: String::new(),
+        };
+        let message = Message::from_digest_slice(&state_hash("chan-1", &state)).unwrap();
+        let secp = Secp256k1::signing_only();
+        state.signature_a = hex::encode(secp.sign_ecdsa(&message, &key(7)).serialize_der());
+        state.signature_b = hex::encode(secp.sign_ecdsa(&message, &key(8)).serialize_der());
+        state
+    }
+
+    fn payout(outputs: &[(Vec<u8>, u64)]) -> Transaction {
+        let mut tx = Transaction::new();
+        tx.add_input("11".repeat(32), 0, 100_000);
+        tx.outputs = outputs
+            .iter()
+            .map(|(script_pubkey, value)| TxOutput { value: *value, script_pubkey: script_pubkey.clone() })
+            .collect();
+        tx
+    }
+
+    #[test]
+    fn test_paillier_is_additively_homomorphic() {
+        let paillier = PaillierSecret::test_key();
+        let a = paillier.public.encrypt(&BigUint::from(1_234u32));
+        let b = paillier.public.encrypt(&BigUint::from(5_678u32));
+        assert_eq!(paillier.decrypt(&paillier.public.add(&a, &b)), BigUint::from(6_912u32));
+        assert_eq!(paillier.decrypt(&paillier.public.mul(&a, &BigUint::from(3u32))), BigUint::from(3_702u32));
+    }
+
+    #[test]
+    fn test_rejects_weak_paillier_modulus() {
+        assert!(PaillierPublicKey::from_hex("c5").is_err());
+        let even = BigUint::from(1u32) << 2048u32;
+        assert!(PaillierPublicKey::from_hex(&even.to_str_radix(16)).is_err());
+    }
+
+    #[test]
+    fn test_key_proof_requires_a_paillier_modulus() {
+        let paillier = PaillierSecret::small_test_key();
+        let sigmas = paillier.prove_key();
+        paillier.public.verify_key_proof(&sigmas).unwrap();
+
+        assert!(paillier.public.verify_key_proof(&sigmas[1..]).is_err());
+        let mut forged = sigmas.clone();
+        forged[3] = &forged[3] + 1u32;
+        assert!(paillier.public.verify_key_proof(&forged).is_err());
+
+        // A small factor is rejected before any root is checked
+        let n = &paillier.public.n * 5u32;
+        let composite = PaillierPublicKey { n_squared: &n * &n, n };
+        assert_eq!(composite.verify_key_proof(&sigmas).unwrap_err(), "paillier_n is divisible by 5");
+    }
+
+    #[test]
+    fn test_share_proof_binds_ciphertext_to_client_key() {
+        let paillier = PaillierSecret::small_test_key();
+        let x1 = key(5);
+        let nonce = BigUint::from(0x1234_5678u32);
+        let ciphertext = paillier.public.encrypt_with_nonce(&to_biguint(&x1), &nonce);
+        let rounds = paillier.prove_share(&x1, &nonce);
+        paillier.public.verify_share_proof(&ciphertext, &pubkey(5), &rounds).unwrap();
+
+        // Another key, another ciphertext or an oversized response fail
+        assert!(paillier.public.verify_share_proof(&ciphertext, &pubkey(6), &rounds).is_err());
+        let other = paillier.public.encrypt_with_nonce(&to_biguint(&key(6)), &nonce);
+        assert!(paillier.public.verify_share_proof(&other, &pubkey(5), &rounds).is_err());
+        let mut oversized = paillier.prove_share(&x1, &nonce);
+        oversized[0].z += BigUint::from(1u32) << (SHARE_PROOF_MASK_BITS + 1);
+        assert!(paillier.public.verify_share_proof(&ciphertext, &pubkey(5), &oversized).is_err());
+    }
+
+    #[test]
+    fn test_state_must_be_signed_by_both_parties() {
+        let channel = channel();
+        channel.verify_state(&signed_state(4, 60_000, 40_000)).unwrap();
+
+        let mut one_sided = signed_state(4, 60_000, 40_000);
+        one_sided.signature_b = one_sided.signature_a.clone();
+        assert!(channel.verify_state(&one_sided).is_err());
+
+        let mut altered = signed_state(4, 60_000, 40_000);
+        altered.balance_a += 1;
+        assert!(channel.verify_state(&altered).is_err());
+    }
+
+    #[test]
+    fn test_payout_must_match_state_balances() {
+        let channel = channel();
+        let state = signed_state(4, 60_000, 40_000);
+        let (pay_a, pay_b) = (ScriptBuilder::p2pkh(&[0xaa; 20]), ScriptBuilder::p2pkh(&[0xbb; 20]));
+
+        let settlement = payout(&[(pay_a.clone(), 59_900), (pay_b.clone(), 39_900)]);
+        channel.check_payout(&settlement, &state, 100_000, 1).unwrap();
+
+        // Commitment outputs pay a party behind a timelock
+        let locked = Timelock::RelativeBlocks { blocks: 144 }.locking_script(&[0xaa; 20]);
+        channel.check_payout(&payout(&[(locked, 59_900), (pay_b.clone(), 39_900)]), &state, 100_000, 1).unwrap();
+
+        let stranger = payout(&[(pay_a.clone(), 59_900), (ScriptBuilder::p2pkh(&[0xcc; 20]), 39_900)]);
+        assert_eq!(channel.check_payout(&stranger, &state, 100_000, 1).unwrap_err(), "Output 1 pays neither channel party");
+        let overpaid = payout(&[(pay_a.clone(), 40_000), (pay_b.clone(), 59_800)]);
+        assert!(channel.check_payout(&overpaid, &state, 100_000, 1).is_err());
+        assert!(channel.check_payout(&settlement, &state, 90_000, 1).is_err());
+    }
+
+    #[test]
+    fn test_payout_fee_follows_rate_and_split() {
+        let state = signed_state(4, 60_000, 40_000);
+        let (pay_a, pay_b) = (ScriptBuilder::p2pkh(&[0xaa; 20]), ScriptBuilder::p2pkh(&[0xbb; 20]));
+        let split = channel();
+
+        // A fee far above the current rate is refused even when split evenly
+        let withheld = payout(&[(pay_a.clone(), 55_000), (pay_b.clone(), 35_000)]);
+        assert!(split.check_payout(&withheld, &state, 100_000, 1).is_err());
+        assert!(split.check_payout(&withheld, &state, 100_000, 50).is_ok());
+
+        // One party paying the whole fee only passes when the stored split says so
+        let a_pays = payout(&[(pay_a, 59_800), (pay_b, 40_000)]);
+        assert!(split.check_payout(&a_pays, &state, 100_000, 1).is_err());
+        let payer = ChannelBinding { fee_divisions: fee_divisions("payer").unwrap(), ..channel() };
+        payer.check_payout(&a_pays, &state, 100_000, 1).unwrap();
+        let proportional = ChannelBinding { fee_divisions: fee_divisions("proportional").unwrap(), ..channel() };
+        assert!(proportional.check_payout(&a_pays, &state, 100_000, 1).is_err());
+        assert!(fee_divisions("bogus").is_err());
+    }
+
+    #[test]
+    fn test_service_share_is_stable_per_key() {
+        let id = Uuid::new_v4();
+        assert_eq!(service_share(&key(1), id), service_share(&key(1), id));
+        assert_ne!(service_share(&key(1), id), service_share(&key(1), Uuid::new_v4()));
+        assert_ne!(service_share(&key(1), id), service_share(&key(2), id));
+    }
+
+    #[test]
+    fn test_both_parties_derive_the_same_key() {
+        let secp = Secp256k1::new();
+        let (x1, x2) = (key(3), key(4));
+        let q1 = PublicKey::from_secret_key(&secp, &x1);
+        let q2 = PublicKey::from_secret_key(&secp, &x2);
+
+        let ours = aggregate_pubkey(&x2, &q1).unwrap();
+        let theirs = q2.mul_tweak(&secp, &Scalar::from(x1)).unwrap();
+        assert_eq!(ours, theirs);
+        assert_eq!(aggregate_script(&ours).len(), 25);
+    }
+
+    #[test]
+    fn test_two_party_signature_verifies() {
+        let secp = Secp256k1::new();
+        let order = curve_order();
+        let paillier = PaillierSecret::test_key();
+
+        // Client: share, public point and encrypted share
+        let x1 = key(5);
+        let q1 = PublicKey::from_secret_key(&secp, &x1);
+        let encrypted_share = paillier.public.encrypt(&to_biguint(&x1));
+
+        // Service
+        let x2 = service_share(&key(6), Uuid::new_v4());
+        let aggregated = aggregate_pubkey(&x2, &q1).unwrap();
+        let sighash = [0x42; 32];
+        let k2 = random_scalar();
+
+        // Client nonce, then our encrypted half
+        let k1 = random_scalar();
+        let r1 = PublicKey::from_secret_key(&secp, &k1);
+        let (r, encrypted_partial) =
+            partial_signature(&paillier.public, &encrypted_share, &x2, &k2, &r1, &sighash).unwrap();
+
+        // Client finishes: s = k1⁻¹ · Dec(c) mod n
+        let s = inverse_mod_order(&to_biguint(&k1), &order) * (paillier.decrypt(&encrypted_partial) % &order) % &order;
+        let mut compact = hex::decode(hex32(&r)).unwrap();
+        compact.extend(hex::decode(hex32(&s)).unwrap());
+        let signature = Signature::from_compact(&compact).unwrap();
+
+        let verified = verify_joint_signature(&aggregated, &sighash, &signature.serialize_der()).unwrap();
+        assert!(verify_joint_signature(&q1, &sighash, &verified.serialize_der()).is_err());
+    }
+}
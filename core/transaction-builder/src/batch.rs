@@ -0,0 +1,439 @@
+// core/transaction-builder/src/batch.rs
+// Batched payouts: many recipients per transaction, chunked when size limits are hit
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::reservations::{self, Reservation, ReservationError};
+use crate::size::{self, InputScript};
+use crate::{
+    AddressUtils, AppState, BuildTransactionResponse, Network, ServiceError, Transaction, UtxoInput,
+};
+
+/// Recipients in one request; larger runs should be split by the caller
+pub const MAX_RECIPIENTS: usize = 10_000;
+/// Default cap on payment outputs per transaction
+pub const DEFAULT_MAX_OUTPUTS_PER_TX: usize = 2_500;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayoutRecipient {
+    pub address: String,
+    pub amount_satoshis: u64,
+    /// Caller's own identifier (loan id, interest period, ...), echoed back
+    pub reference: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BatchPayoutRequest {
+    pub recipients: Vec<PayoutRecipient>,
+    pub utxos: Vec<UtxoInput>,
+    pub change_address: String,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    /// Lower than the service limit to keep each transaction small
+    pub max_tx_size_bytes: Option<usize>,
+    pub max_outputs_per_tx: Option<usize>,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RecipientError {
+    pub index: usize,
+    pub address: String,
+    pub error: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Payout {
+    /// Position in the request's recipient list
+    pub index: usize,
+    pub address: String,
+    pub amount_satoshis: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+    pub vout: u32,
+}
+
+#[derive(Serialize)]
+pub struct BatchTransaction {
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    pub payouts: Vec<Payout>,
+    pub change_satoshis: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BatchSummary {
+    pub recipient_count: usize,
+    pub transaction_count: usize,
+    pub input_count: usize,
+    pub total_paid: u64,
+    pub total_fee: u64,
+    pub total_change: u64,
+    pub fee_per_byte: u64,
+    pub total_bytes: usize,
+}
+
+#[derive(Serialize)]
+pub struct BatchPayoutResponse {
+    pub transactions: Vec<BatchTransaction>,
+    pub summary: BatchSummary,
+    pub reservation: Reservation,
+}
+
+pub struct BatchLimits {
+    pub max_tx_size_bytes: usize,
+    pub max_outputs_per_tx: usize,
+    pub dust_threshold: u64,
+}
+
+// ============================================================================
+// VALIDATION
+// ============================================================================
+
+/// Locking script for every recipient, or every recipient that is wrong
+pub fn validate_recipients(
+    recipients: &[PayoutRecipient],
+    network: Network,
+    dust_threshold: u64,
+) -> Result<Vec<Vec<u8>>, Vec<RecipientError>> {
+    let mut scripts = Vec::with_capacity(recipients.len());
+    let mut errors = Vec::new();
+
+    for (index, recipient) in recipients.iter().enumerate() {
+        let script = AddressUtils::script_for_address(&recipient.address, network).and_then(|script| {
+            if recipient.amount_satoshis <= dust_threshold {
+                Err(format!("Amount {} is at or below the dust threshold of {}", recipient.amount_satoshis, dust_threshold))
+            } else {
+                Ok(script)
+            }
+        });
+        match script {
+            Ok(script) => scripts.push(script),
+            Err(error) => errors.push(RecipientError { index, address: recipient.address.clone(), error }),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(scripts)
+    } else {
+        Err(errors)
+    }
+}
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+/// A chunk in progress: its payment outputs and the inputs funding them
+struct Chunk {
+    tx: Transaction,
+    payouts: Vec<Payout>,
+    total_in: u64,
+    total_out: u64,
+}
+
+impl Chunk {
+    fn new() -> Self {
+        Self { tx: Transaction::new(), payouts: Vec::new(), total_in: 0, total_out: 0 }
+    }
+
+    /// Fee and size with a change output in place, assuming P2PKH inputs
+    fn fee_with_change(&self, change_script: &[u8], fee_per_byte: u64) -> (u64, usize) {
+        let bytes = size::overhead_size(self.tx.inputs.len(), self.tx.outputs.len() + 1)
+            + self.tx.inputs.len() * size::P2PKH_INPUT_SIZE
+            + self.tx.outputs.iter().map(|o| size::output_size(o.script_pubkey.len())).sum::<usize>()
+            + size::output_size(change_script.len());
+        (bytes as u64 * fee_per_byte, bytes)
+    }
+}
+
+/// Pay every recipient, in order, starting a new transaction whenever the next
+/// payment would push the current one past the size or output limits
+pub fn build_batch(
+    recipients: &[PayoutRecipient],
+    scripts: &[Vec<u8>],
+    utxos: Vec<UtxoInput>,
+    change_script: &[u8],
+    fee_per_byte: u64,
+    limits: &BatchLimits,
+) -> Result<Vec<(Transaction, Vec<Payout>)>, String> {
+    let mut pool: VecDeque<UtxoInput> = utxos.into();
+    let mut chunks = Vec::new();
+    let mut chunk = Chunk::new();
+
+    for (index, (recipient, script)) in recipients.iter().zip(scripts).enumerate() {
+        let mut attempt = 0;
+        loop {
+            let outputs_before = chunk.tx.outputs.len();
+            let inputs_before = chunk.tx.inputs.len();
+            chunk.tx.add_output(recipient.amount_satoshis, script.clone());
+            chunk.total_out += recipient.amount_satoshis;
+
+            let mut fits = chunk.tx.outputs.len() <= limits.max_outputs_per_tx;
+            while fits {
+                let (fee, bytes) = chunk.fee_with_change(change_script, fee_per_byte);
+                if bytes > limits.max_tx_size_bytes {
+                    fits = false;
+                } else if chunk.total_in >= chunk.total_out + fee {
+                    break;
+                } else if let Some(utxo) = pool.pop_front() {
+                    chunk.tx.add_input(utxo.txid, utxo.vout, utxo.satoshis);
+                    chunk.total_in += utxo.satoshis;
+                } else {
+                    return Err(format!(
+                        "Insufficient funds: UTXOs exhausted at recipient {} ({} paid so far in this transaction, {} available)",
+                        index, chunk.total_out, chunk.total_in
+                    ));
+                }
+            }
+
+            if fits {
+                chunk.payouts.push(Payout {
+                    index,
+                    address: recipient.address.clone(),
+                    amount_satoshis: recipient.amount_satoshis,
+                    reference: recipient.reference.clone(),
+                    vout: outputs_before as u32,
+                });
+                break;
+            }
+
+            // Undo this recipient, hand its inputs back and close the chunk
+            chunk.tx.outputs.truncate(outputs_before);
+            chunk.total_out -= recipient.amount_satoshis;
+            for input in chunk.tx.inputs.drain(inputs_before..).rev() {
+                chunk.total_in -= input.value;
+                pool.push_front(UtxoInput { txid: input.txid, vout: input.vout, satoshis: input.value, confirmations: None });
+            }
+
+            attempt += 1;
+            if chunk.payouts.is_empty() || attempt > 1 {
+                return Err(format!("Recipient {} cannot fit in a transaction within the size limit", index));
+            }
+            chunks.push(finish(chunk, change_script, fee_per_byte, limits.dust_threshold));
+            chunk = Chunk::new();
+        }
+    }
+
+    if !chunk.payouts.is_empty() {
+        chunks.push(finish(chunk, change_script, fee_per_byte, limits.dust_threshold));
+    }
+    Ok(chunks)
+}
+
+fn finish(mut chunk: Chunk, change_script: &[u8], fee_per_byte: u64, dust_threshold: u64) -> (Transaction, Vec<Payout>) {
+    let (fee, _) = chunk.fee_with_change(change_script, fee_per_byte);
+    let change = chunk.total_in - chunk.total_out - fee;
+    if change > dust_threshold {
+        chunk.tx.add_output(change, change_script.to_vec());
+    }
+    (chunk.tx, chunk.payouts)
+}
+
+// ============================================================================
+// HANDLER
+// ============================================================================
+
+pub async fn build_batch_payout(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    req: web::Json<BatchPayoutRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    if req.recipients.is_empty() {
+        return Err(ServiceError::ValidationError("No recipients provided".to_string()));
+    }
+    if req.recipients.len() > MAX_RECIPIENTS {
+        return Err(ServiceError::ValidationError(format!("At most {} recipients per request", MAX_RECIPIENTS)));
+    }
+    if req.utxos.is_empty() {
+        return Err(ServiceError::ValidationError("No UTXOs provided".to_string()));
+    }
+
+    let config = &data.config;
+    let scripts = match validate_recipients(&req.recipients, config.network, config.dust_threshold) {
+        Ok(scripts) => scripts,
+        Err(errors) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": "validation_error",
+                "message": format!("{} of {} recipients are invalid", errors.len(), req.recipients.len()),
+                "recipients": errors
+            })));
+        }
+    };
+    let change_script = AddressUtils::script_for_address(&req.change_address, config.network)
+        .map_err(|e| ServiceError::ValidationError(format!("Invalid change_address: {}", e)))?;
+
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+    if fee_per_byte == 0 || fee_per_byte > 10000 {
+        return Err(ServiceError::ValidationError("Fee per byte must be between 1 and 10000".to_string()));
+    }
+    let limits = BatchLimits {
+        max_tx_size_bytes: req.max_tx_size_bytes.unwrap_or(usize::MAX).min(config.max_tx_size_bytes),
+        max_outputs_per_tx: req.max_outputs_per_tx.unwrap_or(DEFAULT_MAX_OUTPUTS_PER_TX).max(1),
+        dust_threshold: config.dust_threshold,
+    };
+
+    let mut req = req.into_inner();
+    let utxos = reservations::filter_available(&data.db, std::mem::take(&mut req.utxos))
+        .await
+        .map_err(ReservationError::from)?;
+
+    let chunks = build_batch(&req.recipients, &scripts, utxos, &change_script, fee_per_byte, &limits)
+        .map_err(|e| {
+            tracing::error!("Failed to build batch payout: {}", e);
+            ServiceError::BuildError(e)
+        })?;
+
+    let mut summary = BatchSummary {
+        recipient_count: req.recipients.len(),
+        transaction_count: chunks.len(),
+        fee_per_byte,
+        ..Default::default()
+    };
+    let mut transactions = Vec::with_capacity(chunks.len());
+    let mut all_inputs = Vec::new();
+    let mut built = Vec::with_capacity(chunks.len());
+
+    for (tx, payouts) in chunks {
+        let fee_breakdown = size::fee_breakdown(&tx, &size::input_scripts(&tx, InputScript::P2PKH), fee_per_byte);
+        let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+        let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+        let paid: u64 = payouts.iter().map(|p| p.amount_satoshis).sum();
+        let change_satoshis = total_out - paid;
+
+        summary.input_count += tx.inputs.len();
+        summary.total_paid += paid;
+        summary.total_fee += total_in - total_out;
+        summary.total_change += change_satoshis;
+        summary.total_bytes += fee_breakdown.total_bytes;
+        all_inputs.extend(tx.inputs.iter().cloned());
+
+        transactions.push(BatchTransaction {
+            transaction: BuildTransactionResponse {
+                tx_hex: tx.to_hex(),
+                txid: tx.calculate_txid(),
+                size_bytes: tx.calculate_size(),
+                fee_satoshis: total_in - total_out,
+                inputs: tx.inputs.clone(),
+                outputs: tx.outputs.clone(),
+                fee_breakdown,
+            },
+            payouts,
+            change_satoshis,
+        });
+        built.push(tx);
+    }
+
+    let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, config.reservation_ttl_secs);
+    let reservation = reservations::reserve(&data.db, &all_inputs, ttl, "build:batch-payout").await?;
+    for tx in &built {
+        history::record(&data, &http_req, TxType::BatchPayout, tx).await?;
+    }
+
+    tracing::info!(
+        "Built batch payout: {} recipients in {} transaction(s), {} sats paid, {} sats fee",
+        summary.recipient_count, summary.transaction_count, summary.total_paid, summary.total_fee
+    );
+
+    Ok(HttpResponse::Ok().json(BatchPayoutResponse { transactions, summary, reservation }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AddressKind;
+
+    fn address(byte: u8) -> String {
+        AddressUtils::encode_address(&[byte; 20], Network::Testnet, AddressKind::P2pkh)
+    }
+
+    fn recipients(count: usize, amount: u64) -> Vec<PayoutRecipient> {
+        (0..count)
+            .map(|i| PayoutRecipient { address: address(i as u8), amount_satoshis: amount, reference: Some(format!("r{}", i)) })
+            .collect()
+    }
+
+    fn utxos(count: usize, satoshis: u64) -> Vec<UtxoInput> {
+        (0..count).map(|i| UtxoInput { txid: format!("{:064x}", i + 1), vout: 0, satoshis, confirmations: None }).collect()
+    }
+
+    fn limits(max_tx_size_bytes: usize, max_outputs_per_tx: usize) -> BatchLimits {
+        BatchLimits { max_tx_size_bytes, max_outputs_per_tx, dust_threshold: 546 }
+    }
+
+    fn build(recipients: &[PayoutRecipient], utxos: Vec<UtxoInput>, limits: &BatchLimits) -> Result<Vec<(Transaction, Vec<Payout>)>, String> {
+        let scripts = validate_recipients(recipients, Network::Testnet, 546).unwrap();
+        let change = AddressUtils::script_for_address(&address(0xff), Network::Testnet).unwrap();
+        build_batch(recipients, &scripts, utxos, &change, 1, limits)
+    }
+
+    #[test]
+    fn test_validation_reports_each_bad_recipient() {
+        let mut list = recipients(3, 10_000);
+        list[1].address = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH".to_string(); // mainnet
+        list[2].amount_satoshis = 546;
+
+        let errors = validate_recipients(&list, Network::Testnet, 546).unwrap_err();
+        assert_eq!(errors.iter().map(|e| e.index).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(errors[0].error.contains("testnet"));
+    }
+
+    #[test]
+    fn test_single_transaction_with_change() {
+        let list = recipients(50, 10_000);
+        let chunks = build(&list, utxos(1, 1_000_000), &limits(1_000_000, 2_500)).unwrap();
+        assert_eq!(chunks.len(), 1);
+
+        let (tx, payouts) = &chunks[0];
+        assert_eq!(payouts.len(), 50);
+        assert_eq!(tx.outputs.len(), 51);
+        assert_eq!(payouts[7].vout, 7);
+        assert_eq!(payouts[7].reference.as_deref(), Some("r7"));
+
+        let fee = 1_000_000 - tx.outputs.iter().map(|o| o.value).sum::<u64>();
+        assert_eq!(fee as usize, size::estimate_size(tx, &[InputScript::P2PKH]));
+    }
+
+    #[test]
+    fn test_chunks_on_output_limit() {
+        let list = recipients(25, 10_000);
+        let chunks = build(&list, utxos(5, 200_000), &limits(1_000_000, 10)).unwrap();
+        assert_eq!(chunks.iter().map(|(_, p)| p.len()).collect::<Vec<_>>(), vec![10, 10, 5]);
+        // Every recipient is paid exactly once, in order
+        let indexes: Vec<usize> = chunks.iter().flat_map(|(_, p)| p.iter().map(|p| p.index)).collect();
+        assert_eq!(indexes, (0..25).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_chunks_on_size_limit() {
+        let list = recipients(40, 10_000);
+        let chunks = build(&list, utxos(10, 100_000), &limits(1_000, 2_500)).unwrap();
+        assert!(chunks.len() > 1);
+        for (tx, _) in &chunks {
+            assert!(size::estimate_size(tx, &size::input_scripts(tx, InputScript::P2PKH)) <= 1_000);
+        }
+        // Inputs are not shared between chunks
+        let mut outpoints: Vec<String> = chunks.iter().flat_map(|(tx, _)| tx.inputs.iter().map(|i| i.txid.clone())).collect();
+        let before = outpoints.len();
+        outpoints.sort();
+        outpoints.dedup();
+        assert_eq!(outpoints.len(), before);
+    }
+
+    #[test]
+    fn test_insufficient_funds_and_oversized_recipient() {
+        let list = recipients(10, 10_000);
+        assert!(build(&list, utxos(1, 50_000), &limits(1_000_000, 2_500)).is_err());
+        // A limit below a one-in, one-out transaction can never be met
+        assert!(build(&list, utxos(1, 1_000_000), &limits(100, 2_500)).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+// core/transaction-builder/src/broadcast.rs
+// Pushes signed transactions to the network via blockchain-monitor, falling back to a node's RPC
+
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Where a transaction was accepted
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BroadcastBackend {
+    Monitor,
+    Node,
+}
+
+impl BroadcastBackend {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BroadcastBackend::Monitor => "monitor",
+            BroadcastBackend::Node => "node",
+        }
+    }
+}
+
+struct NodeRpc {
+    url: String,
+    user: String,
+    password: String,
+}
+
+pub struct Broadcaster {
+    client: reqwest::Client,
+    monitor_url: String,
+    node: Option<NodeRpc>,
+}
+
+/// Rejections that mean the network already has this exact transaction
+pub fn already_known(message: &str) -> bool {
+    let message = message.to_lowercase();
+    ["txn-already-known", "txn-already-in-mempool", "already in block chain", "transaction already known"]
+        .iter()
+        .any(|m| message.contains(m))
+}
+
+fn error_message(body: &Value, fallback: &str) -> String {
+    body.get("message")
+        .or_else(|| body.pointer("/error/message"))
+        .or_else(|| body.get("error"))
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .unwrap_or_else(|| fallback.to_string())
+}
+
+impl Broadcaster {
+    pub fn from_env() -> Self {
+        let node = std::env::var("BSV_NODE_RPC_URL").ok().map(|url| NodeRpc {
+            url,
+            user: std::env::var("BSV_NODE_RPC_USER").unwrap_or_default(),
+            password: std::env::var("BSV_NODE_RPC_PASSWORD").unwrap_or_default(),
+        });
+
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(10))
+                .build()
+                .unwrap_or_default(),
+            monitor_url: std::env::var("BLOCKCHAIN_MONITOR_URL")
+                .unwrap_or_else(|_| "http://localhost:8084".to_string()),
+            node,
+        }
+    }
+
+    pub fn has_node(&self) -> bool {
+        self.node.is_some()
+    }
+
+    /// Broadcast `tx_hex` (whose id is `txid`), trying blockchain-monitor first
+    pub async fn broadcast(&self, tx_hex: &str, txid: &str) -> Result<BroadcastBackend, String> {
+        let monitor_error = match self.via_monitor(tx_hex).await {
+            Ok(()) => return Ok(BroadcastBackend::Monitor),
+            Err(e) if already_known(&e) => return Ok(BroadcastBackend::Monitor),
+            Err(e) => e,
+        };
+        tracing::warn!("blockchain-monitor broadcast of {} failed: {}", txid, monitor_error);
+
+        let Some(node) = &self.node else {
+            return Err(monitor_error);
+        };
+        match self.via_node(node, tx_hex).await {
+            Ok(()) => Ok(BroadcastBackend::Node),
+            Err(e) if already_known(&e) => Ok(BroadcastBackend::Node),
+            Err(e) => Err(format!("monitor: {}; node: {}", monitor_error, e)),
+        }
+    }
+
+    async fn via_monitor(&self, tx_hex: &str) -> Result<(), String> {
+        let response = self
+            .client
+            .post(format!("{}/broadcast", self.monitor_url))
+            .json(&json!({ "tx_hex": tx_hex }))
+            .send()
+            .await
+            .map_err(|e| format!("blockchain-monitor unreachable: {}", e))?;
+        let status = response.status();
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+
+        if status.is_success() && body.get("success").and_then(Value::as_bool) == Some(true) {
+            Ok(())
+        } else {
+            Err(error_message(&body, &format!("blockchain-monitor returned {}", status)))
+        }
+    }
+
+    async fn via_node(&self, node: &NodeRpc, tx_hex: &str) -> Result<(), String> {
+        let response = self
+            .client
+            .post(&node.url)
+            .basic_auth(&node.user, Some(&node.password))
+            .json(&json!({
+                "jsonrpc": "1.0",
+                "id": "transaction-builder",
+                "method": "sendrawtransaction",
+                "params": [tx_hex],
+            }))
+            .send()
+            .await
+            .map_err(|e| format!("node unreachable: {}", e))?;
+        let status = response.status();
+        // bitcoind answers RPC errors with a 500 and a JSON body
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+
+        match body.get("error") {
+            Some(error) if !error.is_null() => Err(error_message(&body, &error.to_string())),
+            _ if body.get("result").is_some_and(Value::is_string) => Ok(()),
+            _ => Err(format!("node returned {}", status)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_already_known() {
+        assert!(already_known("258: txn-already-known"));
+        assert!(already_known("Transaction already in block chain"));
+        assert!(!already_known("16: mandatory-script-verify-flag-failed"));
+        assert!(!already_known("258: txn-mempool-conflict"));
+    }
+
+    #[test]
+    fn test_error_message_shapes() {
+        let service = json!({ "error": "validation_error", "message": "Invalid transaction hex" });
+        assert_eq!(error_message(&service, "x"), "Invalid transaction hex");
+
+        let rpc = json!({ "result": null, "error": { "code": -26, "message": "258: txn-mempool-conflict" } });
+        assert_eq!(error_message(&rpc, "x"), "258: txn-mempool-conflict");
+
+        assert_eq!(error_message(&Value::Null, "returned 502"), "returned 502");
+    }
+}
@@ -0,0 +1,395 @@
+// core/transaction-builder/src/chain.rs
+// Transaction chaining: a child spending an unconfirmed parent output, optionally paying for both (CPFP)
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::codec::{classify_script, ScriptType};
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::p2sh::RedeemScript;
+use crate::reservations::{self, Reservation, ReservationError};
+use crate::size::{self, InputScript};
+use crate::{
+    AddressUtils, AppState, BuildTransactionResponse, ScriptBuilder, ServiceError, Transaction, UtxoInput,
+};
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct BuildChildRequest {
+    /// Signed parent; its txid must be final before a child can reference it
+    pub parent_hex: String,
+    pub parent_vout: u32,
+    /// Required when the parent output is P2SH
+    pub parent_redeem_script: Option<String>,
+    /// What the parent pays in fees; needed to price a CPFP package
+    pub parent_fee_satoshis: Option<u64>,
+    pub to_address: String,
+    /// Omit to sweep the parent output (and any extra UTXOs) to `to_address`
+    pub amount_satoshis: Option<u64>,
+    /// Confirmed P2PKH UTXOs added when the parent output alone cannot pay
+    #[serde(default)]
+    pub utxos: Vec<UtxoInput>,
+    pub change_address: Option<String>,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    /// Pay enough that parent and child together reach the fee rate (child-pays-for-parent)
+    #[serde(default)]
+    pub cpfp: bool,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChainRole {
+    Parent,
+    Child,
+}
+
+#[derive(Serialize)]
+pub struct ChainedTransaction {
+    pub role: ChainRole,
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    /// Txids that must be broadcast first
+    pub depends_on: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reservation: Option<Reservation>,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct PackageSummary {
+    pub size_bytes: usize,
+    pub fee_satoshis: u64,
+    /// Effective rate of parent and child together, rounded down
+    pub fee_per_byte: u64,
+}
+
+#[derive(Serialize)]
+pub struct ChainResponse {
+    /// In broadcast order
+    pub transactions: Vec<ChainedTransaction>,
+    pub package: PackageSummary,
+}
+
+/// A validated child and what it was priced against
+pub struct Chain {
+    pub parent: Transaction,
+    pub child: Transaction,
+    pub child_inputs: Vec<InputScript>,
+    pub parent_fee: u64,
+}
+
+// ============================================================================
+// VALIDATION
+// ============================================================================
+
+/// The parent output being spent and how its unlocking script will be sized
+pub fn parent_output(
+    parent: &Transaction,
+    vout: u32,
+    redeem_script: Option<&str>,
+) -> Result<(u64, InputScript), String> {
+    if parent.inputs.iter().any(|i| i.script_sig.is_empty()) {
+        return Err("Parent is not fully signed; its txid would change once signed".to_string());
+    }
+    let output = parent
+        .outputs
+        .get(vout as usize)
+        .ok_or_else(|| format!("Parent has {} outputs; vout {} does not exist", parent.outputs.len(), vout))?;
+
+    let input_script = match classify_script(&output.script_pubkey) {
+        ScriptType::P2pkh => InputScript::P2PKH,
+        ScriptType::P2sh => {
+            let redeem = RedeemScript::from_hex(
+                redeem_script.ok_or("parent_redeem_script is required to spend a P2SH output")?,
+            )?;
+            if ScriptBuilder::p2sh(&AddressUtils::hash160(&redeem.script)) != output.script_pubkey {
+                return Err("parent_redeem_script does not hash to the parent output".to_string());
+            }
+            redeem.input_script()
+        }
+        other => return Err(format!("Cannot spend a {:?} parent output", other)),
+    };
+    if output.value == 0 {
+        return Err("Parent output carries no value".to_string());
+    }
+    Ok((output.value, input_script))
+}
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+pub fn build_child(req: &BuildChildRequest, fee_per_byte: u64, dust_threshold: u64) -> Result<Chain, String> {
+    let parent = Transaction::from_hex(&req.parent_hex).map_err(|e| format!("Invalid parent_hex: {}", e))?;
+    let (parent_value, parent_input) = parent_output(&parent, req.parent_vout, req.parent_redeem_script.as_deref())?;
+    let parent_txid = parent.calculate_txid();
+    let parent_fee = req.parent_fee_satoshis.unwrap_or(0);
+
+    // Extra UTXOs must not be the parent's own outputs or anything the parent already spends
+    let parent_spends: HashSet<(String, u32)> =
+        parent.inputs.iter().map(|i| (i.txid.to_lowercase(), i.vout)).collect();
+    let mut seen = HashSet::new();
+    for utxo in &req.utxos {
+        let key = (utxo.txid.to_lowercase(), utxo.vout);
+        if key.0 == parent_txid {
+            return Err(format!("UTXO {}:{} is a parent output; use parent_vout", utxo.txid, utxo.vout));
+        }
+        if parent_spends.contains(&key) {
+            return Err(format!("UTXO {}:{} is already spent by the parent", utxo.txid, utxo.vout));
+        }
+        if !seen.insert(key) {
+            return Err(format!("Duplicate UTXO {}:{}", utxo.txid, utxo.vout));
+        }
+    }
+
+    let to_script = ScriptBuilder::p2pkh(&AddressUtils::decode_address(&req.to_address)?);
+    let change_script = req
+        .change_address
+        .as_deref()
+        .map(AddressUtils::decode_address)
+        .transpose()?
+        .map(|hash| ScriptBuilder::p2pkh(&hash));
+    if req.amount_satoshis.is_some() && change_script.is_none() && !req.utxos.is_empty() {
+        return Err("change_address is required when adding UTXOs to a fixed amount".to_string());
+    }
+
+    let mut child = Transaction::new();
+    child.add_input(parent_txid.clone(), req.parent_vout, parent_value);
+    let mut child_inputs = vec![parent_input];
+    child.add_output(req.amount_satoshis.unwrap_or(0), to_script);
+
+    // The child's own fee, plus whatever the parent fell short of the rate when bumping
+    let fee_for = |child: &Transaction, inputs: &[InputScript], with_change: bool| {
+        let mut sized = child.clone();
+        if with_change {
+            if let Some(script) = &change_script {
+                sized.add_output(0, script.clone());
+            }
+        }
+        let child_size = size::estimate_size(&sized, inputs);
+        if req.cpfp {
+            let package = (child_size + parent.calculate_size()) as u64 * fee_per_byte;
+            package.saturating_sub(parent_fee).max(child_size as u64 * fee_per_byte)
+        } else {
+            child_size as u64 * fee_per_byte
+        }
+    };
+
+    // A sweep only has to leave more than dust once the fee is paid
+    let floor = match req.amount_satoshis {
+        Some(amount) if amount <= dust_threshold => {
+            return Err(format!("amount_satoshis must exceed the dust threshold of {}", dust_threshold));
+        }
+        Some(amount) => amount,
+        None => dust_threshold + 1,
+    };
+    let mut total_in = parent_value;
+    let mut extra = req.utxos.iter();
+    loop {
+        let needed = floor + fee_for(&child, &child_inputs, false);
+        if total_in >= needed {
+            break;
+        }
+        let utxo = extra
+            .next()
+            .ok_or_else(|| format!("Insufficient funds: need {} sats, have {} sats", needed, total_in))?;
+        child.add_input(utxo.txid.clone(), utxo.vout, utxo.satoshis);
+        child_inputs.push(InputScript::P2PKH);
+        total_in += utxo.satoshis;
+    }
+
+    match req.amount_satoshis {
+        Some(amount) => {
+            let fee_with_change = fee_for(&child, &child_inputs, true);
+            let change = total_in.saturating_sub(amount + fee_with_change);
+            if let Some(script) = change_script.filter(|_| change > dust_threshold) {
+                child.add_output(change, script);
+            }
+        }
+        None => {
+            let fee = fee_for(&child, &child_inputs, false);
+            child.outputs[0].value = total_in - fee;
+        }
+    }
+
+    Ok(Chain { parent, child, child_inputs, parent_fee })
+}
+
+impl Chain {
+    pub fn package(&self) -> PackageSummary {
+        let child_in: u64 = self.child.inputs.iter().map(|i| i.value).sum();
+        let child_out: u64 = self.child.outputs.iter().map(|o| o.value).sum();
+        let size_bytes = self.parent.calculate_size() + size::estimate_size(&self.child, &self.child_inputs);
+        let fee_satoshis = self.parent_fee + child_in - child_out;
+        PackageSummary { size_bytes, fee_satoshis, fee_per_byte: fee_satoshis / size_bytes as u64 }
+    }
+}
+
+// ============================================================================
+// HANDLER
+// ============================================================================
+
+pub async fn build_child_handler(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    req: web::Json<BuildChildRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+    if fee_per_byte == 0 || fee_per_byte > 10000 {
+        return Err(ServiceError::ValidationError("Fee per byte must be between 1 and 10000".to_string()));
+    }
+
+    let mut req = req.into_inner();
+    let supplied = req.utxos.len();
+    req.utxos = reservations::filter_available(&data.db, req.utxos)
+        .await
+        .map_err(ReservationError::from)?;
+    if req.utxos.len() < supplied {
+        tracing::warn!("{} of {} extra UTXOs are reserved elsewhere", supplied - req.utxos.len(), supplied);
+    }
+
+    let chain = build_child(&req, fee_per_byte, data.config.dust_threshold).map_err(|e| {
+        tracing::error!("Failed to build child transaction: {}", e);
+        ServiceError::BuildError(e)
+    })?;
+    let package = chain.package();
+
+    // Locking the parent outpoint too stops a second child double-spending it
+    let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, data.config.reservation_ttl_secs);
+    let reservation = reservations::reserve(&data.db, &chain.child.inputs, ttl, "build:child").await?;
+    history::record(&data, &http_req, TxType::Child, &chain.child).await?;
+
+    let parent_txid = chain.parent.calculate_txid();
+    let parent_inputs = size::input_scripts(&chain.parent, InputScript::P2PKH);
+    let parent = ChainedTransaction {
+        role: ChainRole::Parent,
+        transaction: BuildTransactionResponse {
+            tx_hex: chain.parent.to_hex(),
+            txid: parent_txid.clone(),
+            size_bytes: chain.parent.calculate_size(),
+            fee_satoshis: chain.parent_fee,
+            inputs: chain.parent.inputs.clone(),
+            outputs: chain.parent.outputs.clone(),
+            fee_breakdown: size::fee_breakdown(&chain.parent, &parent_inputs, fee_per_byte),
+        },
+        depends_on: Vec::new(),
+        reservation: None,
+    };
+
+    let child_in: u64 = chain.child.inputs.iter().map(|i| i.value).sum();
+    let child_out: u64 = chain.child.outputs.iter().map(|o| o.value).sum();
+    let child_txid = chain.child.calculate_txid();
+    tracing::info!(
+        "Built child {} of parent {} (package {} bytes at {} sat/byte)",
+        child_txid, parent_txid, package.size_bytes, package.fee_per_byte
+    );
+    let child = ChainedTransaction {
+        role: ChainRole::Child,
+        transaction: BuildTransactionResponse {
+            tx_hex: chain.child.to_hex(),
+            txid: child_txid,
+            size_bytes: chain.child.calculate_size(),
+            fee_satoshis: child_in - child_out,
+            inputs: chain.child.inputs.clone(),
+            outputs: chain.child.outputs.clone(),
+            fee_breakdown: size::fee_breakdown(&chain.child, &chain.child_inputs, fee_per_byte),
+        },
+        depends_on: vec![parent_txid],
+        reservation: Some(reservation),
+    };
+
+    Ok(HttpResponse::Ok().json(ChainResponse { transactions: vec![parent, child], package }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+
+    fn parent(value: u64) -> Transaction {
+        let mut tx = Transaction::new();
+        tx.add_input("aa".repeat(32), 0, 0);
+        tx.inputs[0].script_sig = vec![0x51; 107];
+        tx.add_output(value, ScriptBuilder::p2pkh(&AddressUtils::decode_address(ADDRESS).unwrap()));
+        tx.add_output(0, ScriptBuilder::data_output(&[b"memo".to_vec()], false));
+        tx
+    }
+
+    fn request(parent: &Transaction, amount: Option<u64>, cpfp: bool) -> BuildChildRequest {
+        BuildChildRequest {
+            parent_hex: parent.to_hex(),
+            parent_vout: 0,
+            parent_redeem_script: None,
+            parent_fee_satoshis: Some(0),
+            to_address: ADDRESS.to_string(),
+            amount_satoshis: amount,
+            utxos: Vec::new(),
+            change_address: Some(ADDRESS.to_string()),
+            fee_per_byte: None,
+            fee_policy: None,
+            cpfp,
+            reservation_ttl_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_child_spends_parent_output() {
+        let parent = parent(50_000);
+        let chain = build_child(&request(&parent, Some(20_000), false), 1, 546).unwrap();
+        assert_eq!(chain.child.inputs[0].txid, parent.calculate_txid());
+        assert_eq!(chain.child.inputs[0].vout, 0);
+        assert_eq!(chain.child.outputs[0].value, 20_000);
+        assert_eq!(chain.child.outputs.len(), 2);
+    }
+
+    #[test]
+    fn test_cpfp_brings_package_to_rate() {
+        let parent = parent(50_000);
+        let plain = build_child(&request(&parent, None, false), 2, 546).unwrap();
+        let bumped = build_child(&request(&parent, None, true), 2, 546).unwrap();
+
+        assert!(bumped.child.outputs[0].value < plain.child.outputs[0].value);
+        assert!(bumped.package().fee_per_byte >= 2);
+        assert!(plain.package().fee_per_byte < 2);
+    }
+
+    #[test]
+    fn test_rejects_invalid_chains() {
+        let parent = parent(50_000);
+
+        let mut req = request(&parent, Some(20_000), false);
+        req.parent_vout = 1; // OP_RETURN
+        assert!(build_child(&req, 1, 546).is_err());
+
+        req.parent_vout = 5;
+        assert!(build_child(&req, 1, 546).is_err());
+
+        let mut unsigned = parent.clone();
+        unsigned.inputs[0].script_sig.clear();
+        assert!(build_child(&request(&unsigned, Some(20_000), false), 1, 546).is_err());
+
+        // Double-spending the parent's own input
+        let mut req = request(&parent, Some(20_000), false);
+        req.utxos = vec![UtxoInput { txid: "aa".repeat(32), vout: 0, satoshis: 10_000, confirmations: None }];
+        assert!(build_child(&req, 1, 546).is_err());
+    }
+
+    #[test]
+    fn test_extra_utxos_cover_shortfall() {
+        let parent = parent(5_000);
+        let mut req = request(&parent, Some(20_000), false);
+        assert!(build_child(&req, 1, 546).is_err());
+
+        req.utxos = vec![UtxoInput { txid: "bb".repeat(32), vout: 1, satoshis: 30_000, confirmations: None }];
+        let chain = build_child(&req, 1, 546).unwrap();
+        assert_eq!(chain.child.inputs.len(), 2);
+    }
+}
@@ -0,0 +1,356 @@
+// core/transaction-builder/src/codec.rs
+// Wire-format decoding: varints, transactions and output script classification
+
+use serde::Serialize;
+use thiserror::Error;
+
+use crate::{AddressKind, AddressUtils, Network, Transaction, TxInput, TxOutput};
+
+#[derive(Debug, Error, PartialEq)]
+pub enum DecodeError {
+    #[error("Invalid hex: {0}")]
+    Hex(String),
+    #[error("Unexpected end of data at byte {0}")]
+    UnexpectedEof(usize),
+    #[error("{0} trailing bytes after locktime")]
+    TrailingBytes(usize),
+    #[error("Declared {what} count {count} exceeds the data available")]
+    ImplausibleCount { what: &'static str, count: u64 },
+}
+
+// ============================================================================
+// VARINTS
+// ============================================================================
+
+/// Append a Bitcoin CompactSize integer
+pub fn write_varint(bytes: &mut Vec<u8>, n: u64) {
+    match n {
+        0..=0xfc => bytes.push(n as u8),
+        0xfd..=0xffff => {
+            bytes.push(0xfd);
+            bytes.extend_from_slice(&(n as u16).to_le_bytes());
+        }
+        0x1_0000..=0xffff_ffff => {
+            bytes.push(0xfe);
+            bytes.extend_from_slice(&(n as u32).to_le_bytes());
+        }
+        _ => {
+            bytes.push(0xff);
+            bytes.extend_from_slice(&n.to_le_bytes());
+        }
+    }
+}
+
+/// Encoded size of a CompactSize integer
+pub const fn varint_len(n: u64) -> usize {
+    match n {
+        0..=0xfc => 1,
+        0xfd..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.data.len() - self.pos
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], DecodeError> {
+        if self.remaining() < n {
+            return Err(DecodeError::UnexpectedEof(self.data.len()));
+        }
+        let slice = &self.data[self.pos..self.pos + n];
+        self.pos += n;
+        Ok(slice)
+    }
+
+    fn u8(&mut self) -> Result<u8, DecodeError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, DecodeError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, DecodeError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn u64(&mut self) -> Result<u64, DecodeError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        match self.u8()? {
+            0xfd => self.u16().map(u64::from),
+            0xfe => self.u32().map(u64::from),
+            0xff => self.u64(),
+            n => Ok(n as u64),
+        }
+    }
+
+    /// Read an item count, rejecting counts that could not possibly fit in
+    /// the remaining bytes so hostile input cannot force huge allocations
+    fn count(&mut self, what: &'static str, min_item_size: usize) -> Result<usize, DecodeError> {
+        let count = self.varint()?;
+        if count > (self.remaining() / min_item_size) as u64 {
+            return Err(DecodeError::ImplausibleCount { what, count });
+        }
+        Ok(count as usize)
+    }
+
+    fn var_bytes(&mut self) -> Result<Vec<u8>, DecodeError> {
+        let len = self.count("script byte", 1)?;
+        Ok(self.take(len)?.to_vec())
+    }
+}
+
+// ============================================================================
+// TRANSACTIONS
+// ============================================================================
+
+impl Transaction {
+    /// Parse a serialized transaction. Input values are not part of the wire
+    /// format and are left at zero.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, DecodeError> {
+        let mut r = Reader::new(bytes);
+
+        let version = r.u32()?;
+
+        // txid + vout + script length + sequence
+        let input_count = r.count("input", 41)?;
+        let mut inputs = Vec::with_capacity(input_count);
+        for _ in 0..input_count {
+            let mut txid = r.take(32)?.to_vec();
+            txid.reverse();
+            let vout = r.u32()?;
+            let script_sig = r.var_bytes()?;
+            let sequence = r.u32()?;
+            inputs.push(TxInput {
+                txid: hex::encode(txid),
+                vout,
+                script_sig,
+                sequence,
+                value: 0,
+            });
+        }
+
+        // value + script length
+        let output_count = r.count("output", 9)?;
+        let mut outputs = Vec::with_capacity(output_count);
+        for _ in 0..output_count {
+            let value = r.u64()?;
+            let script_pubkey = r.var_bytes()?;
+            outputs.push(TxOutput { value, script_pubkey });
+        }
+
+        let locktime = r.u32()?;
+
+        if r.remaining() > 0 {
+            return Err(DecodeError::TrailingBytes(r.remaining()));
+        }
+
+        Ok(Transaction { version, inputs, outputs, locktime })
+    }
+
+    pub fn from_hex(tx_hex: &str) -> Result<Self, DecodeError> {
+        let bytes = hex::decode(tx_hex.trim()).map_err(|e| DecodeError::Hex(e.to_string()))?;
+        Self::from_bytes(&bytes)
+    }
+}
+
+// ============================================================================
+// SCRIPT CLASSIFICATION
+// ============================================================================
+
+const OP_0: u8 = 0x00;
+const OP_PUSHDATA1: u8 = 0x4c;
+const OP_PUSHDATA2: u8 = 0x4d;
+const OP_PUSHDATA4: u8 = 0x4e;
+const OP_1: u8 = 0x51;
+const OP_16: u8 = 0x60;
+const OP_RETURN: u8 = 0x6a;
+const OP_CHECKSIG: u8 = 0xac;
+const OP_CHECKMULTISIG: u8 = 0xae;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ScriptType {
+    P2pkh,
+    P2sh,
+    P2pk,
+    Multisig { required: u8, total: u8 },
+    NullData,
+    Nonstandard,
+}
+
+#[derive(Debug, PartialEq)]
+enum ScriptOp<'a> {
+    Push(&'a [u8]),
+    Op(u8),
+}
+
+/// Split a script into opcodes and pushes; `None` if a push runs off the end
+fn parse_script(script: &[u8]) -> Option<Vec<ScriptOp<'_>>> {
+    let mut r = Reader::new(script);
+    let mut ops = Vec::new();
+
+    while r.remaining() > 0 {
+        let opcode = r.u8().ok()?;
+        let len = match opcode {
+            0x01..=0x4b => opcode as usize,
+            OP_PUSHDATA1 => r.u8().ok()? as usize,
+            OP_PUSHDATA2 => r.u16().ok()? as usize,
+            OP_PUSHDATA4 => r.u32().ok()? as usize,
+            _ => {
+                ops.push(ScriptOp::Op(opcode));
+                continue;
+            }
+        };
+        ops.push(ScriptOp::Push(r.take(len).ok()?));
+    }
+
+    Some(ops)
+}
+
+fn small_int(opcode: u8) -> Option<u8> {
+    (OP_1..=OP_16).contains(&opcode).then(|| opcode - OP_1 + 1)
+}
+
+pub fn classify_script(script: &[u8]) -> ScriptType {
+    // Fixed templates first; these are checked byte-for-byte
+    if script.len() == 25
+        && script[..3] == [0x76, 0xa9, 20]
+        && script[23..] == [0x88, OP_CHECKSIG]
+    {
+        return ScriptType::P2pkh;
+    }
+    if script.len() == 23 && script[..2] == [0xa9, 20] && script[22] == 0x87 {
+        return ScriptType::P2sh;
+    }
+    if script.first() == Some(&OP_RETURN) || script.starts_with(&[OP_0, OP_RETURN]) {
+        return ScriptType::NullData;
+    }
+
+    let ops = match parse_script(script) {
+        Some(ops) => ops,
+        None => return ScriptType::Nonstandard,
+    };
+
+    match ops.as_slice() {
+        [ScriptOp::Push(key), ScriptOp::Op(OP_CHECKSIG)] if key.len() == 33 || key.len() == 65 => {
+            ScriptType::P2pk
+        }
+        _ => match multisig_keys(script) {
+            Some((required, keys)) => ScriptType::Multisig { required, total: keys.len() as u8 },
+            None => ScriptType::Nonstandard,
+        },
+    }
+}
+
+/// Threshold and public keys of a bare `OP_m <keys> OP_n OP_CHECKMULTISIG` script
+pub fn multisig_keys(script: &[u8]) -> Option<(u8, Vec<Vec<u8>>)> {
+    let ops = parse_script(script)?;
+    match ops.as_slice() {
+        [ScriptOp::Op(m), keys @ .., ScriptOp::Op(n), ScriptOp::Op(OP_CHECKMULTISIG)] => {
+            let (m, n) = (small_int(*m)?, small_int(*n)?);
+            if m > n || keys.len() != n as usize {
+                return None;
+            }
+            keys.iter()
+                .map(|k| match k {
+                    ScriptOp::Push(k) if k.len() == 33 || k.len() == 65 => Some(k.to_vec()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()
+                .map(|keys| (m, keys))
+        }
+        _ => None,
+    }
+}
+
+/// Address paid by an output script, for the address-bearing script types
+pub fn script_address(script: &[u8], network: Network) -> Option<String> {
+    match classify_script(script) {
+        ScriptType::P2pkh => Some(AddressUtils::encode_address(&script[3..23], network, AddressKind::P2pkh)),
+        ScriptType::P2sh => Some(AddressUtils::encode_address(&script[2..22], network, AddressKind::P2sh)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptBuilder;
+
+    #[test]
+    fn test_varint_roundtrip() {
+        for n in [0u64, 0xfc, 0xfd, 0xffff, 0x1_0000, 0xffff_ffff, 0x1_0000_0000] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, n);
+            assert_eq!(bytes.len(), varint_len(n));
+            assert_eq!(Reader::new(&bytes).varint().unwrap(), n);
+        }
+    }
+
+    #[test]
+    fn test_transaction_roundtrip() {
+        let mut tx = Transaction::new();
+        tx.add_input("ab".repeat(32), 3, 10_000);
+        tx.inputs[0].script_sig = vec![0x51; 300];
+        tx.add_output(9_000, ScriptBuilder::p2pkh(&[7u8; 20]));
+        tx.add_output(0, ScriptBuilder::data_output(&[b"hello".to_vec()], false));
+        tx.locktime = 800_000;
+
+        let decoded = Transaction::from_hex(&tx.to_hex()).unwrap();
+        assert_eq!(decoded.calculate_txid(), tx.calculate_txid());
+        assert_eq!(decoded.inputs[0].txid, "ab".repeat(32));
+        assert_eq!(decoded.inputs[0].script_sig.len(), 300);
+        assert_eq!(decoded.outputs.len(), 2);
+        assert_eq!(decoded.locktime, 800_000);
+    }
+
+    #[test]
+    fn test_rejects_truncated_and_trailing() {
+        let hex = Transaction::new().to_hex();
+        assert!(Transaction::from_hex(&hex[..hex.len() - 2]).is_err());
+        assert!(matches!(
+            Transaction::from_hex(&format!("{}00", hex)),
+            Err(DecodeError::TrailingBytes(1))
+        ));
+        assert!(matches!(
+            Transaction::from_hex("01000000feffffffff"),
+            Err(DecodeError::ImplausibleCount { what: "input", .. })
+        ));
+    }
+
+    #[test]
+    fn test_classify_scripts() {
+        assert_eq!(classify_script(&ScriptBuilder::p2pkh(&[1u8; 20])), ScriptType::P2pkh);
+        assert_eq!(classify_script(&ScriptBuilder::p2sh(&[1u8; 20])), ScriptType::P2sh);
+        assert_eq!(classify_script(&ScriptBuilder::data_output(&[b"data".to_vec()], false)), ScriptType::NullData);
+        assert_eq!(
+            classify_script(&ScriptBuilder::multisig(2, &[vec![2u8; 33], vec![3u8; 33]])),
+            ScriptType::Multisig { required: 2, total: 2 }
+        );
+        assert_eq!(classify_script(&[0x51, 0x52]), ScriptType::Nonstandard);
+    }
+
+    #[test]
+    fn test_script_address_uses_network_prefix() {
+        let script = ScriptBuilder::p2pkh(&[0u8; 20]);
+        assert!(script_address(&script, Network::Mainnet).unwrap().starts_with('1'));
+        assert!(matches!(script_address(&script, Network::Testnet).unwrap().chars().next(), Some('m') | Some('n')));
+        assert_eq!(script_address(&ScriptBuilder::data_output(&[b"x".to_vec()], false), Network::Mainnet), None);
+    }
+}
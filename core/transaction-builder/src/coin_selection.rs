@@ -0,0 +1,390 @@
+// core/transaction-builder/src/coin_selection.rs
+// Coin selection strategies, scored by the waste each selection produces
+
+use serde::{Deserialize, Serialize};
+
+use crate::size::{self, overhead_size, InputScript, P2PKH_INPUT_SIZE, P2PKH_OUTPUT_SIZE};
+use crate::{Transaction, UtxoInput};
+
+/// Version, locktime and the input/output count varints for a small payment
+const TX_OVERHEAD_SIZE: usize = overhead_size(1, 1);
+/// Search budget for branch-and-bound before it gives up
+const BNB_MAX_TRIES: usize = 100_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    Largest,
+    Smallest,
+    OldestFirst,
+    BranchAndBound,
+    Knapsack,
+}
+
+impl Strategy {
+    pub const ALL: [Strategy; 5] = [
+        Strategy::Largest,
+        Strategy::Smallest,
+        Strategy::OldestFirst,
+        Strategy::BranchAndBound,
+        Strategy::Knapsack,
+    ];
+
+    pub fn parse(name: &str) -> Result<Self, String> {
+        match name {
+            "largest" => Ok(Strategy::Largest),
+            "smallest" => Ok(Strategy::Smallest),
+            "oldest" | "oldest_first" => Ok(Strategy::OldestFirst),
+            "bnb" | "branch_and_bound" => Ok(Strategy::BranchAndBound),
+            "knapsack" => Ok(Strategy::Knapsack),
+            other => Err(format!("Unknown selection strategy: {}", other)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SelectionParams {
+    /// Amount paid to the recipient output
+    pub target: u64,
+    pub fee_per_byte: u64,
+    /// Expected future fee rate, used to price spending change later
+    pub long_term_fee_per_byte: u64,
+    /// Change at or below this is added to the fee instead of creating an output
+    pub dust_threshold: u64,
+}
+
+impl SelectionParams {
+    fn input_fee(&self) -> u64 {
+        P2PKH_INPUT_SIZE as u64 * self.fee_per_byte
+    }
+
+    /// Value an input contributes once its own fee is paid
+    fn effective_value(&self, utxo: &UtxoInput) -> u64 {
+        utxo.satoshis.saturating_sub(self.input_fee())
+    }
+
+    /// Effective value needed for a changeless transaction
+    fn changeless_target(&self) -> u64 {
+        self.target + (TX_OVERHEAD_SIZE + P2PKH_OUTPUT_SIZE) as u64 * self.fee_per_byte
+    }
+
+    /// Creating a change output now plus spending it later
+    fn cost_of_change(&self) -> u64 {
+        P2PKH_OUTPUT_SIZE as u64 * self.fee_per_byte + P2PKH_INPUT_SIZE as u64 * self.long_term_fee_per_byte
+    }
+
+    /// What each input costs now relative to spending it at the long-term rate
+    fn input_waste(&self) -> i64 {
+        P2PKH_INPUT_SIZE as i64 * (self.fee_per_byte as i64 - self.long_term_fee_per_byte as i64)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Selection {
+    pub utxos: Vec<UtxoInput>,
+    pub total_value: u64,
+    pub fee: u64,
+    /// Zero when the selection is changeless
+    pub change: u64,
+    /// Timing cost of the inputs plus either the cost of change or the excess given to fees
+    pub waste: i64,
+}
+
+/// Price a set of inputs, adding change when it clears the dust threshold
+fn evaluate(utxos: Vec<UtxoInput>, params: &SelectionParams, allow_change: bool) -> Option<Selection> {
+    let total_value: u64 = utxos.iter().map(|u| u.satoshis).sum();
+    let size_without_change = overhead_size(utxos.len(), 1) + utxos.len() * P2PKH_INPUT_SIZE + P2PKH_OUTPUT_SIZE;
+    let fee_without_change = size_without_change as u64 * params.fee_per_byte;
+    if total_value < params.target + fee_without_change {
+        return None;
+    }
+
+    let input_waste = utxos.len() as i64 * params.input_waste();
+    let fee_with_change = fee_without_change + P2PKH_OUTPUT_SIZE as u64 * params.fee_per_byte;
+    let change = total_value.saturating_sub(params.target + fee_with_change);
+
+    if allow_change && change > params.dust_threshold {
+        return Some(Selection {
+            utxos,
+            total_value,
+            fee: fee_with_change,
+            change,
+            waste: input_waste + params.cost_of_change() as i64,
+        });
+    }
+
+    let excess = total_value - params.target - fee_without_change;
+    Some(Selection {
+        utxos,
+        total_value,
+        fee: fee_without_change + excess,
+        change: 0,
+        waste: input_waste + excess as i64,
+    })
+}
+
+/// Take UTXOs in the given order until the target and fee are covered
+fn accumulate(ordered: Vec<UtxoInput>, params: &SelectionParams) -> Option<Selection> {
+    let mut selected = Vec::new();
+    for utxo in ordered {
+        selected.push(utxo);
+        if let Some(selection) = evaluate(selected.clone(), params, true) {
+            return Some(selection);
+        }
+    }
+    None
+}
+
+// ============================================================================
+// BRANCH AND BOUND
+// ============================================================================
+
+/// Depth-first search for a changeless input set whose effective value lands
+/// between the target and target + cost of change, keeping the least wasteful
+struct BranchAndBound<'a> {
+    values: &'a [u64],
+    target: u64,
+    upper_bound: u64,
+    input_waste: i64,
+    tries: usize,
+    best: Option<(i64, Vec<usize>)>,
+}
+
+impl BranchAndBound<'_> {
+    fn search(&mut self, index: usize, selected: &mut Vec<usize>, sum: u64, remaining: u64) {
+        if self.tries == 0 || sum > self.upper_bound {
+            return;
+        }
+        self.tries -= 1;
+
+        if sum >= self.target {
+            let waste = selected.len() as i64 * self.input_waste + (sum - self.target) as i64;
+            if self.best.as_ref().map(|(best, _)| waste < *best).unwrap_or(true) {
+                self.best = Some((waste, selected.clone()));
+            }
+            return;
+        }
+        if index == self.values.len() || sum + remaining < self.target {
+            return;
+        }
+
+        let value = self.values[index];
+        selected.push(index);
+        self.search(index + 1, selected, sum + value, remaining - value);
+        selected.pop();
+        self.search(index + 1, selected, sum, remaining - value);
+    }
+}
+
+fn branch_and_bound(utxos: &[UtxoInput], params: &SelectionParams) -> Option<Selection> {
+    let mut candidates: Vec<&UtxoInput> = utxos.iter().filter(|u| params.effective_value(u) > 0).collect();
+    candidates.sort_by_key(|u| std::cmp::Reverse(params.effective_value(u)));
+    let values: Vec<u64> = candidates.iter().map(|u| params.effective_value(u)).collect();
+
+    let target = params.changeless_target();
+    let mut search = BranchAndBound {
+        values: &values,
+        target,
+        upper_bound: target + params.cost_of_change(),
+        input_waste: params.input_waste(),
+        tries: BNB_MAX_TRIES,
+        best: None,
+    };
+    search.search(0, &mut Vec::new(), 0, values.iter().sum());
+
+    let (_, indexes) = search.best?;
+    let selected = indexes.into_iter().map(|i| candidates[i].clone()).collect();
+    evaluate(selected, params, false)
+}
+
+// ============================================================================
+// KNAPSACK
+// ============================================================================
+
+/// Deterministic take on the classic knapsack selector: prefer an exact match,
+/// otherwise the smaller of the single lowest larger UTXO and the best subset
+/// of smaller UTXOs that leaves a non-dust change output
+fn knapsack(utxos: &[UtxoInput], params: &SelectionParams) -> Option<Selection> {
+    let exact = params.changeless_target();
+    let with_change = exact + P2PKH_OUTPUT_SIZE as u64 * params.fee_per_byte + params.dust_threshold + 1;
+
+    if let Some(utxo) = utxos.iter().find(|u| params.effective_value(u) == exact) {
+        return evaluate(vec![utxo.clone()], params, true);
+    }
+
+    let mut lowers: Vec<&UtxoInput> = utxos
+        .iter()
+        .filter(|u| params.effective_value(u) > 0 && params.effective_value(u) < with_change)
+        .collect();
+    lowers.sort_by_key(|u| std::cmp::Reverse(params.effective_value(u)));
+    let lowest_larger = utxos
+        .iter()
+        .filter(|u| params.effective_value(u) >= with_change)
+        .min_by_key(|u| u.satoshis);
+
+    let sum_lowers: u64 = lowers.iter().map(|u| params.effective_value(u)).sum();
+    if sum_lowers == exact || (sum_lowers > exact && sum_lowers < with_change && lowest_larger.is_none()) {
+        return evaluate(lowers.into_iter().cloned().collect(), params, true);
+    }
+    if sum_lowers < with_change {
+        return lowest_larger.and_then(|u| evaluate(vec![u.clone()], params, true));
+    }
+
+    // One greedy pass per starting point, keeping the subset closest to the target
+    let mut best: Option<(u64, Vec<&UtxoInput>)> = None;
+    for start in 0..lowers.len() {
+        let mut subset = Vec::new();
+        let mut sum = 0;
+        for utxo in &lowers[start..] {
+            if sum >= with_change {
+                break;
+            }
+            sum += params.effective_value(utxo);
+            subset.push(*utxo);
+        }
+        if sum >= with_change && best.as_ref().map(|(b, _)| sum < *b).unwrap_or(true) {
+            best = Some((sum, subset));
+        }
+    }
+
+    let chosen = match (best, lowest_larger) {
+        (Some((sum, _)), Some(larger)) if params.effective_value(larger) <= sum => vec![larger],
+        (Some((_, subset)), _) => subset,
+        (None, Some(larger)) => vec![larger],
+        (None, None) => return None,
+    };
+    evaluate(chosen.into_iter().cloned().collect(), params, true)
+}
+
+// ============================================================================
+// ENTRY POINT
+// ============================================================================
+
+pub fn select(utxos: &[UtxoInput], params: &SelectionParams, strategy: Strategy) -> Option<Selection> {
+    match strategy {
+        Strategy::Largest => {
+            let mut ordered = utxos.to_vec();
+            ordered.sort_by_key(|u| std::cmp::Reverse(u.satoshis));
+            accumulate(ordered, params)
+        }
+        Strategy::Smallest => {
+            let mut ordered = utxos.to_vec();
+            ordered.sort_by_key(|u| u.satoshis);
+            accumulate(ordered, params)
+        }
+        Strategy::OldestFirst => {
+            // Most confirmations first; unconfirmed or unknown age last
+            let mut ordered = utxos.to_vec();
+            ordered.sort_by_key(|u| (std::cmp::Reverse(u.confirmations.unwrap_or(0)), std::cmp::Reverse(u.satoshis)));
+            accumulate(ordered, params)
+        }
+        Strategy::BranchAndBound => branch_and_bound(utxos, params),
+        Strategy::Knapsack => knapsack(utxos, params),
+    }
+}
+
+/// Add P2PKH `utxos` to `tx`, in the caller's order, until its outputs and fee are
+/// covered; change above `dust_threshold` goes to `change_script`
+pub fn fund_in_order(
+    mut tx: Transaction,
+    utxos: &[UtxoInput],
+    change_script: &[u8],
+    fee_per_byte: u64,
+    dust_threshold: u64,
+) -> Result<Transaction, String> {
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    let fee_for = |tx: &Transaction, with_change: bool| {
+        let mut sized = tx.clone();
+        if with_change {
+            sized.add_output(0, change_script.to_vec());
+        }
+        size::estimate_size(&sized, &size::input_scripts(&sized, InputScript::P2PKH)) as u64 * fee_per_byte
+    };
+
+    let mut total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    for utxo in utxos {
+        if !tx.inputs.is_empty() && total_in >= total_out + fee_for(&tx, false) {
+            break;
+        }
+        tx.add_input(utxo.txid.clone(), utxo.vout, utxo.satoshis);
+        total_in += utxo.satoshis;
+    }
+    let needed = total_out + fee_for(&tx, false);
+    if tx.inputs.is_empty() || total_in < needed {
+        return Err(format!("Insufficient funds: need {} sats, have {} sats", needed, total_in));
+    }
+
+    let change = total_in.saturating_sub(total_out + fee_for(&tx, true));
+    if change > dust_threshold {
+        tx.add_output(change, change_script.to_vec());
+    }
+    Ok(tx)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utxo(satoshis: u64, confirmations: u32) -> UtxoInput {
+        UtxoInput { txid: format!("{:064x}", satoshis), vout: 0, satoshis, confirmations: Some(confirmations) }
+    }
+
+    fn params(target: u64) -> SelectionParams {
+        SelectionParams { target, fee_per_byte: 1, long_term_fee_per_byte: 1, dust_threshold: 546 }
+    }
+
+    #[test]
+    fn test_branch_and_bound_finds_changeless_match() {
+        let p = params(50_000);
+        // Together with the 30_000 coin this pays target and fee with nothing left over
+        let exact_second = p.changeless_target() - (30_000 - P2PKH_INPUT_SIZE as u64) + P2PKH_INPUT_SIZE as u64;
+        let utxos = vec![utxo(100_000, 1), utxo(30_000, 1), utxo(exact_second, 1), utxo(5_000, 1)];
+
+        let selection = select(&utxos, &p, Strategy::BranchAndBound).unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.utxos.len(), 2);
+        assert_eq!(selection.waste, 0);
+
+        // Largest-first uses the big coin and produces change
+        let largest = select(&utxos, &p, Strategy::Largest).unwrap();
+        assert!(largest.change > 0);
+        assert!(largest.waste > selection.waste);
+    }
+
+    #[test]
+    fn test_branch_and_bound_gives_up_without_match() {
+        let utxos = vec![utxo(1_000_000, 1)];
+        assert!(select(&utxos, &params(50_000), Strategy::BranchAndBound).is_none());
+        assert!(select(&utxos, &params(50_000), Strategy::Knapsack).is_some());
+    }
+
+    #[test]
+    fn test_oldest_first_prefers_confirmations() {
+        let utxos = vec![utxo(80_000, 2), utxo(60_000, 500), utxo(70_000, 10)];
+        let selection = select(&utxos, &params(50_000), Strategy::OldestFirst).unwrap();
+        assert_eq!(selection.utxos[0].satoshis, 60_000);
+        assert_eq!(selection.utxos.len(), 1);
+    }
+
+    #[test]
+    fn test_knapsack_prefers_closest_subset() {
+        let utxos = vec![utxo(1_000_000, 1), utxo(30_000, 1), utxo(25_000, 1), utxo(4_000, 1)];
+        let selection = select(&utxos, &params(50_000), Strategy::Knapsack).unwrap();
+        let mut values: Vec<u64> = selection.utxos.iter().map(|u| u.satoshis).collect();
+        values.sort();
+        assert_eq!(values, vec![25_000, 30_000]);
+        assert!(selection.change > 0);
+    }
+
+    #[test]
+    fn test_dust_change_goes_to_fee() {
+        let p = params(50_000);
+        let needed = 50_000 + (10 + P2PKH_INPUT_SIZE + P2PKH_OUTPUT_SIZE) as u64;
+        let selection = select(&[utxo(needed + 500, 1)], &p, Strategy::Largest).unwrap();
+        assert_eq!(selection.change, 0);
+        assert_eq!(selection.fee, needed + 500 - 50_000);
+        assert_eq!(selection.waste, 500);
+
+        assert!(select(&[utxo(needed - 1, 1)], &p, Strategy::Smallest).is_none());
+    }
+}
@@ -0,0 +1,438 @@
+// core/transaction-builder/src/consolidate.rs
+// Dust consolidation: sweep small P2PKH UTXOs from hot-wallet addresses into one output at a low fee rate
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::JwtManager;
+use serde::{Deserialize, Serialize};
+
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::reservations::{self, Reservation, ReservationError};
+use crate::signing::{self, KeyStore, SignInputRequest};
+use crate::size::{self, InputScript, P2PKH_INPUT_SIZE, P2PKH_OUTPUT_SIZE, P2PKH_SCRIPT_SIZE};
+use crate::{AddressUtils, AppState, BuildTransactionResponse, ServiceError, Transaction, UtxoInput};
+
+/// Default cap on inputs swept into one transaction
+pub const DEFAULT_MAX_INPUTS_PER_TX: usize = 500;
+/// Default cap on transactions built per call; the rest is left for a later run
+pub const DEFAULT_MAX_TRANSACTIONS: usize = 10;
+pub const MAX_SOURCES: usize = 100;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ConsolidationSource {
+    pub address: String,
+    pub utxos: Vec<UtxoInput>,
+    /// Stored key for `address`; without it this source's inputs are left unsigned
+    pub key_ref: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConsolidateRequest {
+    pub sources: Vec<ConsolidationSource>,
+    pub to_address: String,
+    /// Only sweep UTXOs worth at most this much; larger ones are left alone
+    pub max_utxo_value: Option<u64>,
+    pub max_inputs_per_tx: Option<usize>,
+    pub max_transactions: Option<usize>,
+    /// Defaults to LONG_TERM_FEE_PER_BYTE rather than the miners' current rate
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// Spending it would cost at least its value at this fee rate
+    Uneconomical,
+    AboveMaxValue,
+    /// A leftover group too small to pay for its own transaction
+    BelowDustAfterFee,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedUtxo {
+    pub txid: String,
+    pub vout: u32,
+    pub satoshis: u64,
+    pub reason: SkipReason,
+}
+
+#[derive(Serialize)]
+pub struct ConsolidationTransaction {
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    pub swept_satoshis: u64,
+    pub signed: bool,
+}
+
+/// When to run consolidation: sweeping pays off most while the network rate is at or below our target
+#[derive(Debug, Clone, Serialize)]
+pub struct ScheduleHint {
+    pub run_now: bool,
+    pub reason: String,
+    pub fee_per_byte: u64,
+    pub network_fee_per_byte: u64,
+    /// Sweepable UTXOs beyond max_transactions, for the next run
+    pub remaining_utxos: usize,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConsolidationSummary {
+    pub transaction_count: usize,
+    pub input_count: usize,
+    pub total_swept: u64,
+    pub total_fee: u64,
+    pub total_output: u64,
+    pub skipped_count: usize,
+}
+
+#[derive(Serialize)]
+pub struct ConsolidateResponse {
+    pub transactions: Vec<ConsolidationTransaction>,
+    pub skipped: Vec<SkippedUtxo>,
+    pub summary: ConsolidationSummary,
+    pub schedule: ScheduleHint,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reservation: Option<Reservation>,
+}
+
+pub struct ConsolidationLimits {
+    pub max_inputs_per_tx: usize,
+    pub max_transactions: usize,
+    pub max_tx_size_bytes: usize,
+    pub max_utxo_value: Option<u64>,
+    pub dust_threshold: u64,
+}
+
+impl ConsolidationLimits {
+    /// Inputs per transaction, also bounded by the size limit
+    fn inputs_per_tx(&self) -> usize {
+        let room = self.max_tx_size_bytes.saturating_sub(size::overhead_size(self.max_inputs_per_tx, 1) + P2PKH_OUTPUT_SIZE);
+        self.max_inputs_per_tx.min(room / P2PKH_INPUT_SIZE).max(1)
+    }
+}
+
+/// A UTXO tagged with the index of the source it came from
+#[derive(Debug, Clone)]
+pub struct SourcedUtxo {
+    pub source: usize,
+    pub utxo: UtxoInput,
+}
+
+#[derive(Debug, Default)]
+pub struct ConsolidationPlan {
+    /// Each transaction with the source of every input, in input order
+    pub transactions: Vec<(Transaction, Vec<usize>)>,
+    pub skipped: Vec<SkippedUtxo>,
+    pub remaining: usize,
+}
+
+// ============================================================================
+// PLANNING
+// ============================================================================
+
+fn skip(utxo: &UtxoInput, reason: SkipReason) -> SkippedUtxo {
+    SkippedUtxo { txid: utxo.txid.clone(), vout: utxo.vout, satoshis: utxo.satoshis, reason }
+}
+
+/// Group sweepable UTXOs, smallest first, into single-output transactions paying `output_script`
+pub fn plan_consolidation(
+    utxos: Vec<SourcedUtxo>,
+    output_script: &[u8],
+    fee_per_byte: u64,
+    limits: &ConsolidationLimits,
+) -> ConsolidationPlan {
+    let mut plan = ConsolidationPlan::default();
+    let spend_cost = P2PKH_INPUT_SIZE as u64 * fee_per_byte;
+
+    let mut sweepable: Vec<SourcedUtxo> = utxos
+        .into_iter()
+        .filter(|s| {
+            let reason = if limits.max_utxo_value.is_some_and(|max| s.utxo.satoshis > max) {
+                SkipReason::AboveMaxValue
+            } else if s.utxo.satoshis <= spend_cost {
+                SkipReason::Uneconomical
+            } else {
+                return true;
+            };
+            plan.skipped.push(skip(&s.utxo, reason));
+            false
+        })
+        .collect();
+    sweepable.sort_by_key(|s| s.utxo.satoshis);
+
+    let per_tx = limits.inputs_per_tx();
+    for (n, chunk) in sweepable.chunks(per_tx).enumerate() {
+        if n >= limits.max_transactions {
+            plan.remaining += chunk.len();
+            continue;
+        }
+
+        let mut tx = Transaction::new();
+        for s in chunk {
+            tx.add_input(s.utxo.txid.clone(), s.utxo.vout, s.utxo.satoshis);
+        }
+        tx.add_output(0, output_script.to_vec());
+
+        let total_in: u64 = chunk.iter().map(|s| s.utxo.satoshis).sum();
+        let fee = size::estimate_size(&tx, &size::input_scripts(&tx, InputScript::P2PKH)) as u64 * fee_per_byte;
+        let output = total_in.saturating_sub(fee);
+        if output <= limits.dust_threshold {
+            plan.skipped.extend(chunk.iter().map(|s| skip(&s.utxo, SkipReason::BelowDustAfterFee)));
+            continue;
+        }
+        tx.outputs[0].value = output;
+        plan.transactions.push((tx, chunk.iter().map(|s| s.source).collect()));
+    }
+
+    plan
+}
+
+pub fn schedule_hint(fee_per_byte: u64, network_fee_per_byte: u64, remaining_utxos: usize) -> ScheduleHint {
+    let run_now = network_fee_per_byte <= fee_per_byte;
+    let reason = if run_now {
+        format!("Network rate {} sat/byte is at or below the {} sat/byte target", network_fee_per_byte, fee_per_byte)
+    } else {
+        format!(
+            "Network rate {} sat/byte is above the {} sat/byte target; miners may be slow to accept, consider deferring",
+            network_fee_per_byte, fee_per_byte
+        )
+    };
+    ScheduleHint { run_now, reason, fee_per_byte, network_fee_per_byte, remaining_utxos }
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+pub async fn build_consolidation(
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    fees: web::Data<FeeOracle>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    req: web::Json<ConsolidateRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let mut req = req.into_inner();
+    let config = &data.config;
+    signing::require_key_access(&http_req, &jwt, &keys, req.sources.iter().filter_map(|s| s.key_ref.as_deref()))?;
+
+    if req.sources.is_empty() || req.sources.iter().all(|s| s.utxos.is_empty()) {
+        return Err(ServiceError::ValidationError("No UTXOs provided".to_string()));
+    }
+    if req.sources.len() > MAX_SOURCES {
+        return Err(ServiceError::ValidationError(format!("At most {} source addresses per request", MAX_SOURCES)));
+    }
+    let output_script = AddressUtils::script_for_address(&req.to_address, config.network)
+        .map_err(|e| ServiceError::ValidationError(format!("Invalid to_address: {}", e)))?;
+
+    for source in &req.sources {
+        let script = AddressUtils::script_for_address(&source.address, config.network)
+            .map_err(|e| ServiceError::ValidationError(format!("Invalid source address {}: {}", source.address, e)))?;
+        if script.len() != P2PKH_SCRIPT_SIZE {
+            return Err(ServiceError::ValidationError(format!("Source {} is not a P2PKH address", source.address)));
+        }
+        if let Some(key_ref) = &source.key_ref {
+            let key = keys
+                .get(key_ref)
+                .ok_or_else(|| ServiceError::ValidationError(format!("Unknown key reference: {}", key_ref)))?;
+            if key.pubkey_hash() != script[3..23] {
+                return Err(ServiceError::ValidationError(format!("Key {} does not own {}", key_ref, source.address)));
+            }
+        }
+    }
+
+    let fee_per_byte = match (req.fee_per_byte, req.fee_policy) {
+        (None, None) => config.long_term_fee_per_byte,
+        (explicit, policy) => fees.fee_per_byte(policy, explicit, FeeKind::Standard).await,
+    };
+    if fee_per_byte == 0 || fee_per_byte > 10000 {
+        return Err(ServiceError::ValidationError("Fee per byte must be between 1 and 10000".to_string()));
+    }
+    let limits = ConsolidationLimits {
+        max_inputs_per_tx: req.max_inputs_per_tx.unwrap_or(DEFAULT_MAX_INPUTS_PER_TX).max(1),
+        max_transactions: req.max_transactions.unwrap_or(DEFAULT_MAX_TRANSACTIONS).max(1),
+        max_tx_size_bytes: config.max_tx_size_bytes,
+        max_utxo_value: req.max_utxo_value,
+        dust_threshold: config.dust_threshold,
+    };
+
+    let mut utxos = Vec::new();
+    for (index, source) in req.sources.iter_mut().enumerate() {
+        let available = reservations::filter_available(&data.db, std::mem::take(&mut source.utxos))
+            .await
+            .map_err(ReservationError::from)?;
+        utxos.extend(available.into_iter().map(|utxo| SourcedUtxo { source: index, utxo }));
+    }
+
+    let plan = plan_consolidation(utxos, &output_script, fee_per_byte, &limits);
+    let network_fee_per_byte = fees.rates().await.standard.ceil_per_byte();
+    let schedule = schedule_hint(fee_per_byte, network_fee_per_byte, plan.remaining);
+
+    let mut summary = ConsolidationSummary { skipped_count: plan.skipped.len(), ..Default::default() };
+    let mut transactions = Vec::with_capacity(plan.transactions.len());
+    let mut all_inputs = Vec::new();
+    let mut built = Vec::with_capacity(plan.transactions.len());
+
+    for (mut tx, sources) in plan.transactions {
+        let inputs: Vec<SignInputRequest> = sources
+            .iter()
+            .enumerate()
+            .filter_map(|(index, &source)| {
+                req.sources[source].key_ref.as_ref().map(|key_ref| SignInputRequest {
+                    index,
+                    value: tx.inputs[index].value,
+                    wif: None,
+                    key_ref: Some(key_ref.clone()),
+                    script_pubkey: None,
+                    sighash: None,
+                })
+            })
+            .collect();
+        signing::sign_p2pkh_inputs(&mut tx, &inputs, &keys, config.network).map_err(ServiceError::BuildError)?;
+
+        let fee_breakdown = size::fee_breakdown(&tx, &size::input_scripts(&tx, InputScript::P2PKH), fee_per_byte);
+        let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+        let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+
+        summary.transaction_count += 1;
+        summary.input_count += tx.inputs.len();
+        summary.total_swept += total_in;
+        summary.total_fee += total_in - total_out;
+        summary.total_output += total_out;
+        all_inputs.extend(tx.inputs.iter().cloned());
+
+        transactions.push(ConsolidationTransaction {
+            transaction: BuildTransactionResponse {
+                tx_hex: tx.to_hex(),
+                txid: tx.calculate_txid(),
+                size_bytes: tx.calculate_size(),
+                fee_satoshis: total_in - total_out,
+                inputs: tx.inputs.clone(),
+                outputs: tx.outputs.clone(),
+                fee_breakdown,
+            },
+            swept_satoshis: total_in,
+            signed: inputs.len() == tx.inputs.len(),
+        });
+        built.push(tx);
+    }
+
+    let reservation = if all_inputs.is_empty() {
+        None
+    } else {
+        let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, config.reservation_ttl_secs);
+        Some(reservations::reserve(&data.db, &all_inputs, ttl, "build:consolidate").await?)
+    };
+    for tx in &built {
+        history::record(&data, &http_req, TxType::Consolidation, tx).await?;
+    }
+
+    tracing::info!(
+        "Built consolidation: {} input(s) in {} transaction(s), {} sats fee, {} skipped, {} remaining",
+        summary.input_count, summary.transaction_count, summary.total_fee, summary.skipped_count, schedule.remaining_utxos
+    );
+
+    Ok(HttpResponse::Ok().json(ConsolidateResponse {
+        transactions,
+        skipped: plan.skipped,
+        summary,
+        schedule,
+        reservation,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptBuilder;
+
+    fn utxos(values: &[u64]) -> Vec<SourcedUtxo> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(i, &satoshis)| SourcedUtxo {
+                source: i % 2,
+                utxo: UtxoInput { txid: format!("{:064x}", i), vout: 0, satoshis, confirmations: None },
+            })
+            .collect()
+    }
+
+    fn limits(max_inputs_per_tx: usize, max_transactions: usize) -> ConsolidationLimits {
+        ConsolidationLimits {
+            max_inputs_per_tx,
+            max_transactions,
+            max_tx_size_bytes: 1_000_000,
+            max_utxo_value: None,
+            dust_threshold: 546,
+        }
+    }
+
+    #[test]
+    fn test_sweeps_smallest_first_in_chunks() {
+        let script = ScriptBuilder::p2pkh(&[7; 20]);
+        let plan = plan_consolidation(utxos(&[5_000, 1_000, 3_000, 2_000, 4_000]), &script, 1, &limits(2, 10));
+
+        assert_eq!(plan.transactions.len(), 3);
+        let first = &plan.transactions[0].0;
+        assert_eq!(first.inputs.iter().map(|i| i.value).collect::<Vec<_>>(), vec![1_000, 2_000]);
+        assert_eq!(plan.transactions[0].1, vec![1, 1]);
+        assert_eq!(first.outputs.len(), 1);
+        assert_eq!(first.outputs[0].script_pubkey, script);
+
+        let fee = size::estimate_size(first, &size::input_scripts(first, InputScript::P2PKH)) as u64;
+        assert_eq!(first.outputs[0].value, 3_000 - fee);
+        assert!(plan.skipped.is_empty());
+    }
+
+    #[test]
+    fn test_skips_uneconomical_and_large() {
+        let mut limits = limits(10, 10);
+        limits.max_utxo_value = Some(10_000);
+        let script = ScriptBuilder::p2pkh(&[7; 20]);
+        let plan = plan_consolidation(utxos(&[100, 2_000, 50_000, 3_000]), &script, 1, &limits);
+
+        let reasons: Vec<_> = plan.skipped.iter().map(|s| (s.satoshis, s.reason)).collect();
+        assert_eq!(reasons, vec![(100, SkipReason::Uneconomical), (50_000, SkipReason::AboveMaxValue)]);
+        assert_eq!(plan.transactions.len(), 1);
+        assert_eq!(plan.transactions[0].0.inputs.len(), 2);
+    }
+
+    #[test]
+    fn test_leftovers_beyond_max_transactions() {
+        let script = ScriptBuilder::p2pkh(&[7; 20]);
+        let plan = plan_consolidation(utxos(&[2_000; 7]), &script, 1, &limits(3, 2));
+
+        assert_eq!(plan.transactions.len(), 2);
+        assert_eq!(plan.remaining, 1);
+    }
+
+    #[test]
+    fn test_group_below_dust_after_fee_is_skipped() {
+        let script = ScriptBuilder::p2pkh(&[7; 20]);
+        let plan = plan_consolidation(utxos(&[300, 320]), &script, 1, &limits(10, 10));
+
+        assert!(plan.transactions.is_empty());
+        assert!(plan.skipped.iter().all(|s| s.reason == SkipReason::BelowDustAfterFee));
+    }
+
+    #[test]
+    fn test_inputs_per_tx_respects_size_limit() {
+        let mut limits = limits(500, 10);
+        limits.max_tx_size_bytes = 10_000;
+        assert_eq!(limits.inputs_per_tx(), (10_000 - size::overhead_size(500, 1) - P2PKH_OUTPUT_SIZE) / P2PKH_INPUT_SIZE);
+    }
+
+    #[test]
+    fn test_schedule_hint() {
+        assert!(schedule_hint(10, 5, 0).run_now);
+        let hint = schedule_hint(1, 50, 3);
+        assert!(!hint.run_now);
+        assert_eq!(hint.remaining_utxos, 3);
+    }
+}
@@ -0,0 +1,245 @@
+// core/transaction-builder/src/data.rs
+// Data-carrier transactions: one OP_FALSE OP_RETURN output with several pushes
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::reservations::{self, ReservationError, ReservedBuildResponse};
+use crate::size::{self, InputScript};
+use crate::{
+    AddressUtils, AppState, BuildTransactionResponse, ScriptBuilder, ServiceError, Transaction,
+    UtxoInput,
+};
+
+const DUST_THRESHOLD: u64 = 546;
+
+// ============================================================================
+// REQUEST
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DataEncoding {
+    #[default]
+    Utf8,
+    Hex,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataPush {
+    #[serde(default)]
+    pub encoding: DataEncoding,
+    pub value: String,
+}
+
+impl DataPush {
+    pub fn bytes(&self) -> Result<Vec<u8>, String> {
+        match self.encoding {
+            DataEncoding::Utf8 => Ok(self.value.as_bytes().to_vec()),
+            DataEncoding::Hex => hex::decode(&self.value).map_err(|_| format!("Invalid hex data: {}", self.value)),
+        }
+    }
+}
+
+fn default_safe() -> bool {
+    true
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BuildDataRequest {
+    /// Pushed ahead of the data, e.g. an application namespace or protocol id
+    pub protocol_prefix: Option<DataPush>,
+    pub data: Vec<DataPush>,
+    pub utxos: Vec<UtxoInput>,
+    pub change_address: String,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    /// Prefix OP_RETURN with OP_FALSE so the output is provably unspendable
+    #[serde(default = "default_safe")]
+    pub safe: bool,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+// ============================================================================
+// BUILDER
+// ============================================================================
+
+/// Assemble the pushes and check them against the configured payload limit
+pub fn data_pushes(req: &BuildDataRequest, max_bytes: usize) -> Result<Vec<Vec<u8>>, String> {
+    if req.data.is_empty() {
+        return Err("At least one data push is required".to_string());
+    }
+
+    let mut pushes = Vec::with_capacity(req.data.len() + 1);
+    if let Some(prefix) = &req.protocol_prefix {
+        let prefix = prefix.bytes()?;
+        if prefix.is_empty() {
+            return Err("protocol_prefix must not be empty".to_string());
+        }
+        pushes.push(prefix);
+    }
+    for push in &req.data {
+        pushes.push(push.bytes()?);
+    }
+
+    let total: usize = pushes.iter().map(Vec::len).sum();
+    if total > max_bytes {
+        return Err(format!("Data payload is {} bytes; the limit is {}", total, max_bytes));
+    }
+    Ok(pushes)
+}
+
+/// Spend UTXOs in order until the data output's fee is covered; the rest is change
+pub fn build_data_transaction(
+    req: &BuildDataRequest,
+    max_bytes: usize,
+    fee_per_byte: u64,
+) -> Result<Transaction, String> {
+    let pushes = data_pushes(req, max_bytes)?;
+    let change_hash = AddressUtils::decode_address(&req.change_address)
+        .map_err(|e| format!("Invalid change_address: {}", e))?;
+    if req.utxos.is_empty() {
+        return Err("No UTXOs provided".to_string());
+    }
+
+    let mut tx = Transaction::new();
+    tx.add_output(0, ScriptBuilder::data_output(&pushes, req.safe));
+    let change_script = ScriptBuilder::p2pkh(&change_hash);
+
+    // Fee with the change output in place, assuming every input gets a P2PKH scriptSig
+    let fee_for = |tx: &Transaction| {
+        let mut with_change = tx.clone();
+        with_change.add_output(0, change_script.clone());
+        size::estimate_size(&with_change, &size::input_scripts(&with_change, InputScript::P2PKH)) as u64 * fee_per_byte
+    };
+
+    let mut total_input = 0u64;
+    for utxo in &req.utxos {
+        tx.add_input(utxo.txid.clone(), utxo.vout, utxo.satoshis);
+        total_input += utxo.satoshis;
+        if total_input >= fee_for(&tx) {
+            break;
+        }
+    }
+
+    let fee = fee_for(&tx);
+    if total_input < fee {
+        return Err(format!("Insufficient funds: need {} sats, have {} sats", fee, total_input));
+    }
+
+    let change = total_input - fee;
+    if change > DUST_THRESHOLD {
+        tx.add_output(change, change_script);
+    }
+    Ok(tx)
+}
+
+// ============================================================================
+// HANDLER
+// ============================================================================
+
+pub async fn build_data(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    req: web::Json<BuildDataRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Data).await;
+    if fee_per_byte == 0 || fee_per_byte > 10000 {
+        return Err(ServiceError::ValidationError("Fee per byte must be between 1 and 10000".to_string()));
+    }
+
+    let mut req = req.into_inner();
+    req.utxos = reservations::filter_available(&data.db, req.utxos)
+        .await
+        .map_err(ReservationError::from)?;
+
+    let tx = build_data_transaction(&req, data.config.max_data_carrier_bytes, fee_per_byte)
+        .map_err(|e| {
+            tracing::error!("Failed to build data transaction: {}", e);
+            ServiceError::BuildError(e)
+        })?;
+
+    let txid = tx.calculate_txid();
+    let fee_breakdown = size::fee_breakdown(&tx, &size::input_scripts(&tx, InputScript::P2PKH), fee_per_byte);
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    tracing::info!("Built data transaction: {} ({} byte data output)", txid, tx.outputs[0].script_pubkey.len());
+
+    let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, data.config.reservation_ttl_secs);
+    let reservation = reservations::reserve(&data.db, &tx.inputs, ttl, "build:data").await?;
+    history::record(&data, &http_req, TxType::Data, &tx).await?;
+
+    Ok(HttpResponse::Ok().json(ReservedBuildResponse {
+        transaction: BuildTransactionResponse {
+            tx_hex: tx.to_hex(),
+            txid,
+            size_bytes: tx.calculate_size(),
+            fee_satoshis: total_in - total_out,
+            inputs: tx.inputs.clone(),
+            outputs: tx.outputs.clone(),
+            fee_breakdown,
+        },
+        reservation,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{classify_script, ScriptType};
+
+    const CHANGE_ADDRESS: &str = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+
+    fn utf8(value: &str) -> DataPush {
+        DataPush { encoding: DataEncoding::Utf8, value: value.to_string() }
+    }
+
+    fn request(data: Vec<DataPush>, satoshis: u64) -> BuildDataRequest {
+        BuildDataRequest {
+            protocol_prefix: Some(utf8("bsvbank")),
+            data,
+            utxos: vec![UtxoInput { txid: "11".repeat(32), vout: 0, satoshis, confirmations: None }],
+            change_address: CHANGE_ADDRESS.to_string(),
+            fee_per_byte: None,
+            fee_policy: None,
+            safe: true,
+            reservation_ttl_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_pushes_include_prefix_and_decode_hex() {
+        let req = request(vec![utf8("hello"), DataPush { encoding: DataEncoding::Hex, value: "beef".into() }], 0);
+        let pushes = data_pushes(&req, 100).unwrap();
+        assert_eq!(pushes, vec![b"bsvbank".to_vec(), b"hello".to_vec(), vec![0xbe, 0xef]]);
+
+        assert!(data_pushes(&req, 10).is_err());
+        assert!(data_pushes(&request(vec![], 0), 100).is_err());
+    }
+
+    #[test]
+    fn test_large_payload_uses_pushdata2() {
+        let payload = "x".repeat(300);
+        let tx = build_data_transaction(&request(vec![utf8(&payload)], 100_000), 1000, 1).unwrap();
+
+        let script = &tx.outputs[0].script_pubkey;
+        assert_eq!(&script[..2], &[0x00, 0x6a]);
+        assert_eq!(script[2], 7); // "bsvbank"
+        assert_eq!(&script[10..13], &[0x4d, 0x2c, 0x01]); // OP_PUSHDATA2 300
+        assert_eq!(script.len(), 13 + 300);
+        assert_eq!(classify_script(script), ScriptType::NullData);
+    }
+
+    #[test]
+    fn test_change_and_insufficient_funds() {
+        let tx = build_data_transaction(&request(vec![utf8("hello")], 10_000), 1000, 1).unwrap();
+        assert_eq!(tx.outputs.len(), 2);
+        let fee = 10_000 - tx.outputs[1].value;
+        assert_eq!(fee as usize, size::estimate_size(&tx, &[InputScript::P2PKH]));
+
+        assert!(build_data_transaction(&request(vec![utf8("hello")], 100), 1000, 1).is_err());
+    }
+}
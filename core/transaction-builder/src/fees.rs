@@ -0,0 +1,367 @@
+// core/transaction-builder/src/fees.rs
+// Fee rates quoted by miners over ARC and mAPI, cached and resolved per request
+
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+// ============================================================================
+// RATES AND POLICIES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct FeeRate {
+    pub satoshis: u64,
+    pub bytes: u64,
+}
+
+impl FeeRate {
+    pub fn per_byte(per_byte: u64) -> Self {
+        Self { satoshis: per_byte, bytes: 1 }
+    }
+
+    /// Whole satoshis per byte, rounded up; the builders work in integer rates
+    pub fn ceil_per_byte(&self) -> u64 {
+        if self.bytes == 0 {
+            return self.satoshis;
+        }
+        self.satoshis.div_ceil(self.bytes).max(1)
+    }
+
+    fn cmp_rate(&self, other: &FeeRate) -> std::cmp::Ordering {
+        // a/b vs c/d without division
+        (self.satoshis as u128 * other.bytes as u128).cmp(&(other.satoshis as u128 * self.bytes as u128))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FeeKind {
+    Standard,
+    Data,
+}
+
+/// `"standard"`, `"data"` or a custom `{ "satoshis": n, "bytes": m }` rate. Build requests take
+/// it as an optional `fee_policy`: without one they pay the standard rate (data builds the data
+/// rate), and an explicit `fee_per_byte` overrides it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FeePolicy {
+    Named(FeeKind),
+    Custom(FeeRate),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeQuote {
+    pub source: String,
+    pub standard: FeeRate,
+    pub data: FeeRate,
+    pub fetched_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// SOURCES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SourceKind {
+    Arc,
+    Mapi,
+}
+
+#[derive(Debug, Clone)]
+pub struct FeeSource {
+    pub name: String,
+    pub kind: SourceKind,
+    pub url: String,
+    pub api_key: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ArcPolicyResponse {
+    policy: ArcPolicy,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ArcPolicy {
+    mining_fee: FeeRate,
+}
+
+/// mAPI wraps its signed payload as a JSON string
+#[derive(Deserialize)]
+struct MapiEnvelope {
+    payload: String,
+}
+
+#[derive(Deserialize)]
+struct MapiPayload {
+    fees: Vec<MapiFee>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MapiFee {
+    fee_type: String,
+    mining_fee: FeeRate,
+}
+
+fn parse_arc_policy(source: &str, body: &str) -> Result<FeeQuote, String> {
+    let response: ArcPolicyResponse = serde_json::from_str(body).map_err(|e| format!("Invalid ARC policy: {}", e))?;
+    // ARC quotes a single rate for all transactions
+    Ok(FeeQuote {
+        source: source.to_string(),
+        standard: response.policy.mining_fee,
+        data: response.policy.mining_fee,
+        fetched_at: Utc::now(),
+    })
+}
+
+fn parse_mapi_quote(source: &str, body: &str) -> Result<FeeQuote, String> {
+    let envelope: MapiEnvelope = serde_json::from_str(body).map_err(|e| format!("Invalid mAPI envelope: {}", e))?;
+    let payload: MapiPayload = serde_json::from_str(&envelope.payload)
+        .map_err(|e| format!("Invalid mAPI payload: {}", e))?;
+
+    let rate = |fee_type: &str| payload.fees.iter().find(|f| f.fee_type == fee_type).map(|f| f.mining_fee);
+    let standard = rate("standard").ok_or("mAPI quote has no standard fee")?;
+    Ok(FeeQuote {
+        source: source.to_string(),
+        standard,
+        data: rate("data").unwrap_or(standard),
+        fetched_at: Utc::now(),
+    })
+}
+
+impl FeeSource {
+    /// Parse `name=arc:https://...` or `name=mapi:https://...`
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let (name, rest) = spec.split_once('=').ok_or_else(|| format!("Invalid fee source: {}", spec))?;
+        let (kind, url) = rest.split_once(':').ok_or_else(|| format!("Invalid fee source: {}", spec))?;
+        let kind = match kind {
+            "arc" => SourceKind::Arc,
+            "mapi" => SourceKind::Mapi,
+            other => return Err(format!("Unknown fee source kind: {}", other)),
+        };
+        let name = name.trim().to_string();
+        let api_key = std::env::var(format!("FEE_SOURCE_API_KEY_{}", name.to_uppercase())).ok();
+        Ok(Self { name, kind, url: url.trim_end_matches('/').to_string(), api_key })
+    }
+
+    async fn fetch(&self, client: &reqwest::Client) -> Result<FeeQuote, String> {
+        let url = match self.kind {
+            SourceKind::Arc => format!("{}/v1/policy", self.url),
+            SourceKind::Mapi => format!("{}/mapi/feeQuote", self.url),
+        };
+
+        let mut request = client.get(&url);
+        if let Some(key) = &self.api_key {
+            request = request.bearer_auth(key);
+        }
+        let response = request.send().await.map_err(|e| format!("Request failed: {}", e))?;
+        if !response.status().is_success() {
+            return Err(format!("Status: {}", response.status()));
+        }
+        let body = response.text().await.map_err(|e| format!("Read failed: {}", e))?;
+
+        match self.kind {
+            SourceKind::Arc => parse_arc_policy(&self.name, &body),
+            SourceKind::Mapi => parse_mapi_quote(&self.name, &body),
+        }
+    }
+}
+
+// ============================================================================
+// ORACLE
+// ============================================================================
+
+/// The rate the service builds with: the highest quote across miners, so any
+/// of them will accept the transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeRates {
+    pub source: String,
+    pub standard: FeeRate,
+    pub data: FeeRate,
+    pub fetched_at: DateTime<Utc>,
+    pub quotes: Vec<FeeQuote>,
+    /// Every source failed, so the static FEE_PER_BYTE default is in use
+    pub fallback: bool,
+}
+
+fn aggregate(quotes: Vec<FeeQuote>) -> Option<FeeRates> {
+    let highest = quotes.iter().max_by(|a, b| a.standard.cmp_rate(&b.standard))?;
+    let (source, standard) = (highest.source.clone(), highest.standard);
+    let data = quotes.iter().map(|q| q.data).max_by(|a, b| a.cmp_rate(b))?;
+    Some(FeeRates {
+        source,
+        standard,
+        data,
+        fetched_at: Utc::now(),
+        quotes,
+        fallback: false,
+    })
+}
+
+pub struct FeeOracle {
+    client: reqwest::Client,
+    sources: Vec<FeeSource>,
+    ttl: Duration,
+    static_rate: FeeRate,
+    cache: RwLock<Option<FeeRates>>,
+}
+
+impl FeeOracle {
+    pub fn from_env(default_fee_per_byte: u64) -> Self {
+        let spec = std::env::var("FEE_SOURCES").unwrap_or_else(|_| {
+            "taal=arc:https://arc.taal.com,gorillapool=mapi:https://mapi.gorillapool.io".to_string()
+        });
+        let sources = spec
+            .split(',')
+            .filter(|s| !s.trim().is_empty())
+            .filter_map(|s| match FeeSource::parse(s.trim()) {
+                Ok(source) => Some(source),
+                Err(e) => {
+                    tracing::warn!("Ignoring fee source: {}", e);
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            client: reqwest::Client::builder()
+                .timeout(std::time::Duration::from_secs(5))
+                .build()
+                .unwrap_or_default(),
+            sources,
+            ttl: Duration::seconds(
+                std::env::var("FEE_CACHE_TTL_SECS").ok().and_then(|s| s.parse().ok()).unwrap_or(300),
+            ),
+            static_rate: FeeRate::per_byte(default_fee_per_byte),
+            cache: RwLock::new(None),
+        }
+    }
+
+    pub fn source_count(&self) -> usize {
+        self.sources.len()
+    }
+
+    fn fallback(&self) -> FeeRates {
+        FeeRates {
+            source: "static".to_string(),
+            standard: self.static_rate,
+            data: self.static_rate,
+            fetched_at: Utc::now(),
+            quotes: Vec::new(),
+            fallback: true,
+        }
+    }
+
+    /// Cached rates, refreshed from the miners once the TTL lapses
+    pub async fn rates(&self) -> FeeRates {
+        if let Some(rates) = self.cache.read().await.as_ref() {
+            if Utc::now() - rates.fetched_at < self.ttl {
+                return rates.clone();
+            }
+        }
+
+        let mut quotes = Vec::new();
+        for source in &self.sources {
+            match source.fetch(&self.client).await {
+                Ok(quote) => quotes.push(quote),
+                Err(e) => tracing::warn!("Fee quote from {} failed: {}", source.name, e),
+            }
+        }
+
+        match aggregate(quotes) {
+            Some(rates) => {
+                *self.cache.write().await = Some(rates.clone());
+                rates
+            }
+            None => {
+                // Keep serving a stale miner quote over the static default
+                if let Some(stale) = self.cache.read().await.as_ref() {
+                    return stale.clone();
+                }
+                self.fallback()
+            }
+        }
+    }
+
+    /// Integer sat/byte rate for a build: an explicit `fee_per_byte` wins, then
+    /// the policy, then the miners' rate of `default_kind`
+    pub async fn fee_per_byte(&self, policy: Option<FeePolicy>, explicit: Option<u64>, default_kind: FeeKind) -> u64 {
+        if let Some(per_byte) = explicit {
+            return per_byte;
+        }
+        let kind = match policy {
+            Some(FeePolicy::Custom(rate)) => return rate.ceil_per_byte(),
+            Some(FeePolicy::Named(kind)) => kind,
+            None => default_kind,
+        };
+        let rates = self.rates().await;
+        match kind {
+            FeeKind::Standard => rates.standard.ceil_per_byte(),
+            FeeKind::Data => rates.data.ceil_per_byte(),
+        }
+    }
+}
+
+pub async fn get_fee_rates(oracle: web::Data<FeeOracle>) -> HttpResponse {
+    HttpResponse::Ok().json(oracle.rates().await)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_arc_policy() {
+        let body = r#"{"policy":{"maxscriptsizepolicy":100000000,"miningFee":{"satoshis":1,"bytes":1000}},"timestamp":"2024-01-01T00:00:00Z"}"#;
+        let quote = parse_arc_policy("taal", body).unwrap();
+        assert_eq!(quote.standard, FeeRate { satoshis: 1, bytes: 1000 });
+        assert_eq!(quote.data, quote.standard);
+    }
+
+    #[test]
+    fn test_parse_mapi_quote() {
+        let payload = r#"{"fees":[{"feeType":"standard","miningFee":{"satoshis":50,"bytes":1000},"relayFee":{"satoshis":0,"bytes":1000}},{"feeType":"data","miningFee":{"satoshis":25,"bytes":1000},"relayFee":{"satoshis":0,"bytes":1000}}]}"#;
+        let body = serde_json::json!({ "payload": payload, "signature": null }).to_string();
+        let quote = parse_mapi_quote("gorillapool", &body).unwrap();
+        assert_eq!(quote.standard.satoshis, 50);
+        assert_eq!(quote.data.satoshis, 25);
+
+        assert!(parse_mapi_quote("x", r#"{"payload":"{\"fees\":[]}"}"#).is_err());
+    }
+
+    #[test]
+    fn test_rates_round_up_and_aggregate_to_highest() {
+        assert_eq!(FeeRate { satoshis: 1, bytes: 1000 }.ceil_per_byte(), 1);
+        assert_eq!(FeeRate { satoshis: 1500, bytes: 1000 }.ceil_per_byte(), 2);
+        assert_eq!(FeeRate::per_byte(50).ceil_per_byte(), 50);
+
+        let quote = |source: &str, standard: u64| FeeQuote {
+            source: source.to_string(),
+            standard: FeeRate { satoshis: standard, bytes: 1000 },
+            data: FeeRate { satoshis: 1, bytes: 1000 },
+            fetched_at: Utc::now(),
+        };
+        let rates = aggregate(vec![quote("a", 50), quote("b", 500)]).unwrap();
+        assert_eq!(rates.source, "b");
+        assert_eq!(rates.standard.satoshis, 500);
+        assert!(aggregate(vec![]).is_none());
+    }
+
+    #[test]
+    fn test_fee_policy_and_source_parsing() {
+        let named: FeePolicy = serde_json::from_str(r#""data""#).unwrap();
+        assert_eq!(named, FeePolicy::Named(FeeKind::Data));
+        let custom: FeePolicy = serde_json::from_str(r#"{"satoshis":5,"bytes":10}"#).unwrap();
+        assert_eq!(custom, FeePolicy::Custom(FeeRate { satoshis: 5, bytes: 10 }));
+        assert!(serde_json::from_str::<FeePolicy>(r#""cheap""#).is_err());
+
+        let source = FeeSource::parse("taal=arc:https://arc.taal.com/").unwrap();
+        assert_eq!((source.kind, source.url.as_str()), (SourceKind::Arc, "https://arc.taal.com"));
+        assert!(FeeSource::parse("bad").is_err());
+        assert!(FeeSource::parse("x=smtp:host").is_err());
+    }
+}
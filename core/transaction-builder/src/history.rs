@@ -0,0 +1,491 @@
+// core/transaction-builder/src/history.rs
+// Audit record of built transactions, per-type build metrics, and fee rebuilds of recorded transactions
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use prometheus::{HistogramOpts, HistogramVec, IntCounterVec, Opts, Registry};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::size::{self, InputScript};
+use crate::{AppState, BuildTransactionResponse, ServiceError, Transaction};
+
+/// Set by calling services so the audit trail shows who asked for each build
+pub const CALLER_HEADER: &str = "X-BSVBank-Caller";
+const MAX_CALLER_LEN: usize = 100;
+const MAX_REASON_LEN: usize = 1_000;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxType {
+    P2pkh,
+    Funding,
+    Commitment,
+    Settlement,
+    Data,
+    BatchPayout,
+    Child,
+    Consolidation,
+    Timelock,
+    TimelockSpend,
+    Template,
+    Payment,
+    P2shSpend,
+    Send,
+}
+
+impl TxType {
+    pub const ALL: [TxType; 14] = [
+        TxType::P2pkh,
+        TxType::Funding,
+        TxType::Commitment,
+        TxType::Settlement,
+        TxType::Data,
+        TxType::BatchPayout,
+        TxType::Child,
+        TxType::Consolidation,
+        TxType::Timelock,
+        TxType::TimelockSpend,
+        TxType::Template,
+        TxType::Payment,
+        TxType::P2shSpend,
+        TxType::Send,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TxType::P2pkh => "p2pkh",
+            TxType::Funding => "funding",
+            TxType::Commitment => "commitment",
+            TxType::Settlement => "settlement",
+            TxType::Data => "data",
+            TxType::BatchPayout => "batch_payout",
+            TxType::Child => "child",
+            TxType::Consolidation => "consolidation",
+            TxType::Timelock => "timelock",
+            TxType::TimelockSpend => "timelock_spend",
+            TxType::Template => "template",
+            TxType::Payment => "payment",
+            TxType::P2shSpend => "p2sh_spend",
+            TxType::Send => "send",
+        }
+    }
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.as_str() == name)
+    }
+
+    /// Every input is plain P2PKH, so the fee can be recomputed without the original request
+    pub fn rebuildable(&self) -> bool {
+        !matches!(
+            self,
+            TxType::Commitment | TxType::Settlement | TxType::Child | TxType::TimelockSpend | TxType::P2shSpend
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InputRecord {
+    pub txid: String,
+    pub vout: u32,
+    pub satoshis: u64,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct BuiltTransaction {
+    pub id: Uuid,
+    pub txid: String,
+    pub tx_type: String,
+    pub caller: Option<String>,
+    pub tx_hex: String,
+    pub inputs: Json<Vec<InputRecord>>,
+    pub output_count: i32,
+    pub fee_satoshis: i64,
+    pub size_bytes: i32,
+    pub signed: bool,
+    pub status: String,
+    pub replaces_txid: Option<String>,
+    pub replaced_by_txid: Option<String>,
+    pub replace_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub replaced_at: Option<DateTime<Utc>>,
+}
+
+impl BuiltTransaction {
+    /// The recorded transaction with its input values restored
+    pub fn transaction(&self) -> Result<Transaction, String> {
+        let mut tx = Transaction::from_hex(&self.tx_hex).map_err(|e| format!("Stored transaction is invalid: {}", e))?;
+        if tx.inputs.len() != self.inputs.0.len() {
+            return Err("Stored inputs do not match the transaction".to_string());
+        }
+        for (input, record) in tx.inputs.iter_mut().zip(&self.inputs.0) {
+            input.value = record.satoshis;
+        }
+        Ok(tx)
+    }
+}
+
+#[derive(Deserialize)]
+pub struct RebuildRequest {
+    /// Output that absorbs the fee difference
+    pub change_vout: usize,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    pub reason: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct RebuildResponse {
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    pub replaces: String,
+}
+
+// ============================================================================
+// METRICS
+// ============================================================================
+
+#[derive(Clone)]
+pub struct BuildMetrics {
+    built_total: IntCounterVec,
+    fee_satoshis_total: IntCounterVec,
+    size_bytes: HistogramVec,
+}
+
+impl BuildMetrics {
+    pub fn new(registry: &Registry) -> Result<Self, prometheus::Error> {
+        let built_total = IntCounterVec::new(
+            Opts::new("built_transactions_total", "Transactions built, by type").namespace("transaction_builder"),
+            &["tx_type"],
+        )?;
+        registry.register(Box::new(built_total.clone()))?;
+
+        let fee_satoshis_total = IntCounterVec::new(
+            Opts::new("built_transaction_fees_satoshis_total", "Fees paid by built transactions, by type")
+                .namespace("transaction_builder"),
+            &["tx_type"],
+        )?;
+        registry.register(Box::new(fee_satoshis_total.clone()))?;
+
+        let size_bytes = HistogramVec::new(
+            HistogramOpts::new("built_transaction_size_bytes", "Size of built transactions, by type")
+                .namespace("transaction_builder")
+                .buckets(vec![250.0, 500.0, 1_000.0, 5_000.0, 25_000.0, 100_000.0, 1_000_000.0]),
+            &["tx_type"],
+        )?;
+        registry.register(Box::new(size_bytes.clone()))?;
+
+        Ok(Self { built_total, fee_satoshis_total, size_bytes })
+    }
+
+    fn observe(&self, tx_type: TxType, fee_satoshis: u64, size_bytes: usize) {
+        let label = [tx_type.as_str()];
+        self.built_total.with_label_values(&label).inc();
+        self.fee_satoshis_total.with_label_values(&label).inc_by(fee_satoshis);
+        self.size_bytes.with_label_values(&label).observe(size_bytes as f64);
+    }
+}
+
+// ============================================================================
+// RECORDING
+// ============================================================================
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    tracing::error!("Built transaction database error: {}", e);
+    ServiceError::DatabaseError("Failed to access built transactions".to_string())
+}
+
+pub fn caller(http_req: &HttpRequest) -> Option<String> {
+    http_req
+        .headers()
+        .get(CALLER_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(|v| v.chars().take(MAX_CALLER_LEN).collect())
+}
+
+fn input_records(tx: &Transaction) -> Vec<InputRecord> {
+    tx.inputs
+        .iter()
+        .map(|i| InputRecord { txid: i.txid.clone(), vout: i.vout, satoshis: i.value })
+        .collect()
+}
+
+async fn insert<'e, E: sqlx::PgExecutor<'e>>(
+    executor: E,
+    tx_type: TxType,
+    caller: Option<&str>,
+    tx: &Transaction,
+    replaces: Option<&str>,
+    reason: Option<&str>,
+) -> Result<(), sqlx::Error> {
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+
+    // Rebuilding the same request yields the same txid; the first record stands
+    sqlx::query(
+        "INSERT INTO built_transactions \
+         (txid, tx_type, caller, tx_hex, inputs, output_count, fee_satoshis, size_bytes, signed, replaces_txid, replace_reason) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) ON CONFLICT (txid) DO NOTHING"
+    )
+    .bind(tx.calculate_txid())
+    .bind(tx_type.as_str())
+    .bind(caller)
+    .bind(tx.to_hex())
+    .bind(Json(input_records(tx)))
+    .bind(tx.outputs.len() as i32)
+    .bind(total_in.saturating_sub(total_out) as i64)
+    .bind(tx.calculate_size() as i32)
+    .bind(!tx.inputs.is_empty() && tx.inputs.iter().all(|i| !i.script_sig.is_empty()))
+    .bind(replaces)
+    .bind(reason)
+    .execute(executor)
+    .await?;
+    Ok(())
+}
+
+/// Store a built transaction and count it in the per-type metrics
+pub async fn record(data: &AppState, http_req: &HttpRequest, tx_type: TxType, tx: &Transaction) -> Result<(), ServiceError> {
+    insert(&data.db, tx_type, caller(http_req).as_deref(), tx, None, None)
+        .await
+        .map_err(db_error)?;
+
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    data.build_metrics.observe(tx_type, total_in.saturating_sub(total_out), tx.calculate_size());
+    Ok(())
+}
+
+async fn fetch(db: &sqlx::PgPool, txid: &str) -> Result<Option<BuiltTransaction>, ServiceError> {
+    sqlx::query_as::<_, BuiltTransaction>("SELECT * FROM built_transactions WHERE txid = $1")
+        .bind(txid.to_lowercase())
+        .fetch_optional(db)
+        .await
+        .map_err(db_error)
+}
+
+// ============================================================================
+// REBUILDING
+// ============================================================================
+
+/// Unsigned copy of `tx` paying `fee_per_byte`, with the difference taken from or added to `change_vout`
+pub fn rebuild_with_fee(tx: &Transaction, change_vout: usize, fee_per_byte: u64, dust_threshold: u64) -> Result<Transaction, String> {
+    if change_vout >= tx.outputs.len() {
+        return Err(format!("change_vout {} out of range", change_vout));
+    }
+
+    let mut rebuilt = tx.clone();
+    for input in &mut rebuilt.inputs {
+        input.script_sig.clear();
+    }
+
+    let total_in: u64 = rebuilt.inputs.iter().map(|i| i.value).sum();
+    let other_outputs: u64 = rebuilt
+        .outputs
+        .iter()
+        .enumerate()
+        .filter(|(vout, _)| *vout != change_vout)
+        .map(|(_, o)| o.value)
+        .sum();
+    let fee = size::estimate_size(&rebuilt, &size::input_scripts(&rebuilt, InputScript::P2PKH)) as u64 * fee_per_byte;
+
+    let change = total_in
+        .checked_sub(other_outputs + fee)
+        .ok_or_else(|| format!("Inputs cannot cover a {} sat fee", fee))?;
+    if change <= dust_threshold {
+        return Err(format!("Change would be {} sats, at or below the dust threshold", change));
+    }
+    rebuilt.outputs[change_vout].value = change;
+    Ok(rebuilt)
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+fn not_found(txid: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "not_found",
+        "message": format!("Transaction {} was not built here", txid)
+    }))
+}
+
+pub async fn get_built_transaction(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    let txid = path.into_inner();
+    if txid.len() != 64 || hex::decode(&txid).is_err() {
+        return Err(ServiceError::ValidationError("txid must be 64 hex characters".to_string()));
+    }
+    match fetch(&data.db, &txid).await? {
+        Some(built) => Ok(HttpResponse::Ok().json(built)),
+        None => Ok(not_found(&txid)),
+    }
+}
+
+/// Rebuild a recorded transaction at a new fee rate; the original is marked replaced
+pub async fn rebuild_transaction(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<RebuildRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let txid = path.into_inner();
+    let Some(original) = fetch(&data.db, &txid).await? else {
+        return Ok(not_found(&txid));
+    };
+    if original.status != "built" {
+        return Err(ServiceError::Conflict(format!(
+            "Transaction {} was already replaced by {}",
+            original.txid,
+            original.replaced_by_txid.as_deref().unwrap_or("another build")
+        )));
+    }
+    let tx_type = TxType::parse(&original.tx_type)
+        .ok_or_else(|| ServiceError::BuildError(format!("Unknown transaction type {}", original.tx_type)))?;
+    if !tx_type.rebuildable() {
+        return Err(ServiceError::ValidationError(format!(
+            "{} transactions do not spend plain P2PKH inputs; rebuild them through their own endpoint",
+            tx_type.as_str()
+        )));
+    }
+    if req.reason.as_ref().is_some_and(|r| r.len() > MAX_REASON_LEN) {
+        return Err(ServiceError::ValidationError(format!("reason is limited to {} characters", MAX_REASON_LEN)));
+    }
+
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+    if fee_per_byte == 0 || fee_per_byte > 10000 {
+        return Err(ServiceError::ValidationError("Fee per byte must be between 1 and 10000".to_string()));
+    }
+    let tx = original.transaction().map_err(ServiceError::BuildError)?;
+    let rebuilt = rebuild_with_fee(&tx, req.change_vout, fee_per_byte, data.config.dust_threshold)
+        .map_err(ServiceError::BuildError)?;
+    let rebuilt_txid = rebuilt.calculate_txid();
+    if rebuilt_txid == original.txid {
+        return Err(ServiceError::Conflict("Rebuild is identical to the original".to_string()));
+    }
+
+    let mut db_tx = data.db.begin().await.map_err(db_error)?;
+    let replaced = sqlx::query(
+        "UPDATE built_transactions SET status = 'replaced', replaced_by_txid = $2, replaced_at = NOW() \
+         WHERE txid = $1 AND status = 'built'"
+    )
+    .bind(&original.txid)
+    .bind(&rebuilt_txid)
+    .execute(&mut *db_tx)
+    .await
+    .map_err(db_error)?;
+    if replaced.rows_affected() == 0 {
+        return Err(ServiceError::Conflict(format!("Transaction {} was replaced concurrently", original.txid)));
+    }
+    insert(
+        &mut *db_tx,
+        tx_type,
+        caller(&http_req).as_deref(),
+        &rebuilt,
+        Some(&original.txid),
+        req.reason.as_deref(),
+    )
+    .await
+    .map_err(db_error)?;
+    db_tx.commit().await.map_err(db_error)?;
+
+    let fee_breakdown = size::fee_breakdown(&rebuilt, &size::input_scripts(&rebuilt, InputScript::P2PKH), fee_per_byte);
+    let total_in: u64 = rebuilt.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = rebuilt.outputs.iter().map(|o| o.value).sum();
+    data.build_metrics.observe(tx_type, total_in - total_out, rebuilt.calculate_size());
+    tracing::info!("Rebuilt {} as {} at {} sat/byte", original.txid, rebuilt_txid, fee_per_byte);
+
+    Ok(HttpResponse::Ok().json(RebuildResponse {
+        transaction: BuildTransactionResponse {
+            tx_hex: rebuilt.to_hex(),
+            txid: rebuilt_txid,
+            size_bytes: rebuilt.calculate_size(),
+            fee_satoshis: total_in - total_out,
+            inputs: rebuilt.inputs.clone(),
+            outputs: rebuilt.outputs.clone(),
+            fee_breakdown,
+        },
+        replaces: original.txid,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptBuilder;
+
+    fn transaction() -> Transaction {
+        let mut tx = Transaction::new();
+        tx.add_input("11".repeat(32), 0, 30_000);
+        tx.add_input("22".repeat(32), 1, 20_000);
+        tx.add_output(40_000, ScriptBuilder::p2pkh(&[1; 20]));
+        tx.add_output(9_000, ScriptBuilder::p2pkh(&[2; 20]));
+        tx.inputs[0].script_sig = vec![0xaa; 107];
+        tx
+    }
+
+    #[test]
+    fn test_tx_type_names_round_trip() {
+        for tx_type in TxType::ALL {
+            assert_eq!(TxType::parse(tx_type.as_str()), Some(tx_type));
+        }
+        assert_eq!(TxType::parse("unknown"), None);
+        assert!(TxType::P2pkh.rebuildable());
+        assert!(!TxType::Settlement.rebuildable());
+    }
+
+    #[test]
+    fn test_rebuild_moves_fee_into_change() {
+        let tx = transaction();
+        let rebuilt = rebuild_with_fee(&tx, 1, 5, 546).unwrap();
+
+        let fee = size::estimate_size(&rebuilt, &size::input_scripts(&rebuilt, InputScript::P2PKH)) as u64 * 5;
+        assert_eq!(rebuilt.outputs[0].value, 40_000);
+        assert_eq!(rebuilt.outputs[1].value, 50_000 - 40_000 - fee);
+        assert!(rebuilt.inputs.iter().all(|i| i.script_sig.is_empty()));
+        assert_ne!(rebuilt.calculate_txid(), tx.calculate_txid());
+    }
+
+    #[test]
+    fn test_rebuild_rejects_dust_change() {
+        let tx = transaction();
+        assert!(rebuild_with_fee(&tx, 1, 30, 546).is_err());
+        assert!(rebuild_with_fee(&tx, 2, 1, 546).is_err());
+    }
+
+    #[test]
+    fn test_stored_inputs_restore_values() {
+        let tx = transaction();
+        let built = BuiltTransaction {
+            id: Uuid::new_v4(),
+            txid: tx.calculate_txid(),
+            tx_type: "p2pkh".to_string(),
+            caller: None,
+            tx_hex: tx.to_hex(),
+            inputs: Json(input_records(&tx)),
+            output_count: 2,
+            fee_satoshis: 1_000,
+            size_bytes: tx.calculate_size() as i32,
+            signed: false,
+            status: "built".to_string(),
+            replaces_txid: None,
+            replaced_by_txid: None,
+            replace_reason: None,
+            created_at: Utc::now(),
+            replaced_at: None,
+        };
+
+        let restored = built.transaction().unwrap();
+        assert_eq!(restored.inputs.iter().map(|i| i.value).collect::<Vec<_>>(), vec![30_000, 20_000]);
+        assert_eq!(restored.calculate_txid(), tx.calculate_txid());
+    }
+}
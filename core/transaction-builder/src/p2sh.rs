@@ -0,0 +1,318 @@
+// core/transaction-builder/src/p2sh.rs
+// Spending P2SH multisig outputs: redeem script parsing, scriptSig assembly and sizing
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use secp256k1::PublicKey;
+use serde::{Deserialize, Serialize};
+
+use crate::codec;
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::signing;
+use crate::size::{self, InputScript};
+use crate::{
+    AddressUtils, AppState, BuildTransactionResponse, ScriptBuilder, ServiceError, Transaction, MAX_REDEEM_SCRIPT_SIZE,
+};
+
+const DUST_THRESHOLD: u64 = 546;
+
+// ============================================================================
+// REDEEM SCRIPTS
+// ============================================================================
+
+#[derive(Debug, Clone)]
+pub struct RedeemScript {
+    pub script: Vec<u8>,
+    pub required: usize,
+    /// Keys in script order, which is the order CHECKMULTISIG expects signatures in
+    pub pubkeys: Vec<Vec<u8>>,
+}
+
+impl RedeemScript {
+    pub fn from_hex(redeem_hex: &str) -> Result<Self, String> {
+        let script = hex::decode(redeem_hex).map_err(|_| "Invalid redeem_script hex".to_string())?;
+        if script.len() > MAX_REDEEM_SCRIPT_SIZE {
+            return Err(format!(
+                "Redeem script is {} bytes; the limit is {}",
+                script.len(), MAX_REDEEM_SCRIPT_SIZE
+            ));
+        }
+        let (required, pubkeys) = codec::multisig_keys(&script)
+            .ok_or_else(|| "redeem_script is not a multisig script".to_string())?;
+        Ok(Self { script, required: required as usize, pubkeys })
+    }
+
+    /// How an input spending this script is sized before it is signed
+    pub fn input_script(&self) -> InputScript {
+        InputScript::P2shMultisig { required: self.required, redeem_script_len: self.script.len() }
+    }
+
+    /// Verify `signatures` against input `index` of `tx` and assemble the scriptSig.
+    /// Signatures may be given in any order; they are emitted in key order.
+    pub fn script_sig(
+        &self,
+        tx: &Transaction,
+        index: usize,
+        value: u64,
+        signatures: &[String],
+    ) -> Result<Vec<u8>, String> {
+        let mut by_key: Vec<Option<Vec<u8>>> = vec![None; self.pubkeys.len()];
+
+        for sig_hex in signatures {
+            let signature = hex::decode(sig_hex).map_err(|_| format!("Invalid signature hex: {}", sig_hex))?;
+            let position = self.pubkeys.iter().position(|pubkey| {
+                PublicKey::from_slice(pubkey)
+                    .map(|key| signing::verify_input_signature(tx, index, &self.script, value, &signature, &key))
+                    .unwrap_or(false)
+            });
+            match position {
+                Some(position) => by_key[position] = Some(signature),
+                None => return Err(format!("Signature {} does not match any key in the redeem script", sig_hex)),
+            }
+        }
+
+        let ordered: Vec<Vec<u8>> = by_key.into_iter().flatten().take(self.required).collect();
+        if ordered.len() < self.required {
+            return Err(format!("{} of {} required signatures present", ordered.len(), self.required));
+        }
+        Ok(ScriptBuilder::p2sh_multisig_script_sig(&ordered, &self.script))
+    }
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct P2shInput {
+    pub txid: String,
+    pub vout: u32,
+    pub satoshis: u64,
+    pub redeem_script: String,
+    /// Hex DER signatures with sighash byte; omit to get the unsigned transaction to sign
+    #[serde(default)]
+    pub signatures: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PaymentOutput {
+    pub address: String,
+    pub satoshis: u64,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SpendP2shRequest {
+    pub inputs: Vec<P2shInput>,
+    pub outputs: Vec<PaymentOutput>,
+    /// Receives whatever is left after outputs and fee; without it the excess is fee
+    pub change_address: Option<String>,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+}
+
+#[derive(Serialize)]
+pub struct SpendP2shResponse {
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    /// Size once every input is signed, which the fee is based on
+    pub estimated_signed_size: usize,
+    /// Whether every input has its scriptSig
+    pub complete: bool,
+}
+
+/// Build a transaction spending P2SH multisig inputs. Building is deterministic,
+/// so the unsigned result can be signed and sent back with the signatures.
+pub fn build_p2sh_spend(req: &SpendP2shRequest, fee_per_byte: u64) -> Result<(Transaction, usize), String> {
+    if req.inputs.is_empty() {
+        return Err("No inputs provided".to_string());
+    }
+    if req.outputs.is_empty() {
+        return Err("No outputs provided".to_string());
+    }
+
+    let mut tx = Transaction::new();
+    let mut redeem_scripts = Vec::with_capacity(req.inputs.len());
+    for (index, input) in req.inputs.iter().enumerate() {
+        let redeem = RedeemScript::from_hex(&input.redeem_script).map_err(|e| format!("Input {}: {}", index, e))?;
+        tx.add_input(input.txid.clone(), input.vout, input.satoshis);
+        redeem_scripts.push(redeem);
+    }
+
+    for output in &req.outputs {
+        let hash = AddressUtils::decode_address(&output.address)?;
+        tx.add_output(output.satoshis, ScriptBuilder::p2pkh(&hash));
+    }
+
+    let input_scripts: Vec<InputScript> = redeem_scripts.iter().map(RedeemScript::input_script).collect();
+    let total_in: u64 = req.inputs.iter().map(|i| i.satoshis).sum();
+    let total_out: u64 = req.outputs.iter().map(|o| o.satoshis).sum();
+
+    let mut estimated_size = size::estimate_size(&tx, &input_scripts);
+    let fee = estimated_size as u64 * fee_per_byte;
+    if total_in < total_out + fee {
+        return Err(format!("Insufficient funds: need {} sats, have {} sats", total_out + fee, total_in));
+    }
+
+    if let Some(change_address) = &req.change_address {
+        let change_script = ScriptBuilder::p2pkh(&AddressUtils::decode_address(change_address)?);
+        let fee_with_change = (estimated_size + size::output_size(change_script.len())) as u64 * fee_per_byte;
+        let change = total_in.saturating_sub(total_out + fee_with_change);
+        if change > DUST_THRESHOLD {
+            tx.add_output(change, change_script);
+            estimated_size = size::estimate_size(&tx, &input_scripts);
+        }
+    }
+
+    // Signatures commit to the outputs, so scriptSigs go in last
+    for (index, (input, redeem)) in req.inputs.iter().zip(&redeem_scripts).enumerate() {
+        if input.signatures.is_empty() {
+            continue;
+        }
+        let script_sig = redeem
+            .script_sig(&tx, index, input.satoshis, &input.signatures)
+            .map_err(|e| format!("Input {}: {}", index, e))?;
+        tx.inputs[index].script_sig = script_sig;
+    }
+
+    Ok((tx, estimated_size))
+}
+
+pub async fn spend_p2sh(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    req: web::Json<SpendP2shRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+
+    let (tx, estimated_signed_size) = build_p2sh_spend(&req, fee_per_byte).map_err(|e| {
+        tracing::error!("Failed to build P2SH spend: {}", e);
+        ServiceError::BuildError(e)
+    })?;
+
+    let unsigned: Vec<InputScript> = req
+        .inputs
+        .iter()
+        .map(|input| RedeemScript::from_hex(&input.redeem_script).map(|r| r.input_script()))
+        .collect::<Result<_, _>>()
+        .map_err(ServiceError::BuildError)?;
+    let input_scripts: Vec<InputScript> = tx
+        .inputs
+        .iter()
+        .zip(unsigned)
+        .map(|(input, script)| {
+            if input.script_sig.is_empty() { script } else { InputScript::Final(input.script_sig.len()) }
+        })
+        .collect();
+    let fee_breakdown = size::fee_breakdown(&tx, &input_scripts, fee_per_byte);
+
+    let complete = tx.inputs.iter().all(|i| !i.script_sig.is_empty());
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    let txid = tx.calculate_txid();
+    tracing::info!("Built P2SH spend: {} ({} inputs, complete: {})", txid, tx.inputs.len(), complete);
+    history::record(&data, &http_req, TxType::P2shSpend, &tx).await?;
+
+    Ok(HttpResponse::Ok().json(SpendP2shResponse {
+        transaction: BuildTransactionResponse {
+            tx_hex: tx.to_hex(),
+            txid,
+            size_bytes: tx.calculate_size(),
+            fee_satoshis: total_in - total_out,
+            inputs: tx.inputs.clone(),
+            outputs: tx.outputs.clone(),
+            fee_breakdown,
+        },
+        estimated_signed_size,
+        complete,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Secp256k1, SecretKey};
+
+    const ADDRESS: &str = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+    const SIGHASH: u32 = signing::SIGHASH_ALL | signing::SIGHASH_FORKID;
+
+    fn keys() -> Vec<SecretKey> {
+        (1..=3u8).map(|i| SecretKey::from_slice(&[i; 32]).unwrap()).collect()
+    }
+
+    fn redeem_hex(required: u8) -> String {
+        let secp = Secp256k1::new();
+        let pubkeys: Vec<Vec<u8>> = keys()
+            .iter()
+            .map(|k| PublicKey::from_secret_key(&secp, k).serialize().to_vec())
+            .collect();
+        hex::encode(ScriptBuilder::multisig(required, &pubkeys))
+    }
+
+    fn request(signatures: Vec<String>) -> SpendP2shRequest {
+        SpendP2shRequest {
+            inputs: vec![P2shInput {
+                txid: "22".repeat(32),
+                vout: 1,
+                satoshis: 100_000,
+                redeem_script: redeem_hex(2),
+                signatures,
+            }],
+            outputs: vec![PaymentOutput { address: ADDRESS.to_string(), satoshis: 60_000 }],
+            change_address: Some(ADDRESS.to_string()),
+            fee_per_byte: None,
+            fee_policy: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_matches_signed_size() {
+        let (unsigned, estimate) = build_p2sh_spend(&request(vec![]), 1).unwrap();
+        assert!(unsigned.inputs[0].script_sig.is_empty());
+        assert_eq!(unsigned.outputs.len(), 2);
+
+        let redeem = RedeemScript::from_hex(&redeem_hex(2)).unwrap();
+        let sigs: Vec<String> = keys()[1..]
+            .iter()
+            .rev() // deliberately out of key order
+            .map(|k| hex::encode(signing::sign_input(&unsigned, 0, &redeem.script, 100_000, SIGHASH, k)))
+            .collect();
+
+        let (signed, _) = build_p2sh_spend(&request(sigs), 1).unwrap();
+        // Signing only fills in the scriptSig; outpoints and outputs are unchanged
+        let outpoints = |tx: &Transaction| tx.inputs.iter().map(|i| (i.txid.clone(), i.vout, i.sequence)).collect::<Vec<_>>();
+        let outputs = |tx: &Transaction| tx.outputs.iter().map(|o| (o.value, o.script_pubkey.clone())).collect::<Vec<_>>();
+        assert_eq!(outpoints(&signed), outpoints(&unsigned));
+        assert_eq!(outputs(&signed), outputs(&unsigned));
+        let actual = signed.calculate_size();
+        // Only signature length variance (at most 2 bytes each) separates estimate and reality
+        assert!(estimate >= actual && estimate - actual <= 4, "estimate {} actual {}", estimate, actual);
+        assert_eq!(signed.inputs[0].script_sig[0], 0x00);
+        assert!(signed.inputs[0].script_sig.ends_with(&redeem.script));
+    }
+
+    #[test]
+    fn test_rejects_missing_and_foreign_signatures() {
+        let (unsigned, _) = build_p2sh_spend(&request(vec![]), 1).unwrap();
+        let redeem = RedeemScript::from_hex(&redeem_hex(2)).unwrap();
+        let sign = |key: &SecretKey| {
+            hex::encode(signing::sign_input(&unsigned, 0, &redeem.script, 100_000, SIGHASH, key))
+        };
+
+        let one = vec![sign(&keys()[0])];
+        assert!(build_p2sh_spend(&request(one), 1).unwrap_err().contains("1 of 2"));
+
+        let outsider = SecretKey::from_slice(&[9u8; 32]).unwrap();
+        assert!(build_p2sh_spend(&request(vec![sign(&outsider)]), 1).is_err());
+    }
+
+    #[test]
+    fn test_redeem_script_validation() {
+        assert!(RedeemScript::from_hex("zz").is_err());
+        assert!(RedeemScript::from_hex(&hex::encode(ScriptBuilder::p2pkh(&[0u8; 20]))).is_err());
+        let redeem = RedeemScript::from_hex(&redeem_hex(2)).unwrap();
+        assert_eq!((redeem.required, redeem.pubkeys.len()), (2, 3));
+        // OP_0 + 2 * (push + 73) + (OP_PUSHDATA1 + len + 105-byte script)
+        assert_eq!(redeem.input_script().script_sig_len(), 1 + 2 * 74 + 107);
+    }
+}
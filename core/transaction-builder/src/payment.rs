@@ -0,0 +1,557 @@
+// core/transaction-builder/src/payment.rs
+// BIP270 payment requests: issuing them, accepting payments, and paying requests from our own UTXOs
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::coin_selection;
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::reservations::{self, Reservation, ReservationError};
+use crate::signing::{self, KeyStore, SignInputRequest};
+use crate::size::{self, InputScript};
+use crate::{
+    AddressUtils, AppState, BuildTransactionResponse, Network, ScriptBuilder, ServiceError, Transaction,
+    UtxoInput,
+};
+
+const DUST_THRESHOLD: u64 = 546;
+const MAX_MEMO_LEN: usize = 1_000;
+const MAX_MERCHANT_DATA_LEN: usize = 10_000;
+
+/// BIP270 network identifier for the configured chain
+pub fn bip270_network(network: Network) -> &'static str {
+    match network {
+        Network::Mainnet => "bitcoin-sv",
+        Network::Testnet => "bitcoin-sv-testnet",
+        Network::Regtest => "bitcoin-sv-regtest",
+    }
+}
+
+// ============================================================================
+// BIP270 MESSAGES
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PaymentOutput {
+    pub amount: u64,
+    /// Locking script, hex
+    pub script: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentRequest {
+    pub network: String,
+    pub outputs: Vec<PaymentOutput>,
+    /// Unix seconds
+    pub creation_timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expiration_timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    pub payment_url: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merchant_data: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Payment {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub merchant_data: Option<String>,
+    /// Signed transaction, hex
+    pub transaction: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refund_to: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PaymentAck {
+    pub payment: Payment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<String>,
+    /// Non-zero when the payment was rejected
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<i32>,
+}
+
+impl PaymentRequest {
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expiration_timestamp.is_some_and(|expires| now.timestamp() >= expires)
+    }
+
+    /// Requested outputs as (value, script), checked for sane amounts and hex
+    pub fn output_scripts(&self) -> Result<Vec<(u64, Vec<u8>)>, String> {
+        if self.outputs.is_empty() {
+            return Err("Payment request has no outputs".to_string());
+        }
+        self.outputs
+            .iter()
+            .enumerate()
+            .map(|(index, output)| {
+                let script = hex::decode(&output.script).map_err(|_| format!("Output {}: invalid script hex", index))?;
+                if script.is_empty() {
+                    return Err(format!("Output {}: empty script", index));
+                }
+                Ok((output.amount, script))
+            })
+            .collect()
+    }
+
+    /// Every requested output must be matched by a distinct transaction output
+    /// with the same script and at least the requested amount
+    pub fn satisfied_by(&self, tx: &Transaction) -> Result<(), String> {
+        let mut used = vec![false; tx.outputs.len()];
+        for (index, (amount, script)) in self.output_scripts()?.into_iter().enumerate() {
+            let found = tx.outputs.iter().enumerate().position(|(i, o)| {
+                !used[i] && o.script_pubkey == script && o.value >= amount
+            });
+            match found {
+                Some(i) => used[i] = true,
+                None => return Err(format!("Transaction does not pay requested output {} ({} sats)", index, amount)),
+            }
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// STORE
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct StoredPaymentRequest {
+    pub id: Uuid,
+    pub network: String,
+    pub outputs: Json<Vec<PaymentOutput>>,
+    pub memo: Option<String>,
+    pub merchant_data: Option<String>,
+    pub status: String,
+    pub txid: Option<String>,
+    pub payment_memo: Option<String>,
+    pub refund_to: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub paid_at: Option<DateTime<Utc>>,
+}
+
+impl StoredPaymentRequest {
+    pub fn to_bip270(&self, public_url: &str) -> PaymentRequest {
+        PaymentRequest {
+            network: self.network.clone(),
+            outputs: self.outputs.0.clone(),
+            creation_timestamp: self.created_at.timestamp(),
+            expiration_timestamp: self.expires_at.map(|t| t.timestamp()),
+            memo: self.memo.clone(),
+            payment_url: format!("{}/payment-requests/{}/pay", public_url.trim_end_matches('/'), self.id),
+            merchant_data: self.merchant_data.clone(),
+        }
+    }
+}
+
+async fn fetch(db: &sqlx::PgPool, id: Uuid) -> Result<Option<StoredPaymentRequest>, ServiceError> {
+    sqlx::query_as::<_, StoredPaymentRequest>("SELECT * FROM payment_requests WHERE id = $1")
+        .bind(id)
+        .fetch_optional(db)
+        .await
+        .map_err(db_error)
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    tracing::error!("Payment request database error: {}", e);
+    ServiceError::DatabaseError("Failed to access payment requests".to_string())
+}
+
+fn not_found(id: Uuid) -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "not_found",
+        "message": format!("Payment request {} not found", id)
+    }))
+}
+
+/// Check a payment against a stored request and mark the request paid
+async fn accept_payment(
+    db: &sqlx::PgPool,
+    stored: &StoredPaymentRequest,
+    payment: &Payment,
+    public_url: &str,
+) -> Result<PaymentAck, ServiceError> {
+    let request = stored.to_bip270(public_url);
+    if stored.status != "pending" {
+        return Err(ServiceError::Conflict(format!("Payment request {} is {}", stored.id, stored.status)));
+    }
+    if request.is_expired(Utc::now()) {
+        return Err(ServiceError::ValidationError(format!("Payment request {} has expired", stored.id)));
+    }
+    if payment.merchant_data != request.merchant_data {
+        return Err(ServiceError::ValidationError("merchantData does not match the payment request".to_string()));
+    }
+
+    let tx = Transaction::from_hex(&payment.transaction)
+        .map_err(|e| ServiceError::ValidationError(format!("Invalid transaction: {}", e)))?;
+    if tx.inputs.iter().any(|i| i.script_sig.is_empty()) {
+        return Err(ServiceError::ValidationError("Payment transaction is not signed".to_string()));
+    }
+    request.satisfied_by(&tx).map_err(ServiceError::ValidationError)?;
+    let txid = tx.calculate_txid();
+
+    let result = sqlx::query(
+        "UPDATE payment_requests SET status = 'paid', txid = $2, payment_memo = $3, refund_to = $4, paid_at = NOW() \
+         WHERE id = $1 AND status = 'pending'"
+    )
+    .bind(stored.id)
+    .bind(&txid)
+    .bind(&payment.memo)
+    .bind(&payment.refund_to)
+    .execute(db)
+    .await
+    .map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Err(ServiceError::Conflict(format!("Payment request {} was settled concurrently", stored.id)));
+    }
+
+    tracing::info!("Payment request {} paid by {}", stored.id, txid);
+    Ok(PaymentAck {
+        payment: payment.clone(),
+        memo: Some(format!("Payment {} accepted", txid)),
+        error: None,
+    })
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct RequestedOutput {
+    /// Either an address or a raw locking script
+    pub address: Option<String>,
+    pub script: Option<String>,
+    pub amount_satoshis: u64,
+    pub description: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreatePaymentRequestRequest {
+    pub outputs: Vec<RequestedOutput>,
+    pub memo: Option<String>,
+    pub merchant_data: Option<String>,
+    /// Omit for a request that never expires
+    pub expires_in_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PayRequestRequest {
+    /// A request issued by this service...
+    pub payment_request_id: Option<Uuid>,
+    /// ...or one fetched from another merchant
+    pub payment_request: Option<PaymentRequest>,
+    pub utxos: Vec<UtxoInput>,
+    pub change_address: String,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    pub memo: Option<String>,
+    pub refund_to: Option<String>,
+    /// Sign every input with this stored key; without it the payment is returned unsigned
+    pub key_ref: Option<String>,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct PayRequestResponse {
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    /// Body to POST to the request's paymentUrl once the transaction is signed
+    pub payment: Payment,
+    /// The acknowledgement, when the request was issued here and the payment is signed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub payment_ack: Option<PaymentAck>,
+    pub reservation: Reservation,
+}
+
+fn requested_outputs(outputs: &[RequestedOutput], network: Network) -> Result<Vec<PaymentOutput>, String> {
+    if outputs.is_empty() {
+        return Err("At least one output is required".to_string());
+    }
+    outputs
+        .iter()
+        .enumerate()
+        .map(|(index, output)| {
+            let script = match (&output.address, &output.script) {
+                (Some(address), None) => AddressUtils::script_for_address(address, network)?,
+                (None, Some(script)) => hex::decode(script).map_err(|_| "Invalid script hex".to_string())?,
+                _ => return Err(format!("Output {}: give exactly one of address or script", index)),
+            };
+            if output.amount_satoshis <= DUST_THRESHOLD {
+                return Err(format!("Output {}: amount must exceed the dust threshold of {}", index, DUST_THRESHOLD));
+            }
+            Ok(PaymentOutput {
+                amount: output.amount_satoshis,
+                script: hex::encode(script),
+                description: output.description.clone(),
+            })
+        })
+        .collect()
+}
+
+pub async fn create_payment_request(
+    data: web::Data<AppState>,
+    req: web::Json<CreatePaymentRequestRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let outputs = requested_outputs(&req.outputs, data.config.network).map_err(ServiceError::ValidationError)?;
+    if req.memo.as_ref().is_some_and(|m| m.len() > MAX_MEMO_LEN) {
+        return Err(ServiceError::ValidationError(format!("memo exceeds {} bytes", MAX_MEMO_LEN)));
+    }
+    if req.merchant_data.as_ref().is_some_and(|m| m.len() > MAX_MERCHANT_DATA_LEN) {
+        return Err(ServiceError::ValidationError(format!("merchant_data exceeds {} bytes", MAX_MERCHANT_DATA_LEN)));
+    }
+    let expires_at = match req.expires_in_seconds {
+        Some(secs) if secs <= 0 => {
+            return Err(ServiceError::ValidationError("expires_in_seconds must be positive".to_string()));
+        }
+        Some(secs) => Some(Utc::now() + Duration::seconds(secs)),
+        None => None,
+    };
+
+    let stored = sqlx::query_as::<_, StoredPaymentRequest>(
+        r#"
+        INSERT INTO payment_requests (network, outputs, memo, merchant_data, expires_at)
+        VALUES ($1, $2, $3, $4, $5)
+        RETURNING *
+        "#
+    )
+    .bind(bip270_network(data.config.network))
+    .bind(Json(&outputs))
+    .bind(&req.memo)
+    .bind(&req.merchant_data)
+    .bind(expires_at)
+    .fetch_one(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!("Created payment request {} ({} outputs)", stored.id, outputs.len());
+    Ok(HttpResponse::Created().json(stored.to_bip270(&data.config.public_url)))
+}
+
+pub async fn get_payment_request(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    match fetch(&data.db, id).await? {
+        Some(stored) => Ok(HttpResponse::Ok().json(stored.to_bip270(&data.config.public_url))),
+        None => Ok(not_found(id)),
+    }
+}
+
+/// Settlement state of a request: status, paying txid and the payer's memo
+pub async fn get_payment_status(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    match fetch(&data.db, id).await? {
+        Some(stored) => Ok(HttpResponse::Ok().json(stored)),
+        None => Ok(not_found(id)),
+    }
+}
+
+/// Wallets POST their Payment here (the request's paymentUrl)
+pub async fn submit_payment(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    payment: web::Json<Payment>,
+) -> Result<HttpResponse, ServiceError> {
+    let id = path.into_inner();
+    let stored = match fetch(&data.db, id).await? {
+        Some(stored) => stored,
+        None => return Ok(not_found(id)),
+    };
+    let ack = accept_payment(&data.db, &stored, &payment, &data.config.public_url).await?;
+    Ok(HttpResponse::Ok().json(ack))
+}
+
+/// Build a transaction that satisfies a payment request, funded from `utxos`
+pub fn build_payment(
+    request: &PaymentRequest,
+    utxos: &[UtxoInput],
+    change_address: &str,
+    fee_per_byte: u64,
+) -> Result<Transaction, String> {
+    let mut tx = Transaction::new();
+    for (amount, script) in request.output_scripts()? {
+        tx.add_output(amount, script);
+    }
+    let change_script = ScriptBuilder::p2pkh(&AddressUtils::decode_address(change_address)?);
+    coin_selection::fund_in_order(tx, utxos, &change_script, fee_per_byte, DUST_THRESHOLD)
+}
+
+pub async fn pay_payment_request(
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    fees: web::Data<FeeOracle>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    req: web::Json<PayRequestRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let mut req = req.into_inner();
+    let config = &data.config;
+    signing::require_key_access(&http_req, &jwt, &keys, req.key_ref.as_deref())?;
+
+    let (request, stored) = match (req.payment_request_id, req.payment_request.take()) {
+        (Some(id), None) => {
+            let stored = fetch(&data.db, id)
+                .await?
+                .ok_or_else(|| ServiceError::ValidationError(format!("Payment request {} not found", id)))?;
+            (stored.to_bip270(&config.public_url), Some(stored))
+        }
+        (None, Some(request)) => (request, None),
+        _ => {
+            return Err(ServiceError::ValidationError(
+                "Provide exactly one of payment_request_id or payment_request".to_string(),
+            ));
+        }
+    };
+    if request.network != bip270_network(config.network) {
+        return Err(ServiceError::ValidationError(format!(
+            "Payment request is for {}, this service is on {}", request.network, config.network
+        )));
+    }
+    if request.is_expired(Utc::now()) {
+        return Err(ServiceError::ValidationError("Payment request has expired".to_string()));
+    }
+
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+    let utxos = reservations::filter_available(&data.db, std::mem::take(&mut req.utxos))
+        .await
+        .map_err(ReservationError::from)?;
+    let mut tx = build_payment(&request, &utxos, &req.change_address, fee_per_byte).map_err(|e| {
+        tracing::error!("Failed to build payment: {}", e);
+        ServiceError::BuildError(e)
+    })?;
+    let fee_breakdown = size::fee_breakdown(&tx, &size::input_scripts(&tx, InputScript::P2PKH), fee_per_byte);
+
+    if let Some(key_ref) = &req.key_ref {
+        let inputs: Vec<SignInputRequest> = tx.inputs.iter().enumerate().map(|(index, input)| SignInputRequest {
+            index,
+            value: input.value,
+            wif: None,
+            key_ref: Some(key_ref.clone()),
+            script_pubkey: None,
+            sighash: None,
+        }).collect();
+        signing::sign_p2pkh_inputs(&mut tx, &inputs, &keys, config.network).map_err(ServiceError::BuildError)?;
+    }
+
+    let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, config.reservation_ttl_secs);
+    let reservation = reservations::reserve(&data.db, &tx.inputs, ttl, "build:payment-request").await?;
+    history::record(&data, &http_req, TxType::Payment, &tx).await?;
+
+    let payment = Payment {
+        merchant_data: request.merchant_data.clone(),
+        transaction: tx.to_hex(),
+        refund_to: req.refund_to.clone(),
+        memo: req.memo.clone(),
+    };
+    // Requests issued here are settled at once when the payment is already signed
+    let payment_ack = match &stored {
+        Some(stored) if tx.inputs.iter().all(|i| !i.script_sig.is_empty()) => {
+            Some(accept_payment(&data.db, stored, &payment, &config.public_url).await?)
+        }
+        _ => None,
+    };
+
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    Ok(HttpResponse::Ok().json(PayRequestResponse {
+        transaction: BuildTransactionResponse {
+            tx_hex: tx.to_hex(),
+            txid: tx.calculate_txid(),
+            size_bytes: tx.calculate_size(),
+            fee_satoshis: total_in - total_out,
+            inputs: tx.inputs.clone(),
+            outputs: tx.outputs.clone(),
+            fee_breakdown,
+        },
+        payment,
+        payment_ack,
+        reservation,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+
+    fn request(outputs: Vec<(u64, Vec<u8>)>) -> PaymentRequest {
+        PaymentRequest {
+            network: "bitcoin-sv".to_string(),
+            outputs: outputs
+                .into_iter()
+                .map(|(amount, script)| PaymentOutput { amount, script: hex::encode(script), description: None })
+                .collect(),
+            creation_timestamp: 1_700_000_000,
+            expiration_timestamp: Some(1_700_000_900),
+            memo: Some("invoice 7".to_string()),
+            payment_url: "http://localhost:8085/payment-requests/x/pay".to_string(),
+            merchant_data: Some("{\"invoice\":7}".to_string()),
+        }
+    }
+
+    fn script(byte: u8) -> Vec<u8> {
+        ScriptBuilder::p2pkh(&[byte; 20])
+    }
+
+    #[test]
+    fn test_bip270_field_names() {
+        let json = serde_json::to_value(request(vec![(1000, script(1))])).unwrap();
+        assert_eq!(json["creationTimestamp"], 1_700_000_000);
+        assert_eq!(json["paymentUrl"], "http://localhost:8085/payment-requests/x/pay");
+        assert!(json["outputs"][0]["script"].is_string());
+        assert!(json.get("merchantData").is_some());
+    }
+
+    #[test]
+    fn test_satisfied_by_matches_each_output_once() {
+        let req = request(vec![(1000, script(1)), (1000, script(1)), (2000, script(2))]);
+
+        let mut tx = Transaction::new();
+        tx.add_output(1000, script(1));
+        tx.add_output(2500, script(2));
+        assert!(req.satisfied_by(&tx).is_err()); // second script(1) output missing
+
+        tx.add_output(1200, script(1));
+        assert!(req.satisfied_by(&tx).is_ok());
+
+        tx.outputs[1].value = 1999;
+        assert!(req.satisfied_by(&tx).is_err());
+    }
+
+    #[test]
+    fn test_build_payment_funds_request() {
+        let req = request(vec![(10_000, script(1)), (5_000, script(2))]);
+        let utxos = vec![UtxoInput { txid: "11".repeat(32), vout: 0, satoshis: 100_000, confirmations: None }];
+        let tx = build_payment(&req, &utxos, ADDRESS, 1).unwrap();
+        assert!(req.satisfied_by(&tx).is_ok());
+        assert_eq!(tx.outputs.len(), 3);
+    }
+
+    #[test]
+    fn test_expiry() {
+        let req = request(vec![(1000, script(1))]);
+        assert!(!req.is_expired(DateTime::from_timestamp(1_700_000_000, 0).unwrap()));
+        assert!(req.is_expired(DateTime::from_timestamp(1_700_000_900, 0).unwrap()));
+    }
+}
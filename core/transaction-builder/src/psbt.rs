@@ -0,0 +1,413 @@
+// core/transaction-builder/src/psbt.rs
+// Partially-signed transactions: each party adds signatures with its own key,
+// then the envelope is finalized into scriptSigs once thresholds are met
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::JwtManager;
+use secp256k1::PublicKey;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::codec::{self, ScriptType};
+use crate::signing::{self, KeyStore};
+use crate::{AddressUtils, AppState, ScriptBuilder, ServiceError, Transaction};
+
+/// Bumped whenever the envelope layout changes incompatibly
+pub const PSBT_FORMAT_VERSION: u32 = 1;
+
+// ============================================================================
+// ENVELOPE
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialTransaction {
+    pub format_version: u32,
+    /// The unsigned transaction; scriptSigs are only filled in on finalize
+    pub tx_hex: String,
+    pub inputs: Vec<PartialInput>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartialInput {
+    pub value: u64,
+    pub script_pubkey: String,
+    pub redeem_script: Option<String>,
+    pub sighash_type: u32,
+    /// Public key (hex) -> signature (hex DER + sighash byte)
+    #[serde(default)]
+    pub signatures: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InputStatus {
+    pub index: usize,
+    pub script_type: ScriptType,
+    pub required: usize,
+    pub signed: usize,
+    pub complete: bool,
+}
+
+/// What an input needs signed, resolved from its scripts
+struct SpendInfo {
+    script_code: Vec<u8>,
+    required: usize,
+    /// Keys allowed to sign, in script order (empty for P2PKH)
+    pubkeys: Vec<Vec<u8>>,
+    pubkey_hash: Option<Vec<u8>>,
+    script_type: ScriptType,
+}
+
+impl PartialInput {
+    fn spend_info(&self) -> Result<SpendInfo, String> {
+        let script_pubkey = hex::decode(&self.script_pubkey).map_err(|_| "Invalid script_pubkey hex")?;
+
+        match codec::classify_script(&script_pubkey) {
+            ScriptType::P2pkh => Ok(SpendInfo {
+                pubkey_hash: Some(script_pubkey[3..23].to_vec()),
+                script_code: script_pubkey,
+                required: 1,
+                pubkeys: Vec::new(),
+                script_type: ScriptType::P2pkh,
+            }),
+            ScriptType::P2sh => {
+                let redeem_hex = self.redeem_script.as_ref().ok_or("P2SH input requires redeem_script")?;
+                let redeem = hex::decode(redeem_hex).map_err(|_| "Invalid redeem_script hex")?;
+                if AddressUtils::hash160(&redeem) != script_pubkey[2..22] {
+                    return Err("redeem_script does not match the P2SH script hash".to_string());
+                }
+                let (required, pubkeys) = codec::multisig_keys(&redeem)
+                    .ok_or("redeem_script is not a multisig script")?;
+                Ok(SpendInfo {
+                    script_type: ScriptType::Multisig { required, total: pubkeys.len() as u8 },
+                    script_code: redeem,
+                    required: required as usize,
+                    pubkeys,
+                    pubkey_hash: None,
+                })
+            }
+            other => Err(format!("Unsupported input script type: {:?}", other)),
+        }
+    }
+}
+
+impl PartialTransaction {
+    fn transaction(&self) -> Result<Transaction, String> {
+        let tx = Transaction::from_hex(&self.tx_hex).map_err(|e| e.to_string())?;
+        if tx.inputs.len() != self.inputs.len() {
+            return Err(format!(
+                "Envelope describes {} inputs but transaction has {}",
+                self.inputs.len(), tx.inputs.len()
+            ));
+        }
+        Ok(tx)
+    }
+
+    pub fn status(&self) -> Vec<InputStatus> {
+        self.inputs.iter().enumerate().map(|(index, input)| {
+            let (script_type, required) = match input.spend_info() {
+                Ok(info) => (info.script_type, info.required),
+                Err(_) => (ScriptType::Nonstandard, usize::MAX),
+            };
+            InputStatus {
+                index,
+                script_type,
+                required,
+                signed: input.signatures.len(),
+                complete: input.signatures.len() >= required,
+            }
+        }).collect()
+    }
+
+    /// Add signatures from one key to every listed input it is allowed to sign
+    pub fn sign(&mut self, key: &signing::SigningKey, only: Option<&[usize]>) -> Result<Vec<usize>, String> {
+        let tx = self.transaction()?;
+        let pubkey = key.public_key_bytes();
+        let pubkey_hex = hex::encode(&pubkey);
+        let mut signed = Vec::new();
+
+        for (index, input) in self.inputs.iter_mut().enumerate() {
+            if only.map(|list| !list.contains(&index)).unwrap_or(false) {
+                continue;
+            }
+            let info = input.spend_info().map_err(|e| format!("Input {}: {}", index, e))?;
+
+            let can_sign = match &info.pubkey_hash {
+                Some(hash) => *hash == key.pubkey_hash(),
+                None => info.pubkeys.contains(&pubkey),
+            };
+            if !can_sign {
+                if only.is_some() {
+                    return Err(format!("Input {}: key is not a signer for this input", index));
+                }
+                continue;
+            }
+
+            let signature = signing::sign_input(
+                &tx, index, &info.script_code, input.value, input.sighash_type, &key.secret,
+            );
+            input.signatures.insert(pubkey_hex.clone(), hex::encode(signature));
+            signed.push(index);
+        }
+
+        if signed.is_empty() {
+            return Err("Key cannot sign any input of this transaction".to_string());
+        }
+        Ok(signed)
+    }
+
+    /// Verify every signature and assemble the final scriptSigs
+    pub fn finalize(&self) -> Result<Transaction, String> {
+        let mut tx = self.transaction()?;
+        let unsigned = tx.clone();
+
+        for (index, input) in self.inputs.iter().enumerate() {
+            let info = input.spend_info().map_err(|e| format!("Input {}: {}", index, e))?;
+
+            let mut valid: BTreeMap<Vec<u8>, Vec<u8>> = BTreeMap::new();
+            for (pubkey_hex, sig_hex) in &input.signatures {
+                let pubkey_bytes = hex::decode(pubkey_hex).map_err(|_| format!("Input {}: bad pubkey hex", index))?;
+                let signature = hex::decode(sig_hex).map_err(|_| format!("Input {}: bad signature hex", index))?;
+                let pubkey = PublicKey::from_slice(&pubkey_bytes)
+                    .map_err(|_| format!("Input {}: invalid public key", index))?;
+
+                if !signing::verify_input_signature(&unsigned, index, &info.script_code, input.value, &signature, &pubkey) {
+                    return Err(format!("Input {}: invalid signature from {}", index, pubkey_hex));
+                }
+                valid.insert(pubkey_bytes, signature);
+            }
+
+            tx.inputs[index].value = input.value;
+            tx.inputs[index].script_sig = match info.pubkey_hash {
+                Some(hash) => {
+                    let (pubkey, signature) = valid
+                        .iter()
+                        .find(|(pubkey, _)| AddressUtils::hash160(pubkey) == hash)
+                        .ok_or_else(|| format!("Input {}: missing signature", index))?;
+                    ScriptBuilder::p2pkh_script_sig(signature, pubkey)
+                }
+                None => {
+                    // CHECKMULTISIG consumes signatures in the same order as the keys
+                    let ordered: Vec<Vec<u8>> = info.pubkeys
+                        .iter()
+                        .filter_map(|pubkey| valid.get(pubkey).cloned())
+                        .take(info.required)
+                        .collect();
+                    if ordered.len() < info.required {
+                        return Err(format!(
+                            "Input {}: {} of {} required signatures present",
+                            index, ordered.len(), info.required
+                        ));
+                    }
+                    ScriptBuilder::p2sh_multisig_script_sig(&ordered, &info.script_code)
+                }
+            };
+        }
+
+        Ok(tx)
+    }
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct CreatePsbtRequest {
+    pub tx_hex: String,
+    pub inputs: Vec<CreatePsbtInput>,
+}
+
+#[derive(Deserialize)]
+pub struct CreatePsbtInput {
+    pub value: u64,
+    pub script_pubkey: String,
+    pub redeem_script: Option<String>,
+    pub sighash: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct SignPsbtRequest {
+    pub psbt: PartialTransaction,
+    pub wif: Option<String>,
+    pub key_ref: Option<String>,
+    /// Restrict signing to these inputs; by default every input the key controls
+    pub inputs: Option<Vec<usize>>,
+}
+
+#[derive(Deserialize)]
+pub struct FinalizePsbtRequest {
+    pub psbt: PartialTransaction,
+}
+
+#[derive(Serialize)]
+pub struct PsbtResponse {
+    pub psbt: PartialTransaction,
+    pub status: Vec<InputStatus>,
+    pub complete: bool,
+}
+
+impl PsbtResponse {
+    fn new(psbt: PartialTransaction) -> Self {
+        let status = psbt.status();
+        let complete = status.iter().all(|s| s.complete);
+        Self { psbt, status, complete }
+    }
+}
+
+fn check_version(psbt: &PartialTransaction) -> Result<(), ServiceError> {
+    if psbt.format_version != PSBT_FORMAT_VERSION {
+        return Err(ServiceError::ValidationError(format!(
+            "Unsupported PSBT format version {}", psbt.format_version
+        )));
+    }
+    Ok(())
+}
+
+pub async fn create_psbt(req: web::Json<CreatePsbtRequest>) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+    let tx = Transaction::from_hex(&req.tx_hex)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    if tx.inputs.len() != req.inputs.len() {
+        return Err(ServiceError::ValidationError(format!(
+            "Expected metadata for {} inputs, got {}", tx.inputs.len(), req.inputs.len()
+        )));
+    }
+    if tx.inputs.iter().any(|i| !i.script_sig.is_empty()) {
+        return Err(ServiceError::ValidationError("Transaction must be unsigned".to_string()));
+    }
+
+    let mut inputs = Vec::with_capacity(req.inputs.len());
+    for (index, input) in req.inputs.into_iter().enumerate() {
+        let partial = PartialInput {
+            value: input.value,
+            script_pubkey: input.script_pubkey.to_lowercase(),
+            redeem_script: input.redeem_script.map(|r| r.to_lowercase()),
+            sighash_type: signing::parse_sighash(input.sighash.as_deref())
+                .map_err(ServiceError::ValidationError)?,
+            signatures: BTreeMap::new(),
+        };
+        partial.spend_info()
+            .map_err(|e| ServiceError::ValidationError(format!("Input {}: {}", index, e)))?;
+        inputs.push(partial);
+    }
+
+    let psbt = PartialTransaction {
+        format_version: PSBT_FORMAT_VERSION,
+        tx_hex: tx.to_hex(),
+        inputs,
+    };
+
+    tracing::info!("Created PSBT for {} ({} inputs)", tx.calculate_txid(), psbt.inputs.len());
+    Ok(HttpResponse::Ok().json(PsbtResponse::new(psbt)))
+}
+
+pub async fn sign_psbt(
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    req: web::Json<SignPsbtRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+    check_version(&req.psbt)?;
+    signing::require_key_access(&http_req, &jwt, &keys, req.key_ref.as_deref())?;
+
+    let key = signing::resolve_key(req.wif.as_deref(), req.key_ref.as_deref(), &keys, data.config.network)
+        .map_err(ServiceError::ValidationError)?;
+
+    let mut psbt = req.psbt;
+    let signed = psbt.sign(&key, req.inputs.as_deref()).map_err(ServiceError::BuildError)?;
+
+    tracing::info!("Added signatures to PSBT inputs {:?}", signed);
+    Ok(HttpResponse::Ok().json(PsbtResponse::new(psbt)))
+}
+
+pub async fn finalize_psbt(req: web::Json<FinalizePsbtRequest>) -> Result<HttpResponse, ServiceError> {
+    check_version(&req.psbt)?;
+
+    let tx = req.psbt.finalize().map_err(ServiceError::BuildError)?;
+    let txid = tx.calculate_txid();
+    tracing::info!("Finalized PSBT into {}", txid);
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "tx_hex": tx.to_hex(),
+        "txid": txid,
+        "size_bytes": tx.calculate_size(),
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use secp256k1::{Secp256k1, SecretKey};
+
+    fn key(byte: u8) -> signing::SigningKey {
+        signing::SigningKey { secret: SecretKey::from_slice(&[byte; 32]).unwrap(), compressed: true }
+    }
+
+    fn multisig_psbt(a: &signing::SigningKey, b: &signing::SigningKey) -> PartialTransaction {
+        let redeem = ScriptBuilder::multisig(2, &[a.public_key_bytes(), b.public_key_bytes()]);
+        let mut tx = Transaction::new();
+        tx.add_input("33".repeat(32), 0, 100_000);
+        tx.add_output(99_000, ScriptBuilder::p2pkh(&[5u8; 20]));
+
+        PartialTransaction {
+            format_version: PSBT_FORMAT_VERSION,
+            tx_hex: tx.to_hex(),
+            inputs: vec![PartialInput {
+                value: 100_000,
+                script_pubkey: hex::encode(ScriptBuilder::p2sh(&AddressUtils::hash160(&redeem))),
+                redeem_script: Some(hex::encode(&redeem)),
+                sighash_type: 0x41,
+                signatures: BTreeMap::new(),
+            }],
+        }
+    }
+
+    #[test]
+    fn test_two_party_signing_flow() {
+        let (a, b) = (key(1), key(2));
+        let mut psbt = multisig_psbt(&a, &b);
+
+        assert_eq!(psbt.sign(&a, None).unwrap(), vec![0]);
+        assert!(!psbt.status()[0].complete);
+        assert!(psbt.finalize().is_err());
+
+        psbt.sign(&b, None).unwrap();
+        assert!(psbt.status()[0].complete);
+
+        let tx = psbt.finalize().unwrap();
+        let script_sig = &tx.inputs[0].script_sig;
+        assert_eq!(script_sig[0], 0x00);
+
+        // Both signatures verify against the redeem script
+        let redeem = hex::decode(psbt.inputs[0].redeem_script.as_ref().unwrap()).unwrap();
+        let unsigned = Transaction::from_hex(&psbt.tx_hex).unwrap();
+        for k in [&a, &b] {
+            let sig = hex::decode(&psbt.inputs[0].signatures[&hex::encode(k.public_key_bytes())]).unwrap();
+            let pubkey = PublicKey::from_secret_key(&Secp256k1::new(), &k.secret);
+            assert!(signing::verify_input_signature(&unsigned, 0, &redeem, 100_000, &sig, &pubkey));
+        }
+    }
+
+    #[test]
+    fn test_outsider_cannot_sign() {
+        let (a, b) = (key(1), key(2));
+        let mut psbt = multisig_psbt(&a, &b);
+        assert!(psbt.sign(&key(3), None).is_err());
+    }
+
+    #[test]
+    fn test_finalize_rejects_tampered_signature() {
+        let (a, b) = (key(1), key(2));
+        let mut psbt = multisig_psbt(&a, &b);
+        psbt.sign(&a, None).unwrap();
+        psbt.sign(&b, None).unwrap();
+
+        // Re-point A's signature at B's key
+        let sig_a = psbt.inputs[0].signatures[&hex::encode(a.public_key_bytes())].clone();
+        psbt.inputs[0].signatures.insert(hex::encode(b.public_key_bytes()), sig_a);
+        assert!(psbt.finalize().is_err());
+    }
+}
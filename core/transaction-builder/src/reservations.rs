@@ -0,0 +1,311 @@
+// core/transaction-builder/src/reservations.rs
+// UTXO reservations: lock selected inputs for a TTL so concurrent builds don't double-spend
+
+use actix_web::{web, HttpResponse};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::collections::HashSet;
+use thiserror::Error;
+use uuid::Uuid;
+
+use crate::{AppState, BuildTransactionResponse, ServiceError, TxInput, UtxoInput};
+
+pub const MIN_RESERVATION_TTL_SECS: i64 = 10;
+pub const MAX_RESERVATION_TTL_SECS: i64 = 24 * 60 * 60;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct UtxoReservation {
+    pub id: Uuid,
+    pub reservation_id: Uuid,
+    pub txid: String,
+    pub vout: i32,
+    pub satoshis: i64,
+    pub reserved_for: Option<String>,
+    pub status: String,
+    pub spending_txid: Option<String>,
+    pub reserved_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub released_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Reservation {
+    pub reservation_id: Uuid,
+    pub expires_at: DateTime<Utc>,
+    /// Locked outpoints as `txid:vout`
+    pub outpoints: Vec<String>,
+}
+
+/// A built transaction together with the lock on its inputs
+#[derive(Serialize)]
+pub struct ReservedBuildResponse {
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    pub reservation: Reservation,
+}
+
+#[derive(Debug, Error)]
+pub enum ReservationError {
+    #[error("UTXOs already reserved: {}", .0.join(", "))]
+    Conflict(Vec<String>),
+    #[error("Reservation {0} is not active")]
+    NotActive(Uuid),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+impl From<ReservationError> for ServiceError {
+    fn from(e: ReservationError) -> Self {
+        match e {
+            ReservationError::Conflict(_) | ReservationError::NotActive(_) => ServiceError::Conflict(e.to_string()),
+            ReservationError::Database(db) => {
+                tracing::error!("Reservation database error: {}", db);
+                ServiceError::DatabaseError("Failed to update UTXO reservations".to_string())
+            }
+        }
+    }
+}
+
+fn outpoint(txid: &str, vout: u32) -> String {
+    format!("{}:{}", txid.to_lowercase(), vout)
+}
+
+/// Requested TTL, or the configured default, kept within sane bounds
+pub fn reservation_ttl(requested: Option<i64>, default_secs: i64) -> Duration {
+    Duration::seconds(requested.unwrap_or(default_secs).clamp(MIN_RESERVATION_TTL_SECS, MAX_RESERVATION_TTL_SECS))
+}
+
+// ============================================================================
+// STORE
+// ============================================================================
+
+/// Outpoints among `utxos` that are locked by a live reservation or already broadcast
+pub async fn locked_outpoints(pool: &PgPool, utxos: &[UtxoInput]) -> Result<HashSet<String>, sqlx::Error> {
+    let txids: Vec<String> = utxos.iter().map(|u| u.txid.to_lowercase()).collect();
+
+    let rows: Vec<(String, i32)> = sqlx::query_as(
+        r#"
+        SELECT txid, vout FROM utxo_reservations
+        WHERE txid = ANY($1)
+          AND (status = 'broadcast' OR (status = 'reserved' AND expires_at > NOW()))
+        "#
+    )
+    .bind(&txids)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows.into_iter().map(|(txid, vout)| outpoint(&txid, vout as u32)).collect())
+}
+
+/// Drop UTXOs that another build currently holds
+pub async fn filter_available(pool: &PgPool, utxos: Vec<UtxoInput>) -> Result<Vec<UtxoInput>, sqlx::Error> {
+    let locked = locked_outpoints(pool, &utxos).await?;
+    Ok(utxos.into_iter().filter(|u| !locked.contains(&outpoint(&u.txid, u.vout))).collect())
+}
+
+/// Lock every input of a built transaction, all or nothing
+pub async fn reserve(
+    pool: &PgPool,
+    inputs: &[TxInput],
+    ttl: Duration,
+    reserved_for: &str,
+) -> Result<Reservation, ReservationError> {
+    let mut db_tx = pool.begin().await?;
+
+    // Lapsed locks must leave the unique index before their outpoints can be re-locked
+    sqlx::query("UPDATE utxo_reservations SET status = 'expired' WHERE status = 'reserved' AND expires_at <= NOW()")
+        .execute(&mut *db_tx)
+        .await?;
+
+    let reservation_id = Uuid::new_v4();
+    let expires_at = Utc::now() + ttl;
+    let mut outpoints = Vec::with_capacity(inputs.len());
+    let mut conflicts = Vec::new();
+
+    for input in inputs {
+        let result = sqlx::query(
+            r#"
+            INSERT INTO utxo_reservations (reservation_id, txid, vout, satoshis, reserved_for, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (txid, vout) WHERE status IN ('reserved', 'broadcast') DO NOTHING
+            "#
+        )
+        .bind(reservation_id)
+        .bind(input.txid.to_lowercase())
+        .bind(input.vout as i32)
+        .bind(input.value as i64)
+        .bind(reserved_for)
+        .bind(expires_at)
+        .execute(&mut *db_tx)
+        .await?;
+
+        let key = outpoint(&input.txid, input.vout);
+        if result.rows_affected() == 0 {
+            conflicts.push(key);
+        } else {
+            outpoints.push(key);
+        }
+    }
+
+    if !conflicts.is_empty() {
+        db_tx.rollback().await?;
+        return Err(ReservationError::Conflict(conflicts));
+    }
+    db_tx.commit().await?;
+
+    tracing::info!("Reserved {} UTXO(s) under {} until {}", outpoints.len(), reservation_id, expires_at);
+    Ok(Reservation { reservation_id, expires_at, outpoints })
+}
+
+pub async fn release(pool: &PgPool, reservation_id: Uuid) -> Result<u64, ReservationError> {
+    let result = sqlx::query(
+        "UPDATE utxo_reservations SET status = 'released', released_at = NOW() \
+         WHERE reservation_id = $1 AND status = 'reserved'"
+    )
+    .bind(reservation_id)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ReservationError::NotActive(reservation_id));
+    }
+    Ok(result.rows_affected())
+}
+
+/// The inputs are spent; keep them locked permanently
+pub async fn mark_broadcast(pool: &PgPool, reservation_id: Uuid, spending_txid: &str) -> Result<u64, ReservationError> {
+    let result = sqlx::query(
+        "UPDATE utxo_reservations SET status = 'broadcast', spending_txid = $2 \
+         WHERE reservation_id = $1 AND status = 'reserved'"
+    )
+    .bind(reservation_id)
+    .bind(spending_txid)
+    .execute(pool)
+    .await?;
+
+    if result.rows_affected() == 0 {
+        return Err(ReservationError::NotActive(reservation_id));
+    }
+    Ok(result.rows_affected())
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct ReserveRequest {
+    pub utxos: Vec<UtxoInput>,
+    pub ttl_seconds: Option<i64>,
+    pub reserved_for: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BroadcastRequest {
+    pub spending_txid: String,
+}
+
+pub async fn reserve_utxos(
+    data: web::Data<AppState>,
+    req: web::Json<ReserveRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    if req.utxos.is_empty() {
+        return Err(ServiceError::ValidationError("No UTXOs provided".to_string()));
+    }
+
+    let inputs: Vec<TxInput> = req.utxos.iter().map(|u| TxInput {
+        txid: u.txid.clone(),
+        vout: u.vout,
+        script_sig: Vec::new(),
+        sequence: 0xffffffff,
+        value: u.satoshis,
+    }).collect();
+    let ttl = reservation_ttl(req.ttl_seconds, data.config.reservation_ttl_secs);
+    let reserved_for = req.reserved_for.as_deref().unwrap_or("manual");
+
+    let reservation = reserve(&data.db, &inputs, ttl, reserved_for).await?;
+    Ok(HttpResponse::Created().json(reservation))
+}
+
+pub async fn get_reservation(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let reservation_id = path.into_inner();
+    let rows = sqlx::query_as::<_, UtxoReservation>(
+        "SELECT * FROM utxo_reservations WHERE reservation_id = $1 ORDER BY txid, vout"
+    )
+    .bind(reservation_id)
+    .fetch_all(&data.db)
+    .await
+    .map_err(ReservationError::from)?;
+
+    if rows.is_empty() {
+        return Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "not_found",
+            "message": format!("Reservation {} not found", reservation_id)
+        })));
+    }
+    Ok(HttpResponse::Ok().json(rows))
+}
+
+pub async fn release_reservation(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, ServiceError> {
+    let reservation_id = path.into_inner();
+    let released = release(&data.db, reservation_id).await?;
+    tracing::info!("Released {} UTXO(s) from {}", released, reservation_id);
+
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "reservation_id": reservation_id,
+        "released": released
+    })))
+}
+
+pub async fn broadcast_reservation(
+    data: web::Data<AppState>,
+    path: web::Path<Uuid>,
+    req: web::Json<BroadcastRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let reservation_id = path.into_inner();
+    if req.spending_txid.len() != 64 || hex::decode(&req.spending_txid).is_err() {
+        return Err(ServiceError::ValidationError("spending_txid must be 64 hex characters".to_string()));
+    }
+
+    let spent = mark_broadcast(&data.db, reservation_id, &req.spending_txid.to_lowercase()).await?;
+    Ok(HttpResponse::Ok().json(serde_json::json!({
+        "reservation_id": reservation_id,
+        "spending_txid": req.spending_txid.to_lowercase(),
+        "spent": spent
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reservation_ttl_bounds() {
+        assert_eq!(reservation_ttl(None, 600), Duration::seconds(600));
+        assert_eq!(reservation_ttl(Some(1), 600), Duration::seconds(MIN_RESERVATION_TTL_SECS));
+        assert_eq!(reservation_ttl(Some(i64::MAX), 600), Duration::seconds(MAX_RESERVATION_TTL_SECS));
+    }
+
+    #[test]
+    fn test_outpoint_is_case_insensitive() {
+        assert_eq!(outpoint("ABCD", 1), outpoint("abcd", 1));
+        assert_ne!(outpoint("abcd", 1), outpoint("abcd", 2));
+    }
+
+    #[test]
+    fn test_conflict_maps_to_409() {
+        let err: ServiceError = ReservationError::Conflict(vec!["ab:0".to_string()]).into();
+        assert!(matches!(err, ServiceError::Conflict(ref msg) if msg.contains("ab:0")));
+    }
+}
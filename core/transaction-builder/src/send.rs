@@ -0,0 +1,470 @@
+// core/transaction-builder/src/send.rs
+// Build, sign, reserve and broadcast a payment in one idempotent call keyed by a client request id
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::JwtManager;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::broadcast::Broadcaster;
+use crate::coin_selection;
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::reservations::{self, ReservationError};
+use crate::signing::{self, KeyStore, SignInputRequest};
+use crate::{AddressUtils, AppState, Config, ScriptBuilder, ServiceError, Transaction, UtxoInput};
+
+const MAX_CLIENT_REQUEST_ID_LEN: usize = 128;
+/// A submission still `building` after this long is taken to be abandoned and may be claimed again
+const STALE_BUILD_SECS: i64 = 120;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct BuildAndSendRequest {
+    /// Caller-chosen idempotency key; repeating it never pays twice
+    pub client_request_id: String,
+    /// Stored key that owns `utxos` and signs every input
+    pub key_ref: String,
+    pub to_address: String,
+    pub amount_satoshis: u64,
+    pub utxos: Vec<UtxoInput>,
+    /// Defaults to the signing key's own address
+    pub change_address: Option<String>,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+/// Lifecycle: building -> signed -> broadcast, or building -> failed.
+/// A `signed` submission whose broadcast failed is re-broadcast when the request is repeated.
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TxSubmission {
+    pub id: Uuid,
+    pub client_request_id: String,
+    pub status: String,
+    pub to_address: String,
+    pub amount_satoshis: i64,
+    pub key_ref: String,
+    pub txid: Option<String>,
+    pub tx_hex: Option<String>,
+    pub fee_satoshis: Option<i64>,
+    pub reservation_id: Option<Uuid>,
+    pub broadcast_via: Option<String>,
+    pub broadcast_attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub broadcast_at: Option<DateTime<Utc>>,
+}
+
+impl TxSubmission {
+    /// Whether a repeated request asks for the same payment
+    pub fn matches(&self, req: &BuildAndSendRequest) -> bool {
+        self.to_address == req.to_address
+            && self.amount_satoshis == req.amount_satoshis as i64
+            && self.key_ref == req.key_ref
+    }
+}
+
+enum Claim {
+    /// This call owns the submission and must build it
+    Claimed(TxSubmission),
+    Existing(TxSubmission),
+}
+
+// ============================================================================
+// BUILDING
+// ============================================================================
+
+pub fn validate_request(req: &BuildAndSendRequest, config: &Config) -> Result<Vec<u8>, String> {
+    let id = req.client_request_id.trim();
+    if id.is_empty() || id.len() > MAX_CLIENT_REQUEST_ID_LEN {
+        return Err(format!("client_request_id must be 1-{} characters", MAX_CLIENT_REQUEST_ID_LEN));
+    }
+    if req.amount_satoshis <= config.dust_threshold || req.amount_satoshis > i64::MAX as u64 {
+        return Err(format!("amount_satoshis must be above the dust threshold of {}", config.dust_threshold));
+    }
+    if req.utxos.is_empty() {
+        return Err("No UTXOs provided".to_string());
+    }
+    AddressUtils::script_for_address(&req.to_address, config.network).map_err(|e| format!("to_address: {}", e))
+}
+
+/// Unsigned payment of `amount` to `to_script`, funded from `utxos` in order
+pub fn build_send(
+    to_script: Vec<u8>,
+    amount: u64,
+    utxos: &[UtxoInput],
+    change_script: &[u8],
+    fee_per_byte: u64,
+    dust_threshold: u64,
+) -> Result<Transaction, String> {
+    let mut tx = Transaction::new();
+    tx.add_output(amount, to_script);
+    coin_selection::fund_in_order(tx, utxos, change_script, fee_per_byte, dust_threshold)
+}
+
+// ============================================================================
+// PERSISTENCE
+// ============================================================================
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    tracing::error!("Transaction submission database error: {}", e);
+    ServiceError::DatabaseError("Failed to access transaction submissions".to_string())
+}
+
+async fn claim(db: &PgPool, req: &BuildAndSendRequest) -> Result<Claim, ServiceError> {
+    let inserted = sqlx::query_as::<_, TxSubmission>(
+        "INSERT INTO tx_submissions (client_request_id, to_address, amount_satoshis, key_ref) \
+         VALUES ($1, $2, $3, $4) ON CONFLICT (client_request_id) DO NOTHING RETURNING *"
+    )
+    .bind(&req.client_request_id)
+    .bind(&req.to_address)
+    .bind(req.amount_satoshis as i64)
+    .bind(&req.key_ref)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    if let Some(submission) = inserted {
+        return Ok(Claim::Claimed(submission));
+    }
+
+    // Nothing left the building for failed or abandoned attempts, so they can be retried
+    let reclaimed = sqlx::query_as::<_, TxSubmission>(
+        "UPDATE tx_submissions SET status = 'building', last_error = NULL, updated_at = NOW() \
+         WHERE client_request_id = $1 AND to_address = $2 AND amount_satoshis = $3 AND key_ref = $4 \
+           AND (status = 'failed' OR (status = 'building' AND updated_at < NOW() - make_interval(secs => $5))) \
+         RETURNING *"
+    )
+    .bind(&req.client_request_id)
+    .bind(&req.to_address)
+    .bind(req.amount_satoshis as i64)
+    .bind(&req.key_ref)
+    .bind(STALE_BUILD_SECS as f64)
+    .fetch_optional(db)
+    .await
+    .map_err(db_error)?;
+    if let Some(submission) = reclaimed {
+        return Ok(Claim::Claimed(submission));
+    }
+
+    fetch(db, &req.client_request_id)
+        .await?
+        .map(Claim::Existing)
+        .ok_or_else(|| ServiceError::Conflict("Submission changed concurrently, retry".to_string()))
+}
+
+async fn fetch(db: &PgPool, client_request_id: &str) -> Result<Option<TxSubmission>, ServiceError> {
+    sqlx::query_as::<_, TxSubmission>("SELECT * FROM tx_submissions WHERE client_request_id = $1")
+        .bind(client_request_id)
+        .fetch_optional(db)
+        .await
+        .map_err(db_error)
+}
+
+async fn mark_failed(db: &PgPool, id: Uuid, error: &str) {
+    let result = sqlx::query(
+        "UPDATE tx_submissions SET status = 'failed', last_error = $2, updated_at = NOW() WHERE id = $1"
+    )
+    .bind(id)
+    .bind(error)
+    .execute(db)
+    .await;
+    if let Err(e) = result {
+        tracing::error!("Failed to mark submission {} failed: {}", id, e);
+    }
+}
+
+/// Build, sign and reserve a claimed submission, leaving it `signed`
+async fn build_and_sign(
+    data: &AppState,
+    keys: &KeyStore,
+    fees: &FeeOracle,
+    http_req: &HttpRequest,
+    submission: &TxSubmission,
+    req: BuildAndSendRequest,
+    to_script: Vec<u8>,
+) -> Result<TxSubmission, ServiceError> {
+    let config = &data.config;
+    let key = keys
+        .get(&req.key_ref)
+        .ok_or_else(|| ServiceError::ValidationError(format!("Unknown key reference: {}", req.key_ref)))?;
+    let change_script = match &req.change_address {
+        Some(address) => AddressUtils::script_for_address(address, config.network)
+            .map_err(|e| ServiceError::ValidationError(format!("change_address: {}", e)))?,
+        None => ScriptBuilder::p2pkh(&key.pubkey_hash()),
+    };
+
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+    let utxos = reservations::filter_available(&data.db, req.utxos)
+        .await
+        .map_err(ReservationError::from)?;
+    let mut tx = build_send(to_script, req.amount_satoshis, &utxos, &change_script, fee_per_byte, config.dust_threshold)
+        .map_err(ServiceError::BuildError)?;
+
+    let inputs: Vec<SignInputRequest> = tx.inputs.iter().enumerate().map(|(index, input)| SignInputRequest {
+        index,
+        value: input.value,
+        wif: None,
+        key_ref: Some(req.key_ref.clone()),
+        script_pubkey: None,
+        sighash: None,
+    }).collect();
+    signing::sign_p2pkh_inputs(&mut tx, &inputs, keys, config.network).map_err(ServiceError::BuildError)?;
+
+    let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, config.reservation_ttl_secs);
+    let reservation = reservations::reserve(&data.db, &tx.inputs, ttl, &format!("send:{}", req.client_request_id)).await?;
+
+    if let Err(e) = history::record(data, http_req, TxType::Send, &tx).await {
+        if let Err(release_error) = reservations::release(&data.db, reservation.reservation_id).await {
+            tracing::error!("Failed to release reservation {}: {}", reservation.reservation_id, release_error);
+        }
+        return Err(e);
+    }
+
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    let signed = sqlx::query_as::<_, TxSubmission>(
+        "UPDATE tx_submissions SET status = 'signed', txid = $2, tx_hex = $3, fee_satoshis = $4, \
+         reservation_id = $5, updated_at = NOW() WHERE id = $1 RETURNING *"
+    )
+    .bind(submission.id)
+    .bind(tx.calculate_txid())
+    .bind(tx.to_hex())
+    .bind((total_in - total_out) as i64)
+    .bind(reservation.reservation_id)
+    .fetch_one(&data.db)
+    .await;
+
+    match signed {
+        Ok(signed) => Ok(signed),
+        Err(e) => {
+            // Never broadcast a transaction we could not record
+            if let Err(release_error) = reservations::release(&data.db, reservation.reservation_id).await {
+                tracing::error!("Failed to release reservation {}: {}", reservation.reservation_id, release_error);
+            }
+            Err(db_error(e))
+        }
+    }
+}
+
+/// Broadcast a `signed` submission and record the outcome
+async fn send(db: &PgPool, broadcaster: &Broadcaster, submission: TxSubmission) -> Result<HttpResponse, ServiceError> {
+    let (Some(tx_hex), Some(txid)) = (&submission.tx_hex, &submission.txid) else {
+        return Err(ServiceError::Conflict(format!("Submission {} has no signed transaction", submission.client_request_id)));
+    };
+
+    match broadcaster.broadcast(tx_hex, txid).await {
+        Ok(backend) => {
+            let submission = sqlx::query_as::<_, TxSubmission>(
+                "UPDATE tx_submissions SET status = 'broadcast', broadcast_via = $2, \
+                 broadcast_attempts = broadcast_attempts + 1, last_error = NULL, \
+                 broadcast_at = NOW(), updated_at = NOW() WHERE id = $1 RETURNING *"
+            )
+            .bind(submission.id)
+            .bind(backend.as_str())
+            .fetch_one(db)
+            .await
+            .map_err(db_error)?;
+
+            if let Some(reservation_id) = submission.reservation_id {
+                if let Err(e) = reservations::mark_broadcast(db, reservation_id, txid).await {
+                    tracing::warn!("Broadcast {} but could not mark reservation {}: {}", txid, reservation_id, e);
+                }
+            }
+            tracing::info!("Broadcast {} for {} via {}", txid, submission.client_request_id, backend.as_str());
+            Ok(HttpResponse::Ok().json(submission))
+        }
+        Err(e) => {
+            tracing::error!("Broadcast of {} for {} failed: {}", txid, submission.client_request_id, e);
+            // Stays `signed` with its inputs reserved; repeating the request retries this same transaction
+            let submission = sqlx::query_as::<_, TxSubmission>(
+                "UPDATE tx_submissions SET broadcast_attempts = broadcast_attempts + 1, last_error = $2, \
+                 updated_at = NOW() WHERE id = $1 RETURNING *"
+            )
+            .bind(submission.id)
+            .bind(&e)
+            .fetch_one(db)
+            .await
+            .map_err(db_error)?;
+            Ok(HttpResponse::BadGateway().json(serde_json::json!({
+                "error": "broadcast_failed",
+                "message": e,
+                "submission": submission,
+            })))
+        }
+    }
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+pub async fn build_and_send(
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    fees: web::Data<FeeOracle>,
+    broadcaster: web::Data<Broadcaster>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    req: web::Json<BuildAndSendRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let req = req.into_inner();
+    let to_script = validate_request(&req, &data.config).map_err(ServiceError::ValidationError)?;
+    signing::require_key_access(&http_req, &jwt, &keys, [req.key_ref.as_str()])?;
+
+    let submission = match claim(&data.db, &req).await? {
+        Claim::Claimed(submission) => {
+            match build_and_sign(&data, &keys, &fees, &http_req, &submission, req, to_script).await {
+                Ok(signed) => signed,
+                Err(e) => {
+                    mark_failed(&data.db, submission.id, &e.to_string()).await;
+                    return Err(e);
+                }
+            }
+        }
+        Claim::Existing(submission) => {
+            if !submission.matches(&req) {
+                return Err(ServiceError::Conflict(format!(
+                    "client_request_id {} was already used for a different payment", req.client_request_id
+                )));
+            }
+            match submission.status.as_str() {
+                "broadcast" => return Ok(HttpResponse::Ok().json(submission)),
+                "signed" => submission,
+                _ => {
+                    return Err(ServiceError::Conflict(format!(
+                        "Submission {} is still being built", req.client_request_id
+                    )));
+                }
+            }
+        }
+    };
+
+    send(&data.db, &broadcaster, submission).await
+}
+
+pub async fn get_submission(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    let client_request_id = path.into_inner();
+    match fetch(&data.db, &client_request_id).await? {
+        Some(submission) => Ok(HttpResponse::Ok().json(submission)),
+        None => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "not_found",
+            "message": format!("Submission {} not found", client_request_id)
+        }))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Network;
+
+    const ADDRESS: &str = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+
+    fn config() -> Config {
+        Config {
+            database_url: String::new(),
+            network: Network::Mainnet,
+            default_fee_per_byte: 1,
+            max_data_carrier_bytes: 100_000,
+            dust_threshold: 546,
+            long_term_fee_per_byte: 1,
+            reservation_ttl_secs: 600,
+            max_tx_size_bytes: 1_000_000,
+            public_url: "http://localhost:8085".to_string(),
+        }
+    }
+
+    fn utxo(satoshis: u64) -> UtxoInput {
+        UtxoInput { txid: "aa".repeat(32), vout: 0, satoshis, confirmations: None }
+    }
+
+    fn request() -> BuildAndSendRequest {
+        BuildAndSendRequest {
+            client_request_id: "payout-42".to_string(),
+            key_ref: "hot".to_string(),
+            to_address: ADDRESS.to_string(),
+            amount_satoshis: 10_000,
+            utxos: vec![utxo(50_000)],
+            change_address: None,
+            fee_per_byte: None,
+            fee_policy: None,
+            reservation_ttl_seconds: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert_eq!(validate_request(&request(), &config()).unwrap(), ScriptBuilder::p2pkh(&AddressUtils::decode_address(ADDRESS).unwrap()));
+
+        let mut req = request();
+        req.client_request_id = "  ".to_string();
+        assert!(validate_request(&req, &config()).is_err());
+
+        let mut req = request();
+        req.client_request_id = "x".repeat(MAX_CLIENT_REQUEST_ID_LEN + 1);
+        assert!(validate_request(&req, &config()).is_err());
+
+        let mut req = request();
+        req.amount_satoshis = 546;
+        assert!(validate_request(&req, &config()).is_err());
+
+        let mut req = request();
+        req.utxos.clear();
+        assert!(validate_request(&req, &config()).is_err());
+
+        let mut req = request();
+        req.to_address = "mrCDrCybB6J1vRfbwM5hemdJz73FwDBC8r".to_string();
+        assert!(validate_request(&req, &config()).is_err());
+    }
+
+    #[test]
+    fn test_build_send_adds_change() {
+        let to = ScriptBuilder::p2pkh(&[1; 20]);
+        let change = ScriptBuilder::p2pkh(&[2; 20]);
+        let tx = build_send(to.clone(), 10_000, &[utxo(5_000), utxo(50_000)], &change, 1, 546).unwrap();
+
+        assert_eq!(tx.inputs.len(), 2);
+        assert_eq!(tx.outputs[0].value, 10_000);
+        assert_eq!(tx.outputs[0].script_pubkey, to);
+        assert_eq!(tx.outputs[1].script_pubkey, change);
+        assert!(build_send(to, 100_000, &[utxo(50_000)], &change, 1, 546).is_err());
+    }
+
+    #[test]
+    fn test_repeat_must_match() {
+        let now = Utc::now();
+        let submission = TxSubmission {
+            id: Uuid::new_v4(),
+            client_request_id: "payout-42".to_string(),
+            status: "signed".to_string(),
+            to_address: ADDRESS.to_string(),
+            amount_satoshis: 10_000,
+            key_ref: "hot".to_string(),
+            txid: None,
+            tx_hex: None,
+            fee_satoshis: None,
+            reservation_id: None,
+            broadcast_via: None,
+            broadcast_attempts: 0,
+            last_error: None,
+            created_at: now,
+            updated_at: now,
+            broadcast_at: None,
+        };
+        assert!(submission.matches(&request()));
+
+        let mut req = request();
+        req.amount_satoshis = 10_001;
+        assert!(!submission.matches(&req));
+    }
+}
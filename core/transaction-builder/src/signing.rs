@@ -0,0 +1,556 @@
+// core/transaction-builder/src/signing.rs
+// ECDSA signing with the BSV sighash algorithm (BIP143-style digest + FORKID)
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use bsv_bank_common::{auth::extract_bearer_token, Claims, JwtManager, SERVICE_PERMISSION};
+use secp256k1::{ecdsa::Signature, Message, PublicKey, Secp256k1, SecretKey};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::timelock::Timelock;
+use crate::{codec, AddressUtils, AppState, Network, ScriptBuilder, ServiceError, Transaction};
+
+pub const SIGHASH_ALL: u32 = 0x01;
+pub const SIGHASH_NONE: u32 = 0x02;
+pub const SIGHASH_SINGLE: u32 = 0x03;
+pub const SIGHASH_FORKID: u32 = 0x40;
+pub const SIGHASH_ANYONECANPAY: u32 = 0x80;
+
+// ============================================================================
+// SIGHASH
+// ============================================================================
+
+/// Parse "all", "none" or "single", optionally suffixed with "|anyonecanpay".
+/// FORKID is always set; BSV nodes reject signatures without it.
+pub fn parse_sighash(name: Option<&str>) -> Result<u32, String> {
+    let name = name.unwrap_or("all").to_ascii_lowercase();
+    let (base, anyone_can_pay) = match name.split_once('|') {
+        Some((base, "anyonecanpay")) => (base.to_string(), true),
+        Some((_, other)) => return Err(format!("Unknown sighash modifier: {}", other)),
+        None => (name, false),
+    };
+
+    let base = match base.as_str() {
+        "all" => SIGHASH_ALL,
+        "none" => SIGHASH_NONE,
+        "single" => SIGHASH_SINGLE,
+        other => return Err(format!("Unknown sighash type: {}", other)),
+    };
+
+    Ok(base | SIGHASH_FORKID | if anyone_can_pay { SIGHASH_ANYONECANPAY } else { 0 })
+}
+
+fn outpoint_bytes(txid: &str, vout: u32) -> Vec<u8> {
+    let mut bytes = hex::decode(txid).unwrap_or_else(|_| vec![0u8; 32]);
+    bytes.reverse();
+    bytes.extend_from_slice(&vout.to_le_bytes());
+    bytes
+}
+
+/// Build the signature preimage for `input_index` spending an output locked by
+/// `script_code` and worth `value` satoshis
+pub fn sighash_preimage(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    value: u64,
+    sighash_type: u32,
+) -> Vec<u8> {
+    let base = sighash_type & 0x1f;
+    let anyone_can_pay = sighash_type & SIGHASH_ANYONECANPAY != 0;
+
+    let hash_prevouts = if anyone_can_pay {
+        [0u8; 32]
+    } else {
+        let mut buf = Vec::with_capacity(tx.inputs.len() * 36);
+        for input in &tx.inputs {
+            buf.extend_from_slice(&outpoint_bytes(&input.txid, input.vout));
+        }
+        AddressUtils::double_sha256(&buf)
+    };
+
+    let hash_sequence = if anyone_can_pay || base == SIGHASH_SINGLE || base == SIGHASH_NONE {
+        [0u8; 32]
+    } else {
+        let mut buf = Vec::with_capacity(tx.inputs.len() * 4);
+        for input in &tx.inputs {
+            buf.extend_from_slice(&input.sequence.to_le_bytes());
+        }
+        AddressUtils::double_sha256(&buf)
+    };
+
+    let serialize_output = |buf: &mut Vec<u8>, index: usize| {
+        let output = &tx.outputs[index];
+        buf.extend_from_slice(&output.value.to_le_bytes());
+        codec::write_varint(buf, output.script_pubkey.len() as u64);
+        buf.extend_from_slice(&output.script_pubkey);
+    };
+
+    let hash_outputs = if base != SIGHASH_SINGLE && base != SIGHASH_NONE {
+        let mut buf = Vec::new();
+        for index in 0..tx.outputs.len() {
+            serialize_output(&mut buf, index);
+        }
+        AddressUtils::double_sha256(&buf)
+    } else if base == SIGHASH_SINGLE && input_index < tx.outputs.len() {
+        let mut buf = Vec::new();
+        serialize_output(&mut buf, input_index);
+        AddressUtils::double_sha256(&buf)
+    } else {
+        [0u8; 32]
+    };
+
+    let input = &tx.inputs[input_index];
+    let mut preimage = Vec::with_capacity(156 + script_code.len());
+    preimage.extend_from_slice(&tx.version.to_le_bytes());
+    preimage.extend_from_slice(&hash_prevouts);
+    preimage.extend_from_slice(&hash_sequence);
+    preimage.extend_from_slice(&outpoint_bytes(&input.txid, input.vout));
+    codec::write_varint(&mut preimage, script_code.len() as u64);
+    preimage.extend_from_slice(script_code);
+    preimage.extend_from_slice(&value.to_le_bytes());
+    preimage.extend_from_slice(&input.sequence.to_le_bytes());
+    preimage.extend_from_slice(&hash_outputs);
+    preimage.extend_from_slice(&tx.locktime.to_le_bytes());
+    preimage.extend_from_slice(&sighash_type.to_le_bytes());
+    preimage
+}
+
+pub fn sighash(tx: &Transaction, input_index: usize, script_code: &[u8], value: u64, sighash_type: u32) -> [u8; 32] {
+    AddressUtils::double_sha256(&sighash_preimage(tx, input_index, script_code, value, sighash_type))
+}
+
+/// DER signature with the sighash byte appended, ready for a scriptSig
+pub fn sign_input(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    value: u64,
+    sighash_type: u32,
+    key: &SecretKey,
+) -> Vec<u8> {
+    let digest = sighash(tx, input_index, script_code, value, sighash_type);
+    let message = Message::from_digest_slice(&digest).expect("sighash is 32 bytes");
+    let signature = Secp256k1::signing_only().sign_ecdsa(&message, key);
+
+    let mut bytes = signature.serialize_der().to_vec();
+    bytes.push(sighash_type as u8);
+    bytes
+}
+
+/// Check a scriptSig-style signature (DER + sighash byte) against `pubkey`
+pub fn verify_input_signature(
+    tx: &Transaction,
+    input_index: usize,
+    script_code: &[u8],
+    value: u64,
+    signature: &[u8],
+    pubkey: &PublicKey,
+) -> bool {
+    let (sighash_byte, der) = match signature.split_last() {
+        Some(parts) => parts,
+        None => return false,
+    };
+    let signature = match Signature::from_der(der) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    let digest = sighash(tx, input_index, script_code, value, *sighash_byte as u32);
+    let message = Message::from_digest_slice(&digest).expect("sighash is 32 bytes");
+    Secp256k1::verification_only().verify_ecdsa(&message, &signature, pubkey).is_ok()
+}
+
+// ============================================================================
+// KEYS
+// ============================================================================
+
+#[derive(Clone)]
+pub struct SigningKey {
+    pub secret: SecretKey,
+    pub compressed: bool,
+}
+
+impl SigningKey {
+    pub fn public_key_bytes(&self) -> Vec<u8> {
+        let pubkey = PublicKey::from_secret_key(&Secp256k1::signing_only(), &self.secret);
+        if self.compressed {
+            pubkey.serialize().to_vec()
+        } else {
+            pubkey.serialize_uncompressed().to_vec()
+        }
+    }
+
+    pub fn pubkey_hash(&self) -> Vec<u8> {
+        AddressUtils::hash160(&self.public_key_bytes())
+    }
+}
+
+/// Decode a WIF private key, checking it belongs to `network`
+pub fn decode_wif(wif: &str, network: Network) -> Result<SigningKey, String> {
+    let bytes = bs58::decode(wif.trim())
+        .into_vec()
+        .map_err(|e| format!("Invalid WIF: {}", e))?;
+
+    if bytes.len() != 37 && bytes.len() != 38 {
+        return Err("Invalid WIF length".to_string());
+    }
+    let (payload, checksum) = bytes.split_at(bytes.len() - 4);
+    if AddressUtils::double_sha256(payload)[..4] != *checksum {
+        return Err("Invalid WIF checksum".to_string());
+    }
+
+    if payload[0] != network.wif_version() {
+        return Err(format!("WIF key is not for {}", network));
+    }
+
+    let compressed = match payload.len() {
+        33 => false,
+        34 if payload[33] == 0x01 => true,
+        _ => return Err("Invalid WIF compression flag".to_string()),
+    };
+
+    let secret = SecretKey::from_slice(&payload[1..33])
+        .map_err(|e| format!("Invalid private key: {}", e))?;
+
+    Ok(SigningKey { secret, compressed })
+}
+
+/// Named hot-wallet keys, so callers can sign without handling raw WIFs.
+/// Loaded from `TX_SIGNING_KEYS` as `name=WIF` pairs separated by commas. Each key signs only
+/// for the services listed in `TX_SIGNING_KEY_CALLERS`, as `name=service|service` pairs.
+#[derive(Clone, Default)]
+pub struct KeyStore {
+    keys: HashMap<String, SigningKey>,
+    callers: HashMap<String, HashSet<String>>,
+}
+
+impl KeyStore {
+    pub fn from_env(network: Network) -> Self {
+        let mut keys = HashMap::new();
+
+        if let Ok(spec) = std::env::var("TX_SIGNING_KEYS") {
+            for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                match entry.split_once('=') {
+                    Some((name, wif)) => match decode_wif(wif, network) {
+                        Ok(key) => {
+                            keys.insert(name.trim().to_string(), key);
+                        }
+                        Err(e) => tracing::error!("Ignoring signing key {}: {}", name, e),
+                    },
+                    None => tracing::error!("Ignoring malformed TX_SIGNING_KEYS entry"),
+                }
+            }
+        }
+
+        let mut callers: HashMap<String, HashSet<String>> = HashMap::new();
+        if let Ok(spec) = std::env::var("TX_SIGNING_KEY_CALLERS") {
+            for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                match entry.split_once('=') {
+                    Some((name, services)) => callers.entry(name.trim().to_string()).or_default().extend(
+                        services.split('|').map(str::trim).filter(|s| !s.is_empty()).map(str::to_string),
+                    ),
+                    None => tracing::error!("Ignoring malformed TX_SIGNING_KEY_CALLERS entry"),
+                }
+            }
+        }
+        for name in keys.keys().filter(|name| !callers.contains_key(*name)) {
+            tracing::warn!("Signing key {} has no callers in TX_SIGNING_KEY_CALLERS and cannot be used", name);
+        }
+
+        Self { keys, callers }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&SigningKey> {
+        self.keys.get(name)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Whether `service` is allowed to sign with `name`
+    pub fn allows(&self, name: &str, service: &str) -> bool {
+        self.callers.get(name).is_some_and(|services| services.contains(service))
+    }
+}
+
+/// Claims of a valid service token on the request
+fn service_claims(req: &HttpRequest, jwt: &JwtManager) -> Result<Claims, ServiceError> {
+    let header = req
+        .headers()
+        .get("Authorization")
+        .and_then(|h| h.to_str().ok())
+        .ok_or_else(|| ServiceError::Unauthorized("Signing with a stored key requires a service token".to_string()))?;
+    let token = extract_bearer_token(header).map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+    let claims = jwt.verify_token(&token).map_err(|e| ServiceError::Unauthorized(e.to_string()))?;
+
+    if !claims.has_permission(SERVICE_PERMISSION) {
+        return Err(ServiceError::Forbidden("A service token is required".to_string()));
+    }
+    Ok(claims)
+}
+
+/// Stored keys only sign for the sibling services allowed to use them, identified by their
+/// service token. Callers that bring their own WIF need none, so requests naming no
+/// `key_ref` pass without one.
+pub fn require_key_access<'a>(
+    req: &HttpRequest,
+    jwt: &JwtManager,
+    keys: &KeyStore,
+    key_refs: impl IntoIterator<Item = &'a str>,
+) -> Result<(), ServiceError> {
+    let mut key_refs = key_refs.into_iter().peekable();
+    if key_refs.peek().is_none() {
+        return Ok(());
+    }
+    let claims = service_claims(req, jwt)?;
+    for key_ref in key_refs {
+        if !keys.allows(key_ref, &claims.sub) {
+            return Err(ServiceError::Forbidden(format!("{} may not sign with key {}", claims.sub, key_ref)));
+        }
+    }
+    Ok(())
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[derive(Deserialize)]
+pub struct SignTransactionRequest {
+    pub tx_hex: String,
+    pub inputs: Vec<SignInputRequest>,
+}
+
+#[derive(Deserialize)]
+pub struct SignInputRequest {
+    pub index: usize,
+    /// Value of the output being spent (not part of the transaction itself)
+    pub value: u64,
+    pub wif: Option<String>,
+    pub key_ref: Option<String>,
+    /// Locking script of the spent output (P2PKH, or P2PKH behind a CLTV/CSV lock); defaults to P2PKH of the key
+    pub script_pubkey: Option<String>,
+    pub sighash: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SignTransactionResponse {
+    pub tx_hex: String,
+    pub txid: String,
+    pub size_bytes: usize,
+    pub signed_inputs: Vec<usize>,
+}
+
+/// Key from either an inline WIF or a named key in the store
+pub fn resolve_key(
+    wif: Option<&str>,
+    key_ref: Option<&str>,
+    keys: &KeyStore,
+    network: Network,
+) -> Result<SigningKey, String> {
+    match (wif, key_ref) {
+        (Some(wif), None) => decode_wif(wif, network),
+        (None, Some(name)) => keys
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown key reference: {}", name)),
+        _ => Err("Provide exactly one of wif or key_ref".to_string()),
+    }
+}
+
+/// Sign the requested P2PKH inputs in place
+pub fn sign_p2pkh_inputs(
+    tx: &mut Transaction,
+    inputs: &[SignInputRequest],
+    keys: &KeyStore,
+    network: Network,
+) -> Result<Vec<usize>, String> {
+    // The FORKID digest does not commit to other inputs' scriptSigs, so each
+    // input can be filled in as soon as it is signed.
+    let mut signed = Vec::with_capacity(inputs.len());
+
+    for input in inputs {
+        if input.index >= tx.inputs.len() {
+            return Err(format!("Input index {} out of range", input.index));
+        }
+
+        let key = resolve_key(input.wif.as_deref(), input.key_ref.as_deref(), keys, network).map_err(|e| format!("Input {}: {}", input.index, e))?;
+        let sighash_type = parse_sighash(input.sighash.as_deref())?;
+        let expected_script = ScriptBuilder::p2pkh(&key.pubkey_hash());
+
+        let script_code = match &input.script_pubkey {
+            Some(script_hex) => {
+                let script = hex::decode(script_hex)
+                    .map_err(|_| format!("Input {}: invalid script_pubkey hex", input.index))?;
+                // Timelocked outputs unlock like P2PKH once the lock has passed
+                let locked_to_key = Timelock::parse(&script).is_some_and(|(_, hash)| hash == key.pubkey_hash());
+                if script != expected_script && !locked_to_key {
+                    return Err(format!("Input {}: key does not match the P2PKH script being spent", input.index));
+                }
+                script
+            }
+            None => expected_script,
+        };
+
+        let signature = sign_input(tx, input.index, &script_code, input.value, sighash_type, &key.secret);
+        tx.inputs[input.index].script_sig = ScriptBuilder::p2pkh_script_sig(&signature, &key.public_key_bytes());
+        tx.inputs[input.index].value = input.value;
+        signed.push(input.index);
+    }
+
+    Ok(signed)
+}
+
+pub async fn sign_transaction(
+    data: web::Data<AppState>,
+    keys: web::Data<KeyStore>,
+    jwt: web::Data<JwtManager>,
+    http_req: HttpRequest,
+    req: web::Json<SignTransactionRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    if req.inputs.is_empty() {
+        return Err(ServiceError::ValidationError("No inputs to sign".to_string()));
+    }
+    require_key_access(&http_req, &jwt, &keys, req.inputs.iter().filter_map(|input| input.key_ref.as_deref()))?;
+
+    let mut tx = Transaction::from_hex(&req.tx_hex)
+        .map_err(|e| ServiceError::ValidationError(e.to_string()))?;
+
+    let signed_inputs = sign_p2pkh_inputs(&mut tx, &req.inputs, &keys, data.config.network)
+        .map_err(ServiceError::BuildError)?;
+
+    let txid = tx.calculate_txid();
+    tracing::info!("Signed {} input(s) of {}", signed_inputs.len(), txid);
+
+    Ok(HttpResponse::Ok().json(SignTransactionResponse {
+        tx_hex: tx.to_hex(),
+        txid,
+        size_bytes: tx.calculate_size(),
+        signed_inputs,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Well-known test key (Bitcoin wiki WIF example)
+    const KEY_HEX: &str = "0c28fca386c7a227600b2fe50b7cae11ec86d3bf1fbe471be89827e19d72aa1d";
+    const WIF_UNCOMPRESSED: &str = "5HueCGU8rMjxEXxiPuD5BDku4MkFqeZyd4dZ1jvhTVqvbTLvyTJ";
+    const WIF_COMPRESSED: &str = "KwdMAjGmerYanjeui5SHS7JkmpZvVipYvB2LJGU1ZxJwYvP98617";
+
+    fn spend_tx() -> Transaction {
+        let mut tx = Transaction::new();
+        tx.add_input("11".repeat(32), 0, 50_000);
+        tx.add_input("22".repeat(32), 1, 20_000);
+        tx.add_output(60_000, ScriptBuilder::p2pkh(&[9u8; 20]));
+        tx
+    }
+
+    #[test]
+    fn test_decode_wif() {
+        let key = decode_wif(WIF_UNCOMPRESSED, Network::Mainnet).unwrap();
+        assert!(!key.compressed);
+        assert_eq!(hex::encode(key.secret.secret_bytes()), KEY_HEX);
+
+        let key = decode_wif(WIF_COMPRESSED, Network::Mainnet).unwrap();
+        assert!(key.compressed);
+        assert_eq!(key.public_key_bytes().len(), 33);
+
+        assert!(decode_wif(WIF_COMPRESSED, Network::Testnet).is_err());
+    }
+
+    #[test]
+    fn test_parse_sighash() {
+        assert_eq!(parse_sighash(None).unwrap(), 0x41);
+        assert_eq!(parse_sighash(Some("single|anyonecanpay")).unwrap(), 0xc3);
+        assert!(parse_sighash(Some("bogus")).is_err());
+    }
+
+    #[test]
+    fn test_preimage_layout() {
+        let tx = spend_tx();
+        let script = ScriptBuilder::p2pkh(&[1u8; 20]);
+        let preimage = sighash_preimage(&tx, 1, &script, 20_000, 0x41);
+
+        // version + 3 hashes + outpoint + script + value + sequence + locktime + type
+        assert_eq!(preimage.len(), 4 + 32 + 32 + 36 + 1 + 25 + 8 + 4 + 32 + 4 + 4);
+        assert_eq!(&preimage[preimage.len() - 4..], &[0x41, 0, 0, 0]);
+
+        // ANYONECANPAY blanks the prevouts and sequence hashes
+        let acp = sighash_preimage(&tx, 1, &script, 20_000, 0xc1);
+        assert_eq!(&acp[4..68], &[0u8; 64][..]);
+    }
+
+    #[test]
+    fn test_sign_and_verify_p2pkh() {
+        let mut tx = spend_tx();
+        let key = decode_wif(WIF_COMPRESSED, Network::Mainnet).unwrap();
+        let script = ScriptBuilder::p2pkh(&key.pubkey_hash());
+
+        let requests = vec![SignInputRequest {
+            index: 0,
+            value: 50_000,
+            wif: Some(WIF_COMPRESSED.to_string()),
+            key_ref: None,
+            script_pubkey: Some(hex::encode(&script)),
+            sighash: None,
+        }];
+        assert_eq!(sign_p2pkh_inputs(&mut tx, &requests, &KeyStore::default(), Network::Mainnet).unwrap(), vec![0]);
+
+        // scriptSig = <sig+hashtype> <pubkey>
+        let script_sig = &tx.inputs[0].script_sig;
+        let sig_len = script_sig[0] as usize;
+        let signature = &script_sig[1..1 + sig_len];
+        assert_eq!(*signature.last().unwrap(), 0x41);
+        assert_eq!(&script_sig[2 + sig_len..], &key.public_key_bytes()[..]);
+
+        let pubkey = PublicKey::from_slice(&key.public_key_bytes()).unwrap();
+        assert!(verify_input_signature(&tx, 0, &script, 50_000, signature, &pubkey));
+        assert!(!verify_input_signature(&tx, 0, &script, 50_001, signature, &pubkey));
+    }
+
+    #[test]
+    fn test_rejects_key_script_mismatch() {
+        let mut tx = spend_tx();
+        let requests = vec![SignInputRequest {
+            index: 0,
+            value: 50_000,
+            wif: Some(WIF_COMPRESSED.to_string()),
+            key_ref: None,
+            script_pubkey: Some(hex::encode(ScriptBuilder::p2pkh(&[0u8; 20]))),
+            sighash: None,
+        }];
+        assert!(sign_p2pkh_inputs(&mut tx, &requests, &KeyStore::default(), Network::Mainnet).is_err());
+    }
+
+    #[test]
+    fn test_stored_keys_require_service_token() {
+        let jwt = JwtManager::new("test-secret".to_string());
+        let bearer = |token: String| {
+            actix_web::test::TestRequest::default()
+                .insert_header(("Authorization", format!("Bearer {}", token)))
+                .to_http_request()
+        };
+        let anonymous = actix_web::test::TestRequest::default().to_http_request();
+        let keys = KeyStore {
+            keys: HashMap::new(),
+            callers: HashMap::from([("hot".to_string(), HashSet::from(["deposit-service".to_string()]))]),
+        };
+
+        // Inline WIFs need no token
+        assert!(require_key_access(&anonymous, &jwt, &keys, []).is_ok());
+        assert!(matches!(require_key_access(&anonymous, &jwt, &keys, ["hot"]), Err(ServiceError::Unauthorized(_))));
+
+        let user = bearer(jwt.create_token("alice@example.com", vec!["read".to_string()], 1).unwrap());
+        assert!(matches!(require_key_access(&user, &jwt, &keys, ["hot"]), Err(ServiceError::Forbidden(_))));
+
+        let service = bearer(jwt.create_service_token("deposit-service").unwrap());
+        assert!(require_key_access(&service, &jwt, &keys, ["hot"]).is_ok());
+        assert!(matches!(require_key_access(&service, &jwt, &keys, ["hot", "cold"]), Err(ServiceError::Forbidden(_))));
+
+        let other = bearer(jwt.create_service_token("lending-service").unwrap());
+        assert!(matches!(require_key_access(&other, &jwt, &keys, ["hot"]), Err(ServiceError::Forbidden(_))));
+    }
+}
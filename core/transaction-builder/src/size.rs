@@ -0,0 +1,201 @@
+// core/transaction-builder/src/size.rs
+// Transaction size estimation from real scripts, and per-input/output fee breakdowns
+
+use serde::Serialize;
+
+use crate::codec::varint_len;
+use crate::Transaction;
+
+/// Largest DER signature plus its sighash byte
+pub const MAX_SIGNATURE_SIZE: usize = 73;
+pub const COMPRESSED_PUBKEY_SIZE: usize = 33;
+pub const UNCOMPRESSED_PUBKEY_SIZE: usize = 65;
+pub const P2PKH_SCRIPT_SIZE: usize = 25;
+pub const P2SH_SCRIPT_SIZE: usize = 23;
+
+/// Bytes taken by a minimal push of `len` bytes of data
+pub const fn push_len(len: usize) -> usize {
+    len + match len {
+        0..=75 => 1,
+        76..=0xff => 2,
+        0x100..=0xffff => 3,
+        _ => 5,
+    }
+}
+
+/// Outpoint, scriptSig length prefix, scriptSig and sequence
+pub const fn input_size(script_sig_len: usize) -> usize {
+    32 + 4 + varint_len(script_sig_len as u64) + script_sig_len + 4
+}
+
+/// Value, scriptPubKey length prefix and scriptPubKey
+pub const fn output_size(script_pubkey_len: usize) -> usize {
+    8 + varint_len(script_pubkey_len as u64) + script_pubkey_len
+}
+
+/// Version, locktime and the input/output counts
+pub const fn overhead_size(input_count: usize, output_count: usize) -> usize {
+    4 + varint_len(input_count as u64) + varint_len(output_count as u64) + 4
+}
+
+pub const P2PKH_INPUT_SIZE: usize =
+    input_size(push_len(MAX_SIGNATURE_SIZE) + push_len(COMPRESSED_PUBKEY_SIZE));
+pub const P2PKH_OUTPUT_SIZE: usize = output_size(P2PKH_SCRIPT_SIZE);
+
+// ============================================================================
+// INPUT SCRIPTS
+// ============================================================================
+
+/// What will eventually unlock an input, for sizing before it is signed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum InputScript {
+    P2pkh { compressed: bool },
+    /// `OP_0 <sig>... <redeem_script>`
+    P2shMultisig { required: usize, redeem_script_len: usize },
+    /// Already signed; the scriptSig is final
+    Final(usize),
+}
+
+impl InputScript {
+    pub const P2PKH: InputScript = InputScript::P2pkh { compressed: true };
+    /// A channel's 2-of-2 funding output over two compressed keys
+    pub const CHANNEL_MULTISIG: InputScript = InputScript::P2shMultisig {
+        required: 2,
+        redeem_script_len: 1 + 2 * push_len(COMPRESSED_PUBKEY_SIZE) + 2,
+    };
+
+    pub fn script_sig_len(&self) -> usize {
+        match *self {
+            InputScript::P2pkh { compressed } => {
+                let pubkey = if compressed { COMPRESSED_PUBKEY_SIZE } else { UNCOMPRESSED_PUBKEY_SIZE };
+                push_len(MAX_SIGNATURE_SIZE) + push_len(pubkey)
+            }
+            InputScript::P2shMultisig { required, redeem_script_len } => {
+                1 + required * push_len(MAX_SIGNATURE_SIZE) + push_len(redeem_script_len)
+            }
+            InputScript::Final(len) => len,
+        }
+    }
+
+    pub fn input_size(&self) -> usize {
+        input_size(self.script_sig_len())
+    }
+}
+
+/// The input scripts to size `tx` with: inputs that already carry a scriptSig
+/// keep it, the rest are assumed to unlock as `unsigned`
+pub fn input_scripts(tx: &Transaction, unsigned: InputScript) -> Vec<InputScript> {
+    tx.inputs
+        .iter()
+        .map(|input| {
+            if input.script_sig.is_empty() {
+                unsigned
+            } else {
+                InputScript::Final(input.script_sig.len())
+            }
+        })
+        .collect()
+}
+
+/// Serialized size of `tx` once every input is unlocked as described
+pub fn estimate_size(tx: &Transaction, inputs: &[InputScript]) -> usize {
+    overhead_size(inputs.len(), tx.outputs.len())
+        + inputs.iter().map(InputScript::input_size).sum::<usize>()
+        + tx.outputs.iter().map(|o| output_size(o.script_pubkey.len())).sum::<usize>()
+}
+
+// ============================================================================
+// FEE BREAKDOWN
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ItemFee {
+    pub index: usize,
+    pub size_bytes: usize,
+    pub fee_satoshis: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct FeeBreakdown {
+    pub fee_per_byte: u64,
+    pub overhead_bytes: usize,
+    pub overhead_fee: u64,
+    pub inputs: Vec<ItemFee>,
+    pub outputs: Vec<ItemFee>,
+    /// Estimated size once signed, which the fee is based on
+    pub total_bytes: usize,
+    pub total_fee: u64,
+}
+
+pub fn fee_breakdown(tx: &Transaction, inputs: &[InputScript], fee_per_byte: u64) -> FeeBreakdown {
+    let item = |index: usize, size_bytes: usize| ItemFee {
+        index,
+        size_bytes,
+        fee_satoshis: size_bytes as u64 * fee_per_byte,
+    };
+    let overhead_bytes = overhead_size(inputs.len(), tx.outputs.len());
+    let total_bytes = estimate_size(tx, inputs);
+
+    FeeBreakdown {
+        fee_per_byte,
+        overhead_bytes,
+        overhead_fee: overhead_bytes as u64 * fee_per_byte,
+        inputs: inputs.iter().enumerate().map(|(i, s)| item(i, s.input_size())).collect(),
+        outputs: tx.outputs.iter().enumerate().map(|(i, o)| item(i, output_size(o.script_pubkey.len()))).collect(),
+        total_bytes,
+        total_fee: total_bytes as u64 * fee_per_byte,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ScriptBuilder;
+
+    #[test]
+    fn test_standard_sizes() {
+        assert_eq!(P2PKH_INPUT_SIZE, 149);
+        assert_eq!(P2PKH_OUTPUT_SIZE, 34);
+        assert_eq!(InputScript::P2pkh { compressed: false }.input_size(), 181);
+        assert_eq!(output_size(P2SH_SCRIPT_SIZE), 32);
+        // 2-of-2 with compressed keys: 71-byte redeem script
+        let redeem = ScriptBuilder::multisig(2, &[vec![2u8; 33], vec![3u8; 33]]);
+        assert_eq!(InputScript::CHANNEL_MULTISIG, InputScript::P2shMultisig { required: 2, redeem_script_len: redeem.len() });
+        assert_eq!(InputScript::CHANNEL_MULTISIG.script_sig_len(), 1 + 2 * 74 + 72);
+    }
+
+    #[test]
+    fn test_estimate_matches_serialized_size_plus_script_sigs() {
+        let mut tx = Transaction::new();
+        for i in 0..3 {
+            tx.add_input(format!("{:064x}", i), 0, 10_000);
+        }
+        tx.add_output(5_000, ScriptBuilder::p2pkh(&[1u8; 20]));
+        tx.add_output(0, ScriptBuilder::data_output(&[vec![0u8; 300]], false));
+
+        let scripts = input_scripts(&tx, InputScript::P2PKH);
+        let unsigned_size = tx.calculate_size();
+        let script_sigs = 3 * InputScript::P2PKH.script_sig_len();
+        // Each empty scriptSig already has its one-byte length prefix
+        assert_eq!(estimate_size(&tx, &scripts), unsigned_size + script_sigs);
+
+        tx.inputs[0].script_sig = vec![0u8; 100];
+        assert_eq!(input_scripts(&tx, InputScript::P2PKH)[0], InputScript::Final(100));
+    }
+
+    #[test]
+    fn test_fee_breakdown_sums_to_total() {
+        let mut tx = Transaction::new();
+        tx.add_input("11".repeat(32), 0, 10_000);
+        tx.add_output(9_000, ScriptBuilder::p2pkh(&[1u8; 20]));
+        tx.add_output(0, ScriptBuilder::data_output(&[b"memo".to_vec()], false));
+
+        let breakdown = fee_breakdown(&tx, &[InputScript::P2PKH], 2);
+        let parts: u64 = breakdown.overhead_fee
+            + breakdown.inputs.iter().map(|i| i.fee_satoshis).sum::<u64>()
+            + breakdown.outputs.iter().map(|o| o.fee_satoshis).sum::<u64>();
+        assert_eq!(parts, breakdown.total_fee);
+        assert_eq!(breakdown.outputs[1].size_bytes, 8 + 1 + 6);
+        assert_eq!(breakdown.total_fee, breakdown.total_bytes as u64 * 2);
+    }
+}
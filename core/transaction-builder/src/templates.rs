@@ -0,0 +1,453 @@
+// core/transaction-builder/src/templates.rs
+// Named transaction templates: stored output layouts instantiated with {{parameter}} values
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
+use std::collections::{BTreeSet, HashMap};
+use uuid::Uuid;
+
+use crate::coin_selection;
+use crate::data::DataPush;
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::reservations::{self, ReservationError, ReservedBuildResponse};
+use crate::size::{self, InputScript};
+use crate::timelock::Timelock;
+use crate::{
+    AddressUtils, AppState, BuildTransactionResponse, ScriptBuilder, ServiceError, Transaction, UtxoInput,
+};
+
+const DUST_THRESHOLD: u64 = 546;
+const MAX_TEMPLATE_NAME_LEN: usize = 100;
+
+// ============================================================================
+// DATA STRUCTURES
+// ============================================================================
+
+/// One output of a template. String fields may contain `{{name}}` placeholders.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OutputTemplate {
+    P2pkh { address: String, amount: String },
+    Timelock { address: String, amount: String, timelock: Timelock },
+    Data {
+        pushes: Vec<DataPush>,
+        #[serde(default = "default_safe")]
+        safe: bool,
+    },
+}
+
+fn default_safe() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateDefinition {
+    pub outputs: Vec<OutputTemplate>,
+    /// Pushed ahead of every data output's own pushes
+    #[serde(default)]
+    pub data_prefix: Vec<DataPush>,
+    /// Used unless the build request gives fee_per_byte
+    pub fee_policy: Option<FeePolicy>,
+}
+
+#[derive(Debug, Clone, Serialize, sqlx::FromRow)]
+pub struct TxTemplate {
+    pub id: Uuid,
+    pub name: String,
+    pub description: Option<String>,
+    pub definition: Json<TemplateDefinition>,
+    pub parameters: Vec<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+// ============================================================================
+// PLACEHOLDERS
+// ============================================================================
+
+/// Replace each `{{name}}` in `text` with `lookup(name)`
+fn substitute(text: &str, mut lookup: impl FnMut(&str) -> Result<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let end = start
+            + rest[start..]
+                .find("}}")
+                .ok_or_else(|| format!("Unterminated placeholder in {:?}", text))?;
+        let name = rest[start + 2..end].trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("Invalid placeholder name {:?}", name));
+        }
+        out.push_str(&rest[..start]);
+        out.push_str(&lookup(name)?);
+        rest = &rest[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+fn placeholders(text: &str) -> Result<Vec<String>, String> {
+    let mut names = Vec::new();
+    substitute(text, |name| {
+        names.push(name.to_string());
+        Ok(String::new())
+    })?;
+    Ok(names)
+}
+
+fn render(text: &str, params: &HashMap<String, String>) -> Result<String, String> {
+    substitute(text, |name| params.get(name).cloned().ok_or_else(|| format!("Missing parameter: {}", name)))
+}
+
+impl OutputTemplate {
+    fn fields(&self) -> Vec<&str> {
+        match self {
+            OutputTemplate::P2pkh { address, amount } | OutputTemplate::Timelock { address, amount, .. } => {
+                vec![address.as_str(), amount.as_str()]
+            }
+            OutputTemplate::Data { pushes, .. } => pushes.iter().map(|p| p.value.as_str()).collect(),
+        }
+    }
+
+    /// Output value and locking script with `params` substituted
+    fn instantiate(&self, params: &HashMap<String, String>, prefix: &[Vec<u8>]) -> Result<(u64, Vec<u8>), String> {
+        let amount = |amount: &str| -> Result<u64, String> {
+            let rendered = render(amount, params)?;
+            let value: u64 = rendered.trim().parse().map_err(|_| format!("Invalid amount: {}", rendered))?;
+            if value <= DUST_THRESHOLD {
+                return Err(format!("Amount {} is at or below the dust threshold", value));
+            }
+            Ok(value)
+        };
+        let pubkey_hash = |address: &str| AddressUtils::decode_address(&render(address, params)?);
+
+        match self {
+            OutputTemplate::P2pkh { address, amount: value } => {
+                Ok((amount(value)?, ScriptBuilder::p2pkh(&pubkey_hash(address)?)))
+            }
+            OutputTemplate::Timelock { address, amount: value, timelock } => {
+                timelock.validate()?;
+                Ok((amount(value)?, timelock.locking_script(&pubkey_hash(address)?)))
+            }
+            OutputTemplate::Data { pushes, safe } => {
+                let mut data = prefix.to_vec();
+                for push in pushes {
+                    let rendered = DataPush { encoding: push.encoding, value: render(&push.value, params)? };
+                    data.push(rendered.bytes()?);
+                }
+                Ok((0, ScriptBuilder::data_output(&data, *safe)))
+            }
+        }
+    }
+}
+
+impl TemplateDefinition {
+    /// Every placeholder the template needs, sorted
+    pub fn parameters(&self) -> Result<Vec<String>, String> {
+        let mut names = BTreeSet::new();
+        for output in &self.outputs {
+            for field in output.fields() {
+                names.extend(placeholders(field)?);
+            }
+        }
+        Ok(names.into_iter().collect())
+    }
+
+    /// Reject templates that could never instantiate: fields without placeholders must already be valid
+    pub fn validate(&self) -> Result<Vec<String>, String> {
+        if self.outputs.is_empty() {
+            return Err("A template needs at least one output".to_string());
+        }
+        for push in &self.data_prefix {
+            push.bytes()?;
+        }
+        let parameters = self.parameters()?;
+        for (index, output) in self.outputs.iter().enumerate() {
+            if output.fields().iter().all(|f| !f.contains("{{")) {
+                output.instantiate(&HashMap::new(), &[]).map_err(|e| format!("Output {}: {}", index, e))?;
+            }
+        }
+        Ok(parameters)
+    }
+
+    /// Build the outputs, then fund them from `utxos` in order with P2PKH change
+    pub fn build(
+        &self,
+        params: &HashMap<String, String>,
+        utxos: &[UtxoInput],
+        change_address: &str,
+        fee_per_byte: u64,
+        max_data_bytes: usize,
+    ) -> Result<Transaction, String> {
+        let expected = self.parameters()?;
+        let unknown: Vec<&String> = params.keys().filter(|k| !expected.contains(k)).collect();
+        if !unknown.is_empty() {
+            return Err(format!("Unknown parameters: {:?}", unknown));
+        }
+
+        let prefix: Vec<Vec<u8>> = self.data_prefix.iter().map(DataPush::bytes).collect::<Result<_, _>>()?;
+        let mut tx = Transaction::new();
+        let mut data_bytes = 0;
+        for (index, output) in self.outputs.iter().enumerate() {
+            let (value, script) = output.instantiate(params, &prefix).map_err(|e| format!("Output {}: {}", index, e))?;
+            if value == 0 {
+                data_bytes += script.len();
+            }
+            tx.add_output(value, script);
+        }
+        if data_bytes > max_data_bytes {
+            return Err(format!("Data outputs total {} bytes; the limit is {}", data_bytes, max_data_bytes));
+        }
+
+        let change_script = ScriptBuilder::p2pkh(&AddressUtils::decode_address(change_address)?);
+        coin_selection::fund_in_order(tx, utxos, &change_script, fee_per_byte, DUST_THRESHOLD)
+    }
+}
+
+fn validate_name(name: &str) -> Result<(), ServiceError> {
+    let valid = !name.is_empty()
+        && name.len() <= MAX_TEMPLATE_NAME_LEN
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        && !name.starts_with(['_', '-']);
+    if !valid {
+        return Err(ServiceError::ValidationError(
+            "Template names are lowercase letters, digits, '_' and '-'".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn db_error(e: sqlx::Error) -> ServiceError {
+    tracing::error!("Template database error: {}", e);
+    ServiceError::DatabaseError("Failed to access transaction templates".to_string())
+}
+
+fn not_found(name: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(serde_json::json!({
+        "error": "not_found",
+        "message": format!("Template {} not found", name)
+    }))
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct SaveTemplateRequest {
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(flatten)]
+    pub definition: TemplateDefinition,
+    pub created_by: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BuildFromTemplateRequest {
+    #[serde(default)]
+    pub parameters: HashMap<String, String>,
+    pub utxos: Vec<UtxoInput>,
+    pub change_address: String,
+    pub fee_per_byte: Option<u64>,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+/// Create a template, or replace the definition of an existing one
+pub async fn save_template(
+    data: web::Data<AppState>,
+    req: web::Json<SaveTemplateRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    validate_name(&req.name)?;
+    let parameters = req.definition.validate().map_err(ServiceError::ValidationError)?;
+
+    let template = sqlx::query_as::<_, TxTemplate>(
+        r#"
+        INSERT INTO tx_templates (name, description, definition, parameters, created_by)
+        VALUES ($1, $2, $3, $4, $5)
+        ON CONFLICT (name) DO UPDATE SET
+            description = EXCLUDED.description,
+            definition = EXCLUDED.definition,
+            parameters = EXCLUDED.parameters,
+            updated_at = NOW()
+        RETURNING *
+        "#
+    )
+    .bind(&req.name)
+    .bind(&req.description)
+    .bind(Json(&req.definition))
+    .bind(&parameters)
+    .bind(&req.created_by)
+    .fetch_one(&data.db)
+    .await
+    .map_err(db_error)?;
+
+    tracing::info!("Saved transaction template {} ({} parameters)", template.name, parameters.len());
+    Ok(HttpResponse::Ok().json(template))
+}
+
+pub async fn list_templates(data: web::Data<AppState>) -> Result<HttpResponse, ServiceError> {
+    let templates = sqlx::query_as::<_, TxTemplate>("SELECT * FROM tx_templates ORDER BY name")
+        .fetch_all(&data.db)
+        .await
+        .map_err(db_error)?;
+    Ok(HttpResponse::Ok().json(templates))
+}
+
+async fn fetch_template(data: &AppState, name: &str) -> Result<Option<TxTemplate>, ServiceError> {
+    sqlx::query_as::<_, TxTemplate>("SELECT * FROM tx_templates WHERE name = $1")
+        .bind(name)
+        .fetch_optional(&data.db)
+        .await
+        .map_err(db_error)
+}
+
+pub async fn get_template(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    let name = path.into_inner();
+    match fetch_template(&data, &name).await? {
+        Some(template) => Ok(HttpResponse::Ok().json(template)),
+        None => Ok(not_found(&name)),
+    }
+}
+
+pub async fn delete_template(
+    data: web::Data<AppState>,
+    path: web::Path<String>,
+) -> Result<HttpResponse, ServiceError> {
+    let name = path.into_inner();
+    let result = sqlx::query("DELETE FROM tx_templates WHERE name = $1")
+        .bind(&name)
+        .execute(&data.db)
+        .await
+        .map_err(db_error)?;
+    if result.rows_affected() == 0 {
+        return Ok(not_found(&name));
+    }
+    Ok(HttpResponse::NoContent().finish())
+}
+
+pub async fn build_from_template(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    path: web::Path<String>,
+    req: web::Json<BuildFromTemplateRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let name = path.into_inner();
+    let template = match fetch_template(&data, &name).await? {
+        Some(template) => template,
+        None => return Ok(not_found(&name)),
+    };
+    let definition = &template.definition.0;
+    let fee_per_byte = fees.fee_per_byte(definition.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+
+    let req = req.into_inner();
+    let utxos = reservations::filter_available(&data.db, req.utxos)
+        .await
+        .map_err(ReservationError::from)?;
+
+    let tx = definition
+        .build(&req.parameters, &utxos, &req.change_address, fee_per_byte, data.config.max_data_carrier_bytes)
+        .map_err(|e| {
+            tracing::error!("Failed to build from template {}: {}", name, e);
+            ServiceError::BuildError(e)
+        })?;
+
+    let txid = tx.calculate_txid();
+    let fee_breakdown = size::fee_breakdown(&tx, &size::input_scripts(&tx, InputScript::P2PKH), fee_per_byte);
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    tracing::info!("Built {} from template {}", txid, name);
+
+    let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, data.config.reservation_ttl_secs);
+    let reservation = reservations::reserve(&data.db, &tx.inputs, ttl, &format!("template:{}", name)).await?;
+    history::record(&data, &http_req, TxType::Template, &tx).await?;
+
+    Ok(HttpResponse::Ok().json(ReservedBuildResponse {
+        transaction: BuildTransactionResponse {
+            tx_hex: tx.to_hex(),
+            txid,
+            size_bytes: tx.calculate_size(),
+            fee_satoshis: total_in - total_out,
+            inputs: tx.inputs.clone(),
+            outputs: tx.outputs.clone(),
+            fee_breakdown,
+        },
+        reservation,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codec::{classify_script, ScriptType};
+    use crate::data::DataEncoding;
+
+    const ADDRESS: &str = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+
+    fn push(value: &str) -> DataPush {
+        DataPush { encoding: DataEncoding::Utf8, value: value.to_string() }
+    }
+
+    fn deposit_commitment() -> TemplateDefinition {
+        TemplateDefinition {
+            outputs: vec![
+                OutputTemplate::P2pkh { address: "{{depositor}}".into(), amount: "{{amount}}".into() },
+                OutputTemplate::Data { pushes: vec![push("deposit"), push("{{ deposit_id }}")], safe: true },
+            ],
+            data_prefix: vec![push("bsvbank")],
+            fee_policy: None,
+        }
+    }
+
+    fn params(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_placeholders_and_render() {
+        assert_eq!(placeholders("a{{x}}b{{ y_1 }}").unwrap(), vec!["x", "y_1"]);
+        assert!(placeholders("{{open").is_err());
+        assert!(placeholders("{{bad name}}").is_err());
+        assert_eq!(render("{{a}}-{{ b }}", &params(&[("a", "1"), ("b", "2")])).unwrap(), "1-2");
+        assert!(render("{{a}}", &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_validate_checks_literal_fields() {
+        assert_eq!(deposit_commitment().validate().unwrap(), vec!["amount", "deposit_id", "depositor"]);
+
+        let bad = TemplateDefinition {
+            outputs: vec![OutputTemplate::P2pkh { address: ADDRESS.into(), amount: "100".into() }],
+            data_prefix: Vec::new(),
+            fee_policy: None,
+        };
+        assert!(bad.validate().is_err());
+        let empty = TemplateDefinition { outputs: Vec::new(), data_prefix: Vec::new(), fee_policy: None };
+        assert!(empty.validate().is_err());
+    }
+
+    #[test]
+    fn test_build_instantiates_outputs_and_change() {
+        let utxos = vec![UtxoInput { txid: "11".repeat(32), vout: 0, satoshis: 100_000, confirmations: None }];
+        let p = params(&[("depositor", ADDRESS), ("amount", "50000"), ("deposit_id", "d-42")]);
+        let tx = deposit_commitment().build(&p, &utxos, ADDRESS, 1, 1000).unwrap();
+
+        assert_eq!(tx.outputs.len(), 3);
+        assert_eq!(tx.outputs[0].value, 50_000);
+        assert_eq!(classify_script(&tx.outputs[1].script_pubkey), ScriptType::NullData);
+        let script = &tx.outputs[1].script_pubkey;
+        assert_eq!(&script[2..10], b"\x07bsvbank");
+
+        let mut extra = p.clone();
+        extra.insert("surprise".into(), "1".into());
+        assert!(deposit_commitment().build(&extra, &utxos, ADDRESS, 1, 1000).is_err());
+        assert!(deposit_commitment().build(&params(&[("amount", "50000")]), &utxos, ADDRESS, 1, 1000).is_err());
+    }
+}
@@ -0,0 +1,493 @@
+// core/transaction-builder/src/timelock.rs
+// Absolute (CLTV) and relative (CSV) timelocked P2PKH outputs, and inputs that spend them
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+
+use crate::fees::{FeeKind, FeeOracle, FeePolicy};
+use crate::history::{self, TxType};
+use crate::reservations::{self, ReservationError, ReservedBuildResponse};
+use crate::size::{self, InputScript};
+use crate::{AddressUtils, AppState, BuildTransactionResponse, ScriptBuilder, ServiceError, Transaction, UtxoInput};
+
+/// nLockTime values below this are block heights, at or above it unix times
+pub const LOCKTIME_THRESHOLD: u32 = 500_000_000;
+/// Any sequence below final lets nLockTime take effect
+pub const SEQUENCE_ENABLE_LOCKTIME: u32 = 0xffff_fffe;
+/// BIP68: relative lock measured in 512-second units rather than blocks
+const SEQUENCE_LOCKTIME_TYPE_FLAG: u32 = 1 << 22;
+const SEQUENCE_LOCKTIME_MASK: u32 = 0x0000_ffff;
+const SEQUENCE_GRANULARITY_SECS: u32 = 512;
+
+const OP_0: u8 = 0x00;
+const OP_1NEGATE: u8 = 0x4f;
+const OP_1: u8 = 0x51;
+const OP_16: u8 = 0x60;
+const OP_DROP: u8 = 0x75;
+const OP_CHECKLOCKTIMEVERIFY: u8 = 0xb1;
+const OP_CHECKSEQUENCEVERIFY: u8 = 0xb2;
+/// CLTV and CSV operands are limited to 5-byte script numbers
+const MAX_LOCK_NUMBER_SIZE: usize = 5;
+const DUST_THRESHOLD: u64 = 546;
+
+// ============================================================================
+// SCRIPT NUMBERS
+// ============================================================================
+
+/// Minimal little-endian sign-magnitude encoding used by script arithmetic
+pub fn script_number(n: i64) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    let negative = n < 0;
+    let mut abs = n.unsigned_abs();
+    while abs > 0 {
+        bytes.push((abs & 0xff) as u8);
+        abs >>= 8;
+    }
+    // The top bit carries the sign, so a set top bit needs an extra byte
+    if let Some(&last) = bytes.last() {
+        if last & 0x80 != 0 {
+            bytes.push(if negative { 0x80 } else { 0x00 });
+        } else if negative {
+            *bytes.last_mut().unwrap() |= 0x80;
+        }
+    }
+    bytes
+}
+
+pub fn decode_script_number(bytes: &[u8]) -> Option<i64> {
+    if bytes.len() > 8 {
+        return None;
+    }
+    // Reject non-minimal encodings, as the interpreter does
+    if let Some(&last) = bytes.last() {
+        if last & 0x7f == 0 && (bytes.len() == 1 || bytes[bytes.len() - 2] & 0x80 == 0) {
+            return None;
+        }
+    }
+    let mut value: i64 = 0;
+    for (i, &byte) in bytes.iter().enumerate() {
+        value |= (byte as i64) << (8 * i);
+    }
+    match bytes.last() {
+        Some(&last) if last & 0x80 != 0 => {
+            Some(-(value & !(0x80i64 << (8 * (bytes.len() - 1)))))
+        }
+        _ => Some(value),
+    }
+}
+
+/// Push `n` with the smallest opcode: OP_0, OP_1NEGATE, OP_1..OP_16 or a data push
+pub fn push_number(script: &mut Vec<u8>, n: i64) {
+    match n {
+        0 => script.push(OP_0),
+        -1 => script.push(OP_1NEGATE),
+        1..=16 => script.push(OP_1 + n as u8 - 1),
+        _ => ScriptBuilder::push_data(script, &script_number(n)),
+    }
+}
+
+/// Read a number pushed by `push_number`, returning it and the bytes consumed
+fn read_number(script: &[u8]) -> Option<(i64, usize)> {
+    match *script.first()? {
+        OP_0 => Some((0, 1)),
+        OP_1NEGATE => Some((-1, 1)),
+        op @ OP_1..=OP_16 => Some(((op - OP_1 + 1) as i64, 1)),
+        len @ 1..=0x4b => {
+            let len = len as usize;
+            if len > MAX_LOCK_NUMBER_SIZE {
+                return None;
+            }
+            Some((decode_script_number(script.get(1..1 + len)?)?, 1 + len))
+        }
+        _ => None,
+    }
+}
+
+// ============================================================================
+// TIMELOCKS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Timelock {
+    /// Spendable once the chain reaches `locktime`: a block height below
+    /// 500,000,000, otherwise a unix timestamp
+    Absolute { locktime: u32 },
+    /// Spendable `blocks` after the output confirms
+    RelativeBlocks { blocks: u16 },
+    /// Spendable `seconds` after the output confirms, rounded up to 512-second units
+    RelativeSeconds { seconds: u32 },
+}
+
+impl Timelock {
+    pub fn validate(&self) -> Result<(), String> {
+        match *self {
+            Timelock::Absolute { locktime: 0 } => Err("Absolute locktime must be non-zero".to_string()),
+            Timelock::RelativeBlocks { blocks: 0 } => Err("Relative lock must be at least one block".to_string()),
+            Timelock::RelativeSeconds { seconds } if seconds == 0 || seconds > SEQUENCE_LOCKTIME_MASK * SEQUENCE_GRANULARITY_SECS => Err(format!(
+                "Relative lock must be between 1 and {} seconds",
+                SEQUENCE_LOCKTIME_MASK * SEQUENCE_GRANULARITY_SECS
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn is_relative(&self) -> bool {
+        !matches!(self, Timelock::Absolute { .. })
+    }
+
+    /// nSequence the spending input must carry
+    pub fn sequence(&self) -> u32 {
+        match *self {
+            Timelock::Absolute { .. } => SEQUENCE_ENABLE_LOCKTIME,
+            Timelock::RelativeBlocks { blocks } => blocks as u32,
+            Timelock::RelativeSeconds { seconds } => {
+                SEQUENCE_LOCKTIME_TYPE_FLAG | seconds.div_ceil(SEQUENCE_GRANULARITY_SECS)
+            }
+        }
+    }
+
+    /// Operand pushed ahead of CLTV/CSV in the locking script
+    fn operand(&self) -> i64 {
+        match *self {
+            Timelock::Absolute { locktime } => locktime as i64,
+            _ => self.sequence() as i64,
+        }
+    }
+
+    fn opcode(&self) -> u8 {
+        if self.is_relative() { OP_CHECKSEQUENCEVERIFY } else { OP_CHECKLOCKTIMEVERIFY }
+    }
+
+    /// `<n> OP_CHECKLOCKTIMEVERIFY|OP_CHECKSEQUENCEVERIFY OP_DROP` followed by P2PKH
+    pub fn locking_script(&self, pubkey_hash: &[u8]) -> Vec<u8> {
+        let mut script = Vec::new();
+        push_number(&mut script, self.operand());
+        script.push(self.opcode());
+        script.push(OP_DROP);
+        script.extend_from_slice(&ScriptBuilder::p2pkh(pubkey_hash));
+        script
+    }
+
+    /// Recover the lock and pubkey hash from a script built by `locking_script`
+    pub fn parse(script: &[u8]) -> Option<(Timelock, Vec<u8>)> {
+        let (operand, used) = read_number(script)?;
+        let rest = &script[used..];
+        if rest.len() != 2 + 25 || rest[1] != OP_DROP {
+            return None;
+        }
+        let p2pkh = &rest[2..];
+        if ScriptBuilder::p2pkh(&p2pkh[3..23]) != p2pkh {
+            return None;
+        }
+        let operand = u32::try_from(operand).ok()?;
+
+        let timelock = match rest[0] {
+            OP_CHECKLOCKTIMEVERIFY => Timelock::Absolute { locktime: operand },
+            OP_CHECKSEQUENCEVERIFY if operand & SEQUENCE_LOCKTIME_TYPE_FLAG != 0 => Timelock::RelativeSeconds {
+                seconds: (operand & SEQUENCE_LOCKTIME_MASK) * SEQUENCE_GRANULARITY_SECS,
+            },
+            OP_CHECKSEQUENCEVERIFY => Timelock::RelativeBlocks { blocks: (operand & SEQUENCE_LOCKTIME_MASK) as u16 },
+            _ => return None,
+        };
+        Some((timelock, p2pkh[3..23].to_vec()))
+    }
+
+    /// Set the fields input `index` of `tx` needs to satisfy this lock
+    pub fn apply(&self, tx: &mut Transaction, index: usize) -> Result<(), String> {
+        tx.inputs[index].sequence = self.sequence();
+        match *self {
+            Timelock::Absolute { locktime } => {
+                // nLockTime is shared, so every absolute lock must be the same kind
+                if tx.locktime != 0 && (tx.locktime < LOCKTIME_THRESHOLD) != (locktime < LOCKTIME_THRESHOLD) {
+                    return Err("Cannot mix block-height and timestamp locktimes in one transaction".to_string());
+                }
+                tx.locktime = tx.locktime.max(locktime);
+            }
+            // BIP68 sequence locks only apply from version 2
+            _ => tx.version = tx.version.max(2),
+        }
+        Ok(())
+    }
+}
+
+// ============================================================================
+// API
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct TimelockOutputRequest {
+    pub utxos: Vec<UtxoInput>,
+    /// Beneficiary who can spend once the lock passes
+    pub address: String,
+    pub amount_satoshis: u64,
+    pub timelock: Timelock,
+    pub change_address: String,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+    pub reservation_ttl_seconds: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimelockInput {
+    pub txid: String,
+    pub vout: u32,
+    pub satoshis: u64,
+    /// Locking script (hex) of the timelocked output being spent
+    pub locking_script: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TimelockSpendRequest {
+    pub inputs: Vec<TimelockInput>,
+    pub to_address: String,
+    pub fee_per_byte: Option<u64>,
+    pub fee_policy: Option<FeePolicy>,
+}
+
+#[derive(Serialize)]
+pub struct TimelockSpendResponse {
+    #[serde(flatten)]
+    pub transaction: BuildTransactionResponse,
+    pub version: u32,
+    pub locktime: u32,
+    pub timelocks: Vec<Timelock>,
+}
+
+/// Pay `amount_satoshis` into a timelocked output, spending UTXOs in order
+pub fn build_timelock_output(req: &TimelockOutputRequest, fee_per_byte: u64) -> Result<Transaction, String> {
+    req.timelock.validate()?;
+    if req.amount_satoshis <= DUST_THRESHOLD {
+        return Err(format!("amount_satoshis must exceed the dust threshold of {}", DUST_THRESHOLD));
+    }
+    let locked_script = req.timelock.locking_script(&AddressUtils::decode_address(&req.address)?);
+    let change_script = ScriptBuilder::p2pkh(&AddressUtils::decode_address(&req.change_address)?);
+
+    let mut tx = Transaction::new();
+    tx.add_output(req.amount_satoshis, locked_script);
+
+    let fee_for = |tx: &Transaction, with_change: bool| {
+        let mut sized = tx.clone();
+        if with_change {
+            sized.add_output(0, change_script.clone());
+        }
+        size::estimate_size(&sized, &size::input_scripts(&sized, InputScript::P2PKH)) as u64 * fee_per_byte
+    };
+
+    let mut total_in = 0u64;
+    for utxo in &req.utxos {
+        if total_in >= req.amount_satoshis + fee_for(&tx, false) {
+            break;
+        }
+        tx.add_input(utxo.txid.clone(), utxo.vout, utxo.satoshis);
+        total_in += utxo.satoshis;
+    }
+    let needed = req.amount_satoshis + fee_for(&tx, false);
+    if tx.inputs.is_empty() || total_in < needed {
+        return Err(format!("Insufficient funds: need {} sats, have {} sats", needed, total_in));
+    }
+
+    let change = total_in.saturating_sub(req.amount_satoshis + fee_for(&tx, true));
+    if change > DUST_THRESHOLD {
+        tx.add_output(change, change_script);
+    }
+    Ok(tx)
+}
+
+/// Sweep timelocked outputs to `to_address` with the sequence/locktime each lock needs
+pub fn build_timelock_spend(req: &TimelockSpendRequest, fee_per_byte: u64) -> Result<(Transaction, Vec<Timelock>), String> {
+    if req.inputs.is_empty() {
+        return Err("No inputs provided".to_string());
+    }
+
+    let mut tx = Transaction::new();
+    let mut timelocks = Vec::with_capacity(req.inputs.len());
+    for (index, input) in req.inputs.iter().enumerate() {
+        let script = hex::decode(&input.locking_script)
+            .map_err(|_| format!("Input {}: invalid locking_script hex", index))?;
+        let (timelock, _) = Timelock::parse(&script)
+            .ok_or_else(|| format!("Input {}: not a timelocked P2PKH script", index))?;
+        tx.add_input(input.txid.clone(), input.vout, input.satoshis);
+        timelock.apply(&mut tx, index).map_err(|e| format!("Input {}: {}", index, e))?;
+        timelocks.push(timelock);
+    }
+
+    let to_script = ScriptBuilder::p2pkh(&AddressUtils::decode_address(&req.to_address)?);
+    tx.add_output(0, to_script);
+
+    // The unlocking script is a plain P2PKH one: <sig> <pubkey>
+    let fee = size::estimate_size(&tx, &size::input_scripts(&tx, InputScript::P2PKH)) as u64 * fee_per_byte;
+    let total_in: u64 = req.inputs.iter().map(|i| i.satoshis).sum();
+    if total_in <= fee + DUST_THRESHOLD {
+        return Err(format!("Inputs total {} sats, not enough to cover the {} sat fee", total_in, fee));
+    }
+    tx.outputs[0].value = total_in - fee;
+    Ok((tx, timelocks))
+}
+
+pub async fn build_timelock_output_handler(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    req: web::Json<TimelockOutputRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+
+    let mut req = req.into_inner();
+    req.utxos = reservations::filter_available(&data.db, req.utxos)
+        .await
+        .map_err(ReservationError::from)?;
+
+    let tx = build_timelock_output(&req, fee_per_byte).map_err(|e| {
+        tracing::error!("Failed to build timelock output: {}", e);
+        ServiceError::BuildError(e)
+    })?;
+
+    let txid = tx.calculate_txid();
+    let fee_breakdown = size::fee_breakdown(&tx, &size::input_scripts(&tx, InputScript::P2PKH), fee_per_byte);
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    tracing::info!("Built timelocked output: {} ({:?})", txid, req.timelock);
+
+    let ttl = reservations::reservation_ttl(req.reservation_ttl_seconds, data.config.reservation_ttl_secs);
+    let reservation = reservations::reserve(&data.db, &tx.inputs, ttl, "build:timelock").await?;
+    history::record(&data, &http_req, TxType::Timelock, &tx).await?;
+
+    Ok(HttpResponse::Ok().json(ReservedBuildResponse {
+        transaction: BuildTransactionResponse {
+            tx_hex: tx.to_hex(),
+            txid,
+            size_bytes: tx.calculate_size(),
+            fee_satoshis: total_in - total_out,
+            inputs: tx.inputs.clone(),
+            outputs: tx.outputs.clone(),
+            fee_breakdown,
+        },
+        reservation,
+    }))
+}
+
+pub async fn build_timelock_spend_handler(
+    data: web::Data<AppState>,
+    fees: web::Data<FeeOracle>,
+    http_req: HttpRequest,
+    req: web::Json<TimelockSpendRequest>,
+) -> Result<HttpResponse, ServiceError> {
+    let fee_per_byte = fees.fee_per_byte(req.fee_policy, req.fee_per_byte, FeeKind::Standard).await;
+
+    let (tx, timelocks) = build_timelock_spend(&req, fee_per_byte).map_err(|e| {
+        tracing::error!("Failed to build timelock spend: {}", e);
+        ServiceError::BuildError(e)
+    })?;
+
+    let fee_breakdown = size::fee_breakdown(&tx, &size::input_scripts(&tx, InputScript::P2PKH), fee_per_byte);
+    let total_in: u64 = tx.inputs.iter().map(|i| i.value).sum();
+    let total_out: u64 = tx.outputs.iter().map(|o| o.value).sum();
+    history::record(&data, &http_req, TxType::TimelockSpend, &tx).await?;
+
+    Ok(HttpResponse::Ok().json(TimelockSpendResponse {
+        transaction: BuildTransactionResponse {
+            tx_hex: tx.to_hex(),
+            txid: tx.calculate_txid(),
+            size_bytes: tx.calculate_size(),
+            fee_satoshis: total_in - total_out,
+            inputs: tx.inputs.clone(),
+            outputs: tx.outputs.clone(),
+            fee_breakdown,
+        },
+        version: tx.version,
+        locktime: tx.locktime,
+        timelocks,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADDRESS: &str = "1BgGZ9tcN4rm9KBzDn7KprQz87SZ26SAMH";
+
+    #[test]
+    fn test_script_number_encoding() {
+        assert_eq!(script_number(0), Vec::<u8>::new());
+        assert_eq!(script_number(127), vec![0x7f]);
+        assert_eq!(script_number(128), vec![0x80, 0x00]);
+        assert_eq!(script_number(-128), vec![0x80, 0x80]);
+        assert_eq!(script_number(255), vec![0xff, 0x00]);
+        assert_eq!(script_number(500_000), vec![0x20, 0xa1, 0x07]);
+        for n in [1, 127, 128, 255, 256, 65_535, 500_000, -1, -255, 0x7fff_ffff, 4_294_967_295] {
+            assert_eq!(decode_script_number(&script_number(n)), Some(n));
+        }
+        // Padded encodings are not minimal
+        assert_eq!(decode_script_number(&[0x01, 0x00]), None);
+    }
+
+    #[test]
+    fn test_cltv_uses_minimal_push() {
+        let hash = [7u8; 20];
+        let script = Timelock::Absolute { locktime: 10 }.locking_script(&hash);
+        assert_eq!(&script[..3], &[0x5a, OP_CHECKLOCKTIMEVERIFY, OP_DROP]); // OP_10
+
+        let script = ScriptBuilder::checklocktimeverify(800_000, &hash);
+        assert_eq!(&script[..5], &[0x03, 0x00, 0x35, 0x0c, OP_CHECKLOCKTIMEVERIFY]);
+        assert_eq!(Timelock::parse(&script), Some((Timelock::Absolute { locktime: 800_000 }, hash.to_vec())));
+    }
+
+    #[test]
+    fn test_csv_sequence_encoding() {
+        assert_eq!(Timelock::RelativeBlocks { blocks: 144 }.sequence(), 144);
+        // 1000 seconds round up to two 512-second units
+        let seconds = Timelock::RelativeSeconds { seconds: 1000 };
+        assert_eq!(seconds.sequence(), SEQUENCE_LOCKTIME_TYPE_FLAG | 2);
+
+        let script = seconds.locking_script(&[1u8; 20]);
+        assert_eq!(script[script.len() - 27], OP_CHECKSEQUENCEVERIFY);
+        assert_eq!(Timelock::parse(&script).unwrap().0, Timelock::RelativeSeconds { seconds: 1024 });
+        assert!(Timelock::RelativeSeconds { seconds: 0 }.validate().is_err());
+    }
+
+    #[test]
+    fn test_spend_sets_sequence_locktime_and_version() {
+        let absolute = Timelock::Absolute { locktime: 800_000 }.locking_script(&[1u8; 20]);
+        let relative = Timelock::RelativeBlocks { blocks: 6 }.locking_script(&[1u8; 20]);
+        let input = |vout, script: &[u8]| TimelockInput {
+            txid: "11".repeat(32),
+            vout,
+            satoshis: 10_000,
+            locking_script: hex::encode(script),
+        };
+        let req = TimelockSpendRequest {
+            inputs: vec![input(0, &absolute), input(1, &relative)],
+            to_address: ADDRESS.to_string(),
+            fee_per_byte: None,
+            fee_policy: None,
+        };
+
+        let (tx, _) = build_timelock_spend(&req, 1).unwrap();
+        assert_eq!(tx.locktime, 800_000);
+        assert_eq!(tx.version, 2);
+        assert_eq!(tx.inputs[0].sequence, SEQUENCE_ENABLE_LOCKTIME);
+        assert_eq!(tx.inputs[1].sequence, 6);
+
+        let timestamp = Timelock::Absolute { locktime: LOCKTIME_THRESHOLD + 1 }.locking_script(&[1u8; 20]);
+        let mixed = TimelockSpendRequest { inputs: vec![input(0, &absolute), input(1, &timestamp)], ..req };
+        assert!(build_timelock_spend(&mixed, 1).is_err());
+    }
+
+    #[test]
+    fn test_build_timelock_output() {
+        let req = TimelockOutputRequest {
+            utxos: vec![UtxoInput { txid: "22".repeat(32), vout: 0, satoshis: 100_000, confirmations: None }],
+            address: ADDRESS.to_string(),
+            amount_satoshis: 50_000,
+            timelock: Timelock::RelativeBlocks { blocks: 144 },
+            change_address: ADDRESS.to_string(),
+            fee_per_byte: None,
+            fee_policy: None,
+            reservation_ttl_seconds: None,
+        };
+        let tx = build_timelock_output(&req, 1).unwrap();
+        assert_eq!(tx.outputs.len(), 2);
+        assert!(Timelock::parse(&tx.outputs[0].script_pubkey).is_some());
+    }
+}
@@ -0,0 +1,15 @@
+-- Migration: 008_channel_dispute_height
+-- Description: Track the block height at which a channel dispute started so
+--              force-close settlement can be gated on real chain progress
+-- Date: 2025-11-20
+
+ALTER TABLE payment_channels
+    ADD COLUMN IF NOT EXISTS dispute_started_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS dispute_start_height INT CHECK (dispute_start_height >= 0);
+
+COMMENT ON COLUMN payment_channels.dispute_started_at IS 'Wall-clock time the force-close dispute was opened';
+COMMENT ON COLUMN payment_channels.dispute_start_height IS 'Chain height when the dispute was opened; settlement allowed once height >= dispute_start_height + timeout_blocks';
+
+CREATE INDEX IF NOT EXISTS idx_channels_disputed
+    ON payment_channels(dispute_start_height)
+    WHERE status = 'Disputed';
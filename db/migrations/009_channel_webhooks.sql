@@ -0,0 +1,38 @@
+-- Migration: 009_channel_webhooks
+-- Description: Per-channel webhook subscriptions and delivery log
+-- Date: 2025-11-21
+
+CREATE TABLE IF NOT EXISTS channel_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id VARCHAR(66) NOT NULL REFERENCES payment_channels(channel_id) ON DELETE CASCADE,
+    paymail VARCHAR(255) NOT NULL,
+    url TEXT NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    -- Empty array means "all events"
+    events TEXT[] NOT NULL DEFAULT '{}',
+    active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    
+    UNIQUE(channel_id, paymail, url)
+);
+
+COMMENT ON TABLE channel_webhooks IS 'Callback URLs registered by channel parties';
+COMMENT ON COLUMN channel_webhooks.secret IS 'HMAC-SHA256 key used to sign deliveries';
+
+CREATE INDEX IF NOT EXISTS idx_channel_webhooks_channel ON channel_webhooks(channel_id) WHERE active;
+
+CREATE TABLE IF NOT EXISTS channel_webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES channel_webhooks(id) ON DELETE CASCADE,
+    event_type VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    response_status INT,
+    delivered BOOLEAN NOT NULL DEFAULT false,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_channel_webhook_deliveries_webhook ON channel_webhook_deliveries(webhook_id);
+CREATE INDEX IF NOT EXISTS idx_channel_webhook_deliveries_pending ON channel_webhook_deliveries(created_at) WHERE NOT delivered;
@@ -0,0 +1,27 @@
+-- Migration: 010_channel_spending_limits
+-- Description: Per-party spending limits and velocity controls on channels
+-- Date: 2025-11-22
+
+CREATE TABLE IF NOT EXISTS channel_spending_limits (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id VARCHAR(66) NOT NULL REFERENCES payment_channels(channel_id) ON DELETE CASCADE,
+    paymail VARCHAR(255) NOT NULL,
+    
+    -- NULL means "no limit"
+    max_payment_satoshis BIGINT CHECK (max_payment_satoshis > 0),
+    daily_volume_satoshis BIGINT CHECK (daily_volume_satoshis > 0),
+    cooldown_seconds INT CHECK (cooldown_seconds >= 0),
+    
+    -- Tightening applies immediately; relaxing is delayed so a stolen
+    -- credential cannot lift the limits and drain the channel in one go
+    effective_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE channel_spending_limits IS 'History of spending limit configurations; the latest effective row applies';
+
+CREATE INDEX IF NOT EXISTS idx_channel_limits_lookup
+    ON channel_spending_limits(channel_id, paymail, effective_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_channel_payments_sender_time
+    ON channel_payments(channel_id, from_paymail, created_at DESC);
@@ -0,0 +1,30 @@
+-- Migration: 011_channel_payment_archive
+-- Description: Archive table for payments of long-closed channels
+-- Date: 2025-11-24
+
+CREATE TABLE IF NOT EXISTS channel_payments_archive (
+    id UUID PRIMARY KEY,
+    channel_id VARCHAR(66) NOT NULL REFERENCES payment_channels(channel_id) ON DELETE CASCADE,
+    from_paymail VARCHAR(255) NOT NULL,
+    to_paymail VARCHAR(255) NOT NULL,
+    amount_satoshis BIGINT NOT NULL,
+    sequence_number BIGINT NOT NULL,
+    memo TEXT,
+    balance_a_after BIGINT NOT NULL,
+    balance_b_after BIGINT NOT NULL,
+    processing_time_ms INT,
+    created_at TIMESTAMP NOT NULL,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE channel_payments_archive IS 'Payments moved out of channel_payments once their channel has been closed past the retention window';
+
+CREATE INDEX IF NOT EXISTS idx_channel_payments_archive_channel
+    ON channel_payments_archive(channel_id, sequence_number);
+
+ALTER TABLE payment_channels
+    ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_channels_archive_candidates
+    ON payment_channels(closed_at)
+    WHERE status = 'Closed' AND archived_at IS NULL;
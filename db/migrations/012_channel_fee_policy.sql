@@ -0,0 +1,19 @@
+-- Migration: 012_channel_fee_policy
+-- Description: Settlement fee split policy chosen at channel open
+-- Date: 2025-11-25
+
+ALTER TABLE payment_channels
+    ADD COLUMN IF NOT EXISTS settlement_fee_policy VARCHAR(20) NOT NULL DEFAULT 'split';
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM pg_constraint WHERE conname = 'valid_settlement_fee_policy'
+    ) THEN
+        ALTER TABLE payment_channels
+            ADD CONSTRAINT valid_settlement_fee_policy
+            CHECK (settlement_fee_policy IN ('payer', 'split', 'proportional'));
+    END IF;
+END $$;
+
+COMMENT ON COLUMN payment_channels.settlement_fee_policy IS 'How the settlement fee is divided: payer (closing party pays), split (50/50), proportional (by final balance)';
@@ -0,0 +1,27 @@
+-- Migration: 013_channel_dispute_evidence
+-- Description: Signed channel states submitted as evidence during a dispute
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS channel_dispute_evidence (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    channel_id VARCHAR(66) NOT NULL REFERENCES payment_channels(channel_id) ON DELETE CASCADE,
+    submitted_by VARCHAR(255) NOT NULL,
+
+    -- The co-signed state being claimed
+    sequence_number BIGINT NOT NULL CHECK (sequence_number >= 0),
+    balance_a BIGINT NOT NULL CHECK (balance_a >= 0),
+    balance_b BIGINT NOT NULL CHECK (balance_b >= 0),
+    state_hash VARCHAR(64) NOT NULL,
+    signature_a TEXT NOT NULL,
+    signature_b TEXT NOT NULL,
+
+    submitted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    submitted_at_height INT NOT NULL,
+
+    CONSTRAINT unique_evidence_per_state UNIQUE (channel_id, sequence_number)
+);
+
+CREATE INDEX IF NOT EXISTS idx_dispute_evidence_channel
+    ON channel_dispute_evidence(channel_id, sequence_number DESC);
+
+COMMENT ON TABLE channel_dispute_evidence IS 'Verified co-signed states; the highest sequence settles the dispute';
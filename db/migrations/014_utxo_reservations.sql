@@ -0,0 +1,35 @@
+-- Migration: 014_utxo_reservations
+-- Description: Locks on UTXOs selected into built transactions so concurrent builds cannot reuse them
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS utxo_reservations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Groups the UTXOs locked by one build
+    reservation_id UUID NOT NULL,
+    txid VARCHAR(64) NOT NULL,
+    vout INT NOT NULL CHECK (vout >= 0),
+    satoshis BIGINT NOT NULL CHECK (satoshis >= 0),
+    reserved_for VARCHAR(255),
+
+    status VARCHAR(20) NOT NULL DEFAULT 'reserved'
+        CHECK (status IN ('reserved', 'broadcast', 'released', 'expired')),
+    spending_txid VARCHAR(64),
+
+    reserved_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    released_at TIMESTAMPTZ
+);
+
+-- At most one live lock per outpoint; broadcast outpoints stay locked for good
+CREATE UNIQUE INDEX IF NOT EXISTS idx_utxo_reservations_active
+    ON utxo_reservations(txid, vout)
+    WHERE status IN ('reserved', 'broadcast');
+
+CREATE INDEX IF NOT EXISTS idx_utxo_reservations_reservation
+    ON utxo_reservations(reservation_id);
+
+CREATE INDEX IF NOT EXISTS idx_utxo_reservations_expiry
+    ON utxo_reservations(expires_at)
+    WHERE status = 'reserved';
+
+COMMENT ON TABLE utxo_reservations IS 'UTXOs locked by built transactions until broadcast, release or TTL expiry';
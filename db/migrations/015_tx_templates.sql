@@ -0,0 +1,21 @@
+-- Migration: 015_tx_templates
+-- Description: Named transaction templates (output layout, data prefix, fee policy) instantiated with parameters
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS tx_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    name VARCHAR(100) NOT NULL UNIQUE
+        CHECK (name ~ '^[a-z0-9][a-z0-9_-]*$'),
+    description TEXT,
+
+    -- Outputs, data prefix and fee policy; string fields may hold {{parameter}} placeholders
+    definition JSONB NOT NULL,
+    -- Placeholder names found in the definition, for discovery
+    parameters TEXT[] NOT NULL DEFAULT '{}',
+
+    created_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE tx_templates IS 'Reusable transaction layouts for POST /tx/build/from-template/{name}';
@@ -0,0 +1,31 @@
+-- Migration: 016_payment_requests
+-- Description: BIP270 payment requests issued by the bank and the payments that settle them
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS payment_requests (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    network VARCHAR(32) NOT NULL,
+    -- [{ "amount": sats, "script": hex, "description": text }]
+    outputs JSONB NOT NULL,
+    memo TEXT,
+    merchant_data TEXT,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'paid', 'cancelled')),
+    txid VARCHAR(64),
+    payment_memo TEXT,
+    refund_to TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ,
+    paid_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_payment_requests_status
+    ON payment_requests(status, created_at DESC);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_payment_requests_txid
+    ON payment_requests(txid)
+    WHERE txid IS NOT NULL;
+
+COMMENT ON TABLE payment_requests IS 'BIP270 payment requests; a request is settled by exactly one payment transaction';
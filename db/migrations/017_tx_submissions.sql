@@ -0,0 +1,36 @@
+-- Migration: 017_tx_submissions
+-- Description: Build-and-send requests keyed by client request id, tracked from build through broadcast
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS tx_submissions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Caller-chosen idempotency key; repeating it returns (or resumes) the same submission
+    client_request_id VARCHAR(128) NOT NULL UNIQUE,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'building'
+        CHECK (status IN ('building', 'signed', 'broadcast', 'failed')),
+    to_address VARCHAR(64) NOT NULL,
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    key_ref VARCHAR(100) NOT NULL,
+
+    txid VARCHAR(64),
+    tx_hex TEXT,
+    fee_satoshis BIGINT,
+    reservation_id UUID,
+    broadcast_via VARCHAR(20),
+    broadcast_attempts INT NOT NULL DEFAULT 0,
+    last_error TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    broadcast_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_tx_submissions_status
+    ON tx_submissions(status, updated_at);
+
+CREATE INDEX IF NOT EXISTS idx_tx_submissions_txid
+    ON tx_submissions(txid)
+    WHERE txid IS NOT NULL;
+
+COMMENT ON TABLE tx_submissions IS 'One row per POST /tx/build-and-send client_request_id';
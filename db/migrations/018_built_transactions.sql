@@ -0,0 +1,36 @@
+-- Migration: 018_built_transactions
+-- Description: Audit record of every transaction the transaction-builder constructs
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS built_transactions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    txid VARCHAR(64) NOT NULL UNIQUE,
+    tx_type VARCHAR(30) NOT NULL,
+    -- X-BSVBank-Caller header of the requesting service, when sent
+    caller VARCHAR(100),
+    tx_hex TEXT NOT NULL,
+    -- [{ txid, vout, satoshis }] in input order
+    inputs JSONB NOT NULL,
+    output_count INT NOT NULL,
+    fee_satoshis BIGINT NOT NULL,
+    size_bytes INT NOT NULL,
+    signed BOOLEAN NOT NULL,
+
+    status VARCHAR(20) NOT NULL DEFAULT 'built'
+        CHECK (status IN ('built', 'replaced')),
+    replaces_txid VARCHAR(64),
+    replaced_by_txid VARCHAR(64),
+    replace_reason TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    replaced_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_built_transactions_type
+    ON built_transactions(tx_type, created_at DESC);
+
+CREATE INDEX IF NOT EXISTS idx_built_transactions_caller
+    ON built_transactions(caller, created_at DESC)
+    WHERE caller IS NOT NULL;
+
+COMMENT ON TABLE built_transactions IS 'Transactions returned by transaction-builder build endpoints';
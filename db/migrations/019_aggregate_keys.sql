@@ -0,0 +1,41 @@
+-- Migration: 019_aggregate_keys
+-- Description: Two-party aggregated-key (single pubkey 2-of-2) channel keys and their signing sessions
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS aggregate_keys (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Stored signing key the service's share is derived from; the share itself is never stored
+    key_ref VARCHAR(100) NOT NULL,
+    client_pubkey VARCHAR(66) NOT NULL,
+    service_pubkey VARCHAR(66) NOT NULL,
+    aggregated_pubkey VARCHAR(66) NOT NULL,
+    address VARCHAR(64) NOT NULL,
+    -- Client's Paillier modulus and its encrypted key share, hex
+    paillier_n TEXT NOT NULL,
+    encrypted_client_share TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_aggregate_keys_address ON aggregate_keys(address);
+
+CREATE TABLE IF NOT EXISTS aggregate_sign_sessions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    key_id UUID NOT NULL REFERENCES aggregate_keys(id) ON DELETE CASCADE,
+    tx_hex TEXT NOT NULL,
+    input_index INT NOT NULL,
+    input_value BIGINT NOT NULL,
+    sighash_type INT NOT NULL,
+    sighash VARCHAR(64) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'nonce'
+        CHECK (status IN ('nonce', 'partial', 'completed')),
+    -- Single-use service nonce; cleared as soon as the partial signature is issued
+    service_nonce TEXT,
+    service_nonce_point VARCHAR(66) NOT NULL,
+    client_nonce_point VARCHAR(66),
+    signature TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_aggregate_sign_sessions_key ON aggregate_sign_sessions(key_id, created_at DESC);
@@ -0,0 +1,47 @@
+-- Migration: 020_monitor_webhooks
+-- Description: Blockchain-monitor webhook subscriptions and durable delivery queue
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS monitor_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Registering service or user, for listing and auditing
+    owner VARCHAR(255) NOT NULL,
+    url TEXT NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    -- At least one of address / txid is set
+    address VARCHAR(255),
+    txid VARCHAR(64),
+    min_confirmations INT NOT NULL DEFAULT 0 CHECK (min_confirmations >= 0),
+    active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    
+    CHECK (address IS NOT NULL OR txid IS NOT NULL)
+);
+
+COMMENT ON TABLE monitor_webhooks IS 'Callbacks fired when a matching transaction reaches min_confirmations';
+COMMENT ON COLUMN monitor_webhooks.secret IS 'HMAC-SHA256 key used to sign deliveries';
+
+CREATE INDEX IF NOT EXISTS idx_monitor_webhooks_address ON monitor_webhooks(address) WHERE active;
+CREATE INDEX IF NOT EXISTS idx_monitor_webhooks_txid ON monitor_webhooks(txid) WHERE active;
+
+CREATE TABLE IF NOT EXISTS monitor_webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES monitor_webhooks(id) ON DELETE CASCADE,
+    event_type VARCHAR(50) NOT NULL,
+    txid VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'delivered', 'failed')),
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    response_status INT,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ,
+    
+    -- Each subscription fires at most once per transaction
+    UNIQUE(webhook_id, txid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_monitor_webhook_deliveries_webhook ON monitor_webhook_deliveries(webhook_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_monitor_webhook_deliveries_due ON monitor_webhook_deliveries(next_attempt_at) WHERE status = 'pending';
@@ -0,0 +1,38 @@
+-- Migration: 021_double_spend_alerts
+-- Description: Outpoints spent by monitored transactions and detected double-spend conflicts
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS tx_spent_outpoints (
+    prev_txid VARCHAR(64) NOT NULL,
+    prev_vout INT NOT NULL,
+    spending_txid VARCHAR(64) NOT NULL,
+    first_seen TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    
+    PRIMARY KEY (prev_txid, prev_vout, spending_txid)
+);
+
+COMMENT ON TABLE tx_spent_outpoints IS 'Inputs of every transaction the monitor records; two spenders of one outpoint is a double-spend';
+
+CREATE INDEX IF NOT EXISTS idx_tx_spent_outpoints_spender ON tx_spent_outpoints(spending_txid);
+
+CREATE TABLE IF NOT EXISTS double_spend_alerts (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    prev_txid VARCHAR(64) NOT NULL,
+    prev_vout INT NOT NULL,
+    -- The monitored transaction whose input was spent elsewhere
+    original_txid VARCHAR(64) NOT NULL,
+    conflicting_txid VARCHAR(64) NOT NULL,
+    -- 'mempool' or 'block'
+    conflict_status VARCHAR(20) NOT NULL,
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    
+    UNIQUE(prev_txid, prev_vout, original_txid, conflicting_txid)
+);
+
+CREATE INDEX IF NOT EXISTS idx_double_spend_alerts_original ON double_spend_alerts(original_txid);
+CREATE INDEX IF NOT EXISTS idx_double_spend_alerts_time ON double_spend_alerts(detected_at DESC);
+
+-- A transaction can now produce more than one delivery per subscription (confirmation, double-spend)
+ALTER TABLE monitor_webhook_deliveries DROP CONSTRAINT IF EXISTS monitor_webhook_deliveries_webhook_id_txid_key;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_monitor_webhook_deliveries_once
+    ON monitor_webhook_deliveries(webhook_id, txid, event_type);
@@ -0,0 +1,21 @@
+-- Migration: 022_address_backfill
+-- Description: Track historical transaction backfill for watched addresses
+-- Date: 2025-11-25
+
+-- Existing rows start as 'pending' so addresses watched before this change are backfilled too
+ALTER TABLE watched_addresses
+    ADD COLUMN IF NOT EXISTS backfill_status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (backfill_status IN ('pending', 'running', 'complete')),
+    ADD COLUMN IF NOT EXISTS backfill_provider VARCHAR(30),
+    ADD COLUMN IF NOT EXISTS backfill_cursor TEXT,
+    ADD COLUMN IF NOT EXISTS backfill_tx_count INT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS backfill_lease_until TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS backfill_error TEXT,
+    ADD COLUMN IF NOT EXISTS backfill_completed_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN watched_addresses.backfill_cursor IS 'Page token, issued by backfill_provider, of the next confirmed-history page to import';
+COMMENT ON COLUMN watched_addresses.backfill_lease_until IS 'Worker lease while running, or retry time after a failed page';
+
+CREATE INDEX IF NOT EXISTS idx_watched_addresses_backfill
+    ON watched_addresses(backfill_lease_until)
+    WHERE backfill_status <> 'complete';
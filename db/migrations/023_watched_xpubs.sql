@@ -0,0 +1,29 @@
+-- Migration: 023_watched_xpubs
+-- Description: HD wallet (xpub) watching with gap-limited address derivation
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS watched_xpubs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    xpub VARCHAR(120) NOT NULL UNIQUE,
+    paymail VARCHAR(255) NOT NULL,
+    purpose VARCHAR(50) NOT NULL,
+    scheme VARCHAR(20) NOT NULL CHECK (scheme IN ('bip44', 'single')),
+    gap_limit INT NOT NULL CHECK (gap_limit > 0),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE watched_xpubs IS 'Account-level extended public keys; addresses are derived at <chain>/<index> below them';
+COMMENT ON COLUMN watched_xpubs.scheme IS 'bip44: external chain 0 and change chain 1; single: one chain of addresses directly under the key';
+
+CREATE TABLE IF NOT EXISTS xpub_addresses (
+    address VARCHAR(255) PRIMARY KEY,
+    xpub_id UUID NOT NULL REFERENCES watched_xpubs(id) ON DELETE CASCADE,
+    chain SMALLINT NOT NULL,
+    address_index INT NOT NULL,
+    used BOOLEAN NOT NULL DEFAULT false,
+    first_used_at TIMESTAMPTZ,
+    
+    UNIQUE (xpub_id, chain, address_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_xpub_addresses_unused ON xpub_addresses(xpub_id, chain, address_index) WHERE NOT used;
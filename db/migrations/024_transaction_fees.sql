@@ -0,0 +1,9 @@
+-- Migration: 024_transaction_fees
+-- Description: Transaction size for fee-rate analytics; fee_satoshis is now populated from resolved prevouts
+-- Date: 2025-11-25
+
+ALTER TABLE blockchain_transactions
+    ADD COLUMN IF NOT EXISTS size_bytes INT;
+
+COMMENT ON COLUMN blockchain_transactions.fee_satoshis IS 'Sum of input prevout values minus outputs; NULL for coinbase or unresolved inputs';
+COMMENT ON COLUMN blockchain_transactions.size_bytes IS 'Serialized transaction size; fee rate is fee_satoshis / size_bytes';
@@ -0,0 +1,25 @@
+-- Migration: 025_arc_broadcasts
+-- Description: Transactions broadcast through ARC and the status miners report back by callback
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS arc_broadcasts (
+    txid VARCHAR(64) PRIMARY KEY,
+    arc_url TEXT NOT NULL,
+    tx_status VARCHAR(40) NOT NULL,
+    block_hash VARCHAR(64),
+    block_height INT,
+    extra_info TEXT,
+    competing_txs JSONB,
+    -- Latest miner response as received, with its signature when the endpoint signs responses
+    response_payload JSONB NOT NULL,
+    response_signature TEXT,
+    response_public_key TEXT,
+    callback_token VARCHAR(64) NOT NULL,
+    callback_count INT NOT NULL DEFAULT 0,
+    submitted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON COLUMN arc_broadcasts.callback_token IS 'Per-transaction bearer token ARC must present on status callbacks';
+
+CREATE INDEX IF NOT EXISTS idx_arc_broadcasts_status ON arc_broadcasts(tx_status);
@@ -0,0 +1,13 @@
+-- Migration: 026_block_scanning
+-- Description: Blocks processed by the block-level scanning mode
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS scanned_blocks (
+    height INT PRIMARY KEY,
+    block_hash VARCHAR(64) NOT NULL,
+    tx_count INT NOT NULL,
+    matched_txs INT NOT NULL,
+    scanned_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE scanned_blocks IS 'SCAN_MODE=block progress; the highest row is where the scanner resumes';
@@ -0,0 +1,8 @@
+-- Migration: 027_transaction_search_indexes
+-- Description: Indexes backing GET /txs filters, which list newest-first
+-- Date: 2025-11-25
+
+CREATE INDEX IF NOT EXISTS idx_blockchain_to_seen ON blockchain_transactions(to_address, first_seen DESC);
+CREATE INDEX IF NOT EXISTS idx_blockchain_from_seen ON blockchain_transactions(from_address, first_seen DESC);
+CREATE INDEX IF NOT EXISTS idx_blockchain_status_seen ON blockchain_transactions(status, first_seen DESC);
+CREATE INDEX IF NOT EXISTS idx_blockchain_amount ON blockchain_transactions(amount_satoshis);
@@ -0,0 +1,20 @@
+-- Migration: 028_watched_scripts
+-- Description: Watch arbitrary locking scripts (multisig funding, data-prefixed outputs) by script hash
+-- Date: 2025-11-25
+
+-- Script entries have no base58 form
+ALTER TABLE watched_addresses ALTER COLUMN address DROP NOT NULL;
+
+ALTER TABLE watched_addresses
+    ADD COLUMN IF NOT EXISTS locking_script TEXT,
+    ADD COLUMN IF NOT EXISTS script_hash VARCHAR(64);
+
+COMMENT ON COLUMN watched_addresses.locking_script IS 'Hex locking script for entries that are not standard addresses';
+COMMENT ON COLUMN watched_addresses.script_hash IS 'Byte-reversed SHA-256 of locking_script, the key providers index scripts by';
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_watched_addresses_script_hash
+    ON watched_addresses(script_hash) WHERE script_hash IS NOT NULL;
+
+ALTER TABLE watched_addresses DROP CONSTRAINT IF EXISTS watched_addresses_target_check;
+ALTER TABLE watched_addresses ADD CONSTRAINT watched_addresses_target_check
+    CHECK (address IS NOT NULL OR (locking_script IS NOT NULL AND script_hash IS NOT NULL));
@@ -0,0 +1,21 @@
+-- Migration: 029_watched_address_lifecycle
+-- Description: Watched address status (active, paused, archived) with archiving after inactivity
+-- Date: 2025-11-25
+
+ALTER TABLE watched_addresses
+    ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'active'
+        CHECK (status IN ('active', 'paused', 'archived')),
+    ADD COLUMN IF NOT EXISTS status_changed_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN watched_addresses.status IS 'Only active entries are polled and scanned for; archived ones return to active on new activity';
+COMMENT ON COLUMN watched_addresses.last_activity IS 'Last time a recorded transaction touched the address; drives automatic archiving';
+
+-- The boolean flag is superseded by status
+UPDATE watched_addresses SET status = 'paused', status_changed_at = NOW() WHERE active = false AND status = 'active';
+DROP INDEX IF EXISTS idx_watched_addresses_active;
+ALTER TABLE watched_addresses DROP COLUMN IF EXISTS active;
+
+CREATE INDEX IF NOT EXISTS idx_watched_addresses_status ON watched_addresses(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_watched_addresses_inactive
+    ON watched_addresses(COALESCE(last_activity, created_at))
+    WHERE status = 'active';
@@ -0,0 +1,28 @@
+-- Migration: 030_confirmation_policies
+-- Description: Per-purpose confirmation targets and one-time finalization of monitored transactions
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS confirmation_policies (
+    purpose VARCHAR(50) PRIMARY KEY,
+    target_confirmations INT NOT NULL CHECK (target_confirmations >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE confirmation_policies IS 'Confirmations a transaction touching an address of this purpose needs before it is finalized';
+
+INSERT INTO confirmation_policies (purpose, target_confirmations) VALUES
+    ('deposit', 3),
+    ('channel_funding', 6),
+    ('settlement', 1)
+ON CONFLICT (purpose) DO NOTHING;
+
+ALTER TABLE blockchain_transactions
+    ADD COLUMN IF NOT EXISTS target_confirmations INT,
+    ADD COLUMN IF NOT EXISTS finalized_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN blockchain_transactions.target_confirmations IS 'Strictest policy among the watched addresses the transaction touches';
+COMMENT ON COLUMN blockchain_transactions.finalized_at IS 'When the transaction reached target_confirmations; the finalized event fires once';
+
+CREATE INDEX IF NOT EXISTS idx_blockchain_transactions_unfinalized
+    ON blockchain_transactions(first_seen DESC)
+    WHERE finalized_at IS NULL;
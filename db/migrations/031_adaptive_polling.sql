@@ -0,0 +1,12 @@
+-- Migration: 031_adaptive_polling
+-- Description: Per-row poll timestamps so transactions and watched addresses are polled on their own schedule
+-- Date: 2025-11-25
+
+ALTER TABLE blockchain_transactions ADD COLUMN IF NOT EXISTS last_checked_at TIMESTAMPTZ;
+
+COMMENT ON COLUMN blockchain_transactions.last_checked_at IS 'Last confirmation poll; young transactions are polled fast, deep ones awaiting a higher target slowly';
+COMMENT ON COLUMN watched_addresses.last_checked IS 'Last poll for new transactions; dormant addresses are polled slowly';
+
+CREATE INDEX IF NOT EXISTS idx_watched_addresses_due
+    ON watched_addresses(last_checked NULLS FIRST)
+    WHERE status = 'active';
@@ -0,0 +1,27 @@
+-- Migration: 032_monitor_event_outbox
+-- Description: Transactional outbox of blockchain-monitor events awaiting publication to the message bus
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS monitor_event_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Publication order
+    seq BIGSERIAL NOT NULL UNIQUE,
+    event_type VARCHAR(50) NOT NULL,
+    -- Ordering key on the bus (the txid)
+    event_key VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMPTZ,
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT
+);
+
+COMMENT ON TABLE monitor_event_outbox IS 'Written in the same database transaction as the state change it describes; the outbox worker publishes and marks rows';
+
+CREATE INDEX IF NOT EXISTS idx_monitor_event_outbox_unpublished
+    ON monitor_event_outbox(seq)
+    WHERE published_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_monitor_event_outbox_published
+    ON monitor_event_outbox(published_at)
+    WHERE published_at IS NOT NULL;
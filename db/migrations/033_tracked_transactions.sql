@@ -0,0 +1,18 @@
+-- Migration: 033_tracked_transactions
+-- Description: Transactions registered with blockchain-monitor by sibling services as soon as they create them
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS tracked_transactions (
+    txid VARCHAR(64) PRIMARY KEY,
+    -- Selects the confirmation policy, like watched_addresses.purpose
+    purpose VARCHAR(50) NOT NULL,
+    -- Registering service, from the service token subject
+    source VARCHAR(100) NOT NULL,
+    -- The registering service's own id for the transaction, e.g. a deposit or channel id
+    reference VARCHAR(255),
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_tracked_transactions_source ON tracked_transactions(source, registered_at);
+
+COMMENT ON TABLE tracked_transactions IS 'Registered through POST /track/tx; a placeholder pending row in blockchain_transactions is polled until the transaction is seen';
@@ -0,0 +1,38 @@
+-- Migration: 034_loan_repayments
+-- Description: Partial loan repayments; interest accrues on the outstanding principal
+-- Date: 2025-11-25
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS principal_outstanding BIGINT,
+    -- Interest accrued up to interest_accrued_through and not yet paid
+    ADD COLUMN IF NOT EXISTS interest_carried BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS interest_paid BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS interest_accrued_through TIMESTAMPTZ;
+
+UPDATE loans
+SET principal_outstanding = CASE WHEN status = 'Repaid' THEN 0 ELSE principal_satoshis END,
+    interest_paid = CASE WHEN status = 'Repaid' THEN interest_accrued ELSE 0 END,
+    interest_accrued_through = COALESCE(repaid_at, created_at)
+WHERE principal_outstanding IS NULL;
+
+ALTER TABLE loans
+    ALTER COLUMN principal_outstanding SET NOT NULL,
+    ALTER COLUMN interest_accrued_through SET NOT NULL,
+    ADD CONSTRAINT check_principal_outstanding
+        CHECK (principal_outstanding >= 0 AND principal_outstanding <= principal_satoshis);
+
+CREATE TABLE IF NOT EXISTS loan_repayments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    interest_satoshis BIGINT NOT NULL,
+    principal_satoshis BIGINT NOT NULL,
+    late_fee_satoshis BIGINT NOT NULL DEFAULT 0,
+    principal_outstanding_after BIGINT NOT NULL,
+    paid_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_repayments_loan ON loan_repayments(loan_id, paid_at);
+
+COMMENT ON COLUMN loans.interest_accrued IS 'Interest for the full term on the original principal, quoted at origination';
+COMMENT ON TABLE loan_repayments IS 'Every payment against a loan, partial or in full, split into interest, late fee and principal';
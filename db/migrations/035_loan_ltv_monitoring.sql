@@ -0,0 +1,38 @@
+-- Migration: 035_loan_ltv_monitoring
+-- Description: Collateral price history and periodic loan-to-value checks of active loans
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS collateral_prices (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    currency VARCHAR(10) NOT NULL,
+    -- Median of the feeds that answered, per BSV
+    price DOUBLE PRECISION NOT NULL CHECK (price > 0),
+    sources TEXT[] NOT NULL,
+    observed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_collateral_prices_observed ON collateral_prices(currency, observed_at DESC);
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS ltv_bps INTEGER,
+    ADD COLUMN IF NOT EXISTS ltv_band VARCHAR(20) NOT NULL DEFAULT 'healthy'
+        CHECK (ltv_band IN ('healthy', 'warning', 'margin_call', 'liquidation')),
+    -- Collateral in the price currency at the last check
+    ADD COLUMN IF NOT EXISTS collateral_value DOUBLE PRECISION,
+    ADD COLUMN IF NOT EXISTS ltv_checked_at TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS loan_margin_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    previous_band VARCHAR(20) NOT NULL,
+    band VARCHAR(20) NOT NULL,
+    ltv_bps INTEGER NOT NULL,
+    price DOUBLE PRECISION NOT NULL,
+    currency VARCHAR(10) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_margin_events_loan ON loan_margin_events(loan_id, created_at);
+
+COMMENT ON COLUMN loans.ltv_bps IS 'Outstanding principal plus accrued interest over collateral, in basis points, at ltv_checked_at';
+COMMENT ON TABLE loan_margin_events IS 'Written when a loan crosses an LTV threshold in either direction';
@@ -0,0 +1,26 @@
+-- Migration: 036_loan_margin_calls
+-- Description: MarginCalled loan status with a cure deadline, and collateral top-ups
+-- Date: 2025-11-25
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS margin_called_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS margin_call_deadline TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS margin_call_reason VARCHAR(20)
+        CHECK (margin_call_reason IN ('ltv', 'overdue'));
+
+CREATE INDEX IF NOT EXISTS idx_loans_margin_call_deadline
+    ON loans(margin_call_deadline) WHERE status = 'MarginCalled';
+
+CREATE TABLE IF NOT EXISTS loan_collateral_topups (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    txid VARCHAR(64),
+    collateral_after BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_collateral_topups_loan ON loan_collateral_topups(loan_id, created_at);
+
+COMMENT ON COLUMN loans.margin_call_deadline IS 'Liquidation happens only after this passes without a cure';
+COMMENT ON COLUMN loans.margin_call_reason IS 'ltv: cured by collateral or repayment; overdue: cured only by repaying in full';
@@ -0,0 +1,30 @@
+-- Migration: 037_loan_liquidations
+-- Description: Liquidations run by the scheduled engine, with their collateral seizure transactions
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS loan_liquidations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('full', 'partial')),
+    reason VARCHAR(20) NOT NULL CHECK (reason IN ('ltv', 'overdue')),
+    ltv_bps INTEGER NOT NULL,
+    collateral_seized BIGINT NOT NULL CHECK (collateral_seized >= 0),
+    -- Debt settled by the seized collateral; the rest of a full liquidation is the lender's loss
+    debt_repaid BIGINT NOT NULL CHECK (debt_repaid >= 0),
+    seizure_txid VARCHAR(64),
+    seizure_tx_hex TEXT,
+    seizure_attempts INTEGER NOT NULL DEFAULT 0,
+    seizure_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_liquidations_loan ON loan_liquidations(loan_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_loan_liquidations_unseized
+    ON loan_liquidations(created_at) WHERE seizure_txid IS NULL;
+
+-- Partial liquidation takes collateral below 150% of the original principal; only enforce it at origination
+ALTER TABLE loans DROP CONSTRAINT IF EXISTS check_collateral_ratio;
+ALTER TABLE loans ADD CONSTRAINT check_collateral_ratio
+    CHECK (status <> 'Pending' OR collateral_satoshis >= principal_satoshis * 1.5);
+
+COMMENT ON COLUMN loan_liquidations.seizure_tx_hex IS 'Unsigned transaction from transaction-builder moving seized collateral out of escrow';
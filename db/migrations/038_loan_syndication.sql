@@ -0,0 +1,54 @@
+-- Migration: 038_loan_syndication
+-- Description: Loans funded by several lenders, with pro rata distribution of repayments
+-- Date: 2025-11-25
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS funded_satoshis BIGINT NOT NULL DEFAULT 0,
+    -- Set by the first commitment; commitments are released if the loan has not filled by then
+    ADD COLUMN IF NOT EXISTS funding_deadline TIMESTAMPTZ;
+
+UPDATE loans SET funded_satoshis = principal_satoshis WHERE lender_paymail IS NOT NULL;
+
+CREATE INDEX IF NOT EXISTS idx_loans_funding_deadline
+    ON loans(funding_deadline) WHERE status = 'Pending';
+
+CREATE TABLE IF NOT EXISTS loan_participations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    lender_paymail VARCHAR(255) NOT NULL,
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'Committed'
+        CHECK (status IN ('Committed', 'Active', 'Released')),
+    principal_repaid BIGINT NOT NULL DEFAULT 0,
+    -- Interest and late fees
+    interest_received BIGINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    released_at TIMESTAMPTZ,
+    UNIQUE (loan_id, lender_paymail)
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_participations_lender ON loan_participations(lender_paymail, created_at DESC);
+
+-- Loans funded before syndication have their single lender as the whole syndicate
+INSERT INTO loan_participations
+    (loan_id, lender_paymail, amount_satoshis, status, principal_repaid, interest_received, created_at)
+SELECT id, lender_paymail, principal_satoshis, 'Active',
+       principal_satoshis - principal_outstanding, interest_paid, created_at
+FROM loans
+WHERE lender_paymail IS NOT NULL
+ON CONFLICT (loan_id, lender_paymail) DO NOTHING;
+
+CREATE TABLE IF NOT EXISTS loan_distributions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    repayment_id UUID NOT NULL REFERENCES loan_repayments(id),
+    participation_id UUID NOT NULL REFERENCES loan_participations(id),
+    principal_satoshis BIGINT NOT NULL,
+    interest_satoshis BIGINT NOT NULL,
+    late_fee_satoshis BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_distributions_repayment ON loan_distributions(repayment_id);
+CREATE INDEX IF NOT EXISTS idx_loan_distributions_participation ON loan_distributions(participation_id);
+
+COMMENT ON COLUMN loans.lender_paymail IS 'Lead lender: the first to commit; every lender is in loan_participations';
@@ -0,0 +1,61 @@
+-- Migration: 039_loan_events
+-- Description: Append-only log of every loan transition with its actor and amounts
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS loan_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Insertion order for events sharing a timestamp
+    seq BIGSERIAL NOT NULL,
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    event_type VARCHAR(30) NOT NULL,
+    -- Borrower or lender paymail, or 'system' for background tasks
+    actor VARCHAR(255) NOT NULL,
+    amount_satoshis BIGINT,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_events_loan ON loan_events(loan_id, created_at, seq);
+CREATE INDEX IF NOT EXISTS idx_loan_events_type ON loan_events(event_type, created_at DESC);
+
+-- Reconstruct what the loans table still knows about existing loans
+INSERT INTO loan_events (loan_id, event_type, actor, amount_satoshis, details, created_at)
+SELECT id, 'created', borrower_paymail, principal_satoshis,
+       jsonb_build_object('backfilled', true, 'collateral_satoshis', collateral_satoshis), created_at
+FROM loans
+WHERE NOT EXISTS (SELECT 1 FROM loan_events e WHERE e.loan_id = loans.id AND e.event_type = 'created');
+
+INSERT INTO loan_events (loan_id, event_type, actor, amount_satoshis, details, created_at)
+SELECT r.loan_id, 'repayment', l.borrower_paymail, r.amount_satoshis,
+       jsonb_build_object('backfilled', true, 'repayment_id', r.id), r.paid_at
+FROM loan_repayments r
+JOIN loans l ON l.id = r.loan_id
+WHERE NOT EXISTS (
+    SELECT 1 FROM loan_events e
+    WHERE e.loan_id = r.loan_id AND e.event_type = 'repayment' AND e.details->>'repayment_id' = r.id::text
+);
+
+INSERT INTO loan_events (loan_id, event_type, actor, amount_satoshis, details, created_at)
+SELECT id, 'repaid', borrower_paymail, NULL, jsonb_build_object('backfilled', true), repaid_at
+FROM loans
+WHERE repaid_at IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM loan_events e WHERE e.loan_id = loans.id AND e.event_type = 'repaid');
+
+INSERT INTO loan_events (loan_id, event_type, actor, amount_satoshis, details, created_at)
+SELECT id, 'liquidated', 'system', collateral_satoshis, jsonb_build_object('backfilled', true), liquidated_at
+FROM loans
+WHERE liquidated_at IS NOT NULL
+  AND NOT EXISTS (SELECT 1 FROM loan_events e WHERE e.loan_id = loans.id AND e.event_type = 'liquidated');
+
+CREATE OR REPLACE FUNCTION prevent_loan_event_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'loan_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS loan_events_append_only ON loan_events;
+CREATE TRIGGER loan_events_append_only
+    BEFORE UPDATE OR DELETE ON loan_events
+    FOR EACH ROW EXECUTE FUNCTION prevent_loan_event_changes();
+
+COMMENT ON TABLE loan_events IS 'Lifecycle history of each loan; rows are never updated or deleted';
@@ -0,0 +1,20 @@
+-- Migration: 040_loan_idempotency_keys
+-- Description: Stored responses for retried funding, repayment and collateral requests
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS loan_idempotency_keys (
+    -- 'fund', 'repay', 'repayment' or 'collateral_add'
+    operation VARCHAR(30) NOT NULL,
+    -- Client-chosen Idempotency-Key header
+    idempotency_key VARCHAR(128) NOT NULL,
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    -- SHA-256 of the request body; reusing a key with another body is rejected
+    request_fingerprint VARCHAR(64) NOT NULL,
+    response JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (operation, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_idempotency_keys_created ON loan_idempotency_keys(created_at);
+
+COMMENT ON TABLE loan_idempotency_keys IS 'Written in the same transaction as the change it records, under the loan row lock';
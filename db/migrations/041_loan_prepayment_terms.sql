@@ -0,0 +1,18 @@
+-- Migration: 041_loan_prepayment_terms
+-- Description: Early payoff terms chosen at origination: interest rebate or prepayment penalty
+-- Date: 2025-11-25
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS prepayment_option VARCHAR(20) NOT NULL DEFAULT 'standard'
+        CHECK (prepayment_option IN ('standard', 'rebate', 'penalty')),
+    ADD COLUMN IF NOT EXISTS prepayment_penalty_bps INTEGER NOT NULL DEFAULT 0
+        CHECK (prepayment_penalty_bps BETWEEN 0 AND 1000);
+
+ALTER TABLE loan_repayments
+    ADD COLUMN IF NOT EXISTS prepayment_penalty_satoshis BIGINT NOT NULL DEFAULT 0;
+
+ALTER TABLE loan_distributions
+    ADD COLUMN IF NOT EXISTS prepayment_penalty_satoshis BIGINT NOT NULL DEFAULT 0;
+
+COMMENT ON COLUMN loans.prepayment_option IS 'standard: interest to the due date; rebate: interest to payoff only; penalty: interest to payoff plus prepayment_penalty_bps of outstanding principal';
+COMMENT ON COLUMN loans.prepayment_penalty_bps IS 'Charged on the outstanding principal when a penalty loan is paid off before its due date';
@@ -0,0 +1,20 @@
+-- Migration: 042_loan_offer_expiry
+-- Description: Borrower cancellation of pending loan requests and expiry of unfunded ones
+-- Date: 2025-11-25
+
+ALTER TABLE loans
+    -- Unfunded requests expire after LOAN_OFFER_EXPIRY_DAYS
+    ADD COLUMN IF NOT EXISTS offer_expires_at TIMESTAMPTZ,
+    -- When a Pending loan was Cancelled or Expired
+    ADD COLUMN IF NOT EXISTS closed_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS collateral_released_at TIMESTAMPTZ;
+
+-- Existing requests get the default window from now rather than expiring at once
+UPDATE loans SET offer_expires_at = GREATEST(created_at, NOW()) + INTERVAL '14 days'
+WHERE status = 'Pending' AND offer_expires_at IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_loans_offer_expires_at
+    ON loans(offer_expires_at) WHERE status = 'Pending';
+
+COMMENT ON COLUMN loans.offer_expires_at IS 'Pending requests with no funding by this time move to Expired';
+COMMENT ON COLUMN loans.collateral_released_at IS 'Collateral returned to the borrower on cancellation or expiry';
@@ -0,0 +1,47 @@
+-- Migration: 043_ledger_entries
+-- Description: Double-entry ledger moving balances between depositors for loan funding and repayment
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS ledger_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- The debit and credit legs of one transfer share this id and sum to zero
+    transfer_id UUID NOT NULL,
+    -- A user's paymail, or an internal account: 'loan:<id>' or 'collateral:<id>'
+    account VARCHAR(255) NOT NULL,
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis <> 0),
+    -- loan_commitment, loan_disbursement, loan_commitment_release, loan_repayment, loan_distribution
+    entry_type VARCHAR(30) NOT NULL,
+    loan_id UUID REFERENCES loans(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_account ON ledger_entries(account, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_loan ON ledger_entries(loan_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_ledger_entries_transfer ON ledger_entries(transfer_id);
+
+CREATE OR REPLACE FUNCTION prevent_ledger_entry_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'ledger_entries is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS ledger_entries_append_only ON ledger_entries;
+CREATE TRIGGER ledger_entries_append_only
+    BEFORE UPDATE OR DELETE ON ledger_entries
+    FOR EACH ROW EXECUTE FUNCTION prevent_ledger_entry_changes();
+
+-- Balances now include what users have lent, borrowed, repaid and received
+CREATE OR REPLACE VIEW user_balances AS
+SELECT
+    u.id as user_id,
+    u.paymail,
+    COALESCE(SUM(d.amount_satoshis) FILTER (WHERE d.status IN ('Confirmed', 'Available')), 0)
+        + COALESCE((SELECT SUM(le.amount_satoshis) FROM ledger_entries le WHERE le.account = u.paymail), 0) as balance_satoshis,
+    COALESCE(SUM(ia.amount_satoshis) FILTER (WHERE NOT ia.paid_out), 0) as accrued_interest_satoshis,
+    COUNT(d.id) FILTER (WHERE d.status = 'Confirmed') as active_deposits
+FROM users u
+LEFT JOIN deposits d ON u.id = d.user_id
+LEFT JOIN interest_accruals ia ON u.id = ia.user_id
+GROUP BY u.id, u.paymail;
+
+COMMENT ON TABLE ledger_entries IS 'Signed movements between accounts; every transfer_id nets to zero and rows are never changed';
@@ -0,0 +1,16 @@
+-- Migration: 044_loan_funded_at
+-- Description: Record when a loan became fully funded, read by the loan history handlers
+-- Date: 2025-11-25
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS funded_at TIMESTAMPTZ;
+
+-- Prefer the funding event; loans funded before events were recorded fall back to creation
+UPDATE loans l
+SET funded_at = COALESCE(
+    (SELECT MIN(e.created_at) FROM loan_events e WHERE e.loan_id = l.id AND e.event_type = 'funded'),
+    l.created_at
+)
+WHERE l.funded_at IS NULL AND l.lender_paymail IS NOT NULL AND l.status <> 'Pending';
+
+COMMENT ON COLUMN loans.funded_at IS 'Set when funded_satoshis reaches principal_satoshis and the loan goes Active';
@@ -0,0 +1,41 @@
+-- Migration: 045_loan_notarization
+-- Description: Signed acceptances of loan terms and their OP_RETURN anchoring with SPV proof
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS loan_acceptances (
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    paymail VARCHAR(255) NOT NULL,
+    role VARCHAR(10) NOT NULL CHECK (role IN ('borrower', 'lender')),
+    public_key VARCHAR(130) NOT NULL,
+    -- DER ECDSA signature over terms_hash
+    signature VARCHAR(150) NOT NULL,
+    -- SHA-256 of the canonical terms at the time of signing
+    terms_hash VARCHAR(64) NOT NULL,
+    accepted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (loan_id, paymail)
+);
+
+CREATE TABLE IF NOT EXISTS loan_notarizations (
+    loan_id UUID PRIMARY KEY REFERENCES loans(id),
+    terms_hash VARCHAR(64) NOT NULL,
+    -- SHA-256 over terms_hash and every acceptance; the OP_RETURN payload
+    commitment VARCHAR(64) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'broadcast', 'anchored')),
+    txid VARCHAR(64),
+    -- Merkle proof from spv-service once the anchoring transaction is mined
+    spv_proof JSONB,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    broadcast_at TIMESTAMPTZ,
+    anchored_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_notarizations_status ON loan_notarizations(status, created_at);
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS notarization_txid VARCHAR(64);
+
+COMMENT ON TABLE loan_notarizations IS 'Written once every party has accepted the current terms; anchored by the notary task';
+COMMENT ON COLUMN loans.notarization_txid IS 'Transaction whose OP_RETURN commits to the signed agreement, set once SPV-proven';
@@ -0,0 +1,33 @@
+-- Migration: 046_loan_products
+-- Description: Loan products carrying the late fee and overdue grace policy, copied onto each loan at origination
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS loan_products (
+    code VARCHAR(50) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    late_fee_daily_bps INTEGER NOT NULL CHECK (late_fee_daily_bps BETWEEN 0 AND 1000),
+    overdue_grace_days INTEGER NOT NULL CHECK (overdue_grace_days BETWEEN 0 AND 365),
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- The policy every loan had before products existed
+INSERT INTO loan_products (code, name, description, late_fee_daily_bps, overdue_grace_days)
+VALUES ('standard', 'Standard', 'Default terms for loans requested without a product', 100, 7)
+ON CONFLICT (code) DO NOTHING;
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS product_code VARCHAR(50) REFERENCES loan_products(code),
+    ADD COLUMN IF NOT EXISTS late_fee_daily_bps INTEGER NOT NULL DEFAULT 100
+        CHECK (late_fee_daily_bps BETWEEN 0 AND 1000),
+    ADD COLUMN IF NOT EXISTS overdue_grace_days INTEGER NOT NULL DEFAULT 7
+        CHECK (overdue_grace_days BETWEEN 0 AND 365);
+
+UPDATE loans SET product_code = 'standard' WHERE product_code IS NULL;
+
+COMMENT ON TABLE loan_products IS 'Admin-defined loan products; editing a product only affects loans originated afterwards';
+COMMENT ON COLUMN loans.product_code IS 'Product the loan was originated under';
+COMMENT ON COLUMN loans.late_fee_daily_bps IS 'Late fee per day past the due date, in bps of outstanding principal; fixed at origination';
+COMMENT ON COLUMN loans.overdue_grace_days IS 'Days past the due date before an unpaid loan is margin called; fixed at origination';
@@ -0,0 +1,24 @@
+-- Migration: 047_loan_collateral_releases
+-- Description: Partial collateral releases on healthy loans, and single use of top-up transactions
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS loan_collateral_releases (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    to_address VARCHAR(64),
+    txid VARCHAR(64),
+    tx_hex TEXT,
+    collateral_after BIGINT NOT NULL CHECK (collateral_after >= 0),
+    ltv_bps_after INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_collateral_releases_loan ON loan_collateral_releases(loan_id, created_at);
+
+-- An escrow deposit can top up collateral once
+CREATE UNIQUE INDEX IF NOT EXISTS idx_loan_collateral_topups_txid
+    ON loan_collateral_topups(txid) WHERE txid IS NOT NULL;
+
+COMMENT ON COLUMN loan_collateral_releases.tx_hex IS 'Unsigned transaction from transaction-builder returning collateral from escrow; NULL when escrow is disabled';
+COMMENT ON COLUMN loan_collateral_releases.ltv_bps_after IS 'LTV once the release is applied; releases must leave the loan healthy at 150% collateral';
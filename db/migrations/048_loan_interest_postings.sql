@@ -0,0 +1,27 @@
+-- Migration: 048_loan_interest_postings
+-- Description: Daily postings of accrued loan interest into lender balances, one per participation and period
+-- Date: 2025-11-25
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS interest_posted_through TIMESTAMPTZ;
+
+CREATE TABLE IF NOT EXISTS loan_interest_postings (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    loan_id UUID NOT NULL REFERENCES loans(id),
+    participation_id UUID NOT NULL REFERENCES loan_participations(id),
+    lender_paymail VARCHAR(255) NOT NULL,
+    accrued_from TIMESTAMPTZ NOT NULL,
+    accrued_to TIMESTAMPTZ NOT NULL,
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    -- Ledger transfer from the participation's interest receivable to the lender
+    transfer_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (accrued_to > accrued_from),
+    UNIQUE (participation_id, accrued_to)
+);
+
+CREATE INDEX IF NOT EXISTS idx_loan_interest_postings_lender ON loan_interest_postings(lender_paymail, accrued_to DESC);
+CREATE INDEX IF NOT EXISTS idx_loan_interest_postings_loan ON loan_interest_postings(loan_id, accrued_to);
+
+COMMENT ON COLUMN loans.interest_posted_through IS 'Lenders have been credited interest up to here, by posting or by a repayment paying it directly';
+COMMENT ON TABLE loan_interest_postings IS 'Interest credited to lenders as it accrues; repayments settle interest_receivable:<participation> instead of paying it again';
@@ -0,0 +1,25 @@
+-- Migration: 049_deposit_addresses
+-- Description: Per-user deposit addresses, and one credit per transaction output
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS deposit_addresses (
+    address VARCHAR(64) PRIMARY KEY,
+    -- NULL until handed to a user; each user has one address
+    paymail VARCHAR(255) UNIQUE,
+    assigned_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_deposit_addresses_unassigned ON deposit_addresses(created_at) WHERE paymail IS NULL;
+
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS vout INTEGER,
+    ADD COLUMN IF NOT EXISTS deposit_address VARCHAR(64);
+
+-- A transaction may pay several users; each output is credited once
+ALTER TABLE deposits DROP CONSTRAINT IF EXISTS unique_txid;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_deposits_txid_vout ON deposits(txid, vout) WHERE vout IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_deposits_txid_legacy ON deposits(txid) WHERE vout IS NULL;
+
+COMMENT ON TABLE deposit_addresses IS 'Bank-controlled P2PKH addresses loaded by operators and assigned to users on first request';
+COMMENT ON COLUMN deposits.vout IS 'Output of txid paying the user''s deposit address; NULL for deposits credited before output checks';
@@ -0,0 +1,35 @@
+-- Migration: 051_term_deposit_products
+-- Description: Term deposit products with an APY boost, early redemption penalty and maturity handling
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS term_deposit_products (
+    code VARCHAR(50) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    duration_days INTEGER NOT NULL CHECK (duration_days > 0),
+    apy_boost_bps INTEGER NOT NULL DEFAULT 0 CHECK (apy_boost_bps >= 0),
+    early_withdrawal_penalty_bps INTEGER NOT NULL DEFAULT 0 CHECK (early_withdrawal_penalty_bps BETWEEN 0 AND 10000),
+    -- Default for deposits opened on this product; depositors may change it per deposit
+    auto_roll BOOLEAN NOT NULL DEFAULT false,
+    active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Terms are copied onto the deposit so product edits never change an open term
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS term_product_code VARCHAR(50) REFERENCES term_deposit_products(code),
+    ADD COLUMN IF NOT EXISTS term_duration_days INTEGER,
+    ADD COLUMN IF NOT EXISTS term_apy_boost_bps INTEGER,
+    ADD COLUMN IF NOT EXISTS early_withdrawal_penalty_bps INTEGER,
+    ADD COLUMN IF NOT EXISTS auto_roll BOOLEAN NOT NULL DEFAULT false,
+    ADD COLUMN IF NOT EXISTS roll_count INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS matured_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS redeemed_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS early_withdrawal_penalty_satoshis BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_deposits_term_maturity ON deposits(lock_until)
+    WHERE term_product_code IS NOT NULL AND matured_at IS NULL AND redeemed_at IS NULL;
+
+COMMENT ON COLUMN deposits.lock_until IS 'Funds are held out of the available balance until then; term deposits roll it forward or mature';
+COMMENT ON COLUMN deposits.redeemed_at IS 'Term ended early at the depositor''s request, charging early_withdrawal_penalty_satoshis';
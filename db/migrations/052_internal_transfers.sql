@@ -0,0 +1,25 @@
+-- Migration: 052_internal_transfers
+-- Description: Instant transfers between users, settled on the ledger without a chain transaction
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS internal_transfers (
+    -- Same id as the transfer_id of its two ledger_entries rows
+    id UUID PRIMARY KEY,
+    from_paymail VARCHAR(255) NOT NULL,
+    to_paymail VARCHAR(255) NOT NULL,
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    memo VARCHAR(280),
+    -- Client-chosen Idempotency-Key header, scoped to the sender
+    idempotency_key VARCHAR(128),
+    -- SHA-256 of the request body; reusing a key with another body is rejected
+    request_fingerprint VARCHAR(64),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (from_paymail <> to_paymail)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_internal_transfers_idempotency
+    ON internal_transfers(from_paymail, idempotency_key) WHERE idempotency_key IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_internal_transfers_from ON internal_transfers(from_paymail, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_internal_transfers_to ON internal_transfers(to_paymail, created_at DESC);
+
+COMMENT ON TABLE internal_transfers IS 'Written in the same transaction as the ledger entries it describes, under the sender''s advisory lock';
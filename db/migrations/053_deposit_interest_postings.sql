@@ -0,0 +1,24 @@
+-- Migration: 053_deposit_interest_postings
+-- Description: Post interest-engine accruals into depositor ledger balances
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS interest_postings (
+    -- Same id as the transfer_id of its two ledger_entries rows
+    id UUID PRIMARY KEY,
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    paymail VARCHAR(255) NOT NULL,
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis > 0),
+    accrual_count INTEGER NOT NULL CHECK (accrual_count > 0),
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE interest_accruals
+    ADD COLUMN IF NOT EXISTS posting_id UUID REFERENCES interest_postings(id);
+
+CREATE INDEX IF NOT EXISTS idx_interest_postings_paymail ON interest_postings(paymail, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_interest_accruals_unposted ON interest_accruals(user_id, period_end) WHERE NOT paid_out;
+
+COMMENT ON TABLE interest_postings IS 'Accrued deposit interest credited to the ledger; each accrual is marked paid_out with its posting_id in the same transaction';
+COMMENT ON COLUMN interest_accruals.posting_id IS 'Posting that moved this accrual out of accrued_interest_satoshis and into the balance';
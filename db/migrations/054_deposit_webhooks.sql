@@ -0,0 +1,46 @@
+-- Migration: 054_deposit_webhooks
+-- Description: Webhook subscriptions for deposit and withdrawal events, and their delivery log
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS deposit_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- NULL for internal services subscribed to every user's events
+    paymail VARCHAR(255),
+    url TEXT NOT NULL,
+    secret VARCHAR(64) NOT NULL,
+    -- Empty array means "all events"
+    events TEXT[] NOT NULL DEFAULT '{}',
+    active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_deposit_webhooks_subscriber ON deposit_webhooks((COALESCE(paymail, '')), url);
+CREATE INDEX IF NOT EXISTS idx_deposit_webhooks_paymail ON deposit_webhooks(paymail) WHERE active;
+
+COMMENT ON TABLE deposit_webhooks IS 'Callback URLs for deposit.* and withdrawal.* events, per user or (paymail NULL) for all users';
+COMMENT ON COLUMN deposit_webhooks.secret IS 'HMAC-SHA256 key used to sign deliveries';
+
+CREATE TABLE IF NOT EXISTS deposit_webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES deposit_webhooks(id) ON DELETE CASCADE,
+    event_type VARCHAR(50) NOT NULL,
+    payload JSONB NOT NULL,
+    attempts INT NOT NULL DEFAULT 0,
+    response_status INT,
+    delivered BOOLEAN NOT NULL DEFAULT false,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    delivered_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_deposit_webhook_deliveries_webhook ON deposit_webhook_deliveries(webhook_id);
+CREATE INDEX IF NOT EXISTS idx_deposit_webhook_deliveries_pending ON deposit_webhook_deliveries(created_at) WHERE NOT delivered;
+
+-- Withdrawal status changes are announced by polling, whichever process moves them
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS notified_status VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS idx_withdrawals_unnotified ON withdrawals(created_at)
+    WHERE status IN ('Broadcast', 'Confirmed') AND notified_status IS DISTINCT FROM status;
+
+COMMENT ON COLUMN withdrawals.notified_status IS 'Last status announced to webhook subscribers';
@@ -0,0 +1,51 @@
+-- Migration: 055_kyc_tiers
+-- Description: KYC tiers with daily and monthly deposit/withdrawal limits, and withdrawal requests
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS kyc_tiers (
+    tier VARCHAR(20) PRIMARY KEY,
+    -- NULL limits are unlimited
+    daily_deposit_limit_satoshis BIGINT CHECK (daily_deposit_limit_satoshis >= 0),
+    monthly_deposit_limit_satoshis BIGINT CHECK (monthly_deposit_limit_satoshis >= 0),
+    daily_withdrawal_limit_satoshis BIGINT CHECK (daily_withdrawal_limit_satoshis >= 0),
+    monthly_withdrawal_limit_satoshis BIGINT CHECK (monthly_withdrawal_limit_satoshis >= 0),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO kyc_tiers (tier, daily_deposit_limit_satoshis, monthly_deposit_limit_satoshis,
+                       daily_withdrawal_limit_satoshis, monthly_withdrawal_limit_satoshis)
+VALUES
+    ('basic', 100000000, 1000000000, 50000000, 500000000),
+    ('verified', 2000000000, 20000000000, 1000000000, 10000000000),
+    ('institutional', NULL, NULL, 50000000000, 500000000000)
+ON CONFLICT (tier) DO NOTHING;
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS kyc_tier VARCHAR(20) NOT NULL DEFAULT 'basic' REFERENCES kyc_tiers(tier);
+
+CREATE TABLE IF NOT EXISTS kyc_tier_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    from_tier VARCHAR(20) NOT NULL,
+    to_tier VARCHAR(20) NOT NULL REFERENCES kyc_tiers(tier),
+    changed_by VARCHAR(255) NOT NULL,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_kyc_tier_changes_user ON kyc_tier_changes(user_id, created_at DESC);
+
+-- Withdrawals are requested against the balance, not a particular deposit
+ALTER TABLE withdrawals
+    ALTER COLUMN deposit_id DROP NOT NULL,
+    ALTER COLUMN signature DROP NOT NULL,
+    ADD COLUMN IF NOT EXISTS settled_transfer_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_withdrawals_user_created ON withdrawals(user_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_withdrawals_unsettled ON withdrawals(created_at)
+    WHERE status = 'Confirmed' AND settled_transfer_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_deposits_user_created ON deposits(user_id, created_at);
+
+COMMENT ON TABLE kyc_tiers IS 'Limits apply per UTC calendar day and month, counting deposits in any status and withdrawals not Failed or Rejected';
+COMMENT ON COLUMN withdrawals.settled_transfer_id IS 'Ledger debit of the user once the withdrawal confirms; until then it is held as in flight';
@@ -0,0 +1,30 @@
+-- Migration: 056_withdrawal_security
+-- Description: TOTP second factor for large withdrawals and per-user withdrawal address whitelists
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS user_two_factor (
+    user_id INTEGER PRIMARY KEY REFERENCES users(id),
+    -- Base32 TOTP secret; replaced on each setup until enabled
+    secret VARCHAR(64) NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT false,
+    -- Highest time step accepted, so a code cannot be used twice
+    last_used_step BIGINT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    enabled_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS withdrawal_address_whitelist (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    address VARCHAR(64) NOT NULL,
+    label VARCHAR(100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    -- Additions cool down before they can receive withdrawals
+    active_at TIMESTAMPTZ NOT NULL,
+    removed_at TIMESTAMPTZ
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_withdrawal_whitelist_address
+    ON withdrawal_address_whitelist(user_id, address) WHERE removed_at IS NULL;
+
+COMMENT ON TABLE withdrawal_address_whitelist IS 'Once a user has any entry, withdrawals may only go to entries past active_at';
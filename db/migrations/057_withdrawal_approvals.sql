@@ -0,0 +1,72 @@
+-- Migration: 057_withdrawal_approvals
+-- Description: Maker-checker approval for withdrawals above a threshold, with an audit trail
+-- Date: 2025-11-25
+
+-- Withdrawals above WITHDRAWAL_APPROVAL_THRESHOLD_SATOSHIS start as PendingApproval and
+-- become Pending (ready to broadcast) only once an approver signs off
+CREATE TABLE IF NOT EXISTS withdrawal_events (
+    id BIGSERIAL PRIMARY KEY,
+    withdrawal_id UUID NOT NULL REFERENCES withdrawals(id),
+    -- approval_requested, approved or rejected
+    event VARCHAR(30) NOT NULL,
+    -- Paymail (token subject) of whoever caused the event
+    actor VARCHAR(255) NOT NULL,
+    reason TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_withdrawal_events_withdrawal
+    ON withdrawal_events(withdrawal_id, created_at);
+CREATE INDEX IF NOT EXISTS idx_withdrawals_pending_approval
+    ON withdrawals(created_at) WHERE status = 'PendingApproval';
+
+COMMENT ON TABLE withdrawal_events IS 'Append-only audit trail of withdrawal approval decisions';
+
+-- Withdrawals awaiting approval are held like any other in-flight withdrawal
+CREATE OR REPLACE VIEW user_balances AS
+SELECT
+    b.user_id,
+    b.paymail,
+    b.balance_satoshis,
+    b.accrued_interest_satoshis,
+    b.active_deposits,
+    b.pending_deposits_satoshis,
+    b.withdrawals_in_flight_satoshis,
+    b.locked_deposits_satoshis,
+    b.loan_collateral_satoshis,
+    b.channel_funds_satoshis,
+    b.balance_satoshis
+        - b.withdrawals_in_flight_satoshis
+        - b.locked_deposits_satoshis
+        - b.loan_collateral_satoshis
+        - b.channel_funds_satoshis AS available_satoshis
+FROM (
+    SELECT
+        u.id as user_id,
+        u.paymail,
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status IN ('Confirmed', 'Available')), 0)
+            + COALESCE((SELECT SUM(le.amount_satoshis) FROM ledger_entries le WHERE le.account = u.paymail), 0) as balance_satoshis,
+        COALESCE((SELECT SUM(ia.amount_satoshis) FROM interest_accruals ia
+                  WHERE ia.user_id = u.id AND NOT ia.paid_out), 0) as accrued_interest_satoshis,
+        (SELECT COUNT(*) FROM deposits d WHERE d.user_id = u.id AND d.status = 'Confirmed') as active_deposits,
+        -- Not yet in the balance: deposits waiting for an SPV proof
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status = 'Pending'), 0) as pending_deposits_satoshis,
+        -- In the balance but not spendable
+        COALESCE((SELECT SUM(w.amount_satoshis) FROM withdrawals w
+                  WHERE w.user_id = u.id AND w.status IN ('PendingApproval', 'Pending', 'Broadcast')), 0) as withdrawals_in_flight_satoshis,
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status IN ('Confirmed', 'Available') AND d.lock_until > NOW()), 0) as locked_deposits_satoshis,
+        COALESCE((SELECT SUM(l.collateral_satoshis) FROM loans l
+                  WHERE l.borrower_paymail = u.paymail AND l.status IN ('Pending', 'Active', 'MarginCalled')
+                    AND l.collateral_txid IS NULL), 0) as loan_collateral_satoshis,
+        COALESCE((SELECT SUM(CASE WHEN c.party_a_paymail = u.paymail THEN c.current_balance_a ELSE 0 END
+                           + CASE WHEN c.party_b_paymail = u.paymail THEN c.current_balance_b ELSE 0 END)
+                  FROM payment_channels c
+                  WHERE (c.party_a_paymail = u.paymail OR c.party_b_paymail = u.paymail)
+                    AND c.status <> 'Closed'), 0) as channel_funds_satoshis
+    FROM users u
+) b;
+
+COMMENT ON VIEW user_balances IS 'balance_satoshis is everything a user owns; available_satoshis excludes in-flight withdrawals (including those awaiting approval), locked term deposits, loan collateral held without an escrow transaction, and funds in open channels';
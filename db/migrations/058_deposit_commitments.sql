@@ -0,0 +1,20 @@
+-- Migration: 058_deposit_commitments
+-- Description: Anchor each deposit's commitment hash on chain in an OP_RETURN, with its SPV proof
+-- Date: 2025-11-25
+
+-- commitment_status: pending until broadcast, broadcast once in a transaction,
+-- anchored once the transaction's SPV proof is stored
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_hash VARCHAR(64);
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_status VARCHAR(20);
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_txid VARCHAR(64);
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_spv_proof JSONB;
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_attempts INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_last_error TEXT;
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_broadcast_at TIMESTAMPTZ;
+ALTER TABLE deposits ADD COLUMN IF NOT EXISTS commitment_anchored_at TIMESTAMPTZ;
+
+CREATE INDEX IF NOT EXISTS idx_deposits_commitment_unanchored
+    ON deposits(created_at) WHERE commitment_status IN ('pending', 'broadcast');
+
+COMMENT ON COLUMN deposits.commitment_hash IS 'SHA-256 of DEPOSIT|paymail|amount|txid|unix time, written to an OP_RETURN as proof of processing';
+COMMENT ON COLUMN deposits.commitment_txid IS 'Transaction carrying the commitment; NULL until broadcast';
@@ -0,0 +1,39 @@
+-- Migration: 059_reconciliation
+-- Description: Scheduled reconciliation of on-chain holdings against ledger liabilities
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS reconciliation_runs (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- balanced, drift, or incomplete when an address balance could not be fetched
+    status VARCHAR(20) NOT NULL,
+    addresses_checked INTEGER NOT NULL,
+    addresses_failed INTEGER NOT NULL DEFAULT 0,
+    onchain_confirmed_satoshis BIGINT NOT NULL,
+    onchain_unconfirmed_satoshis BIGINT NOT NULL,
+    -- Sum of depositor balances, less withdrawals already paid out but not yet settled in the ledger
+    liabilities_satoshis BIGINT NOT NULL,
+    unsettled_withdrawals_satoshis BIGINT NOT NULL,
+    -- On-chain total minus liabilities; negative is a shortfall
+    drift_satoshis BIGINT NOT NULL,
+    tolerance_satoshis BIGINT NOT NULL,
+    started_at TIMESTAMPTZ NOT NULL,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_reconciliation_runs_completed ON reconciliation_runs(completed_at DESC);
+
+CREATE TABLE IF NOT EXISTS reconciliation_discrepancies (
+    id BIGSERIAL PRIMARY KEY,
+    run_id UUID NOT NULL REFERENCES reconciliation_runs(id),
+    -- drift or address_unavailable
+    kind VARCHAR(30) NOT NULL,
+    address VARCHAR(64),
+    expected_satoshis BIGINT,
+    actual_satoshis BIGINT,
+    detail TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_reconciliation_discrepancies_run ON reconciliation_discrepancies(run_id);
+
+COMMENT ON TABLE reconciliation_runs IS 'One row per reconciliation pass over deposit addresses and RECONCILIATION_EXTRA_ADDRESSES';
@@ -0,0 +1,18 @@
+-- Migration: 060_deposit_idempotency
+-- Description: Idempotency-Key support on deposit and withdrawal creation
+-- Date: 2025-11-25
+
+-- Client-chosen Idempotency-Key header, scoped to the user, and the SHA-256 of the request
+-- it was first used with; reusing a key with another request is rejected
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(128),
+    ADD COLUMN IF NOT EXISTS request_fingerprint VARCHAR(64);
+
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS idempotency_key VARCHAR(128),
+    ADD COLUMN IF NOT EXISTS request_fingerprint VARCHAR(64);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_deposits_idempotency
+    ON deposits(user_id, idempotency_key) WHERE idempotency_key IS NOT NULL;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_withdrawals_idempotency
+    ON withdrawals(user_id, idempotency_key) WHERE idempotency_key IS NOT NULL;
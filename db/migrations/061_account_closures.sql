@@ -0,0 +1,32 @@
+-- Migration: 061_account_closures
+-- Description: Account closure: deposits blocked, remaining balance swept on chain, account archived
+-- Date: 2025-11-25
+
+CREATE TABLE IF NOT EXISTS account_closures (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    user_id INTEGER NOT NULL REFERENCES users(id),
+    paymail VARCHAR(255) NOT NULL,
+    -- Closing until the sweep settles, then Closed; SweepFailed reopens the account
+    status VARCHAR(20) NOT NULL DEFAULT 'Closing'
+        CHECK (status IN ('Closing', 'Closed', 'SweepFailed')),
+    destination_address VARCHAR(64) NOT NULL,
+    -- NULL when nothing was left to sweep
+    sweep_withdrawal_id UUID REFERENCES withdrawals(id),
+    sweep_satoshis BIGINT NOT NULL DEFAULT 0,
+    -- Paymail (token subject) of whoever requested the closure
+    requested_by VARCHAR(255) NOT NULL,
+    -- Statement from account opening to closure, as served by GET /statements
+    final_statement JSONB,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    closed_at TIMESTAMPTZ
+);
+
+-- At most one closure in progress or completed per user
+CREATE UNIQUE INDEX IF NOT EXISTS idx_account_closures_user
+    ON account_closures(user_id) WHERE status IN ('Closing', 'Closed');
+CREATE INDEX IF NOT EXISTS idx_account_closures_closing
+    ON account_closures(created_at) WHERE status = 'Closing';
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS archived_at TIMESTAMPTZ;
+
+COMMENT ON TABLE account_closures IS 'Accounts with a Closing or Closed row accept no deposits or incoming transfers';
@@ -0,0 +1,89 @@
+-- Migration: 062_fee_schedule
+-- Description: Configurable deposit and withdrawal fees, charged as ledger entries
+-- Date: 2025-11-25
+
+-- The most specific active rule applies: a term product match beats a tier match, which
+-- beats a catch-all. With no matching rule the operation is free.
+CREATE TABLE IF NOT EXISTS fee_rules (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    operation VARCHAR(20) NOT NULL CHECK (operation IN ('deposit', 'withdrawal')),
+    -- NULL matches every tier
+    kyc_tier VARCHAR(20) REFERENCES kyc_tiers(tier),
+    -- NULL matches every deposit, term or not; withdrawals only match NULL
+    term_product_code VARCHAR(50) REFERENCES term_deposit_products(code),
+    flat_satoshis BIGINT NOT NULL DEFAULT 0 CHECK (flat_satoshis >= 0),
+    bps INTEGER NOT NULL DEFAULT 0 CHECK (bps BETWEEN 0 AND 10000),
+    -- NULL is uncapped
+    max_fee_satoshis BIGINT CHECK (max_fee_satoshis >= 0),
+    active BOOLEAN NOT NULL DEFAULT true,
+    updated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (operation = 'deposit' OR term_product_code IS NULL)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_fee_rules_scope
+    ON fee_rules(operation, COALESCE(kyc_tier, ''), COALESCE(term_product_code, ''));
+
+-- Deposit fees are charged when the deposit is credited; withdrawal fees are held with the
+-- withdrawal and charged when it settles
+ALTER TABLE deposits
+    ADD COLUMN IF NOT EXISTS fee_satoshis BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS fee_transfer_id UUID;
+
+ALTER TABLE withdrawals
+    ADD COLUMN IF NOT EXISTS fee_satoshis BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS fee_transfer_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_deposits_fee_unposted ON deposits(created_at)
+    WHERE fee_satoshis > 0 AND fee_transfer_id IS NULL;
+
+-- Withdrawal fees are held alongside the amount until the withdrawal settles
+CREATE OR REPLACE VIEW user_balances AS
+SELECT
+    b.user_id,
+    b.paymail,
+    b.balance_satoshis,
+    b.accrued_interest_satoshis,
+    b.active_deposits,
+    b.pending_deposits_satoshis,
+    b.withdrawals_in_flight_satoshis,
+    b.locked_deposits_satoshis,
+    b.loan_collateral_satoshis,
+    b.channel_funds_satoshis,
+    b.balance_satoshis
+        - b.withdrawals_in_flight_satoshis
+        - b.locked_deposits_satoshis
+        - b.loan_collateral_satoshis
+        - b.channel_funds_satoshis AS available_satoshis
+FROM (
+    SELECT
+        u.id as user_id,
+        u.paymail,
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status IN ('Confirmed', 'Available')), 0)
+            + COALESCE((SELECT SUM(le.amount_satoshis) FROM ledger_entries le WHERE le.account = u.paymail), 0) as balance_satoshis,
+        COALESCE((SELECT SUM(ia.amount_satoshis) FROM interest_accruals ia
+                  WHERE ia.user_id = u.id AND NOT ia.paid_out), 0) as accrued_interest_satoshis,
+        (SELECT COUNT(*) FROM deposits d WHERE d.user_id = u.id AND d.status = 'Confirmed') as active_deposits,
+        -- Not yet in the balance: deposits waiting for an SPV proof
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status = 'Pending'), 0) as pending_deposits_satoshis,
+        -- In the balance but not spendable
+        COALESCE((SELECT SUM(w.amount_satoshis + w.fee_satoshis) FROM withdrawals w
+                  WHERE w.user_id = u.id AND w.status IN ('PendingApproval', 'Pending', 'Broadcast')), 0) as withdrawals_in_flight_satoshis,
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status IN ('Confirmed', 'Available') AND d.lock_until > NOW()), 0) as locked_deposits_satoshis,
+        COALESCE((SELECT SUM(l.collateral_satoshis) FROM loans l
+                  WHERE l.borrower_paymail = u.paymail AND l.status IN ('Pending', 'Active', 'MarginCalled')
+                    AND l.collateral_txid IS NULL), 0) as loan_collateral_satoshis,
+        COALESCE((SELECT SUM(CASE WHEN c.party_a_paymail = u.paymail THEN c.current_balance_a ELSE 0 END
+                           + CASE WHEN c.party_b_paymail = u.paymail THEN c.current_balance_b ELSE 0 END)
+                  FROM payment_channels c
+                  WHERE (c.party_a_paymail = u.paymail OR c.party_b_paymail = u.paymail)
+                    AND c.status <> 'Closed'), 0) as channel_funds_satoshis
+    FROM users u
+) b;
+
+COMMENT ON VIEW user_balances IS 'balance_satoshis is everything a user owns; available_satoshis excludes in-flight withdrawals and their fees (including those awaiting approval), locked term deposits, loan collateral held without an escrow transaction, and funds in open channels';
+COMMENT ON TABLE fee_rules IS 'Fee = flat_satoshis + amount * bps / 10000, capped at max_fee_satoshis; deposit fees never exceed the deposit';
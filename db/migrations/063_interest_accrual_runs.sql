@@ -0,0 +1,39 @@
+-- Migration: 063_interest_accrual_runs
+-- Description: Scheduled daily interest accrual with per-product compounding, idempotent per period
+-- Date: 2025-11-25
+
+-- Interest products: 'demand' for the ordinary balance, and optionally one row per term
+-- deposit product code. Term deposits on a product without a row use 'demand'.
+CREATE TABLE IF NOT EXISTS interest_products (
+    code VARCHAR(50) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    -- simple: interest on principal only; daily/continuous: unposted accruals earn interest too
+    compounding VARCHAR(20) NOT NULL DEFAULT 'simple'
+        CHECK (compounding IN ('simple', 'daily', 'continuous')),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO interest_products (code, name) VALUES ('demand', 'Demand deposits')
+ON CONFLICT (code) DO NOTHING;
+
+-- One row per accrued UTC day, written in the same transaction as its accruals
+CREATE TABLE IF NOT EXISTS interest_accrual_runs (
+    period_start TIMESTAMPTZ PRIMARY KEY,
+    period_end TIMESTAMPTZ NOT NULL,
+    accrual_count INTEGER NOT NULL DEFAULT 0,
+    accrued_satoshis BIGINT NOT NULL DEFAULT 0,
+    completed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- deposit_id is set for term deposit accruals and NULL for the demand balance
+ALTER TABLE interest_accruals
+    ADD COLUMN IF NOT EXISTS product_code VARCHAR(50),
+    ADD COLUMN IF NOT EXISTS compounding VARCHAR(20);
+
+-- A period is accrued at most once per balance, however many times it is run
+CREATE UNIQUE INDEX IF NOT EXISTS idx_interest_accruals_period
+    ON interest_accruals(user_id, COALESCE(deposit_id, '00000000-0000-0000-0000-000000000000'::uuid), period_start)
+    WHERE product_code IS NOT NULL;
+
+COMMENT ON TABLE interest_accrual_runs IS 'Completed accrual periods; the accrual job catches up on any day without a row';
@@ -0,0 +1,23 @@
+-- Migration: 064_interest_rate_models
+-- Description: Per-product interest rate model (kinked curve, fixed or governance-set) with its parameters
+-- Date: 2025-11-25
+
+-- {"model": "kinked" | "fixed" | "governance", ...parameters}; new products default to
+-- the flat 7% depositors earned before rate models existed
+ALTER TABLE interest_products
+    ADD COLUMN IF NOT EXISTS rate_model JSONB NOT NULL
+        DEFAULT '{"model": "fixed", "borrow_apy": 0.0, "supply_apy": 0.07}'::jsonb;
+
+ALTER TABLE interest_products
+    ADD CONSTRAINT check_interest_products_rate_model
+    CHECK (rate_model->>'model' IN ('kinked', 'fixed', 'governance'));
+
+-- The lending pool keeps the curve that was hard-coded in the interest engine
+INSERT INTO interest_products (code, name, rate_model) VALUES (
+    'lending-pool',
+    'Lending pool',
+    '{"model": "kinked", "base_rate": 0.02, "optimal_utilization": 0.80, "slope_low": 0.10, "slope_high": 1.00, "supplier_share": 0.90}'::jsonb
+)
+ON CONFLICT (code) DO NOTHING;
+
+COMMENT ON COLUMN interest_products.rate_model IS 'Selected via PUT /rates/models/{product}; supply_apy is what depositors on the product earn';
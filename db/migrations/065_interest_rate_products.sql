@@ -0,0 +1,11 @@
+-- Migration: 065_interest_rate_products
+-- Description: Rate snapshots per interest product
+-- Date: 2025-11-25
+
+-- Snapshots taken before products existed priced the lending pool
+ALTER TABLE interest_rates
+    ADD COLUMN IF NOT EXISTS product_code VARCHAR(50) NOT NULL DEFAULT 'lending-pool';
+
+CREATE INDEX IF NOT EXISTS idx_interest_rates_product ON interest_rates(product_code, created_at DESC);
+
+COMMENT ON COLUMN interest_rates.product_code IS 'interest_products.code whose rate model priced this snapshot';
@@ -0,0 +1,24 @@
+-- Migration: 066_interest_posting_settlement
+-- Description: Interest postings written by interest-engine and settled into balances by deposit-service
+-- Date: 2025-11-26
+
+-- Postings made before this migration were credited as they were written
+ALTER TABLE interest_postings
+    ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'Settled'
+        CHECK (status IN ('Pending', 'Settled')),
+    ADD COLUMN IF NOT EXISTS transfer_id UUID,
+    ADD COLUMN IF NOT EXISTS settled_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS push_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS last_push_error TEXT;
+
+UPDATE interest_postings
+SET transfer_id = id, settled_at = created_at
+WHERE status = 'Settled' AND transfer_id IS NULL;
+
+ALTER TABLE interest_postings ALTER COLUMN status SET DEFAULT 'Pending';
+
+CREATE INDEX IF NOT EXISTS idx_interest_postings_pending
+    ON interest_postings(created_at) WHERE status = 'Pending';
+
+COMMENT ON TABLE interest_postings IS 'Accrued interest grouped per user on the posting schedule; Pending until deposit-service credits it';
+COMMENT ON COLUMN interest_postings.transfer_id IS 'Ledger transfer that credited the posting to the depositor balance';
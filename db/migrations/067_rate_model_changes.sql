@@ -0,0 +1,57 @@
+-- Migration: 067_rate_model_changes
+-- Description: Governance of rate model parameters: proposed changes with an effective time, optional second approval and an append-only audit trail
+-- Date: 2025-11-26
+
+CREATE TABLE IF NOT EXISTS rate_model_changes (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    product_code VARCHAR(50) NOT NULL,
+    -- Only used when the change creates the product
+    product_name VARCHAR(100),
+    rate_model JSONB NOT NULL,
+    -- The model the change replaced, recorded when it is applied
+    previous_model JSONB,
+    effective_at TIMESTAMPTZ NOT NULL,
+    requires_approval BOOLEAN NOT NULL,
+    status VARCHAR(20) NOT NULL
+        CHECK (status IN ('Proposed', 'Approved', 'Applied', 'Rejected')),
+    proposed_by VARCHAR(255) NOT NULL,
+    approved_by VARCHAR(255),
+    applied_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CONSTRAINT check_rate_model_changes_model
+        CHECK (rate_model->>'model' IN ('kinked', 'fixed', 'governance'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_rate_model_changes_product ON rate_model_changes(product_code, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_rate_model_changes_due
+    ON rate_model_changes(effective_at) WHERE status = 'Approved';
+
+CREATE TABLE IF NOT EXISTS rate_model_change_events (
+    id BIGSERIAL PRIMARY KEY,
+    change_id UUID NOT NULL REFERENCES rate_model_changes(id),
+    product_code VARCHAR(50) NOT NULL,
+    -- proposed | approved | rejected | applied
+    event VARCHAR(20) NOT NULL,
+    -- Admin token subject, or 'system' for the scheduled apply
+    actor VARCHAR(255) NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}'::jsonb,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_rate_model_change_events_change ON rate_model_change_events(change_id, id);
+CREATE INDEX IF NOT EXISTS idx_rate_model_change_events_product ON rate_model_change_events(product_code, created_at DESC);
+
+CREATE OR REPLACE FUNCTION prevent_rate_model_change_event_changes() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'rate_model_change_events is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS rate_model_change_events_append_only ON rate_model_change_events;
+CREATE TRIGGER rate_model_change_events_append_only
+    BEFORE UPDATE OR DELETE ON rate_model_change_events
+    FOR EACH ROW EXECUTE FUNCTION prevent_rate_model_change_event_changes();
+
+COMMENT ON TABLE rate_model_changes IS 'Rate model changes proposed via POST /rates/changes; Approved changes apply at effective_at';
+COMMENT ON TABLE rate_model_change_events IS 'Who proposed, approved, rejected and applied each rate model change; rows are never updated or deleted';
@@ -0,0 +1,40 @@
+-- Migration: 068_interest_accrual_adjustments
+-- Description: Signed accrual adjustments from recomputing past periods, and signed interest postings
+-- Date: 2025-11-26
+
+-- One recompute applied via POST /interest/accruals/recompute
+CREATE TABLE IF NOT EXISTS interest_accrual_adjustments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    -- Only this depositor was recomputed; NULL for everyone
+    paymail VARCHAR(255),
+    reason TEXT NOT NULL,
+    requested_by VARCHAR(255) NOT NULL,
+    adjustment_count INTEGER NOT NULL,
+    net_satoshis BIGINT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (period_end > period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_interest_accrual_adjustments_created ON interest_accrual_adjustments(created_at DESC);
+
+-- Adjustments are further accrual rows for a period, so the one-accrual-per-period rule
+-- only covers the rows the accrual job writes
+ALTER TABLE interest_accruals
+    ADD COLUMN IF NOT EXISTS adjustment_id UUID REFERENCES interest_accrual_adjustments(id);
+
+DROP INDEX IF EXISTS idx_interest_accruals_period;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_interest_accruals_period
+    ON interest_accruals(user_id, COALESCE(deposit_id, '00000000-0000-0000-0000-000000000000'::uuid), period_start)
+    WHERE product_code IS NOT NULL AND adjustment_id IS NULL;
+
+CREATE INDEX IF NOT EXISTS idx_interest_accruals_period_start ON interest_accruals(period_start);
+
+-- A posting whose accruals net negative takes back interest credited in error
+ALTER TABLE interest_postings DROP CONSTRAINT IF EXISTS interest_postings_amount_satoshis_check;
+ALTER TABLE interest_postings
+    ADD CONSTRAINT interest_postings_amount_satoshis_check CHECK (amount_satoshis <> 0);
+
+COMMENT ON COLUMN interest_accruals.adjustment_id IS 'Set on signed corrections to an accrued period; NULL on accruals written by the accrual job';
+COMMENT ON TABLE interest_accrual_adjustments IS 'Recomputed accrual periods; history is corrected by adding rows, never by changing them';
@@ -0,0 +1,42 @@
+-- Migration: 069_interest_reserves
+-- Description: Explicit reserve factor on kinked rate models, and the bank's share of pool interest accrued into a treasury ledger account
+-- Date: 2025-11-27
+
+-- Kinked models kept supplier_share of borrower interest for suppliers; they now keep
+-- reserve_factor = 1 - supplier_share for the bank
+UPDATE interest_products
+SET rate_model = (rate_model - 'supplier_share')
+    || jsonb_build_object('reserve_factor', 1 - (rate_model->>'supplier_share')::NUMERIC)
+WHERE rate_model->>'model' = 'kinked' AND rate_model ? 'supplier_share';
+
+UPDATE rate_model_changes
+SET rate_model = (rate_model - 'supplier_share')
+    || jsonb_build_object('reserve_factor', 1 - (rate_model->>'supplier_share')::NUMERIC)
+WHERE rate_model->>'model' = 'kinked' AND rate_model ? 'supplier_share';
+
+UPDATE rate_model_changes
+SET previous_model = (previous_model - 'supplier_share')
+    || jsonb_build_object('reserve_factor', 1 - (previous_model->>'supplier_share')::NUMERIC)
+WHERE previous_model->>'model' = 'kinked' AND previous_model ? 'supplier_share';
+
+-- One row per product per accrued day; the amount is moved to the treasury in the same
+-- transaction under transfer_id
+CREATE TABLE IF NOT EXISTS interest_reserve_accruals (
+    product_code VARCHAR(50) NOT NULL,
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    total_borrowed BIGINT NOT NULL,
+    utilization_rate DECIMAL(10, 6) NOT NULL,
+    borrow_apy DECIMAL(10, 6) NOT NULL,
+    reserve_factor DECIMAL(10, 6) NOT NULL,
+    amount_satoshis BIGINT NOT NULL CHECK (amount_satoshis >= 0),
+    -- NULL when nothing was reserved for the day
+    transfer_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (product_code, period_start),
+    CHECK (period_end > period_start)
+);
+
+CREATE INDEX IF NOT EXISTS idx_interest_reserve_accruals_period ON interest_reserve_accruals(period_start DESC);
+
+COMMENT ON TABLE interest_reserve_accruals IS 'Daily reserve share of interest on the lending pool, credited to treasury:reserves; see GET /reserves';
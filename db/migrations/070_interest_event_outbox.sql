@@ -0,0 +1,27 @@
+-- Migration: 070_interest_event_outbox
+-- Description: Transactional outbox of interest-engine events (rate updates, completed accrual runs) awaiting publication to the message bus
+-- Date: 2025-11-27
+
+CREATE TABLE IF NOT EXISTS interest_event_outbox (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    -- Publication order
+    seq BIGSERIAL NOT NULL UNIQUE,
+    event_type VARCHAR(50) NOT NULL,
+    -- Ordering key on the bus (product code, or accrual period)
+    event_key VARCHAR(64) NOT NULL,
+    payload JSONB NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_at TIMESTAMPTZ,
+    attempts INT NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    last_error TEXT
+);
+
+COMMENT ON TABLE interest_event_outbox IS 'Written in the same database transaction as the rate snapshot or accrual run it describes; the outbox worker publishes and marks rows';
+
+CREATE INDEX IF NOT EXISTS idx_interest_event_outbox_unpublished
+    ON interest_event_outbox(seq)
+    WHERE published_at IS NULL;
+CREATE INDEX IF NOT EXISTS idx_interest_event_outbox_published
+    ON interest_event_outbox(published_at)
+    WHERE published_at IS NOT NULL;
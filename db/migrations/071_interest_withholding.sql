@@ -0,0 +1,35 @@
+-- Migration: 071_interest_withholding
+-- Description: Tax jurisdictions with interest withholding rates, withholding taken from interest postings as they settle
+-- Date: 2025-11-27
+
+CREATE TABLE IF NOT EXISTS tax_jurisdictions (
+    code VARCHAR(10) PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    -- Share of gross interest withheld, in basis points
+    withholding_bps INTEGER NOT NULL DEFAULT 0 CHECK (withholding_bps BETWEEN 0 AND 10000),
+    updated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Users without a jurisdiction are withheld at this one's rate
+INSERT INTO tax_jurisdictions (code, name, withholding_bps)
+VALUES ('DEFAULT', 'Jurisdiction not declared', 0)
+ON CONFLICT (code) DO NOTHING;
+
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS tax_jurisdiction VARCHAR(10) REFERENCES tax_jurisdictions(code);
+
+-- What was withheld from each posting, at the rate in force when it settled; negative
+-- postings give back withholding on the interest they reverse
+ALTER TABLE interest_postings
+    ADD COLUMN IF NOT EXISTS tax_jurisdiction VARCHAR(10),
+    ADD COLUMN IF NOT EXISTS withholding_bps INTEGER,
+    ADD COLUMN IF NOT EXISTS withheld_satoshis BIGINT NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS withholding_transfer_id UUID;
+
+CREATE INDEX IF NOT EXISTS idx_interest_postings_settled
+    ON interest_postings(paymail, settled_at)
+    WHERE status = 'Settled';
+
+COMMENT ON TABLE tax_jurisdictions IS 'Interest withholding rates, set via PUT /tax-jurisdictions/{code}; withheld amounts are credited to tax_withholding:{code}';
@@ -0,0 +1,8 @@
+-- Migration: 072_collateral_return_address
+-- Description: Address registered at origination that released collateral is returned to
+-- Date: 2025-11-28
+
+ALTER TABLE loans
+    ADD COLUMN IF NOT EXISTS collateral_return_address VARCHAR(100);
+
+COMMENT ON COLUMN loans.collateral_return_address IS 'Set by the borrower at origination; collateral releases cannot be sent to any other address';
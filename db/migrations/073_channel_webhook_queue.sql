@@ -0,0 +1,17 @@
+-- Migration: 073_channel_webhook_queue
+-- Description: Deliver channel webhooks from a leased queue so retries survive restarts and run on one worker
+-- Date: 2025-11-28
+
+ALTER TABLE channel_webhook_deliveries
+    ADD COLUMN IF NOT EXISTS next_attempt_at TIMESTAMPTZ DEFAULT NOW();
+
+-- Deliveries that succeeded or used up their attempts before the queue existed are finished
+UPDATE channel_webhook_deliveries
+SET next_attempt_at = NULL
+WHERE delivered OR attempts > 0;
+
+CREATE INDEX IF NOT EXISTS idx_channel_webhook_deliveries_due
+    ON channel_webhook_deliveries(next_attempt_at)
+    WHERE NOT delivered AND next_attempt_at IS NOT NULL;
+
+COMMENT ON COLUMN channel_webhook_deliveries.next_attempt_at IS 'When the delivery worker next tries (or a claimed lease expires); NULL once delivered or out of attempts';
@@ -0,0 +1,20 @@
+-- Migration: 074_aggregate_key_channel_binding
+-- Description: Bind aggregated keys to the channel they lock so signing sessions only spend co-signed channel states
+-- Date: 2025-11-28
+
+-- Parties' state-signing keys and payout addresses, recorded when the key is created. Keys
+-- created before this migration have no channel and can no longer open signing sessions.
+ALTER TABLE aggregate_keys
+    ADD COLUMN IF NOT EXISTS channel_id VARCHAR(66),
+    ADD COLUMN IF NOT EXISTS party_a_pubkey VARCHAR(66),
+    ADD COLUMN IF NOT EXISTS party_b_pubkey VARCHAR(66),
+    ADD COLUMN IF NOT EXISTS party_a_address VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS party_b_address VARCHAR(64),
+    -- Highest state the service has released a partial signature for; older states are refused
+    ADD COLUMN IF NOT EXISTS last_signed_sequence BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_aggregate_keys_channel ON aggregate_keys(channel_id);
+
+-- Channel state each session's transaction settles
+ALTER TABLE aggregate_sign_sessions
+    ADD COLUMN IF NOT EXISTS sequence_number BIGINT;
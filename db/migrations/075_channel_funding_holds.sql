@@ -0,0 +1,55 @@
+-- Migration: 075_channel_funding_holds
+-- Description: Hold what each party funded a channel with until the channel settles to the ledger
+-- Date: 2025-11-28
+
+-- Off-ledger channel payments move current balances between the parties, so holding
+-- current balances let a payer's hold shrink and a payee spend an incoming payment twice.
+-- The funding amounts stay held while the channel is open; closing posts the net movement
+-- as a channel_settlement transfer in the same transaction.
+CREATE OR REPLACE VIEW user_balances AS
+SELECT
+    b.user_id,
+    b.paymail,
+    b.balance_satoshis,
+    b.accrued_interest_satoshis,
+    b.active_deposits,
+    b.pending_deposits_satoshis,
+    b.withdrawals_in_flight_satoshis,
+    b.locked_deposits_satoshis,
+    b.loan_collateral_satoshis,
+    b.channel_funds_satoshis,
+    b.balance_satoshis
+        - b.withdrawals_in_flight_satoshis
+        - b.locked_deposits_satoshis
+        - b.loan_collateral_satoshis
+        - b.channel_funds_satoshis AS available_satoshis
+FROM (
+    SELECT
+        u.id as user_id,
+        u.paymail,
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status IN ('Confirmed', 'Available')), 0)
+            + COALESCE((SELECT SUM(le.amount_satoshis) FROM ledger_entries le WHERE le.account = u.paymail), 0) as balance_satoshis,
+        COALESCE((SELECT SUM(ia.amount_satoshis) FROM interest_accruals ia
+                  WHERE ia.user_id = u.id AND NOT ia.paid_out), 0) as accrued_interest_satoshis,
+        (SELECT COUNT(*) FROM deposits d WHERE d.user_id = u.id AND d.status = 'Confirmed') as active_deposits,
+        -- Not yet in the balance: deposits waiting for an SPV proof
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status = 'Pending'), 0) as pending_deposits_satoshis,
+        -- In the balance but not spendable
+        COALESCE((SELECT SUM(w.amount_satoshis + w.fee_satoshis) FROM withdrawals w
+                  WHERE w.user_id = u.id AND w.status IN ('PendingApproval', 'Pending', 'Broadcast')), 0) as withdrawals_in_flight_satoshis,
+        COALESCE((SELECT SUM(d.amount_satoshis) FROM deposits d
+                  WHERE d.user_id = u.id AND d.status IN ('Confirmed', 'Available') AND d.lock_until > NOW()), 0) as locked_deposits_satoshis,
+        COALESCE((SELECT SUM(l.collateral_satoshis) FROM loans l
+                  WHERE l.borrower_paymail = u.paymail AND l.status IN ('Pending', 'Active', 'MarginCalled')
+                    AND l.collateral_txid IS NULL), 0) as loan_collateral_satoshis,
+        COALESCE((SELECT SUM(CASE WHEN c.party_a_paymail = u.paymail THEN c.initial_balance_a ELSE 0 END
+                           + CASE WHEN c.party_b_paymail = u.paymail THEN c.initial_balance_b ELSE 0 END)
+                  FROM payment_channels c
+                  WHERE (c.party_a_paymail = u.paymail OR c.party_b_paymail = u.paymail)
+                    AND c.status <> 'Closed'), 0) as channel_funds_satoshis
+    FROM users u
+) b;
+
+COMMENT ON VIEW user_balances IS 'balance_satoshis is everything a user owns; available_satoshis excludes in-flight withdrawals and their fees (including those awaiting approval), locked term deposits, loan collateral held without an escrow transaction, and the amount each party funded its open channels with';
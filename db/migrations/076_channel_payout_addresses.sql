@@ -0,0 +1,9 @@
+-- Migration: 076_channel_payout_addresses
+-- Description: Payout addresses each channel party registers for itself, used to settle funded channels
+-- Date: 2025-11-28
+
+-- Party A registers at open; either party can set or replace only its own address, with its
+-- own token, until the channel closes. The closing party no longer supplies either address.
+ALTER TABLE payment_channels
+    ADD COLUMN IF NOT EXISTS party_a_payout_address VARCHAR(64),
+    ADD COLUMN IF NOT EXISTS party_b_payout_address VARCHAR(64);
@@ -0,0 +1,8 @@
+-- Migration: 077_aggregate_key_fee_split
+-- Description: Record each aggregated key's settlement fee split so signing sessions check payouts against it
+-- Date: 2025-11-28
+
+-- "payer", "split" or "proportional", as the channel stores it; payouts must divide their fee
+-- this way and stay within the current fee rate
+ALTER TABLE aggregate_keys
+    ADD COLUMN IF NOT EXISTS fee_split VARCHAR(20) NOT NULL DEFAULT 'split';
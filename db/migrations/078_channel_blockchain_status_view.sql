@@ -0,0 +1,26 @@
+-- Migration: 078_channel_blockchain_status_view
+-- Description: Recreate channel_blockchain_status from the channel party columns that exist
+-- Date: 2025-11-28
+
+-- 004 selected pc.party_a and pc.party_b, which payment_channels has never had, so the view
+-- failed to create and the rest of a fresh migration run carried on without it. Output column
+-- names are unchanged for readers of the view.
+CREATE OR REPLACE VIEW channel_blockchain_status AS
+SELECT 
+    pc.id AS channel_id,
+    pc.party_a_paymail AS party_a,
+    pc.party_b_paymail AS party_b,
+    pc.status AS channel_status,
+    pc.blockchain_enabled,
+    pc.funding_txid,
+    pc.funding_confirmations,
+    pc.settlement_txid,
+    pc.settlement_confirmations,
+    pc.spv_verified,
+    bt_funding.status AS funding_tx_status,
+    bt_settlement.status AS settlement_tx_status
+FROM payment_channels pc
+LEFT JOIN blockchain_transactions bt_funding ON pc.funding_txid = bt_funding.txid AND pc.funding_txid IS NOT NULL
+LEFT JOIN blockchain_transactions bt_settlement ON pc.settlement_txid = bt_settlement.txid AND pc.settlement_txid IS NOT NULL
+WHERE pc.blockchain_enabled = true
+ORDER BY pc.created_at DESC;
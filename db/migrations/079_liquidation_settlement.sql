@@ -0,0 +1,17 @@
+-- Migration: 079_liquidation_settlement
+-- Description: Close participations when their loan settles, writing off principal a liquidation left unpaid
+-- Date: 2025-11-29
+
+ALTER TABLE loan_participations DROP CONSTRAINT IF EXISTS loan_participations_status_check;
+ALTER TABLE loan_participations ADD CONSTRAINT loan_participations_status_check
+    CHECK (status IN ('Committed', 'Active', 'Released', 'Closed'));
+
+ALTER TABLE loan_participations
+    ADD COLUMN IF NOT EXISTS principal_written_off BIGINT NOT NULL DEFAULT 0 CHECK (principal_written_off >= 0),
+    ADD COLUMN IF NOT EXISTS closed_at TIMESTAMPTZ;
+
+ALTER TABLE loan_liquidations
+    ADD COLUMN IF NOT EXISTS shortfall_satoshis BIGINT NOT NULL DEFAULT 0 CHECK (shortfall_satoshis >= 0);
+
+COMMENT ON COLUMN loan_participations.principal_written_off IS 'Share of principal the seized collateral did not cover when the loan was liquidated';
+COMMENT ON COLUMN loan_liquidations.shortfall_satoshis IS 'Debt a full liquidation left unpaid, written off against the participations';
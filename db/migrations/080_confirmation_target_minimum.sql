@@ -0,0 +1,9 @@
+-- Migration: 080_confirmation_target_minimum
+-- Description: Confirmation targets need at least one confirmation; zero finalized unmined transactions
+-- Date: 2025-11-30
+
+UPDATE confirmation_policies SET target_confirmations = 1, updated_at = NOW() WHERE target_confirmations < 1;
+
+ALTER TABLE confirmation_policies DROP CONSTRAINT IF EXISTS confirmation_policies_target_confirmations_check;
+ALTER TABLE confirmation_policies ADD CONSTRAINT confirmation_policies_target_confirmations_check
+    CHECK (target_confirmations >= 1);
@@ -18,6 +18,7 @@ function App() {
   const [interestRate, setInterestRate] = useState(1000);
   const [availableLoans, setAvailableLoans] = useState([]);
   const [currentView, setCurrentView] = useState('dashboard');
+  const [projection, setProjection] = useState(null);
 
   const connectWallet = async () => {
     const userPaymail = prompt('Enter your Paymail (e.g., yourname@handcash.io):');
@@ -45,7 +46,7 @@ function App() {
       setBalance(data);
     } catch (error) {
       console.error('Failed to fetch balance:', error);
-      setBalance({ balance_satoshis: 0, accrued_interest_satoshis: 0, total_available_satoshis: 0, current_apy: 7.0, active_deposits: 0 });
+      setBalance({ balance_satoshis: 0, accrued_interest_satoshis: 0, total_available_satoshis: 0, current_apy: null, active_deposits: 0 });
     }
   };
 
@@ -169,6 +170,31 @@ function App() {
     }
   }, [activeTab]);
 
+  // Earnings are projected by the interest-engine; an unlocked deposit is shown over a year
+  useEffect(() => {
+    const amount = parseFloat(depositAmount);
+    if (!amount || amount <= 0) {
+      setProjection(null);
+      return;
+    }
+    const controller = new AbortController();
+    fetch('http://localhost:8081/interest/project', {
+      method: 'POST',
+      headers: { 'Content-Type': 'application/json' },
+      body: JSON.stringify({
+        principal_satoshis: Math.floor(amount * 100000000),
+        horizon_days: lockDays > 0 ? lockDays : 365,
+      }),
+      signal: controller.signal,
+    })
+      .then(response => (response.ok ? response.json() : null))
+      .then(setProjection)
+      .catch(error => {
+        if (error.name !== 'AbortError') setProjection(null);
+      });
+    return () => controller.abort();
+  }, [depositAmount, lockDays]);
+
   return (
     <div className="min-h-screen bg-gradient-to-br from-gray-50 to-gray-100 p-6 font-sans">
       <header className="flex justify-between items-center mb-8">
@@ -270,7 +296,7 @@ function App() {
                 <ArrowDownToLine className="w-5 h-5 text-purple-500" />
                 <h3 className="font-semibold text-gray-700">APY</h3>
               </div>
-              <p className="text-2xl font-bold text-gray-900">{balance.current_apy.toFixed(2)}%</p>
+              <p className="text-2xl font-bold text-gray-900">{balance.current_apy != null ? `${balance.current_apy.toFixed(2)}%` : '—'}</p>
             </div>
           </div>
 
@@ -332,6 +358,22 @@ function App() {
                       ))}
                     </div>
                   </div>
+                  {projection && (
+                    <div className="bg-orange-50 rounded-lg p-4 text-sm text-gray-700 space-y-1">
+                      <p>
+                        Projected earnings over {projection.horizon_days} days:{' '}
+                        <span className="font-semibold">{formatBSV(projection.current.earnings_satoshis)} BSV</span>{' '}
+                        at {(projection.current.apy * 100).toFixed(2)}% APY
+                      </p>
+                      {projection.historical_average && (
+                        <p className="text-gray-500">
+                          At the {projection.historical_average.window_days}-day average of{' '}
+                          {(projection.historical_average.projection.apy * 100).toFixed(2)}%:{' '}
+                          {formatBSV(projection.historical_average.projection.earnings_satoshis)} BSV
+                        </p>
+                      )}
+                    </div>
+                  )}
                   <button
                     onClick={handleDeposit}
                     disabled={loading}
@@ -75,12 +75,19 @@ const LoanHistory = ({ userPaymail, apiBaseUrl = 'http://localhost:8082' }) => {
   };
 
   const calculateTotalDue = (loan) => {
-    const interest = Math.floor(loan.amount_satoshis * loan.interest_rate);
-    return loan.amount_satoshis + interest;
+    return loan.principal_satoshis + loan.interest_accrued;
   };
 
   const calculateCollateralRatio = (loan) => {
-    return ((loan.collateral_satoshis / loan.amount_satoshis) * 100).toFixed(0);
+    return ((loan.collateral_satoshis / loan.principal_satoshis) * 100).toFixed(0);
+  };
+
+  const formatRate = (loan) => {
+    return (loan.interest_rate_bps / 100).toFixed(2) + '%';
+  };
+
+  const durationDays = (loan) => {
+    return Math.round((new Date(loan.due_date) - new Date(loan.created_at)) / 86400000);
   };
 
   const getRole = (loan) => {
@@ -92,10 +99,10 @@ const LoanHistory = ({ userPaymail, apiBaseUrl = 'http://localhost:8082' }) => {
   const stats = {
     totalBorrowed: loans
       .filter(l => l.borrower_paymail === userPaymail)
-      .reduce((sum, l) => sum + l.amount_satoshis, 0),
+      .reduce((sum, l) => sum + l.principal_satoshis, 0),
     totalLent: loans
       .filter(l => l.lender_paymail === userPaymail)
-      .reduce((sum, l) => sum + l.amount_satoshis, 0),
+      .reduce((sum, l) => sum + l.principal_satoshis, 0),
     activeBorrowed: loans.filter(l => 
       l.borrower_paymail === userPaymail && l.status === 'Active'
     ).length,
@@ -206,17 +213,17 @@ const LoanHistory = ({ userPaymail, apiBaseUrl = 'http://localhost:8082' }) => {
               <div className="loan-card-body">
                 <div className="loan-amount">
                   <span className="amount-label">Loan Amount</span>
-                  <span className="amount-value">{formatSatoshis(loan.amount_satoshis)}</span>
+                  <span className="amount-value">{formatSatoshis(loan.principal_satoshis)}</span>
                 </div>
 
                 <div className="loan-details-grid">
                   <div className="detail-item">
                     <span className="detail-label">Interest Rate</span>
-                    <span className="detail-value">{(loan.interest_rate * 100).toFixed(2)}%</span>
+                    <span className="detail-value">{formatRate(loan)}</span>
                   </div>
                   <div className="detail-item">
                     <span className="detail-label">Duration</span>
-                    <span className="detail-value">{loan.duration_days} days</span>
+                    <span className="detail-value">{durationDays(loan)} days</span>
                   </div>
                   <div className="detail-item">
                     <span className="detail-label">Collateral</span>
@@ -284,18 +291,16 @@ const LoanHistory = ({ userPaymail, apiBaseUrl = 'http://localhost:8082' }) => {
                 <h4>Financial Details</h4>
                 <div className="detail-row">
                   <span>Principal Amount:</span>
-                  <span>{formatSatoshis(selectedLoan.amount_satoshis)}</span>
+                  <span>{formatSatoshis(selectedLoan.principal_satoshis)}</span>
                 </div>
                 <div className="detail-row">
                   <span>Interest Rate:</span>
-                  <span>{(selectedLoan.interest_rate * 100).toFixed(2)}%</span>
+                  <span>{formatRate(selectedLoan)}</span>
                 </div>
                 <div className="detail-row">
                   <span>Interest Amount:</span>
                   <span>
-                    {formatSatoshis(
-                      Math.floor(selectedLoan.amount_satoshis * selectedLoan.interest_rate)
-                    )}
+                    {formatSatoshis(selectedLoan.interest_accrued)}
                   </span>
                 </div>
                 <div className="detail-row highlight">
@@ -312,7 +317,7 @@ const LoanHistory = ({ userPaymail, apiBaseUrl = 'http://localhost:8082' }) => {
                 </div>
                 <div className="detail-row">
                   <span>Duration:</span>
-                  <span>{selectedLoan.duration_days} days</span>
+                  <span>{durationDays(selectedLoan)} days</span>
                 </div>
               </div>
 